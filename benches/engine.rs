@@ -0,0 +1,204 @@
+//! Regression guards for engine hot paths: ingestion, recall at several store
+//! sizes and cue fan-outs, OrderedSet operations, and snapshot save/load.
+//!
+//! Fixtures are generated from a fixed seed, so runs are comparable across
+//! commits. Run with `cargo bench --bench engine` (filter with e.g. `-- recall`).
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use cuemap_rust::config::EngineSizing;
+use cuemap_rust::engine::CueMapEngine;
+use cuemap_rust::persistence::PersistenceManager;
+use cuemap_rust::structures::OrderedSet;
+
+/// Distinct cue values per key; a few popular values get most of the traffic.
+const CUE_KEYS: &[&str] = &["service", "error", "topic", "team", "region"];
+const VALUES_PER_KEY: u64 = 200;
+const CUES_PER_MEMORY: usize = 4;
+const STORE_SIZES: &[usize] = &[1_000, 10_000, 100_000];
+const FAN_OUTS: &[usize] = &[1, 3, 8];
+
+/// xorshift64*: small, fast and deterministic for a given seed.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Skewed towards low values: roughly 80% of draws hit the first 20%.
+    fn skewed(&mut self, n: u64) -> u64 {
+        let u = (self.next() >> 11) as f64 / (1u64 << 53) as f64;
+        ((u.powf(7.2)) * n as f64) as u64
+    }
+
+    fn cue(&mut self) -> String {
+        let key = CUE_KEYS[(self.next() % CUE_KEYS.len() as u64) as usize];
+        format!("{}:v{}", key, self.skewed(VALUES_PER_KEY))
+    }
+
+    fn cues(&mut self, n: usize) -> Vec<String> {
+        (0..n).map(|_| self.cue()).collect()
+    }
+}
+
+fn populated_engine(size: usize) -> CueMapEngine {
+    let engine = CueMapEngine::new();
+    let mut rng = Rng::new(42);
+    for i in 0..size {
+        engine.add_memory(format!("memory {}", i), rng.cues(CUES_PER_MEMORY), None, true);
+    }
+    engine
+}
+
+fn bench_add_memory(c: &mut Criterion) {
+    const BATCH: usize = 1_000;
+    let mut group = c.benchmark_group("add_memory");
+    group.throughput(Throughput::Elements(BATCH as u64));
+    group.bench_function("batch_1000", |b| {
+        b.iter_batched(
+            || {
+                let mut rng = Rng::new(7);
+                (CueMapEngine::new(), (0..BATCH).map(|_| rng.cues(CUES_PER_MEMORY)).collect::<Vec<_>>())
+            },
+            |(engine, cue_sets)| {
+                for (i, cues) in cue_sets.into_iter().enumerate() {
+                    engine.add_memory(format!("memory {}", i), cues, None, true);
+                }
+                engine
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+/// Parallel writers against one engine, by DashMap shard count.
+fn bench_concurrent_add(c: &mut Criterion) {
+    const THREADS: usize = 8;
+    const PER_THREAD: usize = 1_000;
+    let mut group = c.benchmark_group("concurrent_add");
+    group.throughput(Throughput::Elements((THREADS * PER_THREAD) as u64));
+    group.sample_size(20);
+    for &shards in &[4usize, 16, 128] {
+        let sizing = EngineSizing { shard_count: shards, expected_memories: 0 };
+        group.bench_with_input(BenchmarkId::new("shards", shards), &sizing, |b, sizing| {
+            b.iter_batched(
+                || {
+                    let cue_sets: Vec<Vec<Vec<String>>> = (0..THREADS)
+                        .map(|t| {
+                            let mut rng = Rng::new(100 + t as u64);
+                            (0..PER_THREAD).map(|_| rng.cues(CUES_PER_MEMORY)).collect()
+                        })
+                        .collect();
+                    (CueMapEngine::with_sizing(*sizing), cue_sets)
+                },
+                |(engine, cue_sets)| {
+                    std::thread::scope(|scope| {
+                        for cue_set in cue_sets {
+                            let engine = &engine;
+                            scope.spawn(move || {
+                                for (i, cues) in cue_set.into_iter().enumerate() {
+                                    engine.add_memory(format!("memory {}", i), cues, None, true);
+                                }
+                            });
+                        }
+                    });
+                    engine
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_recall(c: &mut Criterion) {
+    let mut group = c.benchmark_group("recall_weighted");
+    for &size in STORE_SIZES {
+        let engine = populated_engine(size);
+        for &fan_out in FAN_OUTS {
+            let mut rng = Rng::new(size as u64 + fan_out as u64);
+            let queries: Vec<Vec<(String, f64)>> = (0..64)
+                .map(|_| rng.cues(fan_out).into_iter().map(|c| (c, 1.0)).collect())
+                .collect();
+            let mut next = 0;
+            group.bench_with_input(
+                BenchmarkId::new(format!("{}_memories", size), format!("{}_cues", fan_out)),
+                &queries,
+                |b, queries| {
+                    b.iter(|| {
+                        next = (next + 1) % queries.len();
+                        black_box(engine.recall_weighted(queries[next].clone(), 10, false, None, false, false, false, false, None))
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+fn bench_ordered_set(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ordered_set");
+    for &size in &[1_000u64, 100_000] {
+        let mut set = OrderedSet::new();
+        for handle in 0..size {
+            set.add(handle);
+        }
+        let mut rng = Rng::new(size);
+        let probes: Vec<u64> = (0..1024).map(|_| rng.next() % size).collect();
+
+        group.bench_with_input(BenchmarkId::new("add_new", size), &size, |b, _| {
+            b.iter_batched(|| set.clone(), |mut s| { s.add(size); s }, BatchSize::LargeInput)
+        });
+        group.bench_with_input(BenchmarkId::new("move_to_front", size), &size, |b, _| {
+            let mut s = set.clone();
+            let mut i = 0;
+            b.iter(|| {
+                i = (i + 1) % probes.len();
+                s.move_to_front(probes[i]);
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("get_index_of", size), &size, |b, _| {
+            let mut i = 0;
+            b.iter(|| {
+                i = (i + 1) % probes.len();
+                black_box(set.get_index_of(probes[i]))
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("get_recent_100", size), &size, |b, _| {
+            b.iter(|| black_box(set.get_recent(Some(100))))
+        });
+    }
+    group.finish();
+}
+
+fn bench_snapshot(c: &mut Criterion) {
+    let mut group = c.benchmark_group("snapshot");
+    group.sample_size(10);
+    let dir = tempfile::tempdir().expect("temp dir");
+    for &size in &[10_000usize, 100_000] {
+        let engine = populated_engine(size);
+        let path = dir.path().join(format!("bench_{}.bin", size));
+
+        group.bench_with_input(BenchmarkId::new("save", size), &size, |b, _| {
+            b.iter(|| PersistenceManager::save_to_path(&engine, &path).expect("save"))
+        });
+        group.bench_with_input(BenchmarkId::new("load", size), &size, |b, _| {
+            b.iter(|| {
+                let (memories, cue_index) = PersistenceManager::load_from_path(&path).expect("load");
+                black_box(CueMapEngine::from_state(memories, cue_index))
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_add_memory, bench_concurrent_add, bench_recall, bench_ordered_set, bench_snapshot);
+criterion_main!(benches);
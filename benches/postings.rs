@@ -0,0 +1,35 @@
+//! Intersection of large cue posting lists: bitmap intersection of hybrid
+//! sets versus probing every member of one list into the other.
+//!
+//! Run with `cargo bench --bench postings`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use cuemap_rust::structures::OrderedSet;
+
+fn postings(range: std::ops::Range<u64>, stride: u64) -> OrderedSet {
+    let mut set = OrderedSet::new();
+    for handle in range.step_by(stride as usize) {
+        set.add(handle);
+    }
+    set
+}
+
+fn bench_intersection(c: &mut Criterion) {
+    let mut group = c.benchmark_group("postings_intersection");
+    for &size in &[100_000u64, 400_000] {
+        // Two overlapping lists, each `size` members long
+        let a = postings(0..size * 2, 2);
+        let b = postings(size / 2..size / 2 + size * 3, 3);
+
+        group.bench_with_input(BenchmarkId::new("bitmap", size), &size, |bench, _| {
+            bench.iter(|| black_box(a.intersection_len(&b)))
+        });
+        group.bench_with_input(BenchmarkId::new("probe", size), &size, |bench, _| {
+            bench.iter(|| black_box(a.iter().filter(|h| b.contains(*h)).count()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_intersection);
+criterion_main!(benches);
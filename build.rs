@@ -0,0 +1,15 @@
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        // Use the bundled protoc so the feature builds without a system install
+        if std::env::var_os("PROTOC").is_none() {
+            let protoc = protoc_bin_vendored::protoc_bin_path().expect("no bundled protoc for this platform");
+            std::env::set_var("PROTOC", protoc);
+        }
+        tonic_prost_build::configure()
+            .build_client(true)
+            .compile_protos(&["proto/cuemap.proto"], &["proto"])
+            .expect("failed to compile proto/cuemap.proto");
+    }
+    println!("cargo:rerun-if-changed=proto/cuemap.proto");
+}
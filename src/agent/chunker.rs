@@ -1,5 +1,10 @@
+use crate::agent::readability::{self, ReadableDocument};
+use tracing::warn;
 use tree_sitter::Parser;
+use std::collections::HashMap;
+use std::io::Read;
 use std::path::Path;
+use std::sync::{Arc, OnceLock, RwLock};
 
 #[derive(Debug, Clone)]
 pub struct Chunk {
@@ -8,6 +13,8 @@ pub struct Chunk {
     pub end_line: usize,
     pub context: String, // e.g., "function calculate_tax"
     pub structural_cues: Vec<String>,
+    /// Stored on the memory ingested from the chunk, e.g. `page` for PDFs
+    pub metadata: HashMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -31,6 +38,194 @@ pub enum ChunkerType {
     Text,
 }
 
+/// Splits one kind of file into chunks. Implement it and register it on
+/// [`registry()`] to teach the agent a file type without editing this file.
+pub trait FileChunker: Send + Sync {
+    /// Chunk the file at `path`. `content` is its text, or empty when the
+    /// file is not UTF-8; binary formats read `path` themselves.
+    fn chunk(&self, path: &Path, content: &str) -> Vec<Chunk>;
+}
+
+impl FileChunker for ChunkerType {
+    fn chunk(&self, path: &Path, content: &str) -> Vec<Chunk> {
+        match self {
+            ChunkerType::Python => Chunker::chunk_python(content),
+            ChunkerType::Rust => Chunker::chunk_rust(content),
+            ChunkerType::TypeScript => Chunker::chunk_typescript(content),
+            ChunkerType::JavaScript => Chunker::chunk_javascript(content),
+            ChunkerType::Go => Chunker::chunk_go(content),
+            ChunkerType::Html => Chunker::chunk_html(content),
+            ChunkerType::Css => Chunker::chunk_css(content),
+            ChunkerType::Php => Chunker::chunk_php(content),
+            ChunkerType::Java => Chunker::chunk_java(content),
+            ChunkerType::Markdown => Chunker::chunk_markdown(content),
+            ChunkerType::Csv => Chunker::chunk_csv(content),
+            ChunkerType::Json => Chunker::chunk_json(content),
+            ChunkerType::Yaml => Chunker::chunk_yaml(content),
+            ChunkerType::Xml => Chunker::chunk_xml(content),
+            ChunkerType::Pdf => Chunker::chunk_pdf(path),
+            ChunkerType::Office => Chunker::chunk_office(path),
+            ChunkerType::Text => Chunker::chunk_text(content),
+        }
+    }
+}
+
+impl ChunkerType {
+    /// Built-in chunker for a lowercase file extension.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Some(match extension {
+            "py" => ChunkerType::Python,
+            "rs" => ChunkerType::Rust,
+            "ts" | "tsx" => ChunkerType::TypeScript,
+            "js" | "jsx" => ChunkerType::JavaScript,
+            "go" => ChunkerType::Go,
+            "html" | "htm" => ChunkerType::Html,
+            "css" => ChunkerType::Css,
+            "php" => ChunkerType::Php,
+            "java" => ChunkerType::Java,
+            "md" => ChunkerType::Markdown,
+            "csv" => ChunkerType::Csv,
+            "json" => ChunkerType::Json,
+            "yaml" | "yml" => ChunkerType::Yaml,
+            "xml" => ChunkerType::Xml,
+            "pdf" => ChunkerType::Pdf,
+            "docx" | "xlsx" | "pptx" => ChunkerType::Office,
+            "txt" => ChunkerType::Text,
+            _ => return None,
+        })
+    }
+
+    /// Built-in chunker for a MIME type, as returned by [`sniff_mime`].
+    pub fn from_mime(mime: &str) -> Option<Self> {
+        Some(match mime {
+            "text/x-python" => ChunkerType::Python,
+            "text/x-rust" => ChunkerType::Rust,
+            "text/javascript" => ChunkerType::JavaScript,
+            "text/html" => ChunkerType::Html,
+            "text/css" => ChunkerType::Css,
+            "application/x-php" => ChunkerType::Php,
+            "text/markdown" => ChunkerType::Markdown,
+            "text/csv" => ChunkerType::Csv,
+            "application/json" => ChunkerType::Json,
+            "application/yaml" => ChunkerType::Yaml,
+            "application/xml" => ChunkerType::Xml,
+            "application/pdf" => ChunkerType::Pdf,
+            "text/plain" => ChunkerType::Text,
+            _ => return None,
+        })
+    }
+}
+
+/// Bytes of a file looked at by [`sniff_mime`].
+const SNIFF_BYTES: usize = 512;
+
+/// MIME type of a file from its first bytes: magic numbers, shebang lines
+/// and markup prologues. `content` is the file's text when it has been read
+/// already; otherwise the start of `path` is read.
+pub fn sniff_mime(path: &Path, content: &str) -> Option<&'static str> {
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    if content.is_empty() {
+        std::fs::File::open(path).ok()?.take(SNIFF_BYTES as u64).read_to_end(&mut head).ok()?;
+    } else {
+        head.extend_from_slice(&content.as_bytes()[..content.len().min(SNIFF_BYTES)]);
+    }
+
+    if head.starts_with(b"%PDF-") {
+        return Some("application/pdf");
+    }
+    if head.starts_with(b"PK\x03\x04") {
+        return Some("application/zip");
+    }
+    // A multi-byte character may be cut at SNIFF_BYTES
+    let text = match std::str::from_utf8(&head) {
+        Ok(text) => text,
+        Err(err) if err.error_len().is_none() => std::str::from_utf8(&head[..err.valid_up_to()]).ok()?,
+        Err(_) => return Some("application/octet-stream"),
+    };
+
+    if let Some(shebang) = text.strip_prefix("#!") {
+        let interpreter = shebang.lines().next().unwrap_or("");
+        return Some(if interpreter.contains("python") {
+            "text/x-python"
+        } else if interpreter.contains("node") {
+            "text/javascript"
+        } else if interpreter.contains("php") {
+            "application/x-php"
+        } else {
+            "text/x-shellscript"
+        });
+    }
+
+    let trimmed = text.trim_start();
+    let lower = trimmed.get(..trimmed.len().min(16)).unwrap_or("").to_ascii_lowercase();
+    if lower.starts_with("<?xml") {
+        Some("application/xml")
+    } else if lower.starts_with("<?php") {
+        Some("application/x-php")
+    } else if lower.starts_with("<!doctype html") || lower.starts_with("<html") {
+        Some("text/html")
+    } else if (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && (content.is_empty() || serde_json::from_str::<serde_json::Value>(content).is_ok())
+    {
+        Some("application/json")
+    } else if trimmed.starts_with("---\n") {
+        Some("application/yaml")
+    } else if text.is_empty() {
+        None
+    } else {
+        Some("text/plain")
+    }
+}
+
+/// Custom chunkers, looked up before the built-in ones. Extensions are
+/// matched first; files without a known extension by their sniffed MIME type.
+#[derive(Default)]
+pub struct ChunkerRegistry {
+    extensions: RwLock<HashMap<String, Arc<dyn FileChunker>>>,
+    mime_types: RwLock<HashMap<String, Arc<dyn FileChunker>>>,
+}
+
+impl ChunkerRegistry {
+    /// Chunk files ending in `.extension` (case-insensitive, leading dot
+    /// optional) with `chunker`, replacing any earlier chunker for it.
+    pub fn register_extension(&self, extension: &str, chunker: Arc<dyn FileChunker>) {
+        let extension = extension.trim_start_matches('.').to_ascii_lowercase();
+        self.extensions.write().unwrap().insert(extension, chunker);
+    }
+
+    /// Chunk files sniffed as `mime` with `chunker`; only used for files
+    /// whose extension no chunker claims.
+    pub fn register_mime(&self, mime: &str, chunker: Arc<dyn FileChunker>) {
+        self.mime_types.write().unwrap().insert(mime.to_ascii_lowercase(), chunker);
+    }
+
+    /// The chunker for a file: a custom one for its extension, the built-in
+    /// one for its extension, then by MIME type, custom before built-in.
+    /// Unrecognized files are chunked as plain text.
+    pub fn resolve(&self, path: &Path, content: &str) -> Arc<dyn FileChunker> {
+        let extension = path.extension().and_then(|s| s.to_str()).map(str::to_ascii_lowercase);
+        if let Some(extension) = &extension {
+            if let Some(chunker) = self.extensions.read().unwrap().get(extension) {
+                return chunker.clone();
+            }
+            if let Some(builtin) = ChunkerType::from_extension(extension) {
+                return Arc::new(builtin);
+            }
+        }
+        let mime = sniff_mime(path, content);
+        if let Some(chunker) = mime.and_then(|mime| self.mime_types.read().unwrap().get(mime).cloned()) {
+            return chunker;
+        }
+        Arc::new(mime.and_then(ChunkerType::from_mime).unwrap_or(ChunkerType::Text))
+    }
+}
+
+/// Process-wide chunker registry used by [`Chunker::chunk_file`].
+pub fn registry() -> &'static ChunkerRegistry {
+    static REGISTRY: OnceLock<ChunkerRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(ChunkerRegistry::default)
+}
+
 pub struct Chunker {
     // Parsers are not thread-safe so we create them on demand or thread-local 
     // but for simplicity here we re-create or use a pool later.
@@ -38,49 +233,16 @@ pub struct Chunker {
 
 impl Chunker {
     pub fn chunk_file(path: &Path, content: &str) -> Vec<Chunk> {
-        let file_type = Self::detect_type(path);
-        
-        match file_type {
-            ChunkerType::Python => Self::chunk_python(content),
-            ChunkerType::Rust => Self::chunk_rust(content),
-            ChunkerType::TypeScript => Self::chunk_typescript(content),
-            ChunkerType::JavaScript => Self::chunk_javascript(content),
-            ChunkerType::Go => Self::chunk_go(content),
-            ChunkerType::Html => Self::chunk_html(content),
-            ChunkerType::Css => Self::chunk_css(content),
-            ChunkerType::Php => Self::chunk_php(content),
-            ChunkerType::Java => Self::chunk_java(content),
-            ChunkerType::Markdown => Self::chunk_markdown(content),
-            ChunkerType::Csv => Self::chunk_csv(content),
-            ChunkerType::Json => Self::chunk_json(content),
-            ChunkerType::Yaml => Self::chunk_yaml(content),
-            ChunkerType::Xml => Self::chunk_xml(content),
-            ChunkerType::Pdf => Self::chunk_pdf(path),
-            ChunkerType::Office => Self::chunk_office(path),
-            ChunkerType::Text => Self::chunk_text(content),
-        }
+        registry().resolve(path, content).chunk(path, content)
     }
 
-    fn detect_type(path: &Path) -> ChunkerType {
-        match path.extension().and_then(|s| s.to_str()) {
-            Some("py") => ChunkerType::Python,
-            Some("rs") => ChunkerType::Rust,
-            Some("ts" | "tsx") => ChunkerType::TypeScript,
-            Some("js" | "jsx") => ChunkerType::JavaScript,
-            Some("go") => ChunkerType::Go,
-            Some("html" | "htm") => ChunkerType::Html,
-            Some("css") => ChunkerType::Css,
-            Some("php") => ChunkerType::Php,
-            Some("java") => ChunkerType::Java,
-            Some("md") => ChunkerType::Markdown,
-            Some("csv") => ChunkerType::Csv,
-            Some("json") => ChunkerType::Json,
-            Some("yaml" | "yml") => ChunkerType::Yaml,
-            Some("xml") => ChunkerType::Xml,
-            Some("pdf") => ChunkerType::Pdf,
-            Some("docx" | "xlsx" | "pptx") => ChunkerType::Office,
-            _ => ChunkerType::Text,
-        }
+    /// Built-in chunker for a file: by extension, else by sniffed MIME type.
+    pub fn detect_type(path: &Path, content: &str) -> ChunkerType {
+        path.extension()
+            .and_then(|s| s.to_str())
+            .and_then(|extension| ChunkerType::from_extension(&extension.to_ascii_lowercase()))
+            .or_else(|| sniff_mime(path, content).and_then(ChunkerType::from_mime))
+            .unwrap_or(ChunkerType::Text)
     }
 
     fn chunk_python(content: &str) -> Vec<Chunk> {
@@ -119,6 +281,12 @@ impl Chunker {
     }
 
     fn chunk_html(content: &str) -> Vec<Chunk> {
+        // Pages with prose are read as text by section; markup-heavy files
+        // (templates, components) keep one chunk per element
+        if let Some(page) = readability::extract(content).filter(|page| page.text_len() >= readability::MIN_READABLE_CHARS) {
+            return Self::chunk_readable_html(page);
+        }
+
         let mut parser = Parser::new();
         let language = tree_sitter_html::LANGUAGE;
         parser.set_language(&language.into()).expect("Error loading HTML grammar");
@@ -131,6 +299,36 @@ impl Chunker {
         chunks
     }
 
+    fn chunk_readable_html(page: ReadableDocument) -> Vec<Chunk> {
+        let mut page_cues = vec!["lang:html".to_string(), "type:html_section".to_string()];
+        if let Some(title) = &page.title {
+            page_cues.push(format!("title:{}", title));
+        }
+        if let Some(url) = &page.url {
+            page_cues.push(format!("url:{}", url));
+        }
+
+        page.sections.into_iter().map(|section| {
+            let mut cues = page_cues.clone();
+            let (content, context) = match &section.heading {
+                Some(heading) => {
+                    cues.push(format!("header:{}", heading));
+                    let content = format!("{} {}\n\n{}", "#".repeat(section.level), heading, section.text);
+                    (content.trim_end().to_string(), format!("section:{}", heading))
+                }
+                None => (section.text, "section:intro".to_string()),
+            };
+            Chunk {
+                content,
+                start_line: section.start_line,
+                end_line: section.end_line,
+                context,
+                structural_cues: cues,
+                metadata: HashMap::new(),
+            }
+        }).collect()
+    }
+
     fn visit_html_nodes(node: tree_sitter::Node, content: &str, chunks: &mut Vec<Chunk>) {
         if node.kind() == "element" {
             let start = node.start_position().row + 1;
@@ -204,6 +402,7 @@ impl Chunker {
                 end_line: end,
                 context: format!("html:{}", tag_name),
                 structural_cues: cues,
+                metadata: HashMap::new(),
             });
         }
 
@@ -288,6 +487,7 @@ impl Chunker {
                     format!("type:{}", type_cue),
                     format!("{}:{}", name_label, name),
                 ],
+                 metadata: HashMap::new(),
             });
         }
 
@@ -319,6 +519,7 @@ impl Chunker {
                             "type:markdown_section".to_string(),
                             format!("header:{}", current_header),
                         ],
+                        metadata: HashMap::new(),
                     });
                     current_block.clear();
                 }
@@ -338,6 +539,7 @@ impl Chunker {
                     "type:markdown_section".to_string(),
                     format!("header:{}", current_header),
                 ],
+                metadata: HashMap::new(),
             });
         }
         
@@ -366,6 +568,7 @@ impl Chunker {
                         end_line: row_count + 10,
                         context: "csv_rows".to_string(),
                         structural_cues: header_cues.clone(),
+                        metadata: HashMap::new(),
                     });
                     current_chunk.clear();
                     current_chunk.push_str(&headers.iter().collect::<Vec<_>>().join(","));
@@ -384,6 +587,7 @@ impl Chunker {
                 end_line: row_count,
                 context: "csv_rows".to_string(),
                 structural_cues: header_cues,
+                metadata: HashMap::new(),
             });
         }
         chunks
@@ -401,6 +605,7 @@ impl Chunker {
                         "type:json_entry".to_string(),
                         format!("key:{}", key),
                     ],
+                    metadata: HashMap::new(),
                 }).collect();
             } else if let Some(arr) = value.as_array() {
                 return arr.iter().enumerate().map(|(i, val)| Chunk {
@@ -412,6 +617,7 @@ impl Chunker {
                         "type:json_item".to_string(),
                         format!("index:{}", i),
                     ],
+                    metadata: HashMap::new(),
                 }).collect();
             }
         }
@@ -432,6 +638,7 @@ impl Chunker {
                             "type:yaml_entry".to_string(),
                             format!("key:{}", key_str),
                         ],
+                        metadata: HashMap::new(),
                     }
                 }).collect();
             }
@@ -462,6 +669,7 @@ impl Chunker {
                         end_line: 0,
                         context: format!("xml_tag:{}", node.tag_name().name()),
                         structural_cues: cues,
+                        metadata: HashMap::new(),
                     });
                 }
             }
@@ -472,11 +680,52 @@ impl Chunker {
         Self::chunk_text(content)
     }
 
+    /// One chunk per page, tagged with the document's title and author from
+    /// its information dictionary.
     fn chunk_pdf(path: &Path) -> Vec<Chunk> {
-        if let Ok(content) = pdf_extract::extract_text(path) {
-            return Self::chunk_text(&content);
+        let Ok(mut doc) = pdf_extract::Document::load(path) else {
+            return Vec::new();
+        };
+        if doc.is_encrypted() && doc.decrypt("").is_err() {
+            return Vec::new();
         }
-        Vec::new()
+
+        let mut document_cues = vec!["lang:pdf".to_string(), "type:pdf_page".to_string()];
+        let mut document_metadata = HashMap::new();
+        for (key, name) in [(&b"Title"[..], "title"), (&b"Author"[..], "author")] {
+            if let Some(value) = pdf_info(&doc, key) {
+                document_cues.push(format!("{}:{}", name, value));
+                document_metadata.insert(name.to_string(), serde_json::Value::String(value));
+            }
+        }
+
+        let mut chunks = Vec::new();
+        for page in doc.get_pages().into_keys() {
+            let mut text = String::new();
+            let mut output = pdf_extract::PlainTextOutput::new(&mut text);
+            if let Err(e) = pdf_extract::output_doc_page(&doc, &mut output, page) {
+                warn!("Skipping page {} of {:?}: {}", page, path, e);
+                continue;
+            }
+            let text = text.trim();
+            if text.is_empty() {
+                continue;
+            }
+
+            let mut cues = document_cues.clone();
+            cues.push(format!("page:{}", page));
+            let mut metadata = document_metadata.clone();
+            metadata.insert("page".to_string(), serde_json::Value::from(page));
+            chunks.push(Chunk {
+                content: text.to_string(),
+                start_line: 0,
+                end_line: 0,
+                context: format!("page:{}", page),
+                structural_cues: cues,
+                metadata,
+            });
+        }
+        chunks
     }
 
     fn chunk_office(path: &Path) -> Vec<Chunk> {
@@ -532,11 +781,33 @@ impl Chunker {
                      "lang:text".to_string(),
                      "type:text_paragraph".to_string()
                  ],
+                 metadata: HashMap::new(),
              }
         }).filter(|c| !c.content.trim().is_empty()).collect()
     }
 }
 
+/// Text value of `key` in a PDF's document information dictionary.
+fn pdf_info(doc: &pdf_extract::Document, key: &[u8]) -> Option<String> {
+    let info = doc.trailer.get_deref(b"Info", doc).ok()?.as_dict().ok()?;
+    let value = decode_pdf_text(info.get_deref(key, doc).ok()?.as_str().ok()?);
+    let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!value.is_empty()).then_some(value)
+}
+
+/// PDF text strings are UTF-16BE behind a byte order mark, else
+/// PDFDocEncoding, which agrees with Latin-1 on printable characters.
+fn decode_pdf_text(bytes: &[u8]) -> String {
+    match bytes {
+        [0xFE, 0xFF, rest @ ..] => {
+            let units: Vec<u16> = rest.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect();
+            String::from_utf16_lossy(&units)
+        }
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).into_owned(),
+        _ => bytes.iter().map(|&b| b as char).collect(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -608,9 +879,23 @@ mod tests {
 
     #[test]
     fn test_detect_type() {
-        assert_eq!(Chunker::detect_type(&PathBuf::from("test.py")), ChunkerType::Python);
-        assert_eq!(Chunker::detect_type(&PathBuf::from("test.csv")), ChunkerType::Csv);
-        assert_eq!(Chunker::detect_type(&PathBuf::from("test.pdf")), ChunkerType::Pdf);
-        assert_eq!(Chunker::detect_type(&PathBuf::from("test.docx")), ChunkerType::Office);
+        assert_eq!(Chunker::detect_type(&PathBuf::from("test.py"), ""), ChunkerType::Python);
+        assert_eq!(Chunker::detect_type(&PathBuf::from("test.csv"), ""), ChunkerType::Csv);
+        assert_eq!(Chunker::detect_type(&PathBuf::from("test.pdf"), ""), ChunkerType::Pdf);
+        assert_eq!(Chunker::detect_type(&PathBuf::from("test.docx"), ""), ChunkerType::Office);
+    }
+
+    #[test]
+    fn test_detect_type_sniffs_extensionless_files() {
+        let detect = |content: &str| Chunker::detect_type(&PathBuf::from("bin/tool"), content);
+        assert_eq!(detect("#!/usr/bin/env python3\nprint('hi')"), ChunkerType::Python);
+        assert_eq!(detect("#!/usr/bin/env node\nconsole.log(1)"), ChunkerType::JavaScript);
+        assert_eq!(detect("<?xml version='1.0'?><root/>"), ChunkerType::Xml);
+        assert_eq!(detect("<!DOCTYPE html><html></html>"), ChunkerType::Html);
+        assert_eq!(detect("{\"key\": 1}"), ChunkerType::Json);
+        assert_eq!(detect("{ not json"), ChunkerType::Text);
+        assert_eq!(detect("#!/bin/sh\necho hi"), ChunkerType::Text);
+        // An unknown extension is sniffed too
+        assert_eq!(Chunker::detect_type(&PathBuf::from("LICENSE.v2"), "<?php echo 1; ?>"), ChunkerType::Php);
     }
 }
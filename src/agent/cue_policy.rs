@@ -0,0 +1,213 @@
+//! Cues the agent attaches to, or withholds from, the memories it ingests.
+//!
+//! On top of the `path:` and `source:agent` cues every ingested memory gets,
+//! a policy adds static cues (`repo:backend`) and cues templated from the
+//! file's path below the watch root. Templates name directories by position,
+//! `{0}` being the top-level one, and the file by `{file}` (its stem) and
+//! `{ext}`, so `service:{0}` tags `billing/api/refund.rs` with
+//! `service:billing`. A template referring to a directory the file does not
+//! have is left out for that file. Blocked keys apply to the cues the LLM
+//! proposes only: `author` drops `author:jane` but never a configured cue.
+//!
+//! Directory routes partition one watch root: a file below a route's
+//! directory is filed under the route's collection and gets its cues, so
+//! `docs` → `docs` and `src` → `code` keep documentation and code apart.
+//! The most specific route a file is below applies; files below none are
+//! left outside any collection.
+
+use crate::collections::{validate_name, CollectionError};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CuePolicy {
+    pub static_cues: Vec<String>,
+    pub path_cues: Vec<String>,
+    pub blocked_keys: Vec<String>,
+    pub routes: Vec<DirectoryRoute>,
+}
+
+/// Where the memories of files below `dir` go.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DirectoryRoute {
+    /// Below the watch root, e.g. `docs` or `src/api`
+    pub dir: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cues: Vec<String>,
+}
+
+impl DirectoryRoute {
+    /// Parse `DIR=COLLECTION`, as given to `--agent-route`.
+    pub fn parse(route: &str) -> Result<Self, String> {
+        let (dir, collection) = route
+            .split_once('=')
+            .ok_or_else(|| format!("route {:?} must be DIR=COLLECTION", route))?;
+        Ok(Self { dir: dir.trim().to_string(), collection: Some(collection.trim().to_string()), cues: Vec::new() })
+    }
+
+    /// The route's directory as path components, lowercased like the paths
+    /// the agent tracks.
+    fn components(&self) -> Vec<String> {
+        self.dir.split(['/', '\\']).filter(|part| !part.is_empty() && *part != ".").map(str::to_lowercase).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Placeholder {
+    Directory(usize),
+    File,
+    Extension,
+}
+
+impl CuePolicy {
+    pub fn is_empty(&self) -> bool {
+        self.static_cues.is_empty() && self.path_cues.is_empty() && self.blocked_keys.is_empty() && self.routes.is_empty()
+    }
+
+    /// Problems with the policy, one per offending entry.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for cue in &self.static_cues {
+            if cue.trim().is_empty() {
+                problems.push("static cues must not be empty".to_string());
+            }
+        }
+        for template in &self.path_cues {
+            match parse_template(template) {
+                Ok(parts) if !parts.iter().any(|part| matches!(part, Part::Placeholder(_))) => {
+                    problems.push(format!("path cue {:?} has no placeholder; list it as a static cue", template));
+                }
+                Ok(_) => {}
+                Err(e) => problems.push(format!("path cue {:?}: {}", template, e)),
+            }
+        }
+        for key in &self.blocked_keys {
+            if key.trim().is_empty() || key.contains(':') {
+                problems.push(format!("blocked key {:?} must be a cue key without ':'", key));
+            }
+        }
+        let mut dirs = Vec::new();
+        for route in &self.routes {
+            let components = route.components();
+            if components.is_empty() || route.dir.starts_with('/') || components.iter().any(|part| part == "..") {
+                problems.push(format!("route {:?}: dir must be a directory below the watch root", route.dir));
+            } else if dirs.contains(&components) {
+                problems.push(format!("route {:?}: dir is routed twice", route.dir));
+            }
+            dirs.push(components);
+            if route.collection.is_none() && route.cues.is_empty() {
+                problems.push(format!("route {:?} names neither a collection nor cues", route.dir));
+            }
+            if let Some(Err(CollectionError::InvalidName(detail))) = route.collection.as_deref().map(validate_name) {
+                problems.push(format!("route {:?}: {}", route.dir, detail));
+            }
+            if route.cues.iter().any(|cue| cue.trim().is_empty()) {
+                problems.push(format!("route {:?}: cues must not be empty", route.dir));
+            }
+        }
+        problems
+    }
+
+    /// The most specific route `relative`, a path below the watch root, is
+    /// below.
+    pub fn route_for(&self, relative: &Path) -> Option<&DirectoryRoute> {
+        let path: Vec<String> = relative
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => name.to_str().map(str::to_lowercase),
+                _ => None,
+            })
+            .collect();
+        self.routes
+            .iter()
+            .map(|route| (route, route.components()))
+            // Below the directory, not the directory itself
+            .filter(|(_, dir)| !dir.is_empty() && dir.len() < path.len() && path.starts_with(dir))
+            .max_by_key(|(_, dir)| dir.len())
+            .map(|(route, _)| route)
+    }
+
+    /// Cues for a file at `relative`, its path below the watch root.
+    pub fn cues_for(&self, relative: &Path) -> Vec<String> {
+        let directories: Vec<&str> = relative
+            .parent()
+            .into_iter()
+            .flat_map(|parent| parent.components())
+            .filter_map(|component| match component {
+                Component::Normal(name) => name.to_str(),
+                _ => None,
+            })
+            .collect();
+        let file = relative.file_stem().and_then(|s| s.to_str());
+        let extension = relative.extension().and_then(|s| s.to_str());
+
+        let mut cues = self.static_cues.clone();
+        for template in &self.path_cues {
+            let Ok(parts) = parse_template(template) else { continue };
+            let rendered: Option<String> = parts
+                .iter()
+                .map(|part| match part {
+                    Part::Literal(text) => Some(*text),
+                    Part::Placeholder(Placeholder::Directory(index)) => directories.get(*index).copied(),
+                    Part::Placeholder(Placeholder::File) => file,
+                    Part::Placeholder(Placeholder::Extension) => extension,
+                })
+                .collect();
+            if let Some(cue) = rendered {
+                cues.push(cue);
+            }
+        }
+        if let Some(route) = self.route_for(relative) {
+            cues.extend(route.cues.iter().cloned());
+        }
+        cues
+    }
+
+    /// Whether the LLM may attach `cue`: its key, the part before the first
+    /// `:`, is not blocked. Cues without a key are always allowed.
+    pub fn allows(&self, cue: &str) -> bool {
+        match cue.split_once(':') {
+            Some((key, _)) => !self.blocked_keys.iter().any(|blocked| blocked.trim().eq_ignore_ascii_case(key.trim())),
+            None => true,
+        }
+    }
+}
+
+enum Part<'a> {
+    Literal(&'a str),
+    Placeholder(Placeholder),
+}
+
+fn parse_template(template: &str) -> Result<Vec<Part<'_>>, String> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        if rest[..start].contains('}') {
+            return Err("unmatched '}'".to_string());
+        }
+        if start > 0 {
+            parts.push(Part::Literal(&rest[..start]));
+        }
+        let end = rest[start..].find('}').ok_or("unclosed '{'")? + start;
+        let name = &rest[start + 1..end];
+        let placeholder = match name {
+            "file" => Placeholder::File,
+            "ext" => Placeholder::Extension,
+            _ => Placeholder::Directory(name.parse().map_err(|_| {
+                format!("unknown placeholder {{{}}} (expected a directory index, {{file}} or {{ext}})", name)
+            })?),
+        };
+        parts.push(Part::Placeholder(placeholder));
+        rest = &rest[end + 1..];
+    }
+    if rest.contains('}') {
+        return Err("unmatched '}'".to_string());
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Literal(rest));
+    }
+    Ok(parts)
+}
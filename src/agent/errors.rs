@@ -0,0 +1,156 @@
+//! Files the agent failed to ingest (`GET /agent/errors`).
+//!
+//! Failing to read a file, to get any text out of it, or to extract one of
+//! its chunks is recorded against the file with the reason. Each ingest of a
+//! file is an [`IngestAttempt`]; one that fails at any stage counts once,
+//! and one that completes cleanly clears the file's record. After
+//! [`AGENT_QUARANTINE_FAILURES`] failed ingests in a row the file is
+//! quarantined: scans and file events skip it without reading it until its
+//! modification time changes or it is re-ingested explicitly.
+
+use crate::config::{AGENT_ERRORS_PER_FILE, AGENT_QUARANTINE_FAILURES};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IngestStage {
+    Read,
+    Parse,
+    Extract,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IngestError {
+    pub stage: IngestStage,
+    pub reason: String,
+    /// The chunk's memory, for extraction errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_id: Option<String>,
+    pub at: f64,
+}
+
+/// The ingestion record of one failing file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileErrors {
+    pub path: PathBuf,
+    /// Ingests of the file that failed in a row
+    pub failures: u32,
+    pub quarantined: bool,
+    /// Most recent first
+    pub errors: Vec<IngestError>,
+    #[serde(skip)]
+    failed_attempt: u64,
+    /// Modification time when quarantined
+    #[serde(skip)]
+    modified: Option<SystemTime>,
+}
+
+/// Ingestion errors of the agent's files, by normalized path.
+#[derive(Debug, Default)]
+pub struct IngestErrors {
+    files: Mutex<BTreeMap<String, FileErrors>>,
+    attempts: AtomicU64,
+}
+
+impl IngestErrors {
+    /// Start an ingest of the file at `path`, normalized as `path_norm`.
+    pub fn start(self: &Arc<Self>, path_norm: &str, path: &Path) -> IngestAttempt {
+        IngestAttempt {
+            errors: self.clone(),
+            path_norm: path_norm.to_string(),
+            path: path.to_path_buf(),
+            attempt: self.attempts.fetch_add(1, Ordering::Relaxed) + 1,
+        }
+    }
+
+    /// Whether `path_norm` is quarantined and `path` has not been modified
+    /// since. A modified file is released.
+    pub fn is_quarantined(&self, path_norm: &str, path: &Path) -> bool {
+        let mut files = self.files.lock().unwrap();
+        let Some(file) = files.get_mut(path_norm).filter(|file| file.quarantined) else { return false };
+        if file.modified.is_some() && file.modified == modified(path) {
+            return true;
+        }
+        file.quarantined = false;
+        file.failures = 0;
+        false
+    }
+
+    /// Whether the last ingest of `path_norm` failed. Such a file is retried
+    /// even if unchanged.
+    pub fn is_failing(&self, path_norm: &str) -> bool {
+        self.files.lock().unwrap().contains_key(path_norm)
+    }
+
+    /// Take `path_norm` out of quarantine. Returns whether it was in it.
+    pub fn release(&self, path_norm: &str) -> bool {
+        let mut files = self.files.lock().unwrap();
+        let Some(file) = files.get_mut(path_norm).filter(|file| file.quarantined) else { return false };
+        file.quarantined = false;
+        file.failures = 0;
+        true
+    }
+
+    /// Failing files, by path.
+    pub fn files(&self) -> Vec<FileErrors> {
+        self.files.lock().unwrap().values().cloned().collect()
+    }
+}
+
+/// One ingest of a file, carried by the jobs it queues.
+#[derive(Debug, Clone)]
+pub struct IngestAttempt {
+    errors: Arc<IngestErrors>,
+    path_norm: String,
+    path: PathBuf,
+    attempt: u64,
+}
+
+impl IngestAttempt {
+    /// Record that this ingest failed at `stage`.
+    pub fn fail(&self, stage: IngestStage, reason: String, memory_id: Option<String>) {
+        let mut files = self.errors.files.lock().unwrap();
+        let file = files.entry(self.path_norm.clone()).or_insert_with(|| FileErrors {
+            path: self.path.clone(),
+            failures: 0,
+            quarantined: false,
+            errors: Vec::new(),
+            failed_attempt: 0,
+            modified: None,
+        });
+        let at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs_f64()).unwrap_or(0.0);
+        file.errors.insert(0, IngestError { stage, reason, memory_id, at });
+        file.errors.truncate(AGENT_ERRORS_PER_FILE);
+        // Counted already, or overtaken by a later ingest
+        if file.failed_attempt >= self.attempt {
+            return;
+        }
+        file.failed_attempt = self.attempt;
+        file.failures += 1;
+        if file.failures >= AGENT_QUARANTINE_FAILURES && !file.quarantined {
+            file.quarantined = true;
+            file.modified = modified(&self.path);
+            warn!("Agent: Quarantined {:?} after {} failed ingests", self.path, file.failures);
+        }
+    }
+
+    /// The ingest is over: clear the file's record unless it, or a later
+    /// ingest, failed.
+    pub fn finish(&self) {
+        let mut files = self.errors.files.lock().unwrap();
+        if files.get(&self.path_norm).is_some_and(|file| file.failed_attempt < self.attempt) {
+            files.remove(&self.path_norm);
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
@@ -0,0 +1,78 @@
+//! CueMap-specific exclusions from the watch root's `.cuemapignore`.
+//!
+//! The file uses gitignore syntax and applies on top of `.gitignore`, so a
+//! repository can be ingested without, say, its tests. Patterns are
+//! relative to the watch root. Only the root's file is read.
+
+use crate::agent::paths;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Name of the ignore file read from the watch root.
+pub const IGNORE_FILE: &str = ".cuemapignore";
+
+#[derive(Clone)]
+pub struct IgnoreRules {
+    /// Watch root as configured, which prefixes walked paths
+    watch_dir: PathBuf,
+    /// Canonical watch root, which prefixes paths from file events
+    root: PathBuf,
+    matcher: Gitignore,
+}
+
+impl IgnoreRules {
+    /// Rules of `watch_dir`'s ignore file; none when it is missing. Invalid
+    /// lines are logged and skipped.
+    pub fn load(watch_dir: &str) -> Self {
+        let watch_dir = PathBuf::from(watch_dir);
+        let root = paths::canonical(&watch_dir);
+        let file = root.join(IGNORE_FILE);
+        let mut builder = GitignoreBuilder::new(&root);
+        if file.is_file() {
+            if let Some(err) = builder.add(&file) {
+                warn!("Ignoring invalid lines of {:?}: {}", file, err);
+            }
+        }
+        let matcher = builder.build().unwrap_or_else(|err| {
+            warn!("Failed to load {:?}: {}", file, err);
+            Gitignore::empty()
+        });
+        Self { watch_dir, root, matcher }
+    }
+
+    /// Path of the ignore file.
+    pub fn file(&self) -> PathBuf {
+        self.root.join(IGNORE_FILE)
+    }
+
+    /// Number of patterns loaded.
+    pub fn len(&self) -> usize {
+        self.matcher.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether `path` is the ignore file.
+    pub fn is_ignore_file(&self, path: &Path) -> bool {
+        self.relative(path) == Some(Path::new(IGNORE_FILE))
+    }
+
+    /// Whether `path`, walked or reported by the watcher, is excluded. The
+    /// ignore file itself always is; paths outside the watch root never are.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        match self.relative(path) {
+            Some(relative) if relative == Path::new(IGNORE_FILE) => true,
+            Some(relative) if !relative.as_os_str().is_empty() => {
+                self.matcher.matched_path_or_any_parents(relative, is_dir).is_ignore()
+            }
+            _ => false,
+        }
+    }
+
+    fn relative<'a>(&self, path: &'a Path) -> Option<&'a Path> {
+        path.strip_prefix(&self.root).or_else(|_| path.strip_prefix(&self.watch_dir)).ok()
+    }
+}
@@ -1,10 +1,17 @@
 use crate::agent::chunker::Chunker;
+use crate::agent::cue_policy::CuePolicy;
+use crate::agent::errors::{IngestErrors, IngestStage};
+use crate::agent::ignore_rules::IgnoreRules;
+use crate::agent::paths;
 use crate::agent::AgentConfig;
 use crate::jobs::{Job, JobQueue};
+use crate::provenance::Provenance;
+use globset::Glob;
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::time::{sleep, Duration};
 use tracing::{info, warn, debug};
@@ -13,27 +20,108 @@ use ignore::WalkBuilder;
 pub struct Ingester {
     config: AgentConfig,
     job_queue: Arc<JobQueue>,
-    file_hashes: HashMap<String, String>, // path -> sha256
+    file_hashes: HashMap<String, TrackedFile>, // normalized path -> file
+    ignore: IgnoreRules,
+    cue_policy: Arc<CuePolicy>,
+    errors: Arc<IngestErrors>,
+}
+
+struct TrackedFile {
+    path: PathBuf,
+    hash: String, // sha256
 }
 
 impl Ingester {
     pub fn new(config: AgentConfig, job_queue: Arc<JobQueue>) -> Self {
+        let ignore = IgnoreRules::load(&config.watch_dir);
+        let cue_policy = Arc::new(config.cue_policy.clone());
         Self {
             config,
             job_queue,
             file_hashes: HashMap::new(),
+            ignore,
+            cue_policy,
+            errors: Arc::new(IngestErrors::default()),
+        }
+    }
+
+    /// Files that failed to ingest, and those quarantined for it.
+    pub fn errors(&self) -> Arc<IngestErrors> {
+        self.errors.clone()
+    }
+
+    /// Whether `.cuemapignore` excludes `path`.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        self.ignore.is_ignored(path, path.is_dir())
+    }
+
+    /// Whether `path` is the watch root's `.cuemapignore`.
+    pub fn is_ignore_file(&self, path: &Path) -> bool {
+        self.ignore.is_ignore_file(path)
+    }
+
+    /// Re-read `.cuemapignore` and drop the memories of tracked files it now
+    /// excludes. Files it no longer excludes are picked up by the next scan.
+    pub async fn reload_ignore(&mut self) {
+        self.ignore = IgnoreRules::load(&self.config.watch_dir);
+        info!("Reloaded {:?} ({} patterns)", self.ignore.file(), self.ignore.len());
+
+        let excluded: Vec<PathBuf> = self
+            .file_hashes
+            .values()
+            .filter(|file| self.ignore.is_ignored(&file.path, false))
+            .map(|file| file.path.clone())
+            .collect();
+        for path in excluded {
+            let _ = self.delete_file_path(path).await;
         }
     }
 
     pub async fn scan_all(&mut self) -> Result<(), String> {
         info!("Starting full scan of {}", self.config.watch_dir);
         
-        let path_str = self.config.watch_dir.clone();
+        let root = PathBuf::from(&self.config.watch_dir);
+        self.walk(&root).await;
         
-        // Use ignore crate to respect .gitignore
-        let walker = WalkBuilder::new(&path_str)
+        info!("Scan complete. Tracking {} files.", self.file_hashes.len());
+        Ok(())
+    }
+
+    /// Catch up on events the watcher missed below `root`, a directory or
+    /// file under the watch root: ingest what changed or appeared, and drop
+    /// the memories of tracked files that are gone. Returns how many files
+    /// were dropped.
+    pub async fn rescan(&mut self, root: &Path) -> usize {
+        debug!("Rescanning {:?}", root);
+        if root.exists() {
+            self.walk(root).await;
+        }
+
+        let relative_root = self.relative_path(root);
+        let gone: Vec<PathBuf> = self
+            .file_hashes
+            .values()
+            .filter(|file| self.relative_path(&file.path).starts_with(&relative_root) && !file.path.exists())
+            .map(|file| file.path.clone())
+            .collect();
+        for path in &gone {
+            let _ = self.delete_file_path(path.clone()).await;
+        }
+        if !gone.is_empty() {
+            info!("Rescan of {:?} dropped {} deleted files", root, gone.len());
+        }
+        gone.len()
+    }
+
+    /// Ingest the files below `root` that `.gitignore` and `.cuemapignore`
+    /// let through.
+    async fn walk(&mut self, root: &Path) {
+        // Use ignore crate to respect .gitignore, plus .cuemapignore
+        let ignore = self.ignore.clone();
+        let walker = WalkBuilder::new(root)
             .hidden(true)
             .git_ignore(true)
+            .filter_entry(move |entry| !ignore.is_ignored(entry.path(), entry.file_type().is_some_and(|t| t.is_dir())))
             .build();
 
         for result in walker {
@@ -41,8 +129,9 @@ impl Ingester {
                 Ok(entry) => {
                     let path = entry.path();
                     if path.is_file() {
-                        if let Err(_e) = self.process_file_path(path.to_path_buf()).await {
-                            // warn!("Failed to process {:?}: {}", path, e);
+                        // Recorded in `errors`; a broken file is no reason to stop the scan
+                        if let Err(e) = self.process_file_path(path.to_path_buf()).await {
+                            debug!("Failed to process {:?}: {}", path, e);
                         }
                         // Throttle
                         if self.config.throttle_ms > 0 {
@@ -53,34 +142,38 @@ impl Ingester {
                 Err(err) => warn!("Walk error: {}", err),
             }
         }
-        
-        info!("Scan complete. Tracking {} files.", self.file_hashes.len());
-        Ok(())
     }
 
     pub async fn process_file_path(&mut self, path: PathBuf) -> Result<(), String> {
         let path_str = path.to_string_lossy().to_string();
-        // Standardize casing for case-insensitive filesystems (MacOS/Windows)
-        let path_norm = path_str.to_lowercase();
+        let path_norm = paths::key(&path);
+        if self.errors.is_quarantined(&path_norm, &path) {
+            debug!("Skipping quarantined file: {}", path_norm);
+            return Ok(());
+        }
+        let attempt = self.errors.start(&path_norm, &path);
         
         // 1. Read file as bytes first (works for both text and binary)
-        let bytes = fs::read(&path)
-            .map_err(|e| format!("Read error: {}", e))?;
+        let bytes = fs::read(&path).map_err(|e| {
+            let reason = format!("Read error: {}", e);
+            attempt.fail(IngestStage::Read, reason.clone(), None);
+            reason
+        })?;
             
         // 2. Hash check
         let mut hasher = Sha256::new();
         hasher.update(&bytes);
         let hash = format!("{:x}", hasher.finalize());
         
-        if let Some(old_hash) = self.file_hashes.get(&path_norm) {
-            if old_hash == &hash {
+        if let Some(tracked) = self.file_hashes.get(&path_norm) {
+            if tracked.hash == hash && !self.errors.is_failing(&path_norm) {
                 debug!("Skipping unchanged file: {}", path_norm);
                 return Ok(());
             }
         }
         
         // Update hash
-        self.file_hashes.insert(path_norm.clone(), hash.clone());
+        self.file_hashes.insert(path_norm.clone(), TrackedFile { path: path.clone(), hash: hash.clone() });
         info!("Ingesting: {}", path_str);
         
         // 3. Chunk
@@ -88,10 +181,20 @@ impl Ingester {
         // The chunker will use the path for binary formats (PDF, Office)
         let content_str = String::from_utf8(bytes).ok();
         let chunks = Chunker::chunk_file(&path, content_str.as_deref().unwrap_or(""));
+        // A file with text (or any binary) that yields none failed to parse
+        let blank = content_str.as_deref().is_some_and(|content| content.trim().is_empty());
+        if !blank && chunks.iter().all(|chunk| chunk.content.trim().is_empty()) {
+            let reason = if content_str.is_some() { "Chunker found no text" } else { "Unsupported binary format or unreadable document" };
+            attempt.fail(IngestStage::Parse, reason.to_string(), None);
+        }
         
         // 4. Send to Job Queue
         let project_id = "main".to_string();
+        let relative = self.relative_path(&path);
+        let policy_cues = self.cue_policy.cues_for(&relative);
+        let collection = self.cue_policy.route_for(&relative).and_then(|route| route.collection.clone());
         let mut valid_memory_ids = Vec::new();
+        let mut cursor = 0;
         
         for chunk in chunks.iter() {
             let mut chunk_hasher = Sha256::new();
@@ -99,6 +202,11 @@ impl Ingester {
             let chunk_hash = format!("{:x}", chunk_hasher.finalize());
             // Use normalized path for ID consistency
             let memory_id = format!("file:{}:{}", path_norm, chunk_hash); 
+            let byte_range = content_str.as_deref().and_then(|content| chunk_byte_range(content, &chunk.content, cursor));
+            if let Some(range) = &byte_range {
+                cursor = range.end;
+            }
+            let provenance = Box::new(Provenance::new(path_norm.clone(), chunk_hash, byte_range, uuid::Uuid::new_v4().to_string()));
             
             let full_content = format!(
                 "File: {}\nContext: {}\nLines: {}-{}\n\n{}", 
@@ -110,6 +218,12 @@ impl Ingester {
                 memory_id: memory_id.clone(),
                 content: full_content,
                 file_path: path_norm.clone(),
+                metadata: chunk.metadata.clone(),
+                cues: policy_cues.clone(),
+                cue_policy: self.cue_policy.clone(),
+                collection: collection.clone(),
+                provenance,
+                attempt: attempt.clone(),
             }).await;
             
             valid_memory_ids.push(memory_id);
@@ -120,14 +234,77 @@ impl Ingester {
             project_id,
             file_path: path_norm,
             valid_memory_ids,
+            attempt: Some(attempt),
         }).await;
 
         Ok(())
     }
 
+    /// Ingest the tracked files whose path, as tracked or below the watch
+    /// root, matches `pattern` (a path or glob) again, as if they had
+    /// changed: forget their hashes, prune their memories and queue their
+    /// chunks for extraction. Failing files match too, and are taken out of
+    /// quarantine. A file `pattern` names that is not tracked yet is ingested
+    /// too. Returns the paths queued, by path.
+    pub async fn reingest(&mut self, pattern: &str) -> Result<Vec<PathBuf>, String> {
+        let matcher = Glob::new(&paths::key(Path::new(pattern)))
+            .map_err(|e| format!("Invalid path pattern: {}", e))?
+            .compile_matcher();
+        let failing = self.errors.files().into_iter().map(|file| file.path);
+        let mut paths: Vec<PathBuf> = self
+            .file_hashes
+            .values()
+            .map(|file| file.path.clone())
+            .chain(failing)
+            .filter(|path| {
+                matcher.is_match(paths::key(path)) || matcher.is_match(paths::key(&self.relative_path(path)))
+            })
+            .collect();
+        if paths.is_empty() {
+            let path = Path::new(&self.config.watch_dir).join(pattern);
+            if path.is_file() && !self.is_ignored(&path) {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        paths.dedup();
+
+        let mut queued = Vec::with_capacity(paths.len());
+        for path in paths {
+            let path_norm = paths::key(&path);
+            self.file_hashes.remove(&path_norm);
+            self.errors.release(&path_norm);
+            // Pruned first: extraction only adds cues to memories it finds
+            self.job_queue.enqueue(Job::VerifyFile {
+                project_id: "main".to_string(),
+                file_path: path_norm,
+                valid_memory_ids: Vec::new(),
+                attempt: None,
+            }).await;
+            match self.process_file_path(path.clone()).await {
+                Ok(()) => queued.push(path),
+                Err(e) => warn!("Re-ingest of {:?} failed: {}", path, e),
+            }
+        }
+        info!("Re-ingesting {} files matching {}", queued.len(), pattern);
+        Ok(queued)
+    }
+
+    /// `path` below the watch root, whether walked from it or reported by the
+    /// watcher with the root canonicalized.
+    fn relative_path(&self, path: &Path) -> PathBuf {
+        let watch_dir = Path::new(&self.config.watch_dir);
+        if let Ok(relative) = path.strip_prefix(watch_dir) {
+            return relative.to_path_buf();
+        }
+        path.strip_prefix(paths::canonical(watch_dir))
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|_| path.to_path_buf())
+    }
+
     pub async fn delete_file_path(&mut self, path: PathBuf) -> Result<(), String> {
         let path_str = path.to_string_lossy().to_string();
-        let path_norm = path_str.to_lowercase();
+        let path_norm = paths::key(&path);
         info!("Processing deletion: {}", path_str);
 
         // Remove from tracking
@@ -138,8 +315,23 @@ impl Ingester {
             project_id: "main".to_string(),
             file_path: path_norm,
             valid_memory_ids: Vec::new(),
+            attempt: None,
         }).await;
 
         Ok(())
     }
 }
+
+/// Where `chunk` sits in `content`: the first occurrence at or after `from`,
+/// else the first anywhere. `None` when the chunker rewrote the text.
+fn chunk_byte_range(content: &str, chunk: &str, from: usize) -> Option<Range<usize>> {
+    if chunk.is_empty() {
+        return None;
+    }
+    let start = content
+        .get(from..)
+        .and_then(|rest| rest.find(chunk))
+        .map(|offset| from + offset)
+        .or_else(|| content.find(chunk))?;
+    Some(start..start + chunk.len())
+}
@@ -1,11 +1,18 @@
 pub mod chunker;
+pub mod cue_policy;
+pub mod errors;
+pub mod ignore_rules;
+pub mod paths;
+pub mod readability;
 pub mod watcher;
 pub mod ingester;
 
 use crate::jobs::JobQueue;
 use crate::jobs::ProjectProvider;
 use crate::llm::LlmConfig;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 use tracing::{info, warn};
 
@@ -14,10 +21,27 @@ pub struct AgentConfig {
     pub watch_dir: String,
     pub throttle_ms: u64,
     pub llm: LlmConfig,
+    pub cue_policy: cue_policy::CuePolicy,
+}
+
+/// What the API reaches the agent through.
+#[derive(Clone)]
+pub struct AgentHandle {
+    /// Shared with the watcher, for re-ingesting on request
+    pub ingester: Arc<Mutex<ingester::Ingester>>,
+    /// Kept apart so reading them does not wait on a scan
+    pub errors: Arc<errors::IngestErrors>,
+}
+
+impl AgentHandle {
+    pub async fn new(ingester: Arc<Mutex<ingester::Ingester>>) -> Self {
+        let errors = ingester.lock().await.errors();
+        Self { ingester, errors }
+    }
 }
 
 pub struct Agent {
-    _config: AgentConfig,
+    config: AgentConfig,
     ingester: Arc<Mutex<ingester::Ingester>>,
     _watcher: watcher::Watcher,
 }
@@ -40,13 +64,18 @@ impl Agent {
             .map_err(|e| format!("Failed to create watcher: {}", e))?;
 
         Ok(Self {
-            _config: config,
+            config,
             ingester,
             _watcher: watcher,
         })
     }
 
-    pub async fn start(&self) {
+    pub async fn handle(&self) -> AgentHandle {
+        AgentHandle::new(self.ingester.clone()).await
+    }
+
+    /// Scan the watch directory, then reconcile it each `reconcile_every`.
+    pub async fn start(&self, reconcile_every: Option<Duration>) {
         info!("Agent started.");
         // Watcher runs in its own thread/task locally managed
         
@@ -58,5 +87,24 @@ impl Agent {
                 warn!("Initial scan failed: {}", e);
             }
         });
+        if let Some(every) = reconcile_every {
+            spawn_reconciler(self.ingester.clone(), PathBuf::from(&self.config.watch_dir), every);
+        }
     }
 }
+
+/// Rescan `watch_dir` each `every`, comparing the tracked files to the disk
+/// to catch changes and deletions the watcher never reported. The first run
+/// happens one interval after startup.
+pub fn spawn_reconciler(ingester: Arc<Mutex<ingester::Ingester>>, watch_dir: PathBuf, every: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(every);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            let dropped = ingester.lock().await.rescan(&watch_dir).await;
+            info!("Agent: Reconciled {:?} ({} deleted files dropped)", watch_dir, dropped);
+        }
+    })
+}
@@ -0,0 +1,52 @@
+//! Path handling that holds on every platform the agent is deployed to.
+
+use std::path::{Path, PathBuf};
+
+/// `path` resolved through symlinks, in the form the watcher reports it.
+/// Windows canonicalizes to verbatim `\\?\C:\...` paths, which no event path
+/// has as a prefix, so the marker is dropped when the rest is a plain drive
+/// path. Falls back to `path` itself when it cannot be resolved.
+pub fn canonical(path: &Path) -> PathBuf {
+    let resolved = match path.canonicalize() {
+        Ok(resolved) => resolved,
+        Err(_) => return path.to_path_buf(),
+    };
+    #[cfg(windows)]
+    {
+        let raw = resolved.to_string_lossy();
+        if let Some(plain) = raw.strip_prefix(r"\\?\") {
+            if plain.as_bytes().get(1) == Some(&b':') {
+                return PathBuf::from(plain);
+            }
+        }
+    }
+    resolved
+}
+
+/// Key a file is tracked, cued and pruned by. Lowercased for case-insensitive
+/// filesystems (macOS/Windows); on Windows, separators are also unified so
+/// `C:/notes\a.md` and `C:\notes\a.md` are the same file.
+pub fn key(path: &Path) -> String {
+    let key = path.to_string_lossy().to_lowercase();
+    if cfg!(windows) {
+        key.replace('\\', "/")
+    } else {
+        key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_lowercases() {
+        assert_eq!(key(Path::new("/Notes/Plan.MD")), "/notes/plan.md");
+    }
+
+    #[test]
+    fn test_canonical_falls_back_for_missing_path() {
+        let missing = Path::new("/definitely/not/here");
+        assert_eq!(canonical(missing), missing);
+    }
+}
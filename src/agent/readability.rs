@@ -0,0 +1,348 @@
+//! Readable text of saved web pages, for the HTML chunker.
+//!
+//! Boilerplate is dropped: scripts, styles, navigation, headers, footers,
+//! sidebars, forms, and elements whose id or class marks them as menus,
+//! banners or share widgets. When the page has an `<article>` or `<main>`,
+//! only that is read. The remaining text is split into sections at each
+//! heading, with whitespace collapsed and entities decoded. The page's
+//! `<title>` and canonical URL are kept for cues.
+
+use tree_sitter::{Node, Parser};
+
+/// Readable characters a page needs before it is chunked by section;
+/// shorter markup (templates, fragments) keeps the element chunker.
+pub const MIN_READABLE_CHARS: usize = 200;
+
+/// Elements never read.
+const SKIPPED_TAGS: &[&str] = &[
+    "head", "nav", "header", "footer", "aside", "form", "button", "select", "svg", "iframe", "noscript", "template", "canvas",
+];
+
+/// Id or class fragments of boilerplate containers.
+const BOILERPLATE_MARKERS: &[&str] = &[
+    "nav", "menu", "footer", "sidebar", "cookie", "banner", "advert", "promo", "share", "social", "breadcrumb", "newsletter",
+];
+
+/// Elements that end a line of text.
+const BLOCK_TAGS: &[&str] = &[
+    "p", "div", "section", "article", "main", "li", "ul", "ol", "dl", "dt", "dd", "table", "tr", "blockquote", "pre", "figure",
+    "figcaption", "br", "hr", "body", "html",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadableSection {
+    /// Text of the heading opening the section; `None` before the first one
+    pub heading: Option<String>,
+    /// Heading level, 1 for `<h1>`; 0 without a heading
+    pub level: usize,
+    /// Paragraphs separated by blank lines
+    pub text: String,
+    /// 1-based source lines the section spans
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ReadableDocument {
+    /// `<title>`, else `og:title`
+    pub title: Option<String>,
+    /// `<link rel="canonical">`, else `og:url`
+    pub url: Option<String>,
+    pub sections: Vec<ReadableSection>,
+}
+
+impl ReadableDocument {
+    /// Characters of readable text, headings included.
+    pub fn text_len(&self) -> usize {
+        self.sections
+            .iter()
+            .map(|section| section.text.chars().count() + section.heading.as_ref().map_or(0, |heading| heading.chars().count()))
+            .sum()
+    }
+}
+
+/// Readable content of an HTML page; `None` when it cannot be parsed.
+pub fn extract(html: &str) -> Option<ReadableDocument> {
+    let mut parser = Parser::new();
+    parser.set_language(&tree_sitter_html::LANGUAGE.into()).ok()?;
+    let tree = parser.parse(html, None)?;
+    let root = tree.root_node();
+
+    let mut document = ReadableDocument::default();
+    let mut og_title = None;
+    let mut og_url = None;
+    let mut main = None;
+    collect_metadata(root, html, &mut document, &mut og_title, &mut og_url, &mut main);
+    document.title = document.title.or(og_title);
+    document.url = document.url.or(og_url);
+
+    // Inside an article, <header> holds its title rather than site chrome
+    let mut reader = Reader { html, read_headers: main.is_some(), sections: Vec::new(), paragraph: String::new(), paragraphs: Vec::new() };
+    reader.open_section(None, 0, main.unwrap_or(root).start_position().row + 1);
+    reader.visit(main.unwrap_or(root));
+    reader.close_section(main.unwrap_or(root).end_position().row + 1);
+    document.sections = reader.sections;
+    Some(document)
+}
+
+/// Title, canonical URL and the main content element, if any.
+fn collect_metadata<'a>(
+    node: Node<'a>,
+    html: &str,
+    document: &mut ReadableDocument,
+    og_title: &mut Option<String>,
+    og_url: &mut Option<String>,
+    main: &mut Option<Node<'a>>,
+) {
+    if node.kind() == "element" {
+        match tag_name(node, html).as_deref() {
+            Some("title") if document.title.is_none() => {
+                document.title = Some(collapse_whitespace(&inner_text(node, html))).filter(|title| !title.is_empty());
+            }
+            Some("meta") => {
+                let content = attribute(node, html, "content");
+                match attribute(node, html, "property").or_else(|| attribute(node, html, "name")).as_deref() {
+                    Some("og:title") => *og_title = og_title.take().or(content),
+                    Some("og:url") => *og_url = og_url.take().or(content),
+                    _ => {}
+                }
+            }
+            Some("link") if attribute(node, html, "rel").as_deref() == Some("canonical") && document.url.is_none() => {
+                document.url = attribute(node, html, "href");
+            }
+            Some("article") if main.is_none_or(|main| tag_name(main, html).as_deref() != Some("article")) => {
+                *main = Some(node);
+            }
+            Some("main") if main.is_none() => *main = Some(node),
+            _ => {}
+        }
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_metadata(child, html, document, og_title, og_url, main);
+    }
+}
+
+struct Reader<'a> {
+    html: &'a str,
+    read_headers: bool,
+    sections: Vec<ReadableSection>,
+    paragraph: String,
+    paragraphs: Vec<String>,
+}
+
+impl Reader<'_> {
+    fn visit(&mut self, node: Node) {
+        match node.kind() {
+            "text" => self.push_text(node.utf8_text(self.html.as_bytes()).unwrap_or("")),
+            "entity" => self.push_text(&decode_entity(node.utf8_text(self.html.as_bytes()).unwrap_or(""))),
+            "element" => self.visit_element(node),
+            "document" => self.visit_children(node),
+            // Scripts, styles, comments, doctype
+            _ => {}
+        }
+    }
+
+    fn visit_element(&mut self, node: Node) {
+        let tag = tag_name(node, self.html).unwrap_or_default();
+        let skipped = SKIPPED_TAGS.contains(&tag.as_str()) && !(tag == "header" && self.read_headers);
+        if skipped || is_boilerplate(node, self.html) {
+            return;
+        }
+        if let Some(level) = heading_level(&tag) {
+            let heading = collapse_whitespace(&inner_text(node, self.html));
+            if !heading.is_empty() {
+                self.close_section(node.start_position().row);
+                self.open_section(Some(heading), level, node.start_position().row + 1);
+            }
+            return;
+        }
+        let block = BLOCK_TAGS.contains(&tag.as_str());
+        if block {
+            self.end_paragraph();
+        }
+        if tag == "li" {
+            self.paragraph.push_str("- ");
+        }
+        self.visit_children(node);
+        if block {
+            self.end_paragraph();
+        }
+    }
+
+    fn visit_children(&mut self, node: Node) {
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.visit(child);
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        for word in text.split_whitespace() {
+            // Text nodes carry no surrounding whitespace; punctuation attaches
+            let attached = word.starts_with(['.', ',', ';', ':', '!', '?', ')']);
+            if !self.paragraph.is_empty() && !self.paragraph.ends_with(' ') && !attached {
+                self.paragraph.push(' ');
+            }
+            self.paragraph.push_str(word);
+        }
+    }
+
+    fn end_paragraph(&mut self) {
+        let paragraph = std::mem::take(&mut self.paragraph);
+        let paragraph = paragraph.trim();
+        if !paragraph.is_empty() && paragraph != "-" {
+            self.paragraphs.push(paragraph.to_string());
+        }
+    }
+
+    fn open_section(&mut self, heading: Option<String>, level: usize, start_line: usize) {
+        self.sections.push(ReadableSection { heading, level, text: String::new(), start_line, end_line: start_line });
+    }
+
+    /// Finish the open section at `end_line`; dropped when it has no text
+    /// and no heading.
+    fn close_section(&mut self, end_line: usize) {
+        self.end_paragraph();
+        let text = std::mem::take(&mut self.paragraphs).join("\n\n");
+        if let Some(section) = self.sections.last_mut() {
+            section.text = text;
+            section.end_line = end_line.max(section.start_line);
+            if section.text.is_empty() && section.heading.is_none() {
+                self.sections.pop();
+            }
+        }
+    }
+}
+
+fn heading_level(tag: &str) -> Option<usize> {
+    match tag {
+        "h1" => Some(1),
+        "h2" => Some(2),
+        "h3" => Some(3),
+        "h4" => Some(4),
+        "h5" => Some(5),
+        "h6" => Some(6),
+        _ => None,
+    }
+}
+
+fn start_tag(node: Node) -> Option<Node> {
+    let mut cursor = node.walk();
+    let tag = node.children(&mut cursor).find(|child| matches!(child.kind(), "start_tag" | "self_closing_tag"));
+    tag
+}
+
+/// Lowercase tag name of an element.
+fn tag_name(node: Node, html: &str) -> Option<String> {
+    let start = start_tag(node)?;
+    let mut cursor = start.walk();
+    let name = start
+        .children(&mut cursor)
+        .find(|child| child.kind() == "tag_name")
+        .and_then(|name| name.utf8_text(html.as_bytes()).ok())
+        .map(str::to_ascii_lowercase);
+    name
+}
+
+/// Value of an element's attribute, unquoted and with entities decoded.
+fn attribute(node: Node, html: &str, name: &str) -> Option<String> {
+    let start = start_tag(node)?;
+    let mut cursor = start.walk();
+    for attr in start.children(&mut cursor).filter(|child| child.kind() == "attribute") {
+        let mut attr_cursor = attr.walk();
+        let mut parts = attr.children(&mut attr_cursor);
+        let Some(attr_name) = parts.next().and_then(|n| n.utf8_text(html.as_bytes()).ok()) else { continue };
+        if !attr_name.eq_ignore_ascii_case(name) {
+            continue;
+        }
+        let value = parts
+            .find(|part| matches!(part.kind(), "attribute_value" | "quoted_attribute_value"))
+            .and_then(|value| value.utf8_text(html.as_bytes()).ok())
+            .unwrap_or("");
+        let value = value.trim_matches(|c| c == '"' || c == '\'');
+        return Some(decode_entities(value.trim()));
+    }
+    None
+}
+
+fn is_boilerplate(node: Node, html: &str) -> bool {
+    if attribute(node, html, "role").is_some_and(|role| matches!(role.as_str(), "navigation" | "banner" | "contentinfo" | "complementary")) {
+        return true;
+    }
+    let id = attribute(node, html, "id").unwrap_or_default().to_ascii_lowercase();
+    let class = attribute(node, html, "class").unwrap_or_default().to_ascii_lowercase();
+    id.split(['-', '_', ' '])
+        .chain(class.split(['-', '_', ' ']))
+        .any(|word| BOILERPLATE_MARKERS.contains(&word))
+}
+
+/// All text under a node, entities decoded, scripts and styles left out.
+fn inner_text(node: Node, html: &str) -> String {
+    let mut text = String::new();
+    let mut cursor = node.walk();
+    let mut previous_end = None;
+    for child in node.children(&mut cursor) {
+        // Text nodes are trimmed, so whitespace only shows up between siblings
+        if previous_end.is_some_and(|end| end < child.start_byte()) {
+            text.push(' ');
+        }
+        previous_end = Some(child.end_byte());
+        match child.kind() {
+            "text" => text.push_str(child.utf8_text(html.as_bytes()).unwrap_or("")),
+            "entity" => text.push_str(&decode_entity(child.utf8_text(html.as_bytes()).unwrap_or(""))),
+            "element" => text.push_str(&inner_text(child, html)),
+            _ => {}
+        }
+    }
+    text
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn decode_entity(entity: &str) -> String {
+    let name = entity.trim_start_matches('&').trim_end_matches(';');
+    let decoded = match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        "mdash" => Some('—'),
+        "ndash" => Some('–'),
+        "hellip" => Some('…'),
+        "copy" => Some('©'),
+        _ => name
+            .strip_prefix("#x")
+            .or_else(|| name.strip_prefix("#X"))
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .or_else(|| name.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+            .and_then(char::from_u32),
+    };
+    decoded.map_or_else(|| entity.to_string(), String::from)
+}
+
+/// Decode the entities in attribute text, which the grammar does not split out.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match rest.find(';').filter(|end| *end <= 10) {
+            Some(end) => {
+                decoded.push_str(&decode_entity(&rest[..=end]));
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
@@ -1,8 +1,11 @@
 use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher as NotifyWatcher};
-use std::path::Path;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tracing::{error, debug};
+use tracing::{error, debug, warn};
+use crate::agent::ignore_rules::IGNORE_FILE;
 use crate::agent::ingester::Ingester;
 
 pub struct Watcher {
@@ -15,11 +18,50 @@ impl Watcher {
         
         let tx_ingester = ingester.clone();
         let handle = tokio::runtime::Handle::current();
+        let watch_root = PathBuf::from(&path);
+        // Subtrees with a rescan queued, so a burst of overflows queues one each
+        let pending_rescans: Arc<std::sync::Mutex<HashSet<PathBuf>>> = Arc::default();
         
         let watcher_plugin = move |res: notify::Result<Event>| {
             match res {
+                // The OS dropped events (inotify queue overflow, FSEvents
+                // MustScanSubDirs): rescan what they were about, or everything
+                Ok(event) if event.need_rescan() => {
+                    let roots = if event.paths.is_empty() { vec![watch_root.clone()] } else { event.paths };
+                    for root in roots {
+                        if !pending_rescans.lock().unwrap().insert(root.clone()) {
+                            continue;
+                        }
+                        warn!("Watcher missed events under {:?}; rescanning", root);
+                        let ingester = tx_ingester.clone();
+                        let pending_rescans = pending_rescans.clone();
+                        handle.spawn(async move {
+                            let mut locked = ingester.lock().await;
+                            // Events from here on are caught by another rescan
+                            pending_rescans.lock().unwrap().remove(&root);
+                            locked.rescan(&root).await;
+                        });
+                    }
+                }
                 Ok(event) => {
                     // Filter for Modify, Create, Remove
+                    if event.kind.is_modify() || event.kind.is_create() || event.kind.is_remove() {
+                        // A changed .cuemapignore is reloaded, whatever happened to it
+                        for path in event.paths.iter().filter(|path| path.file_name() == Some(OsStr::new(IGNORE_FILE))) {
+                            let ingester = tx_ingester.clone();
+                            let path = path.clone();
+                            handle.spawn(async move {
+                                let mut locked = ingester.lock().await;
+                                if locked.is_ignore_file(&path) {
+                                    locked.reload_ignore().await;
+                                    // Pick up files the new rules no longer exclude
+                                    if let Err(e) = locked.scan_all().await {
+                                        error!("Rescan after {:?} changed failed: {}", path, e);
+                                    }
+                                }
+                            });
+                        }
+                    }
                     if event.kind.is_modify() || event.kind.is_create() {
                         for path in event.paths {
                             if path.is_file() {
@@ -28,6 +70,9 @@ impl Watcher {
                                 // Spawn onto the specific runtime handle
                                 handle.spawn(async move {
                                     let mut locked = ingester.lock().await;
+                                    if locked.is_ignored(&path) {
+                                        return;
+                                    }
                                     if let Err(e) = locked.process_file_path(path.clone()).await {
                                        // reduce noise
                                        debug!("Skipping file {:?}: {}", path, e);
@@ -41,6 +86,9 @@ impl Watcher {
                             let ingester = tx_ingester.clone();
                             handle.spawn(async move {
                                 let mut locked = ingester.lock().await;
+                                if locked.is_ignored(&path) {
+                                    return;
+                                }
                                 if let Err(e) = locked.delete_file_path(path.clone()).await {
                                     error!("Error processing deletion {:?}: {}", path, e);
                                 }
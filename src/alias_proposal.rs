@@ -0,0 +1,305 @@
+//! Selecting cue pairs to propose as aliases (the `ProposeAliases` job and
+//! `GET /admin/alias-candidates`).
+//!
+//! Only mid-frequency cues are compared: cues on fewer than
+//! `min_cue_memories` or more than `max_cue_memories` memories are skipped,
+//! as is the most frequent 1% of the rest. Two cues are a candidate pair when
+//! their memory counts are within `size_similarity_max_ratio` of each other
+//! and they share a significant token. A pair is proposed when the memories
+//! of the smaller cue overlap those of the larger one by at least
+//! `overlap_threshold`. The overlap is first estimated on the
+//! `sample_size` most recent memories of each cue, and only computed exactly
+//! for pairs the estimate does not rule out.
+
+use crate::config::{
+    ALIAS_MAX_CANDIDATES, ALIAS_MAX_CUE_MEMORIES, ALIAS_MIN_CUE_MEMORIES, ALIAS_OVERLAP_THRESHOLD,
+    ALIAS_SAMPLE_SIZE, ALIAS_SIZE_SIMILARITY_MAX_RATIO,
+};
+use crate::engine::CueMapEngine;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// How far under `overlap_threshold` a sampled overlap may fall before the
+/// pair is dropped without computing the exact overlap.
+const SAMPLE_OVERLAP_SLACK: f64 = 0.15;
+
+/// Per-project alias proposal thresholds, defaulting to the `ALIAS_*`
+/// constants.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct AliasConfig {
+    #[serde(default = "default_min_cue_memories")]
+    pub min_cue_memories: usize,
+    #[serde(default = "default_max_cue_memories")]
+    pub max_cue_memories: usize,
+    /// Cues compared pairwise, most frequent first
+    #[serde(default = "default_max_candidates")]
+    pub max_candidates: usize,
+    /// Largest difference in memory counts, relative to the larger cue
+    #[serde(default = "default_size_similarity_max_ratio")]
+    pub size_similarity_max_ratio: f64,
+    #[serde(default = "default_overlap_threshold")]
+    pub overlap_threshold: f64,
+    #[serde(default = "default_sample_size")]
+    pub sample_size: usize,
+}
+
+fn default_min_cue_memories() -> usize {
+    ALIAS_MIN_CUE_MEMORIES
+}
+
+fn default_max_cue_memories() -> usize {
+    ALIAS_MAX_CUE_MEMORIES
+}
+
+fn default_max_candidates() -> usize {
+    ALIAS_MAX_CANDIDATES
+}
+
+fn default_size_similarity_max_ratio() -> f64 {
+    ALIAS_SIZE_SIMILARITY_MAX_RATIO
+}
+
+fn default_overlap_threshold() -> f64 {
+    ALIAS_OVERLAP_THRESHOLD
+}
+
+fn default_sample_size() -> usize {
+    ALIAS_SAMPLE_SIZE
+}
+
+impl Default for AliasConfig {
+    fn default() -> Self {
+        Self {
+            min_cue_memories: default_min_cue_memories(),
+            max_cue_memories: default_max_cue_memories(),
+            max_candidates: default_max_candidates(),
+            size_similarity_max_ratio: default_size_similarity_max_ratio(),
+            overlap_threshold: default_overlap_threshold(),
+            sample_size: default_sample_size(),
+        }
+    }
+}
+
+impl AliasConfig {
+    /// Reject settings that could never select a pair.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_cue_memories == 0 {
+            return Err("min_cue_memories must be at least 1".to_string());
+        }
+        if self.max_cue_memories < self.min_cue_memories {
+            return Err("max_cue_memories must be at least min_cue_memories".to_string());
+        }
+        if self.sample_size == 0 {
+            return Err("sample_size must be at least 1".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.size_similarity_max_ratio) {
+            return Err("size_similarity_max_ratio must be between 0 and 1".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.overlap_threshold) {
+            return Err("overlap_threshold must be between 0 and 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A pair of cues that passed the frequency, size and lexical gates.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AliasCandidate {
+    /// The cue that would become the alias
+    pub from: String,
+    /// The canonical cue it would point to
+    pub to: String,
+    pub from_memories: usize,
+    pub to_memories: usize,
+    /// Overlap of the most recent `sample_size` memories of each cue
+    pub sample_overlap: f64,
+    /// Exact overlap, `None` when the sample already ruled the pair out
+    pub overlap: Option<f64>,
+    /// Whether the overlap meets `overlap_threshold`
+    pub proposed: bool,
+}
+
+/// Outcome of the candidate selection over one engine.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AliasCandidates {
+    /// Cues left after frequency filtering, which were compared pairwise
+    pub cues_compared: usize,
+    /// Pairs by descending overlap, then sampled overlap
+    pub pairs: Vec<AliasCandidate>,
+}
+
+impl AliasCandidates {
+    /// The pairs to propose.
+    pub fn proposed(self) -> impl Iterator<Item = AliasCandidate> {
+        self.pairs.into_iter().filter(|pair| pair.proposed)
+    }
+}
+
+struct CueCandidate {
+    cue: Arc<str>,
+    len: usize,
+    sample: HashSet<u64>, // Hashed set for fast lookups in stage 1
+}
+
+/// Run the candidate selection over the cue index of `engine`. Nothing is
+/// written.
+pub fn select(engine: &CueMapEngine, config: &AliasConfig) -> AliasCandidates {
+    let cue_index = engine.get_cue_index();
+
+    // 1. Filter and Select Mid-Frequency Cues
+    let mut stats: Vec<(Arc<str>, usize)> = cue_index
+        .iter()
+        .map(|entry| (entry.key().clone(), entry.value().len()))
+        .filter(|(k, cnt)| k.len() >= 3 && *cnt >= config.min_cue_memories && *cnt <= config.max_cue_memories)
+        .collect();
+
+    stats.sort_unstable_by_key(|&(_, count)| std::cmp::Reverse(count));
+    let drop_count = (stats.len() as f64 * 0.01) as usize;
+    let stats = stats.into_iter().skip(drop_count).take(config.max_candidates).collect::<Vec<_>>();
+
+    // 2. Build Candidates
+    let candidates: Vec<CueCandidate> = stats
+        .into_iter()
+        .filter_map(|(key, len)| {
+            let entry = cue_index.get(&*key)?;
+            let sample: HashSet<u64> = entry.get_recent(Some(config.sample_size)).into_iter().collect();
+            Some(CueCandidate { cue: key, len, sample })
+        })
+        .collect();
+
+    // 3. Parallel Comparison
+    let mut pairs: Vec<AliasCandidate> = candidates
+        .par_iter()
+        .enumerate()
+        .fold(Vec::new, |mut acc, (i, cand_a)| {
+            for cand_b in candidates.iter().skip(i + 1) {
+                let diff = cand_a.len.abs_diff(cand_b.len);
+                let max_len = std::cmp::max(cand_a.len, cand_b.len);
+                if (diff as f64 / max_len as f64) > config.size_similarity_max_ratio {
+                    continue;
+                }
+
+                if !lexical_gate(&cand_a.cue, &cand_b.cue) {
+                    continue;
+                }
+
+                let intersection = cand_a.sample.intersection(&cand_b.sample).count();
+                let min_sample_len = std::cmp::min(cand_a.sample.len(), cand_b.sample.len());
+                if min_sample_len == 0 {
+                    continue;
+                }
+                let sample_overlap = intersection as f64 / min_sample_len as f64;
+
+                let overlap = if sample_overlap < config.overlap_threshold - SAMPLE_OVERLAP_SLACK {
+                    None
+                } else {
+                    let (Some(entry_a), Some(entry_b)) = (cue_index.get(&*cand_a.cue), cue_index.get(&*cand_b.cue)) else {
+                        continue;
+                    };
+                    let min_len = std::cmp::min(entry_a.len(), entry_b.len());
+                    if min_len == 0 {
+                        continue;
+                    }
+                    Some(entry_a.intersection_len(&entry_b) as f64 / min_len as f64)
+                };
+
+                let (canon, alias) = choose_canonical(&cand_a.cue, &cand_b.cue);
+                let (from_memories, to_memories) = if alias.as_str() == &*cand_a.cue {
+                    (cand_a.len, cand_b.len)
+                } else {
+                    (cand_b.len, cand_a.len)
+                };
+                acc.push(AliasCandidate {
+                    from: alias,
+                    to: canon,
+                    from_memories,
+                    to_memories,
+                    sample_overlap,
+                    overlap,
+                    proposed: overlap.is_some_and(|overlap| overlap >= config.overlap_threshold),
+                });
+            }
+            acc
+        })
+        .reduce(Vec::new, |mut a, b| {
+            a.extend(b);
+            a
+        });
+
+    pairs.sort_by(|a, b| {
+        b.overlap
+            .unwrap_or(-1.0)
+            .total_cmp(&a.overlap.unwrap_or(-1.0))
+            .then_with(|| b.sample_overlap.total_cmp(&a.sample_overlap))
+            .then_with(|| a.from.cmp(&b.from))
+            .then_with(|| a.to.cmp(&b.to))
+    });
+    AliasCandidates { cues_compared: candidates.len(), pairs }
+}
+
+// --- Helper Functions ---
+
+/// Split cue into significant tokens
+fn cue_tokens(cue: &str) -> SmallVec<[String; 8]> {
+    let mut tokens = SmallVec::new();
+    let parts = cue.split([':', '-', '_']);
+
+    for part in parts {
+        let lower = part.to_lowercase();
+        if lower.len() >= 3 {
+            tokens.push(lower);
+        }
+    }
+    tokens
+}
+
+/// Check if two cues share at least one significant token
+fn lexical_gate(a: &str, b: &str) -> bool {
+    // 1. Check if one contains the other (simple rewrite)
+    if a.contains(b) || b.contains(a) {
+        return true;
+    }
+
+    // 2. Token overlap
+    let tokens_a = cue_tokens(a);
+    if tokens_a.is_empty() {
+        return false;
+    }
+
+    let tokens_b = cue_tokens(b);
+    if tokens_b.is_empty() {
+        return false;
+    }
+
+    tokens_a.iter().any(|ta| tokens_b.contains(ta))
+}
+
+/// Check if cue is in canonical key:value format
+fn is_canonical_format(cue: &str) -> bool {
+    match cue.split_once(':') {
+        Some((k, v)) => !k.is_empty() && !v.is_empty(),
+        None => false,
+    }
+}
+
+/// Deterministically choose (canonical, alias)
+fn choose_canonical(a: &str, b: &str) -> (String, String) {
+    let a_canon = is_canonical_format(a);
+    let b_canon = is_canonical_format(b);
+
+    if a_canon && !b_canon {
+        (a.to_string(), b.to_string())
+    } else if !a_canon && b_canon {
+        (b.to_string(), a.to_string())
+    } else {
+        // Tie-breaker: lexicographical
+        if a < b {
+            (a.to_string(), b.to_string())
+        } else {
+            (b.to_string(), a.to_string())
+        }
+    }
+}
@@ -1,21 +1,49 @@
-use crate::auth::AuthConfig;
+use crate::auth::{AuthConfig, KeyStore, Scope};
+use crate::collections::{CollectionError, CollectionFilter};
 use crate::multi_tenant::{MultiTenantEngine, validate_project_id};
-use crate::projects::ProjectContext;
-use crate::normalization::normalize_cue;
-use crate::taxonomy::validate_cues;
+use crate::projects::{CueExpansion, ProjectContext, RecallDefaults, SpellingCorrection};
+use crate::normalization::{normalize_cue, NormalizationConfig};
+use crate::nl::TokenizerConfig;
+use crate::taxonomy::Taxonomy;
 use crate::jobs::{Job, JobQueue};
+use crate::grounding::{GroundingTraceStore, TraceFilter};
+use crate::alias_proposal::{self, AliasConfig};
+use crate::config::{self, ALIAS_CANDIDATES_DEFAULT_LIMIT, RECALL_BATCH_MAX_QUERIES, RERANK_TOP_N};
+use crate::engine::{AttachOutcome, CueMapEngine, RecallResult, UpdateOutcome};
+use crate::structures::Memory;
+use crate::rerank::{self, RerankConfig};
+use crate::provenance;
+use crate::agent::AgentHandle;
+use crate::ranking::RankingConfig;
+use crate::result_fields::ResultFields;
+use crate::content_limit::{self, ContentLimit, Oversized};
+use crate::shared_vocabulary::SharedVocabulary;
+use crate::reproposal::ReproposalFilter;
+use crate::evals::golden::{self, EvalRun, EvalStore, GoldenQuery, GoldenSet};
+use crate::error::ApiError;
+use crate::limits::{self, with_budget, RouteBudget};
 use axum::{
-    extract::{Path, State},
-    http::{StatusCode, HeaderMap},
+    extract::{DefaultBodyLimit, Extension, Path, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     middleware,
+    response::sse::{Event, KeepAlive, Sse},
     response::IntoResponse,
     routing::{get, patch, post, delete},
     Json, Router,
 };
 use rayon::prelude::*;
+use tower_http::compression::predicate::{NotForContentType, Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::convert::Infallible;
 use std::sync::Arc;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+
+/// JSON response of a handler; failures become problem+json bodies.
+type ApiResult = Result<(StatusCode, Json<serde_json::Value>), ApiError>;
 
 #[derive(Debug, Deserialize)]
 pub struct AddMemoryRequest {
@@ -25,6 +53,9 @@ pub struct AddMemoryRequest {
     metadata: Option<HashMap<String, serde_json::Value>>,
     #[serde(default)]
     pub disable_temporal_chunking: bool,
+    /// One of the project's collections to file the memory under
+    #[serde(default)]
+    pub collection: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -33,37 +64,253 @@ pub struct AddMemoryResponse {
     status: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct RecallRequest {
     #[serde(default)]
     cues: Vec<String>,
     #[serde(default)]
     query_text: Option<String>,
-    #[serde(default = "default_limit")]
-    limit: usize,
+    /// Unset fields among `limit`, `min_intersection`, `auto_reinforce`
+    /// and `explain` take the project's recall defaults
+    #[serde(default)]
+    limit: Option<usize>,
     #[serde(default)]
-    auto_reinforce: bool,
+    auto_reinforce: Option<bool>,
     #[serde(default)]
     projects: Option<Vec<String>>,
     #[serde(default)]
     min_intersection: Option<usize>,
     #[serde(default)]
-    pub explain: bool,
+    pub explain: Option<bool>,
     #[serde(default)]
     pub disable_pattern_completion: bool,
     #[serde(default)]
     pub disable_salience_bias: bool,
     #[serde(default)]
     pub disable_systems_consolidation: bool,
+    /// Cap on candidates probed before scoring (defaults to MAX_RECALL_CANDIDATES)
+    #[serde(default)]
+    pub max_candidates: Option<usize>,
+    /// Rerank the top cue matches by embedding similarity to the query
+    /// (single-project recalls only)
+    #[serde(default)]
+    pub rerank: bool,
+    /// Correct misspelled `query_text` words against the lexicon before resolving
+    #[serde(default)]
+    pub spell_correct: bool,
+    /// Expand query cues through aliases; off for exact cue lookups
+    #[serde(default = "default_true")]
+    pub expand_aliases: bool,
+    /// Resolve `query_text` to cues through the lexicon; off ignores `query_text`
+    #[serde(default = "default_true")]
+    pub resolve_text: bool,
+    /// Collections to recall from or leave out, applied before scoring
+    #[serde(default)]
+    pub collections: Option<CollectionFilter>,
+    /// Only score these memories, e.g. the ids returned by an earlier recall
+    #[serde(default)]
+    pub within_ids: Option<Vec<String>>,
+    /// Comma-separated result fields to return, e.g. `id,score,cues`
+    /// (single-project recalls only)
+    #[serde(default)]
+    pub fields: Option<String>,
+}
+
+impl RecallRequest {
+    /// Fill the settings the request left out from the project's defaults.
+    fn apply_defaults(&mut self, defaults: &RecallDefaults) {
+        self.limit = self.limit.or(defaults.limit);
+        self.min_intersection = self.min_intersection.or(defaults.min_intersection);
+        self.auto_reinforce = self.auto_reinforce.or(defaults.auto_reinforce);
+        self.explain = self.explain.or(defaults.explain);
+    }
+
+    fn limit(&self) -> usize {
+        self.limit.unwrap_or_else(default_limit)
+    }
+
+    fn auto_reinforce(&self) -> bool {
+        self.auto_reinforce.unwrap_or(false)
+    }
+
+    /// Auto-reinforcing recalls write to the project, so a read key may not
+    /// make one, whether it asks or the project's defaults do. `caller` is
+    /// the key's scope, unset when auth is off.
+    fn check_auto_reinforce(&self, caller: Option<&Extension<Scope>>) -> Result<(), ApiError> {
+        if self.auto_reinforce() && caller.is_some_and(|Extension(scope)| *scope < Scope::Write) {
+            return Err(ApiError::Forbidden("Insufficient scope: write access required for auto_reinforce".to_string()));
+        }
+        Ok(())
+    }
+
+    fn explain(&self) -> bool {
+        self.explain.unwrap_or(false)
+    }
+
+    /// Candidates to fetch from the engine: reranking looks past `limit`.
+    fn recall_limit(&self) -> usize {
+        if self.rerank {
+            self.limit().max(RERANK_TOP_N)
+        } else {
+            self.limit()
+        }
+    }
+
+    /// `query_text` to resolve against `ctx`, spell-corrected when requested.
+    fn resolution_text(&self, ctx: &ProjectContext) -> (Option<String>, Vec<SpellingCorrection>) {
+        match &self.query_text {
+            _ if !self.resolve_text => (None, Vec::new()),
+            Some(text) if self.spell_correct => {
+                let (corrected, corrections) = ctx.correct_spelling(text);
+                (Some(corrected), corrections)
+            }
+            text => (text.clone(), Vec::new()),
+        }
+    }
+
+    /// Alias hops to follow for this request.
+    fn alias_depth(&self) -> usize {
+        if self.expand_aliases { config::alias_max_depth() } else { 0 }
+    }
+
+    /// The result fields selected with `fields`, `None` for full results.
+    fn result_fields(&self) -> Result<Option<ResultFields>, ApiError> {
+        self.fields.as_deref().map(ResultFields::parse).transpose().map_err(ApiError::BadRequest)
+    }
+
+    /// Text the query is embedded from when reranking.
+    fn rerank_query(&self) -> Option<String> {
+        self.rerank.then(|| self.query_text.clone().unwrap_or_else(|| self.cues.join(" ")))
+    }
+
+    /// Key of this recall's response in `ctx`'s recall cache: the expanded
+    /// cues and every setting that shapes the response. `None` while the
+    /// cache is off, for auto-reinforcing recalls, which must run to
+    /// reinforce, and while recall hooks are registered, which must see every
+    /// recall. Explained recalls also key on the query before expansion,
+    /// which the explanation reports.
+    fn cache_key(
+        &self,
+        ctx: &ProjectContext,
+        expanded_cues: &[(String, f64)],
+        query_cues: &[String],
+        spelling_corrections: &[SpellingCorrection],
+    ) -> Option<String> {
+        if !ctx.recall_cache.is_enabled() || self.auto_reinforce() || ctx.hooks().has_recall_hooks() {
+            return None;
+        }
+        let explained = self.explain().then(|| serde_json::json!([query_cues, spelling_corrections]));
+        let key = serde_json::json!({
+            "cues": expanded_cues,
+            "explained": explained,
+            "limit": self.limit(),
+            "min_intersection": self.min_intersection,
+            "disable": [self.disable_pattern_completion, self.disable_salience_bias, self.disable_systems_consolidation],
+            "max_candidates": self.max_candidates,
+            "rerank": self.rerank_query(),
+            "collections": self.collections,
+            "within_ids": self.within_ids,
+            "fields": self.fields,
+        });
+        Some(key.to_string())
+    }
+}
+
+/// The response cached under `key` at `generation`, marked as cached and
+/// with the lookup time as its latency.
+fn cached_recall(ctx: &ProjectContext, key: Option<&str>, generation: u64, start: std::time::Instant) -> Option<serde_json::Value> {
+    let mut response = ctx.recall_cache.get(key?, generation)?;
+    response["engine_latency"] = serde_json::json!(start.elapsed().as_secs_f64() * 1000.0);
+    response["cached"] = serde_json::json!(true);
+    Some(response)
+}
+
+/// Body of a grounded recall response, marked as cached when the memory
+/// selection came from the recall cache.
+fn grounded_response(context_block: String, proof: crate::grounding::GroundingProof, elapsed: std::time::Duration, cached: bool) -> serde_json::Value {
+    let mut response = serde_json::json!({
+        "verified_context": context_block,
+        "proof": proof,
+        "engine_latency_ms": elapsed.as_secs_f64() * 1000.0
+    });
+    if cached {
+        response["cached"] = serde_json::json!(true);
+    }
+    response
+}
+
+/// Rerank `results` against `query` and cut them to `limit`. Returns how many
+/// were re-scored; when embedding fails the cue order is kept.
+async fn rerank_results(engine: &CueMapEngine, query: &str, limit: usize, results: &mut Vec<RecallResult>) -> usize {
+    let reranked = match RerankConfig::from_env() {
+        Some(config) => rerank::rerank(engine, query, results, &config).await.unwrap_or_else(|e| {
+            tracing::warn!("Embedding rerank failed, keeping cue order: {}", e);
+            0
+        }),
+        None => 0,
+    };
+    results.truncate(limit);
+    reranked
+}
+
+/// Recall results as JSON, narrowed to `fields` when the request selected some.
+fn results_json(engine: &CueMapEngine, results: &[RecallResult], fields: Option<&ResultFields>) -> serde_json::Value {
+    match fields {
+        Some(fields) => serde_json::json!(fields.project(engine, results)),
+        None => serde_json::json!(results),
+    }
+}
+
+/// The parts of a recall request that decide its query cues; requests in a
+/// batch that agree on them share one normalization and alias expansion.
+#[derive(PartialEq, Eq, Hash)]
+struct QueryKey<'a> {
+    cues: &'a [String],
+    query_text: Option<&'a str>,
+    resolve_text: bool,
+    spell_correct: bool,
+    alias_depth: usize,
+}
+
+/// Query cues of a recall request, resolved and alias-expanded.
+struct PreparedQuery {
+    query_cues: Vec<String>,
+    expanded_cues: Vec<(String, f64)>,
+    alias_paths: Vec<CueExpansion>,
+    spelling_corrections: Vec<SpellingCorrection>,
+}
+
+impl PreparedQuery {
+    fn new(ctx: &ProjectContext, req: &RecallRequest) -> Self {
+        let (resolution_text, spelling_corrections) = req.resolution_text(ctx);
+        let query_cues = ctx.query_cues(&req.cues, resolution_text.as_deref());
+        let cues = query_cues.iter().map(|(cue, _)| cue.clone()).collect();
+        let (expanded_cues, alias_paths) = expand_with_paths(ctx, query_cues, req.alias_depth());
+        Self { query_cues: cues, expanded_cues, alias_paths, spelling_corrections }
+    }
+}
+
+/// Expand query cues through up to `depth` alias hops. Returns the weighted
+/// cues to recall and, for explain output, the cues reached through aliases.
+fn expand_with_paths(ctx: &ProjectContext, cues: Vec<(String, f64)>, depth: usize) -> (Vec<(String, f64)>, Vec<CueExpansion>) {
+    let expansions = ctx.expand_query_paths(cues, depth);
+    let weighted = expansions.iter().map(|e| (e.cue.clone(), e.weight)).collect();
+    let aliased = expansions.into_iter().filter(|e| e.path.len() > 1).collect();
+    (weighted, aliased)
 }
 
 #[derive(Debug, Deserialize)]
 pub struct RecallGroundedRequest {
     pub query_text: String,
+    /// Cues recalled alongside those resolved from `query_text`
+    #[serde(default)]
+    pub cues: Vec<String>,
     #[serde(default = "default_token_budget")]
     pub token_budget: u32,
-    #[serde(default = "default_limit")]
-    pub limit: usize,
+    /// Unset fields among `limit`, `max_age_days` and `prefer_fresh` take
+    /// the project's recall defaults
+    #[serde(default)]
+    pub limit: Option<usize>,
     #[serde(default)]
     pub projects: Option<Vec<String>>,
     #[serde(default)]
@@ -72,12 +319,126 @@ pub struct RecallGroundedRequest {
     pub disable_salience_bias: bool,
     #[serde(default)]
     pub disable_systems_consolidation: bool,
+    #[serde(default)]
+    pub max_age_days: Option<f64>,
+    #[serde(default)]
+    pub prefer_fresh: Option<bool>,
+    /// Expand query cues through aliases; off for exact cue lookups
+    #[serde(default = "default_true")]
+    pub expand_aliases: bool,
+    /// Resolve `query_text` to cues through the lexicon; off recalls `cues` only
+    #[serde(default = "default_true")]
+    pub resolve_text: bool,
+}
+
+impl RecallGroundedRequest {
+    /// Query cues, and the weighted cues to recall after alias expansion
+    /// (skipped when `expand_aliases` is off).
+    fn expanded_cues(&self, ctx: &ProjectContext) -> (Vec<String>, Vec<(String, f64)>) {
+        let text = self.resolve_text.then_some(self.query_text.as_str());
+        let query_cues = ctx.query_cues(&self.cues, text);
+        let resolved = query_cues.iter().map(|(cue, _)| cue.clone()).collect();
+        let depth = if self.expand_aliases { config::alias_max_depth() } else { 0 };
+        (resolved, expand_with_paths(ctx, query_cues, depth).0)
+    }
+
+    /// Fill the settings the request left out from the project's defaults.
+    fn apply_defaults(&mut self, defaults: &RecallDefaults) {
+        self.limit = self.limit.or(defaults.limit);
+        self.max_age_days = self.max_age_days.or(defaults.max_age_days);
+        self.prefer_fresh = self.prefer_fresh.or(defaults.prefer_fresh);
+    }
+
+    fn limit(&self) -> usize {
+        self.limit.unwrap_or_else(default_limit)
+    }
+
+    fn freshness(&self) -> crate::grounding::FreshnessPolicy {
+        crate::grounding::FreshnessPolicy {
+            max_age_days: self.max_age_days,
+            prefer_fresh: self.prefer_fresh.unwrap_or(false),
+        }
+    }
+
+    /// Key of this recall's memory selection in `ctx`'s recall cache, apart
+    /// from `/recall` keys. `None` while the cache is off and while recall
+    /// hooks are registered. Only the selection is cached: every response
+    /// still gets its own proof and recorded trace.
+    fn cache_key(&self, ctx: &ProjectContext, expanded_cues: &[(String, f64)]) -> Option<String> {
+        if !ctx.recall_cache.is_enabled() || ctx.hooks().has_recall_hooks() {
+            return None;
+        }
+        let key = serde_json::json!({
+            "grounded": expanded_cues,
+            "limit": self.limit(),
+            "token_budget": self.token_budget,
+            "disable": [self.disable_pattern_completion, self.disable_salience_bias, self.disable_systems_consolidation],
+            "max_age_days": self.max_age_days,
+            "prefer_fresh": self.prefer_fresh,
+        });
+        Some(key.to_string())
+    }
+
+    /// Recall `expanded_cues` and select memories within the token budget,
+    /// from the recall cache when it holds this selection. Returns the
+    /// selected and excluded memories, the context block and whether the
+    /// selection was cached.
+    async fn select(&self, ctx: &Arc<ProjectContext>, expanded_cues: &[(String, f64)]) -> Result<GroundedSelection, ApiError> {
+        let cache_key = self.cache_key(ctx, expanded_cues);
+        let generation = ctx.main.recall_generation();
+        if let Some(cached) = cache_key.as_deref().and_then(|key| ctx.recall_cache.get(key, generation)) {
+            if let Ok((selected, excluded, context_block)) = serde_json::from_value(cached) {
+                return Ok((selected, excluded, context_block, true));
+            }
+        }
+
+        let mut results = tokio::task::spawn_blocking({
+            let (ctx, expanded_cues, limit) = (ctx.clone(), expanded_cues.to_vec(), self.limit().max(20));
+            let disable = (self.disable_pattern_completion, self.disable_salience_bias, self.disable_systems_consolidation);
+            move || ctx.main.recall_weighted(expanded_cues, limit, false, None, true, disable.0, disable.1, disable.2, None)
+        })
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+        ctx.after_recall(expanded_cues, Some(&self.query_text), &mut results);
+
+        let (selected, excluded, context_block) = crate::grounding::GroundingEngine::select_memories(
+            self.query_text.clone(),
+            Vec::new(),
+            expanded_cues.to_vec(),
+            results,
+            self.token_budget,
+            &self.freshness(),
+        );
+        if let Some(key) = cache_key {
+            ctx.recall_cache.insert(key, serde_json::json!([selected, excluded, context_block]), generation);
+        }
+        Ok((selected, excluded, context_block, false))
+    }
 }
 
+type GroundedSelection = (Vec<crate::grounding::SelectedItem>, Vec<crate::grounding::ExcludedItem>, String, bool);
+
 fn default_token_budget() -> u32 {
     500
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CitationCheckRequest {
+    pub trace_id: String,
+    pub answer: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecallFeedbackRequest {
+    pub trace_id: String,
+    /// Selected memory_ids that helped answer the query
+    #[serde(default)]
+    pub useful: Vec<String>,
+    /// Selected memory_ids that were wrong or misleading
+    #[serde(default)]
+    pub harmful: Vec<String>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct RecallGroundedResponse {
     pub verified_context: String,
@@ -94,6 +455,19 @@ pub struct ReinforceRequest {
     cues: Vec<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct AttachCuesRequest {
+    cues: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMemoryRequest {
+    content: String,
+    /// Replaces every cue of the memory; the cues are kept when omitted
+    #[serde(default)]
+    cues: Option<Vec<String>>,
+}
+
 
 #[derive(Debug, Deserialize)]
 pub struct AddAliasRequest {
@@ -113,6 +487,113 @@ pub struct MergeAliasRequest {
     pub to: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct NormalizationTestRequest {
+    pub cues: Vec<String>,
+    /// Candidate config to test instead of the project's current one.
+    #[serde(default)]
+    pub config: Option<NormalizationConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeprecateKeyRequest {
+    pub key: String,
+    pub replacement: String,
+    #[serde(default = "default_true")]
+    pub migrate: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RejectionsQuery {
+    /// Only list rejections of this key
+    pub key: Option<String>,
+    #[serde(default = "default_rejections_limit")]
+    pub limit: usize,
+}
+
+fn default_rejections_limit() -> usize {
+    100
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CuesQuery {
+    /// Only list cues starting with this, as normalized
+    pub prefix: Option<String>,
+    #[serde(default = "default_cues_limit")]
+    pub limit: usize,
+}
+
+fn default_cues_limit() -> usize {
+    100
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubgraphQuery {
+    pub cue: String,
+    /// Hops through co-occurring cues; `SUBGRAPH_DEFAULT_DEPTH` when absent
+    pub depth: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReproposalQuery {
+    /// Only memories holding this cue
+    pub filter: Option<String>,
+    /// Only memories with at most this many cues
+    pub max_cues: Option<usize>,
+    /// LLM proposals per second; `REPROPOSAL_DEFAULT_RATE` when absent
+    pub rate: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyQuery {
+    /// Rebuild inconsistent cue indexes from their memories
+    #[serde(default)]
+    pub rebuild: bool,
+}
+
+/// Threshold overrides for `GET /admin/alias-candidates`, applied on top of
+/// the project's alias proposal settings.
+#[derive(Debug, Deserialize)]
+pub struct AliasCandidatesQuery {
+    pub min_cue_memories: Option<usize>,
+    pub max_cue_memories: Option<usize>,
+    pub max_candidates: Option<usize>,
+    pub size_similarity_max_ratio: Option<f64>,
+    pub overlap_threshold: Option<f64>,
+    pub sample_size: Option<usize>,
+    /// Pairs to list, highest overlap first
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PromoteRejectionRequest {
+    pub key: String,
+    /// Promote only this value of `key`; all quarantined values otherwise
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCollectionRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateCollectionRequest {
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CollectionMembersRequest {
+    pub memory_ids: Vec<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
 #[derive(Debug, Serialize)]
 pub struct AliasResponse {
     pub id: String,
@@ -134,357 +615,2481 @@ pub enum EngineState {
     SingleTenant { 
         project: Arc<ProjectContext>, 
         read_only: bool,
-        job_queue: Arc<JobQueue> 
+        job_queue: Arc<JobQueue>,
+        traces: Arc<GroundingTraceStore>,
     },
     MultiTenant { 
         mt_engine: Arc<MultiTenantEngine>, 
         read_only: bool,
-        job_queue: Arc<JobQueue>
+        job_queue: Arc<JobQueue>,
+        traces: Arc<GroundingTraceStore>,
     },
 }
 
+impl EngineState {
+    pub(crate) fn traces(&self) -> &Arc<GroundingTraceStore> {
+        match self {
+            EngineState::SingleTenant { traces, .. } => traces,
+            EngineState::MultiTenant { traces, .. } => traces,
+        }
+    }
+
+    pub(crate) fn read_only(&self) -> bool {
+        match self {
+            EngineState::SingleTenant { read_only, .. } => *read_only,
+            EngineState::MultiTenant { read_only, .. } => *read_only,
+        }
+    }
+
+    pub(crate) fn job_queue(&self) -> &Arc<JobQueue> {
+        match self {
+            EngineState::SingleTenant { job_queue, .. } => job_queue,
+            EngineState::MultiTenant { job_queue, .. } => job_queue,
+        }
+    }
+
+    /// Every loaded project with its id ("default" in single-tenant mode).
+    fn loaded_projects(&self) -> Vec<(String, Arc<ProjectContext>)> {
+        match self {
+            EngineState::SingleTenant { project, .. } => vec![("default".to_string(), project.clone())],
+            EngineState::MultiTenant { mt_engine, .. } => mt_engine
+                .list_projects()
+                .into_iter()
+                .filter_map(|stats| {
+                    let ctx = mt_engine.get_project(&stats.project_id)?;
+                    Some((stats.project_id, ctx))
+                })
+                .collect(),
+        }
+    }
+
+    /// Resolve the project addressed by a request: the single project in single-tenant
+    /// mode, or the X-Project-ID project in multi-tenant mode.
+    fn resolve_project(
+        &self,
+        headers: &HeaderMap,
+    ) -> Result<(String, Arc<ProjectContext>), ApiError> {
+        match self {
+            EngineState::SingleTenant { project, .. } => Ok(("default".to_string(), project.clone())),
+            EngineState::MultiTenant { mt_engine, .. } => {
+                let project_id = extract_project_id(headers)?;
+                let ctx = mt_engine.get_or_create_project(project_id.clone());
+                Ok((project_id, ctx))
+            }
+        }
+    }
+}
+
 /// Routes for single-tenant mode
-pub fn routes(project: std::sync::Arc<ProjectContext>, job_queue: Arc<JobQueue>, auth_config: AuthConfig, read_only: bool) -> Router {
+pub fn routes(project: std::sync::Arc<ProjectContext>, job_queue: Arc<JobQueue>, traces: Arc<GroundingTraceStore>, auth_config: AuthConfig, read_only: bool) -> Router {
     let mut router = Router::new()
         .route("/", get(root))
+        .route("/healthz", get(healthz))
+        .route("/livez", get(livez))
+        .route("/readyz", get(readyz))
+        .route("/admin/config", get(get_admin_config))
+        .route("/admin/memory-usage", with_budget(get(get_memory_usage), RouteBudget::ADMIN))
+        .route("/admin/replica", with_budget(get(get_replica), RouteBudget::ADMIN))
+        .route("/admin/webhooks", post(create_webhook).get(list_webhooks))
+        .route("/admin/webhooks/:id", delete(delete_webhook))
+        .route("/admin/webhooks/:id/deliveries", get(get_webhook_deliveries))
+        .route("/admin/shared/aliases", get(list_shared_aliases).post(add_shared_alias))
+        .route("/admin/shared/aliases/:id", delete(delete_shared_alias))
+        .route("/admin/shared/lexicon", get(list_shared_terms).post(put_shared_term))
+        .route("/admin/shared/lexicon/:cue", delete(delete_shared_term))
+        .route("/admin/forget", with_budget(post(forget), RouteBudget::ADMIN))
+        .route("/admin/lexicon/rebuild", post(rebuild_lexicon).get(get_lexicon_rebuild))
+        .route("/admin/jobs/reproposal", post(start_reproposal).get(get_reproposal))
+        .route("/admin/verify", with_budget(post(verify_indexes), RouteBudget::ADMIN))
+        .route("/admin/alias-candidates", with_budget(get(get_alias_candidates), RouteBudget::ADMIN))
+        .route("/admin/eval/sets", get(list_eval_sets))
+        .route("/admin/eval/sets/:name", get(get_eval_set).put(put_eval_set).delete(delete_eval_set))
+        .route("/admin/eval/run", with_budget(post(run_evals), RouteBudget::ADMIN))
+        .route("/admin/eval/runs", get(list_eval_runs))
         .route("/memories", post(add_memory))
-        .route("/recall", post(recall))
+        .route("/recall", with_budget(post(recall), RouteBudget::QUERY))
+        .route("/recall/batch", with_budget(post(recall_batch), RouteBudget::QUERY))
         .route("/memories/:id/reinforce", patch(reinforce_memory))
-        .route("/memories/:id", get(get_memory))
+        .route("/memories/:id/cues", post(attach_memory_cues))
+        .route("/memories/:id/lock", post(lock_memory).delete(unlock_memory))
+        .route("/memories/:id/provenance", get(get_memory_provenance))
+        .route("/memories/:id/full-content", get(get_memory_full_content))
+        .route("/agent/files", with_budget(get(list_agent_files), RouteBudget::ADMIN))
+        .route("/agent/files/memories", with_budget(get(get_agent_file_memories), RouteBudget::ADMIN))
+        .route("/agent/reingest", post(reingest_agent_files))
+        .route("/agent/errors", with_budget(get(list_agent_errors), RouteBudget::ADMIN))
+        .route("/memories/:id", get(get_memory).put(update_memory).delete(delete_memory))
         .route("/stats", get(get_stats))
-        .route("/recall/grounded", post(recall_grounded))
+        .route("/cues", get(list_cues))
+        .route("/recall/grounded", with_budget(post(recall_grounded), RouteBudget::QUERY))
+        .route("/grounding/traces", get(list_grounding_traces))
+        .route("/grounding/traces/:id", get(get_grounding_trace))
+        .route("/grounding/traces/:id/reinforce", post(reinforce_trace))
+        .route("/grounding/check", with_budget(post(check_grounded_answer), RouteBudget::QUERY))
+        .route("/recall/feedback", post(submit_recall_feedback).get(get_recall_feedback))
+        .route("/recall/defaults", get(get_recall_defaults).put(update_recall_defaults))
+        .route("/content-limit", get(get_content_limit).put(update_content_limit))
         .route("/aliases", post(add_alias).get(get_aliases))
         .route("/aliases/merge", post(merge_aliases))
+        .route("/aliases/stats", get(get_alias_stats))
+        .route("/aliases/config", get(get_alias_config).put(update_alias_config))
+        .route("/taxonomy", get(get_taxonomy).put(update_taxonomy))
+        .route("/normalization", get(get_normalization).put(update_normalization))
+        .route("/normalization/test", post(test_normalization))
+        .route("/tokenizer", get(get_tokenizer).put(update_tokenizer))
+        .route("/ranking", get(get_ranking).put(update_ranking))
+        .route("/taxonomy/deprecations", post(deprecate_taxonomy_key))
+        .route("/taxonomy/rejections", get(get_taxonomy_rejections))
+        .route("/taxonomy/rejections/promote", post(promote_taxonomy_rejection))
+        .route("/collections", get(list_collections).post(create_collection))
+        .route("/collections/:name", get(get_collection).put(update_collection).delete(delete_collection))
+        .route("/collections/:name/memories", post(add_collection_memories))
+        .route("/export/subgraph", with_budget(get(export_subgraph), RouteBudget::ADMIN))
+        .route("/stream", get(stream_changes))
         .with_state(EngineState::SingleTenant { 
             project,
             read_only,
-            job_queue 
+            job_queue,
+            traces,
         });
     
-    // Add auth middleware if enabled
-    if auth_config.is_enabled() {
-        router = router.layer(middleware::from_fn_with_state(auth_config, crate::auth::auth_middleware));
+    // Auth middleware is always installed: it is a no-op until a key exists,
+    // and keys can be created at runtime via /admin/keys.
+    router = router
+        .merge(key_routes(auth_config.key_store()))
+        .layer(DefaultBodyLimit::max(config::request_limits().max_body_bytes))
+        .layer(middleware::from_fn(limits::body_limit_middleware))
+        .layer(middleware::from_fn_with_state(auth_config, crate::auth::auth_middleware));
+    if let Some(min_bytes) = config::compression_min_bytes() {
+        router = router.layer(compression_layer(min_bytes));
     }
     
     router
 }
 
 /// Routes for multi-tenant mode
-pub fn routes_with_mt_engine(mt_engine: Arc<MultiTenantEngine>, job_queue: Arc<JobQueue>, auth_config: AuthConfig, read_only: bool) -> Router {
+pub fn routes_with_mt_engine(mt_engine: Arc<MultiTenantEngine>, job_queue: Arc<JobQueue>, traces: Arc<GroundingTraceStore>, auth_config: AuthConfig, read_only: bool) -> Router {
     let mut router = Router::new()
         .route("/", get(root))
+        .route("/healthz", get(healthz))
+        .route("/livez", get(livez))
+        .route("/readyz", get(readyz))
+        .route("/admin/config", get(get_admin_config))
+        .route("/admin/memory-usage", with_budget(get(get_memory_usage), RouteBudget::ADMIN))
+        .route("/admin/replica", with_budget(get(get_replica), RouteBudget::ADMIN))
+        .route("/admin/webhooks", post(create_webhook).get(list_webhooks))
+        .route("/admin/webhooks/:id", delete(delete_webhook))
+        .route("/admin/webhooks/:id/deliveries", get(get_webhook_deliveries))
+        .route("/admin/shared/aliases", get(list_shared_aliases).post(add_shared_alias))
+        .route("/admin/shared/aliases/:id", delete(delete_shared_alias))
+        .route("/admin/shared/lexicon", get(list_shared_terms).post(put_shared_term))
+        .route("/admin/shared/lexicon/:cue", delete(delete_shared_term))
+        .route("/admin/forget", with_budget(post(forget), RouteBudget::ADMIN))
+        .route("/admin/lexicon/rebuild", post(rebuild_lexicon).get(get_lexicon_rebuild))
+        .route("/admin/jobs/reproposal", post(start_reproposal).get(get_reproposal))
+        .route("/admin/verify", with_budget(post(verify_indexes), RouteBudget::ADMIN))
+        .route("/admin/alias-candidates", with_budget(get(get_alias_candidates), RouteBudget::ADMIN))
+        .route("/admin/eval/sets", get(list_eval_sets))
+        .route("/admin/eval/sets/:name", get(get_eval_set).put(put_eval_set).delete(delete_eval_set))
+        .route("/admin/eval/run", with_budget(post(run_evals), RouteBudget::ADMIN))
+        .route("/admin/eval/runs", get(list_eval_runs))
         .route("/memories", post(add_memory_mt))
-        .route("/recall", post(recall_mt))
+        .route("/recall", with_budget(post(recall_mt), RouteBudget::QUERY))
+        .route("/recall/batch", with_budget(post(recall_batch), RouteBudget::QUERY))
         .route("/memories/:id/reinforce", patch(reinforce_memory_mt))
-        .route("/memories/:id", get(get_memory_mt))
+        .route("/memories/:id/cues", post(attach_memory_cues))
+        .route("/memories/:id/lock", post(lock_memory).delete(unlock_memory))
+        .route("/memories/:id/provenance", get(get_memory_provenance))
+        .route("/memories/:id/full-content", get(get_memory_full_content))
+        .route("/agent/files", with_budget(get(list_agent_files), RouteBudget::ADMIN))
+        .route("/agent/files/memories", with_budget(get(get_agent_file_memories), RouteBudget::ADMIN))
+        .route("/agent/reingest", post(reingest_agent_files))
+        .route("/agent/errors", with_budget(get(list_agent_errors), RouteBudget::ADMIN))
+        .route("/memories/:id", get(get_memory_mt).put(update_memory).delete(delete_memory))
         .route("/stats", get(get_stats_mt))
+        .route("/cues", get(list_cues))
         .route("/projects", get(list_projects))
-        .route("/recall/grounded", post(recall_grounded_mt))
+        .route("/recall/grounded", with_budget(post(recall_grounded_mt), RouteBudget::QUERY))
+        .route("/grounding/traces", get(list_grounding_traces))
+        .route("/grounding/traces/:id", get(get_grounding_trace))
+        .route("/grounding/traces/:id/reinforce", post(reinforce_trace))
+        .route("/grounding/check", with_budget(post(check_grounded_answer), RouteBudget::QUERY))
+        .route("/recall/feedback", post(submit_recall_feedback).get(get_recall_feedback))
+        .route("/recall/defaults", get(get_recall_defaults).put(update_recall_defaults))
+        .route("/content-limit", get(get_content_limit).put(update_content_limit))
         .route("/projects/:id", delete(delete_project))
         .route("/aliases", post(add_alias_mt).get(get_aliases_mt))
         .route("/aliases/merge", post(merge_aliases_mt))
+        .route("/aliases/stats", get(get_alias_stats))
+        .route("/aliases/config", get(get_alias_config).put(update_alias_config))
+        .route("/taxonomy", get(get_taxonomy).put(update_taxonomy))
+        .route("/normalization", get(get_normalization).put(update_normalization))
+        .route("/normalization/test", post(test_normalization))
+        .route("/tokenizer", get(get_tokenizer).put(update_tokenizer))
+        .route("/ranking", get(get_ranking).put(update_ranking))
+        .route("/taxonomy/deprecations", post(deprecate_taxonomy_key))
+        .route("/taxonomy/rejections", get(get_taxonomy_rejections))
+        .route("/taxonomy/rejections/promote", post(promote_taxonomy_rejection))
+        .route("/collections", get(list_collections).post(create_collection))
+        .route("/collections/:name", get(get_collection).put(update_collection).delete(delete_collection))
+        .route("/collections/:name/memories", post(add_collection_memories))
+        .route("/export/subgraph", with_budget(get(export_subgraph), RouteBudget::ADMIN))
+        .route("/stream", get(stream_changes))
         .with_state(EngineState::MultiTenant { 
             mt_engine,
             read_only,
-            job_queue 
+            job_queue,
+            traces,
         });
     
-    // Add auth middleware if enabled
-    if auth_config.is_enabled() {
-        router = router.layer(middleware::from_fn_with_state(auth_config, crate::auth::auth_middleware));
+    // Auth middleware is always installed: it is a no-op until a key exists,
+    // and keys can be created at runtime via /admin/keys.
+    router = router
+        .merge(key_routes(auth_config.key_store()))
+        .layer(DefaultBodyLimit::max(config::request_limits().max_body_bytes))
+        .layer(middleware::from_fn(limits::body_limit_middleware))
+        .layer(middleware::from_fn_with_state(auth_config, crate::auth::auth_middleware));
+    if let Some(min_bytes) = config::compression_min_bytes() {
+        router = router.layer(compression_layer(min_bytes));
+    }
+    
+    router
+}
+
+/// gzip/brotli for responses over `min_bytes`; the change stream and gRPC
+/// pass through uncompressed so events are not held back in a buffer.
+fn compression_layer(min_bytes: u16) -> CompressionLayer<impl Predicate> {
+    let predicate = SizeAbove::new(min_bytes)
+        .and(NotForContentType::GRPC)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::SSE);
+    CompressionLayer::new().compress_when(predicate)
+}
+
+/// API key management routes (admin scope, enforced by the auth middleware)
+fn key_routes(store: Arc<KeyStore>) -> Router {
+    Router::new()
+        .route("/admin/keys", post(create_api_key).get(list_api_keys))
+        .route("/admin/keys/:id", delete(revoke_api_key))
+        .with_state(store)
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateApiKeyRequest {
+    #[serde(default)]
+    label: Option<String>,
+    #[serde(default = "default_key_scope")]
+    scope: Scope,
+}
+
+fn default_key_scope() -> Scope {
+    Scope::Write
+}
+
+async fn create_api_key(
+    State(store): State<Arc<KeyStore>>,
+    Json(req): Json<CreateApiKeyRequest>,
+) -> ApiResult {
+    let (info, key) = store.create(req.label, req.scope);
+    tracing::info!("POST /admin/keys id={} scope={}", info.id, info.scope.as_str());
+
+    let mut body = serde_json::json!(info);
+    body["key"] = serde_json::json!(key);
+    Ok((StatusCode::CREATED, Json(body)))
+}
+
+async fn list_api_keys(
+    State(store): State<Arc<KeyStore>>,
+) -> ApiResult {
+    Ok((StatusCode::OK, Json(serde_json::json!({"keys": store.list()}))))
+}
+
+async fn revoke_api_key(
+    State(store): State<Arc<KeyStore>>,
+    Path(id): Path<String>,
+) -> ApiResult {
+    if store.revoke(&id) {
+        tracing::info!("DELETE /admin/keys/{}", id);
+        Ok((StatusCode::OK, Json(serde_json::json!({"status": "revoked", "id": id}))))
+    } else {
+        Err(ApiError::NotFound("API key not found".to_string()))
+    }
+}
+
+async fn root() -> impl IntoResponse {
+    Json(serde_json::json!({
+        "name": "CueMap Rust Engine",
+        "version": "0.2.1",
+        "description": "High-performance Temporal-Associative Memory Store"
+    }))
+}
+
+// Health Probes (shared by both tenancy modes)
+
+/// Process is up and serving HTTP.
+async fn healthz() -> (StatusCode, Json<serde_json::Value>) {
+    (StatusCode::OK, Json(serde_json::json!({
+        "status": "ok",
+        "version": env!("CARGO_PKG_VERSION")
+    })))
+}
+
+/// Fails only when the process cannot recover without a restart
+/// (the background job worker has died).
+async fn livez(State(state): State<EngineState>) -> (StatusCode, Json<serde_json::Value>) {
+    if state.job_queue().is_running() {
+        (StatusCode::OK, Json(serde_json::json!({"status": "alive"})))
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "status": "dead",
+            "error": "Job queue worker stopped"
+        })))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ReadyzQuery {
+    /// Also require the configured LLM provider to be reachable
+    #[serde(default)]
+    llm: bool,
+}
+
+/// Last `?llm=true` check: when it ran, the provider settings it checked
+/// and its result.
+type LlmReadiness = (std::time::Instant, (String, String, String), serde_json::Value);
+
+static LLM_READINESS: tokio::sync::Mutex<Option<LlmReadiness>> = tokio::sync::Mutex::const_new(None);
+
+/// Whether the configured LLM provider is reachable. Probes skip auth, so
+/// the result is reused for [`config::LLM_READINESS_CACHE_SECS`] rather than
+/// calling the provider on every request; concurrent probes wait for one
+/// check.
+async fn llm_readiness() -> serde_json::Value {
+    let Some(llm) = crate::llm::LlmConfig::from_env() else {
+        return serde_json::json!({"ok": false, "error": "LLM not configured"});
+    };
+    let settings = (llm.provider.clone(), llm.model.clone(), llm.ollama_url.clone());
+    let mut last = LLM_READINESS.lock().await;
+    if let Some((checked_at, checked, result)) = last.as_ref() {
+        if *checked == settings && checked_at.elapsed() < std::time::Duration::from_secs(config::LLM_READINESS_CACHE_SECS) {
+            return result.clone();
+        }
+    }
+    let result = match crate::llm::check_available(&llm).await {
+        Ok(()) => serde_json::json!({"ok": true, "provider": llm.provider}),
+        Err(e) => serde_json::json!({"ok": false, "provider": llm.provider, "error": e}),
+    };
+    *last = Some((std::time::Instant::now(), settings, result.clone()));
+    result
+}
+
+/// Ready to take traffic: snapshots loaded, job queue running and,
+/// with `?llm=true`, the configured LLM provider reachable.
+async fn readyz(
+    State(state): State<EngineState>,
+    follower: Option<Extension<Arc<crate::replication::FollowerStatus>>>,
+    axum::extract::Query(query): axum::extract::Query<ReadyzQuery>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    // Routes are only served once startup loading has finished
+    let snapshots = match &state {
+        EngineState::SingleTenant { project, .. } => serde_json::json!({
+            "ok": true,
+            "memories": project.main.get_memories().len()
+        }),
+        EngineState::MultiTenant { mt_engine, .. } => serde_json::json!({
+            "ok": true,
+            "projects": mt_engine.project_count()
+        }),
+    };
+
+    let job_queue_ok = state.job_queue().is_running();
+    let mut ready = job_queue_ok;
+    let mut checks = serde_json::json!({
+        "snapshots": snapshots,
+        "job_queue": {"ok": job_queue_ok}
+    });
+
+    // Followers serve nothing useful until the primary's state has arrived
+    if let Some(Extension(follower)) = follower {
+        ready &= follower.is_synced();
+        checks["replication"] = follower.to_json();
+    }
+
+    if query.llm {
+        let llm = llm_readiness().await;
+        ready &= llm["ok"].as_bool().unwrap_or(false);
+        checks["llm"] = llm;
+    }
+
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(serde_json::json!({
+        "status": if ready { "ready" } else { "not_ready" },
+        "checks": checks
+    })))
+}
+
+/// Effective runtime configuration: startup settings, LLM settings (secrets
+/// masked), compiled-in tuning constants, and per-project config overrides.
+async fn get_admin_config(
+    State(state): State<EngineState>,
+    settings: Option<Extension<Arc<crate::config::RuntimeSettings>>>,
+) -> ApiResult {
+    let project_overrides: serde_json::Map<String, serde_json::Value> = state
+        .loaded_projects()
+        .into_iter()
+        .map(|(id, ctx)| (id, ctx.config().overrides()))
+        .collect();
+
+    let llm = match crate::llm::LlmConfig::from_env() {
+        Some(config) => config.masked(),
+        None => serde_json::json!({"enabled": false}),
+    };
+
+    Ok((StatusCode::OK, Json(serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "read_only": state.read_only(),
+        "server": settings.map(|Extension(s)| s.0.clone()).unwrap_or(serde_json::Value::Null),
+        "llm": llm,
+        "tuning": crate::config::tuning_constants(),
+        "project_overrides": project_overrides,
+    }))))
+}
+
+/// Estimated memory footprint per project, for capacity planning.
+async fn get_memory_usage(
+    State(state): State<EngineState>,
+) -> ApiResult {
+    // Walks every memory and cue; off the async workers, so the route's timeout can fire
+    let (total_bytes, projects) = tokio::task::spawn_blocking(move || {
+        let mut total_bytes = 0;
+        let projects: serde_json::Map<String, serde_json::Value> = state
+            .loaded_projects()
+            .into_iter()
+            .map(|(id, ctx)| {
+                let usage = ctx.memory_usage();
+                total_bytes += usage.total_bytes;
+                (id, serde_json::to_value(usage).unwrap_or_default())
+            })
+            .collect();
+        (total_bytes, projects)
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({
+        "estimated": true,
+        "total_bytes": total_bytes,
+        "projects": projects,
+    }))))
+}
+
+/// Project state for followers (`--follow`). Answers `304` when the caller's
+/// `If-None-Match` still matches.
+async fn get_replica(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+) -> Result<axum::response::Response, ApiError> {
+    let (_, ctx) = state.resolve_project(&headers)?;
+    let etag = crate::replication::replica_etag(&ctx);
+    let etag_header = [(axum::http::header::ETAG, etag.clone())];
+    if headers.get(axum::http::header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()) == Some(etag.as_str()) {
+        return Ok((StatusCode::NOT_MODIFIED, etag_header).into_response());
+    }
+
+    let snapshot = tokio::task::spawn_blocking(move || crate::replication::ReplicaSnapshot::capture(&ctx))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok((etag_header, Json(snapshot)).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateWebhookRequest {
+    url: String,
+    /// Event types to deliver; all of them when omitted
+    #[serde(default)]
+    events: Vec<String>,
+    /// Signing secret; generated when omitted
+    #[serde(default)]
+    secret: Option<String>,
+}
+
+type Webhooks = Option<Extension<Arc<crate::webhooks::WebhookHub>>>;
+
+fn webhooks_enabled(webhooks: Webhooks) -> Result<Arc<crate::webhooks::WebhookHub>, ApiError> {
+    webhooks
+        .map(|Extension(hub)| hub)
+        .ok_or(ApiError::NotEnabled("Webhooks"))
+}
+
+/// Subscribe a URL to the project's memory lifecycle events.
+async fn create_webhook(
+    State(state): State<EngineState>,
+    webhooks: Webhooks,
+    headers: HeaderMap,
+    Json(req): Json<CreateWebhookRequest>,
+) -> ApiResult {
+    let hub = webhooks_enabled(webhooks)?;
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+    let (info, secret) = hub
+        .create(&project_id, &ctx, req.url, req.events, req.secret)
+        .map_err(ApiError::BadRequest)?;
+    tracing::info!("POST /admin/webhooks project={} id={}", project_id, info.id);
+
+    let mut body = serde_json::json!(info);
+    body["secret"] = serde_json::json!(secret);
+    Ok((StatusCode::CREATED, Json(body)))
+}
+
+async fn list_webhooks(
+    State(state): State<EngineState>,
+    webhooks: Webhooks,
+    headers: HeaderMap,
+) -> ApiResult {
+    let hub = webhooks_enabled(webhooks)?;
+    let (project_id, _) = state.resolve_project(&headers)?;
+    Ok((StatusCode::OK, Json(serde_json::json!({"webhooks": hub.list(&project_id)}))))
+}
+
+async fn delete_webhook(
+    State(state): State<EngineState>,
+    webhooks: Webhooks,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> ApiResult {
+    let hub = webhooks_enabled(webhooks)?;
+    let (project_id, _) = state.resolve_project(&headers)?;
+    if hub.delete(&project_id, &id) {
+        tracing::info!("DELETE /admin/webhooks/{} project={}", id, project_id);
+        Ok((StatusCode::OK, Json(serde_json::json!({"status": "deleted", "id": id}))))
+    } else {
+        Err(ApiError::NotFound("Webhook not found".to_string()))
+    }
+}
+
+/// Recent deliveries of a webhook, newest first.
+async fn get_webhook_deliveries(
+    State(state): State<EngineState>,
+    webhooks: Webhooks,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> ApiResult {
+    let hub = webhooks_enabled(webhooks)?;
+    let (project_id, _) = state.resolve_project(&headers)?;
+    match hub.deliveries(&project_id, &id) {
+        Some(deliveries) => Ok((StatusCode::OK, Json(serde_json::json!({"deliveries": deliveries})))),
+        None => Err(ApiError::NotFound("Webhook not found".to_string())),
+    }
+}
+
+/// Delete every memory matching a cue or metadata selector, with its aliases,
+/// lexicon entry and grounding traces, save the project's snapshot, and
+/// return a signed deletion report.
+async fn forget(
+    State(state): State<EngineState>,
+    signer: Option<Extension<Arc<crate::forget::ReportSigner>>>,
+    persistence: Option<Extension<crate::persistence::PersistenceManager>>,
+    headers: HeaderMap,
+    Json(selector): Json<crate::forget::Selector>,
+) -> ApiResult {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+    if selector.is_empty() {
+        return Err(ApiError::BadRequest("A cue or metadata selector is required".to_string()));
+    }
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+    let report = tokio::task::spawn_blocking({
+        let (project_id, traces, state) = (project_id.clone(), state.traces().clone(), state.clone());
+        move || {
+            let mut report = crate::forget::forget(&project_id, &ctx, &traces, &selector);
+            // Rewrite the snapshot now rather than at the next interval, so
+            // a crash can't restore the memories
+            let saved = match &state {
+                EngineState::SingleTenant { .. } => persistence.map(|Extension(pm)| pm.save_state(&ctx.main).map_err(|e| e.to_string())),
+                EngineState::MultiTenant { mt_engine, .. } => Some(mt_engine.save_project(&project_id).map(|_| ())),
+            };
+            match saved {
+                Some(Ok(())) => report.snapshot_purge = "saved".to_string(),
+                Some(Err(e)) => tracing::error!("Failed to save the snapshot after forgetting in project {}: {}", project_id, e),
+                None => {}
+            }
+            report
+        }
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    tracing::info!("POST /admin/forget project={} report={} memories={}", project_id, report.id, report.memories.len());
+
+    let signature = signer.map(|Extension(signer)| signer.sign(&report));
+    Ok((StatusCode::OK, Json(serde_json::json!({"report": report, "signature": signature}))))
+}
+
+/// Cross-check the project's cue indexes against its memories, and with
+/// `?rebuild=true` rebuild the inconsistent ones.
+async fn verify_indexes(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    axum::extract::Query(query): axum::extract::Query<VerifyQuery>,
+) -> ApiResult {
+    if query.rebuild && state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+    let report = tokio::task::spawn_blocking(move || ctx.verify_indexes(query.rebuild))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    tracing::info!(
+        "POST /admin/verify project={} consistent={} rebuilt={}",
+        project_id,
+        report.is_consistent(),
+        report.rebuilt
+    );
+    Ok((StatusCode::OK, Json(serde_json::json!({
+        "project_id": project_id,
+        "consistent": report.is_consistent(),
+        "report": report,
+    }))))
+}
+
+/// Run the candidate selection of `ProposeAliases` without proposing
+/// anything, to see what a set of thresholds would select.
+async fn get_alias_candidates(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    axum::extract::Query(query): axum::extract::Query<AliasCandidatesQuery>,
+) -> ApiResult {
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+    let defaults = ctx.alias_config();
+    let config = AliasConfig {
+        min_cue_memories: query.min_cue_memories.unwrap_or(defaults.min_cue_memories),
+        max_cue_memories: query.max_cue_memories.unwrap_or(defaults.max_cue_memories),
+        max_candidates: query.max_candidates.unwrap_or(defaults.max_candidates),
+        size_similarity_max_ratio: query.size_similarity_max_ratio.unwrap_or(defaults.size_similarity_max_ratio),
+        overlap_threshold: query.overlap_threshold.unwrap_or(defaults.overlap_threshold),
+        sample_size: query.sample_size.unwrap_or(defaults.sample_size),
+    };
+    config.validate().map_err(ApiError::BadRequest)?;
+
+    let candidates = tokio::task::spawn_blocking({
+        let config = config.clone();
+        move || alias_proposal::select(&ctx.main, &config)
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let total = candidates.pairs.len();
+    let proposed = candidates.pairs.iter().filter(|pair| pair.proposed).count();
+    let pairs: Vec<_> = candidates
+        .pairs
+        .into_iter()
+        .take(query.limit.unwrap_or(ALIAS_CANDIDATES_DEFAULT_LIMIT))
+        .collect();
+    tracing::info!(
+        "GET /admin/alias-candidates project={} cues={} pairs={} proposed={}",
+        project_id,
+        candidates.cues_compared,
+        total,
+        proposed
+    );
+
+    Ok((StatusCode::OK, Json(serde_json::json!({
+        "project_id": project_id,
+        "config": config,
+        "cues_compared": candidates.cues_compared,
+        "total_pairs": total,
+        "proposed": proposed,
+        "pairs": pairs,
+    }))))
+}
+
+/// Queue a `RebuildLexicon` job for the project. Answers `409` while a
+/// rebuild is already queued or running.
+async fn rebuild_lexicon(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+) -> ApiResult {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+    if !ctx.queue_lexicon_rebuild() {
+        return Err(ApiError::Conflict("A lexicon rebuild is already in progress".to_string()));
+    }
+    state.job_queue().enqueue(Job::RebuildLexicon { project_id: project_id.clone() }).await;
+    tracing::info!("POST /admin/lexicon/rebuild project={}", project_id);
+    Ok((StatusCode::ACCEPTED, Json(serde_json::json!({"rebuild": ctx.lexicon_rebuild()}))))
+}
+
+/// Progress of the project's latest lexicon rebuild.
+async fn get_lexicon_rebuild(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+) -> ApiResult {
+    let (_, ctx) = state.resolve_project(&headers)?;
+    match ctx.lexicon_rebuild() {
+        Some(rebuild) => Ok((StatusCode::OK, Json(serde_json::json!({"rebuild": rebuild})))),
+        None => Err(ApiError::NotFound("No lexicon rebuild has been queued".to_string())),
+    }
+}
+
+/// Queue a `Repropose` job asking the LLM again for cues for the memories
+/// the query selects. Answers `409` while a reproposal is already queued or
+/// running.
+async fn start_reproposal(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    axum::extract::Query(query): axum::extract::Query<ReproposalQuery>,
+) -> ApiResult {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+    let rate = query.rate.unwrap_or(config::REPROPOSAL_DEFAULT_RATE);
+    if !(rate > 0.0 && rate <= config::REPROPOSAL_MAX_RATE) {
+        return Err(ApiError::BadRequest(format!("rate must be above 0 and at most {}", config::REPROPOSAL_MAX_RATE)));
+    }
+    if query.filter.as_deref().is_some_and(|cue| cue.trim().is_empty()) {
+        return Err(ApiError::BadRequest("filter must not be empty".to_string()));
+    }
+    if crate::llm::LlmConfig::from_env().is_none() {
+        return Err(ApiError::NotEnabled("LLM cue proposals"));
+    }
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+    let filter = ReproposalFilter { cue: query.filter, max_cues: query.max_cues };
+    if !ctx.queue_reproposal(filter.clone(), rate) {
+        return Err(ApiError::Conflict("A reproposal is already in progress".to_string()));
+    }
+    state.job_queue().enqueue(Job::Repropose { project_id: project_id.clone(), filter, rate }).await;
+    tracing::info!("POST /admin/jobs/reproposal project={}", project_id);
+    Ok((StatusCode::ACCEPTED, Json(serde_json::json!({"reproposal": ctx.reproposal()}))))
+}
+
+/// Progress of the project's latest reproposal.
+async fn get_reproposal(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+) -> ApiResult {
+    let (_, ctx) = state.resolve_project(&headers)?;
+    match ctx.reproposal() {
+        Some(reproposal) => Ok((StatusCode::OK, Json(serde_json::json!({"reproposal": reproposal})))),
+        None => Err(ApiError::NotFound("No reproposal has been queued".to_string())),
+    }
+}
+
+type Shared = Option<Extension<Arc<SharedVocabulary>>>;
+
+fn shared_vocabulary_enabled(shared: Shared) -> Result<Arc<SharedVocabulary>, ApiError> {
+    shared
+        .map(|Extension(shared)| shared)
+        .ok_or(ApiError::NotEnabled("Shared vocabulary"))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PutSharedTermRequest {
+    pub cue: String,
+    /// Query text mentioning its tokens resolves to `cue`
+    pub text: String,
+}
+
+/// Aliases every project falls back to after its own.
+async fn list_shared_aliases(shared: Shared) -> ApiResult {
+    let shared = shared_vocabulary_enabled(shared)?;
+    Ok((StatusCode::OK, Json(serde_json::json!({"aliases": shared.aliases()}))))
+}
+
+async fn add_shared_alias(
+    State(state): State<EngineState>,
+    shared: Shared,
+    Json(req): Json<AddAliasRequest>,
+) -> ApiResult {
+    let shared = shared_vocabulary_enabled(shared)?;
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+    let alias = shared.add_alias(&req.from, &req.to, req.weight).map_err(ApiError::BadRequest)?;
+    tracing::info!("POST /admin/shared/aliases id={} {} -> {}", alias.id, alias.from, alias.to);
+    Ok((StatusCode::CREATED, Json(serde_json::json!(alias))))
+}
+
+async fn delete_shared_alias(
+    State(state): State<EngineState>,
+    shared: Shared,
+    Path(id): Path<String>,
+) -> ApiResult {
+    let shared = shared_vocabulary_enabled(shared)?;
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+    if shared.remove_alias(&id) {
+        tracing::info!("DELETE /admin/shared/aliases/{}", id);
+        Ok((StatusCode::OK, Json(serde_json::json!({"status": "deleted", "id": id}))))
+    } else {
+        Err(ApiError::NotFound("Shared alias not found".to_string()))
+    }
+}
+
+/// Lexicon terms every project falls back to after its own lexicon.
+async fn list_shared_terms(shared: Shared) -> ApiResult {
+    let shared = shared_vocabulary_enabled(shared)?;
+    Ok((StatusCode::OK, Json(serde_json::json!({"terms": shared.terms()}))))
+}
+
+async fn put_shared_term(
+    State(state): State<EngineState>,
+    shared: Shared,
+    Json(req): Json<PutSharedTermRequest>,
+) -> ApiResult {
+    let shared = shared_vocabulary_enabled(shared)?;
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+    let term = shared.put_term(&req.cue, &req.text).map_err(ApiError::BadRequest)?;
+    tracing::info!("POST /admin/shared/lexicon cue={}", term.cue);
+    Ok((StatusCode::OK, Json(serde_json::json!(term))))
+}
+
+async fn delete_shared_term(
+    State(state): State<EngineState>,
+    shared: Shared,
+    Path(cue): Path<String>,
+) -> ApiResult {
+    let shared = shared_vocabulary_enabled(shared)?;
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+    if shared.remove_term(&cue) {
+        tracing::info!("DELETE /admin/shared/lexicon/{}", cue);
+        Ok((StatusCode::OK, Json(serde_json::json!({"status": "deleted", "cue": cue}))))
+    } else {
+        Err(ApiError::NotFound("Shared term not found".to_string()))
+    }
+}
+
+type Evals = Option<Extension<Arc<EvalStore>>>;
+
+fn evals_enabled(evals: Evals) -> Result<Arc<EvalStore>, ApiError> {
+    evals
+        .map(|Extension(store)| store)
+        .ok_or(ApiError::NotEnabled("Evals"))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PutEvalSetRequest {
+    pub queries: Vec<GoldenQuery>,
+}
+
+async fn list_eval_sets(
+    State(state): State<EngineState>,
+    evals: Evals,
+    headers: HeaderMap,
+) -> ApiResult {
+    let store = evals_enabled(evals)?;
+    let (project_id, _) = state.resolve_project(&headers)?;
+    Ok((StatusCode::OK, Json(serde_json::json!({"sets": store.sets(&project_id)}))))
+}
+
+async fn get_eval_set(
+    State(state): State<EngineState>,
+    evals: Evals,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> ApiResult {
+    let store = evals_enabled(evals)?;
+    let (project_id, _) = state.resolve_project(&headers)?;
+    match store.get_set(&project_id, &name) {
+        Some(set) => Ok((StatusCode::OK, Json(serde_json::json!(set)))),
+        None => Err(ApiError::NotFound("Eval set not found".to_string())),
+    }
+}
+
+/// Add or replace a golden set of the project.
+async fn put_eval_set(
+    State(state): State<EngineState>,
+    evals: Evals,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Json(req): Json<PutEvalSetRequest>,
+) -> ApiResult {
+    let store = evals_enabled(evals)?;
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+    let (project_id, _) = state.resolve_project(&headers)?;
+    let set = GoldenSet { name, queries: req.queries };
+    let queries = set.queries.len();
+    store
+        .put_set(&project_id, set.clone())
+        .map_err(|problems| ApiError::Invalid { detail: "Invalid eval set".to_string(), problems })?;
+    tracing::info!("PUT /admin/eval/sets project={} set={} queries={}", project_id, set.name, queries);
+    Ok((StatusCode::OK, Json(serde_json::json!(set))))
+}
+
+async fn delete_eval_set(
+    State(state): State<EngineState>,
+    evals: Evals,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> ApiResult {
+    let store = evals_enabled(evals)?;
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+    let (project_id, _) = state.resolve_project(&headers)?;
+    if store.delete_set(&project_id, &name) {
+        Ok((StatusCode::OK, Json(serde_json::json!({"status": "deleted", "set": name}))))
+    } else {
+        Err(ApiError::NotFound("Eval set not found".to_string()))
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RunEvalsRequest {
+    /// Set to run; all sets of the project when omitted
+    #[serde(default)]
+    pub set: Option<String>,
+}
+
+/// Run golden sets against the project and record the scores.
+async fn run_evals(
+    State(state): State<EngineState>,
+    evals: Evals,
+    headers: HeaderMap,
+    req: Option<Json<RunEvalsRequest>>,
+) -> ApiResult {
+    let store = evals_enabled(evals)?;
+    // Runs are recorded in the history
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+    let req = req.map(|Json(req)| req).unwrap_or_default();
+    let sets = match &req.set {
+        Some(name) => match store.get_set(&project_id, name) {
+            Some(set) => vec![set],
+            None => return Err(ApiError::NotFound("Eval set not found".to_string())),
+        },
+        None => store.sets(&project_id),
+    };
+    // Off the async workers, so the route's timeout can fire mid-run
+    let runs: Vec<EvalRun> = tokio::task::spawn_blocking(move || {
+        sets.iter()
+            .map(|set| {
+                let run = golden::run_set(&project_id, &ctx, set);
+                store.record(&run);
+                run
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok((StatusCode::OK, Json(serde_json::json!({"runs": runs}))))
+}
+
+/// Score history of the project's golden sets, oldest first.
+async fn list_eval_runs(
+    State(state): State<EngineState>,
+    evals: Evals,
+    headers: HeaderMap,
+    axum::extract::Query(params): axum::extract::Query<HashMap<String, String>>,
+) -> ApiResult {
+    let store = evals_enabled(evals)?;
+    let (project_id, _) = state.resolve_project(&headers)?;
+    let runs = store.runs(&project_id, params.get("set").map(String::as_str));
+    Ok((StatusCode::OK, Json(serde_json::json!({"runs": runs}))))
+}
+
+/// Apply the project's content limit to content about to be stored: `413`
+/// when oversized content is rejected, else the byte limit to shorten it to.
+fn check_content_limit(ctx: &ProjectContext, content: &str) -> Result<Option<usize>, ApiError> {
+    let content_limit = ctx.content_limit();
+    let Some(max_bytes) = content_limit.exceeded_by(content) else { return Ok(None) };
+    match content_limit.oversized {
+        Oversized::Reject => Err(ApiError::PayloadTooLarge(format!(
+            "content is {} bytes, over the project's limit of {}",
+            content.len(),
+            max_bytes
+        ))),
+        Oversized::Summarize => Ok(Some(max_bytes)),
+    }
+}
+
+/// Cut an oversized memory to `max_bytes` until its summary replaces it.
+fn shorten_oversized(ctx: &ProjectContext, memory_id: &str, content: &str, max_bytes: usize) {
+    ctx.main.shorten_content(memory_id, content_limit::truncate(content, max_bytes).to_string());
+}
+
+async fn add_memory(
+    State(state): State<EngineState>,
+    Json(req): Json<AddMemoryRequest>,
+) -> ApiResult {
+    limits::check_memory(&config::request_limits(), &req.content, &req.cues)?;
+    if let EngineState::SingleTenant { project, read_only, job_queue, .. } = state {
+        // Check if read-only
+        if read_only {
+            return Err(ApiError::ReadOnly);
+        }
+        check_collection(&project, req.collection.as_deref())?;
+        
+        // Run ingest hooks, then normalize, language-tag and validate cues
+        let memory = match project.prepare_memory(req.content, req.cues, req.metadata) {
+            Ok(memory) => memory,
+            Err(reason) => return Err(ApiError::HookRejected(reason)),
+        };
+        let report = memory.report;
+        let oversized = check_content_limit(&project, &memory.content)?;
+        
+        let memory_id = project.main.add_memory_to(memory.content.clone(), report.accepted, memory.metadata, req.disable_temporal_chunking, req.collection);
+        project.rejections.record(&memory_id, &report.rejected);
+        
+        // Enqueue background jobs
+        if let Some(max_bytes) = oversized {
+            shorten_oversized(&project, &memory_id, &memory.content, max_bytes);
+            job_queue.enqueue(Job::SummarizeOversized {
+                project_id: "default".to_string(),
+                memory_id: memory_id.clone(),
+                max_bytes,
+            }).await;
+        }
+        job_queue.enqueue(Job::TrainLexiconFromMemory {
+            project_id: "default".to_string(), 
+            memory_id: memory_id.clone()
+        }).await;
+        
+        job_queue.enqueue(Job::LlmProposeCues {
+            project_id: "default".to_string(),
+            memory_id: memory_id.clone(),
+            content: memory.content,
+        }).await;
+        
+        Ok((
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "id": memory_id,
+                "status": "stored",
+                "shortened": oversized.is_some(),
+                "rejected_cues": report.rejected,
+                "rewritten_cues": report.rewritten,
+                "flagged_cues": report.flagged
+            })),
+        ))
+    } else {
+        Err(ApiError::WrongMode("single-tenant"))
+    }
+}
+
+async fn recall(
+    State(state): State<EngineState>,
+    caller: Option<Extension<Scope>>,
+    Json(mut req): Json<RecallRequest>,
+) -> ApiResult {
+    use std::time::Instant;
+
+    limits::check_recall_limit(&config::request_limits(), req.limit())?;
+    let fields = req.result_fields()?;
+    
+    if let EngineState::SingleTenant { project, .. } = state {
+        req.apply_defaults(&project.recall_defaults());
+        req.check_auto_reinforce(caller.as_ref())?;
+        let start = Instant::now();
+        let (recall_limit, rerank_query) = (req.recall_limit(), req.rerank_query());
+        
+        // Normalized request cues, then cues resolved from text at their confidence
+        let (resolution_text, spelling_corrections) = req.resolution_text(&project);
+        let query_cues = project.query_cues(&req.cues, resolution_text.as_deref());
+        let cues_to_process: Vec<String> = query_cues.iter().map(|(cue, _)| cue.clone()).collect();
+        
+        // Expand aliases
+        let (expanded_cues, alias_paths) = expand_with_paths(&project, query_cues, req.alias_depth());
+        let cache_key = req.cache_key(&project, &expanded_cues, &cues_to_process, &spelling_corrections);
+        let generation = project.main.recall_generation();
+        if let Some(response) = cached_recall(&project, cache_key.as_deref(), generation, start) {
+            return Ok((StatusCode::OK, Json(response)));
+        }
+        // Off the async workers, so the route's timeout can fire mid-recall
+        let mut results = tokio::task::spawn_blocking({
+            let (project, expanded_cues, req) = (project.clone(), expanded_cues.clone(), req.clone());
+            move || {
+                project.main.recall_weighted_in(
+                    expanded_cues,
+                    recall_limit,
+                    req.auto_reinforce(),
+                    req.min_intersection,
+                    req.explain(),
+                    req.disable_pattern_completion,
+                    req.disable_salience_bias,
+                    req.disable_systems_consolidation,
+                    req.max_candidates,
+                    req.collections.as_ref(),
+                    req.within_ids.as_deref(),
+                )
+            }
+        })
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+        let reranked = match &rerank_query {
+            Some(query) => Some(rerank_results(&project.main, query, req.limit(), &mut results).await),
+            None => None,
+        };
+        project.after_recall(&expanded_cues, req.query_text.as_deref(), &mut results);
+        
+        let elapsed = start.elapsed();
+        let engine_latency_ms = elapsed.as_secs_f64() * 1000.0;
+        
+        let mut response = serde_json::json!({ 
+            "results": results_json(&project.main, &results, fields.as_ref()),
+            "engine_latency": engine_latency_ms
+        });
+        if let Some(reranked) = reranked {
+            response["reranked"] = serde_json::json!(reranked);
+        }
+        
+        // Add query explanation if requested
+        if req.explain() {
+            response["explain"] = serde_json::json!({
+                "normalized_query": cues_to_process,
+                "expanded_cues": expanded_cues,
+                "alias_paths": alias_paths,
+                "spelling_corrections": spelling_corrections,
+                "display_names": project.display_names(expanded_cues.iter().map(|(cue, _)| cue.as_str()))
+            });
+        }
+        if let Some(key) = cache_key {
+            project.recall_cache.insert(key, response.clone(), generation);
+        }
+        
+        Ok((StatusCode::OK, Json(response)))
+    } else {
+        Err(ApiError::WrongMode("single-tenant"))
+    }
+}
+
+/// Run up to `RECALL_BATCH_MAX_QUERIES` recalls against one project in one
+/// request. Requests with the same cues and text are normalized and expanded
+/// once, the recalls run in parallel and the responses keep request order,
+/// each with its request `index` and its own latency.
+async fn recall_batch(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    caller: Option<Extension<Scope>>,
+    Json(mut reqs): Json<Vec<RecallRequest>>,
+) -> ApiResult {
+    use std::time::Instant;
+
+    if reqs.len() > RECALL_BATCH_MAX_QUERIES {
+        return Err(ApiError::BadRequest(format!(
+            "batch of {} recalls is over the maximum of {}",
+            reqs.len(),
+            RECALL_BATCH_MAX_QUERIES
+        )));
+    }
+    let request_limits = config::request_limits();
+    let mut fields = Vec::with_capacity(reqs.len());
+    for (i, req) in reqs.iter().enumerate() {
+        limits::check_recall_limit(&request_limits, req.limit())?;
+        fields.push(req.result_fields()?);
+        if req.projects.is_some() {
+            return Err(ApiError::BadRequest(format!("recall {}: cross-project recalls can't be batched", i + 1)));
+        }
+    }
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+    let defaults = ctx.recall_defaults();
+    for req in &mut reqs {
+        req.apply_defaults(&defaults);
+        req.check_auto_reinforce(caller.as_ref())?;
+    }
+    let start = Instant::now();
+
+    let mut distinct: HashMap<QueryKey, usize> = HashMap::new();
+    let mut prepared: Vec<PreparedQuery> = Vec::new();
+    let query_of: Vec<usize> = reqs
+        .iter()
+        .map(|req| {
+            let key = QueryKey {
+                cues: &req.cues,
+                query_text: req.query_text.as_deref(),
+                resolve_text: req.resolve_text,
+                spell_correct: req.spell_correct,
+                alias_depth: req.alias_depth(),
+            };
+            *distinct.entry(key).or_insert_with(|| {
+                prepared.push(PreparedQuery::new(&ctx, req));
+                prepared.len() - 1
+            })
+        })
+        .collect();
+
+    // rayon threads don't inherit the current span, so pass it explicitly
+    let parent_span = tracing::Span::current();
+    // Wait for rayon off the async workers, so the route's timeout can fire
+    let (reqs, query_of, prepared, recalled) = tokio::task::spawn_blocking({
+        let ctx = ctx.clone();
+        move || {
+            let recalled: Vec<(Vec<RecallResult>, std::time::Duration)> = reqs
+                .par_iter()
+                .zip(query_of.par_iter())
+                .map(|(req, &query)| {
+                    let _span = tracing::info_span!(parent: &parent_span, "batch_recall").entered();
+                    let recall_start = Instant::now();
+                    let results = ctx.main.recall_weighted_in(
+                        prepared[query].expanded_cues.clone(),
+                        req.recall_limit(),
+                        req.auto_reinforce(),
+                        req.min_intersection,
+                        req.explain(),
+                        req.disable_pattern_completion,
+                        req.disable_salience_bias,
+                        req.disable_systems_consolidation,
+                        req.max_candidates,
+                        req.collections.as_ref(),
+                        req.within_ids.as_deref(),
+                    );
+                    (results, recall_start.elapsed())
+                })
+                .collect();
+            (reqs, query_of, prepared, recalled)
+        }
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    let mut responses = Vec::with_capacity(reqs.len());
+    for (index, (((req, &query), (mut results, recall_elapsed)), fields)) in reqs.iter().zip(&query_of).zip(recalled).zip(&fields).enumerate() {
+        let query = &prepared[query];
+        let finish_start = Instant::now();
+        let reranked = match req.rerank_query() {
+            Some(text) => Some(rerank_results(&ctx.main, &text, req.limit(), &mut results).await),
+            None => None,
+        };
+        ctx.after_recall(&query.expanded_cues, req.query_text.as_deref(), &mut results);
+
+        let mut response = serde_json::json!({ "index": index, "results": results_json(&ctx.main, &results, fields.as_ref()) });
+        if let Some(reranked) = reranked {
+            response["reranked"] = serde_json::json!(reranked);
+        }
+        if req.explain() {
+            response["explain"] = serde_json::json!({
+                "normalized_query": query.query_cues,
+                "expanded_cues": query.expanded_cues,
+                "alias_paths": query.alias_paths,
+                "spelling_corrections": query.spelling_corrections,
+                "display_names": ctx.display_names(query.expanded_cues.iter().map(|(cue, _)| cue.as_str()))
+            });
+        }
+        // Its own scoring, reranking and hooks; shared normalization is not counted
+        let elapsed = recall_elapsed + finish_start.elapsed();
+        response["engine_latency"] = serde_json::json!(elapsed.as_secs_f64() * 1000.0);
+        responses.push(response);
+    }
+
+    let engine_latency_ms = start.elapsed().as_secs_f64() * 1000.0;
+    tracing::info!(
+        "POST /recall/batch project={} recalls={} distinct_queries={} latency={:.2}ms",
+        project_id,
+        reqs.len(),
+        prepared.len(),
+        engine_latency_ms
+    );
+    Ok((StatusCode::OK, Json(serde_json::json!({
+        "responses": responses,
+        "engine_latency": engine_latency_ms
+    }))))
+}
+
+/// Whether an `If-Match` or `If-None-Match` value is `*` or lists `etag`.
+/// `weak` compares `W/` tags too, as `If-None-Match` does.
+fn etag_listed(value: &HeaderValue, etag: &str, weak: bool) -> bool {
+    let Ok(value) = value.to_str() else { return false };
+    value.split(',').map(str::trim).any(|tag| {
+        let tag = if weak { tag.trim_start_matches("W/") } else { tag };
+        tag == "*" || tag == etag
+    })
+}
+
+/// ETag an update must still find under the request's `If-Match`: `None`
+/// without the header, `412` when the memory has changed since.
+fn check_if_match(headers: &HeaderMap, engine: &CueMapEngine, memory_id: &str) -> Result<Option<String>, ApiError> {
+    let Some(value) = headers.get(header::IF_MATCH) else { return Ok(None) };
+    let memory = engine
+        .get_memory(memory_id)
+        .ok_or_else(|| ApiError::NotFound(format!("Memory {} not found", memory_id)))?;
+    let current = memory.etag();
+    if !etag_listed(value, &current, false) {
+        return Err(stale_memory(memory_id, &current));
+    }
+    Ok(Some(current))
+}
+
+fn stale_memory(memory_id: &str, current: &str) -> ApiError {
+    ApiError::PreconditionFailed(format!("Memory {} has changed; its ETag is now {}", memory_id, current))
+}
+
+/// The memory with its `ETag`, or `304` when `If-None-Match` already has it.
+fn memory_response(headers: &HeaderMap, memory: Memory) -> axum::response::Response {
+    let etag = memory.etag();
+    let etag_header = [(header::ETAG, etag.clone())];
+    if headers.get(header::IF_NONE_MATCH).is_some_and(|value| etag_listed(value, &etag, true)) {
+        return (StatusCode::NOT_MODIFIED, etag_header).into_response();
+    }
+    (etag_header, Json(memory)).into_response()
+}
+
+fn reinforced_response(engine: &CueMapEngine, memory_id: String) -> axum::response::Response {
+    let etag = engine.get_memory(&memory_id).map(|memory| memory.etag()).unwrap_or_default();
+    let body = serde_json::json!({
+        "status": "reinforced",
+        "memory_id": memory_id
+    });
+    ([(header::ETAG, etag)], Json(body)).into_response()
+}
+
+/// Attach cues to a memory after the same normalization and taxonomy
+/// validation as ingest. With `If-Match`, only while the memory is unchanged.
+async fn attach_memory_cues(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Path(memory_id): Path<String>,
+    Json(req): Json<AttachCuesRequest>,
+) -> Result<axum::response::Response, ApiError> {
+    limits::check_cues(&config::request_limits(), &req.cues)?;
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+    let expected = check_if_match(&headers, &ctx.main, &memory_id)?;
+
+    let mut normalized_cues = ctx.normalize_cues(&req.cues);
+    normalized_cues.extend(ctx.derived_cues(&normalized_cues));
+    let report = ctx.validate_cues(normalized_cues.clone());
+    let attached = match ctx.main.attach_cues_if_match(&memory_id, report.accepted.clone(), expected.as_deref()) {
+        AttachOutcome::Attached => true,
+        AttachOutcome::Unchanged => false,
+        AttachOutcome::NotFound => return Err(ApiError::NotFound(format!("Memory {} not found", memory_id))),
+        AttachOutcome::Stale(current) => return Err(stale_memory(&memory_id, &current)),
+    };
+    ctx.record_display_names(&req.cues, &normalized_cues, &report.accepted);
+    ctx.rejections.record(&memory_id, &report.rejected);
+    if attached {
+        state.job_queue().enqueue(Job::TrainLexiconFromMemory { project_id, memory_id: memory_id.clone() }).await;
+    }
+
+    let etag = ctx.main.get_memory(&memory_id).map(|memory| memory.etag()).unwrap_or_default();
+    let body = serde_json::json!({
+        "memory_id": memory_id,
+        "status": if attached { "attached" } else { "unchanged" },
+        "accepted_cues": report.accepted,
+        "rejected_cues": report.rejected,
+        "rewritten_cues": report.rewritten,
+        "flagged_cues": report.flagged,
+    });
+    Ok(([(header::ETAG, etag)], Json(body)).into_response())
+}
+
+/// Replace a memory's content, and its cues when the request has them,
+/// after the same hooks, normalization and validation as ingest. With
+/// `If-Match`, only while the memory is unchanged.
+async fn update_memory(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Path(memory_id): Path<String>,
+    Json(req): Json<UpdateMemoryRequest>,
+) -> Result<axum::response::Response, ApiError> {
+    limits::check_memory(&config::request_limits(), &req.content, req.cues.as_deref().unwrap_or_default())?;
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+    let expected = check_if_match(&headers, &ctx.main, &memory_id)?;
+
+    let replace_cues = req.cues.is_some();
+    let memory = match ctx.prepare_memory(req.content, req.cues.unwrap_or_default(), None) {
+        Ok(memory) => memory,
+        Err(reason) => return Err(ApiError::HookRejected(reason)),
+    };
+    let report = memory.report;
+    let oversized = check_content_limit(&ctx, &memory.content)?;
+    let cues = replace_cues.then(|| report.accepted.clone());
+    match ctx.main.update_memory_if_match(&memory_id, memory.content.clone(), cues, expected.as_deref()) {
+        UpdateOutcome::Updated => {}
+        UpdateOutcome::NotFound => return Err(ApiError::NotFound(format!("Memory {} not found", memory_id))),
+        UpdateOutcome::Stale(current) => return Err(stale_memory(&memory_id, &current)),
+    }
+    if replace_cues {
+        ctx.rejections.record(&memory_id, &report.rejected);
+    }
+    tracing::info!("PUT /memories/{} project={} cues_replaced={}", memory_id, project_id, replace_cues);
+
+    let job_queue = state.job_queue();
+    if let Some(max_bytes) = oversized {
+        shorten_oversized(&ctx, &memory_id, &memory.content, max_bytes);
+        job_queue.enqueue(Job::SummarizeOversized {
+            project_id: project_id.clone(),
+            memory_id: memory_id.clone(),
+            max_bytes,
+        }).await;
+    }
+    job_queue.enqueue(Job::TrainLexiconFromMemory { project_id: project_id.clone(), memory_id: memory_id.clone() }).await;
+    job_queue.enqueue(Job::LlmProposeCues {
+        project_id,
+        memory_id: memory_id.clone(),
+        content: memory.content,
+    }).await;
+
+    let current = ctx.main.get_memory(&memory_id);
+    let etag = current.as_ref().map(|memory| memory.etag()).unwrap_or_default();
+    let body = serde_json::json!({
+        "memory_id": memory_id,
+        "status": "updated",
+        "shortened": oversized.is_some(),
+        "cues": current.map(|memory| memory.cues).unwrap_or_default(),
+        "rejected_cues": if replace_cues { report.rejected } else { Vec::new() },
+        "rewritten_cues": if replace_cues { report.rewritten } else { Vec::new() },
+        "flagged_cues": if replace_cues { report.flagged } else { Vec::new() },
+    });
+    Ok(([(header::ETAG, etag)], Json(body)).into_response())
+}
+
+/// Lock a memory so background jobs (LLM cue proposals, consolidation,
+/// maintenance and agent pruning) leave it alone.
+async fn lock_memory(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Path(memory_id): Path<String>,
+) -> Result<axum::response::Response, ApiError> {
+    set_memory_lock(&state, &headers, memory_id, true)
+}
+
+async fn unlock_memory(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Path(memory_id): Path<String>,
+) -> Result<axum::response::Response, ApiError> {
+    set_memory_lock(&state, &headers, memory_id, false)
+}
+
+fn set_memory_lock(state: &EngineState, headers: &HeaderMap, memory_id: String, locked: bool) -> Result<axum::response::Response, ApiError> {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+    let (project_id, ctx) = state.resolve_project(headers)?;
+    check_if_match(headers, &ctx.main, &memory_id)?;
+    if !ctx.main.set_locked(&memory_id, locked) {
+        return Err(ApiError::NotFound(format!("Memory {} not found", memory_id)));
+    }
+    tracing::info!("{} /memories/{}/lock project={}", if locked { "POST" } else { "DELETE" }, memory_id, project_id);
+
+    let etag = ctx.main.get_memory(&memory_id).map(|memory| memory.etag()).unwrap_or_default();
+    let body = serde_json::json!({ "memory_id": memory_id, "locked": locked });
+    Ok(([(header::ETAG, etag)], Json(body)).into_response())
+}
+
+/// Where a memory came from: its agent provenance and, for summaries, the
+/// chains of the memories it was consolidated from.
+async fn get_memory_provenance(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Path(memory_id): Path<String>,
+) -> ApiResult {
+    let (_, ctx) = state.resolve_project(&headers)?;
+    let chain = provenance::chain(&ctx.main, &memory_id)
+        .ok_or_else(|| ApiError::NotFound(format!("Memory {} not found", memory_id)))?;
+    Ok((StatusCode::OK, Json(serde_json::json!(chain))))
+}
+
+/// Files the agent has ingested, with their chunk and memory counts.
+async fn list_agent_files(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+) -> ApiResult {
+    let (_, ctx) = state.resolve_project(&headers)?;
+    // Scans every memory; off the async workers, so the route's timeout can fire
+    let files = tokio::task::spawn_blocking(move || provenance::agent_files(&ctx.main))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok((StatusCode::OK, Json(serde_json::json!({ "files": files }))))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AgentFileQuery {
+    pub path: String,
+}
+
+/// The memories the agent extracted from one file.
+async fn get_agent_file_memories(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    axum::extract::Query(query): axum::extract::Query<AgentFileQuery>,
+) -> ApiResult {
+    let (_, ctx) = state.resolve_project(&headers)?;
+    // The agent tracks paths lowercased
+    let path = query.path.to_lowercase();
+    let memories = tokio::task::spawn_blocking({
+        let path = path.clone();
+        move || provenance::file_memories(&ctx.main, &path)
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if memories.is_empty() {
+        return Err(ApiError::NotFound(format!("No agent memories from {}", query.path)));
+    }
+    Ok((StatusCode::OK, Json(serde_json::json!({ "path": path, "memories": memories }))))
+}
+
+type AgentExtension = Option<Extension<AgentHandle>>;
+
+fn agent_enabled(agent: AgentExtension) -> Result<AgentHandle, ApiError> {
+    agent
+        .map(|Extension(agent)| agent)
+        .ok_or(ApiError::NotEnabled("Agent endpoints"))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReingestRequest {
+    /// A file path or glob, as tracked or below the watch directory
+    pub path: String,
+}
+
+/// Ingest the agent's files matching a path or glob again now, replacing
+/// their memories, without waiting for them to change on disk.
+async fn reingest_agent_files(
+    State(state): State<EngineState>,
+    agent: AgentExtension,
+    Json(req): Json<ReingestRequest>,
+) -> ApiResult {
+    let agent = agent_enabled(agent)?;
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+
+    let paths = agent.ingester.lock().await.reingest(&req.path).await.map_err(ApiError::BadRequest)?;
+    if paths.is_empty() {
+        return Err(ApiError::NotFound(format!("No agent file matches {}", req.path)));
+    }
+    tracing::info!("POST /agent/reingest path={} files={}", req.path, paths.len());
+
+    Ok((StatusCode::OK, Json(serde_json::json!({ "status": "queued", "paths": paths }))))
+}
+
+/// Files the agent failed to read, parse or extract, with the reasons, and
+/// whether they are quarantined.
+async fn list_agent_errors(agent: AgentExtension) -> ApiResult {
+    let agent = agent_enabled(agent)?;
+    Ok((StatusCode::OK, Json(serde_json::json!({ "files": agent.errors.files() }))))
+}
+
+/// The content a memory was written with, before its project's content
+/// limit shortened it.
+async fn get_memory_full_content(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Path(memory_id): Path<String>,
+) -> ApiResult {
+    let (_, ctx) = state.resolve_project(&headers)?;
+    let memory = ctx
+        .main
+        .get_memory(&memory_id)
+        .ok_or_else(|| ApiError::NotFound(format!("Memory {} not found", memory_id)))?;
+    let shortened = memory.full_content.is_some();
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "memory_id": memory.id,
+            "full_content": memory.full_content.unwrap_or(memory.content),
+            "shortened": shortened,
+        })),
+    ))
+}
+
+async fn reinforce_memory(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Path(memory_id): Path<String>,
+    Json(req): Json<ReinforceRequest>,
+) -> Result<axum::response::Response, ApiError> {
+    limits::check_cues(&config::request_limits(), &req.cues)?;
+
+    if let EngineState::SingleTenant { project, read_only, .. } = state {
+        // Check if read-only
+        if read_only {
+            return Err(ApiError::ReadOnly);
+        }
+        check_if_match(&headers, &project.main, &memory_id)?;
+        
+        // Normalize cues
+        let normalized_cues = project.normalize_cues(&req.cues);
+        
+        let success = project.main.reinforce_memory(&memory_id, normalized_cues);
+        
+        if success {
+            Ok(reinforced_response(&project.main, memory_id))
+        } else {
+            Err(ApiError::NotFound(format!("Memory {} not found", memory_id)))
+        }
+    } else {
+        Err(ApiError::WrongMode("single-tenant"))
+    }
+}
+
+async fn get_memory(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Path(memory_id): Path<String>,
+) -> Result<axum::response::Response, ApiError> {
+    if let EngineState::SingleTenant { project, .. } = state {
+        match project.main.get_memory(&memory_id) {
+            Some(memory) => Ok(memory_response(&headers, memory)),
+            None => Err(ApiError::NotFound("Memory not found".to_string())),
+        }
+    } else {
+        Err(ApiError::WrongMode("single-tenant"))
+    }
+}
+
+/// Remove a memory and its cue index entries. Honours `If-Match`.
+async fn delete_memory(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Path(memory_id): Path<String>,
+) -> ApiResult {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+    check_if_match(&headers, &ctx.main, &memory_id)?;
+    if !ctx.main.delete_memory(&memory_id) {
+        return Err(ApiError::NotFound(format!("Memory {} not found", memory_id)));
+    }
+    tracing::info!("DELETE /memories/{} project={}", memory_id, project_id);
+    Ok((StatusCode::OK, Json(serde_json::json!({"status": "deleted", "memory_id": memory_id}))))
+}
+
+/// Cues of the project's memories, most used first, with the form each was
+/// first written in when normalization changed its case.
+async fn list_cues(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    axum::extract::Query(query): axum::extract::Query<CuesQuery>,
+) -> ApiResult {
+    let (_, ctx) = state.resolve_project(&headers)?;
+    let prefix = query.prefix.unwrap_or_default();
+    let mut cues: Vec<(Arc<str>, usize)> = ctx
+        .main
+        .get_cue_index()
+        .iter()
+        .filter(|entry| entry.key().starts_with(prefix.as_str()) && !entry.value().is_empty())
+        .map(|entry| (entry.key().clone(), entry.value().len()))
+        .collect();
+    cues.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let total = cues.len();
+    let cues: Vec<serde_json::Value> = cues
+        .into_iter()
+        .take(query.limit)
+        .map(|(cue, memories)| {
+            let mut entry = serde_json::json!({"cue": &*cue, "memories": memories});
+            if let Some(display_name) = ctx.display_name(&cue) {
+                entry["display_name"] = serde_json::json!(display_name);
+            }
+            entry
+        })
+        .collect();
+    Ok((StatusCode::OK, Json(serde_json::json!({"total": total, "cues": cues}))))
+}
+
+/// Engine statistics, plus how the taxonomy is enforced, what `warn` mode
+/// has let through and how the recall cache is doing.
+fn project_stats(ctx: &ProjectContext) -> serde_json::Value {
+    let mut stats = ctx.main.get_stats();
+    stats.insert(
+        "taxonomy_enforcement".to_string(),
+        serde_json::json!({
+            "mode": ctx.taxonomy().enforcement,
+            "would_reject": ctx.enforcement_warnings.snapshot(),
+        }),
+    );
+    stats.insert("recall_cache".to_string(), serde_json::json!(ctx.recall_cache.stats()));
+    serde_json::Value::Object(stats.into_iter().collect())
+}
+
+async fn get_stats(State(state): State<EngineState>) -> ApiResult {
+    if let EngineState::SingleTenant { project, .. } = state {
+        Ok((StatusCode::OK, Json(project_stats(&project))))
+    } else {
+        Err(ApiError::WrongMode("single-tenant"))
+    }
+}
+
+async fn recall_grounded(
+    State(state): State<EngineState>,
+    Json(mut req): Json<RecallGroundedRequest>,
+) -> ApiResult {
+    use std::time::Instant;
+    use crate::grounding::create_grounding_proof;
+
+    limits::check_recall_limit(&config::request_limits(), req.limit())?;
+
+    if let EngineState::SingleTenant { project, traces, .. } = state {
+        req.apply_defaults(&project.recall_defaults());
+        let start = Instant::now();
+        
+        // 1-2. Standard CueMap Recall, then Budgeting Logic
+        let (resolved, expanded_cues) = req.expanded_cues(&project);
+        let (selected, excluded, context_block, cached) = req.select(&project, &expanded_cues).await?;
+        
+        // 3. Create Proof
+        let proof = create_grounding_proof(
+            uuid::Uuid::new_v4().to_string(),
+            req.query_text,
+            resolved,
+            expanded_cues,
+            req.token_budget,
+            selected,
+            excluded,
+        );
+        
+        let elapsed = start.elapsed();
+
+        // 4. Retain proof for audits
+        traces.record("default", proof.clone());
+        
+        Ok((StatusCode::OK, Json(grounded_response(context_block, proof, elapsed, cached))))
+    } else {
+        Err(ApiError::WrongMode("single-tenant"))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamQuery {
+    /// Project to follow in multi-tenant mode, for clients such as browser
+    /// `EventSource` that cannot set X-Project-ID
+    #[serde(default)]
+    project: Option<String>,
+    /// Comma-separated event types to send (all when omitted)
+    #[serde(default)]
+    types: Option<String>,
+}
+
+/// Server-sent events for memory adds, deletes and reinforcements and
+/// background job completions in one project. A client that falls behind
+/// gets a `lagged` event with the number of events it missed.
+async fn stream_changes(
+    State(state): State<EngineState>,
+    mut headers: HeaderMap,
+    axum::extract::Query(query): axum::extract::Query<StreamQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    if let Some(project) = query.project.as_deref().and_then(|p| p.parse().ok()) {
+        headers.entry("X-Project-ID").or_insert(project);
+    }
+    let (_, ctx) = state.resolve_project(&headers)?;
+    let types: Option<Vec<String>> = query.types.map(|t| t.split(',').map(|s| s.trim().to_string()).collect());
+
+    let events = BroadcastStream::new(ctx.main.events().subscribe()).filter_map(move |item| match item {
+        Ok(record) => {
+            let wanted = types.as_ref().is_none_or(|types| types.iter().any(|t| t == record.event.kind()));
+            wanted.then(|| Ok(Event::default().event(record.event.kind()).data(serde_json::to_string(&record).unwrap_or_default())))
+        }
+        Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+            Some(Ok(Event::default().event("lagged").data(serde_json::json!({"skipped": skipped}).to_string())))
+        }
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+// Grounding Trace Handlers (shared by both tenancy modes)
+
+async fn get_grounding_trace(
+    State(state): State<EngineState>,
+    Path(trace_id): Path<String>,
+) -> ApiResult {
+    match state.traces().get(&trace_id) {
+        Some(trace) => Ok((StatusCode::OK, Json(serde_json::json!(trace)))),
+        None => Err(ApiError::NotFound("Trace not found".to_string())),
+    }
+}
+
+async fn list_grounding_traces(
+    State(state): State<EngineState>,
+    axum::extract::Query(filter): axum::extract::Query<TraceFilter>,
+) -> ApiResult {
+    let traces = state.traces().list(&filter);
+    Ok((StatusCode::OK, Json(serde_json::json!({
+        "traces": traces,
+        "count": traces.len()
+    }))))
+}
+
+async fn check_grounded_answer(
+    State(state): State<EngineState>,
+    Json(req): Json<CitationCheckRequest>,
+) -> ApiResult {
+    match state.traces().get(&req.trace_id) {
+        Some(trace) => {
+            let report = crate::grounding::check_citations(&trace.proof, &req.answer);
+            Ok((StatusCode::OK, Json(serde_json::json!(report))))
+        }
+        None => Err(ApiError::NotFound("Trace not found".to_string())),
+    }
+}
+
+/// Claim `trace` for feedback, then reinforce the `useful` memories and
+/// penalize the `harmful` ones with the query cues each of them matched.
+/// Returns how many memories were reinforced and penalized.
+fn apply_trace_feedback(
+    state: &EngineState,
+    trace_id: &str,
+    trace: &crate::grounding::StoredTrace,
+    useful: &[String],
+    harmful: &[String],
+) -> Result<(usize, usize), ApiError> {
+    use crate::grounding::{FeedbackError, TraceFeedback};
+
+    let ctx = match state {
+        EngineState::SingleTenant { project, .. } => project.clone(),
+        EngineState::MultiTenant { mt_engine, .. } => match mt_engine.get_project(&trace.project_id) {
+            Some(ctx) => ctx,
+            None => return Err(ApiError::NotFound("Project not found".to_string())),
+        },
+    };
+
+    // Claim the trace before adjusting anything, so concurrent reports can't double-apply
+    let feedback = TraceFeedback::new(useful.to_vec(), harmful.to_vec());
+    match state.traces().record_feedback(trace_id, feedback) {
+        Ok(_) => {}
+        Err(FeedbackError::TraceNotFound) => {
+            return Err(ApiError::NotFound("Trace not found".to_string()));
+        }
+        Err(FeedbackError::AlreadySubmitted) => {
+            return Err(ApiError::Conflict("Feedback already submitted for this trace".to_string()));
+        }
+    }
+
+    // Only the query cues a memory actually matched are adjusted
+    let query_cues: std::collections::HashSet<String> =
+        trace.proof.expanded_cues.iter().map(|(cue, _)| cue.to_lowercase()).collect();
+    let matched_cues = |memory_id: &str| -> Vec<String> {
+        ctx.main
+            .get_memory(memory_id)
+            .map(|memory| memory.cues.iter().filter(|c| query_cues.contains(&c.to_lowercase())).map(|c| c.to_string()).collect())
+            .unwrap_or_default()
+    };
+
+    // Credit the aliases that led to judged memories, before reinforcement moves anything
+    for (ids, was_useful) in [(useful, true), (harmful, false)] {
+        for id in ids {
+            ctx.record_alias_feedback(&trace.proof.expanded_cues, &matched_cues(id), was_useful);
+        }
+    }
+
+    let reinforced = useful.iter()
+        .filter(|id| ctx.main.reinforce_memory(id, matched_cues(id)))
+        .count();
+    let penalized = harmful.iter()
+        .filter(|id| ctx.main.penalize_memory(id, matched_cues(id)))
+        .count();
+
+    Ok((reinforced, penalized))
+}
+
+/// Report which memories selected for a grounding trace were useful or harmful.
+/// Useful memories are reinforced with the query cues they matched; harmful ones
+/// lose salience and drop to the least recent position for those cues.
+async fn submit_recall_feedback(
+    State(state): State<EngineState>,
+    Json(req): Json<RecallFeedbackRequest>,
+) -> ApiResult {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+
+    let Some(trace) = state.traces().get(&req.trace_id) else {
+        return Err(ApiError::NotFound("Trace not found".to_string()));
+    };
+
+    let selected: std::collections::HashSet<&str> =
+        trace.proof.selected.iter().map(|item| item.memory_id.as_str()).collect();
+    let unknown: Vec<&String> = req.useful.iter().chain(&req.harmful)
+        .filter(|id| !selected.contains(id.as_str()))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(ApiError::Invalid {
+            detail: "memory_ids were not selected for this trace".to_string(),
+            problems: unknown.into_iter().cloned().collect(),
+        });
+    }
+    if let Some(both) = req.useful.iter().find(|id| req.harmful.contains(id)) {
+        return Err(ApiError::BadRequest(format!("memory_id {} is both useful and harmful", both)));
+    }
+
+    let (reinforced, penalized) = apply_trace_feedback(&state, &req.trace_id, &trace, &req.useful, &req.harmful)?;
+
+    tracing::info!(
+        "POST /recall/feedback project={} trace={} reinforced={} penalized={}",
+        trace.project_id, req.trace_id, reinforced, penalized
+    );
+
+    Ok((StatusCode::OK, Json(serde_json::json!({
+        "trace_id": req.trace_id,
+        "reinforced": reinforced,
+        "penalized": penalized,
+        "metrics": state.traces().feedback_metrics(&trace.project_id)
+    }))))
+}
+
+/// Aggregate recall precision for the project, from feedback on retained traces.
+async fn get_recall_feedback(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+) -> ApiResult {
+    let (project_id, _) = state.resolve_project(&headers)?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({
+        "project_id": project_id,
+        "metrics": state.traces().feedback_metrics(&project_id)
+    }))))
+}
+
+/// Reinforce every memory selected for a grounding trace with the query cues
+/// it matched, as if each were reported useful. Counts as the trace's feedback.
+async fn reinforce_trace(
+    State(state): State<EngineState>,
+    Path(trace_id): Path<String>,
+) -> ApiResult {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+
+    let Some(trace) = state.traces().get(&trace_id) else {
+        return Err(ApiError::NotFound("Trace not found".to_string()));
+    };
+
+    let memory_ids: Vec<String> = trace.proof.selected.iter().map(|item| item.memory_id.clone()).collect();
+    let (reinforced, _) = apply_trace_feedback(&state, &trace_id, &trace, &memory_ids, &[])?;
+
+    tracing::info!(
+        "POST /grounding/traces/{}/reinforce project={} reinforced={}",
+        trace_id, trace.project_id, reinforced
+    );
+
+    Ok((StatusCode::OK, Json(serde_json::json!({
+        "trace_id": trace_id,
+        "reinforced": reinforced,
+        "memory_ids": memory_ids,
+        "metrics": state.traces().feedback_metrics(&trace.project_id)
+    }))))
+}
+
+// Alias Handlers (Single Tenant)
+
+async fn get_normalization(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+) -> ApiResult {
+    let (_, ctx) = state.resolve_project(&headers)?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!(ctx.normalization()))))
+}
+
+async fn update_normalization(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Json(normalization): Json<NormalizationConfig>,
+) -> ApiResult {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+
+    let invalid: Vec<&str> = normalization.rewrite_rules.iter()
+        .filter(|rule| !rule.is_valid())
+        .map(|rule| rule.name.as_str())
+        .collect();
+    if !invalid.is_empty() {
+        return Err(ApiError::Invalid { detail: "Invalid rewrite rule pattern".to_string(), problems: invalid.into_iter().map(String::from).collect() });
+    }
+
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+
+    ctx.set_normalization(normalization);
+    tracing::info!("PUT /normalization project={}", project_id);
+
+    Ok((StatusCode::OK, Json(serde_json::json!(ctx.normalization()))))
+}
+
+async fn test_normalization(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Json(req): Json<NormalizationTestRequest>,
+) -> ApiResult {
+    let (_, ctx) = state.resolve_project(&headers)?;
+
+    let candidate = req.config.is_some();
+    let config = req.config.unwrap_or_else(|| ctx.normalization());
+
+    let invalid_rules: Vec<&str> = config.rewrite_rules.iter()
+        .filter(|rule| !rule.is_valid())
+        .map(|rule| rule.name.as_str())
+        .collect();
+
+    let traces: Vec<_> = req.cues.iter()
+        .map(|cue| normalize_cue(cue, &config).1)
+        .collect();
+
+    Ok((StatusCode::OK, Json(serde_json::json!({
+        "config": if candidate { "candidate" } else { "current" },
+        "invalid_rules": invalid_rules,
+        "traces": traces
+    }))))
+}
+
+async fn get_tokenizer(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+) -> ApiResult {
+    let (_, ctx) = state.resolve_project(&headers)?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!(ctx.tokenizer()))))
+}
+
+async fn update_tokenizer(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Json(tokenizer): Json<TokenizerConfig>,
+) -> ApiResult {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+
+    if tokenizer.min_token_len == 0 {
+        return Err(ApiError::BadRequest("min_token_len must be at least 1".to_string()));
+    }
+
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+
+    ctx.set_tokenizer(tokenizer);
+    tracing::info!("PUT /tokenizer project={}", project_id);
+
+    Ok((StatusCode::OK, Json(serde_json::json!(ctx.tokenizer()))))
+}
+
+async fn get_alias_config(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+) -> ApiResult {
+    let (_, ctx) = state.resolve_project(&headers)?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!(ctx.alias_config()))))
+}
+
+async fn update_alias_config(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Json(alias_config): Json<AliasConfig>,
+) -> ApiResult {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+
+    alias_config.validate().map_err(ApiError::BadRequest)?;
+
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+
+    ctx.set_alias_config(alias_config);
+    tracing::info!("PUT /aliases/config project={}", project_id);
+
+    Ok((StatusCode::OK, Json(serde_json::json!(ctx.alias_config()))))
+}
+
+async fn get_recall_defaults(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+) -> ApiResult {
+    let (_, ctx) = state.resolve_project(&headers)?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!(ctx.recall_defaults()))))
+}
+
+async fn update_recall_defaults(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Json(defaults): Json<RecallDefaults>,
+) -> ApiResult {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+
+    defaults.validate(config::request_limits().max_recall_limit).map_err(ApiError::BadRequest)?;
+
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+
+    ctx.set_recall_defaults(defaults);
+    tracing::info!("PUT /recall/defaults project={}", project_id);
+
+    Ok((StatusCode::OK, Json(serde_json::json!(ctx.recall_defaults()))))
+}
+
+async fn get_content_limit(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+) -> ApiResult {
+    let (_, ctx) = state.resolve_project(&headers)?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!(ctx.content_limit()))))
+}
+
+async fn update_content_limit(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Json(content_limit): Json<ContentLimit>,
+) -> ApiResult {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+
+    content_limit.validate(config::request_limits().max_content_bytes).map_err(ApiError::BadRequest)?;
+
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+
+    ctx.set_content_limit(content_limit);
+    tracing::info!("PUT /content-limit project={}", project_id);
+
+    Ok((StatusCode::OK, Json(serde_json::json!(ctx.content_limit()))))
+}
+
+async fn get_ranking(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+) -> ApiResult {
+    let (_, ctx) = state.resolve_project(&headers)?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!(ctx.ranking()))))
+}
+
+async fn update_ranking(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Json(ranking): Json<RankingConfig>,
+) -> ApiResult {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+
+    let problems = ranking.validate();
+    if !problems.is_empty() {
+        return Err(ApiError::Invalid { detail: "invalid ranking rules".to_string(), problems });
+    }
+
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+
+    ctx.set_ranking(ranking);
+    tracing::info!("PUT /ranking project={}", project_id);
+
+    Ok((StatusCode::OK, Json(serde_json::json!(ctx.ranking()))))
+}
+
+async fn get_taxonomy(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+) -> ApiResult {
+    let (_, ctx) = state.resolve_project(&headers)?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!(ctx.taxonomy()))))
+}
+
+async fn update_taxonomy(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Json(taxonomy): Json<Taxonomy>,
+) -> ApiResult {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+
+    ctx.set_taxonomy(taxonomy);
+    tracing::info!("PUT /taxonomy project={}", project_id);
+
+    Ok((StatusCode::OK, Json(serde_json::json!(ctx.taxonomy()))))
+}
+
+async fn deprecate_taxonomy_key(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Json(req): Json<DeprecateKeyRequest>,
+) -> ApiResult {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+
+    let key = req.key.trim().to_lowercase();
+    let replacement = req.replacement.trim().to_lowercase();
+    if key.is_empty() || replacement.is_empty() || key.contains(':') || replacement.contains(':') {
+        return Err(ApiError::BadRequest("key and replacement must be non-empty bare keys".to_string()));
+    }
+    if key == replacement {
+        return Err(ApiError::BadRequest("key and replacement must differ".to_string()));
+    }
+
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+
+    if ctx.taxonomy().resolve_deprecated_key(&replacement).as_deref() == Some(key.as_str()) {
+        return Err(ApiError::BadRequest("Deprecation would create a cycle".to_string()));
     }
-    
-    router
+
+    ctx.deprecate_key(&key, &replacement);
+
+    if req.migrate {
+        state.job_queue().enqueue(Job::MigrateDeprecatedKey {
+            project_id: project_id.clone(),
+            from_key: key.clone(),
+            to_key: replacement.clone(),
+        }).await;
+    }
+
+    tracing::info!("POST /taxonomy/deprecations project={} {} -> {}", project_id, key, replacement);
+
+    Ok((StatusCode::OK, Json(serde_json::json!({
+        "status": "deprecated",
+        "key": key,
+        "replacement": replacement,
+        "migration_queued": req.migrate
+    }))))
 }
 
-async fn root() -> impl IntoResponse {
-    Json(serde_json::json!({
-        "name": "CueMap Rust Engine",
-        "version": "0.2.1",
-        "description": "High-performance Temporal-Associative Memory Store"
-    }))
+async fn get_taxonomy_rejections(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    axum::extract::Query(query): axum::extract::Query<RejectionsQuery>,
+) -> ApiResult {
+    let (_, ctx) = state.resolve_project(&headers)?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({
+        "total": ctx.rejections.len(),
+        "by_key": ctx.rejections.by_key(),
+        "rejections": ctx.rejections.entries(query.key.as_deref(), query.limit)
+    }))))
 }
 
-async fn add_memory(
+async fn promote_taxonomy_rejection(
     State(state): State<EngineState>,
-    Json(req): Json<AddMemoryRequest>,
-) -> (StatusCode, Json<serde_json::Value>) {
-    if let EngineState::SingleTenant { project, read_only, job_queue } = state {
-        // Check if read-only
-        if read_only {
-            return (
-                StatusCode::FORBIDDEN,
-                Json(serde_json::json!({
-                    "error": "Read-only mode: modifications are not allowed"
-                })),
-            );
-        }
-        
-        // 1. Normalize cues
-        let mut normalized_cues = Vec::new();
-        for cue in req.cues {
-            let (normalized, _) = normalize_cue(&cue, &project.normalization);
-            normalized_cues.push(normalized);
-        }
-        
-        // 2. Validate cues
-        let report = validate_cues(normalized_cues, &project.taxonomy);
-        
-        let memory_id = project.main.add_memory(req.content.clone(), report.accepted, req.metadata, req.disable_temporal_chunking);
-        
-        // Enqueue background jobs
-        job_queue.enqueue(Job::TrainLexiconFromMemory {
-            project_id: "default".to_string(), 
-            memory_id: memory_id.clone()
-        }).await;
-        
-        job_queue.enqueue(Job::LlmProposeCues {
-            project_id: "default".to_string(),
-            memory_id: memory_id.clone(),
-            content: req.content,
+    headers: HeaderMap,
+    Json(req): Json<PromoteRejectionRequest>,
+) -> ApiResult {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
+
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+
+    // Normalize as the quarantined cues were, so the promotion matches them
+    let key = req.key.trim().to_lowercase();
+    let cue = match req.value.as_deref().map(str::trim) {
+        Some(value) => ctx.normalize_cues(&[format!("{}:{}", key, value)]).remove(0),
+        None => key.clone(),
+    };
+    let (key, value) = match cue.split_once(':') {
+        Some((key, value)) if req.value.is_some() => (key.to_string(), Some(value.to_string())),
+        _ => (key, None),
+    };
+    if key.is_empty() || key.contains(':') || value.as_deref().is_some_and(str::is_empty) {
+        return Err(ApiError::BadRequest("key must be a non-empty bare key and value non-empty".to_string()));
+    }
+    if !ctx.rejections.contains(&key, value.as_deref()) {
+        return Err(ApiError::NotFound(format!("No quarantined cues for '{}'", cue)));
+    }
+
+    let promotion = ctx.promote_rejected(&key, value.as_deref());
+    let mut memory_ids: Vec<&str> = promotion.reattached.iter().map(|entry| entry.memory_id.as_str()).collect();
+    memory_ids.sort_unstable();
+    memory_ids.dedup();
+    for memory_id in memory_ids {
+        state.job_queue().enqueue(Job::TrainLexiconFromMemory {
+            project_id: project_id.clone(),
+            memory_id: memory_id.to_string(),
         }).await;
-        
-        (
-            StatusCode::OK,
-            Json(serde_json::json!({
-                "id": memory_id,
-                "status": "stored",
-                "rejected_cues": report.rejected
-            })),
-        )
-    } else {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({
-                "id": "",
-                "status": "error"
-            })),
-        )
     }
+    tracing::info!(
+        "POST /taxonomy/rejections/promote project={} {} reattached={}",
+        project_id, cue, promotion.reattached.len()
+    );
+
+    Ok((StatusCode::OK, Json(serde_json::json!({
+        "status": "promoted",
+        "key": key,
+        "value": value,
+        "reattached": promotion.reattached,
+        "still_rejected": promotion.still_rejected,
+        "taxonomy": ctx.taxonomy()
+    }))))
 }
 
-async fn recall(
-    State(state): State<EngineState>,
-    Json(req): Json<RecallRequest>,
-) -> (StatusCode, Json<serde_json::Value>) {
-    use std::time::Instant;
-    
-    if let EngineState::SingleTenant { project, .. } = state {
-        let start = Instant::now();
-        
-        // Collect cues from request
-        let mut cues_to_process = req.cues;
-        
-        // Resolve cues from text if present
-        if let Some(text) = req.query_text {
-            let resolved = project.resolve_cues_from_text(&text);
-            cues_to_process.extend(resolved);
-        }
+/// 400 unless `collection` is unset or one of the project's collections.
+fn check_collection(ctx: &ProjectContext, collection: Option<&str>) -> Result<(), ApiError> {
+    match collection {
+        Some(name) if ctx.collection(name).is_none() => Err(ApiError::BadRequest(format!("Unknown collection '{}'", name))),
+        _ => Ok(()),
+    }
+}
 
-        // Normalize query cues
-        let mut normalized_cues = Vec::new();
-        for cue in &cues_to_process {
-            let (normalized, _) = normalize_cue(cue, &project.normalization);
-            normalized_cues.push(normalized);
-        }
-        
-        // Expand aliases
-        let expanded_cues = project.expand_query_cues(normalized_cues);
-        let results = project.main.recall_weighted(
-            expanded_cues.clone(), 
-            req.limit, 
-            req.auto_reinforce, 
-            req.min_intersection,
-            req.explain,
-            req.disable_pattern_completion,
-            req.disable_salience_bias,
-            req.disable_systems_consolidation
-        );
-        
-        let elapsed = start.elapsed();
-        let engine_latency_ms = elapsed.as_secs_f64() * 1000.0;
-        
-        // Add query explanation if requested
-        if req.explain {
-            let explanation = serde_json::json!({
-                "normalized_query": cues_to_process,
-                "expanded_cues": expanded_cues
-            });
-            
-            return (StatusCode::OK, Json(serde_json::json!({ 
-                "results": results,
-                "engine_latency": engine_latency_ms,
-                "explain": explanation
-            })));
-        }
-        
-        (StatusCode::OK, Json(serde_json::json!({ 
-            "results": results,
-            "engine_latency": engine_latency_ms
-        })))
-    } else {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({"error": "Invalid state"})),
-        )
+fn collection_error(name: &str, error: CollectionError) -> ApiError {
+    match error {
+        CollectionError::InvalidName(detail) => ApiError::BadRequest(detail),
+        CollectionError::AlreadyExists => ApiError::Conflict(format!("Collection '{}' already exists", name)),
+        CollectionError::NotFound => ApiError::NotFound(format!("Collection '{}' not found", name)),
     }
 }
 
-async fn reinforce_memory(
+async fn list_collections(
     State(state): State<EngineState>,
-    Path(memory_id): Path<String>,
-    Json(req): Json<ReinforceRequest>,
-) -> (StatusCode, Json<serde_json::Value>) {
-    if let EngineState::SingleTenant { project, read_only, .. } = state {
-        // Check if read-only
-        if read_only {
-            return (
-                StatusCode::FORBIDDEN,
-                Json(serde_json::json!({
-                    "error": "Read-only mode: modifications are not allowed"
-                })),
-            );
-        }
-        
-        // Normalize cues
-        let mut normalized_cues = Vec::new();
-        for cue in req.cues {
-            let (normalized, _) = normalize_cue(&cue, &project.normalization);
-            normalized_cues.push(normalized);
-        }
-        
-        let success = project.main.reinforce_memory(&memory_id, normalized_cues);
-        
-        if success {
-            (
-                StatusCode::OK,
-                Json(serde_json::json!({
-                    "status": "reinforced",
-                    "memory_id": memory_id
-                })),
-            )
-        } else {
-            (
-                StatusCode::NOT_FOUND,
-                Json(serde_json::json!({
-                    "status": "not_found",
-                    "memory_id": memory_id
-                })),
-            )
-        }
-    } else {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({
-                "status": "error",
-                "memory_id": ""
-            })),
-        )
+    headers: HeaderMap,
+) -> ApiResult {
+    let (_, ctx) = state.resolve_project(&headers)?;
+
+    let sizes = ctx.main.collection_sizes();
+    let collections: Vec<serde_json::Value> = ctx
+        .collections()
+        .into_iter()
+        .map(|collection| {
+            let memories = sizes.get(&collection.name).copied().unwrap_or(0);
+            let mut entry = serde_json::json!(collection);
+            entry["memories"] = serde_json::json!(memories);
+            entry
+        })
+        .collect();
+
+    Ok((StatusCode::OK, Json(serde_json::json!({
+        "collections": collections,
+        "count": collections.len()
+    }))))
+}
+
+async fn create_collection(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Json(req): Json<CreateCollectionRequest>,
+) -> ApiResult {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
     }
+
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+    let collection = ctx
+        .create_collection(&req.name, req.description)
+        .map_err(|e| collection_error(&req.name, e))?;
+    tracing::info!("POST /collections project={} name={}", project_id, collection.name);
+
+    Ok((StatusCode::CREATED, Json(serde_json::json!(collection))))
 }
 
-async fn get_memory(
+async fn get_collection(
     State(state): State<EngineState>,
-    Path(memory_id): Path<String>,
-) -> (StatusCode, Json<serde_json::Value>) {
-    if let EngineState::SingleTenant { project, .. } = state {
-        match project.main.get_memory(&memory_id) {
-            Some(memory) => (StatusCode::OK, Json(serde_json::json!(memory))),
-            None => (
-                StatusCode::NOT_FOUND,
-                Json(serde_json::json!({"error": "Memory not found"})),
-            ),
-        }
-    } else {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({"error": "Invalid state"})),
-        )
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> ApiResult {
+    let (_, ctx) = state.resolve_project(&headers)?;
+    let collection = ctx.collection(&name).ok_or_else(|| collection_error(&name, CollectionError::NotFound))?;
+
+    let mut body = serde_json::json!(collection);
+    body["memories"] = serde_json::json!(ctx.main.collection_sizes().get(&name).copied().unwrap_or(0));
+    Ok((StatusCode::OK, Json(body)))
+}
+
+async fn update_collection(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Json(req): Json<UpdateCollectionRequest>,
+) -> ApiResult {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
     }
+
+    let (_, ctx) = state.resolve_project(&headers)?;
+    let collection = ctx
+        .update_collection(&name, req.description)
+        .map_err(|e| collection_error(&name, e))?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!(collection))))
 }
 
-async fn get_stats(State(state): State<EngineState>) -> (StatusCode, Json<serde_json::Value>) {
-    if let EngineState::SingleTenant { project, .. } = state {
-        let stats = project.main.get_stats();
-        (StatusCode::OK, Json(serde_json::Value::Object(stats.into_iter().collect())))
-    } else {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({"error": "Invalid state"})),
-        )
+/// Delete a collection; its memories stay, outside any collection.
+async fn delete_collection(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> ApiResult {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
     }
+
+    let (project_id, ctx) = state.resolve_project(&headers)?;
+    let released = ctx.delete_collection(&name).map_err(|e| collection_error(&name, e))?;
+    tracing::info!("DELETE /collections/{} project={} released={}", name, project_id, released);
+
+    Ok((StatusCode::OK, Json(serde_json::json!({
+        "status": "deleted",
+        "name": name,
+        "released": released
+    }))))
 }
 
-async fn recall_grounded(
+/// Move existing memories into a collection.
+async fn add_collection_memories(
     State(state): State<EngineState>,
-    Json(req): Json<RecallGroundedRequest>,
-) -> (StatusCode, Json<serde_json::Value>) {
-    use std::time::Instant;
-    use crate::grounding::{GroundingEngine, create_grounding_proof};
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Json(req): Json<CollectionMembersRequest>,
+) -> ApiResult {
+    if state.read_only() {
+        return Err(ApiError::ReadOnly);
+    }
 
-    if let EngineState::SingleTenant { project, .. } = state {
-        let start = Instant::now();
-        
-        // 1. Standard CueMap Recall
-        let resolved = project.resolve_cues_from_text(&req.query_text);
-        let mut normalized_cues = Vec::new();
-        for cue in &resolved {
-            let (normalized, _) = crate::normalization::normalize_cue(cue, &project.normalization);
-            normalized_cues.push(normalized);
-        }
-        let expanded_cues = project.expand_query_cues(normalized_cues);
-        let results = project.main.recall_weighted(
-            expanded_cues.clone(), 
-            req.limit.max(20),
-            false, 
-            None,
-            true,
-            req.disable_pattern_completion,
-            req.disable_salience_bias,
-            req.disable_systems_consolidation
-        );
-        
-        // 2. Apply Budgeting Logic
-        let (selected, excluded, context_block) = GroundingEngine::select_memories(
-            req.query_text.clone(),
-            resolved.clone(),
-            expanded_cues.clone(),
-            results,
-            req.token_budget,
-        );
-        
-        // 3. Create Proof
-        let proof = create_grounding_proof(
-            uuid::Uuid::new_v4().to_string(),
-            req.query_text,
-            resolved,
-            expanded_cues,
-            req.token_budget,
-            selected,
-            excluded,
-        );
-        
-        let elapsed = start.elapsed();
-        
-        (StatusCode::OK, Json(serde_json::json!({ 
-            "verified_context": context_block,
-            "proof": proof,
-            "engine_latency_ms": elapsed.as_secs_f64() * 1000.0
-        })))
-    } else {
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": "Invalid state"})))
+    let (_, ctx) = state.resolve_project(&headers)?;
+    if ctx.collection(&name).is_none() {
+        return Err(collection_error(&name, CollectionError::NotFound));
     }
+    let missing: Vec<&String> = req.memory_ids.iter().filter(|id| !ctx.main.set_collection(id, Some(&name))).collect();
+
+    Ok((StatusCode::OK, Json(serde_json::json!({
+        "name": name,
+        "moved": req.memory_ids.len() - missing.len(),
+        "not_found": missing
+    }))))
 }
 
-// Alias Handlers (Single Tenant)
+async fn export_subgraph(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+    axum::extract::Query(query): axum::extract::Query<SubgraphQuery>,
+) -> ApiResult {
+    let depth = query.depth.unwrap_or(config::SUBGRAPH_DEFAULT_DEPTH);
+    if depth > config::SUBGRAPH_MAX_DEPTH {
+        return Err(ApiError::BadRequest(format!("depth must be at most {}", config::SUBGRAPH_MAX_DEPTH)));
+    }
+    if query.cue.trim().is_empty() {
+        return Err(ApiError::BadRequest("cue must not be empty".to_string()));
+    }
+
+    let (_, ctx) = state.resolve_project(&headers)?;
+    // Normalize as ingest did, so the cue matches the indexed form
+    let cue = ctx.normalize_cues(std::slice::from_ref(&query.cue)).remove(0);
+    let subgraph = tokio::task::spawn_blocking({
+        let cue = cue.clone();
+        move || crate::subgraph::extract(&ctx.main, &cue, depth)
+    })
+    .await
+    .map_err(|e| ApiError::Internal(e.to_string()))?
+    .ok_or_else(|| ApiError::NotFound(format!("No memories hold cue '{}'", cue)))?;
+
+    Ok((StatusCode::OK, Json(serde_json::json!(subgraph))))
+}
 
 async fn add_alias(
     State(state): State<EngineState>,
     Json(req): Json<AddAliasRequest>,
-) -> (StatusCode, Json<serde_json::Value>) {
+) -> ApiResult {
     if let EngineState::SingleTenant { project, read_only, .. } = state {
         if read_only {
-            return (StatusCode::FORBIDDEN, Json(serde_json::json!({"error": "Read-only"})));
+            return Err(ApiError::ReadOnly);
         }
 
         let alias_id = uuid::Uuid::new_v4().to_string();
@@ -512,20 +3117,20 @@ async fn add_alias(
             false // no reinforce
         );
 
-        (StatusCode::OK, Json(serde_json::json!({"id": alias_id, "status": "created"})))
+        Ok((StatusCode::OK, Json(serde_json::json!({"id": alias_id, "status": "created"}))))
     } else {
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": "Invalid state"})))
+        Err(ApiError::WrongMode("single-tenant"))
     }
 }
 
 async fn get_aliases(
     State(state): State<EngineState>,
     axum::extract::Query(params): axum::extract::Query<HashMap<String, String>>,
-) -> (StatusCode, Json<serde_json::Value>) {
+) -> ApiResult {
     if let EngineState::SingleTenant { project, .. } = state {
         let cue = params.get("cue").cloned().unwrap_or_default();
         if cue.is_empty() {
-            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "Missing 'cue' query param"})));
+            return Err(ApiError::BadRequest("Missing 'cue' query param".to_string()));
         }
 
         let query_cues = vec![
@@ -548,19 +3153,28 @@ async fn get_aliases(
             }
         }
         
-        (StatusCode::OK, Json(serde_json::json!({"aliases": aliases})))
+        Ok((StatusCode::OK, Json(serde_json::json!({"aliases": aliases}))))
     } else {
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": "Invalid state"})))
+        Err(ApiError::WrongMode("single-tenant"))
     }
 }
 
+/// Usage of every alias of the project: expansions, hits and feedback.
+async fn get_alias_stats(
+    State(state): State<EngineState>,
+    headers: HeaderMap,
+) -> ApiResult {
+    let (_, ctx) = state.resolve_project(&headers)?;
+    Ok((StatusCode::OK, Json(serde_json::json!({"aliases": ctx.alias_stats()}))))
+}
+
 async fn merge_aliases(
     State(state): State<EngineState>,
     Json(req): Json<MergeAliasRequest>,
-) -> (StatusCode, Json<serde_json::Value>) {
+) -> ApiResult {
     if let EngineState::SingleTenant { project, read_only, .. } = state {
         if read_only {
-            return (StatusCode::FORBIDDEN, Json(serde_json::json!({"error": "Read-only"})));
+            return Err(ApiError::ReadOnly);
         }
 
         let mut created_ids = Vec::new();
@@ -593,33 +3207,25 @@ async fn merge_aliases(
             created_ids.push(alias_id);
         }
 
-        (StatusCode::OK, Json(serde_json::json!({
+        Ok((StatusCode::OK, Json(serde_json::json!({
             "status": "merged", 
             "target": req.to, 
             "count": created_ids.len()
-        })))
+        }))))
     } else {
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": "Invalid state"})))
+        Err(ApiError::WrongMode("single-tenant"))
     }
 }
 
 // Multi-tenant handlers
-fn extract_project_id(headers: &HeaderMap) -> Result<String, (StatusCode, Json<serde_json::Value>)> {
+fn extract_project_id(headers: &HeaderMap) -> Result<String, ApiError> {
     let project_id = headers
         .get("X-Project-ID")
         .and_then(|v| v.to_str().ok())
-        .ok_or_else(|| {
-            (
-                StatusCode::BAD_REQUEST,
-                Json(serde_json::json!({"error": "Missing X-Project-ID header"})),
-            )
-        })?;
+        .ok_or_else(|| ApiError::BadRequest("Missing X-Project-ID header".to_string()))?;
     
     if !validate_project_id(project_id) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({"error": "Invalid project ID format"})),
-        ));
+        return Err(ApiError::BadRequest("Invalid project ID format".to_string()));
     }
     
     Ok(project_id.to_string())
@@ -629,38 +3235,40 @@ async fn add_memory_mt(
     State(state): State<EngineState>,
     headers: HeaderMap,
     Json(req): Json<AddMemoryRequest>,
-) -> (StatusCode, Json<serde_json::Value>) {
-    let project_id = match extract_project_id(&headers) {
-        Ok(id) => id,
-        Err(e) => return e,
-    };
+) -> ApiResult {
+    limits::check_memory(&config::request_limits(), &req.content, &req.cues)?;
+    let project_id = extract_project_id(&headers)?;
     
-    if let EngineState::MultiTenant { mt_engine, read_only, job_queue } = state {
+    if let EngineState::MultiTenant { mt_engine, read_only, job_queue, .. } = state {
         // Check if read-only
         if read_only {
-            return (
-                StatusCode::FORBIDDEN,
-                Json(serde_json::json!({
-                    "error": "Read-only mode: modifications are not allowed"
-                })),
-            );
+            return Err(ApiError::ReadOnly);
         }
         
         let ctx = mt_engine.get_or_create_project(project_id.clone());
+        check_collection(&ctx, req.collection.as_deref())?;
         
-        // 1. Normalize cues
-        let mut normalized_cues = Vec::new();
-        for cue in &req.cues {
-            let (normalized, _) = normalize_cue(&cue, &ctx.normalization);
-            normalized_cues.push(normalized);
-        }
-        
-        // 2. Validate cues
-        let report = validate_cues(normalized_cues, &ctx.taxonomy);
+        // Run ingest hooks, then normalize, language-tag and validate cues
+        let cue_count = req.cues.len();
+        let memory = match ctx.prepare_memory(req.content, req.cues, req.metadata) {
+            Ok(memory) => memory,
+            Err(reason) => return Err(ApiError::HookRejected(reason)),
+        };
+        let report = memory.report;
+        let oversized = check_content_limit(&ctx, &memory.content)?;
         
-        let memory_id = ctx.main.add_memory(req.content.clone(), report.accepted, req.metadata, req.disable_temporal_chunking);
+        let memory_id = ctx.main.add_memory_to(memory.content.clone(), report.accepted, memory.metadata, req.disable_temporal_chunking, req.collection);
+        ctx.rejections.record(&memory_id, &report.rejected);
         
         // Enqueue background jobs
+        if let Some(max_bytes) = oversized {
+            shorten_oversized(&ctx, &memory_id, &memory.content, max_bytes);
+            job_queue.enqueue(Job::SummarizeOversized {
+                project_id: project_id.clone(),
+                memory_id: memory_id.clone(),
+                max_bytes,
+            }).await;
+        }
         job_queue.enqueue(Job::TrainLexiconFromMemory {
             project_id: project_id.clone(), 
             memory_id: memory_id.clone()
@@ -669,80 +3277,80 @@ async fn add_memory_mt(
         job_queue.enqueue(Job::LlmProposeCues {
             project_id: project_id.clone(),
             memory_id: memory_id.clone(),
-            content: req.content,
+            content: memory.content,
         }).await;
         
         tracing::info!(
             "POST /memories project={} cues={} id={}",
             project_id,
-            req.cues.len(),
+            cue_count,
             memory_id
         );
         
-        (
+        Ok((
             StatusCode::OK,
             Json(serde_json::json!({
                 "id": memory_id,
                 "status": "stored",
-                "rejected_cues": report.rejected
+                "shortened": oversized.is_some(),
+                "rejected_cues": report.rejected,
+                "rewritten_cues": report.rewritten,
+                "flagged_cues": report.flagged
             })),
-        )
+        ))
     } else {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({
-                "id": "",
-                "status": "error"
-            })),
-        )
+        Err(ApiError::WrongMode("multi-tenant"))
     }
 }
 
 async fn recall_mt(
     State(state): State<EngineState>,
     headers: HeaderMap,
-    Json(req): Json<RecallRequest>,
-) -> (StatusCode, Json<serde_json::Value>) {
+    caller: Option<Extension<Scope>>,
+    Json(mut req): Json<RecallRequest>,
+) -> ApiResult {
     use std::time::Instant;
+
+    limits::check_recall_limit(&config::request_limits(), req.limit())?;
+    let fields = req.result_fields()?;
     
     if let EngineState::MultiTenant { mt_engine, .. } = state {
         // Cross-domain query if projects array is provided
-        if let Some(projects) = req.projects {
+        if let Some(projects) = &req.projects {
             let start = Instant::now();
             
             // Query all projects in parallel using rayon
+            // (rayon threads don't inherit the current span, so pass it explicitly)
+            let parent_span = tracing::Span::current();
             let all_results: Vec<serde_json::Value> = projects
                 .par_iter()
                 .map(|project_id| {
+                    let _span = tracing::info_span!(parent: &parent_span, "project_recall", project_id = %project_id).entered();
                     let ctx = mt_engine.get_or_create_project(project_id.clone());
+                    let mut req = req.clone();
+                    req.apply_defaults(&ctx.recall_defaults());
                     
-                    // Collect cues
-                    let mut cues_to_process = req.cues.clone();
-                    
-                    if let Some(text) = &req.query_text {
-                         let resolved = ctx.resolve_cues_from_text(text);
-                         cues_to_process.extend(resolved);
-                    }
-                    
-                    // Normalize query cues
-                    let mut normalized_cues = Vec::new();
-                    for cue in &cues_to_process {
-                        let (normalized, _) = normalize_cue(cue, &ctx.normalization);
-                        normalized_cues.push(normalized);
-                    }
+                    // Normalized request cues, then cues resolved from text at their confidence
+                    let (resolution_text, spelling_corrections) = req.resolution_text(&ctx);
+                    let query_cues = ctx.query_cues(&req.cues, resolution_text.as_deref());
+                    let cues_to_process: Vec<String> = query_cues.iter().map(|(cue, _)| cue.clone()).collect();
                     
                     // Expand aliases
-                    let expanded_cues = ctx.expand_query_cues(normalized_cues);
-                    let results = ctx.main.recall_weighted(
+                    let (expanded_cues, alias_paths) = expand_with_paths(&ctx, query_cues, req.alias_depth());
+                    let mut results = ctx.main.recall_weighted_in(
                         expanded_cues.clone(), 
-                        req.limit, 
+                        req.limit(), 
                         false,
                         req.min_intersection,
-                        req.explain,
+                        req.explain(),
                         req.disable_pattern_completion,
                         req.disable_salience_bias,
-                        req.disable_systems_consolidation
+                        req.disable_systems_consolidation,
+                        req.max_candidates,
+                        req.collections.as_ref(),
+                        req.within_ids.as_deref()
                     );
+                    ctx.after_recall(&expanded_cues, req.query_text.as_deref(), &mut results);
                     
                     let json_results: Vec<serde_json::Value> = results
                         .into_iter()
@@ -751,6 +3359,7 @@ async fn recall_mt(
                             "content": r.content,
                             "score": r.score,
                             "intersection_count": r.intersection_count,
+                            "matched_cues": r.matched_cues,
                             "recency_score": r.recency_score,
                             "metadata": r.metadata,
                             "explain": r.explain
@@ -762,12 +3371,15 @@ async fn recall_mt(
                         "results": json_results
                     });
                     
-                    if req.explain {
+                    if req.explain() {
                         response_block.as_object_mut().unwrap().insert(
                             "explain".to_string(), 
                             serde_json::json!({
                                 "query_cues": cues_to_process,
-                                "expanded_cues": expanded_cues
+                                "expanded_cues": expanded_cues,
+                                "alias_paths": alias_paths,
+                                "spelling_corrections": spelling_corrections,
+                                "display_names": ctx.display_names(expanded_cues.iter().map(|(cue, _)| cue.as_str()))
                             })
                         );
                     }
@@ -791,49 +3403,60 @@ async fn recall_mt(
                 engine_latency_ms
             );
             
-            return (StatusCode::OK, Json(serde_json::json!({ 
+            return Ok((StatusCode::OK, Json(serde_json::json!({ 
                 "results": all_results,
                 "engine_latency": engine_latency_ms
-            })));
+            }))));
         }
         
         // Single project query using X-Project-ID header
-        let project_id = match extract_project_id(&headers) {
-            Ok(id) => id,
-            Err(e) => return e,
-        };
+        let project_id = extract_project_id(&headers)?;
         
         let start = Instant::now();
         let ctx = mt_engine.get_or_create_project(project_id.clone());
+        req.apply_defaults(&ctx.recall_defaults());
+        req.check_auto_reinforce(caller.as_ref())?;
+        let (recall_limit, rerank_query) = (req.recall_limit(), req.rerank_query());
         
-        // Collect cues
-        let mut cues_to_process = req.cues;
-        
-        if let Some(text) = req.query_text {
-             let resolved = ctx.resolve_cues_from_text(&text);
-             cues_to_process.extend(resolved);
-        }
-        
-        // Normalize query cues
-        let mut normalized_cues = Vec::new();
-        for cue in &cues_to_process {
-            let (normalized, _) = normalize_cue(cue, &ctx.normalization);
-            normalized_cues.push(normalized);
-        }
+        // Normalized request cues, then cues resolved from text at their confidence
+        let (resolution_text, spelling_corrections) = req.resolution_text(&ctx);
+        let query_cues = ctx.query_cues(&req.cues, resolution_text.as_deref());
+        let cues_to_process: Vec<String> = query_cues.iter().map(|(cue, _)| cue.clone()).collect();
         
         // Expand aliases
-        let expanded_cues = ctx.expand_query_cues(normalized_cues);
+        let (expanded_cues, alias_paths) = expand_with_paths(&ctx, query_cues, req.alias_depth());
+        let cache_key = req.cache_key(&ctx, &expanded_cues, &cues_to_process, &spelling_corrections);
+        let generation = ctx.main.recall_generation();
+        if let Some(response) = cached_recall(&ctx, cache_key.as_deref(), generation, start) {
+            return Ok((StatusCode::OK, Json(response)));
+        }
         
-        let results = ctx.main.recall_weighted(
-            expanded_cues.clone(), 
-            req.limit, 
-            req.auto_reinforce, 
-            req.min_intersection,
-            req.explain,
-            req.disable_pattern_completion,
-            req.disable_salience_bias,
-            req.disable_systems_consolidation
-        );
+        // Off the async workers, so the route's timeout can fire mid-recall
+        let mut results = tokio::task::spawn_blocking({
+            let (ctx, expanded_cues, req) = (ctx.clone(), expanded_cues.clone(), req.clone());
+            move || {
+                ctx.main.recall_weighted_in(
+                    expanded_cues,
+                    recall_limit,
+                    req.auto_reinforce(),
+                    req.min_intersection,
+                    req.explain(),
+                    req.disable_pattern_completion,
+                    req.disable_salience_bias,
+                    req.disable_systems_consolidation,
+                    req.max_candidates,
+                    req.collections.as_ref(),
+                    req.within_ids.as_deref(),
+                )
+            }
+        })
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+        let reranked = match &rerank_query {
+            Some(query) => Some(rerank_results(&ctx.main, query, req.limit(), &mut results).await),
+            None => None,
+        };
+        ctx.after_recall(&expanded_cues, req.query_text.as_deref(), &mut results);
         let elapsed = start.elapsed();
         
         let engine_latency_ms = elapsed.as_secs_f64() * 1000.0;
@@ -846,26 +3469,30 @@ async fn recall_mt(
             engine_latency_ms
         );
         
-        if req.explain {
-            return (StatusCode::OK, Json(serde_json::json!({ 
-                "results": results,
-                "engine_latency": engine_latency_ms,
-                "explain": {
-                    "query_cues": cues_to_process,
-                    "expanded_cues": expanded_cues
-                }
-            })));
+        let mut response = serde_json::json!({ 
+            "results": results_json(&ctx.main, &results, fields.as_ref()),
+            "engine_latency": engine_latency_ms
+        });
+        if let Some(reranked) = reranked {
+            response["reranked"] = serde_json::json!(reranked);
         }
         
-        (StatusCode::OK, Json(serde_json::json!({ 
-            "results": results,
-            "engine_latency": engine_latency_ms
-        })))
+        if req.explain() {
+            response["explain"] = serde_json::json!({
+                "query_cues": cues_to_process,
+                "expanded_cues": expanded_cues,
+                "alias_paths": alias_paths,
+                "spelling_corrections": spelling_corrections,
+                "display_names": ctx.display_names(expanded_cues.iter().map(|(cue, _)| cue.as_str()))
+            });
+        }
+        if let Some(key) = cache_key {
+            ctx.recall_cache.insert(key, response.clone(), generation);
+        }
+        
+        Ok((StatusCode::OK, Json(response)))
     } else {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({"error": "Invalid state"})),
-        )
+        Err(ApiError::WrongMode("multi-tenant"))
     }
 }
 
@@ -874,49 +3501,26 @@ async fn reinforce_memory_mt(
     headers: HeaderMap,
     Path(memory_id): Path<String>,
     Json(req): Json<ReinforceRequest>,
-) -> (StatusCode, Json<serde_json::Value>) {
-    let project_id = match extract_project_id(&headers) {
-        Ok(id) => id,
-        Err(e) => return e,
-    };
+) -> Result<axum::response::Response, ApiError> {
+    limits::check_cues(&config::request_limits(), &req.cues)?;
+    let project_id = extract_project_id(&headers)?;
     
     if let EngineState::MultiTenant { mt_engine, .. } = state {
         let ctx = mt_engine.get_or_create_project(project_id);
+        check_if_match(&headers, &ctx.main, &memory_id)?;
         
         // Normalize cues
-        let mut normalized_cues = Vec::new();
-        for cue in req.cues {
-            let (normalized, _) = normalize_cue(&cue, &ctx.normalization);
-            normalized_cues.push(normalized);
-        }
+        let normalized_cues = ctx.normalize_cues(&req.cues);
         
         let success = ctx.main.reinforce_memory(&memory_id, normalized_cues);
         
         if success {
-            (
-                StatusCode::OK,
-                Json(serde_json::json!({
-                    "status": "reinforced",
-                    "memory_id": memory_id
-                })),
-            )
+            Ok(reinforced_response(&ctx.main, memory_id))
         } else {
-            (
-                StatusCode::NOT_FOUND,
-                Json(serde_json::json!({
-                    "status": "not_found",
-                    "memory_id": memory_id
-                })),
-            )
+            Err(ApiError::NotFound(format!("Memory {} not found", memory_id)))
         }
     } else {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({
-                "status": "error",
-                "memory_id": ""
-            })),
-        )
+        Err(ApiError::WrongMode("multi-tenant"))
     }
 }
 
@@ -924,101 +3528,60 @@ async fn get_memory_mt(
     State(state): State<EngineState>,
     headers: HeaderMap,
     Path(memory_id): Path<String>,
-) -> (StatusCode, Json<serde_json::Value>) {
-    let project_id = match extract_project_id(&headers) {
-        Ok(id) => id,
-        Err(e) => return e,
-    };
+) -> Result<axum::response::Response, ApiError> {
+    let project_id = extract_project_id(&headers)?;
     
     if let EngineState::MultiTenant { mt_engine, .. } = state {
         let ctx = mt_engine.get_or_create_project(project_id);
         match ctx.main.get_memory(&memory_id) {
-            Some(memory) => (StatusCode::OK, Json(serde_json::json!(memory))),
-            None => (
-                StatusCode::NOT_FOUND,
-                Json(serde_json::json!({"error": "Memory not found"})),
-            ),
+            Some(memory) => Ok(memory_response(&headers, memory)),
+            None => Err(ApiError::NotFound("Memory not found".to_string())),
         }
     } else {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({"error": "Invalid state"})),
-        )
+        Err(ApiError::WrongMode("multi-tenant"))
     }
 }
 
 async fn get_stats_mt(
     State(state): State<EngineState>,
     headers: HeaderMap,
-) -> (StatusCode, Json<serde_json::Value>) {
-    let project_id = match extract_project_id(&headers) {
-        Ok(id) => id,
-        Err(e) => return e,
-    };
+) -> ApiResult {
+    let project_id = extract_project_id(&headers)?;
     
     if let EngineState::MultiTenant { mt_engine, .. } = state {
         let ctx = mt_engine.get_or_create_project(project_id);
-        let stats = ctx.main.get_stats();
-        (StatusCode::OK, Json(serde_json::Value::Object(stats.into_iter().collect())))
+        Ok((StatusCode::OK, Json(project_stats(&ctx))))
     } else {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({"error": "Invalid state"})),
-        )
+        Err(ApiError::WrongMode("multi-tenant"))
     }
 }
 
 async fn recall_grounded_mt(
     State(state): State<EngineState>,
     headers: HeaderMap,
-    Json(req): Json<RecallGroundedRequest>,
-) -> (StatusCode, Json<serde_json::Value>) {
+    Json(mut req): Json<RecallGroundedRequest>,
+) -> ApiResult {
     use std::time::Instant;
-    use crate::grounding::{GroundingEngine, create_grounding_proof};
+    use crate::grounding::create_grounding_proof;
+
+    limits::check_recall_limit(&config::request_limits(), req.limit())?;
 
     let project_id = if let Some(ref projects) = req.projects {
         projects.first().cloned().unwrap_or_else(|| {
              headers.get("X-Project-ID").and_then(|v| v.to_str().ok()).unwrap_or("default").to_string()
         })
     } else {
-        match extract_project_id(&headers) {
-            Ok(id) => id,
-            Err(e) => return e,
-        }
+        extract_project_id(&headers)?
     };
 
-    if let EngineState::MultiTenant { mt_engine, .. } = state {
+    if let EngineState::MultiTenant { mt_engine, traces, .. } = state {
         let start = Instant::now();
-        let ctx = mt_engine.get_or_create_project(project_id);
-        
-        // 1. Standard CueMap Recall
-        let resolved = ctx.resolve_cues_from_text(&req.query_text);
-        let mut normalized_cues = Vec::new();
-        for cue in &resolved {
-            let (normalized, _) = crate::normalization::normalize_cue(cue, &ctx.normalization);
-            normalized_cues.push(normalized);
-        }
-        let expanded_cues = ctx.expand_query_cues(normalized_cues);
-        
-        let results = ctx.main.recall_weighted(
-            expanded_cues.clone(), 
-            req.limit.max(20),
-            false, 
-            None,
-            true,
-            req.disable_pattern_completion,
-            req.disable_salience_bias,
-            req.disable_systems_consolidation
-        );
+        let ctx = mt_engine.get_or_create_project(project_id.clone());
+        req.apply_defaults(&ctx.recall_defaults());
         
-        // 2. Apply Budgeting Logic
-        let (selected, excluded, context_block) = GroundingEngine::select_memories(
-            req.query_text.clone(),
-            resolved.clone(),
-            expanded_cues.clone(),
-            results,
-            req.token_budget,
-        );
+        // 1-2. Standard CueMap Recall, then Budgeting Logic
+        let (resolved, expanded_cues) = req.expanded_cues(&ctx);
+        let (selected, excluded, context_block, cached) = req.select(&ctx, &expanded_cues).await?;
         
         // 3. Create Proof
         let proof = create_grounding_proof(
@@ -1032,53 +3595,47 @@ async fn recall_grounded_mt(
         );
         
         let elapsed = start.elapsed();
+
+        // 4. Retain proof for audits
+        traces.record(&project_id, proof.clone());
         
-        (StatusCode::OK, Json(serde_json::json!({ 
-            "verified_context": context_block,
-            "proof": proof,
-            "engine_latency_ms": elapsed.as_secs_f64() * 1000.0
-        })))
+        Ok((StatusCode::OK, Json(grounded_response(context_block, proof, elapsed, cached))))
     } else {
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": "Invalid state"})))
+        Err(ApiError::WrongMode("multi-tenant"))
     }
 }
 
 async fn list_projects(
     State(state): State<EngineState>,
-) -> (StatusCode, Json<serde_json::Value>) {
+) -> ApiResult {
     if let EngineState::MultiTenant { mt_engine, .. } = state {
         let projects = mt_engine.list_projects();
-        (StatusCode::OK, Json(serde_json::json!({ "projects": projects })))
+        Ok((StatusCode::OK, Json(serde_json::json!({ "projects": projects }))))
     } else {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({"error": "Not in multi-tenant mode"})),
-        )
+        Err(ApiError::WrongMode("multi-tenant"))
     }
 }
 
 async fn delete_project(
     State(state): State<EngineState>,
+    webhooks: Webhooks,
     Path(project_id): Path<String>,
-) -> (StatusCode, Json<serde_json::Value>) {
+) -> ApiResult {
     if let EngineState::MultiTenant { mt_engine, .. } = state {
         let deleted = mt_engine.delete_project(&project_id);
         if deleted {
-            (
+            if let Some(Extension(hub)) = webhooks {
+                hub.remove_project(&project_id);
+            }
+            Ok((
                 StatusCode::OK,
                 Json(serde_json::json!({"status": "deleted", "project_id": project_id})),
-            )
+            ))
         } else {
-            (
-                StatusCode::NOT_FOUND,
-                Json(serde_json::json!({"error": "Project not found"})),
-            )
+            Err(ApiError::NotFound("Project not found".to_string()))
         }
     } else {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({"error": "Not in multi-tenant mode"})),
-        )
+        Err(ApiError::WrongMode("multi-tenant"))
     }
 }
 
@@ -1088,15 +3645,12 @@ async fn add_alias_mt(
     State(state): State<EngineState>,
     headers: HeaderMap,
     Json(req): Json<AddAliasRequest>,
-) -> (StatusCode, Json<serde_json::Value>) {
-    let project_id = match extract_project_id(&headers) {
-        Ok(id) => id,
-        Err(e) => return e,
-    };
+) -> ApiResult {
+    let project_id = extract_project_id(&headers)?;
 
     if let EngineState::MultiTenant { mt_engine, read_only, .. } = state {
         if read_only {
-            return (StatusCode::FORBIDDEN, Json(serde_json::json!({"error": "Read-only"})));
+            return Err(ApiError::ReadOnly);
         }
 
         let ctx = mt_engine.get_or_create_project(project_id);
@@ -1126,9 +3680,9 @@ async fn add_alias_mt(
             false 
         );
 
-        (StatusCode::OK, Json(serde_json::json!({"id": alias_id, "status": "created"})))
+        Ok((StatusCode::OK, Json(serde_json::json!({"id": alias_id, "status": "created"}))))
     } else {
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": "Invalid state"})))
+        Err(ApiError::WrongMode("multi-tenant"))
     }
 }
 
@@ -1136,18 +3690,15 @@ async fn get_aliases_mt(
     State(state): State<EngineState>,
     headers: HeaderMap,
     axum::extract::Query(params): axum::extract::Query<HashMap<String, String>>,
-) -> (StatusCode, Json<serde_json::Value>) {
-    let project_id = match extract_project_id(&headers) {
-        Ok(id) => id,
-        Err(e) => return e,
-    };
+) -> ApiResult {
+    let project_id = extract_project_id(&headers)?;
 
     if let EngineState::MultiTenant { mt_engine, .. } = state {
         let ctx = mt_engine.get_or_create_project(project_id);
         
         let cue = params.get("cue").cloned().unwrap_or_default();
         if cue.is_empty() {
-            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "Missing 'cue' query param"})));
+            return Err(ApiError::BadRequest("Missing 'cue' query param".to_string()));
         }
         
         let query_cues = vec![
@@ -1170,9 +3721,9 @@ async fn get_aliases_mt(
             }
         }
         
-        (StatusCode::OK, Json(serde_json::json!({"aliases": aliases})))
+        Ok((StatusCode::OK, Json(serde_json::json!({"aliases": aliases}))))
     } else {
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": "Invalid state"})))
+        Err(ApiError::WrongMode("multi-tenant"))
     }
 }
 
@@ -1180,15 +3731,12 @@ async fn merge_aliases_mt(
     State(state): State<EngineState>,
     headers: HeaderMap,
     Json(req): Json<MergeAliasRequest>,
-) -> (StatusCode, Json<serde_json::Value>) {
-    let project_id = match extract_project_id(&headers) {
-        Ok(id) => id,
-        Err(e) => return e,
-    };
+) -> ApiResult {
+    let project_id = extract_project_id(&headers)?;
 
     if let EngineState::MultiTenant { mt_engine, read_only, .. } = state {
         if read_only {
-            return (StatusCode::FORBIDDEN, Json(serde_json::json!({"error": "Read-only"})));
+            return Err(ApiError::ReadOnly);
         }
 
         let ctx = mt_engine.get_or_create_project(project_id);
@@ -1222,12 +3770,12 @@ async fn merge_aliases_mt(
             created_ids.push(alias_id);
         }
 
-        (StatusCode::OK, Json(serde_json::json!({
+        Ok((StatusCode::OK, Json(serde_json::json!({
             "status": "merged", 
             "target": req.to, 
             "count": created_ids.len()
-        })))
+        }))))
     } else {
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": "Invalid state"})))
+        Err(ApiError::WrongMode("multi-tenant"))
     }
 }
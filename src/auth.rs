@@ -1,67 +1,367 @@
 //! Authentication middleware for API key validation.
 
+use crate::error::ApiError;
 use axum::{
     extract::{Request, State},
-    http::{HeaderMap, StatusCode},
+    http::{HeaderMap, Method},
     middleware::Next,
-    response::{IntoResponse, Response},
+    response::Response,
 };
-use std::collections::HashSet;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::env;
-use tracing::info;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+/// Access level granted to an API key. Each scope includes the ones below it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    /// Recall, stats and other queries
+    Read,
+    /// Adding, reinforcing and aliasing memories
+    Write,
+    /// Project deletion, snapshots and configuration
+    Admin,
+}
+
+impl Scope {
+    pub fn parse(s: &str) -> Option<Scope> {
+        match s.trim().to_lowercase().as_str() {
+            "ro" | "r" | "read" => Some(Scope::Read),
+            "rw" | "w" | "write" => Some(Scope::Write),
+            "admin" => Some(Scope::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Write => "write",
+            Scope::Admin => "admin",
+        }
+    }
+}
+
+/// Parse a comma-separated key list such as `key1:rw,key2:ro,key3:admin`.
+/// Keys without a recognised scope suffix keep full (admin) access.
+pub fn parse_api_keys(keys_str: &str) -> HashMap<String, Scope> {
+    let mut api_keys = HashMap::new();
+    for entry in keys_str.split(',') {
+        let entry = entry.trim();
+        if entry.is_empty() {
+            continue;
+        }
+        let (key, scope) = match entry.rsplit_once(':') {
+            Some((key, scope)) if !key.is_empty() => match Scope::parse(scope) {
+                Some(scope) => (key, scope),
+                None => (entry, Scope::Admin),
+            },
+            _ => (entry, Scope::Admin),
+        };
+        api_keys.insert(key.to_string(), scope);
+    }
+    api_keys
+}
+
+/// Scope required to call `method path`.
+pub fn required_scope(method: &Method, path: &str) -> Scope {
+    const CONFIG_PATHS: [&str; 9] = [
+        "/normalization",
+        "/taxonomy",
+        "/taxonomy/deprecations",
+        "/taxonomy/rejections/promote",
+        "/tokenizer",
+        "/aliases/config",
+        "/ranking",
+        "/recall/defaults",
+        "/content-limit",
+    ];
+    const QUERY_PATHS: [&str; 5] = ["/recall", "/recall/batch", "/recall/grounded", "/grounding/check", "/normalization/test"];
+
+    let path = path.trim_end_matches('/');
+    let is_read_method = matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS);
+
+    if path.starts_with("/admin") || path.contains("snapshot") {
+        return Scope::Admin;
+    }
+    if *method == Method::DELETE && path.starts_with("/projects/") {
+        return Scope::Admin;
+    }
+    if !is_read_method && CONFIG_PATHS.contains(&path) {
+        return Scope::Admin;
+    }
+    if is_read_method || (*method == Method::POST && QUERY_PATHS.contains(&path)) {
+        return Scope::Read;
+    }
+    Scope::Write
+}
+
+/// Probe endpoints that stay reachable without an API key (orchestrators don't send one).
+pub const UNAUTHENTICATED_PATHS: [&str; 3] = ["/healthz", "/livez", "/readyz"];
+
+/// Prefix of keys issued by [`KeyStore`]: `cm_<id>_<secret>`.
+const MANAGED_KEY_PREFIX: &str = "cm_";
+
+/// Minimum interval between persisting `last_used` updates, in seconds.
+const LAST_USED_PERSIST_INTERVAL: f64 = 60.0;
+
+/// A key created at runtime. Only a salted SHA-256 of the key is stored.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ManagedKey {
+    pub id: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    pub scope: Scope,
+    salt: String,
+    hash: String,
+    pub created_at: f64,
+    #[serde(default)]
+    pub last_used: Option<f64>,
+}
+
+/// Public view of a managed key (never includes the hash or salt).
+#[derive(Clone, Debug, Serialize)]
+pub struct ApiKeyInfo {
+    pub id: String,
+    pub label: Option<String>,
+    pub scope: Scope,
+    pub created_at: f64,
+    pub last_used: Option<f64>,
+}
+
+impl From<&ManagedKey> for ApiKeyInfo {
+    fn from(key: &ManagedKey) -> Self {
+        ApiKeyInfo {
+            id: key.id.clone(),
+            label: key.label.clone(),
+            scope: key.scope,
+            created_at: key.created_at,
+            last_used: key.last_used,
+        }
+    }
+}
+
+fn hash_key(salt: &str, key: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(key.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Compare two strings without short-circuiting on the first mismatch.
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn now_secs() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs_f64()
+}
+
+/// API keys created and revoked at runtime, persisted as salted hashes.
+#[derive(Default)]
+pub struct KeyStore {
+    keys: DashMap<String, ManagedKey>,
+    /// Keys file; `None` keeps keys in memory only.
+    path: Option<PathBuf>,
+}
+
+impl KeyStore {
+    /// Load keys from `path`, starting empty if the file is missing or unreadable.
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let keys = DashMap::new();
+        if path.exists() {
+            match fs::read(&path).map_err(|e| e.to_string()).and_then(|data| {
+                serde_json::from_slice::<Vec<ManagedKey>>(&data).map_err(|e| e.to_string())
+            }) {
+                Ok(loaded) => {
+                    for key in loaded {
+                        keys.insert(key.id.clone(), key);
+                    }
+                    info!("Loaded {} managed API keys from {:?}", keys.len(), path);
+                }
+                Err(e) => warn!("Failed to load API keys from {:?}: {}", path, e),
+            }
+        }
+        Self { keys, path: Some(path) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Create a key and return its info together with the plaintext key,
+    /// which is not stored and cannot be retrieved again.
+    pub fn create(&self, label: Option<String>, scope: Scope) -> (ApiKeyInfo, String) {
+        let id = uuid::Uuid::new_v4().simple().to_string()[..12].to_string();
+        let secret = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+        let key = format!("{}{}_{}", MANAGED_KEY_PREFIX, id, secret);
+        let salt = uuid::Uuid::new_v4().simple().to_string();
+
+        let managed = ManagedKey {
+            id: id.clone(),
+            label,
+            scope,
+            hash: hash_key(&salt, &key),
+            salt,
+            created_at: now_secs(),
+            last_used: None,
+        };
+        let info = ApiKeyInfo::from(&managed);
+        self.keys.insert(id, managed);
+        self.persist();
+        (info, key)
+    }
+
+    /// Revoke a key by id. Returns false if no such key exists.
+    pub fn revoke(&self, id: &str) -> bool {
+        let removed = self.keys.remove(id).is_some();
+        if removed {
+            self.persist();
+        }
+        removed
+    }
+
+    /// All keys, oldest first.
+    pub fn list(&self) -> Vec<ApiKeyInfo> {
+        let mut keys: Vec<ApiKeyInfo> = self.keys.iter().map(|k| ApiKeyInfo::from(k.value())).collect();
+        keys.sort_by(|a, b| a.created_at.total_cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        keys
+    }
+
+    /// Verify `key` and record its use. Returns the key's scope if valid.
+    pub fn authenticate(&self, key: &str) -> Option<Scope> {
+        let (id, _) = key.strip_prefix(MANAGED_KEY_PREFIX)?.split_once('_')?;
+        let now = now_secs();
+        let (scope, persist) = {
+            let mut entry = self.keys.get_mut(id)?;
+            if !constant_time_eq(&hash_key(&entry.salt, key), &entry.hash) {
+                return None;
+            }
+            let persist = entry.last_used.is_none_or(|t| now - t >= LAST_USED_PERSIST_INTERVAL);
+            entry.last_used = Some(now);
+            (entry.scope, persist)
+        };
+        if persist {
+            self.persist();
+        }
+        Some(scope)
+    }
+
+    fn persist(&self) {
+        let Some(path) = &self.path else { return };
+        let mut keys: Vec<ManagedKey> = self.keys.iter().map(|k| k.value().clone()).collect();
+        keys.sort_by(|a, b| a.id.cmp(&b.id));
+        if let Err(e) = Self::save(path, &keys) {
+            warn!("Failed to persist API keys to {:?}: {}", path, e);
+        }
+    }
+
+    fn save(path: &Path, keys: &[ManagedKey]) -> std::io::Result<()> {
+        let data = serde_json::to_vec_pretty(keys)?;
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, data)?;
+        fs::rename(&temp_path, path)
+    }
+}
 
 #[derive(Clone)]
 pub struct AuthConfig {
-    api_keys: HashSet<String>,
-    require_auth: bool,
+    api_keys: HashMap<String, Scope>,
+    managed: Arc<KeyStore>,
 }
 
 impl AuthConfig {
     pub fn new() -> Self {
-        let mut api_keys = HashSet::new();
+        Self::from_env_or(&[])
+    }
+
+    /// Keys from `CUEMAP_API_KEYS`/`CUEMAP_API_KEY`, or `file_keys` (entries in
+    /// the same `key[:scope]` format) when neither variable is set.
+    pub fn from_env_or(file_keys: &[String]) -> Self {
+        let mut api_keys = HashMap::new();
         
         // Load API keys from environment
         if let Ok(keys_str) = env::var("CUEMAP_API_KEYS") {
-            for key in keys_str.split(',') {
-                let key = key.trim();
-                if !key.is_empty() {
-                    api_keys.insert(key.to_string());
-                }
-            }
+            api_keys.extend(parse_api_keys(&keys_str));
         }
         
-        // Single API key support
+        // Single API key support (full access)
         if let Ok(key) = env::var("CUEMAP_API_KEY") {
-            let key = key.trim();
-            if !key.is_empty() {
-                api_keys.insert(key.to_string());
-            }
+            api_keys.extend(parse_api_keys(&key));
         }
-        
-        let require_auth = !api_keys.is_empty();
-        
-        if require_auth {
-            info!("Authentication enabled ({} API keys configured)", api_keys.len());
-        } else {
-            info!("Authentication disabled (no API keys configured)");
+
+        if api_keys.is_empty() {
+            api_keys.extend(parse_api_keys(&file_keys.join(",")));
         }
         
+        Self::with_keys(api_keys)
+    }
+
+    pub fn with_keys(api_keys: HashMap<String, Scope>) -> Self {
         Self {
             api_keys,
-            require_auth,
+            managed: Arc::new(KeyStore::default()),
+        }
+    }
+
+    /// Use `store` for keys managed through `/admin/keys`.
+    pub fn with_key_store(mut self, store: KeyStore) -> Self {
+        self.managed = Arc::new(store);
+        self
+    }
+
+    /// Number of keys configured through environment variables.
+    pub fn env_key_count(&self) -> usize {
+        self.api_keys.len()
+    }
+
+    pub fn key_store(&self) -> Arc<KeyStore> {
+        self.managed.clone()
+    }
+
+    /// Log whether authentication is active at startup.
+    pub fn log_status(&self) {
+        if self.is_enabled() {
+            info!(
+                "Authentication enabled ({} API keys configured, {} managed)",
+                self.api_keys.len(),
+                self.managed.len()
+            );
+        } else {
+            info!("Authentication disabled (no API keys configured)");
         }
     }
     
+    /// Authentication is required as soon as any key exists, including keys created at runtime.
     pub fn is_enabled(&self) -> bool {
-        self.require_auth
+        !self.api_keys.is_empty() || !self.managed.is_empty()
     }
     
-    fn validate_key(&self, key: &str) -> bool {
-        if !self.require_auth {
-            return true;
+    /// Scope granted to `key`, or `None` if the key is unknown.
+    pub fn key_scope(&self, key: &str) -> Option<Scope> {
+        if !self.is_enabled() {
+            return Some(Scope::Admin);
         }
         
-        self.api_keys.contains(key)
+        self.api_keys
+            .get(key)
+            .copied()
+            .or_else(|| self.managed.authenticate(key))
     }
 }
 
@@ -69,11 +369,11 @@ impl AuthConfig {
 pub async fn auth_middleware(
     State(auth_config): State<AuthConfig>,
     headers: HeaderMap,
-    request: Request,
+    mut request: Request,
     next: Next,
-) -> Result<Response, impl IntoResponse> {
+) -> Result<Response, ApiError> {
     // Skip auth if not required
-    if !auth_config.require_auth {
+    if !auth_config.is_enabled() || UNAUTHENTICATED_PATHS.contains(&request.uri().path()) {
         return Ok(next.run(request).await);
     }
     
@@ -82,21 +382,16 @@ pub async fn auth_middleware(
         .get("X-API-Key")
         .and_then(|v| v.to_str().ok());
     
-    match api_key {
-        Some(key) if auth_config.validate_key(key) => {
+    let required = required_scope(request.method(), request.uri().path());
+    
+    match api_key.map(|key| auth_config.key_scope(key)) {
+        Some(Some(scope)) if scope >= required => {
+            // Handlers whose scope depends on the body check it themselves
+            request.extensions_mut().insert(scope);
             Ok(next.run(request).await)
         }
-        Some(_) => {
-            Err((
-                StatusCode::UNAUTHORIZED,
-                "Invalid API key"
-            ))
-        }
-        None => {
-            Err((
-                StatusCode::UNAUTHORIZED,
-                "Missing X-API-Key header"
-            ))
-        }
+        Some(Some(_)) => Err(ApiError::Forbidden(format!("Insufficient scope: {} access required", required.as_str()))),
+        Some(None) => Err(ApiError::Unauthorized("Invalid API key".to_string())),
+        None => Err(ApiError::Unauthorized("Missing X-API-Key header".to_string())),
     }
 }
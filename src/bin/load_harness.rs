@@ -0,0 +1,283 @@
+//! Concurrent load harness for an embedded engine.
+//!
+//! Drives a mixed add/recall/reinforce workload from several threads for a fixed
+//! time, reports per-operation latency percentiles and throughput, then checks
+//! that no memory was lost and that the cue index is consistent. Exits non-zero
+//! when an invariant fails, so it doubles as a soak test:
+//!
+//! ```text
+//! cargo run --release --bin load_harness -- --threads 8 --duration-secs 600
+//! ```
+
+use clap::Parser;
+use cuemap_rust::engine::CueMapEngine;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Parser, Debug)]
+#[command(about = "Mixed-workload load and soak test against an embedded engine")]
+struct Args {
+    /// Worker threads issuing operations
+    #[arg(long, default_value = "4")]
+    threads: usize,
+
+    /// How long to run the workload
+    #[arg(long, default_value = "10")]
+    duration_secs: u64,
+
+    /// Memories added before the timed run
+    #[arg(long, default_value = "10000")]
+    preload: usize,
+
+    /// Percentages of add, recall and reinforce operations
+    #[arg(long, value_delimiter = ',', default_value = "20,70,10")]
+    mix: Vec<u32>,
+
+    /// Cues attached to each added memory
+    #[arg(long, default_value = "4")]
+    cues_per_memory: usize,
+
+    /// Cues per recall query
+    #[arg(long, default_value = "2")]
+    recall_cues: usize,
+
+    /// Results requested per recall
+    #[arg(long, default_value = "10")]
+    recall_limit: usize,
+
+    /// Serve recalls from a read view republished every N ms (0 = read live)
+    #[arg(long, default_value = "0")]
+    read_view_refresh_ms: u64,
+
+    /// Workload seed; each thread derives its own stream from it
+    #[arg(long, default_value = "42")]
+    seed: u64,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+const CUE_KEYS: &[&str] = &["service", "error", "topic", "team", "region"];
+const VALUES_PER_KEY: u64 = 200;
+
+/// xorshift64*: small, fast and deterministic for a given seed.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Skewed towards low values: roughly 80% of draws hit the first 20%.
+    fn skewed(&mut self, n: u64) -> u64 {
+        let u = (self.next() >> 11) as f64 / (1u64 << 53) as f64;
+        ((u.powf(7.2)) * n as f64) as u64
+    }
+
+    fn cues(&mut self, n: usize) -> Vec<String> {
+        (0..n)
+            .map(|_| {
+                let key = CUE_KEYS[(self.next() % CUE_KEYS.len() as u64) as usize];
+                format!("{}:v{}", key, self.skewed(VALUES_PER_KEY))
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Add,
+    Recall,
+    Reinforce,
+}
+
+const OP_NAMES: [&str; 3] = ["add", "recall", "reinforce"];
+
+/// Latencies in nanoseconds per operation kind, indexed like `OP_NAMES`.
+#[derive(Default)]
+struct WorkerResult {
+    latencies: [Vec<u64>; 3],
+    added: Vec<String>,
+}
+
+fn run_worker(engine: &CueMapEngine, args: &Args, seed: u64, known: &[String], stop: &AtomicBool) -> WorkerResult {
+    let mut rng = Rng::new(seed);
+    let mut result = WorkerResult::default();
+    let total: u32 = args.mix.iter().sum();
+    let mut n = 0u64;
+
+    while !stop.load(Ordering::Relaxed) {
+        let roll = (rng.next() % total as u64) as u32;
+        let (index, op) = if roll < args.mix[0] {
+            (0, Op::Add)
+        } else if roll < args.mix[0] + args.mix[1] {
+            (1, Op::Recall)
+        } else {
+            (2, Op::Reinforce)
+        };
+
+        let started = Instant::now();
+        match op {
+            Op::Add => {
+                let cues = rng.cues(args.cues_per_memory);
+                let id = engine.add_memory(format!("load memory {} {}", seed, n), cues, None, true);
+                result.added.push(id);
+            }
+            Op::Recall => {
+                let cues = rng.cues(args.recall_cues);
+                engine.recall(cues, args.recall_limit, false);
+            }
+            Op::Reinforce => {
+                let pool = if result.added.is_empty() || rng.next().is_multiple_of(2) { known } else { &result.added[..] };
+                if let Some(id) = pool.get((rng.next() % pool.len().max(1) as u64) as usize) {
+                    let cues = rng.cues(1);
+                    engine.reinforce_memory(id, cues);
+                }
+            }
+        }
+        result.latencies[index].push(started.elapsed().as_nanos() as u64);
+        n += 1;
+    }
+
+    result
+}
+
+/// Value at quantile `q` of sorted samples, in microseconds.
+fn percentile_us(sorted: &[u64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((sorted.len() - 1) as f64 * q).round() as usize;
+    sorted[rank] as f64 / 1_000.0
+}
+
+fn main() {
+    let args = Args::parse();
+    if args.mix.len() != 3 || args.mix.iter().sum::<u32>() == 0 {
+        eprintln!("--mix takes three percentages: add,recall,reinforce");
+        std::process::exit(2);
+    }
+    if args.threads == 0 {
+        eprintln!("--threads must be at least 1");
+        std::process::exit(2);
+    }
+
+    let engine = Arc::new(CueMapEngine::new());
+    let mut rng = Rng::new(args.seed);
+    let known: Arc<Vec<String>> = Arc::new(
+        (0..args.preload)
+            .map(|i| engine.add_memory(format!("preloaded memory {}", i), rng.cues(args.cues_per_memory), None, true))
+            .collect(),
+    );
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let refresher = (args.read_view_refresh_ms > 0).then(|| {
+        engine.enable_read_view();
+        let engine = engine.clone();
+        let stop = stop.clone();
+        let every = Duration::from_millis(args.read_view_refresh_ms);
+        thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                thread::sleep(every);
+                engine.refresh_read_view();
+            }
+        })
+    });
+
+    let args = Arc::new(args);
+    let started = Instant::now();
+    let workers: Vec<_> = (0..args.threads)
+        .map(|t| {
+            let (engine, args, known, stop) = (engine.clone(), args.clone(), known.clone(), stop.clone());
+            let seed = args.seed.wrapping_add(t as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+            thread::spawn(move || run_worker(&engine, &args, seed, &known, &stop))
+        })
+        .collect();
+
+    thread::sleep(Duration::from_secs(args.duration_secs));
+    stop.store(true, Ordering::Relaxed);
+    let results: Vec<WorkerResult> = workers.into_iter().map(|w| w.join().expect("worker panicked")).collect();
+    let elapsed = started.elapsed();
+    if let Some(refresher) = refresher {
+        refresher.join().expect("refresher panicked");
+    }
+
+    // Invariants: every acknowledged add is stored, and the index agrees with the memories
+    let mut problems = Vec::new();
+    let added: usize = results.iter().map(|r| r.added.len()).sum();
+    let expected = args.preload + added;
+    if engine.get_memories().len() != expected {
+        problems.push(format!("{} memories stored, expected {}", engine.get_memories().len(), expected));
+    }
+    for id in known.iter().chain(results.iter().flat_map(|r| r.added.iter())) {
+        if engine.get_memory(id).is_none() {
+            problems.push(format!("memory {} was lost", id));
+        }
+    }
+    problems.extend(engine.check_index_consistency());
+
+    let mut ops = Vec::new();
+    let mut total_ops = 0;
+    for (index, name) in OP_NAMES.iter().enumerate() {
+        let mut samples: Vec<u64> = results.iter().flat_map(|r| r.latencies[index].iter().copied()).collect();
+        samples.sort_unstable();
+        total_ops += samples.len();
+        ops.push(serde_json::json!({
+            "op": name,
+            "count": samples.len(),
+            "p50_us": percentile_us(&samples, 0.50),
+            "p99_us": percentile_us(&samples, 0.99),
+            "max_us": percentile_us(&samples, 1.0),
+        }));
+    }
+    let throughput = total_ops as f64 / elapsed.as_secs_f64();
+
+    if args.json {
+        let report = serde_json::json!({
+            "threads": args.threads,
+            "elapsed_secs": elapsed.as_secs_f64(),
+            "ops_per_sec": throughput,
+            "operations": ops,
+            "memories": engine.get_memories().len(),
+            "cues": engine.get_cue_index().len(),
+            "problems": problems,
+        });
+        println!("{}", serde_json::to_string_pretty(&report).unwrap_or_default());
+    } else {
+        println!("{:<10} {:>10} {:>10} {:>10} {:>10}", "op", "count", "p50 (us)", "p99 (us)", "max (us)");
+        for op in &ops {
+            println!(
+                "{:<10} {:>10} {:>10.1} {:>10.1} {:>10.1}",
+                op["op"].as_str().unwrap_or_default(),
+                op["count"],
+                op["p50_us"].as_f64().unwrap_or_default(),
+                op["p99_us"].as_f64().unwrap_or_default(),
+                op["max_us"].as_f64().unwrap_or_default(),
+            );
+        }
+        println!("{} ops in {:.1}s ({:.0} ops/s) on {} threads", total_ops, elapsed.as_secs_f64(), throughput, args.threads);
+        println!("{} memories, {} cues", engine.get_memories().len(), engine.get_cue_index().len());
+        if problems.is_empty() {
+            println!("invariants: ok");
+        } else {
+            println!("invariants: {} problems", problems.len());
+            for problem in problems.iter().take(20) {
+                println!("  {}", problem);
+            }
+        }
+    }
+
+    if !problems.is_empty() {
+        std::process::exit(1);
+    }
+}
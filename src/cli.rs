@@ -0,0 +1,335 @@
+//! Offline subcommands of the server binary.
+//!
+//! Each command opens the data directory through [`CueMap`], does its work and
+//! exits, so scripts can load, dump and inspect a store without the HTTP API.
+//! Do not point them at a directory a running server is using: the server's
+//! next snapshot overwrites whatever an import wrote.
+
+use crate::config::{REPROPOSAL_DEFAULT_RATE, REPROPOSAL_MAX_RATE};
+use crate::config_file::ConfigFile;
+use crate::embedded::{CueMap, GroundedQuery, RecallQuery};
+use crate::evals::golden::{self, EvalRun, EvalStore, GoldenSet};
+use crate::llm::LlmConfig;
+use crate::repl::Repl;
+use crate::reproposal::{self, ReproposalFilter};
+use crate::structures::Memory;
+use clap::Subcommand;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::future::Future;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Project the data directory's store is filed under, as in single-tenant mode.
+const EVAL_PROJECT: &str = "default";
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Add memories from a JSON Lines file (`-` for stdin) and snapshot
+    Import {
+        /// One object per line: `content`, `cues`, and optionally `id`,
+        /// `metadata` and `disable_temporal_chunking`. Lines written by
+        /// `export` are accepted as-is
+        file: PathBuf,
+    },
+    /// Write every memory as JSON Lines, oldest first
+    Export {
+        /// Output file (stdout when omitted)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Recall memories and print the results as JSON
+    Query {
+        /// Query cue (repeatable or comma-separated)
+        #[arg(short, long = "cue", value_delimiter = ',')]
+        cues: Vec<String>,
+        /// Free text resolved to cues through the lexicon
+        #[arg(short, long)]
+        text: Option<String>,
+        #[arg(short, long, default_value = "10")]
+        limit: usize,
+        /// Include per-result score explanations
+        #[arg(long)]
+        explain: bool,
+        /// Select context for `--text` under a token budget, as /recall/grounded
+        #[arg(long, requires = "text")]
+        grounded: bool,
+        #[arg(long, default_value = "500")]
+        token_budget: u32,
+    },
+    /// Print store statistics and estimated memory usage as JSON
+    Stats,
+    /// Run golden sets against the store and record their scores
+    Eval {
+        /// Stored set to run (all stored sets when omitted)
+        #[arg(short, long, conflicts_with = "file")]
+        set: Option<String>,
+        /// Run a golden set from a JSON file (`{"name", "queries"}`) instead
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+    },
+    /// Ask the LLM again for cues for existing memories and attach the new
+    /// ones, then snapshot. Progress is reported on stderr
+    Repropose {
+        /// Only memories holding this cue
+        #[arg(long)]
+        filter: Option<String>,
+        /// Only memories with at most this many cues
+        #[arg(long)]
+        max_cues: Option<usize>,
+        /// LLM proposals per second
+        #[arg(long, default_value_t = REPROPOSAL_DEFAULT_RATE)]
+        rate: f64,
+    },
+    /// Interactive shell for adding, recalling and explaining memories
+    Repl {
+        /// File to keep command history in across sessions
+        #[arg(long)]
+        history: Option<PathBuf>,
+        /// Leave the data directory untouched on exit
+        #[arg(long)]
+        no_save: bool,
+    },
+    /// Inspect a TOML configuration file
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigCommand {
+    /// Parse a config file and check its settings without starting the server
+    Validate {
+        file: PathBuf,
+    },
+}
+
+/// A memory to import; also matches the lines `export` writes.
+#[derive(Debug, Deserialize)]
+struct ImportRecord {
+    #[serde(default)]
+    id: Option<String>,
+    content: String,
+    #[serde(default)]
+    cues: Vec<String>,
+    #[serde(default)]
+    metadata: Option<HashMap<String, serde_json::Value>>,
+    #[serde(default)]
+    disable_temporal_chunking: bool,
+}
+
+/// Run `command` against `data_dir`, writing its output to `out`.
+pub fn run(command: Command, data_dir: &Path, out: &mut dyn Write) -> Result<(), String> {
+    if let Command::Config { action: ConfigCommand::Validate { file } } = &command {
+        return validate_config(file, out);
+    }
+    if !matches!(command, Command::Import { .. } | Command::Repl { .. }) && !data_dir.is_dir() {
+        return Err(format!("data directory {:?} does not exist", data_dir));
+    }
+    let cuemap = CueMap::open(data_dir).map_err(|e| e.to_string())?;
+
+    match command {
+        Command::Import { file } => import(&cuemap, &file, out),
+        Command::Export { output } => match output {
+            Some(path) => {
+                let file = fs::File::create(&path).map_err(|e| format!("cannot create {:?}: {}", path, e))?;
+                let mut writer = BufWriter::new(file);
+                let count = export(&cuemap, &mut writer)?;
+                writer.flush().map_err(|e| e.to_string())?;
+                print_json(out, &serde_json::json!({"exported": count, "output": path}))
+            }
+            None => export(&cuemap, out).map(|_| ()),
+        },
+        Command::Query { cues, text, limit, explain, grounded, token_budget } => {
+            if grounded {
+                let mut query = GroundedQuery::new(text.unwrap_or_default());
+                query.limit = limit;
+                query.token_budget = token_budget;
+                let grounded = cuemap.recall_grounded(&query);
+                return print_json(out, &serde_json::json!({
+                    "verified_context": grounded.verified_context,
+                    "proof": grounded.proof,
+                }));
+            }
+            if cues.is_empty() && text.is_none() {
+                return Err("query needs --cue or --text".to_string());
+            }
+            let results = cuemap.recall_with(&RecallQuery {
+                cues,
+                query_text: text,
+                limit,
+                explain,
+                ..Default::default()
+            });
+            print_json(out, &serde_json::json!({"results": results}))
+        }
+        Command::Stats => {
+            let mut stats = serde_json::Map::new();
+            stats.extend(cuemap.project().main.get_stats());
+            stats.insert("memory_usage".to_string(), serde_json::json!(cuemap.project().memory_usage()));
+            print_json(out, &serde_json::Value::Object(stats))
+        }
+        Command::Eval { set, file } => eval(&cuemap, data_dir, set, file, out),
+        Command::Repropose { filter, max_cues, rate } => {
+            repropose(&cuemap, ReproposalFilter { cue: filter, max_cues }, rate, out)
+        }
+        Command::Repl { history, no_save } => Repl::new(cuemap).run(history, !no_save),
+        Command::Config { .. } => unreachable!("handled before opening the data directory"),
+    }
+}
+
+/// Parse every line before adding anything, so a malformed file imports nothing.
+fn import(cuemap: &CueMap, file: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let reader: Box<dyn BufRead> = if file == Path::new("-") {
+        Box::new(BufReader::new(io::stdin()))
+    } else {
+        let f = fs::File::open(file).map_err(|e| format!("cannot open {:?}: {}", file, e))?;
+        Box::new(BufReader::new(f))
+    };
+
+    let mut records = Vec::new();
+    for (n, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| format!("line {}: {}", n + 1, e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: ImportRecord = serde_json::from_str(&line).map_err(|e| format!("line {}: {}", n + 1, e))?;
+        records.push(record);
+    }
+
+    let mut rejected_cues = 0;
+    let mut flagged_cues = 0;
+    for (n, record) in records.iter().enumerate() {
+        let outcome = match &record.id {
+            Some(id) => cuemap.upsert(id.as_str(), record.content.as_str(), record.cues.iter().cloned(), record.metadata.clone()),
+            None => cuemap.add_with(record.content.as_str(), record.cues.iter().cloned(), record.metadata.clone(), record.disable_temporal_chunking),
+        }
+        .map_err(|e| format!("record {}: {}", n + 1, e))?;
+        rejected_cues += outcome.rejected.len();
+        flagged_cues += outcome.flagged.len();
+    }
+    cuemap.snapshot().map_err(|e| e.to_string())?;
+
+    print_json(out, &serde_json::json!({
+        "imported": records.len(),
+        "rejected_cues": rejected_cues,
+        "flagged_cues": flagged_cues,
+        "total_memories": cuemap.len(),
+    }))
+}
+
+/// Run golden sets of the store's project and append them to its run history.
+fn eval(cuemap: &CueMap, data_dir: &Path, set: Option<String>, file: Option<PathBuf>, out: &mut dyn Write) -> Result<(), String> {
+    let store = EvalStore::open(data_dir);
+    let sets = match (file, set) {
+        (Some(file), _) => {
+            let data = fs::read(&file).map_err(|e| format!("cannot open {:?}: {}", file, e))?;
+            let set: GoldenSet = serde_json::from_slice(&data).map_err(|e| format!("{:?}: {}", file, e))?;
+            let problems = set.validate();
+            if !problems.is_empty() {
+                return Err(format!("{:?}: {}", file, problems.join("; ")));
+            }
+            vec![set]
+        }
+        (None, Some(name)) => vec![store.get_set(EVAL_PROJECT, &name).ok_or_else(|| format!("no eval set named {:?}", name))?],
+        (None, None) => store.sets(EVAL_PROJECT),
+    };
+    if sets.is_empty() {
+        return Err("no eval sets stored; pass --file".to_string());
+    }
+
+    let runs: Vec<EvalRun> = sets
+        .iter()
+        .map(|set| {
+            let run = golden::run_set(EVAL_PROJECT, cuemap.project(), set);
+            store.record(&run);
+            run
+        })
+        .collect();
+    print_json(out, &serde_json::json!({"runs": runs}))
+}
+
+/// Report every problem at once; fails when there is any.
+fn repropose(cuemap: &CueMap, filter: ReproposalFilter, rate: f64, out: &mut dyn Write) -> Result<(), String> {
+    if !(rate > 0.0 && rate <= REPROPOSAL_MAX_RATE) {
+        return Err(format!("--rate must be above 0 and at most {}", REPROPOSAL_MAX_RATE));
+    }
+    let config = LlmConfig::from_env().ok_or("repropose needs an LLM; set LLM_PROVIDER")?;
+    let memory_ids = filter.select(cuemap.project());
+    // Roughly every 5%, so large stores don't flood the terminal
+    let every = (memory_ids.len() / 20).max(1);
+    let counts = block_on(reproposal::run(cuemap.project(), &memory_ids, &config, rate, |counts| {
+        if counts.processed % every == 0 || counts.processed == counts.total {
+            eprintln!(
+                "repropose: {}/{} memories, {} cues attached, {} failed",
+                counts.processed, counts.total, counts.attached, counts.failed
+            );
+        }
+    }))?;
+    cuemap.snapshot().map_err(|e| e.to_string())?;
+
+    print_json(out, &serde_json::json!({
+        "filter": filter,
+        "rate": rate,
+        "total": counts.total,
+        "processed": counts.processed,
+        "attached": counts.attached,
+        "failed": counts.failed,
+    }))
+}
+
+/// Drive `future` on a runtime of its own. It runs on a separate thread so
+/// this also works when called from inside the server's runtime.
+fn block_on<F>(future: F) -> Result<F::Output, String>
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().map_err(|e| e.to_string())?;
+                Ok(runtime.block_on(future))
+            })
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+fn validate_config(file: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let config = ConfigFile::load(file)?;
+    let problems = config.validate();
+    print_json(out, &serde_json::json!({
+        "file": file,
+        "valid": problems.is_empty(),
+        "problems": problems,
+    }))?;
+    match problems.len() {
+        0 => Ok(()),
+        n => Err(format!("{} problem(s) in {:?}", n, file)),
+    }
+}
+
+fn export(cuemap: &CueMap, out: &mut dyn Write) -> Result<usize, String> {
+    let mut memories: Vec<Memory> = cuemap
+        .project()
+        .main
+        .get_memories()
+        .iter()
+        .map(|entry| entry.value().clone())
+        .collect();
+    memories.sort_by(|a, b| a.created_at.total_cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+
+    for memory in &memories {
+        let line = serde_json::to_string(memory).map_err(|e| e.to_string())?;
+        writeln!(out, "{}", line).map_err(|e| e.to_string())?;
+    }
+    Ok(memories.len())
+}
+
+fn print_json(out: &mut dyn Write, value: &serde_json::Value) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    writeln!(out, "{}", text).map_err(|e| e.to_string())
+}
@@ -0,0 +1,87 @@
+//! Collections: named groups of memories within a project.
+//!
+//! A memory belongs to at most one collection (`Memory::collection`), e.g.
+//! "runbooks" or "chat-history". The project keeps the registry of its
+//! collections with the rest of its config. A recall's [`CollectionFilter`]
+//! targets or excludes collections while candidates are gathered, before
+//! scoring, so excluded memories never take a slot of the `limit`.
+
+use serde::{Deserialize, Serialize};
+
+/// Longest accepted collection name, in bytes.
+pub const MAX_COLLECTION_NAME_LEN: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Collection {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Seconds since the epoch
+    pub created_at: f64,
+}
+
+impl Collection {
+    pub fn new(name: String, description: Option<String>) -> Self {
+        Self { name, description, created_at: now_secs() }
+    }
+}
+
+/// Why a collection change was refused.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CollectionError {
+    /// The name is empty, too long or has characters other than ASCII
+    /// letters, digits, `-`, `_` and `.`
+    InvalidName(String),
+    AlreadyExists,
+    NotFound,
+}
+
+/// Check a collection name; the error says what is wrong with it.
+pub fn validate_name(name: &str) -> Result<(), CollectionError> {
+    let problem = if name.is_empty() {
+        "collection name is empty".to_string()
+    } else if name.len() > MAX_COLLECTION_NAME_LEN {
+        format!("collection name is longer than {} bytes", MAX_COLLECTION_NAME_LEN)
+    } else if let Some(c) = name.chars().find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))) {
+        format!("collection name has invalid character '{}'", c)
+    } else {
+        return Ok(());
+    };
+    Err(CollectionError::InvalidName(problem))
+}
+
+/// Which collections a recall may return memories from.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CollectionFilter {
+    /// Only memories in one of these collections; empty allows any
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Never memories in these collections
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl CollectionFilter {
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Whether a memory in `collection` (`None` for none) passes the filter.
+    /// Memories outside any collection only pass filters without `include`.
+    pub fn allows(&self, collection: Option<&str>) -> bool {
+        match collection {
+            Some(collection) => {
+                (self.include.is_empty() || self.include.iter().any(|c| c == collection))
+                    && !self.exclude.iter().any(|c| c == collection)
+            }
+            None => self.include.is_empty(),
+        }
+    }
+}
+
+fn now_secs() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
@@ -1,18 +1,28 @@
 /// Performance tuning configuration for CueMap engine
 
+use dashmap::DashMap;
+use serde::Serialize;
+use std::hash::Hash;
+use std::sync::OnceLock;
+use std::time::Duration;
+
 // Search configuration
 pub const MAX_DRIVER_SCAN: usize = 10000;
+// Candidates probed per recall before scanning stops (requests may override)
+pub const MAX_RECALL_CANDIDATES: usize = 50_000;
+// With approximate early exit, stop scanning further cue lists once the top
+// `limit` candidates each match at least this much more cue weight than any
+// unscanned candidate could. Salience, recency and frequency can outweigh it.
+pub const EARLY_TERMINATION_WEIGHT_MARGIN: f64 = 1.0;
 pub const MAX_SEARCH_DEPTH: usize = 5000; // Deprecated, but keeping for compatibility/reference
 
 // DashMap shard configuration (power of 2)
 // Higher = less contention but more memory
-// Default is 64, we can tune based on workload
+// Default is 64, we can tune based on workload (override with --dashmap-shards)
 pub const DASHMAP_SHARD_COUNT: usize = 128;
 
 // Pre-allocation hints
-#[allow(dead_code)]
 pub const EXPECTED_CUES_PER_MEMORY: usize = 4;
-#[allow(dead_code)]
 pub const EXPECTED_MEMORIES_PER_CUE: usize = 100;
 
 // Alias Proposal Configuration
@@ -22,4 +32,440 @@ pub const ALIAS_MAX_CANDIDATES: usize = 1500;
 pub const ALIAS_SIZE_SIMILARITY_MAX_RATIO: f64 = 0.10;
 pub const ALIAS_OVERLAP_THRESHOLD: f64 = 0.90;
 pub const ALIAS_SAMPLE_SIZE: usize = 512;
+pub const ALIAS_CANDIDATES_DEFAULT_LIMIT: usize = 100; // Pairs listed by GET /admin/alias-candidates unless `limit` is given
+
+// Alias Expansion
+pub const ALIAS_MAX_DEPTH: usize = 3; // Alias hops followed from a query cue unless set at startup
+
+// Alias Expiry (active aliases reverted to proposed by the maintenance job)
+pub const ALIAS_IDLE_DAYS: u64 = 30; // Aliases whose target matched nothing for this long expire
+pub const ALIAS_MIN_FEEDBACK: u64 = 10; // Judged memories needed before feedback can expire an alias
+pub const ALIAS_MIN_PRECISION: f64 = 0.5; // Useful share of judged memories an alias must keep
+
+// Memory Consolidation (LLM-summarized merges)
+pub const CONSOLIDATION_CUE_OVERLAP: f64 = 0.6; // Jaccard similarity of cue sets
+pub const CONSOLIDATION_MAX_GROUPS: usize = 50; // Groups summarized per job run
+
+// Maintenance (forgetting curve and cue pruning)
+pub const DECAY_HALF_LIFE_DAYS: u64 = 30; // Reinforcement counts halve this often
+pub const STALE_MEMORY_DAYS: u64 = 90; // Memories not accessed for this long are demoted
+pub const STALE_SALIENCE_PENALTY: f64 = 0.1; // Salience removed per maintenance run
+
+// Stale Memory Digest (review list of memories going stale)
+pub const STALE_DIGEST_LOOKAHEAD_DAYS: u64 = 7; // Memories this close to STALE_MEMORY_DAYS are listed too
+pub const STALE_DIGEST_MAX_ENTRIES: usize = 200; // Longest idle first beyond this
+pub const STALE_DIGEST_PREVIEW_CHARS: usize = 120; // Content shown per listed memory
+
+// Subgraph Export (GET /export/subgraph)
+pub const SUBGRAPH_DEFAULT_DEPTH: usize = 1; // Hops taken when the request names none
+pub const SUBGRAPH_MAX_DEPTH: usize = 4; // Deeper requests are refused with 400
+pub const SUBGRAPH_MAX_NODES: usize = 5_000; // Cue and memory nodes; the export is marked truncated beyond this
+
+// Lexicon Rebuild (full retraining from every memory)
+pub const LEXICON_REBUILD_BATCH_SIZE: usize = 500; // Memories retrained between pauses
+pub const LEXICON_REBUILD_BATCH_PAUSE_MS: u64 = 20; // Pause after each batch so recalls keep their share
+
+// Cue Reproposal (POST /admin/jobs/reproposal)
+pub const REPROPOSAL_DEFAULT_RATE: f64 = 2.0; // LLM proposals per second when the request names no rate
+pub const REPROPOSAL_MAX_RATE: f64 = 50.0; // Faster requests are refused with 400
+
+// Embedding Rerank (optional second stage over cue recall)
+pub const RERANK_TOP_N: usize = 20; // Cue-ranked candidates embedded per query
+pub const RERANK_SIMILARITY_WEIGHT: f64 = 0.3; // Share of the final score from cosine similarity
+
+// Recall Batches (POST /recall/batch)
+pub const RECALL_BATCH_MAX_QUERIES: usize = 64; // Larger batches are refused with 400
+
+// Recall Scoring
+pub const INTERSECTION_SCORE_SCALE: f64 = 100.0;
+pub const SALIENCE_SCORE_SCALE: f64 = 10.0;
+pub const MAX_RECENCY_WEIGHT: f64 = 20.0;
+pub const MAX_FREQUENCY_WEIGHT: f64 = 5.0;
+pub const PATTERN_COMPLETION_WEIGHT: f64 = 0.7; // Weight for inferred cues
+pub const PATTERN_COMPLETION_TOP_K: usize = 5;
+
+// Recall Feedback
+pub const NEGATIVE_FEEDBACK_PENALTY: f64 = 0.2; // Salience removed per harmful report
+
+// Cue Index Postings
+// Cue lists longer than this switch to a roaring bitmap for older members
+pub const POSTINGS_BITMAP_THRESHOLD: usize = 50_000;
+// Newest members kept in exact recency order once a list is a bitmap
+pub const POSTINGS_RECENT_RING: usize = 4_096;
+
+// Text Resolution (query text -> cues through the lexicon)
+pub const LEXICON_MIN_RELATIVE_SCORE: f64 = 0.5; // Lexicon hits scoring under this share of the best are dropped
+
+// Spell Correction (unknown query words -> closest lexicon token)
+pub const SPELL_MIN_WORD_LEN: usize = 4; // Shorter words are never corrected
+pub const SPELL_LONG_WORD_LEN: usize = 8; // Words this long may be two edits off, shorter ones one
+
+// Rejected-Cue Quarantine (per project)
+pub const REJECTION_QUARANTINE_CAPACITY: usize = 10_000; // Oldest rejections are dropped beyond this
+
+// Agent Ingestion Errors (GET /agent/errors)
+pub const AGENT_QUARANTINE_FAILURES: u32 = 3; // Failed ingests in a row before a file is skipped until it changes
+pub const AGENT_ERRORS_PER_FILE: usize = 5; // Most recent errors kept per file
+
+// Eval Run History (eval_runs.jsonl)
+pub const EVAL_RUNS_PER_SET: usize = 500; // Most recent runs kept per project and golden set
+
+// Readiness Probe (GET /readyz?llm=true; probes skip auth)
+pub const LLM_READINESS_CACHE_SECS: u64 = 30; // A provider check is reused this long before the provider is called again
+
+// Data Directory Lock (cuemap.lock, held by the process that snapshots the directory)
+pub const DATA_DIR_LOCK_HEARTBEAT_SECS: u64 = 5; // How often the holder refreshes its heartbeat
+pub const DATA_DIR_LOCK_STALE_SECS: u64 = 30; // A lock without a heartbeat for this long is taken over
+
+// Request Limits (HTTP API; each can be overridden at startup)
+pub const MAX_BODY_BYTES: usize = 2 * 1024 * 1024; // Larger request bodies are refused with 413
+pub const MAX_CONTENT_BYTES: usize = 256 * 1024; // Longest memory content accepted
+pub const MAX_CUES_PER_MEMORY: usize = 256; // Cues accepted on one memory, before normalization
+pub const MAX_RECALL_LIMIT: usize = 1_000; // Largest `limit` a recall may ask for
+
+// Route Budgets (per-route time limit and requests in flight; beyond them 408/503)
+pub const QUERY_ROUTE_TIMEOUT_MS: u64 = 10_000; // Recall, grounded recall and citation checks
+pub const QUERY_ROUTE_MAX_CONCURRENT: usize = 256;
+pub const ADMIN_ROUTE_TIMEOUT_MS: u64 = 120_000; // Eval runs, forget, replica and memory usage
+pub const ADMIN_ROUTE_MAX_CONCURRENT: usize = 4;
+
+// Response Compression (gzip/brotli, when the client accepts it)
+pub const COMPRESSION_MIN_BYTES: u16 = 1024; // Smaller responses are sent as-is (override with --compression-min-bytes)
+
+// Query Cache (text -> resolved cues, per project)
+pub const QUERY_CACHE_CAPACITY: usize = 10_000;
+pub const QUERY_CACHE_TTL_SECS: u64 = 600;
+
+// Recall Cache (recall responses, per project; off unless --recall-cache-ttl-ms is set)
+pub const RECALL_CACHE_CAPACITY: usize = 1_000;
+
+// Memory Usage Estimation
+// Hash tables keep spare capacity and per-slot control bytes; this factor
+// approximates that on top of the raw entry sizes.
+pub const HASH_TABLE_OVERHEAD_FACTOR: f64 = 1.3;
+// Per-entry bookkeeping of an IndexSet (stored hash + index slot)
+pub const INDEX_SET_ENTRY_OVERHEAD_BYTES: usize = 16;
+
+/// Hash map sizing for engines, fixed once at startup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct EngineSizing {
+    /// DashMap shard count (power of two, at least 2)
+    pub shard_count: usize,
+    /// Memories to pre-allocate for when a store is created empty
+    pub expected_memories: usize,
+}
+
+impl Default for EngineSizing {
+    fn default() -> Self {
+        Self {
+            shard_count: DASHMAP_SHARD_COUNT,
+            expected_memories: 0,
+        }
+    }
+}
+
+impl EngineSizing {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.shard_count < 2 || !self.shard_count.is_power_of_two() {
+            return Err(format!("shard count must be a power of two >= 2, got {}", self.shard_count));
+        }
+        Ok(())
+    }
+
+    /// Distinct cues expected for `expected_memories`, from the pre-allocation hints.
+    pub fn expected_cues(&self) -> usize {
+        self.expected_memories * EXPECTED_CUES_PER_MEMORY / EXPECTED_MEMORIES_PER_CUE
+    }
+
+    /// A DashMap with this sizing's shard count and the given capacity.
+    pub fn dashmap<K: Eq + Hash, V>(&self, capacity: usize) -> DashMap<K, V> {
+        DashMap::with_capacity_and_shard_amount(capacity, self.shard_count)
+    }
+}
+
+static ENGINE_SIZING: OnceLock<EngineSizing> = OnceLock::new();
+
+/// Set the process-wide engine sizing. Must happen before any engine is built;
+/// fails if the sizing is invalid or was already set.
+pub fn set_engine_sizing(sizing: EngineSizing) -> Result<(), String> {
+    sizing.validate()?;
+    ENGINE_SIZING
+        .set(sizing)
+        .map_err(|_| "engine sizing already initialized".to_string())
+}
+
+/// Process-wide engine sizing (defaults unless set at startup).
+pub fn engine_sizing() -> EngineSizing {
+    *ENGINE_SIZING.get_or_init(EngineSizing::default)
+}
+
+static READ_VIEW_REFRESH: OnceLock<Duration> = OnceLock::new();
+
+/// Make engines serve recalls from a published index view refreshed every
+/// `interval`. Must happen before any engine is built; fails if already set.
+pub fn set_read_view_refresh(interval: Duration) -> Result<(), String> {
+    if interval.is_zero() {
+        return Err("read view refresh interval must be positive".to_string());
+    }
+    READ_VIEW_REFRESH
+        .set(interval)
+        .map_err(|_| "read view refresh already initialized".to_string())
+}
+
+/// Refresh interval of published read views, or `None` when recalls read the
+/// live index (the default).
+pub fn read_view_refresh() -> Option<Duration> {
+    READ_VIEW_REFRESH.get().copied()
+}
+
+static RECALL_CACHE_TTL: OnceLock<Duration> = OnceLock::new();
+
+/// Cache recall responses for `ttl`, until any write to their project. Must
+/// happen before any project is built; fails if already set.
+pub fn set_recall_cache_ttl(ttl: Duration) -> Result<(), String> {
+    if ttl.is_zero() {
+        return Err("recall cache TTL must be positive".to_string());
+    }
+    RECALL_CACHE_TTL
+        .set(ttl)
+        .map_err(|_| "recall cache TTL already initialized".to_string())
+}
+
+/// How long recall responses are cached, or `None` when they are not (the
+/// default).
+pub fn recall_cache_ttl() -> Option<Duration> {
+    RECALL_CACHE_TTL.get().copied()
+}
+
+static ALIAS_MAX_DEPTH_SETTING: OnceLock<usize> = OnceLock::new();
+
+/// Set how many alias hops query expansion follows (1 = direct aliases only,
+/// 0 = none). Fails if already set.
+pub fn set_alias_max_depth(depth: usize) -> Result<(), String> {
+    ALIAS_MAX_DEPTH_SETTING
+        .set(depth)
+        .map_err(|_| "alias max depth already initialized".to_string())
+}
+
+/// Alias hops followed by query expansion ([`ALIAS_MAX_DEPTH`] unless set at startup).
+pub fn alias_max_depth() -> usize {
+    *ALIAS_MAX_DEPTH_SETTING.get_or_init(|| ALIAS_MAX_DEPTH)
+}
+
+static APPROXIMATE_EARLY_EXIT: OnceLock<bool> = OnceLock::new();
+
+/// Let recalls stop scanning cue lists early, which may miss a memory whose
+/// salience, recency or frequency would have ranked it in the top `limit`.
+/// Must happen before any project is built; fails if already set.
+pub fn set_approximate_early_exit(enabled: bool) -> Result<(), String> {
+    APPROXIMATE_EARLY_EXIT
+        .set(enabled)
+        .map_err(|_| "approximate early exit already initialized".to_string())
+}
+
+/// Whether projects' main engines use approximate early exit (off by default).
+pub fn approximate_early_exit() -> bool {
+    APPROXIMATE_EARLY_EXIT.get().copied().unwrap_or(false)
+}
+
+static COMPRESSION_MIN_BYTES_SETTING: OnceLock<u16> = OnceLock::new();
+
+/// Set the response size above which HTTP responses are compressed (0 turns
+/// compression off). Must happen before the router is built; fails if already set.
+pub fn set_compression_min_bytes(min_bytes: u16) -> Result<(), String> {
+    COMPRESSION_MIN_BYTES_SETTING
+        .set(min_bytes)
+        .map_err(|_| "compression threshold already initialized".to_string())
+}
+
+/// Smallest compressed response size, or `None` when compression is off
+/// ([`COMPRESSION_MIN_BYTES`] unless set at startup).
+pub fn compression_min_bytes() -> Option<u16> {
+    Some(*COMPRESSION_MIN_BYTES_SETTING.get_or_init(|| COMPRESSION_MIN_BYTES)).filter(|min_bytes| *min_bytes > 0)
+}
+
+/// Size limits enforced on HTTP requests, fixed once at startup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RequestLimits {
+    pub max_body_bytes: usize,
+    pub max_content_bytes: usize,
+    pub max_cues_per_memory: usize,
+    pub max_recall_limit: usize,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: MAX_BODY_BYTES,
+            max_content_bytes: MAX_CONTENT_BYTES,
+            max_cues_per_memory: MAX_CUES_PER_MEMORY,
+            max_recall_limit: MAX_RECALL_LIMIT,
+        }
+    }
+}
+
+impl RequestLimits {
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("max body bytes", self.max_body_bytes),
+            ("max content bytes", self.max_content_bytes),
+            ("max cues per memory", self.max_cues_per_memory),
+            ("max recall limit", self.max_recall_limit),
+        ] {
+            if value == 0 {
+                return Err(format!("{} must be positive", name));
+            }
+        }
+        if self.max_content_bytes > self.max_body_bytes {
+            return Err(format!(
+                "max content bytes ({}) cannot exceed max body bytes ({})",
+                self.max_content_bytes, self.max_body_bytes
+            ));
+        }
+        Ok(())
+    }
+}
+
+static REQUEST_LIMITS: OnceLock<RequestLimits> = OnceLock::new();
+
+/// Set the process-wide request limits. Must happen before the router is
+/// built; fails if the limits are invalid or were already set.
+pub fn set_request_limits(limits: RequestLimits) -> Result<(), String> {
+    limits.validate()?;
+    REQUEST_LIMITS
+        .set(limits)
+        .map_err(|_| "request limits already initialized".to_string())
+}
+
+/// Process-wide request limits (defaults unless set at startup).
+pub fn request_limits() -> RequestLimits {
+    *REQUEST_LIMITS.get_or_init(RequestLimits::default)
+}
+
+/// Startup settings resolved from CLI flags and the environment, reported by
+/// `GET /admin/config`. Installed as a router extension by the server binary.
+#[derive(Clone, Debug, Default)]
+pub struct RuntimeSettings(pub serde_json::Value);
 
+/// Compiled-in tuning constants, reported by `GET /admin/config`.
+pub fn tuning_constants() -> serde_json::Value {
+    serde_json::json!({
+        "search": {
+            "max_driver_scan": MAX_DRIVER_SCAN,
+            "max_recall_candidates": MAX_RECALL_CANDIDATES,
+            "early_termination_weight_margin": EARLY_TERMINATION_WEIGHT_MARGIN,
+            "dashmap_shard_count": DASHMAP_SHARD_COUNT,
+        },
+        "scoring": {
+            "intersection_score_scale": INTERSECTION_SCORE_SCALE,
+            "salience_score_scale": SALIENCE_SCORE_SCALE,
+            "max_recency_weight": MAX_RECENCY_WEIGHT,
+            "max_frequency_weight": MAX_FREQUENCY_WEIGHT,
+            "pattern_completion_weight": PATTERN_COMPLETION_WEIGHT,
+            "pattern_completion_top_k": PATTERN_COMPLETION_TOP_K,
+        },
+        "alias_proposal": {
+            "min_cue_memories": ALIAS_MIN_CUE_MEMORIES,
+            "max_cue_memories": ALIAS_MAX_CUE_MEMORIES,
+            "max_candidates": ALIAS_MAX_CANDIDATES,
+            "size_similarity_max_ratio": ALIAS_SIZE_SIMILARITY_MAX_RATIO,
+            "overlap_threshold": ALIAS_OVERLAP_THRESHOLD,
+            "sample_size": ALIAS_SAMPLE_SIZE,
+            "candidates_default_limit": ALIAS_CANDIDATES_DEFAULT_LIMIT,
+        },
+        "alias_expansion": {
+            "max_depth": ALIAS_MAX_DEPTH,
+        },
+        "alias_expiry": {
+            "idle_days": ALIAS_IDLE_DAYS,
+            "min_feedback": ALIAS_MIN_FEEDBACK,
+            "min_precision": ALIAS_MIN_PRECISION,
+        },
+        "consolidation": {
+            "cue_overlap": CONSOLIDATION_CUE_OVERLAP,
+            "max_groups": CONSOLIDATION_MAX_GROUPS,
+        },
+        "maintenance": {
+            "decay_half_life_days": DECAY_HALF_LIFE_DAYS,
+            "stale_memory_days": STALE_MEMORY_DAYS,
+            "stale_salience_penalty": STALE_SALIENCE_PENALTY,
+        },
+        "stale_digest": {
+            "lookahead_days": STALE_DIGEST_LOOKAHEAD_DAYS,
+            "max_entries": STALE_DIGEST_MAX_ENTRIES,
+            "preview_chars": STALE_DIGEST_PREVIEW_CHARS,
+        },
+        "subgraph": {
+            "default_depth": SUBGRAPH_DEFAULT_DEPTH,
+            "max_depth": SUBGRAPH_MAX_DEPTH,
+            "max_nodes": SUBGRAPH_MAX_NODES,
+        },
+        "lexicon_rebuild": {
+            "batch_size": LEXICON_REBUILD_BATCH_SIZE,
+            "batch_pause_ms": LEXICON_REBUILD_BATCH_PAUSE_MS,
+        },
+        "reproposal": {
+            "default_rate": REPROPOSAL_DEFAULT_RATE,
+            "max_rate": REPROPOSAL_MAX_RATE,
+        },
+        "rerank": {
+            "top_n": RERANK_TOP_N,
+            "similarity_weight": RERANK_SIMILARITY_WEIGHT,
+        },
+        "recall_batch": {
+            "max_queries": RECALL_BATCH_MAX_QUERIES,
+        },
+        "text_resolution": {
+            "min_relative_score": LEXICON_MIN_RELATIVE_SCORE,
+        },
+        "spell_correction": {
+            "min_word_len": SPELL_MIN_WORD_LEN,
+            "long_word_len": SPELL_LONG_WORD_LEN,
+        },
+        "rejection_quarantine": {
+            "capacity": REJECTION_QUARANTINE_CAPACITY,
+        },
+        "evals": {
+            "runs_per_set": EVAL_RUNS_PER_SET,
+        },
+        "readiness": {
+            "llm_cache_secs": LLM_READINESS_CACHE_SECS,
+        },
+        "data_dir_lock": {
+            "heartbeat_secs": DATA_DIR_LOCK_HEARTBEAT_SECS,
+            "stale_secs": DATA_DIR_LOCK_STALE_SECS,
+        },
+        "agent_errors": {
+            "quarantine_failures": AGENT_QUARANTINE_FAILURES,
+            "errors_per_file": AGENT_ERRORS_PER_FILE,
+        },
+        "request_limits": {
+            "max_body_bytes": MAX_BODY_BYTES,
+            "max_content_bytes": MAX_CONTENT_BYTES,
+            "max_cues_per_memory": MAX_CUES_PER_MEMORY,
+            "max_recall_limit": MAX_RECALL_LIMIT,
+        },
+        "route_budgets": {
+            "query_timeout_ms": QUERY_ROUTE_TIMEOUT_MS,
+            "query_max_concurrent": QUERY_ROUTE_MAX_CONCURRENT,
+            "admin_timeout_ms": ADMIN_ROUTE_TIMEOUT_MS,
+            "admin_max_concurrent": ADMIN_ROUTE_MAX_CONCURRENT,
+        },
+        "compression": {
+            "min_bytes": COMPRESSION_MIN_BYTES,
+        },
+        "query_cache": {
+            "capacity": QUERY_CACHE_CAPACITY,
+            "ttl_secs": QUERY_CACHE_TTL_SECS,
+        },
+        "recall_cache": {
+            "capacity": RECALL_CACHE_CAPACITY,
+        },
+        "feedback": {
+            "negative_feedback_penalty": NEGATIVE_FEEDBACK_PENALTY,
+        },
+        "grounding": {
+            "freshness_half_life_days": crate::grounding::FRESHNESS_HALF_LIFE_DAYS,
+        },
+    })
+}
@@ -0,0 +1,244 @@
+//! TOML configuration file for the server (`--config cuemap.toml`).
+//!
+//! Every section and key is optional. Values are applied with the precedence
+//! CLI flag > environment variable > config file > built-in default, so a
+//! checked-in file describes a deployment and flags or env vars override it
+//! per host:
+//!
+//! ```toml
+//! [server]
+//! port = 8080
+//! data_dir = "/var/lib/cuemap"
+//! snapshot_interval = 60
+//!
+//! [tls]
+//! cert = "/etc/cuemap/cert.pem"
+//! key = "/etc/cuemap/key.pem"
+//!
+//! [network]
+//! allow_cidr = ["10.0.0.0/8"]
+//!
+//! [auth]
+//! api_keys = ["reader-key:ro", "admin-key:admin"]
+//!
+//! [llm]
+//! provider = "ollama"
+//! model = "mistral"
+//!
+//! [agent]
+//! dir = "./docs"
+//! cues = ["repo:docs"]
+//! ```
+
+use crate::agent::cue_policy::{CuePolicy, DirectoryRoute};
+use crate::config::{EngineSizing, RequestLimits};
+use crate::ip_filter::IpFilter;
+use crate::telemetry::LogFormat;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+pub const LLM_PROVIDERS: [&str; 3] = ["ollama", "openai", "google"];
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub server: ServerSection,
+    pub tls: TlsSection,
+    pub network: NetworkSection,
+    pub auth: AuthSection,
+    pub llm: LlmSection,
+    pub agent: AgentSection,
+}
+
+/// Settings that mirror the server's CLI flags of the same name.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerSection {
+    pub port: Option<u16>,
+    pub data_dir: Option<String>,
+    pub snapshot_interval: Option<u64>,
+    pub multi_tenant: Option<bool>,
+    pub load_static: Option<String>,
+    pub trace_retention: Option<usize>,
+    pub trace_retention_days: Option<u64>,
+    pub dashmap_shards: Option<usize>,
+    pub expected_memories: Option<usize>,
+    pub read_view_refresh_ms: Option<u64>,
+    pub recall_cache_ttl_ms: Option<u64>,
+    pub alias_max_depth: Option<usize>,
+    pub approximate_early_exit: Option<bool>,
+    pub max_body_bytes: Option<usize>,
+    pub max_content_bytes: Option<usize>,
+    pub max_cues_per_memory: Option<usize>,
+    pub max_recall_limit: Option<usize>,
+    pub compression_min_bytes: Option<u16>,
+    pub consolidate_interval: Option<u64>,
+    pub maintenance_interval: Option<u64>,
+    pub stale_digest_interval: Option<u64>,
+    pub grpc_port: Option<u16>,
+    pub shutdown_timeout: Option<u64>,
+    pub follow: Option<String>,
+    pub follow_interval: Option<u64>,
+    pub snapshot_store: Option<String>,
+    pub snapshot_store_reader: Option<bool>,
+    pub verify_on_start: Option<bool>,
+    pub rebuild_index: Option<bool>,
+    pub warm_up: Option<bool>,
+    pub shared_vocabulary: Option<bool>,
+    pub log_format: Option<LogFormat>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsSection {
+    pub cert: Option<String>,
+    pub key: Option<String>,
+    pub client_ca: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkSection {
+    pub allow_cidr: Option<Vec<String>>,
+    pub deny_cidr: Option<Vec<String>>,
+    pub trusted_proxy: Option<Vec<String>>,
+}
+
+/// Static API keys, in the `CUEMAP_API_KEYS` format (`key` or `key:scope`).
+/// Ignored when `CUEMAP_API_KEYS` or `CUEMAP_API_KEY` is set.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthSection {
+    pub api_keys: Option<Vec<String>>,
+}
+
+/// Fallbacks for the `LLM_*` and `OLLAMA_URL` environment variables.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LlmSection {
+    pub enabled: Option<bool>,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub api_key: Option<String>,
+    pub ollama_url: Option<String>,
+}
+
+/// Self-learning agent; mirrors the `--agent-*` flags.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AgentSection {
+    pub dir: Option<String>,
+    pub throttle_ms: Option<u64>,
+    pub reconcile_interval: Option<u64>,
+    pub cues: Option<Vec<String>>,
+    pub path_cues: Option<Vec<String>>,
+    pub blocked_keys: Option<Vec<String>>,
+    pub routes: Option<Vec<DirectoryRoute>>,
+}
+
+impl ConfigFile {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("cannot read {:?}: {}", path, e))?;
+        Self::parse(&text).map_err(|e| format!("{:?}: {}", path, e))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.to_string().trim_end().to_string())
+    }
+
+    /// Problems that would stop the server from starting or silently
+    /// misconfigure it. Empty when the file is usable.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if let Some(shard_count) = self.server.dashmap_shards {
+            let sizing = EngineSizing { shard_count, expected_memories: 0 };
+            if let Err(e) = sizing.validate() {
+                problems.push(format!("server.dashmap_shards: {}", e));
+            }
+        }
+
+        let defaults = RequestLimits::default();
+        let limits = RequestLimits {
+            max_body_bytes: self.server.max_body_bytes.unwrap_or(defaults.max_body_bytes),
+            max_content_bytes: self.server.max_content_bytes.unwrap_or(defaults.max_content_bytes),
+            max_cues_per_memory: self.server.max_cues_per_memory.unwrap_or(defaults.max_cues_per_memory),
+            max_recall_limit: self.server.max_recall_limit.unwrap_or(defaults.max_recall_limit),
+        };
+        if let Err(e) = limits.validate() {
+            problems.push(format!("server: {}", e));
+        }
+
+        if self.server.follow.is_some() && (self.server.multi_tenant == Some(true) || self.server.load_static.is_some()) {
+            problems.push("server.follow cannot be combined with server.multi_tenant or server.load_static".to_string());
+        }
+
+        match &self.server.snapshot_store {
+            Some(url) if !url.starts_with("s3://") => {
+                problems.push(format!("server.snapshot_store: unsupported URL '{}' (expected s3://bucket/prefix)", url));
+            }
+            Some(_) if self.server.multi_tenant == Some(true) || self.server.load_static.is_some() || self.server.follow.is_some() => {
+                problems.push("server.snapshot_store cannot be combined with server.multi_tenant, server.load_static or server.follow".to_string());
+            }
+            None if self.server.snapshot_store_reader == Some(true) => {
+                problems.push("server.snapshot_store_reader requires server.snapshot_store".to_string());
+            }
+            _ => {}
+        }
+
+        match (&self.tls.cert, &self.tls.key) {
+            (Some(_), None) => problems.push("tls.cert requires tls.key".to_string()),
+            (None, Some(_)) => problems.push("tls.key requires tls.cert".to_string()),
+            _ => {}
+        }
+        if self.tls.client_ca.is_some() && self.tls.cert.is_none() {
+            problems.push("tls.client_ca requires tls.cert".to_string());
+        }
+        for (name, path) in [("tls.cert", &self.tls.cert), ("tls.key", &self.tls.key), ("tls.client_ca", &self.tls.client_ca)] {
+            if let Some(path) = path {
+                if !Path::new(path).is_file() {
+                    problems.push(format!("{}: {} does not exist", name, path));
+                }
+            }
+        }
+
+        let empty = Vec::new();
+        if let Err(e) = IpFilter::new(
+            self.network.allow_cidr.as_ref().unwrap_or(&empty),
+            self.network.deny_cidr.as_ref().unwrap_or(&empty),
+            self.network.trusted_proxy.as_ref().unwrap_or(&empty),
+        ) {
+            problems.push(format!("network: {}", e));
+        }
+
+        for key in self.auth.api_keys.iter().flatten() {
+            if key.trim().is_empty() || key.contains(',') {
+                problems.push(format!("auth.api_keys: invalid key entry {:?}", key));
+            }
+        }
+
+        if let Some(provider) = &self.llm.provider {
+            if !LLM_PROVIDERS.contains(&provider.as_str()) {
+                problems.push(format!("llm.provider: unknown provider '{}' (expected one of {})", provider, LLM_PROVIDERS.join(", ")));
+            }
+        }
+
+        if let Some(dir) = &self.agent.dir {
+            if !Path::new(dir).is_dir() {
+                problems.push(format!("agent.dir: {} is not a directory", dir));
+            }
+        }
+        let cue_policy = CuePolicy {
+            static_cues: self.agent.cues.clone().unwrap_or_default(),
+            path_cues: self.agent.path_cues.clone().unwrap_or_default(),
+            blocked_keys: self.agent.blocked_keys.clone().unwrap_or_default(),
+            routes: self.agent.routes.clone().unwrap_or_default(),
+        };
+        for problem in cue_policy.validate() {
+            problems.push(format!("agent: {}", problem));
+        }
+
+        problems
+    }
+}
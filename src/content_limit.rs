@@ -0,0 +1,60 @@
+//! Keeping memory content within a per-project size (`GET`/`PUT /content-limit`).
+//!
+//! Content over `max_bytes` is refused with `413`, or with `"oversized":
+//! "summarize"` stored cut to `max_bytes` while an LLM job writes a summary
+//! to replace it. A shortened memory keeps what it was written with in
+//! `full_content`, served by `GET /memories/:id/full-content`, so recall and
+//! grounding budgets only see the short form. Without an LLM the cut content
+//! stays.
+
+use serde::{Deserialize, Serialize};
+
+/// What happens to content over the limit.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Oversized {
+    #[default]
+    Reject,
+    Summarize,
+}
+
+/// Per-project content size limit; no limit by default.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ContentLimit {
+    /// Largest content stored as written, in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<usize>,
+    #[serde(default)]
+    pub oversized: Oversized,
+}
+
+impl ContentLimit {
+    /// Reject limits no memory could be shortened to, or that the server's
+    /// `max_content_bytes` already enforces.
+    pub fn validate(&self, max_content_bytes: usize) -> Result<(), String> {
+        match self.max_bytes {
+            Some(max_bytes) if max_bytes == 0 || max_bytes > max_content_bytes => {
+                Err(format!("max_bytes must be between 1 and {}", max_content_bytes))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The limit `content` is over, if any.
+    pub fn exceeded_by(&self, content: &str) -> Option<usize> {
+        self.max_bytes.filter(|max_bytes| content.len() > *max_bytes)
+    }
+}
+
+/// The longest prefix of `content` within `max_bytes`, cut at a character
+/// boundary.
+pub fn truncate(content: &str, max_bytes: usize) -> &str {
+    if content.len() <= max_bytes {
+        return content;
+    }
+    let mut end = max_bytes;
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    &content[..end]
+}
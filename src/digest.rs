@@ -0,0 +1,114 @@
+//! Review digest of memories going stale.
+//!
+//! Maintenance demotes memories nobody accessed for [`STALE_MEMORY_DAYS`].
+//! The digest job lists those memories, and the ones that will cross the
+//! line within [`STALE_DIGEST_LOOKAHEAD_DAYS`], in a digest memory tagged
+//! [`DIGEST_CUE`] and publishes it as a `stale_digest` change event, so a
+//! human can reinforce what is still worth keeping. Each run replaces the
+//! previous digest memory.
+
+use crate::config::{STALE_DIGEST_LOOKAHEAD_DAYS, STALE_DIGEST_MAX_ENTRIES, STALE_DIGEST_PREVIEW_CHARS, STALE_MEMORY_DAYS};
+use crate::engine::CueMapEngine;
+use crate::events::ChangeEvent;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Cue of digest memories; they are never listed in a digest themselves.
+pub const DIGEST_CUE: &str = "type:stale_digest";
+/// Metadata key holding the [`StaleDigest`] of a digest memory.
+pub const DIGEST_METADATA_KEY: &str = "stale_digest";
+
+const DAY_SECS: f64 = 86_400.0;
+
+/// A memory listed for review.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DigestEntry {
+    pub memory_id: String,
+    /// Start of the content, at most [`STALE_DIGEST_PREVIEW_CHARS`] characters
+    pub preview: String,
+    pub idle_days: u64,
+    /// Already past [`STALE_MEMORY_DAYS`] and demoted by maintenance
+    pub stale: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StaleDigest {
+    /// Seconds since the epoch
+    pub generated_at: f64,
+    pub stale_after_days: u64,
+    /// Longest idle first
+    pub entries: Vec<DigestEntry>,
+    /// Memories that qualified but did not fit in [`STALE_DIGEST_MAX_ENTRIES`]
+    pub omitted: usize,
+}
+
+impl StaleDigest {
+    pub fn stale_count(&self) -> usize {
+        self.entries.iter().filter(|entry| entry.stale).count()
+    }
+
+    /// Text of the digest memory.
+    pub fn render(&self) -> String {
+        let stale = self.stale_count();
+        let mut text = format!(
+            "Stale memory review: {} not accessed for {}+ days, {} within {} days of it. Reinforce a memory to keep it from being demoted.",
+            stale,
+            self.stale_after_days,
+            self.entries.len() - stale,
+            STALE_DIGEST_LOOKAHEAD_DAYS
+        );
+        for entry in &self.entries {
+            text.push_str(&format!("\n- {} (idle {} days): {}", entry.memory_id, entry.idle_days, entry.preview));
+        }
+        if self.omitted > 0 {
+            text.push_str(&format!("\n... and {} more", self.omitted));
+        }
+        text
+    }
+}
+
+/// Memories of `engine` that are stale or will be within the lookahead,
+/// as of `now` (seconds since the epoch).
+pub fn compile(engine: &CueMapEngine, now: f64) -> StaleDigest {
+    let listed_after = now - (STALE_MEMORY_DAYS.saturating_sub(STALE_DIGEST_LOOKAHEAD_DAYS)) as f64 * DAY_SECS;
+    let mut idle: Vec<(f64, String, String)> = engine
+        .get_memories()
+        .iter()
+        .filter(|m| m.last_accessed < listed_after && !m.is_tombstoned())
+        .filter(|m| !m.cues.iter().any(|cue| &**cue == DIGEST_CUE))
+        .map(|m| (m.last_accessed, m.id.clone(), m.content.chars().take(STALE_DIGEST_PREVIEW_CHARS).collect()))
+        .collect();
+    idle.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+
+    let omitted = idle.len().saturating_sub(STALE_DIGEST_MAX_ENTRIES);
+    let entries = idle
+        .into_iter()
+        .take(STALE_DIGEST_MAX_ENTRIES)
+        .map(|(last_accessed, memory_id, preview)| {
+            let idle_days = ((now - last_accessed) / DAY_SECS).max(0.0) as u64;
+            DigestEntry { memory_id, preview, idle_days, stale: idle_days >= STALE_MEMORY_DAYS }
+        })
+        .collect();
+    StaleDigest { generated_at: now, stale_after_days: STALE_MEMORY_DAYS, entries, omitted }
+}
+
+/// Compile a digest and store it as the project's digest memory, replacing
+/// earlier ones. Returns the new memory's id, or `None` when nothing is
+/// going stale (earlier digests are still removed then).
+pub fn publish(engine: &CueMapEngine, now: f64) -> Option<(String, StaleDigest)> {
+    for previous in engine.cue_memory_ids(DIGEST_CUE, None) {
+        engine.delete_memory(&previous);
+    }
+    let digest = compile(engine, now);
+    if digest.entries.is_empty() {
+        return None;
+    }
+
+    let metadata = HashMap::from([(DIGEST_METADATA_KEY.to_string(), serde_json::json!(digest))]);
+    let memory_id = engine.add_memory(digest.render(), vec![DIGEST_CUE.to_string()], Some(metadata), true);
+    engine.events().publish(ChangeEvent::StaleDigest {
+        memory_id: memory_id.clone(),
+        memory_ids: digest.entries.iter().map(|entry| entry.memory_id.clone()).collect(),
+    });
+    Some((memory_id, digest))
+}
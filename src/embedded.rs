@@ -0,0 +1,510 @@
+//! Embedded use of CueMap, without the HTTP server.
+//!
+//! [`CueMap`] wraps a single project the same way the single-tenant server
+//! does: cues are normalized, language-tagged and validated against the
+//! project taxonomy on the way in, and queries are resolved, normalized and
+//! alias-expanded before recall. A data directory written by the server can be
+//! opened here and vice versa.
+//!
+//! ```no_run
+//! use cuemap_rust::CueMap;
+//!
+//! let cuemap = CueMap::open("./data")?;
+//! let added = cuemap.add("Payments timed out after the deploy", ["service:payments", "error:timeout"])?;
+//! let results = cuemap.recall(["service:payments"], 10);
+//! assert_eq!(results[0].memory_id, added.id);
+//! cuemap.snapshot()?;
+//! # Ok::<(), cuemap_rust::embedded::CueMapError>(())
+//! ```
+//!
+//! Background jobs (lexicon training and LLM cue proposals) are off by default
+//! so the facade works without an async runtime. Enable them with
+//! [`CueMapBuilder::background_jobs`] from inside a tokio runtime.
+
+use crate::config::read_view_refresh;
+use crate::engine::{CueMapEngine, RecallResult};
+use crate::hooks::Hooks;
+use crate::grounding::{create_grounding_proof, FreshnessPolicy, GroundingEngine, GroundingProof, GroundingTraceStore, StoredTrace, GROUNDING_TRACES_FILE};
+use crate::jobs::{spawn_read_view_refresher, Job, JobQueue, SingleTenantProvider};
+use crate::persistence::PersistenceManager;
+use crate::projects::{PreparedMemory, ProjectConfig, ProjectContext, PROJECT_CONFIG_FILE};
+use crate::structures::Memory;
+use crate::taxonomy::{RejectedCue, RewrittenCue, ValidationReport};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Grounding traces retained by default, matching the server's `--trace-retention`.
+pub const DEFAULT_TRACE_RETENTION: usize = 10_000;
+
+/// Project id attached to jobs and traces, as in single-tenant mode.
+const PROJECT_ID: &str = "default";
+
+/// Why a [`CueMap`] could not be opened or saved.
+#[derive(Debug)]
+pub enum CueMapError {
+    /// The snapshot in the data directory could not be read or written
+    Snapshot(String),
+    /// `snapshot` was called on an in-memory instance
+    NoDataDir,
+    /// Background jobs were requested outside a tokio runtime
+    NoRuntime,
+    /// An ingest hook refused the memory, for the given reason
+    Rejected(String),
+    /// Another live process holds the data directory's lock
+    Locked(String),
+}
+
+impl fmt::Display for CueMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CueMapError::Snapshot(e) => write!(f, "snapshot error: {}", e),
+            CueMapError::NoDataDir => write!(f, "no data directory configured"),
+            CueMapError::NoRuntime => write!(f, "background jobs require a tokio runtime"),
+            CueMapError::Rejected(reason) => write!(f, "memory rejected: {}", reason),
+            CueMapError::Locked(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for CueMapError {}
+
+/// Options for opening a [`CueMap`].
+#[derive(Debug, Clone)]
+pub struct CueMapBuilder {
+    data_dir: Option<PathBuf>,
+    background_jobs: bool,
+    trace_retention: usize,
+    trace_max_age_days: Option<f64>,
+    hooks: Hooks,
+    force_unlock: bool,
+}
+
+impl Default for CueMapBuilder {
+    fn default() -> Self {
+        Self {
+            data_dir: None,
+            background_jobs: false,
+            trace_retention: DEFAULT_TRACE_RETENTION,
+            trace_max_age_days: Some(30.0),
+            hooks: Hooks::default(),
+            force_unlock: false,
+        }
+    }
+}
+
+impl CueMapBuilder {
+    /// Load from and snapshot to `dir` (the server's `--data-dir` layout).
+    /// Without one, everything stays in memory.
+    pub fn data_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.data_dir = Some(dir.into());
+        self
+    }
+
+    /// Run lexicon training and LLM cue proposals for added memories on the
+    /// current tokio runtime, and refresh read views when configured.
+    pub fn background_jobs(mut self, enabled: bool) -> Self {
+        self.background_jobs = enabled;
+        self
+    }
+
+    /// Maximum number of grounding traces retained for audits.
+    pub fn trace_retention(mut self, max_traces: usize) -> Self {
+        self.trace_retention = max_traces;
+        self
+    }
+
+    /// Maximum age of retained grounding traces, `None` for no age limit.
+    pub fn trace_max_age_days(mut self, days: Option<f64>) -> Self {
+        self.trace_max_age_days = days;
+        self
+    }
+
+    /// Ingest and recall hooks, see [`crate::hooks`].
+    pub fn hooks(mut self, hooks: Hooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    /// Take over the data directory's lock even if another live process
+    /// holds it, as `--force-unlock` does for the server.
+    pub fn force_unlock(mut self, force: bool) -> Self {
+        self.force_unlock = force;
+        self
+    }
+
+    pub fn open(self) -> Result<CueMap, CueMapError> {
+        let runtime = if self.background_jobs {
+            Some(tokio::runtime::Handle::try_current().map_err(|_| CueMapError::NoRuntime)?)
+        } else {
+            None
+        };
+        let trace_max_age = self.trace_max_age_days.map(|days| days * 86_400.0);
+
+        let (project, persistence, traces) = match &self.data_dir {
+            Some(dir) => {
+                let persistence = PersistenceManager::new(dir, 0, self.force_unlock).map_err(CueMapError::Locked)?;
+                let (memories, cue_index) = persistence
+                    .load_state()
+                    .map_err(|e| CueMapError::Snapshot(e.to_string()))?;
+                let main = if memories.is_empty() {
+                    CueMapEngine::new()
+                } else {
+                    CueMapEngine::from_state(memories, cue_index)
+                };
+                let config_path = dir.join(PROJECT_CONFIG_FILE);
+                let config = ProjectConfig::load_or_default(&config_path);
+                let project = ProjectContext::from_config(main, config).with_config_path(config_path);
+                let traces = GroundingTraceStore::open(dir.join(GROUNDING_TRACES_FILE), self.trace_retention, trace_max_age);
+                (project, Some(persistence), traces)
+            }
+            None => (
+                ProjectContext::from_config(CueMapEngine::new(), ProjectConfig::default()),
+                None,
+                GroundingTraceStore::new(self.trace_retention, trace_max_age),
+            ),
+        };
+        let project = Arc::new(project.with_hooks(self.hooks));
+
+        let jobs = runtime.map(|runtime| {
+            let _guard = runtime.enter();
+            let provider = Arc::new(SingleTenantProvider { project: project.clone() });
+            if let Some(every) = read_view_refresh() {
+                spawn_read_view_refresher(provider.clone(), every);
+            }
+            Arc::new(JobQueue::new(provider))
+        });
+
+        Ok(CueMap {
+            project,
+            persistence,
+            traces: Arc::new(traces),
+            jobs,
+        })
+    }
+}
+
+/// Cue validation outcome of [`CueMap::add`].
+#[derive(Debug, Clone)]
+pub struct AddOutcome {
+    pub id: String,
+    pub rejected: Vec<RejectedCue>,
+    pub rewritten: Vec<RewrittenCue>,
+    /// Cues accepted despite a policy violation
+    pub flagged: Vec<RejectedCue>,
+}
+
+/// A recall with every knob `POST /recall` exposes.
+#[derive(Debug, Clone)]
+pub struct RecallQuery {
+    pub cues: Vec<String>,
+    /// Resolved to cues through the project lexicon
+    pub query_text: Option<String>,
+    pub limit: usize,
+    pub auto_reinforce: bool,
+    pub min_intersection: Option<usize>,
+    pub explain: bool,
+    pub disable_pattern_completion: bool,
+    pub disable_salience_bias: bool,
+    pub disable_systems_consolidation: bool,
+    pub max_candidates: Option<usize>,
+}
+
+impl Default for RecallQuery {
+    fn default() -> Self {
+        Self {
+            cues: Vec::new(),
+            query_text: None,
+            limit: 10,
+            auto_reinforce: false,
+            min_intersection: None,
+            explain: false,
+            disable_pattern_completion: false,
+            disable_salience_bias: false,
+            disable_systems_consolidation: false,
+            max_candidates: None,
+        }
+    }
+}
+
+/// A grounded recall, as `POST /recall/grounded`.
+#[derive(Debug, Clone)]
+pub struct GroundedQuery {
+    pub query_text: String,
+    pub token_budget: u32,
+    pub limit: usize,
+    pub freshness: FreshnessPolicy,
+    pub disable_pattern_completion: bool,
+    pub disable_salience_bias: bool,
+    pub disable_systems_consolidation: bool,
+}
+
+impl GroundedQuery {
+    pub fn new(query_text: impl Into<String>) -> Self {
+        Self {
+            query_text: query_text.into(),
+            token_budget: 500,
+            limit: 10,
+            freshness: FreshnessPolicy::default(),
+            disable_pattern_completion: false,
+            disable_salience_bias: false,
+            disable_systems_consolidation: false,
+        }
+    }
+}
+
+/// Context selected under the token budget, with the proof recorded for it.
+#[derive(Debug, Clone)]
+pub struct GroundedRecall {
+    pub verified_context: String,
+    pub proof: GroundingProof,
+}
+
+/// A single CueMap project used as a library.
+///
+/// Cheap to share behind an `Arc`; all methods take `&self`. Nothing is saved
+/// implicitly: call [`CueMap::snapshot`] to persist memories to the data
+/// directory. Project config changes are written as they happen.
+pub struct CueMap {
+    project: Arc<ProjectContext>,
+    persistence: Option<PersistenceManager>,
+    traces: Arc<GroundingTraceStore>,
+    jobs: Option<Arc<JobQueue>>,
+}
+
+impl CueMap {
+    pub fn builder() -> CueMapBuilder {
+        CueMapBuilder::default()
+    }
+
+    /// Open (or create) a data directory with default options.
+    pub fn open(data_dir: impl AsRef<Path>) -> Result<Self, CueMapError> {
+        Self::builder().data_dir(data_dir.as_ref()).open()
+    }
+
+    /// A fresh instance that is never persisted.
+    pub fn in_memory() -> Self {
+        Self::builder().open().expect("in-memory open without background jobs cannot fail")
+    }
+
+    /// Store a memory under `cues`.
+    pub fn add<I, S>(&self, content: impl Into<String>, cues: I) -> Result<AddOutcome, CueMapError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.add_with(content, cues, None, false)
+    }
+
+    /// Store a memory with metadata. Temporal chunking links it to the
+    /// previously added memory unless disabled.
+    pub fn add_with<I, S>(
+        &self,
+        content: impl Into<String>,
+        cues: I,
+        metadata: Option<HashMap<String, serde_json::Value>>,
+        disable_temporal_chunking: bool,
+    ) -> Result<AddOutcome, CueMapError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let memory = self.prepare(content.into(), cues, metadata)?;
+        let id = self.project.main.add_memory(memory.content.clone(), memory.report.accepted.clone(), memory.metadata, disable_temporal_chunking);
+        Ok(self.memory_added(id, memory.content, memory.report))
+    }
+
+    /// Store a memory under a caller-chosen id. If it already exists, `cues`
+    /// are attached to it and its content is left unchanged.
+    pub fn upsert<I, S>(
+        &self,
+        memory_id: impl Into<String>,
+        content: impl Into<String>,
+        cues: I,
+        metadata: Option<HashMap<String, serde_json::Value>>,
+    ) -> Result<AddOutcome, CueMapError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let memory = self.prepare(content.into(), cues, metadata)?;
+        let id = self.project.main.upsert_memory_with_id(memory_id.into(), memory.content.clone(), memory.report.accepted.clone(), memory.metadata, false);
+        Ok(self.memory_added(id, memory.content, memory.report))
+    }
+
+    /// Run ingest hooks, then normalize, language-tag and validate cues as
+    /// `POST /memories` does.
+    fn prepare<I, S>(
+        &self,
+        content: String,
+        cues: I,
+        metadata: Option<HashMap<String, serde_json::Value>>,
+    ) -> Result<PreparedMemory, CueMapError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let cues: Vec<String> = cues.into_iter().map(Into::into).collect();
+        self.project.prepare_memory(content, cues, metadata).map_err(CueMapError::Rejected)
+    }
+
+    fn memory_added(&self, id: String, content: String, report: ValidationReport) -> AddOutcome {
+        self.project.rejections.record(&id, &report.rejected);
+        if let Some(jobs) = &self.jobs {
+            jobs.try_enqueue(Job::TrainLexiconFromMemory {
+                project_id: PROJECT_ID.to_string(),
+                memory_id: id.clone(),
+            });
+            jobs.try_enqueue(Job::LlmProposeCues {
+                project_id: PROJECT_ID.to_string(),
+                memory_id: id.clone(),
+                content,
+            });
+        }
+
+        AddOutcome {
+            id,
+            rejected: report.rejected,
+            rewritten: report.rewritten,
+            flagged: report.flagged,
+        }
+    }
+
+    /// Memories best matching `cues`, strongest first.
+    pub fn recall<I, S>(&self, cues: I, limit: usize) -> Vec<RecallResult>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.recall_with(&RecallQuery {
+            cues: cues.into_iter().map(Into::into).collect(),
+            limit,
+            ..Default::default()
+        })
+    }
+
+    /// Memories matching the cues the lexicon resolves from `query_text`.
+    pub fn recall_text(&self, query_text: &str, limit: usize) -> Vec<RecallResult> {
+        self.recall_with(&RecallQuery {
+            query_text: Some(query_text.to_string()),
+            limit,
+            ..Default::default()
+        })
+    }
+
+    pub fn recall_with(&self, query: &RecallQuery) -> Vec<RecallResult> {
+        let query_cues = self.project.query_cues(&query.cues, query.query_text.as_deref());
+        let expanded_cues = self.project.expand_weighted_query_cues(query_cues);
+
+        let mut results = self.project.main.recall_weighted(
+            expanded_cues.clone(),
+            query.limit,
+            query.auto_reinforce,
+            query.min_intersection,
+            query.explain,
+            query.disable_pattern_completion,
+            query.disable_salience_bias,
+            query.disable_systems_consolidation,
+            query.max_candidates,
+        );
+        self.project.after_recall(&expanded_cues, query.query_text.as_deref(), &mut results);
+        results
+    }
+
+    /// Select memories for `query` within its token budget and record the
+    /// grounding proof, retrievable later with [`CueMap::trace`].
+    pub fn recall_grounded(&self, query: &GroundedQuery) -> GroundedRecall {
+        let query_cues = self.project.query_cues(&[], Some(&query.query_text));
+        let resolved: Vec<String> = query_cues.iter().map(|(cue, _)| cue.clone()).collect();
+        let expanded_cues = self.project.expand_weighted_query_cues(query_cues);
+        let mut results = self.project.main.recall_weighted(
+            expanded_cues.clone(),
+            query.limit.max(20),
+            false,
+            None,
+            true,
+            query.disable_pattern_completion,
+            query.disable_salience_bias,
+            query.disable_systems_consolidation,
+            None,
+        );
+        self.project.after_recall(&expanded_cues, Some(&query.query_text), &mut results);
+
+        let (selected, excluded, verified_context) = GroundingEngine::select_memories(
+            query.query_text.clone(),
+            resolved.clone(),
+            expanded_cues.clone(),
+            results,
+            query.token_budget,
+            &query.freshness,
+        );
+        let proof = create_grounding_proof(
+            uuid::Uuid::new_v4().to_string(),
+            query.query_text.clone(),
+            resolved,
+            expanded_cues,
+            query.token_budget,
+            selected,
+            excluded,
+        );
+        self.traces.record(PROJECT_ID, proof.clone());
+
+        GroundedRecall { verified_context, proof }
+    }
+
+    /// Strengthen a memory and move it to the front of the `cues` lists.
+    /// False if it does not exist.
+    pub fn reinforce<I, S>(&self, memory_id: &str, cues: I) -> bool
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let cues: Vec<String> = cues.into_iter().map(Into::into).collect();
+        self.project.main.reinforce_memory(memory_id, self.project.normalize_cues(&cues))
+    }
+
+    pub fn get(&self, memory_id: &str) -> Option<Memory> {
+        self.project.main.get_memory(memory_id)
+    }
+
+    /// Remove a memory. False if it does not exist.
+    pub fn delete(&self, memory_id: &str) -> bool {
+        self.project.main.delete_memory(memory_id)
+    }
+
+    /// Number of stored memories.
+    pub fn len(&self) -> usize {
+        self.project.main.get_memories().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// A recorded grounding proof by trace id.
+    pub fn trace(&self, trace_id: &str) -> Option<StoredTrace> {
+        self.traces.get(trace_id)
+    }
+
+    /// Write all memories to the data directory, atomically replacing the
+    /// previous snapshot.
+    pub fn snapshot(&self) -> Result<(), CueMapError> {
+        let persistence = self.persistence.as_ref().ok_or(CueMapError::NoDataDir)?;
+        persistence.check_lock().map_err(CueMapError::Locked)?;
+        persistence
+            .save_state(&self.project.main)
+            .map_err(|e| CueMapError::Snapshot(e.to_string()))
+    }
+
+    /// The underlying project (engines, normalization, taxonomy, lexicon),
+    /// for anything the facade does not cover.
+    pub fn project(&self) -> &Arc<ProjectContext> {
+        &self.project
+    }
+
+    /// Grounding traces recorded by [`CueMap::recall_grounded`].
+    pub fn traces(&self) -> &Arc<GroundingTraceStore> {
+        &self.traces
+    }
+}
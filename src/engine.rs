@@ -1,9 +1,19 @@
+use crate::collections::CollectionFilter;
 use crate::config::*;
-use crate::structures::{Memory, OrderedSet};
-use dashmap::DashMap;
+use crate::events::{ChangeEvent, ChangeFeed};
+use crate::ranking::RankingConfig;
+use crate::structures::{CueInterner, IdInterner, Memory, OrderedSet, CONSOLIDATED_INTO_KEY};
+use arc_swap::ArcSwap;
+use dashmap::mapref::entry::Entry;
+use dashmap::mapref::one::{Ref, RefMut};
+use dashmap::{DashMap, DashSet};
 use serde::Serialize;
-use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 
 #[derive(Debug, Clone, Serialize)]
 pub struct RecallResult {
@@ -15,60 +25,748 @@ pub struct RecallResult {
     pub recency_score: f64,
     pub reinforcement_score: f64,
     pub salience_score: f64,
+    pub created_at: f64,
     pub metadata: HashMap<String, serde_json::Value>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool,
+    /// Query cues the memory is listed under, with their weight after alias
+    /// expansion and pattern completion
+    pub matched_cues: Vec<(String, f64)>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub explain: Option<serde_json::Value>,
 }
 
+/// A memory id and a cue it is, or should be, listed under in the index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CuePosting {
+    pub cue: String,
+    pub memory_id: String,
+}
+
+/// Where memories and the cue index disagree, from
+/// [`CueMapEngine::verify_index`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct IndexReport {
+    pub memories: usize,
+    pub cues: usize,
+    /// Cues memories hold without being listed under them
+    pub orphans: Vec<CuePosting>,
+    /// Index entries whose memory does not exist; the id is `#<handle>`
+    /// when the handle resolves to no id at all
+    pub dangling: Vec<CuePosting>,
+    /// Index entries for memories that do not hold the cue
+    pub stray: Vec<CuePosting>,
+    /// Ids in the handle table without a memory
+    pub stale_ids: Vec<String>,
+}
+
+impl IndexReport {
+    pub fn is_consistent(&self) -> bool {
+        self.orphans.is_empty() && self.dangling.is_empty() && self.stray.is_empty() && self.stale_ids.is_empty()
+    }
+
+    /// One line per problem.
+    pub fn problems(&self) -> Vec<String> {
+        let orphans = self.orphans.iter().map(|p| format!("memory {} missing from cue {}", p.memory_id, p.cue));
+        let dangling = self.dangling.iter().map(|p| format!("cue {} lists unknown handle {}", p.cue, p.memory_id));
+        let stray = self.stray.iter().map(|p| format!("cue {} lists memory {}, which does not hold it", p.cue, p.memory_id));
+        let stale = self.stale_ids.iter().map(|id| format!("id table holds {} without a memory", id));
+        orphans.chain(dangling).chain(stray).chain(stale).collect()
+    }
+}
+
+/// Memories listed per cue across an engine's index, from
+/// [`CueMapEngine::warm_up`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct CueSizeStats {
+    pub cues: usize,
+    /// Index entries summed over all cues
+    pub postings: usize,
+    pub p50: usize,
+    pub p99: usize,
+    pub max: usize,
+}
+
+impl CueSizeStats {
+    fn from_lens(mut lens: Vec<usize>) -> Self {
+        lens.sort_unstable();
+        let percentile = |p: usize| lens.get((lens.len().saturating_sub(1)) * p / 100).copied().unwrap_or(0);
+        Self {
+            cues: lens.len(),
+            postings: lens.iter().sum(),
+            p50: percentile(50),
+            p99: percentile(99),
+            max: lens.last().copied().unwrap_or(0),
+        }
+    }
+}
+
+/// Disagreements found by `CueMapEngine::index_disagreements`, with the
+/// handles needed to repair them.
+#[derive(Default)]
+struct Disagreements {
+    orphans: Vec<(String, String)>,
+    misplaced: Vec<MisplacedPosting>,
+    stale_ids: Vec<Arc<str>>,
+}
+
+/// A handle listed under a cue its memory does not hold, or without a memory.
+struct MisplacedPosting {
+    cue: Arc<str>,
+    handle: u64,
+    memory_id: Option<Arc<str>>,
+    exists: bool,
+}
+
+/// Estimated heap footprint of an engine, in bytes
+/// (string lengths plus fixed per-entry overheads, not a heap profile).
+/// Cues are counted as pointers; their strings live in the cue table, which
+/// engines of a project share (see `CueMapEngine::cue_table_bytes`).
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct MemoryUsage {
+    pub memory_count: usize,
+    pub cue_count: usize,
+    pub memories_bytes: usize,
+    pub cue_index_bytes: usize,
+    pub co_occurrence_bytes: usize,
+    pub id_table_bytes: usize,
+    pub total_bytes: usize,
+}
+
+/// Result of [`CueMapEngine::attach_cues_if_match`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttachOutcome {
+    Attached,
+    /// Every cue was already on the memory
+    Unchanged,
+    NotFound,
+    /// The memory's ETag differs from the expected one; carries the current ETag
+    Stale(String),
+}
+
+/// Result of [`CueMapEngine::update_memory_if_match`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateOutcome {
+    Updated,
+    NotFound,
+    /// The memory's ETag differs from the expected one; carries the current ETag
+    Stale(String),
+}
+
+/// Heap bytes of an owned string, including its header.
+pub(crate) fn string_bytes(s: &str) -> usize {
+    std::mem::size_of::<String>() + s.len()
+}
+
+/// A cue as stored in the index: trimmed and lowercased. Borrows when the cue
+/// already is in that form.
+pub(crate) fn index_key(cue: &str) -> Cow<'_, str> {
+    let trimmed = cue.trim();
+    let needs_lowercase = if trimmed.is_ascii() {
+        trimmed.bytes().any(|b| b.is_ascii_uppercase())
+    } else {
+        trimmed.chars().any(|c| {
+            let mut lower = c.to_lowercase();
+            lower.next() != Some(c) || lower.next().is_some()
+        })
+    };
+    if needs_lowercase {
+        Cow::Owned(trimmed.to_lowercase().trim().to_string())
+    } else {
+        Cow::Borrowed(trimmed)
+    }
+}
+
+/// Apply the hash table overhead factor to raw entry bytes.
+pub(crate) fn with_table_overhead(bytes: usize) -> usize {
+    (bytes as f64 * HASH_TABLE_OVERHEAD_FACTOR) as usize
+}
+
+/// Total order over scores so they can live in a `BinaryHeap`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ScoreKey(f64);
+
+impl Eq for ScoreKey {}
+
+impl PartialOrd for ScoreKey {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScoreKey {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// How recall orders memories with equal scores, best first: newer
+/// `created_at`, then the smaller id. Makes result order independent of
+/// candidate and hash map iteration order.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct TieBreak(ScoreKey, Reverse<Arc<str>>);
+
+/// A recall candidate before scoring: its memory handle, the `(recency
+/// position, list length, cue weight)` of each query cue it matched, and its
+/// matched weight.
+type Candidate = (u64, Vec<(usize, usize, f64)>, f64);
+
+/// Score components of one recall candidate.
+struct ScoreBreakdown {
+    score: f64,
+    total_weight: f64,
+    intersection_score: f64,
+    recency_score: f64,
+    frequency_score: f64,
+    salience_score: f64,
+    match_integrity: f64,
+    avg_w_rec: f64,
+    avg_w_freq: f64,
+    match_count: f64,
+    /// Combined multiplier of the ranking rules the memory matched
+    rule_multiplier: f64,
+    applied_rules: Vec<String>,
+}
+
+/// Score a candidate from its (recency position, list length, cue weight) per matched cue.
+fn score_breakdown(memory: &Memory, positions_info: &[(usize, usize, f64)], total_weight: f64, disable_salience_bias: bool) -> ScoreBreakdown {
+    let mut total_recency = 0.0;
+    let mut total_w_rec = 0.0;
+    let mut total_w_freq = 0.0;
+    
+    let match_count = positions_info.len() as f64;
+
+    for &(pos, list_len, weight) in positions_info {
+        let pos_f64 = pos as f64;
+        let list_len_f64 = list_len as f64;
+        let sigma = list_len_f64.sqrt();
+        let ratio = pos_f64 / sigma;
+        
+        let w_rec = MAX_RECENCY_WEIGHT / (ratio + 1.0);
+        let w_freq = 1.0 + (MAX_FREQUENCY_WEIGHT * (1.0 - (1.0 / (ratio + 1.0))));
+        
+        let mut recency_component = 1.0 / (pos_f64 + 1.0);
+        if pos == 0 {
+            recency_component += 1.0;
+        }
+        
+        total_recency += recency_component * weight; // Weigh the recency contribution
+        total_w_rec += w_rec;
+        total_w_freq += w_freq;
+    }
+    
+    let avg_w_rec = total_w_rec / match_count;
+    let avg_w_freq = total_w_freq / match_count;
+    let recency_score = total_recency / match_count;
+    
+    let frequency_score = if memory.reinforcement_count > 0 {
+        (memory.reinforcement_count as f64).log10()
+    } else {
+        0.0
+    };
+    
+    let salience_score = if disable_salience_bias {
+        0.0
+    } else {
+        memory.salience
+    };
+    let intersection_score = total_weight * INTERSECTION_SCORE_SCALE;
+    
+    // Final score includes salience
+    let score = intersection_score + (recency_score * avg_w_rec) + (frequency_score * avg_w_freq) + (salience_score * SALIENCE_SCORE_SCALE);
+    
+    // Match integrity calculation
+    // 1. Intersection strength (relative to match count)
+    let intersection_strength = total_weight / match_count.max(1.0);
+    // 2. Context agreement: how many of the memory's cues matched the query
+    let context_agreement = if !memory.cues.is_empty() {
+        match_count / (memory.cues.len() as f64)
+    } else {
+        0.0
+    };
+    // 3. Reinforcement boost (capped)
+    let reinforcement_boost = (frequency_score / 2.0).min(1.0);
+    
+    let match_integrity = (intersection_strength * 0.5 + context_agreement * 0.3 + reinforcement_boost * 0.2).min(1.0);
+
+    ScoreBreakdown {
+        score,
+        total_weight,
+        intersection_score,
+        recency_score,
+        frequency_score,
+        salience_score,
+        match_integrity,
+        avg_w_rec,
+        avg_w_freq,
+        match_count,
+        rule_multiplier: 1.0,
+        applied_rules: Vec::new(),
+    }
+}
+
+/// Immutable copy of the cue index published for recalls. Unchanged cue lists
+/// are shared between consecutive views.
+#[derive(Default)]
+pub struct IndexView {
+    pub epoch: u64,
+    sets: imbl::HashMap<Arc<str>, Arc<OrderedSet>>,
+}
+
+/// Copy-on-write publication of the cue index. Writers flag the cues they
+/// touch; `CueMapEngine::refresh_read_view` copies just those lists into a
+/// new view and swaps it in, so recalls never wait on index write locks.
+#[derive(Default)]
+struct ReadView {
+    enabled: AtomicBool,
+    published: ArcSwap<IndexView>,
+    dirty: DashSet<Arc<str>>,
+    refresh: Mutex<()>,
+}
+
+impl ReadView {
+    fn mark_dirty(&self, cue: &Arc<str>) {
+        if self.enabled.load(Ordering::Acquire) {
+            self.dirty.insert(cue.clone());
+        }
+    }
+
+    fn current(&self) -> Option<Arc<IndexView>> {
+        self.enabled.load(Ordering::Acquire).then(|| self.published.load_full())
+    }
+}
+
+/// Write access to one cue list. Flags the cue for the next read view refresh
+/// once the write is done.
+struct PostingsMut<'a> {
+    entry: RefMut<'a, Arc<str>, OrderedSet>,
+    read_view: &'a ReadView,
+}
+
+impl Deref for PostingsMut<'_> {
+    type Target = OrderedSet;
+
+    fn deref(&self) -> &OrderedSet {
+        &self.entry
+    }
+}
+
+impl DerefMut for PostingsMut<'_> {
+    fn deref_mut(&mut self) -> &mut OrderedSet {
+        &mut self.entry
+    }
+}
+
+impl Drop for PostingsMut<'_> {
+    fn drop(&mut self) {
+        self.read_view.mark_dirty(self.entry.key());
+    }
+}
+
+/// A cue list read either from the live index or from a published view.
+enum Postings<'a> {
+    Live(Ref<'a, Arc<str>, OrderedSet>),
+    Published(Arc<OrderedSet>),
+}
+
+impl Deref for Postings<'_> {
+    type Target = OrderedSet;
+
+    fn deref(&self) -> &OrderedSet {
+        match self {
+            Postings::Live(set) => set,
+            Postings::Published(set) => set,
+        }
+    }
+}
+
+/// Co-occurrence counts between cue pairs
+type CoOccurrence = DashMap<Arc<str>, DashMap<Arc<str>, u64>>;
+/// Last event of a session: memory id, timestamp and cues
+type LastEvent = (String, f64, Vec<Arc<str>>);
+
 #[derive(Clone)]
 pub struct CueMapEngine {
     memories: Arc<DashMap<String, Memory>>,
-    cue_index: Arc<DashMap<String, OrderedSet>>,
+    cue_index: Arc<DashMap<Arc<str>, OrderedSet>>,
+    // Memory id <-> u64 handles stored in the cue index
+    ids: Arc<IdInterner>,
+    // Shared cue strings (memory cues, index keys, co-occurrence keys)
+    cues: Arc<CueInterner>,
     // Pattern Completion: cue co-occurrence matrix
-    cue_co_occurrence: Arc<DashMap<String, DashMap<String, u64>>>,
+    cue_co_occurrence: Arc<CoOccurrence>,
     // Temporal Chunking: track last event per session/project (using a dummy key for now or extending API)
-    last_events: Arc<DashMap<String, (String, f64, Vec<String>)>>,
+    last_events: Arc<DashMap<String, LastEvent>>,
+    // Cardinality guard: number of distinct indexed values per cue key
+    key_cardinality: Arc<DashMap<String, usize>>,
+    // Bumped on structural changes (adds, deletes, cue changes), not on reinforcement
+    revision: Arc<AtomicU64>,
+    // Bumped when recall scores or order change without a revision change
+    reorders: Arc<AtomicU64>,
+    // Published cue index for recalls, when read isolation is on
+    read_view: Arc<ReadView>,
+    // Memory adds, deletes and reinforcements, for GET /stream
+    events: ChangeFeed,
+    // Business rules applied to recall scores
+    ranking: Arc<ArcSwap<RankingConfig>>,
+    // Stop scanning cue lists on cue weight alone, see `set_approximate_early_exit`
+    approximate_early_exit: Arc<AtomicBool>,
 }
 
 impl CueMapEngine {
+    /// An empty engine with the process-wide shard count and no pre-allocation.
     pub fn new() -> Self {
+        Self::with_sizing(EngineSizing {
+            expected_memories: 0,
+            ..engine_sizing()
+        })
+    }
+
+    /// An empty engine whose hot maps use `sizing`'s shard count and are
+    /// pre-allocated for `sizing.expected_memories`.
+    pub fn with_sizing(sizing: EngineSizing) -> Self {
+        Self::build(sizing, Arc::new(CueInterner::new()))
+    }
+
+    /// An empty engine that interns cues into `cues`, so engines of one
+    /// project share a single copy of each cue string.
+    pub fn with_cue_interner(cues: Arc<CueInterner>) -> Self {
+        Self::build(
+            EngineSizing {
+                expected_memories: 0,
+                ..engine_sizing()
+            },
+            cues,
+        )
+    }
+
+    fn build(sizing: EngineSizing, cues: Arc<CueInterner>) -> Self {
         Self {
-            memories: Arc::new(DashMap::new()),
-            cue_index: Arc::new(DashMap::new()),
-            cue_co_occurrence: Arc::new(DashMap::new()),
+            memories: Arc::new(sizing.dashmap(sizing.expected_memories)),
+            cue_index: Arc::new(sizing.dashmap(sizing.expected_cues())),
+            ids: Arc::new(IdInterner::new()),
+            cues,
+            cue_co_occurrence: Arc::new(sizing.dashmap(sizing.expected_cues())),
             last_events: Arc::new(DashMap::new()),
+            key_cardinality: Arc::new(DashMap::new()),
+            revision: Arc::new(AtomicU64::new(0)),
+            reorders: Arc::new(AtomicU64::new(0)),
+            read_view: Arc::new(ReadView::default()),
+            events: ChangeFeed::default(),
+            ranking: Arc::new(ArcSwap::from_pointee(RankingConfig::default())),
+            approximate_early_exit: Arc::new(AtomicBool::new(false)),
         }
     }
     
+    /// Build an engine from persisted state; `cue_index` lists memory ids per cue
+    /// in the order they are re-added.
     pub fn from_state(
         memories: DashMap<String, Memory>,
-        cue_index: DashMap<String, OrderedSet>,
+        cue_index: DashMap<String, Vec<String>>,
     ) -> Self {
-        Self {
+        let ids = IdInterner::new();
+        let cues = CueInterner::new();
+        for mut entry in memories.iter_mut() {
+            ids.intern(entry.key());
+            for cue in entry.value_mut().cues.iter_mut() {
+                *cue = cues.intern_shared(cue);
+            }
+        }
+        let interned_index = engine_sizing().dashmap(cue_index.len());
+        for (cue, memory_ids) in cue_index {
+            let mut ordered_set = OrderedSet::new();
+            for memory_id in memory_ids {
+                ordered_set.add(ids.intern(&memory_id));
+            }
+            interned_index.insert(cues.intern(&cue), ordered_set);
+        }
+
+        let interned_len = interned_index.len();
+        let engine = Self {
             memories: Arc::new(memories),
-            cue_index: Arc::new(cue_index),
-            cue_co_occurrence: Arc::new(DashMap::new()), // Could be hydrated if we add persistence
+            cue_index: Arc::new(interned_index),
+            ids: Arc::new(ids),
+            cues: Arc::new(cues),
+            cue_co_occurrence: Arc::new(engine_sizing().dashmap(interned_len)), // Could be hydrated if we add persistence
             last_events: Arc::new(DashMap::new()),
-        }
+            key_cardinality: Arc::new(DashMap::new()),
+            revision: Arc::new(AtomicU64::new(0)),
+            reorders: Arc::new(AtomicU64::new(0)),
+            read_view: Arc::new(ReadView::default()),
+            events: ChangeFeed::default(),
+            ranking: Arc::new(ArcSwap::from_pointee(RankingConfig::default())),
+            approximate_early_exit: Arc::new(AtomicBool::new(false)),
+        };
+        engine.rebuild_key_cardinality();
+        engine
     }
     
+    /// Replace all memories and cue lists with persisted state, in place, so
+    /// every handle to this engine sees the new contents. Followers use this
+    /// to apply a primary's state. Co-occurrence is not persisted and is kept.
+    pub fn replace_state(
+        &self,
+        memories: DashMap<String, Memory>,
+        cue_index: DashMap<String, Vec<String>>,
+    ) {
+        self.memories.retain(|id, _| {
+            let keep = memories.contains_key(id);
+            if !keep {
+                self.ids.release(id);
+            }
+            keep
+        });
+        for (id, mut memory) in memories {
+            self.ids.intern(&id);
+            for cue in memory.cues.iter_mut() {
+                *cue = self.cues.intern_shared(cue);
+            }
+            self.memories.insert(id, memory);
+        }
+
+        let incoming: HashMap<Arc<str>, OrderedSet> = cue_index
+            .into_iter()
+            .map(|(cue, memory_ids)| {
+                let mut ordered_set = OrderedSet::new();
+                for memory_id in memory_ids {
+                    ordered_set.add(self.ids.intern(&memory_id));
+                }
+                (self.cues.intern(&cue), ordered_set)
+            })
+            .collect();
+        self.cue_index.retain(|cue, _| {
+            let keep = incoming.contains_key(cue);
+            if !keep {
+                self.read_view.mark_dirty(cue);
+            }
+            keep
+        });
+        for (cue, ordered_set) in incoming {
+            self.read_view.mark_dirty(&cue);
+            self.cue_index.insert(cue, ordered_set);
+        }
+
+        self.rebuild_key_cardinality();
+        self.bump_revision();
+    }
+
+    /// Changes to this engine's memories, as they happen.
+    pub fn events(&self) -> &ChangeFeed {
+        &self.events
+    }
+
+    /// Ranking rules applied to recall scores.
+    pub fn ranking(&self) -> RankingConfig {
+        RankingConfig::clone(&self.ranking.load())
+    }
+
+    /// Replace the ranking rules applied to recall scores.
+    pub fn set_ranking(&self, ranking: RankingConfig) {
+        self.ranking.store(Arc::new(ranking));
+        self.bump_reorders();
+    }
+
+    /// Stop scanning cue lists once the top `limit` candidates match
+    /// `EARLY_TERMINATION_WEIGHT_MARGIN` more cue weight than any unscanned
+    /// memory could. Faster on broad queries, but approximate: salience,
+    /// recency and frequency are not bounded, so a memory that would have
+    /// outranked them can be missed. Off by default.
+    pub fn set_approximate_early_exit(&self, enabled: bool) {
+        self.approximate_early_exit.store(enabled, Ordering::Release);
+        self.bump_reorders();
+    }
+
     // Expose internal state for persistence
     pub fn get_memories(&self) -> &Arc<DashMap<String, Memory>> {
         &self.memories
     }
     
-    pub fn get_cue_index(&self) -> &Arc<DashMap<String, OrderedSet>> {
+    pub fn get_cue_index(&self) -> &Arc<DashMap<Arc<str>, OrderedSet>> {
         &self.cue_index
     }
+
+    /// Cue string table, shared with engines built by `with_cue_interner`.
+    pub fn cue_interner(&self) -> &Arc<CueInterner> {
+        &self.cues
+    }
+
+    /// Counter that changes whenever memories or their cues are added, removed or
+    /// rewritten, memories change collection or are shortened. Reinforcement
+    /// does not change it.
+    pub fn revision(&self) -> u64 {
+        self.revision.load(Ordering::Acquire)
+    }
+
+    fn bump_revision(&self) {
+        self.revision.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Counter that changes whenever a recall could answer differently: on
+    /// every [`revision`](Self::revision) change, on reinforcement, demotion,
+    /// decay, ranking and metadata changes, and when a read view is published.
+    /// Tags cached recall responses.
+    pub fn recall_generation(&self) -> u64 {
+        let epoch = self.read_view.current().map_or(0, |view| view.epoch);
+        self.revision()
+            .wrapping_add(self.reorders.load(Ordering::Acquire))
+            .wrapping_add(epoch)
+    }
+
+    fn bump_reorders(&self) {
+        self.reorders.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Serve recalls from a published copy of the cue index instead of the live
+    /// maps. Writes become visible to recall only after `refresh_read_view`.
+    pub fn enable_read_view(&self) {
+        if self.read_view.enabled.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        for entry in self.cue_index.iter() {
+            self.read_view.dirty.insert(entry.key().clone());
+        }
+        self.refresh_read_view();
+    }
+
+    /// Publish a new read view with every cue list written since the last one.
+    /// Returns the published epoch, or `None` when read isolation is off.
+    pub fn refresh_read_view(&self) -> Option<u64> {
+        if !self.read_view.enabled.load(Ordering::Acquire) {
+            return None;
+        }
+        let _refresh = self.read_view.refresh.lock().unwrap_or_else(|e| e.into_inner());
+        let current = self.read_view.published.load_full();
+        let dirty: Vec<Arc<str>> = self.read_view.dirty.iter().map(|cue| cue.key().clone()).collect();
+        if dirty.is_empty() {
+            return Some(current.epoch);
+        }
+
+        let mut sets = current.sets.clone();
+        for cue in dirty {
+            // Unflag before copying: a write that lands after the copy flags it again
+            self.read_view.dirty.remove(&cue);
+            match self.cue_index.get(&*cue) {
+                Some(set) => {
+                    sets.insert(cue, Arc::new(set.clone()));
+                }
+                None => {
+                    sets.remove(&*cue);
+                }
+            }
+        }
+        let epoch = current.epoch + 1;
+        self.read_view.published.store(Arc::new(IndexView { epoch, sets }));
+        Some(epoch)
+    }
+
+    /// Epoch of the published read view and the number of cue lists written
+    /// since, or `None` when recalls read the live index.
+    pub fn read_view_status(&self) -> Option<(u64, usize)> {
+        let view = self.read_view.current()?;
+        Some((view.epoch, self.read_view.dirty.len()))
+    }
+
+    /// A cue list for recall: from `view` when one is published, else live.
+    fn postings<'a>(&'a self, view: Option<&IndexView>, cue: &str) -> Option<Postings<'a>> {
+        match view {
+            Some(view) => view.sets.get(cue).cloned().map(Postings::Published),
+            None => self.cue_index.get(cue).map(Postings::Live),
+        }
+    }
+
+    /// Mutable access to an existing cue list.
+    fn postings_mut(&self, cue_lower: &str) -> Option<PostingsMut<'_>> {
+        self.cue_index.get_mut(cue_lower).map(|entry| PostingsMut {
+            entry,
+            read_view: &self.read_view,
+        })
+    }
+
+    /// Handle table for the ids stored in the cue index.
+    pub fn get_id_table(&self) -> &Arc<IdInterner> {
+        &self.ids
+    }
+
+    /// Memory ids indexed under a normalized cue, most recent first.
+    pub fn cue_memory_ids(&self, cue: &str, limit: Option<usize>) -> Vec<String> {
+        let handles = match self.cue_index.get(cue) {
+            Some(set) => set.get_recent(limit),
+            None => return Vec::new(),
+        };
+        self.ids.resolve_all(&handles)
+    }
     
-    fn update_cue_co_occurrence(&self, cues: &[String]) {
+    /// Index form of an interned cue, sharing its allocation when the cue is
+    /// already normalized. `None` for blank cues.
+    fn index_form(&self, cue: &Arc<str>) -> Option<Arc<str>> {
+        match index_key(cue) {
+            key if key.is_empty() => None,
+            Cow::Borrowed(key) if key.len() == cue.len() => Some(cue.clone()),
+            key => Some(self.cues.intern(&key)),
+        }
+    }
+
+    /// Interned index form of a raw cue. `None` for blank cues.
+    fn index_cue(&self, cue: &str) -> Option<Arc<str>> {
+        let key = index_key(cue);
+        (!key.is_empty()).then(|| self.cues.intern(&key))
+    }
+
+    /// Get (or create) the index entry for a normalized cue, tracking key cardinality
+    /// when a new value is first indexed.
+    fn index_entry(&self, cue_lower: Arc<str>) -> PostingsMut<'_> {
+        let entry = match self.cue_index.entry(cue_lower) {
+            Entry::Occupied(entry) => entry.into_ref(),
+            Entry::Vacant(entry) => {
+                if let Some((key, _)) = entry.key().split_once(':') {
+                    *self.key_cardinality.entry(key.to_string()).or_insert(0) += 1;
+                }
+                entry.insert(OrderedSet::new())
+            }
+        };
+        PostingsMut {
+            entry,
+            read_view: &self.read_view,
+        }
+    }
+
+    fn rebuild_key_cardinality(&self) {
+        self.key_cardinality.clear();
+        for entry in self.cue_index.iter() {
+            if let Some((key, _)) = entry.key().split_once(':') {
+                *self.key_cardinality.entry(key.to_string()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Number of distinct values indexed for a cue key.
+    pub fn key_cardinality(&self, key: &str) -> usize {
+        self.key_cardinality.get(key).map(|c| *c).unwrap_or(0)
+    }
+
+    /// Distinct indexed values per cue key.
+    pub fn key_cardinalities(&self) -> HashMap<String, usize> {
+        self.key_cardinality.iter().map(|e| (e.key().clone(), *e.value())).collect()
+    }
+
+    /// Whether a cue already has an index entry.
+    pub fn has_cue(&self, cue: &str) -> bool {
+        self.cue_index.contains_key(&*index_key(cue))
+    }
+
+    /// Number of memories listed under `cue`.
+    pub fn cue_len(&self, cue: &str) -> usize {
+        self.cue_index.get(&*index_key(cue)).map_or(0, |entry| entry.len())
+    }
+
+    /// Count co-occurrences between index-form cues.
+    fn update_cue_co_occurrence(&self, cues: &[Arc<str>]) {
         for i in 0..cues.len() {
-            let cue_a = cues[i].to_lowercase().trim().to_string();
-            if cue_a.is_empty() { continue; }
+            let cue_a = &cues[i];
             
             for j in (i + 1)..cues.len() {
-                let cue_b = cues[j].to_lowercase().trim().to_string();
-                if cue_b.is_empty() || cue_a == cue_b { continue; }
+                let cue_b = &cues[j];
+                if cue_a == cue_b { continue; }
                 
                 // Update A -> B
                 self.cue_co_occurrence
@@ -95,12 +793,29 @@ impl CueMapEngine {
         cues: Vec<String>,
         metadata: Option<HashMap<String, serde_json::Value>>,
         disable_temporal_chunking: bool,
+    ) -> String {
+        self.add_memory_to(content, cues, metadata, disable_temporal_chunking, None)
+    }
+
+    /// [`add_memory`](Self::add_memory) into `collection`, which recalls see
+    /// from the moment the memory is indexed.
+    pub fn add_memory_to(
+        &self,
+        content: String,
+        cues: Vec<String>,
+        metadata: Option<HashMap<String, serde_json::Value>>,
+        disable_temporal_chunking: bool,
+        collection: Option<String>,
     ) -> String {
         let mut memory = Memory::new(content, metadata);
+        memory.collection = collection;
         let memory_id = memory.id.clone();
         
-        // Store cues in memory
-        memory.cues = cues.clone();
+        // Store cues in memory (room for the episode cue added below)
+        memory.cues = Vec::with_capacity(cues.len() + 1);
+        memory.cues.extend(cues.iter().map(|cue| self.cues.intern(cue)));
+        let mut index_cues: Vec<Arc<str>> = memory.cues.iter().filter_map(|cue| self.index_form(cue)).collect();
+        let indexed = index_cues.len();
         
         // 1. Salience calculation (proxies)
         // High cue density boost
@@ -136,27 +851,32 @@ impl CueMapEngine {
             };
             
             if time_diff < 300.0 && overlap_ratio > 0.5 && !disable_temporal_chunking {
-                let episode_cue = format!("episode:{}", last_id);
-                memory.cues.push(episode_cue.clone());
+                let episode_cue = self.cues.intern(&format!("episode:{}", last_id));
+                index_cues.extend(self.index_form(&episode_cue));
+                memory.cues.push(episode_cue);
             }
         }
         self.last_events.insert(project_id, (memory_id.clone(), memory.created_at, memory.cues.clone()));
 
         // 3. Update co-occurrence matrix
-        self.update_cue_co_occurrence(&memory.cues);
+        self.update_cue_co_occurrence(&index_cues);
         
-        // Store memory
+        let added = self.events.has_subscribers().then(|| ChangeEvent::MemoryAdded {
+            memory_id: memory_id.clone(),
+            cues: memory.cues.iter().map(|cue| cue.to_string()).collect(),
+        });
+        
+        // Store memory; its handle first, so a concurrent delete releases it
+        let handle = self.ids.intern(&memory_id);
         self.memories.insert(memory_id.clone(), memory);
         
-        // Index by cues
-        for cue in &cues {
-            let cue_lower = cue.to_lowercase().trim().to_string();
-            if !cue_lower.is_empty() {
-                self.cue_index
-                    .entry(cue_lower)
-                    .or_insert_with(OrderedSet::new)
-                    .add(memory_id.clone());
-            }
+        // Index by cues (the episode cue only feeds co-occurrence)
+        for cue in index_cues.into_iter().take(indexed) {
+            self.index_entry(cue).add(handle);
+        }
+        self.bump_revision();
+        if let Some(event) = added {
+            self.events.publish(event);
         }
         
         memory_id
@@ -172,34 +892,116 @@ impl CueMapEngine {
         }
         
         // Update co-occurrence matrix with cues used for reinforcement
-        self.update_cue_co_occurrence(&cues);
+        let index_cues: Vec<Arc<str>> = cues.iter().filter_map(|cue| self.index_cue(cue)).collect();
+        self.update_cue_co_occurrence(&index_cues);
 
         // Move to front for each cue
+        let handle = self.ids.intern(memory_id);
+        for cue in index_cues {
+            let mut entry = self.index_entry(cue);
+            entry.move_to_front(handle);
+        }
+        self.bump_reorders();
+        self.events.publish(ChangeEvent::MemoryReinforced { memory_id: memory_id.to_string(), cues });
+        
+        true
+    }
+
+    /// Negative reinforcement: lower salience and move the memory to the least
+    /// recent position in the given cue lists. Unknown cues are ignored.
+    pub fn penalize_memory(&self, memory_id: &str, cues: Vec<String>) -> bool {
+        if let Some(mut memory) = self.memories.get_mut(memory_id) {
+            memory.salience = (memory.salience - NEGATIVE_FEEDBACK_PENALTY).max(0.0);
+        } else {
+            return false;
+        }
+
+        self.bump_reorders();
+        let Some(handle) = self.ids.get(memory_id) else { return true };
         for cue in cues {
-            let cue_lower = cue.to_lowercase().trim().to_string();
-            if !cue_lower.is_empty() {
-                let mut entry = self.cue_index
-                    .entry(cue_lower)
-                    .or_insert_with(OrderedSet::new);
-                entry.move_to_front(memory_id);
+            if let Some(mut entry) = self.postings_mut(&index_key(&cue)) {
+                entry.move_to_back(handle);
             }
         }
-        
+
         true
     }
 
+    /// Move a memory into `collection`, or out of any with `None`.
+    /// Returns false when the memory does not exist.
+    pub fn set_collection(&self, memory_id: &str, collection: Option<&str>) -> bool {
+        let Some(mut memory) = self.memories.get_mut(memory_id) else { return false };
+        if memory.collection.as_deref() != collection {
+            memory.collection = collection.map(str::to_string);
+            drop(memory);
+            self.bump_revision();
+        }
+        true
+    }
+
+    /// Lock or unlock a memory. Background jobs leave locked memories
+    /// alone. Returns false when the memory does not exist.
+    pub fn set_locked(&self, memory_id: &str, locked: bool) -> bool {
+        let Some(mut memory) = self.memories.get_mut(memory_id) else { return false };
+        if memory.locked != locked {
+            memory.locked = locked;
+            drop(memory);
+            self.bump_revision();
+        }
+        true
+    }
+
+    /// Replace a memory's content with a shorter form, keeping the content it
+    /// was first written with in `full_content`. Cues are untouched. Returns
+    /// false when the memory does not exist.
+    pub fn shorten_content(&self, memory_id: &str, content: String) -> bool {
+        let Some(mut memory) = self.memories.get_mut(memory_id) else { return false };
+        let original = std::mem::replace(&mut memory.content, content);
+        memory.full_content.get_or_insert(original);
+        drop(memory);
+        self.bump_revision();
+        true
+    }
+
+    /// Take every memory out of `collection`. Returns how many were in it.
+    pub fn clear_collection(&self, collection: &str) -> usize {
+        let mut cleared = 0;
+        for mut memory in self.memories.iter_mut() {
+            if memory.collection.as_deref() == Some(collection) {
+                memory.collection = None;
+                cleared += 1;
+            }
+        }
+        if cleared > 0 {
+            self.bump_revision();
+        }
+        cleared
+    }
+
+    /// Memories per collection; memories outside any collection are not counted.
+    pub fn collection_sizes(&self) -> HashMap<String, usize> {
+        let mut sizes = HashMap::new();
+        for memory in self.memories.iter() {
+            if let Some(collection) = &memory.collection {
+                *sizes.entry(collection.clone()).or_default() += 1;
+            }
+        }
+        sizes
+    }
+
     pub fn delete_memory(&self, memory_id: &str) -> bool {
         if let Some((_, memory)) = self.memories.remove(memory_id) {
-            // Remove from cue index
-            for cue in memory.cues {
-                 let cue_lower = cue.to_lowercase().trim().to_string();
-                 if let Some(mut entry) = self.cue_index.get_mut(&cue_lower) {
-                     entry.remove(memory_id);
-                     // If set becomes empty, we might want to remove the cue entry entirely
-                     // But OrderedSet might not expose "is_empty" or we might want to keep the cue
-                     // For now, simple removal is enough.
-                 }
+            // Memories get their handle before they are stored, so one without
+            // a handle was never indexed
+            if let Some(handle) = self.ids.release(memory_id) {
+                for cue in &memory.cues {
+                    if let Some(mut entry) = self.postings_mut(&index_key(cue)) {
+                        entry.remove(handle);
+                    }
+                }
             }
+            self.bump_revision();
+            self.events.publish(ChangeEvent::MemoryDeleted { memory_id: memory_id.to_string() });
             true
         } else {
             false
@@ -226,68 +1028,205 @@ impl CueMapEngine {
         // Insert new
         let mut memory = Memory::new(content, metadata);
         memory.id = id.clone();
-        memory.cues = cues.clone();
+        memory.cues = cues.iter().map(|cue| self.cues.intern(cue)).collect();
+        let index_cues: Vec<Arc<str>> = memory.cues.iter().filter_map(|cue| self.index_form(cue)).collect();
         
+        let handle = self.ids.intern(&id);
         self.memories.insert(id.clone(), memory);
         
         // Index by cues
-        for cue in &cues { // Iterate by reference to avoid move
-            let cue_lower = cue.to_lowercase().trim().to_string();
-            if !cue_lower.is_empty() {
-                self.cue_index
-                    .entry(cue_lower)
-                    .or_insert_with(OrderedSet::new)
-                    .add(id.clone());
-            }
+        for cue in &index_cues {
+            self.index_entry(cue.clone()).add(handle);
         }
         
         // FIX: Update co-occurrence matrix for new memory
-        self.update_cue_co_occurrence(&cues);
+        self.update_cue_co_occurrence(&index_cues);
+        self.bump_revision();
+        self.events.publish(ChangeEvent::MemoryAdded { memory_id: id.clone(), cues });
         
         id
     }
 
     pub fn attach_cues(&self, memory_id: &str, cues: Vec<String>) -> bool {
-        // 1. Get memory and check if it exists
-        if let Some(mut memory) = self.memories.get_mut(memory_id) {
-            // 2. Identify new cues (deduplication)
-            let mut new_cues = Vec::new();
-            for cue in cues {
-                if !memory.cues.contains(&cue) {
-                    new_cues.push(cue);
+        self.attach_cues_if_match(memory_id, cues, None) == AttachOutcome::Attached
+    }
+
+    /// Attach `cues` only while the memory still has ETag `expected` (any
+    /// ETag when `None`); the check and the update happen under one lock.
+    pub fn attach_cues_if_match(&self, memory_id: &str, cues: Vec<String>, expected: Option<&str>) -> AttachOutcome {
+        self.attach_cues_checked(memory_id, cues, expected, false)
+    }
+
+    /// Attach `cues` unless the memory is locked, checked under the same lock
+    /// as the update, so a memory locked while a job waited on the LLM is
+    /// left alone. Returns whether any cue was attached.
+    pub fn attach_cues_unless_locked(&self, memory_id: &str, cues: Vec<String>) -> bool {
+        self.attach_cues_checked(memory_id, cues, None, true) == AttachOutcome::Attached
+    }
+
+    /// Locked memories count as `Unchanged` with `skip_locked`.
+    fn attach_cues_checked(&self, memory_id: &str, cues: Vec<String>, expected: Option<&str>, skip_locked: bool) -> AttachOutcome {
+        // 1. Get memory and check if it exists and is unchanged
+        let Some(mut memory) = self.memories.get_mut(memory_id) else {
+            return AttachOutcome::NotFound;
+        };
+        if skip_locked && memory.locked {
+            return AttachOutcome::Unchanged;
+        }
+        if let Some(expected) = expected {
+            let current = memory.etag();
+            if current != expected {
+                return AttachOutcome::Stale(current);
+            }
+        }
+
+        // 2. Identify new cues (deduplication)
+        let mut new_cues = Vec::new();
+        for cue in cues {
+            if !memory.cues.iter().any(|existing| **existing == *cue) {
+                new_cues.push(self.cues.intern(&cue));
+            }
+        }
+
+        if new_cues.is_empty() {
+            return AttachOutcome::Unchanged;
+        }
+
+        // 3. Update memory.cues
+        memory.cues.extend(new_cues.iter().cloned());
+
+        // 4. Update index for new cues
+        let handle = self.ids.intern(memory_id);
+        for cue in &new_cues {
+            if let Some(cue_lower) = self.index_form(cue) {
+                self.index_entry(cue_lower).add(handle);
+            }
+        }
+
+        // FIX: Update co-occurrence with extended cue set
+        // We pass ALL cues to reinforce associations between old and new cues
+        let all_cues: Vec<Arc<str>> = memory.cues.iter().filter_map(|cue| self.index_form(cue)).collect();
+        drop(memory); // Release lock before calling update (though update uses different map, safer)
+        self.update_cue_co_occurrence(&all_cues);
+        self.bump_revision();
+
+        AttachOutcome::Attached
+    }
+    
+    /// Replace a memory's content and, when `cues` is given, its whole cue
+    /// set: cues it no longer has leave the cue index and new ones join it.
+    /// Only while the memory still has ETag `expected` (any ETag when
+    /// `None`); the check and the update happen under one lock. Salience,
+    /// collection, lock and metadata are kept.
+    pub fn update_memory_if_match(
+        &self,
+        memory_id: &str,
+        content: String,
+        cues: Option<Vec<String>>,
+        expected: Option<&str>,
+    ) -> UpdateOutcome {
+        let Some(mut memory) = self.memories.get_mut(memory_id) else {
+            return UpdateOutcome::NotFound;
+        };
+        if let Some(expected) = expected {
+            let current = memory.etag();
+            if current != expected {
+                return UpdateOutcome::Stale(current);
+            }
+        }
+
+        memory.content = content;
+        // The first-written content no longer applies to the replacement
+        memory.full_content = None;
+        memory.touch();
+
+        let mut index_cues = None;
+        if let Some(cues) = cues {
+            let mut replaced: Vec<Arc<str>> = Vec::with_capacity(cues.len());
+            for cue in &cues {
+                let cue = self.cues.intern(cue);
+                if !replaced.contains(&cue) {
+                    replaced.push(cue);
                 }
             }
+            let old_index: HashSet<Arc<str>> = memory.cues.iter().filter_map(|cue| self.index_form(cue)).collect();
+            let new_index: Vec<Arc<str>> = replaced.iter().filter_map(|cue| self.index_form(cue)).collect();
 
-            if new_cues.is_empty() {
-                return false;
+            let handle = self.ids.intern(memory_id);
+            for cue in old_index.iter().filter(|cue| !new_index.contains(cue)) {
+                if let Some(mut entry) = self.postings_mut(cue) {
+                    entry.remove(handle);
+                }
+            }
+            for cue in new_index.iter().filter(|cue| !old_index.contains(*cue)) {
+                self.index_entry(cue.clone()).add(handle);
             }
+            memory.cues = replaced;
+            index_cues = Some(new_index);
+        }
+        let cues: Vec<String> = memory.cues.iter().map(|cue| cue.to_string()).collect();
+        drop(memory);
+
+        if let Some(index_cues) = index_cues {
+            self.update_cue_co_occurrence(&index_cues);
+        }
+        self.bump_revision();
+        self.events.publish(ChangeEvent::MemoryUpdated { memory_id: memory_id.to_string(), cues });
+
+        UpdateOutcome::Updated
+    }
 
-            // 3. Update memory.cues
-            memory.cues.extend(new_cues.clone());
+    /// Rewrite every `old_key:*` cue to `new_key:*` across stored memories and the cue index.
+    /// Locked memories keep their cues. Returns the number of memories migrated.
+    pub fn rename_cue_key(&self, old_key: &str, new_key: &str) -> usize {
+        let old_prefix = format!("{}:", old_key.to_lowercase().trim());
+        let new_prefix = format!("{}:", new_key.to_lowercase().trim());
+        if old_prefix == new_prefix {
+            return 0;
+        }
+
+        let affected: Vec<String> = self.memories
+            .iter()
+            .filter(|m| !m.locked && m.cues.iter().any(|c| index_key(c).starts_with(&old_prefix)))
+            .map(|m| m.id.clone())
+            .collect();
 
-            // 4. Update index for new cues
-            for cue in new_cues {
-                let cue_lower = cue.to_lowercase().trim().to_string();
-                if !cue_lower.is_empty() {
-                    self.cue_index
-                        .entry(cue_lower)
-                        .or_insert_with(OrderedSet::new)
-                        .add(memory_id.to_string());
+        for memory_id in &affected {
+            let Some(mut memory) = self.memories.get_mut(memory_id) else { continue };
+            let handle = self.ids.intern(memory_id);
+
+            let mut rewritten: Vec<Arc<str>> = Vec::with_capacity(memory.cues.len());
+            for cue in &memory.cues {
+                let cue_lower = index_key(cue);
+                let new_cue = match cue_lower.strip_prefix(&old_prefix) {
+                    Some(value) => {
+                        if let Some(mut entry) = self.postings_mut(&cue_lower) {
+                            entry.remove(handle);
+                        }
+                        let new_cue = self.cues.intern(&format!("{}{}", new_prefix, value));
+                        self.index_entry(new_cue.clone()).add(handle);
+                        new_cue
+                    }
+                    None => cue.clone(),
+                };
+                if !rewritten.contains(&new_cue) {
+                    rewritten.push(new_cue);
                 }
             }
-            
-            // FIX: Update co-occurrence with extended cue set
-            // We pass ALL cues to reinforce associations between old and new cues
-            let all_cues = memory.cues.clone();
-            drop(memory); // Release lock before calling update (though update uses different map, safer)
-            self.update_cue_co_occurrence(&all_cues);
-            
-            return true;
-        } else {
-            false
+            memory.cues = rewritten;
+        }
+
+        // Drop index entries left empty by the migration
+        self.cue_index.retain(|cue, set| !(cue.starts_with(&old_prefix) && set.is_empty()));
+        self.cues.purge_unused();
+        self.rebuild_key_cardinality();
+        if !affected.is_empty() {
+            self.bump_revision();
         }
+
+        affected.len()
     }
-    
+
     pub fn recall(
         &self,
         query_cues: Vec<String>,
@@ -314,9 +1253,10 @@ impl CueMapEngine {
             .map(|c| (c, 1.0))
             .collect();
             
-        self.recall_weighted(weighted_cues, limit, auto_reinforce, min_intersection, false, false, false, false)
+        self.recall_weighted(weighted_cues, limit, auto_reinforce, min_intersection, false, false, false, false, None)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn recall_weighted(
         &self,
         query_cues: Vec<(String, f64)>,
@@ -327,16 +1267,48 @@ impl CueMapEngine {
         disable_pattern_completion: bool,
         disable_salience_bias: bool,
         disable_systems_consolidation: bool,
+        max_candidates: Option<usize>,
     ) -> Vec<RecallResult> {
-        if query_cues.is_empty() {
+        self.recall_weighted_in(query_cues, limit, auto_reinforce, min_intersection, explain, disable_pattern_completion, disable_salience_bias, disable_systems_consolidation, max_candidates, None, None)
+    }
+
+    /// [`recall_weighted`](Self::recall_weighted) over the memories that
+    /// `collections` allows. Other memories are skipped while candidates are
+    /// gathered, so they count against neither `limit` nor `max_candidates`.
+    ///
+    /// With `within`, only those memory ids are scored: each is probed in the
+    /// query cue lists instead of scanning them, which makes drilling into an
+    /// earlier result set cheap. Unknown ids are ignored.
+    #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument(name = "recall", skip_all, fields(cues = query_cues.len(), limit))]
+    pub fn recall_weighted_in(
+        &self,
+        query_cues: Vec<(String, f64)>,
+        limit: usize,
+        auto_reinforce: bool,
+        min_intersection: Option<usize>,
+        explain: bool,
+        disable_pattern_completion: bool,
+        disable_salience_bias: bool,
+        disable_systems_consolidation: bool,
+        max_candidates: Option<usize>,
+        collections: Option<&CollectionFilter>,
+        within: Option<&[String]>,
+    ) -> Vec<RecallResult> {
+        if query_cues.is_empty() || limit == 0 {
             return Vec::new();
         }
         
-        // Normalize primary cues
-        let mut active_cues: Vec<(String, f64)> = query_cues
+        // Recalls see the published index view when read isolation is on
+        let read_view = self.read_view.current();
+
+        // Normalize primary cues (cues never interned cannot be indexed)
+        let mut active_cues: Vec<(Arc<str>, f64)> = query_cues
             .iter()
-            .map(|(c, w)| (c.to_lowercase().trim().to_string(), *w))
-            .filter(|(c, _)| !c.is_empty() && self.cue_index.contains_key(c))
+            .filter_map(|(c, w)| {
+                let cue = self.cues.get(&index_key(c))?;
+                self.postings(read_view.as_deref(), &cue).is_some().then_some((cue, *w))
+            })
             .collect();
         
         if active_cues.is_empty() {
@@ -346,7 +1318,7 @@ impl CueMapEngine {
         // 1. Pattern Completion (Hippocampal CA3)
         // Find cues that strongly co-occur with the query cues
         if !disable_pattern_completion {
-            let mut inferred_candidates: HashMap<String, u64> = HashMap::new();
+            let mut inferred_candidates: HashMap<Arc<str>, u64> = HashMap::new();
             for (cue, _) in &active_cues {
                 if let Some(co_map) = self.cue_co_occurrence.get(cue) {
                 for entry in co_map.iter() {
@@ -361,23 +1333,22 @@ impl CueMapEngine {
         }
 
         // Take top-K inferred cues and inject them with low weight
-            let mut inferred_list: Vec<(String, u64)> = inferred_candidates.into_iter().collect();
-            inferred_list.sort_unstable_by(|a, b| b.1.cmp(&a.1));
+            let mut inferred_list: Vec<(Arc<str>, u64)> = inferred_candidates.into_iter().collect();
+            inferred_list.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
             
-            let pattern_completion_weight = 0.7; // Weight for inferred cues
-            for (inf_cue, _) in inferred_list.into_iter().take(5) {
-                active_cues.push((inf_cue, pattern_completion_weight));
+            for (inf_cue, _) in inferred_list.into_iter().take(PATTERN_COMPLETION_TOP_K) {
+                active_cues.push((inf_cue, PATTERN_COMPLETION_WEIGHT));
             }
         }
         
-        // 2. Consolidated search using Selective Set Intersection
-        let mut results = self.consolidated_search(&active_cues, limit, explain, disable_salience_bias, disable_systems_consolidation);
-        
-        // Filter by minimum intersection if specified (on primary cues only?)
-        // For now, simple retention.
-        if let Some(min_int) = min_intersection {
-            results.retain(|r| r.intersection_count >= min_int);
-        }
+        // 2. Consolidated search using Selective Set Intersection (top `limit`, best first)
+        let max_candidates = max_candidates.unwrap_or(MAX_RECALL_CANDIDATES).max(limit);
+        let collections = collections.filter(|filter| !filter.is_empty());
+        let within: Option<Vec<u64>> = within.map(|ids| {
+            let mut seen = HashSet::new();
+            ids.iter().filter_map(|id| self.ids.get(id)).filter(|handle| seen.insert(*handle)).collect()
+        });
+        let results = self.consolidated_search(read_view.as_deref(), &active_cues, limit, min_intersection, max_candidates, collections, within.as_deref(), explain, disable_salience_bias, disable_systems_consolidation);
         
         // 3. Auto-reinforce if enabled (only primary cues)
         if auto_reinforce {
@@ -387,19 +1358,23 @@ impl CueMapEngine {
             }
         }
 
-        // Global sort by score
-        results.sort_unstable_by(|a, b| {
-            b.score
-                .partial_cmp(&a.score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-
-        results.truncate(limit);
-        
         results
     }
     
-    fn consolidated_search(&self, query_cues: &[(String, f64)], _limit: usize, explain: bool, disable_salience_bias: bool, disable_systems_consolidation: bool) -> Vec<RecallResult> {
+    #[allow(clippy::too_many_arguments)]
+    fn consolidated_search(
+        &self,
+        read_view: Option<&IndexView>,
+        query_cues: &[(Arc<str>, f64)],
+        limit: usize,
+        min_intersection: Option<usize>,
+        max_candidates: usize,
+        collections: Option<&CollectionFilter>,
+        within: Option<&[u64]>,
+        explain: bool,
+        disable_salience_bias: bool,
+        disable_systems_consolidation: bool,
+    ) -> Vec<RecallResult> {
         if query_cues.is_empty() {
             return Vec::new();
         }
@@ -407,7 +1382,7 @@ impl CueMapEngine {
         // 1. Gather cue data
         let mut cue_data = Vec::with_capacity(query_cues.len());
         for (cue, weight) in query_cues {
-            if let Some(ordered_set) = self.cue_index.get(cue) {
+            if let Some(ordered_set) = self.postings(read_view, cue) {
                 cue_data.push((cue.clone(), *weight, ordered_set));
             }
         }
@@ -418,19 +1393,78 @@ impl CueMapEngine {
 
         // 2. Perform Union-based search with O(1) Probing
         // We iterate through EVERY cue's list up to MAX_DRIVER_SCAN to ensure partial matches are found.
-        let mut candidates = Vec::new();
+        let intersect_span = tracing::info_span!(
+            "intersect",
+            cues = cue_data.len(),
+            candidates = tracing::field::Empty,
+            capped = tracing::field::Empty,
+            early_exit = tracing::field::Empty,
+        ).entered();
+        let mut candidates: Vec<Candidate> = Vec::new();
         let mut seen_memories = HashSet::new();
+        // A memory first seen in list k is absent from lists 0..k, so it can match
+        // at most the weight of the lists not yet scanned.
+        let mut remaining_weight: f64 = cue_data.iter().map(|(_, weight, _)| *weight).sum();
+        // Matched weights of the best `limit` candidates so far (min-heap)
+        let mut best_weights: BinaryHeap<Reverse<ScoreKey>> = BinaryHeap::with_capacity(limit + 1);
+        let mut capped = false;
+        let mut early_exit = false;
+        // Ranking rules rescale scores after this pass, so raw weights can't bound the top `limit`
+        let can_exit_early = self.approximate_early_exit.load(Ordering::Acquire) && self.ranking.load().rules.is_empty();
+
+        // A drill-down probes its memories instead of scanning the lists
+        let lists = if within.is_some() { &cue_data[..0] } else { &cue_data[..] };
+        for &memory_id in within.unwrap_or_default() {
+            if !self.passes_filters(memory_id, collections, disable_systems_consolidation) {
+                continue;
+            }
+            if candidates.len() >= max_candidates {
+                capped = true;
+                break;
+            }
+            let mut total_weight = 0.0;
+            let mut positions_info = Vec::with_capacity(cue_data.len());
+            for (_cue, weight, set) in &cue_data {
+                if let Some(oldest_idx) = set.get_index_of(memory_id) {
+                    total_weight += *weight;
+                    positions_info.push(((set.len() - 1) - oldest_idx, set.len(), *weight));
+                }
+            }
+            if positions_info.is_empty() || min_intersection.is_some_and(|min| positions_info.len() < min) {
+                continue;
+            }
+            candidates.push((memory_id, positions_info, total_weight));
+        }
+
+        'lists: for (cue_idx, (_cue, weight, set)) in lists.iter().enumerate() {
+            // Approximate: stop once every remaining candidate would trail the
+            // current top `limit` by at least a full cue weight of intersection.
+            if can_exit_early && best_weights.len() >= limit {
+                if let Some(Reverse(ScoreKey(kth_weight))) = best_weights.peek() {
+                    if *kth_weight >= remaining_weight + EARLY_TERMINATION_WEIGHT_MARGIN {
+                        early_exit = true;
+                        break;
+                    }
+                }
+            }
+            remaining_weight -= *weight;
 
-        for (cue_idx, (_cue, _weight, set)) in cue_data.iter().enumerate() {
             let scan_limit = std::cmp::min(set.len(), MAX_DRIVER_SCAN);
             let items = set.get_recent(Some(scan_limit));
 
-            for (pos_rev, memory_id) in items.iter().enumerate() {
+            for (pos_rev, &memory_id) in items.iter().enumerate() {
                 // If we've already processed this memory from a previous (likely more selective or relevant) cue, skip it
-                if seen_memories.contains(*memory_id) {
+                if !seen_memories.insert(memory_id) {
+                    continue;
+                }
+                // Filtered memories must not count towards the early exit's top `limit`
+                if !self.passes_filters(memory_id, collections, disable_systems_consolidation) {
                     continue;
                 }
-                seen_memories.insert((*memory_id).clone());
+                if candidates.len() >= max_candidates {
+                    capped = true;
+                    break 'lists;
+                }
 
                 let mut total_weight = 0.0;
                 let mut positions_info = Vec::with_capacity(cue_data.len());
@@ -452,157 +1486,287 @@ impl CueMapEngine {
                     }
                 }
 
+                if min_intersection.is_some_and(|min| positions_info.len() < min) {
+                    continue;
+                }
+
                 // 4. Collect candidate
-                candidates.push(((*memory_id).clone(), positions_info, total_weight));
+                best_weights.push(Reverse(ScoreKey(total_weight)));
+                if best_weights.len() > limit {
+                    best_weights.pop();
+                }
+                candidates.push((memory_id, positions_info, total_weight));
             }
         }
 
+        intersect_span.record("candidates", candidates.len());
+        intersect_span.record("capped", capped);
+        intersect_span.record("early_exit", early_exit);
+        drop(intersect_span);
+
         // 5. Score candidates
-        self.score_consolidated_candidates(candidates, explain, disable_salience_bias, disable_systems_consolidation)
+        let mut results = self.score_consolidated_candidates(candidates, limit, explain, disable_salience_bias);
+
+        // 6. Name the query cues each survivor matched
+        for result in &mut results {
+            let Some(handle) = self.ids.get(&result.memory_id) else { continue };
+            result.matched_cues = cue_data
+                .iter()
+                .filter(|(_, _, set)| set.contains(handle))
+                .map(|(cue, weight, _)| (cue.to_string(), *weight))
+                .collect();
+        }
+        results
     }
 
-    fn score_consolidated_candidates(&self, candidates: Vec<(String, Vec<(usize, usize, f64)>, f64)>, explain: bool, disable_salience_bias: bool, disable_systems_consolidation: bool) -> Vec<RecallResult> {
-        const MAX_REC_WEIGHT: f64 = 20.0;
-        const MAX_FREQ_WEIGHT: f64 = 5.0;
-        
-        let mut results = Vec::with_capacity(candidates.len());
+    /// Whether the memory behind `handle` passes a recall's collection filter
+    /// and, with `exclude_summaries`, is not a consolidated summary.
+    fn passes_filters(&self, handle: u64, collections: Option<&CollectionFilter>, exclude_summaries: bool) -> bool {
+        if collections.is_none() && !exclude_summaries {
+            return true;
+        }
+        let Some(memory_id) = self.ids.resolve(handle) else { return false };
+        let Some(memory) = self.memories.get(&*memory_id) else { return false };
+        collections.is_none_or(|filter| filter.allows(memory.collection.as_deref()))
+            && !(exclude_summaries && memory.cues.iter().any(|c| &**c == "type:summary"))
+    }
+
+    /// Score candidates and keep the best `limit`, sorted by descending score.
+    /// Full results (content and metadata copies) are only built for the survivors.
+    #[tracing::instrument(name = "score", skip_all, fields(candidates = candidates.len()))]
+    fn score_consolidated_candidates(&self, candidates: Vec<Candidate>, limit: usize, explain: bool, disable_salience_bias: bool) -> Vec<RecallResult> {
+        // Min-heap on score, then tie-break, holding the current top `limit`
+        let mut top: BinaryHeap<Reverse<(ScoreKey, TieBreak, usize)>> = BinaryHeap::with_capacity(limit + 1);
+        let mut breakdowns = Vec::with_capacity(candidates.len());
+        let ranking = self.ranking.load();
+
+        for (handle, positions_info, total_weight) in candidates {
+            let Some(memory_id) = self.ids.resolve(handle) else { continue };
+            let Some(memory) = self.memories.get(&*memory_id) else { continue };
+            let mut breakdown = score_breakdown(&memory, &positions_info, total_weight, disable_salience_bias);
+            if !ranking.rules.is_empty() {
+                (breakdown.rule_multiplier, breakdown.applied_rules) = ranking.apply(&memory);
+                breakdown.score *= breakdown.rule_multiplier;
+            }
+            let tie_break = TieBreak(ScoreKey(memory.created_at), Reverse(memory_id.clone()));
+            drop(memory);
+
+            top.push(Reverse((ScoreKey(breakdown.score), tie_break, breakdowns.len())));
+            breakdowns.push(Some((memory_id, breakdown)));
+            if top.len() > limit {
+                top.pop();
+            }
+        }
+
+        let ranked = top.into_sorted_vec();
+        let mut results = Vec::with_capacity(ranked.len());
+        for Reverse((_, _, index)) in ranked {
+            let Some((memory_id, b)) = breakdowns[index].take() else { continue };
+            let Some(memory) = self.memories.get(&*memory_id) else { continue };
+
+            let explain_data = if explain {
+                Some(serde_json::json!({
+                    "intersection_weighted": b.total_weight,
+                    "intersection_score": b.intersection_score,
+                    "recency_component": b.recency_score,
+                    "frequency_component": b.frequency_score,
+                    "salience_score": b.salience_score,
+                    "match_integrity": b.match_integrity,
+                    "weights": {
+                        "recency": b.avg_w_rec,
+                        "frequency": b.avg_w_freq,
+                        "salience": SALIENCE_SCORE_SCALE
+                    },
+                    "match_count": b.match_count,
+                    "ranking_rules": b.applied_rules,
+                    "rule_multiplier": b.rule_multiplier
+                }))
+            } else {
+                None
+            };
+
+            results.push(RecallResult {
+                memory_id: memory_id.to_string(),
+                content: memory.content.clone(),
+                score: b.score,
+                match_integrity: b.match_integrity,
+                intersection_count: b.match_count as usize,
+                recency_score: b.recency_score,
+                reinforcement_score: b.frequency_score,
+                salience_score: b.salience_score,
+                created_at: memory.created_at,
+                metadata: memory.metadata.clone(),
+                locked: memory.locked,
+                matched_cues: Vec::new(),
+                explain: explain_data,
+            });
+        }
         
-        for (memory_id, positions_info, total_weight) in candidates {
-            if let Some(memory) = self.memories.get(&memory_id) {
-                // Skip consolidated summaries if disabled
-                if disable_systems_consolidation && memory.cues.iter().any(|c| c == "type:summary") {
+        results
+    }
+    
+    /// Cross-check memories, the id table and the cue index. Returns one line per
+    /// problem, empty when consistent. Run it while no writes are in flight.
+    pub fn check_index_consistency(&self) -> Vec<String> {
+        self.verify_index().problems()
+    }
+
+    /// Cross-check memories, the id table and the cue index. Run it while no
+    /// writes are in flight.
+    ///
+    /// Every memory must be listed under each of its cues (temporal
+    /// `episode:` cues are never indexed), every handle in the index must
+    /// resolve to a stored memory holding the cue, and every id in the
+    /// handle table must belong to a memory.
+    pub fn verify_index(&self) -> IndexReport {
+        let found = self.index_disagreements();
+        let posting = |cue: &str, memory_id: String| CuePosting { cue: cue.to_string(), memory_id };
+        let (mut dangling, mut stray) = (Vec::new(), Vec::new());
+        for misplaced in &found.misplaced {
+            match (&misplaced.memory_id, misplaced.exists) {
+                (Some(id), true) => stray.push(posting(&misplaced.cue, id.to_string())),
+                (Some(id), false) => dangling.push(posting(&misplaced.cue, id.to_string())),
+                (None, _) => dangling.push(posting(&misplaced.cue, format!("#{}", misplaced.handle))),
+            }
+        }
+        IndexReport {
+            memories: self.memories.len(),
+            cues: self.cue_index.len(),
+            orphans: found.orphans.into_iter().map(|(cue, memory_id)| CuePosting { cue, memory_id }).collect(),
+            dangling,
+            stray,
+            stale_ids: found.stale_ids.iter().map(|id| id.to_string()).collect(),
+        }
+    }
+
+    /// Rebuild the cue index from the memories: drop entries for missing
+    /// memories or cues they no longer hold, forget stale ids and list
+    /// memories under the cues they are missing from. Restored entries rank
+    /// least recent, oldest access first; the rest keep their order. Returns
+    /// what was found before the rebuild. Run it while no writes are in flight.
+    pub fn rebuild_cue_index(&self) -> IndexReport {
+        let report = self.verify_index();
+        if report.is_consistent() {
+            return report;
+        }
+        let found = self.index_disagreements();
+
+        for misplaced in &found.misplaced {
+            if let Some(mut entry) = self.postings_mut(&misplaced.cue) {
+                entry.remove(misplaced.handle);
+            }
+        }
+        for id in &found.stale_ids {
+            self.ids.release(id);
+        }
+
+        let mut missing: HashMap<String, Vec<(f64, u64)>> = HashMap::new();
+        for (cue, memory_id) in found.orphans {
+            let Some(last_accessed) = self.memories.get(&memory_id).map(|m| m.last_accessed) else { continue };
+            missing.entry(cue).or_default().push((last_accessed, self.ids.intern(&memory_id)));
+        }
+        for (cue, mut handles) in missing {
+            handles.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let mut entry = self.index_entry(self.cues.intern(&cue));
+            let mut rebuilt = OrderedSet::new();
+            for handle in handles.into_iter().map(|(_, handle)| handle).chain(entry.iter()) {
+                rebuilt.add(handle);
+            }
+            *entry = rebuilt;
+        }
+
+        self.prune_empty_cues();
+        self.rebuild_key_cardinality();
+        self.bump_revision();
+        report
+    }
+
+    /// Touch every memory, cue index shard and posting so the first recalls
+    /// after a snapshot load don't pay for page faults, and refresh the
+    /// read view when one is enabled. Returns the cue sizes seen on the way.
+    pub fn warm_up(&self) -> CueSizeStats {
+        let mut checksum = 0u64;
+        for entry in self.memories.iter() {
+            checksum = checksum.wrapping_add(entry.content.len() as u64 + entry.cues.len() as u64);
+        }
+        let mut lens = Vec::with_capacity(self.cue_index.len());
+        for entry in self.cue_index.iter() {
+            checksum = entry.value().iter().fold(checksum, u64::wrapping_add);
+            lens.push(entry.value().len());
+        }
+        std::hint::black_box(checksum);
+        self.refresh_read_view();
+        CueSizeStats::from_lens(lens)
+    }
+
+    fn index_disagreements(&self) -> Disagreements {
+        let mut found = Disagreements::default();
+
+        for entry in self.memories.iter() {
+            let handle = self.ids.get(entry.key());
+            for cue in &entry.cues {
+                let key = index_key(cue);
+                if key.is_empty() || key.starts_with("episode:") {
                     continue;
                 }
-                let mut total_recency = 0.0;
-                let mut total_w_rec = 0.0;
-                let mut total_w_freq = 0.0;
-                
-                let match_count = positions_info.len() as f64;
-
-                for (pos, list_len, weight) in positions_info {
-                    let pos_f64 = pos as f64;
-                    let list_len_f64 = list_len as f64;
-                    let sigma = list_len_f64.sqrt();
-                    let ratio = pos_f64 / sigma;
-                    
-                    let w_rec = MAX_REC_WEIGHT / (ratio + 1.0);
-                    let w_freq = 1.0 + (MAX_FREQ_WEIGHT * (1.0 - (1.0 / (ratio + 1.0))));
-                    
-                    let mut recency_component = 1.0 / (pos_f64 + 1.0);
-                    if pos == 0 {
-                        recency_component += 1.0;
-                    }
-                    
-                    total_recency += recency_component * weight; // Weigh the recency contribution
-                    total_w_rec += w_rec;
-                    total_w_freq += w_freq;
+                let listed = handle.is_some_and(|handle| self.cue_index.get(&*key).is_some_and(|set| set.contains(handle)));
+                if !listed {
+                    found.orphans.push((key.into_owned(), entry.key().clone()));
                 }
-                
-                let avg_w_rec = total_w_rec / match_count;
-                let avg_w_freq = total_w_freq / match_count;
-                let recency_score = total_recency / match_count;
-                
-                let frequency_score = if memory.reinforcement_count > 0 {
-                    (memory.reinforcement_count as f64).log10()
-                } else {
-                    0.0
-                };
-                
-                let salience_score = if disable_salience_bias {
-                    0.0
-                } else {
-                    memory.salience
-                };
-                let intersection_score = total_weight * 100.0;
-                
-                // Final score includes salience
-                let score = intersection_score + (recency_score * avg_w_rec) + (frequency_score * avg_w_freq) + (salience_score * 10.0);
-                
-                // Match integrity calculation
-                // 1. Intersection strength (relative to match count)
-                let intersection_strength = total_weight / match_count.max(1.0);
-                // 2. Context agreement: how many of the memory's cues matched the query
-                let context_agreement = if !memory.cues.is_empty() {
-                    match_count / (memory.cues.len() as f64)
-                } else {
-                    0.0
-                };
-                // 3. Reinforcement boost (capped)
-                let reinforcement_boost = (frequency_score / 2.0).min(1.0);
-                
-                let match_integrity = (intersection_strength * 0.5 + context_agreement * 0.3 + reinforcement_boost * 0.2).min(1.0);
-
-                let explain_data = if explain {
-                    Some(serde_json::json!({
-                        "intersection_weighted": total_weight,
-                        "intersection_score": intersection_score,
-                        "recency_component": recency_score,
-                        "frequency_component": frequency_score,
-                        "salience_score": salience_score,
-                        "match_integrity": match_integrity,
-                        "weights": {
-                            "recency": avg_w_rec,
-                            "frequency": avg_w_freq,
-                            "salience": 10.0
-                        },
-                        "match_count": match_count
-                    }))
-                } else {
-                    None
-                };
+            }
+        }
 
-                results.push(RecallResult {
-                    memory_id,
-                    content: memory.content.clone(),
-                    score,
-                    match_integrity,
-                    intersection_count: match_count as usize,
-                    recency_score,
-                    reinforcement_score: frequency_score,
-                    salience_score,
-                    metadata: memory.metadata.clone(),
-                    explain: explain_data,
+        for entry in self.cue_index.iter() {
+            for handle in entry.value().iter() {
+                let memory_id = self.ids.resolve(handle);
+                let holds = memory_id.as_ref().and_then(|id| self.memories.get(&**id)).map(|memory| {
+                    memory.cues.iter().any(|cue| index_key(cue) == entry.key().as_ref())
                 });
+                if holds != Some(true) {
+                    found.misplaced.push(MisplacedPosting { cue: entry.key().clone(), handle, memory_id, exists: holds.is_some() });
+                }
             }
         }
-        
-        results
+
+        found.stale_ids = self.ids.ids().into_iter().filter(|id| !self.memories.contains_key(&**id)).collect();
+        found
     }
-    
+
     pub fn get_memory(&self, memory_id: &str) -> Option<Memory> {
         self.memories.get(memory_id).map(|m| m.clone())
     }
     
-    pub fn consolidate_memories(&self, cue_overlap_threshold: f64) -> Vec<(String, Vec<String>)> {
+    /// Groups of live memories whose cue sets overlap by at least
+    /// `cue_overlap_threshold` (Jaccard similarity). Tombstoned and locked
+    /// memories are left out.
+    pub fn consolidation_groups(&self, cue_overlap_threshold: f64) -> Vec<Vec<String>> {
         let mut to_merge = Vec::new();
         let mut seen = HashSet::new();
 
-        // 1. Find overlapping memories
         // This is a naive O(N^2) or O(N * C) approach, but we can limit it using cues
         for entry in self.memories.iter() {
             let (id_a, mem_a) = entry.pair();
-            if seen.contains(id_a) { continue; }
+            if seen.contains(id_a) || mem_a.is_tombstoned() || mem_a.locked { continue; }
             
             let mut group = vec![id_a.clone()];
             
             // Use the first cue to find candidates
             if let Some(first_cue) = mem_a.cues.first() {
-                if let Some(ordered_set) = self.cue_index.get(first_cue) {
-                    for id_b in ordered_set.get_recent(None) {
-                        if id_a == id_b || seen.contains(id_b) { continue; }
+                for id_b in self.cue_memory_ids(first_cue, None) {
+                    if *id_a == id_b || seen.contains(&id_b) { continue; }
+                    
+                    if let Some(mem_b) = self.memories.get(&id_b) {
+                        if mem_b.is_tombstoned() || mem_b.locked { continue; }
+                        // Calculate Jaccard similarity of cues
+                        let cues_a: HashSet<_> = mem_a.cues.iter().collect();
+                        let cues_b: HashSet<_> = mem_b.cues.iter().collect();
+                        
+                        let intersection = cues_a.intersection(&cues_b).count();
+                        let union = cues_a.union(&cues_b).count();
+                        let similarity = (intersection as f64) / (union as f64);
                         
-                        if let Some(mem_b) = self.memories.get(id_b) {
-                            // Calculate Jaccard similarity of cues
-                            let cues_a: HashSet<_> = mem_a.cues.iter().collect();
-                            let cues_b: HashSet<_> = mem_b.cues.iter().collect();
-                            
-                            let intersection = cues_a.intersection(&cues_b).count();
-                            let union = cues_a.union(&cues_b).count();
-                            let similarity = (intersection as f64) / (union as f64);
-                            
-                            if similarity >= cue_overlap_threshold {
-                                group.push(id_b.clone());
-                            }
+                        if similarity >= cue_overlap_threshold {
+                            group.push(id_b);
                         }
                     }
                 }
@@ -616,6 +1780,13 @@ impl CueMapEngine {
             }
         }
 
+        to_merge
+    }
+
+    pub fn consolidate_memories(&self, cue_overlap_threshold: f64) -> Vec<(String, Vec<String>)> {
+        // 1. Find overlapping memories
+        let to_merge = self.consolidation_groups(cue_overlap_threshold);
+
         let mut results = Vec::new();
         // 2. Merge groups
         for group in to_merge {
@@ -648,7 +1819,7 @@ impl CueMapEngine {
             metadata.insert("consolidated".to_string(), serde_json::json!(true));
             metadata.insert("original_count".to_string(), serde_json::json!(group.len()));
             
-            let mut cues_vec: Vec<String> = combined_cues.into_iter().collect();
+            let mut cues_vec: Vec<String> = combined_cues.into_iter().map(|cue: Arc<str>| cue.to_string()).collect();
             cues_vec.push("type:summary".to_string());
             
             let new_id = self.add_memory(summary_content, cues_vec, Some(metadata), false);
@@ -658,6 +1829,7 @@ impl CueMapEngine {
                 new_mem.reinforcement_count = total_reinforcement;
                 new_mem.salience = max_salience * 0.8; // Lower priority than fresh memories
             }
+            self.events.publish(ChangeEvent::MemoryMerged { memory_id: new_id.clone(), sources: group.clone() });
             
             results.push((new_id, group));
         }
@@ -665,6 +1837,246 @@ impl CueMapEngine {
         results
     }
 
+    /// Replace `group` with a single `summary` memory carrying their combined
+    /// cues plus `type:summary`, and tombstone the originals so they point at
+    /// it. Locked memories are not merged. Returns the summary's id, or
+    /// `None` when fewer than two of the memories are live and unlocked.
+    pub fn merge_memories(&self, group: &[String], summary: String) -> Option<String> {
+        let sources: Vec<Memory> = group
+            .iter()
+            .filter_map(|id| self.memories.get(id).filter(|m| !m.is_tombstoned() && !m.locked).map(|m| m.clone()))
+            .collect();
+        if sources.len() < 2 {
+            return None;
+        }
+
+        let mut cues: Vec<String> = Vec::new();
+        for cue in sources.iter().flat_map(|m| m.cues.iter()) {
+            if !cue.starts_with("episode:") && !cues.iter().any(|c| **c == **cue) {
+                cues.push(cue.to_string());
+            }
+        }
+        if !cues.iter().any(|c| c == "type:summary") {
+            cues.push("type:summary".to_string());
+        }
+        let source_ids: Vec<String> = sources.iter().map(|m| m.id.clone()).collect();
+
+        let mut metadata = HashMap::new();
+        metadata.insert("consolidated".to_string(), serde_json::json!(true));
+        metadata.insert("original_count".to_string(), serde_json::json!(sources.len()));
+        metadata.insert("consolidated_from".to_string(), serde_json::json!(source_ids));
+
+        let new_id = self.add_memory(summary, cues, Some(metadata), true);
+        if let Some(mut new_mem) = self.memories.get_mut(&new_id) {
+            new_mem.reinforcement_count = sources.iter().map(|m| m.reinforcement_count).sum();
+            new_mem.salience = sources.iter().map(|m| m.salience).fold(0.0, f64::max);
+        }
+        for id in &source_ids {
+            self.tombstone_memory(id, &new_id);
+        }
+        self.events.publish(ChangeEvent::MemoryMerged { memory_id: new_id.clone(), sources: source_ids });
+
+        Some(new_id)
+    }
+
+    /// Take a memory out of recall after it was consolidated into
+    /// `summary_id`. The record stays readable by id: its cues are replaced by
+    /// `status:tombstoned`, the original cues move to the `original_cues`
+    /// metadata entry and `consolidated_into` references the summary.
+    pub fn tombstone_memory(&self, memory_id: &str, summary_id: &str) -> bool {
+        let Some(mut memory) = self.memories.get_mut(memory_id) else { return false };
+        if memory.is_tombstoned() {
+            return false;
+        }
+        let handle = self.ids.intern(memory_id);
+        for cue in &memory.cues {
+            if let Some(mut entry) = self.postings_mut(&index_key(cue)) {
+                entry.remove(handle);
+            }
+        }
+
+        let original_cues: Vec<String> = memory.cues.iter().map(|cue| cue.to_string()).collect();
+        memory.metadata.insert("original_cues".to_string(), serde_json::json!(original_cues));
+        memory.metadata.insert(CONSOLIDATED_INTO_KEY.to_string(), serde_json::json!(summary_id));
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        memory.metadata.insert("tombstoned_at".to_string(), serde_json::json!(now));
+        let tombstone = self.cues.intern("status:tombstoned");
+        memory.cues = vec![tombstone.clone()];
+        drop(memory);
+        self.index_entry(tombstone).add(handle);
+        self.bump_revision();
+
+        true
+    }
+
+    /// Set one metadata entry of a memory in place. Cues are untouched, so
+    /// no change event is published; meant for derived data such as cached
+    /// embeddings. Cached recalls are invalidated, as results carry metadata
+    /// and ranking rules match on it.
+    pub fn set_metadata(&self, memory_id: &str, key: &str, value: serde_json::Value) -> bool {
+        self.update_metadata(memory_id, |metadata| metadata.insert(key.to_string(), value)).is_some()
+    }
+
+    /// Edit a memory's metadata in place while holding its entry, so
+    /// concurrent read-modify-write updates don't lose each other. Publishes
+    /// nothing, like [`set_metadata`](Self::set_metadata). `None` when the
+    /// memory does not exist.
+    pub fn update_metadata<R>(&self, memory_id: &str, f: impl FnOnce(&mut HashMap<String, serde_json::Value>) -> R) -> Option<R> {
+        let result = {
+            let mut memory = self.memories.get_mut(memory_id)?;
+            f(&mut memory.metadata)
+        };
+        self.bump_reorders();
+        Some(result)
+    }
+
+    /// Halve every memory's reinforcement count `halvings` times, the
+    /// forgetting curve applied by the maintenance job. Locked memories keep
+    /// theirs. Returns how many counts changed.
+    pub fn decay_reinforcement(&self, halvings: u32) -> usize {
+        if halvings == 0 {
+            return 0;
+        }
+        let mut decayed = 0;
+        for mut memory in self.memories.iter_mut().filter(|m| !m.locked) {
+            let count = memory.reinforcement_count.checked_shr(halvings).unwrap_or(0);
+            if count != memory.reinforcement_count {
+                memory.reinforcement_count = count;
+                decayed += 1;
+            }
+        }
+        if decayed > 0 {
+            self.bump_reorders();
+        }
+        decayed
+    }
+
+    /// Lower the salience of live memories not accessed since `idle_before`
+    /// (seconds since the epoch) by `penalty`, and move them to the least
+    /// recent position of their cue lists. Locked memories and those already
+    /// at zero salience are left alone. Returns how many were demoted.
+    pub fn demote_stale(&self, idle_before: f64, penalty: f64) -> usize {
+        let stale: Vec<(String, Vec<Arc<str>>)> = self
+            .memories
+            .iter()
+            .filter(|m| m.last_accessed < idle_before && m.salience > 0.0 && !m.is_tombstoned() && !m.locked)
+            .map(|m| (m.id.clone(), m.cues.clone()))
+            .collect();
+
+        for (memory_id, cues) in &stale {
+            if let Some(mut memory) = self.memories.get_mut(memory_id) {
+                memory.salience = (memory.salience - penalty).max(0.0);
+            }
+            let Some(handle) = self.ids.get(memory_id) else { continue };
+            for cue in cues {
+                if let Some(mut entry) = self.postings_mut(&index_key(cue)) {
+                    entry.move_to_back(handle);
+                }
+            }
+        }
+        if !stale.is_empty() {
+            self.bump_reorders();
+        }
+
+        stale.len()
+    }
+
+    /// Drop cue index entries left empty by deletes, along with their
+    /// co-occurrence counts. Returns how many cues were pruned.
+    pub fn prune_empty_cues(&self) -> usize {
+        let mut pruned: HashSet<Arc<str>> = HashSet::new();
+        self.cue_index.retain(|cue, set| {
+            if set.is_empty() {
+                pruned.insert(cue.clone());
+                false
+            } else {
+                true
+            }
+        });
+        if pruned.is_empty() {
+            return 0;
+        }
+
+        self.cue_co_occurrence.retain(|cue, related| {
+            related.retain(|other, _| !pruned.contains(other));
+            !pruned.contains(cue) && !related.is_empty()
+        });
+        self.cues.purge_unused();
+        self.rebuild_key_cardinality();
+        self.bump_revision();
+
+        pruned.len()
+    }
+
+    /// Estimate the bytes held by memories, the cue index and the co-occurrence matrix.
+    /// Walks every entry, so cost is proportional to the store size.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let memories_bytes = with_table_overhead(
+            self.memories
+                .iter()
+                .map(|entry| {
+                    let memory = entry.value();
+                    let metadata: usize = memory
+                        .metadata
+                        .iter()
+                        .map(|(k, v)| string_bytes(k) + std::mem::size_of::<serde_json::Value>() + v.to_string().len())
+                        .sum();
+                    string_bytes(entry.key())
+                        + std::mem::size_of::<Memory>()
+                        + memory.id.len()
+                        + memory.content.len()
+                        + memory.cues.len() * std::mem::size_of::<Arc<str>>()
+                        + metadata
+                })
+                .sum(),
+        );
+
+        let cue_index_bytes = with_table_overhead(
+            self.cue_index
+                .iter()
+                .map(|entry| std::mem::size_of::<Arc<str>>() + std::mem::size_of::<OrderedSet>() + entry.value().estimated_bytes())
+                .sum(),
+        );
+
+        let co_occurrence_bytes = with_table_overhead(
+            self.cue_co_occurrence
+                .iter()
+                .map(|entry| {
+                    let inner: usize = entry
+                        .value()
+                        .iter()
+                        .map(|_| std::mem::size_of::<Arc<str>>() + std::mem::size_of::<u64>())
+                        .sum();
+                    std::mem::size_of::<Arc<str>>() + std::mem::size_of::<DashMap<Arc<str>, u64>>() + with_table_overhead(inner)
+                })
+                .sum(),
+        );
+
+        // Each interned id is one shared string referenced from both directions of the table
+        let id_table_bytes = with_table_overhead(
+            self.ids.len() * (2 * (std::mem::size_of::<Arc<str>>() + std::mem::size_of::<u64>()))
+                + self.memories.iter().map(|entry| entry.key().len()).sum::<usize>(),
+        );
+
+        MemoryUsage {
+            memory_count: self.memories.len(),
+            cue_count: self.cue_index.len(),
+            memories_bytes,
+            cue_index_bytes,
+            co_occurrence_bytes,
+            id_table_bytes,
+            total_bytes: memories_bytes + cue_index_bytes + co_occurrence_bytes + id_table_bytes,
+        }
+    }
+
+    /// Estimated bytes of the (possibly shared) cue string table.
+    pub fn cue_table_bytes(&self) -> usize {
+        with_table_overhead(self.cues.estimated_bytes())
+    }
+
     pub fn get_stats(&self) -> HashMap<String, serde_json::Value> {
         let mut stats = HashMap::new();
         stats.insert(
@@ -676,8 +2088,15 @@ impl CueMapEngine {
             serde_json::json!(self.cue_index.len()),
         );
         
-        let cues: Vec<String> = self.cue_index.iter().map(|e| e.key().clone()).collect();
+        let cues: Vec<String> = self.cue_index.iter().map(|e| e.key().to_string()).collect();
         stats.insert("cues".to_string(), serde_json::json!(cues));
+        stats.insert("key_cardinality".to_string(), serde_json::json!(self.key_cardinalities()));
+        if let Some((epoch, pending_cues)) = self.read_view_status() {
+            stats.insert(
+                "read_view".to_string(),
+                serde_json::json!({"epoch": epoch, "pending_cues": pending_cues}),
+            );
+        }
         
         stats
     }
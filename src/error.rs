@@ -0,0 +1,148 @@
+//! Errors returned by the HTTP API.
+//!
+//! Every failed request answers with an RFC 7807 `application/problem+json`
+//! body. Besides the standard `type`, `title`, `status` and `detail` members
+//! it carries a stable machine-readable `code` and the `request_id` also sent
+//! in the `X-Request-Id` header, so a client report can be matched to the
+//! server's log lines. `error` repeats `detail` for clients written against
+//! the earlier `{"error": ...}` bodies.
+
+use crate::telemetry::current_request_id;
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde_json::Value;
+use std::fmt;
+
+/// Media type of error bodies.
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiError {
+    /// The request is malformed or asks for something invalid
+    BadRequest(String),
+    /// The request failed validation; `problems` lists every issue found
+    Invalid { detail: String, problems: Vec<String> },
+    /// No API key, or one the server does not know
+    Unauthorized(String),
+    /// The API key's scope or the client address does not allow the request
+    Forbidden(String),
+    /// The server does not accept writes (`--load-static`, followers)
+    ReadOnly,
+    NotFound(String),
+    /// An optional component (webhooks, evals) is not installed
+    NotEnabled(&'static str),
+    /// The endpoint only exists in the other tenancy mode
+    WrongMode(&'static str),
+    /// The request clashes with the current state of the resource
+    Conflict(String),
+    /// The request ran past its route's time budget
+    Timeout(String),
+    /// An `If-Match` precondition no longer holds
+    PreconditionFailed(String),
+    /// The request body, or a value in it, is over a configured size limit
+    PayloadTooLarge(String),
+    /// An ingest hook refused to store the memory
+    HookRejected(String),
+    /// The server cannot take the request now, e.g. a route is at its concurrency budget
+    Unavailable(String),
+    Internal(String),
+}
+
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) | ApiError::Invalid { .. } => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) | ApiError::ReadOnly => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) | ApiError::NotEnabled(_) | ApiError::WrongMode(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::Timeout(_) => StatusCode::REQUEST_TIMEOUT,
+            ApiError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            ApiError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::HookRejected(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Stable error code for clients to branch on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::Invalid { .. } => "validation_failed",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::Forbidden(_) => "forbidden",
+            ApiError::ReadOnly => "read_only",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::NotEnabled(_) => "not_enabled",
+            ApiError::WrongMode(_) => "wrong_mode",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::Timeout(_) => "timeout",
+            ApiError::PreconditionFailed(_) => "precondition_failed",
+            ApiError::PayloadTooLarge(_) => "payload_too_large",
+            ApiError::HookRejected(_) => "hook_rejected",
+            ApiError::Unavailable(_) => "unavailable",
+            ApiError::Internal(_) => "internal",
+        }
+    }
+
+    pub fn detail(&self) -> String {
+        match self {
+            ApiError::BadRequest(detail)
+            | ApiError::Invalid { detail, .. }
+            | ApiError::Unauthorized(detail)
+            | ApiError::Forbidden(detail)
+            | ApiError::NotFound(detail)
+            | ApiError::Conflict(detail)
+            | ApiError::Timeout(detail)
+            | ApiError::PreconditionFailed(detail)
+            | ApiError::PayloadTooLarge(detail)
+            | ApiError::HookRejected(detail)
+            | ApiError::Unavailable(detail)
+            | ApiError::Internal(detail) => detail.clone(),
+            ApiError::ReadOnly => "Read-only mode: modifications are not allowed".to_string(),
+            ApiError::NotEnabled(component) => format!("{} are not enabled", component),
+            ApiError::WrongMode(mode) => format!("Endpoint is only available in {} mode", mode),
+        }
+    }
+
+    /// The problem+json body, with the current request's id when known.
+    pub fn to_problem(&self) -> Value {
+        let status = self.status();
+        let mut problem = serde_json::json!({
+            "type": "about:blank",
+            "title": status.canonical_reason().unwrap_or("Error"),
+            "status": status.as_u16(),
+            "detail": self.detail(),
+            "code": self.code(),
+            "error": self.detail(),
+        });
+        if let ApiError::Invalid { problems, .. } = self {
+            problem["problems"] = serde_json::json!(problems);
+        }
+        if let Some(request_id) = current_request_id() {
+            problem["request_id"] = serde_json::json!(request_id);
+        }
+        problem
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code(), self.detail())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        if self.status().is_server_error() {
+            tracing::error!("Request failed: {}", self);
+        }
+        let body = self.to_problem().to_string();
+        let mut response = (self.status(), body).into_response();
+        response.headers_mut().insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        response
+    }
+}
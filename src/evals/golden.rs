@@ -0,0 +1,315 @@
+//! Golden sets: labeled queries for measuring recall quality.
+//!
+//! A [`GoldenSet`] is a named list of queries, each with the ids of the
+//! memories it should return. [`run_set`] recalls every query against a
+//! project and scores the results with precision, recall and reciprocal rank
+//! at the query's `limit`. [`EvalStore`] keeps the sets of every project and
+//! the history of runs, so the effect of scoring changes and alias or
+//! lexicon training shows up as a trend instead of being eyeballed.
+//!
+//! Eval recalls never reinforce memories, but recall hooks run as usual.
+
+use crate::config::EVAL_RUNS_PER_SET;
+use crate::projects::ProjectContext;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+/// Golden sets of every project, in the data directory.
+pub const EVAL_SETS_FILE: &str = "eval_sets.json";
+/// Run history (one summary per line), in the data directory.
+pub const EVAL_RUNS_FILE: &str = "eval_runs.jsonl";
+
+fn default_limit() -> usize {
+    10
+}
+
+/// A query and the memories it should return.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GoldenQuery {
+    #[serde(default)]
+    pub cues: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_text: Option<String>,
+    /// Ids of the relevant memories
+    pub expected: Vec<String>,
+    /// Results considered, the `k` of precision@k
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GoldenSet {
+    pub name: String,
+    pub queries: Vec<GoldenQuery>,
+}
+
+impl GoldenSet {
+    /// Every problem with the set; empty when it can be run.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.name.trim().is_empty() {
+            problems.push("set name is empty".to_string());
+        }
+        if self.queries.is_empty() {
+            problems.push("set has no queries".to_string());
+        }
+        for (i, query) in self.queries.iter().enumerate() {
+            if query.cues.is_empty() && query.query_text.as_deref().is_none_or(|text| text.trim().is_empty()) {
+                problems.push(format!("query {}: needs cues or query_text", i + 1));
+            }
+            if query.expected.is_empty() {
+                problems.push(format!("query {}: expected is empty", i + 1));
+            }
+            if query.limit == 0 {
+                problems.push(format!("query {}: limit must be positive", i + 1));
+            }
+        }
+        problems
+    }
+}
+
+/// How one query of a run scored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryScore {
+    /// Returned memory ids, best first
+    pub returned: Vec<String>,
+    /// Expected memories that were not returned
+    pub missed: Vec<String>,
+    pub precision: f64,
+    pub recall: f64,
+    /// 1 / rank of the first expected memory, 0 when none was returned
+    pub reciprocal_rank: f64,
+}
+
+/// Scores of one golden set run, averaged over its queries.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalRun {
+    pub id: String,
+    pub project: String,
+    pub set: String,
+    /// Seconds since the epoch
+    pub run_at: u64,
+    pub queries: usize,
+    pub precision: f64,
+    pub recall: f64,
+    pub mrr: f64,
+    /// Per-query detail, in set order; not kept in the run history
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub per_query: Vec<QueryScore>,
+}
+
+fn score_query(returned: Vec<String>, expected: &[String]) -> QueryScore {
+    let expected_ids: HashSet<&str> = expected.iter().map(String::as_str).collect();
+    let hits = returned.iter().filter(|id| expected_ids.contains(id.as_str())).count();
+    let reciprocal_rank = returned
+        .iter()
+        .position(|id| expected_ids.contains(id.as_str()))
+        .map_or(0.0, |rank| 1.0 / (rank + 1) as f64);
+    let returned_ids: HashSet<&str> = returned.iter().map(String::as_str).collect();
+    let missed = expected.iter().filter(|id| !returned_ids.contains(id.as_str())).cloned().collect();
+    QueryScore {
+        precision: if returned.is_empty() { 0.0 } else { hits as f64 / returned.len() as f64 },
+        recall: if expected_ids.is_empty() { 0.0 } else { hits as f64 / expected_ids.len() as f64 },
+        reciprocal_rank,
+        missed,
+        returned,
+    }
+}
+
+/// Memory ids `query` recalls from `project`, best first.
+fn recall(project: &ProjectContext, query: &GoldenQuery) -> Vec<String> {
+    let query_cues = project.query_cues(&query.cues, query.query_text.as_deref());
+    let expanded_cues = project.expand_weighted_query_cues(query_cues);
+    let mut results = project.main.recall_weighted(expanded_cues.clone(), query.limit, false, None, false, false, false, false, None);
+    project.after_recall(&expanded_cues, query.query_text.as_deref(), &mut results);
+    results.into_iter().take(query.limit).map(|r| r.memory_id).collect()
+}
+
+/// Run every query of `set` against `project`.
+pub fn run_set(project_id: &str, project: &ProjectContext, set: &GoldenSet) -> EvalRun {
+    let per_query: Vec<QueryScore> = set.queries.iter().map(|query| score_query(recall(project, query), &query.expected)).collect();
+    let mean = |score: fn(&QueryScore) -> f64| {
+        if per_query.is_empty() {
+            0.0
+        } else {
+            per_query.iter().map(score).sum::<f64>() / per_query.len() as f64
+        }
+    };
+    let run = EvalRun {
+        id: uuid::Uuid::new_v4().to_string(),
+        project: project_id.to_string(),
+        set: set.name.clone(),
+        run_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+        queries: per_query.len(),
+        precision: mean(|q| q.precision),
+        recall: mean(|q| q.recall),
+        mrr: mean(|q| q.reciprocal_rank),
+        per_query,
+    };
+    info!(
+        "Eval {} on project {}: precision={:.3} recall={:.3} mrr={:.3} over {} queries",
+        run.set, run.project, run.precision, run.recall, run.mrr, run.queries
+    );
+    run
+}
+
+/// Golden sets per project and the history of their runs.
+#[derive(Default)]
+pub struct EvalStore {
+    /// Project id → set name → set
+    sets: RwLock<BTreeMap<String, BTreeMap<String, GoldenSet>>>,
+    runs: RwLock<Vec<EvalRun>>,
+    /// Data directory; `None` keeps everything in memory only.
+    dir: Option<PathBuf>,
+}
+
+impl EvalStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load sets and run history from `dir`, starting empty when the files
+    /// are missing or unreadable.
+    pub fn open(dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        let sets_path = dir.join(EVAL_SETS_FILE);
+        let sets = match fs::read(&sets_path) {
+            Ok(data) => serde_json::from_slice(&data).unwrap_or_else(|e| {
+                warn!("Failed to load eval sets from {:?}: {}", sets_path, e);
+                BTreeMap::new()
+            }),
+            Err(_) => BTreeMap::new(),
+        };
+        let mut runs: Vec<EvalRun> = fs::read_to_string(dir.join(EVAL_RUNS_FILE))
+            .map(|data| data.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+            .unwrap_or_default();
+        let trimmed = trim_runs(&mut runs);
+        let store = Self { sets: RwLock::new(sets), runs: RwLock::new(runs), dir: Some(dir) };
+        if trimmed {
+            store.persist_runs(&store.runs.read().unwrap());
+        }
+        store
+    }
+
+    /// Add or replace a set of `project`. Errors with the set's problems.
+    pub fn put_set(&self, project: &str, set: GoldenSet) -> Result<(), Vec<String>> {
+        let problems = set.validate();
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+        let mut sets = self.sets.write().unwrap();
+        sets.entry(project.to_string()).or_default().insert(set.name.clone(), set);
+        self.persist_sets(&sets);
+        Ok(())
+    }
+
+    pub fn get_set(&self, project: &str, name: &str) -> Option<GoldenSet> {
+        self.sets.read().unwrap().get(project)?.get(name).cloned()
+    }
+
+    /// Sets of `project`, by name.
+    pub fn sets(&self, project: &str) -> Vec<GoldenSet> {
+        self.sets.read().unwrap().get(project).map(|sets| sets.values().cloned().collect()).unwrap_or_default()
+    }
+
+    pub fn delete_set(&self, project: &str, name: &str) -> bool {
+        let mut sets = self.sets.write().unwrap();
+        let Some(project_sets) = sets.get_mut(project) else { return false };
+        if project_sets.remove(name).is_none() {
+            return false;
+        }
+        if project_sets.is_empty() {
+            sets.remove(project);
+        }
+        self.persist_sets(&sets);
+        true
+    }
+
+    /// Append a run, without its per-query detail, to the history. Beyond
+    /// [`EVAL_RUNS_PER_SET`] runs of its set, the oldest is dropped and the
+    /// history file rewritten.
+    pub fn record(&self, run: &EvalRun) {
+        let summary = EvalRun { per_query: Vec::new(), ..run.clone() };
+        let mut runs = self.runs.write().unwrap();
+        runs.push(summary);
+        if trim_runs(&mut runs) {
+            self.persist_runs(&runs);
+            return;
+        }
+        let Some(dir) = &self.dir else { return };
+        let path = dir.join(EVAL_RUNS_FILE);
+        let appended = serde_json::to_string(&runs[runs.len() - 1]).map_err(std::io::Error::other).and_then(|line| {
+            let mut file = fs::OpenOptions::new().create(true).append(true).open(&path)?;
+            writeln!(file, "{}", line)
+        });
+        if let Err(e) = appended {
+            warn!("Failed to append eval run to {:?}: {}", path, e);
+        }
+    }
+
+    /// Past runs of `project`, oldest first, optionally of one set only.
+    pub fn runs(&self, project: &str, set: Option<&str>) -> Vec<EvalRun> {
+        self.runs
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|run| run.project == project && set.is_none_or(|set| run.set == set))
+            .cloned()
+            .collect()
+    }
+
+    fn persist_runs(&self, runs: &[EvalRun]) {
+        let Some(dir) = &self.dir else { return };
+        let path = dir.join(EVAL_RUNS_FILE);
+        let written = runs
+            .iter()
+            .map(|run| serde_json::to_string(run).map(|line| line + "\n"))
+            .collect::<Result<String, _>>()
+            .map_err(std::io::Error::other)
+            .and_then(|data| {
+                let temp_path = path.with_extension("jsonl.tmp");
+                fs::write(&temp_path, data)?;
+                fs::rename(&temp_path, &path)
+            });
+        if let Err(e) = written {
+            warn!("Failed to rewrite eval runs in {:?}: {}", path, e);
+        }
+    }
+
+    fn persist_sets(&self, sets: &BTreeMap<String, BTreeMap<String, GoldenSet>>) {
+        let Some(dir) = &self.dir else { return };
+        let path = dir.join(EVAL_SETS_FILE);
+        if let Err(e) = Self::save(&path, sets) {
+            warn!("Failed to persist eval sets to {:?}: {}", path, e);
+        }
+    }
+
+    fn save(path: &Path, sets: &BTreeMap<String, BTreeMap<String, GoldenSet>>) -> std::io::Result<()> {
+        let data = serde_json::to_vec_pretty(sets)?;
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, data)?;
+        fs::rename(&temp_path, path)
+    }
+}
+
+/// Drop the oldest runs of every project and set beyond [`EVAL_RUNS_PER_SET`].
+/// Returns whether any were dropped.
+fn trim_runs(runs: &mut Vec<EvalRun>) -> bool {
+    let mut kept: HashMap<(String, String), usize> = HashMap::new();
+    let mut keep = vec![false; runs.len()];
+    for (i, run) in runs.iter().enumerate().rev() {
+        let count = kept.entry((run.project.clone(), run.set.clone())).or_default();
+        *count += 1;
+        keep[i] = *count <= EVAL_RUNS_PER_SET;
+    }
+    let before = runs.len();
+    let mut keep = keep.into_iter();
+    runs.retain(|_| keep.next().unwrap_or(true));
+    runs.len() < before
+}
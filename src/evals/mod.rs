@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use crate::engine::RecallResult;
 
 pub mod runner;
+pub mod golden;
 pub mod evals;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,93 @@
+//! Change feed of a project's memory store.
+//!
+//! Every [`CueMapEngine`](crate::engine::CueMapEngine) publishes memory adds,
+//! deletes, reinforcements and consolidation merges to its [`ChangeFeed`];
+//! the job worker adds job completions and stale memory digests to the feed
+//! of the project's main engine. `GET /stream` relays the feed as server-sent events, and webhooks
+//! (see [`crate::webhooks`]) deliver it to subscribed URLs.
+//!
+//! The feed is a bounded broadcast channel: nothing is buffered while nobody
+//! listens, and a subscriber that falls more than [`CHANGE_FEED_CAPACITY`]
+//! events behind loses the oldest ones.
+
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+/// Events held per project for slow subscribers before they start to lag.
+pub const CHANGE_FEED_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChangeEvent {
+    MemoryAdded { memory_id: String, cues: Vec<String> },
+    MemoryDeleted { memory_id: String },
+    MemoryReinforced { memory_id: String, cues: Vec<String> },
+    /// The memory's content was replaced; `cues` is its cue set afterwards
+    MemoryUpdated { memory_id: String, cues: Vec<String> },
+    /// Consolidation summarized `sources` into the new memory `memory_id`
+    MemoryMerged { memory_id: String, sources: Vec<String> },
+    /// A background job finished; `memory_id` is set for per-memory jobs
+    JobCompleted { job: String, memory_id: Option<String> },
+    /// The digest memory `memory_id` lists `memory_ids` as stale or going stale
+    StaleDigest { memory_id: String, memory_ids: Vec<String> },
+}
+
+impl ChangeEvent {
+    /// Event name used for the SSE `event:` field.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ChangeEvent::MemoryAdded { .. } => "memory_added",
+            ChangeEvent::MemoryDeleted { .. } => "memory_deleted",
+            ChangeEvent::MemoryReinforced { .. } => "memory_reinforced",
+            ChangeEvent::MemoryUpdated { .. } => "memory_updated",
+            ChangeEvent::MemoryMerged { .. } => "memory_merged",
+            ChangeEvent::JobCompleted { .. } => "job_completed",
+            ChangeEvent::StaleDigest { .. } => "stale_digest",
+        }
+    }
+}
+
+/// A published event with its publication time (seconds since the epoch).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChangeRecord {
+    pub at: f64,
+    #[serde(flatten)]
+    pub event: ChangeEvent,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChangeFeed {
+    sender: broadcast::Sender<ChangeRecord>,
+}
+
+impl Default for ChangeFeed {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(CHANGE_FEED_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl ChangeFeed {
+    /// Whether anyone is listening; lets publishers skip building events.
+    pub fn has_subscribers(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+
+    pub fn publish(&self, event: ChangeEvent) {
+        if !self.has_subscribers() {
+            return;
+        }
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        // Fails only when the last subscriber left in the meantime
+        let _ = self.sender.send(ChangeRecord { at, event });
+    }
+
+    /// Events published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<ChangeRecord> {
+        self.sender.subscribe()
+    }
+}
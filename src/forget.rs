@@ -0,0 +1,265 @@
+//! Right-to-be-forgotten deletes (`POST /admin/forget`).
+//!
+//! A [`Selector`] names a cue (`user:id_123`), metadata values, or both; every
+//! memory of the project matching all of them is deleted. That includes
+//! memories tombstoned by consolidation whose original cues match. Aliases
+//! from or to the cue and the lexicon entry of the cue go too. The other
+//! lexicon entries trained on a deleted memory are retrained from the
+//! memories left, so no token of the erased content remains, and cues the
+//! taxonomy quarantined from the memories are dropped. Grounding traces that
+//! selected or excluded a forgotten memory are dropped and the trace log is
+//! compacted right away.
+//!
+//! `POST /admin/forget` saves the project's snapshot as soon as the purge is
+//! done, so a restart can't bring the memories back; the report says whether
+//! that save happened. Followers and the shared snapshot store still hold
+//! the memories until they next sync with the primary, which the report
+//! states too.
+//!
+//! The outcome is returned as a [`DeletionReport`] signed by a
+//! [`ReportSigner`], so it can be filed as proof of erasure.
+
+use crate::grounding::GroundingTraceStore;
+use crate::jobs::is_lexicon_trainable;
+use crate::projects::ProjectContext;
+use crate::structures::Memory;
+use crate::webhooks::sign;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+/// Signing key file in the data directory, used when
+/// `CUEMAP_FORGET_SIGNING_KEY` is not set.
+pub const SIGNING_KEY_FILE: &str = "forget_signing.key";
+
+/// Which memories to forget. Every given criterion must match.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Selector {
+    /// A cue, normalized like the project's cues before matching
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cue: Option<String>,
+    /// Metadata entries that must all be equal; ordered so reports
+    /// serialize, and therefore sign, the same way every time
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, serde_json::Value>,
+}
+
+impl Selector {
+    pub fn is_empty(&self) -> bool {
+        self.cue.as_deref().is_none_or(|cue| cue.trim().is_empty()) && self.metadata.is_empty()
+    }
+
+    fn matches(&self, memory: &Memory, cue: Option<&str>) -> bool {
+        if let Some(cue) = cue {
+            let has_cue = memory.cues.iter().any(|c| c.to_lowercase() == cue)
+                || memory.metadata.get("original_cues").and_then(|v| v.as_array()).is_some_and(|original| {
+                    original.iter().any(|c| c.as_str().is_some_and(|c| c.to_lowercase() == cue))
+                });
+            if !has_cue {
+                return false;
+            }
+        }
+        self.metadata.iter().all(|(key, value)| memory.metadata.get(key) == Some(value))
+    }
+}
+
+/// What a forget request deleted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeletionReport {
+    pub id: String,
+    pub project: String,
+    pub selector: Selector,
+    /// Ids of deleted memories
+    pub memories: Vec<String>,
+    /// Ids of deleted aliases
+    pub aliases: Vec<String>,
+    /// Ids of deleted lexicon entries
+    pub lexicon: Vec<String>,
+    /// Ids of lexicon entries retrained without the deleted memories' content
+    pub lexicon_retrained: Vec<String>,
+    /// Quarantined cues dropped with the deleted memories
+    pub quarantined_cues: usize,
+    /// Grounding traces dropped because they referenced a deleted memory
+    pub grounding_traces: usize,
+    /// `saved` once the project's snapshot was rewritten without the
+    /// memories, else `next_snapshot`: the snapshot on disk still holds them
+    pub snapshot_purge: String,
+    /// Followers and the shared snapshot store hold the memories until they
+    /// next sync (`next_sync`)
+    pub replica_purge: String,
+    /// Seconds since the epoch; whole seconds so the report re-serializes
+    /// byte for byte when its signature is checked
+    pub deleted_at: u64,
+}
+
+/// Delete everything `selector` matches in `project` and drop the grounding
+/// traces that reference it.
+pub fn forget(project_id: &str, project: &ProjectContext, traces: &GroundingTraceStore, selector: &Selector) -> DeletionReport {
+    let cue = selector
+        .cue
+        .as_ref()
+        .map(|cue| project.normalize_cues(std::slice::from_ref(cue)).remove(0).to_lowercase());
+
+    let matched: Vec<(String, Vec<Arc<str>>, String)> = project
+        .main
+        .get_memories()
+        .iter()
+        .filter(|m| selector.matches(m.value(), cue.as_deref()))
+        .map(|m| (m.key().clone(), m.cues.clone(), m.content.clone()))
+        .collect();
+    let mut memories = Vec::with_capacity(matched.len());
+    let mut trained_cues: HashSet<Arc<str>> = HashSet::new();
+    for (memory_id, cues, content) in matched {
+        if project.main.delete_memory(&memory_id) {
+            project.forget_document(&content);
+            trained_cues.extend(cues.into_iter().filter(|cue| is_lexicon_trainable(cue)));
+            memories.push(memory_id);
+        }
+    }
+
+    let mut aliases = Vec::new();
+    let mut lexicon = Vec::new();
+    if let Some(cue) = &cue {
+        let mut alias_ids: HashSet<String> = HashSet::new();
+        alias_ids.extend(project.aliases.cue_memory_ids(&format!("from:{}", cue), None));
+        alias_ids.extend(project.aliases.cue_memory_ids(&format!("to:{}", cue), None));
+        for alias_id in alias_ids {
+            if project.aliases.delete_memory(&alias_id) {
+                aliases.push(alias_id);
+            }
+        }
+        let lex_id = format!("cue:{}", cue);
+        if project.lexicon.delete_memory(&lex_id) {
+            lexicon.push(lex_id);
+        }
+        trained_cues.retain(|trained| **trained != **cue);
+    }
+    let lexicon_retrained = retrain_lexicon(project, &trained_cues, &mut lexicon);
+    project.prune_display_names();
+    project.query_cache.clear();
+
+    let forgotten: HashSet<&str> = memories.iter().map(String::as_str).collect();
+    let quarantined_cues = project.rejections.forget_memories(&forgotten);
+    let grounding_traces = traces.forget_memories(project_id, &forgotten);
+
+    info!(
+        "Forgot {} memories, {} aliases and {} lexicon entries in project {}",
+        memories.len(),
+        aliases.len(),
+        lexicon.len(),
+        project_id
+    );
+    DeletionReport {
+        id: uuid::Uuid::new_v4().to_string(),
+        project: project_id.to_string(),
+        selector: selector.clone(),
+        memories,
+        aliases,
+        lexicon,
+        lexicon_retrained,
+        quarantined_cues,
+        grounding_traces,
+        snapshot_purge: "next_snapshot".to_string(),
+        replica_purge: "next_sync".to_string(),
+        deleted_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+    }
+}
+
+/// Rebuild the lexicon entries of `cues` from the memories that still hold
+/// them, as ingest trained them; entries no memory holds any more are
+/// deleted and their ids added to `deleted`. Returns the retrained ids.
+fn retrain_lexicon(project: &ProjectContext, cues: &HashSet<Arc<str>>, deleted: &mut Vec<String>) -> Vec<String> {
+    let mut tokens: BTreeMap<Arc<str>, Vec<String>> = cues.iter().map(|cue| (cue.clone(), Vec::new())).collect();
+    for memory in project.main.get_memories().iter() {
+        let held: Vec<&Arc<str>> = memory.cues.iter().filter(|cue| tokens.contains_key(*cue)).collect();
+        if held.is_empty() {
+            continue;
+        }
+        let content_tokens = project.lexicon_tokens(&memory.content);
+        for cue in held {
+            tokens.get_mut(cue).unwrap().extend(content_tokens.iter().cloned());
+        }
+    }
+
+    let mut retrained = Vec::new();
+    for (cue, tokens) in tokens {
+        let lex_id = format!("cue:{}", cue);
+        if !project.lexicon.delete_memory(&lex_id) {
+            continue;
+        }
+        if tokens.is_empty() {
+            deleted.push(lex_id);
+        } else {
+            let mut seen = HashSet::new();
+            let tokens: Vec<String> = tokens.into_iter().filter(|token| seen.insert(token.clone())).collect();
+            project.lexicon.upsert_memory_with_id(lex_id.clone(), cue.to_string(), tokens, None, false);
+            retrained.push(lex_id);
+        }
+    }
+    project.lexicon.prune_empty_cues();
+    retrained
+}
+
+/// Signs deletion reports with HMAC-SHA256.
+pub struct ReportSigner {
+    key: String,
+}
+
+impl ReportSigner {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self { key: key.into() }
+    }
+
+    /// Key from `CUEMAP_FORGET_SIGNING_KEY`, else from the key file at `path`,
+    /// which is created with a random key on first use.
+    pub fn open(path: impl AsRef<Path>) -> Self {
+        if let Ok(key) = std::env::var("CUEMAP_FORGET_SIGNING_KEY") {
+            if !key.is_empty() {
+                return Self::new(key);
+            }
+        }
+        let path = path.as_ref();
+        if let Ok(key) = fs::read_to_string(path) {
+            let key = key.trim();
+            if !key.is_empty() {
+                return Self::new(key);
+            }
+        }
+        let key = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+        if let Err(e) = fs::write(path, &key) {
+            warn!("Failed to save deletion report signing key to {:?}: {}", path, e);
+        }
+        Self::new(key)
+    }
+
+    /// `sha256=<hex HMAC>` of the report's JSON encoding.
+    pub fn sign(&self, report: &DeletionReport) -> String {
+        let body = serde_json::to_vec(report).unwrap_or_default();
+        format!("sha256={}", sign(&self.key, &body))
+    }
+
+    /// Whether `signature` is this signer's signature of `report`, compared
+    /// in constant time.
+    pub fn verify(&self, report: &DeletionReport, signature: &str) -> bool {
+        let Some(expected) = signature.strip_prefix("sha256=").and_then(decode_hex) else {
+            return false;
+        };
+        let body = serde_json::to_vec(report).unwrap_or_default();
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.key.as_bytes()).expect("HMAC accepts keys of any length");
+        mac.update(&body);
+        mac.verify_slice(&expected).is_ok()
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok()).collect()
+}
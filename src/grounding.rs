@@ -1,5 +1,13 @@
 use crate::engine::RecallResult;
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{error, info, warn};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SelectedItem {
@@ -34,6 +42,21 @@ pub struct GroundingProof {
     pub excluded_top: Vec<ExcludedItem>,
 }
 
+/// Recency constraints applied during grounded selection.
+#[derive(Debug, Clone, Default)]
+pub struct FreshnessPolicy {
+    /// Memories created more than this many days ago are excluded.
+    pub max_age_days: Option<f64>,
+    /// Decay scores by age so fresher memories win the token budget.
+    pub prefer_fresh: bool,
+}
+
+/// Age at which `prefer_fresh` halves a memory's score.
+pub const FRESHNESS_HALF_LIFE_DAYS: f64 = 30.0;
+
+/// Only the top exclusions are tracked in the proof.
+const MAX_EXCLUDED_TRACKED: usize = 5;
+
 pub struct GroundingEngine;
 
 impl GroundingEngine {
@@ -42,18 +65,22 @@ impl GroundingEngine {
         ((content.len() as f64) / 4.0).ceil() as u32
     }
 
+    #[tracing::instrument(name = "grounding_select", skip_all, fields(candidates = results.len(), token_budget))]
     pub fn select_memories(
         _query_text: String,
         _normalized_query: Vec<String>,
         _expanded_cues: Vec<(String, f64)>,
         results: Vec<RecallResult>,
         token_budget: u32,
+        freshness: &FreshnessPolicy,
     ) -> (Vec<SelectedItem>, Vec<ExcludedItem>, String) {
         let mut selected = Vec::new();
         let mut excluded_top = Vec::new();
         let mut current_tokens = 0;
 
-        // Results are already sorted by cue_score desc from engine.rs
+        let results = Self::apply_freshness(results, freshness, &mut excluded_top);
+
+        // Results are sorted by (freshness-adjusted) score desc
         // We perform a greedy selection
         for result in results {
             let tokens = Self::estimate_tokens(&result.content);
@@ -94,7 +121,7 @@ impl GroundingEngine {
                 });
                 current_tokens += tokens;
             } else {
-                if excluded_top.len() < 5 { // Only track top 5 exclusions
+                if excluded_top.len() < MAX_EXCLUDED_TRACKED {
                     excluded_top.push(ExcludedItem {
                         memory_id: result.memory_id,
                         score: result.score,
@@ -108,6 +135,49 @@ impl GroundingEngine {
         (selected, excluded_top, context_block)
     }
 
+    /// Drops memories older than `max_age_days` (recorded as "too_old"
+    /// exclusions) and, with `prefer_fresh`, decays scores by age and re-sorts.
+    fn apply_freshness(
+        results: Vec<RecallResult>,
+        freshness: &FreshnessPolicy,
+        excluded_top: &mut Vec<ExcludedItem>,
+    ) -> Vec<RecallResult> {
+        if freshness.max_age_days.is_none() && !freshness.prefer_fresh {
+            return results;
+        }
+
+        let now = now_secs();
+        let mut kept = Vec::with_capacity(results.len());
+
+        for mut result in results {
+            let age_days = ((now - result.created_at) / 86_400.0).max(0.0);
+
+            if let Some(max_age_days) = freshness.max_age_days {
+                if age_days > max_age_days {
+                    if excluded_top.len() < MAX_EXCLUDED_TRACKED {
+                        excluded_top.push(ExcludedItem {
+                            memory_id: result.memory_id,
+                            score: result.score,
+                            reason: "too_old".to_string(),
+                        });
+                    }
+                    continue;
+                }
+            }
+
+            if freshness.prefer_fresh {
+                result.score *= 0.5_f64.powf(age_days / FRESHNESS_HALF_LIFE_DAYS);
+            }
+            kept.push(result);
+        }
+
+        if freshness.prefer_fresh {
+            kept.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        }
+
+        kept
+    }
+
     pub fn format_context_block(selected: &[SelectedItem]) -> String {
         if selected.is_empty() {
             return "[VERIFIED CONTEXT]\nNo verified memories found for this query.\n[/VERIFIED CONTEXT]".to_string();
@@ -148,3 +218,471 @@ pub fn create_grounding_proof(
         excluded_top,
     }
 }
+
+/// Result of checking an LLM answer against the proof it was grounded on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CitationReport {
+    pub trace_id: String,
+    /// Citations that reference a memory selected in the proof.
+    pub valid_citations: Vec<String>,
+    /// Citations that reference memories the agent was never given.
+    pub invalid_citations: Vec<String>,
+    /// Factual sentences that carry no citation at all.
+    pub uncited_sentences: Vec<String>,
+    pub total_sentences: usize,
+    /// True when there are no invalid citations and no uncited factual sentences.
+    pub grounded: bool,
+}
+
+/// Minimum words for a sentence to be treated as a factual claim.
+const MIN_FACTUAL_WORDS: usize = 4;
+
+/// Verifies `[memory_id]` citations in `answer` against the proof's selected
+/// set and flags factual sentences without any citation.
+///
+/// Questions, very short sentences and the "Unknown" refusal mandated by the
+/// context block rules are not considered factual claims.
+pub fn check_citations(proof: &GroundingProof, answer: &str) -> CitationReport {
+    let selected: HashSet<&str> = proof.selected.iter().map(|s| s.memory_id.as_str()).collect();
+
+    let mut valid_citations = Vec::new();
+    let mut invalid_citations = Vec::new();
+    let mut uncited_sentences = Vec::new();
+
+    let sentences = split_sentences(answer);
+    for sentence in &sentences {
+        let citations = extract_citations(sentence);
+
+        for citation in &citations {
+            let bucket = if selected.contains(citation.as_str()) {
+                &mut valid_citations
+            } else {
+                &mut invalid_citations
+            };
+            if !bucket.contains(citation) {
+                bucket.push(citation.clone());
+            }
+        }
+
+        if citations.is_empty() && is_factual(sentence) {
+            uncited_sentences.push(sentence.clone());
+        }
+    }
+
+    let grounded = invalid_citations.is_empty() && uncited_sentences.is_empty();
+
+    CitationReport {
+        trace_id: proof.trace_id.clone(),
+        valid_citations,
+        invalid_citations,
+        uncited_sentences,
+        total_sentences: sentences.len(),
+        grounded,
+    }
+}
+
+/// Splits text on sentence terminators, ignoring terminators inside brackets
+/// so that ids such as `file:src/main.rs:abc` stay intact.
+fn split_sentences(text: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        current.push(c);
+        match c {
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            '.' | '!' | '?' | '\n' if depth == 0 => {
+                let at_boundary = c == '\n' || chars.peek().is_none_or(|n| n.is_whitespace());
+                if at_boundary {
+                    // Keep trailing citations ("... done. [id]") with their sentence
+                    let trimmed = current.trim().to_string();
+                    if !trimmed.is_empty() {
+                        sentences.push(trimmed);
+                    }
+                    current.clear();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let trimmed = current.trim().to_string();
+    if !trimmed.is_empty() {
+        sentences.push(trimmed);
+    }
+
+    // Attach citation-only fragments to the preceding sentence
+    let mut merged: Vec<String> = Vec::new();
+    for sentence in sentences {
+        let is_citation_only = strip_citations(&sentence)
+            .trim_matches(|c: char| c.is_whitespace() || c.is_ascii_punctuation())
+            .is_empty();
+        match merged.last_mut() {
+            Some(prev) if is_citation_only => {
+                prev.push(' ');
+                prev.push_str(&sentence);
+            }
+            _ => merged.push(sentence),
+        }
+    }
+    merged
+}
+
+fn extract_citations(sentence: &str) -> Vec<String> {
+    let mut citations = Vec::new();
+    let mut rest = sentence;
+    while let Some(start) = rest.find('[') {
+        let after = &rest[start + 1..];
+        let Some(end) = after.find(']') else { break };
+        for id in after[..end].split(',') {
+            let id = id.trim();
+            if !id.is_empty() {
+                citations.push(id.to_string());
+            }
+        }
+        rest = &after[end + 1..];
+    }
+    citations
+}
+
+fn strip_citations(sentence: &str) -> String {
+    let mut out = String::new();
+    let mut depth = 0usize;
+    for c in sentence.chars() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => out.push(c),
+            _ => {}
+        }
+    }
+    out
+}
+
+fn is_factual(sentence: &str) -> bool {
+    let text = strip_citations(sentence);
+    let text = text.trim();
+    if text.ends_with('?') {
+        return false;
+    }
+    if text.trim_end_matches(['.', '!']).eq_ignore_ascii_case("unknown") {
+        return false;
+    }
+    text.split_whitespace().count() >= MIN_FACTUAL_WORDS
+}
+
+/// A grounding proof as retained for audits, tagged with the project it was
+/// produced for and the time it was produced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredTrace {
+    pub project_id: String,
+    pub created_at: f64,
+    pub proof: GroundingProof,
+    /// Caller's judgement of the selected memories, if reported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feedback: Option<TraceFeedback>,
+}
+
+/// Which selected memories the caller found useful or wrong.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceFeedback {
+    #[serde(default)]
+    pub useful: Vec<String>,
+    #[serde(default)]
+    pub harmful: Vec<String>,
+    pub submitted_at: f64,
+}
+
+impl TraceFeedback {
+    pub fn new(useful: Vec<String>, harmful: Vec<String>) -> Self {
+        Self { useful, harmful, submitted_at: now_secs() }
+    }
+}
+
+/// Why feedback could not be attached to a trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedbackError {
+    /// Unknown trace_id, or the trace has been evicted
+    TraceNotFound,
+    /// Feedback is accepted once per trace
+    AlreadySubmitted,
+}
+
+/// Aggregate recall precision over retained traces that received feedback.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FeedbackMetrics {
+    pub traces_with_feedback: usize,
+    pub useful: usize,
+    pub harmful: usize,
+    /// useful / (useful + harmful) over all judged memories
+    pub precision: Option<f64>,
+    /// Mean of per-trace useful / (useful + harmful)
+    pub mean_trace_precision: Option<f64>,
+}
+
+/// Filter for listing stored traces. All bounds are inclusive; unset fields
+/// do not constrain the result.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TraceFilter {
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub since: Option<f64>,
+    #[serde(default)]
+    pub until: Option<f64>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+pub const DEFAULT_TRACE_LIST_LIMIT: usize = 100;
+
+/// Grounding trace log, stored in the single-tenant data directory
+pub const GROUNDING_TRACES_FILE: &str = "grounding_traces.jsonl";
+
+/// Bounded store of grounding proofs keyed by trace_id.
+///
+/// Traces are kept in insertion order and evicted oldest-first once either
+/// `max_traces` or `max_age_secs` is exceeded. When backed by a file, every
+/// trace is appended as a JSON line and the file is compacted once it holds
+/// more than twice the retained set, so restarts do not lose audit history.
+pub struct GroundingTraceStore {
+    traces: RwLock<IndexMap<String, StoredTrace>>,
+    max_traces: usize,
+    max_age_secs: Option<f64>,
+    path: Option<PathBuf>,
+    appended_since_compaction: Mutex<usize>,
+}
+
+impl GroundingTraceStore {
+    /// In-memory store (used in static mode and tests).
+    pub fn new(max_traces: usize, max_age_secs: Option<f64>) -> Self {
+        Self {
+            traces: RwLock::new(IndexMap::new()),
+            max_traces,
+            max_age_secs,
+            path: None,
+            appended_since_compaction: Mutex::new(0),
+        }
+    }
+
+    /// File-backed store. Existing traces are loaded from `path` and pruned
+    /// to the retention bounds.
+    pub fn open(path: impl AsRef<Path>, max_traces: usize, max_age_secs: Option<f64>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let mut traces = IndexMap::new();
+
+        if let Ok(file) = fs::File::open(&path) {
+            for line in BufReader::new(file).lines().map_while(Result::ok) {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<StoredTrace>(&line) {
+                    Ok(trace) => {
+                        traces.insert(trace.proof.trace_id.clone(), trace);
+                    }
+                    Err(e) => warn!("Skipping unreadable grounding trace: {}", e),
+                }
+            }
+        }
+
+        let store = Self {
+            traces: RwLock::new(traces),
+            max_traces,
+            max_age_secs,
+            path: Some(path),
+            appended_since_compaction: Mutex::new(0),
+        };
+        store.prune();
+        if let Err(e) = store.compact() {
+            warn!("Failed to compact grounding traces: {}", e);
+        }
+        info!("Loaded {} grounding traces", store.len());
+        store
+    }
+
+    pub fn record(&self, project_id: &str, proof: GroundingProof) {
+        let trace = StoredTrace {
+            project_id: project_id.to_string(),
+            created_at: now_secs(),
+            proof,
+            feedback: None,
+        };
+
+        if let Some(path) = &self.path {
+            if let Err(e) = Self::append_line(path, &trace) {
+                error!("Failed to persist grounding trace {}: {}", trace.proof.trace_id, e);
+            }
+        }
+
+        self.traces
+            .write()
+            .unwrap()
+            .insert(trace.proof.trace_id.clone(), trace);
+        self.prune();
+
+        if self.path.is_some() {
+            let mut appended = self.appended_since_compaction.lock().unwrap();
+            *appended += 1;
+            if *appended > self.max_traces.max(1) {
+                *appended = 0;
+                drop(appended);
+                if let Err(e) = self.compact() {
+                    warn!("Failed to compact grounding traces: {}", e);
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, trace_id: &str) -> Option<StoredTrace> {
+        self.traces.read().unwrap().get(trace_id).cloned()
+    }
+
+    /// Attach feedback to a retained trace, at most once. The updated trace is
+    /// appended to the file; on load, the later line replaces the earlier one.
+    pub fn record_feedback(&self, trace_id: &str, feedback: TraceFeedback) -> Result<StoredTrace, FeedbackError> {
+        let updated = {
+            let mut traces = self.traces.write().unwrap();
+            let trace = traces.get_mut(trace_id).ok_or(FeedbackError::TraceNotFound)?;
+            if trace.feedback.is_some() {
+                return Err(FeedbackError::AlreadySubmitted);
+            }
+            trace.feedback = Some(feedback);
+            trace.clone()
+        };
+
+        if let Some(path) = &self.path {
+            if let Err(e) = Self::append_line(path, &updated) {
+                error!("Failed to persist feedback for grounding trace {}: {}", trace_id, e);
+            }
+        }
+        Ok(updated)
+    }
+
+    /// Precision metrics over the project's retained traces with feedback.
+    pub fn feedback_metrics(&self, project_id: &str) -> FeedbackMetrics {
+        let traces = self.traces.read().unwrap();
+        let mut metrics = FeedbackMetrics::default();
+        let mut trace_precision_sum = 0.0;
+        let mut judged_traces = 0usize;
+
+        for feedback in traces
+            .values()
+            .filter(|t| t.project_id == project_id)
+            .filter_map(|t| t.feedback.as_ref())
+        {
+            metrics.traces_with_feedback += 1;
+            metrics.useful += feedback.useful.len();
+            metrics.harmful += feedback.harmful.len();
+            let judged = feedback.useful.len() + feedback.harmful.len();
+            if judged > 0 {
+                trace_precision_sum += feedback.useful.len() as f64 / judged as f64;
+                judged_traces += 1;
+            }
+        }
+
+        let judged = metrics.useful + metrics.harmful;
+        if judged > 0 {
+            metrics.precision = Some(metrics.useful as f64 / judged as f64);
+        }
+        if judged_traces > 0 {
+            metrics.mean_trace_precision = Some(trace_precision_sum / judged_traces as f64);
+        }
+        metrics
+    }
+
+    /// Drop the project's traces that selected or excluded any of
+    /// `memory_ids`, and rewrite the trace file without them. Returns how many
+    /// traces were dropped.
+    pub fn forget_memories(&self, project_id: &str, memory_ids: &HashSet<&str>) -> usize {
+        let dropped = {
+            let mut traces = self.traces.write().unwrap();
+            let before = traces.len();
+            traces.retain(|_, t| {
+                t.project_id != project_id
+                    || !(t.proof.selected.iter().any(|item| memory_ids.contains(item.memory_id.as_str()))
+                        || t.proof.excluded_top.iter().any(|item| memory_ids.contains(item.memory_id.as_str())))
+            });
+            before - traces.len()
+        };
+        if dropped > 0 {
+            if let Err(e) = self.compact() {
+                error!("Failed to compact grounding traces after a forget: {}", e);
+            }
+        }
+        dropped
+    }
+
+    /// Lists traces matching the filter, newest first.
+    pub fn list(&self, filter: &TraceFilter) -> Vec<StoredTrace> {
+        let limit = filter.limit.unwrap_or(DEFAULT_TRACE_LIST_LIMIT);
+        self.traces
+            .read()
+            .unwrap()
+            .values()
+            .rev()
+            .filter(|t| filter.project.as_ref().is_none_or(|p| &t.project_id == p))
+            .filter(|t| filter.since.is_none_or(|s| t.created_at >= s))
+            .filter(|t| filter.until.is_none_or(|u| t.created_at <= u))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.traces.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn prune(&self) {
+        let mut traces = self.traces.write().unwrap();
+
+        if let Some(max_age) = self.max_age_secs {
+            let cutoff = now_secs() - max_age;
+            traces.retain(|_, t| t.created_at >= cutoff);
+        }
+
+        if traces.len() > self.max_traces {
+            let excess = traces.len() - self.max_traces;
+            traces.drain(..excess);
+        }
+    }
+
+    fn append_line(path: &Path, trace: &StoredTrace) -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+        let line = serde_json::to_string(trace)?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+
+    /// Rewrites the backing file with only the retained traces.
+    fn compact(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let mut data = String::new();
+        for trace in self.traces.read().unwrap().values() {
+            data.push_str(&serde_json::to_string(trace)?);
+            data.push('\n');
+        }
+
+        // Write to temp file first (atomic operation)
+        let temp_path = path.with_extension("jsonl.tmp");
+        fs::write(&temp_path, data)?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+}
+
+fn now_secs() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs_f64()
+}
@@ -0,0 +1,304 @@
+//! gRPC service mirroring the REST memory and recall endpoints.
+//!
+//! Generated from `proto/cuemap.proto` and served on `--grpc-port` next to
+//! the HTTP API, over the same engines. Multi-tenant calls name their project
+//! in `x-project-id` metadata, and API keys go in `x-api-key` with the same
+//! scopes as REST.
+
+use crate::api::EngineState;
+use crate::auth::{AuthConfig, Scope};
+use crate::grounding::{create_grounding_proof, FreshnessPolicy, GroundingEngine, GroundingProof};
+use crate::jobs::Job;
+use crate::multi_tenant::validate_project_id;
+use crate::projects::ProjectContext;
+use crate::structures::Memory;
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio_stream::Stream;
+use tonic::{Request, Response, Status};
+
+pub mod pb {
+    tonic::include_proto!("cuemap.v1");
+}
+
+use pb::cue_map_server::{CueMap, CueMapServer};
+
+pub use pb::cue_map_client::CueMapClient;
+
+pub struct GrpcService {
+    state: EngineState,
+    auth: AuthConfig,
+}
+
+impl GrpcService {
+    pub fn new(state: EngineState, auth: AuthConfig) -> Self {
+        Self { state, auth }
+    }
+
+    pub fn into_server(self) -> CueMapServer<Self> {
+        CueMapServer::new(self)
+    }
+
+    /// Check that the caller's key grants `required`.
+    fn check_scope<T>(&self, request: &Request<T>, required: Scope) -> Result<(), Status> {
+        if !self.auth.is_enabled() {
+            return Ok(());
+        }
+        let key = request
+            .metadata()
+            .get("x-api-key")
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| Status::unauthenticated("Missing x-api-key metadata"))?;
+        match self.auth.key_scope(key) {
+            Some(scope) if scope >= required => Ok(()),
+            Some(_) => Err(Status::permission_denied(format!("Insufficient scope: {} access required", required.as_str()))),
+            None => Err(Status::unauthenticated("Invalid API key")),
+        }
+    }
+
+    /// Check the caller's key and resolve the project the call addresses.
+    fn project<T>(&self, request: &Request<T>, required: Scope) -> Result<(String, Arc<ProjectContext>), Status> {
+        self.check_scope(request, required)?;
+        if required >= Scope::Write && self.state.read_only() {
+            return Err(Status::permission_denied("Read-only mode: modifications are not allowed"));
+        }
+
+        match &self.state {
+            EngineState::SingleTenant { project, .. } => Ok(("default".to_string(), project.clone())),
+            EngineState::MultiTenant { mt_engine, .. } => {
+                let project_id = request
+                    .metadata()
+                    .get("x-project-id")
+                    .and_then(|v| v.to_str().ok())
+                    .ok_or_else(|| Status::invalid_argument("Missing x-project-id metadata"))?;
+                if !validate_project_id(project_id) {
+                    return Err(Status::invalid_argument("Invalid project ID format"));
+                }
+                Ok((project_id.to_string(), mt_engine.get_or_create_project(project_id.to_string())))
+            }
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl CueMap for GrpcService {
+    async fn add_memory(&self, request: Request<pb::AddMemoryRequest>) -> Result<Response<pb::AddMemoryResponse>, Status> {
+        let (project_id, ctx) = self.project(&request, Scope::Write)?;
+        let req = request.into_inner();
+        let metadata = parse_metadata(&req.metadata_json)?;
+
+        let memory = ctx
+            .prepare_memory(req.content, req.cues, metadata)
+            .map_err(|reason| Status::failed_precondition(format!("Memory rejected: {}", reason)))?;
+        let report = memory.report;
+        let memory_id = ctx.main.add_memory(memory.content.clone(), report.accepted, memory.metadata, req.disable_temporal_chunking);
+        ctx.rejections.record(&memory_id, &report.rejected);
+
+        let job_queue = self.state.job_queue();
+        job_queue.enqueue(Job::TrainLexiconFromMemory {
+            project_id: project_id.clone(),
+            memory_id: memory_id.clone(),
+        }).await;
+        job_queue.enqueue(Job::LlmProposeCues {
+            project_id,
+            memory_id: memory_id.clone(),
+            content: memory.content,
+        }).await;
+
+        let issue = |c: crate::taxonomy::RejectedCue| pb::RejectedCue { cue: c.cue, code: c.code, detail: c.detail };
+        Ok(Response::new(pb::AddMemoryResponse {
+            id: memory_id,
+            rejected_cues: report.rejected.into_iter().map(issue).collect(),
+            rewritten_cues: report.rewritten.into_iter().map(|r| pb::RewrittenCue { from: r.from, to: r.to }).collect(),
+            flagged_cues: report.flagged.into_iter().map(issue).collect(),
+        }))
+    }
+
+    async fn recall(&self, request: Request<pb::RecallRequest>) -> Result<Response<pb::RecallResponse>, Status> {
+        let (_, ctx) = self.project(&request, Scope::Read)?;
+        // Auto-reinforcing recalls write to the project
+        if request.get_ref().auto_reinforce {
+            self.check_scope(&request, Scope::Write)?;
+        }
+        let req = request.into_inner();
+        let start = Instant::now();
+
+        let query_cues = ctx.query_cues(&req.cues, req.query_text.as_deref());
+        let expanded_cues = ctx.expand_weighted_query_cues(query_cues);
+        let mut results = ctx.main.recall_weighted(
+            expanded_cues.clone(),
+            limit_or_default(req.limit),
+            req.auto_reinforce,
+            req.min_intersection.map(|n| n as usize),
+            req.explain,
+            req.disable_pattern_completion,
+            req.disable_salience_bias,
+            req.disable_systems_consolidation,
+            req.max_candidates.map(|n| n as usize),
+        );
+        ctx.after_recall(&expanded_cues, req.query_text.as_deref(), &mut results);
+
+        Ok(Response::new(pb::RecallResponse {
+            results: results
+                .into_iter()
+                .map(|r| pb::RecallResult {
+                    memory_id: r.memory_id,
+                    content: r.content,
+                    score: r.score,
+                    match_integrity: r.match_integrity,
+                    intersection_count: r.intersection_count as u32,
+                    recency_score: r.recency_score,
+                    reinforcement_score: r.reinforcement_score,
+                    salience_score: r.salience_score,
+                    created_at: r.created_at,
+                    metadata_json: metadata_json(&r.metadata),
+                    explain_json: r.explain.map(|e| e.to_string()).unwrap_or_default(),
+                    matched_cues: r.matched_cues.into_iter().map(|(cue, weight)| pb::WeightedCue { cue, weight }).collect(),
+                })
+                .collect(),
+            engine_latency_ms: start.elapsed().as_secs_f64() * 1000.0,
+        }))
+    }
+
+    async fn recall_grounded(&self, request: Request<pb::RecallGroundedRequest>) -> Result<Response<pb::RecallGroundedResponse>, Status> {
+        let (project_id, ctx) = self.project(&request, Scope::Read)?;
+        let req = request.into_inner();
+        let start = Instant::now();
+        let token_budget = if req.token_budget == 0 { 500 } else { req.token_budget };
+
+        let query_cues = ctx.query_cues(&[], Some(&req.query_text));
+        let resolved: Vec<String> = query_cues.iter().map(|(cue, _)| cue.clone()).collect();
+        let expanded_cues = ctx.expand_weighted_query_cues(query_cues);
+        let mut results = ctx.main.recall_weighted(
+            expanded_cues.clone(),
+            limit_or_default(req.limit).max(20),
+            false,
+            None,
+            true,
+            req.disable_pattern_completion,
+            req.disable_salience_bias,
+            req.disable_systems_consolidation,
+            None,
+        );
+        ctx.after_recall(&expanded_cues, Some(&req.query_text), &mut results);
+        let freshness = FreshnessPolicy {
+            max_age_days: req.max_age_days,
+            prefer_fresh: req.prefer_fresh,
+        };
+        let (selected, excluded, verified_context) = GroundingEngine::select_memories(
+            req.query_text.clone(),
+            resolved.clone(),
+            expanded_cues.clone(),
+            results,
+            token_budget,
+            &freshness,
+        );
+        let proof = create_grounding_proof(
+            uuid::Uuid::new_v4().to_string(),
+            req.query_text,
+            resolved,
+            expanded_cues,
+            token_budget,
+            selected,
+            excluded,
+        );
+        self.state.traces().record(&project_id, proof.clone());
+
+        Ok(Response::new(pb::RecallGroundedResponse {
+            verified_context,
+            proof: Some(proof_message(proof)),
+            engine_latency_ms: start.elapsed().as_secs_f64() * 1000.0,
+        }))
+    }
+
+    async fn reinforce(&self, request: Request<pb::ReinforceRequest>) -> Result<Response<pb::ReinforceResponse>, Status> {
+        let (_, ctx) = self.project(&request, Scope::Write)?;
+        let req = request.into_inner();
+        if !ctx.main.reinforce_memory(&req.memory_id, ctx.normalize_cues(&req.cues)) {
+            return Err(Status::not_found(format!("Memory {} not found", req.memory_id)));
+        }
+        Ok(Response::new(pb::ReinforceResponse { memory_id: req.memory_id }))
+    }
+
+    type ExportStream = Pin<Box<dyn Stream<Item = Result<pb::Memory, Status>> + Send>>;
+
+    async fn export(&self, request: Request<pb::ExportRequest>) -> Result<Response<Self::ExportStream>, Status> {
+        let (_, ctx) = self.project(&request, Scope::Read)?;
+        let mut memories: Vec<Memory> = ctx.main.get_memories().iter().map(|entry| entry.value().clone()).collect();
+        memories.sort_by(|a, b| a.created_at.total_cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+
+        let stream = tokio_stream::iter(memories.into_iter().map(|memory| {
+            Ok(pb::Memory {
+                metadata_json: metadata_json(&memory.metadata),
+                cues: memory.cues.iter().map(|cue| cue.to_string()).collect(),
+                id: memory.id,
+                content: memory.content,
+                created_at: memory.created_at,
+                last_accessed: memory.last_accessed,
+                reinforcement_count: memory.reinforcement_count,
+                salience: memory.salience,
+            })
+        }));
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+/// Serve `service` on `addr` until `shutdown` resolves, then finish
+/// in-flight calls and return.
+pub async fn serve(service: GrpcService, addr: SocketAddr, shutdown: impl Future<Output = ()>) -> Result<(), tonic::transport::Error> {
+    tonic::transport::Server::builder()
+        .add_service(service.into_server())
+        .serve_with_shutdown(addr, shutdown)
+        .await
+}
+
+fn limit_or_default(limit: u32) -> usize {
+    if limit == 0 { 10 } else { limit as usize }
+}
+
+fn parse_metadata(json: &str) -> Result<Option<HashMap<String, serde_json::Value>>, Status> {
+    if json.trim().is_empty() {
+        return Ok(None);
+    }
+    serde_json::from_str(json)
+        .map(Some)
+        .map_err(|e| Status::invalid_argument(format!("metadata_json: {}", e)))
+}
+
+fn metadata_json(metadata: &HashMap<String, serde_json::Value>) -> String {
+    if metadata.is_empty() {
+        String::new()
+    } else {
+        serde_json::to_string(metadata).unwrap_or_default()
+    }
+}
+
+fn proof_message(proof: GroundingProof) -> pb::GroundingProof {
+    pb::GroundingProof {
+        trace_id: proof.trace_id,
+        query_text: proof.query_text,
+        normalized_query: proof.normalized_query,
+        expanded_cues: proof.expanded_cues.into_iter().map(|(cue, weight)| pb::WeightedCue { cue, weight }).collect(),
+        token_budget: proof.token_budget,
+        selected: proof
+            .selected
+            .into_iter()
+            .map(|item| pb::SelectedMemory {
+                memory_id: item.memory_id,
+                content: item.content,
+                score: item.score,
+                estimated_tokens: item.estimated_tokens,
+                why: item.why,
+            })
+            .collect(),
+        excluded_top: proof
+            .excluded_top
+            .into_iter()
+            .map(|item| pb::ExcludedMemory { memory_id: item.memory_id, score: item.score, reason: item.reason })
+            .collect(),
+    }
+}
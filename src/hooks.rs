@@ -0,0 +1,145 @@
+//! Plugin hooks for the ingest and recall pipelines.
+//!
+//! An [`IngestHook`] sees every memory before it is stored, after the caller's
+//! cues and before normalization and taxonomy validation, so cues it adds go
+//! through the same checks. It can rewrite content, cues and metadata, or
+//! reject the memory. A [`RecallHook`] sees the results of every recall and
+//! can re-rank or drop them.
+//!
+//! Hooks are registered per project through [`Hooks`] on the
+//! [`ProjectContext`](crate::projects::ProjectContext) handed to
+//! [`api::routes`](crate::api::routes), on the
+//! [`MultiTenantEngine`](crate::multi_tenant::MultiTenantEngine) for
+//! [`api::routes_with_mt_engine`](crate::api::routes_with_mt_engine), or with
+//! [`CueMapBuilder::hooks`](crate::embedded::CueMapBuilder::hooks):
+//!
+//! ```
+//! use cuemap_rust::hooks::{Hooks, PendingMemory, RecallQueryInfo};
+//! use cuemap_rust::engine::RecallResult;
+//! use cuemap_rust::CueMap;
+//!
+//! let hooks = Hooks::new()
+//!     .ingest(|memory: &mut PendingMemory| {
+//!         if memory.content.contains("password") {
+//!             return Err("content contains a credential".to_string());
+//!         }
+//!         if memory.content.contains("INC-") {
+//!             memory.cues.push("type:incident".to_string());
+//!         }
+//!         Ok(())
+//!     })
+//!     .recall(|_: &RecallQueryInfo, results: &mut Vec<RecallResult>| {
+//!         results.retain(|r| !r.content.starts_with("[draft]"));
+//!     });
+//!
+//! let cuemap = CueMap::builder().hooks(hooks).open()?;
+//! let added = cuemap.add("INC-42: payments timed out", ["service:payments"])?;
+//! assert!(cuemap.add("the password is hunter2", ["topic:secrets"]).is_err());
+//! # Ok::<(), cuemap_rust::embedded::CueMapError>(())
+//! ```
+//!
+//! Hooks run synchronously on the request path, in registration order; keep
+//! them cheap or hand slow work to a background task.
+
+use crate::engine::RecallResult;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// A memory on its way into the store.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingMemory {
+    pub content: String,
+    /// Cues as supplied by the caller, before normalization
+    pub cues: Vec<String>,
+    pub metadata: HashMap<String, serde_json::Value>,
+}
+
+/// The query a recall ran with.
+#[derive(Debug, Clone, Copy)]
+pub struct RecallQueryInfo<'a> {
+    /// Normalized and alias-expanded cues with their weights
+    pub cues: &'a [(String, f64)],
+    pub query_text: Option<&'a str>,
+}
+
+pub trait IngestHook: Send + Sync {
+    /// Inspect or rewrite a memory before it is stored. An error rejects the
+    /// memory, with the message as the reason.
+    fn before_add(&self, memory: &mut PendingMemory) -> Result<(), String>;
+}
+
+impl<F> IngestHook for F
+where
+    F: Fn(&mut PendingMemory) -> Result<(), String> + Send + Sync,
+{
+    fn before_add(&self, memory: &mut PendingMemory) -> Result<(), String> {
+        self(memory)
+    }
+}
+
+pub trait RecallHook: Send + Sync {
+    /// Re-rank or filter results, which arrive strongest first. Removing
+    /// results does not backfill up to the requested limit.
+    fn after_recall(&self, query: &RecallQueryInfo<'_>, results: &mut Vec<RecallResult>);
+}
+
+impl<F> RecallHook for F
+where
+    F: Fn(&RecallQueryInfo<'_>, &mut Vec<RecallResult>) + Send + Sync,
+{
+    fn after_recall(&self, query: &RecallQueryInfo<'_>, results: &mut Vec<RecallResult>) {
+        self(query, results)
+    }
+}
+
+/// The hooks of a project, run in registration order. Cheap to clone.
+#[derive(Clone, Default)]
+pub struct Hooks {
+    ingest: Vec<Arc<dyn IngestHook>>,
+    recall: Vec<Arc<dyn RecallHook>>,
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hooks")
+            .field("ingest", &self.ingest.len())
+            .field("recall", &self.recall.len())
+            .finish()
+    }
+}
+
+impl Hooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ingest(mut self, hook: impl IngestHook + 'static) -> Self {
+        self.ingest.push(Arc::new(hook));
+        self
+    }
+
+    pub fn recall(mut self, hook: impl RecallHook + 'static) -> Self {
+        self.recall.push(Arc::new(hook));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ingest.is_empty() && self.recall.is_empty()
+    }
+
+    pub fn has_recall_hooks(&self) -> bool {
+        !self.recall.is_empty()
+    }
+
+    /// Run the ingest hooks; stops at the first rejection.
+    pub fn before_add(&self, memory: &mut PendingMemory) -> Result<(), String> {
+        self.ingest.iter().try_for_each(|hook| hook.before_add(memory))
+    }
+
+    pub fn after_recall(&self, query: &RecallQueryInfo<'_>, results: &mut Vec<RecallResult>) {
+        for hook in &self.recall {
+            hook.after_recall(query, results);
+        }
+    }
+}
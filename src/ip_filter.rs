@@ -0,0 +1,121 @@
+//! CIDR allow/deny lists enforced before authentication.
+
+use crate::error::ApiError;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use ipnet::IpNet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Parse a CIDR (`10.0.0.0/8`) or a bare address (`10.1.2.3`, treated as a single host).
+pub fn parse_net(s: &str) -> Result<IpNet, String> {
+    let s = s.trim();
+    s.parse::<IpNet>()
+        .or_else(|_| s.parse::<IpAddr>().map(IpNet::from))
+        .map_err(|_| format!("Invalid CIDR or IP address: {}", s))
+}
+
+fn parse_nets(list: &[String]) -> Result<Vec<IpNet>, String> {
+    list.iter().filter(|s| !s.trim().is_empty()).map(|s| parse_net(s)).collect()
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct IpFilter {
+    allow: Vec<IpNet>,
+    deny: Vec<IpNet>,
+    trusted_proxies: Vec<IpNet>,
+}
+
+impl IpFilter {
+    pub fn new(allow: &[String], deny: &[String], trusted_proxies: &[String]) -> Result<Self, String> {
+        Ok(Self {
+            allow: parse_nets(allow)?,
+            deny: parse_nets(deny)?,
+            trusted_proxies: parse_nets(trusted_proxies)?,
+        })
+    }
+
+    /// Whether any allow or deny rule is configured.
+    pub fn is_enabled(&self) -> bool {
+        !self.allow.is_empty() || !self.deny.is_empty()
+    }
+
+    pub fn log_status(&self) {
+        if self.is_enabled() {
+            info!(
+                "IP filtering enabled ({} allowed, {} denied, {} trusted proxies)",
+                self.allow.len(),
+                self.deny.len(),
+                self.trusted_proxies.len()
+            );
+        }
+    }
+
+    /// Deny rules win; with a non-empty allowlist, only listed addresses pass.
+    pub fn is_allowed(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        if self.deny.iter().any(|net| net.contains(&ip)) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(|net| net.contains(&ip))
+    }
+
+    fn is_trusted_proxy(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.trusted_proxies.iter().any(|net| net.contains(&ip))
+    }
+
+    /// Resolve the client address. `X-Forwarded-For` is only honoured when the
+    /// peer is a trusted proxy; the chain is walked right to left and the first
+    /// hop that is not itself a trusted proxy is the client.
+    pub fn client_ip(&self, peer: IpAddr, forwarded_for: Option<&str>) -> IpAddr {
+        if !self.is_trusted_proxy(peer) {
+            return peer;
+        }
+        let Some(header) = forwarded_for else { return peer };
+
+        let mut client = peer;
+        for hop in header.rsplit(',') {
+            match hop.trim().parse::<IpAddr>() {
+                Ok(ip) => {
+                    client = ip;
+                    if !self.is_trusted_proxy(ip) {
+                        break;
+                    }
+                }
+                // A malformed hop cannot be trusted past; stop at the last valid one
+                Err(_) => break,
+            }
+        }
+        client
+    }
+}
+
+/// Middleware rejecting clients outside the allowlist or inside the denylist
+pub async fn ip_filter_middleware(
+    State(filter): State<Arc<IpFilter>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let Some(ConnectInfo(peer)) = request.extensions().get::<ConnectInfo<SocketAddr>>().copied() else {
+        warn!("IP filter: peer address unavailable, rejecting request");
+        return Err(ApiError::Forbidden("Client IP not allowed".to_string()));
+    };
+
+    let forwarded_for = request
+        .headers()
+        .get("X-Forwarded-For")
+        .and_then(|v| v.to_str().ok());
+    let client = filter.client_ip(peer.ip(), forwarded_for);
+
+    if filter.is_allowed(client) {
+        Ok(next.run(request).await)
+    } else {
+        warn!("IP filter: rejected request from {}", client);
+        Err(ApiError::Forbidden("Client IP not allowed".to_string()))
+    }
+}
@@ -1,15 +1,21 @@
+use crate::alias_proposal::{self, AliasCandidate};
+use crate::content_limit;
 use crate::multi_tenant::MultiTenantEngine;
 use crate::projects::ProjectContext;
-use crate::llm::{LlmConfig, propose_cues};
-use crate::normalization::normalize_cue;
-use crate::taxonomy::validate_cues;
+use crate::structures::Memory;
+use crate::hooks::PendingMemory;
+use crate::agent::cue_policy::CuePolicy;
+use crate::agent::errors::{IngestAttempt, IngestStage};
+use crate::llm::{LlmConfig, propose_cues, shorten_memory, summarize_memories};
+use crate::provenance::{Provenance, PROVENANCE_KEY};
+use crate::reproposal::{self, ReproposalFilter};
 use crate::config::*;
-use std::sync::Arc;
-use tokio::sync::mpsc;
-use tracing::{info, warn, error, debug};
-use std::collections::HashSet;
-use rayon::prelude::*;
-use smallvec::SmallVec;
+use crate::events::ChangeEvent;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, Notify};
+use tracing::{info, warn, error, debug, Instrument, Span};
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 #[derive(Debug)]
@@ -17,23 +23,118 @@ pub enum Job {
     LlmProposeCues { project_id: String, memory_id: String, content: String },
     TrainLexiconFromMemory { project_id: String, memory_id: String },
     ProposeAliases { project_id: String },
-    ExtractAndIngest { project_id: String, memory_id: String, content: String, file_path: String },
-    VerifyFile { project_id: String, file_path: String, valid_memory_ids: Vec<String> },
+    /// `metadata` comes from the chunker, e.g. the page of a PDF chunk, and
+    /// `cues` from the agent's cue policy, which also vets the LLM's cues
+    ExtractAndIngest {
+        project_id: String,
+        memory_id: String,
+        content: String,
+        file_path: String,
+        metadata: HashMap<String, serde_json::Value>,
+        cues: Vec<String>,
+        cue_policy: Arc<CuePolicy>,
+        /// From the file's directory route; created in the project if missing
+        collection: Option<String>,
+        /// Completed with the extraction model and stored in the metadata
+        provenance: Box<Provenance>,
+        /// The ingest of the file, told when extraction fails
+        attempt: IngestAttempt,
+    },
+    /// Ends `attempt`, the ingest that queued the chunks before it
+    VerifyFile { project_id: String, file_path: String, valid_memory_ids: Vec<String>, attempt: Option<IngestAttempt> },
+    MigrateDeprecatedKey { project_id: String, from_key: String, to_key: String },
+    ConsolidateMemories { project_id: String },
+    /// Forgetting-curve decay, stale memory demotion and empty cue pruning
+    Maintenance { project_id: String, decay_halvings: u32 },
+    /// Clear the lexicon and retrain it from every memory
+    RebuildLexicon { project_id: String },
+    /// Replace the project's digest of memories going stale
+    StaleDigest { project_id: String },
+    /// Ask the LLM again for cues for the memories the filter selects
+    Repropose { project_id: String, filter: ReproposalFilter, rate: f64 },
+    /// Replace the cut content of a memory over the project's content limit
+    /// with an LLM summary of its full content
+    SummarizeOversized { project_id: String, memory_id: String, max_bytes: usize },
+}
+
+impl Job {
+    /// Short job name, used for tracing spans
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Job::LlmProposeCues { .. } => "llm_propose_cues",
+            Job::TrainLexiconFromMemory { .. } => "train_lexicon_from_memory",
+            Job::ProposeAliases { .. } => "propose_aliases",
+            Job::ExtractAndIngest { .. } => "extract_and_ingest",
+            Job::VerifyFile { .. } => "verify_file",
+            Job::MigrateDeprecatedKey { .. } => "migrate_deprecated_key",
+            Job::ConsolidateMemories { .. } => "consolidate_memories",
+            Job::Maintenance { .. } => "maintenance",
+            Job::RebuildLexicon { .. } => "rebuild_lexicon",
+            Job::StaleDigest { .. } => "stale_digest",
+            Job::Repropose { .. } => "repropose",
+            Job::SummarizeOversized { .. } => "summarize_oversized",
+        }
+    }
+
+    pub fn project_id(&self) -> &str {
+        match self {
+            Job::LlmProposeCues { project_id, .. }
+            | Job::TrainLexiconFromMemory { project_id, .. }
+            | Job::ProposeAliases { project_id }
+            | Job::ExtractAndIngest { project_id, .. }
+            | Job::VerifyFile { project_id, .. }
+            | Job::MigrateDeprecatedKey { project_id, .. }
+            | Job::ConsolidateMemories { project_id }
+            | Job::Maintenance { project_id, .. }
+            | Job::RebuildLexicon { project_id }
+            | Job::StaleDigest { project_id }
+            | Job::Repropose { project_id, .. }
+            | Job::SummarizeOversized { project_id, .. } => project_id,
+        }
+    }
+
+    /// The memory a per-memory job works on.
+    pub fn memory_id(&self) -> Option<&str> {
+        match self {
+            Job::LlmProposeCues { memory_id, .. }
+            | Job::TrainLexiconFromMemory { memory_id, .. }
+            | Job::ExtractAndIngest { memory_id, .. }
+            | Job::SummarizeOversized { memory_id, .. } => Some(memory_id),
+            _ => None,
+        }
+    }
 }
 
 pub struct JobQueue {
-    sender: mpsc::Sender<Job>,
+    /// Jobs are sent with the span that enqueued them, so job processing
+    /// shows up in the originating request's trace.
+    sender: mpsc::Sender<(Job, Span)>,
+    /// Tells the worker to stop taking new jobs and drain the queue
+    stop: Arc<Notify>,
+    worker: Mutex<Option<tokio::task::JoinHandle<()>>>,
 }
 
 // Abstraction to access projects regardless of mode
 pub trait ProjectProvider: Send + Sync + 'static {
     fn get_project(&self, project_id: &str) -> Option<Arc<ProjectContext>>;
+    /// Every loaded project.
+    fn projects(&self) -> Vec<Arc<ProjectContext>>;
+    /// Ids of every loaded project, as accepted by `get_project`.
+    fn project_ids(&self) -> Vec<String>;
 }
 
 impl ProjectProvider for MultiTenantEngine {
     fn get_project(&self, project_id: &str) -> Option<Arc<ProjectContext>> {
         self.get_project(&project_id.to_string())
     }
+
+    fn projects(&self) -> Vec<Arc<ProjectContext>> {
+        self.project_contexts()
+    }
+
+    fn project_ids(&self) -> Vec<String> {
+        self.project_ids()
+    }
 }
 
 // Wrapper for single tenant
@@ -45,100 +146,254 @@ impl ProjectProvider for SingleTenantProvider {
     fn get_project(&self, _project_id: &str) -> Option<Arc<ProjectContext>> {
         Some(self.project.clone())
     }
+
+    fn projects(&self) -> Vec<Arc<ProjectContext>> {
+        vec![self.project.clone()]
+    }
+
+    fn project_ids(&self) -> Vec<String> {
+        vec!["default".to_string()]
+    }
+}
+
+/// Publish pending index writes of every project's read view each `every`.
+/// Only useful when `config::read_view_refresh` is set.
+pub fn spawn_read_view_refresher(provider: Arc<dyn ProjectProvider>, every: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(every);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let provider = provider.clone();
+            // Copying hot cue lists is CPU work; keep it off the async workers
+            let refreshed = tokio::task::spawn_blocking(move || {
+                for ctx in provider.projects() {
+                    ctx.refresh_read_view();
+                }
+            })
+            .await;
+            if let Err(e) = refreshed {
+                error!("Read view refresh failed: {}", e);
+            }
+        }
+    })
+}
+
+/// Enqueue a `ConsolidateMemories` job for every project each `every`.
+/// The first run happens one interval after startup.
+pub fn spawn_consolidation_scheduler(provider: Arc<dyn ProjectProvider>, queue: Arc<JobQueue>, every: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(every);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            if !queue.is_running() {
+                return;
+            }
+            for project_id in provider.project_ids() {
+                queue.enqueue(Job::ConsolidateMemories { project_id }).await;
+            }
+        }
+    })
+}
+
+/// Enqueue a `Maintenance` job for every project each `every`. Reinforcement
+/// counts halve once per `DECAY_HALF_LIFE_DAYS` of uptime, so the decay a run
+/// applies is the number of whole half-lives since the last one that decayed.
+pub fn spawn_maintenance_scheduler(provider: Arc<dyn ProjectProvider>, queue: Arc<JobQueue>, every: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let half_life = Duration::from_secs(DECAY_HALF_LIFE_DAYS * 86_400);
+        let mut ticker = tokio::time::interval(every);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        ticker.tick().await;
+        let mut decay_clock = tokio::time::Instant::now();
+        loop {
+            ticker.tick().await;
+            if !queue.is_running() {
+                return;
+            }
+            let decay_halvings = (decay_clock.elapsed().as_secs() / half_life.as_secs()) as u32;
+            decay_clock += half_life * decay_halvings;
+            for project_id in provider.project_ids() {
+                queue.enqueue(Job::Maintenance { project_id, decay_halvings }).await;
+            }
+        }
+    })
+}
+
+/// Enqueue a `StaleDigest` job for every project each `every`.
+/// The first run happens one interval after startup.
+pub fn spawn_stale_digest_scheduler(provider: Arc<dyn ProjectProvider>, queue: Arc<JobQueue>, every: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(every);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            if !queue.is_running() {
+                return;
+            }
+            for project_id in provider.project_ids() {
+                queue.enqueue(Job::StaleDigest { project_id }).await;
+            }
+        }
+    })
 }
 
 impl JobQueue {
     pub fn new(provider: Arc<dyn ProjectProvider>) -> Self {
-        let (tx, mut rx) = mpsc::channel(1000);
+        let (tx, mut rx) = mpsc::channel::<(Job, Span)>(1000);
+        let stop = Arc::new(Notify::new());
         
-        tokio::spawn(async move {
-            while let Some(job) = rx.recv().await {
-                process_job(job, &provider).await;
+        let stop_signal = stop.clone();
+        let worker = tokio::spawn(async move {
+            loop {
+                let next = tokio::select! {
+                    next = rx.recv() => next,
+                    _ = stop_signal.notified() => {
+                        // Refuse new jobs, then finish the ones already queued
+                        rx.close();
+                        rx.recv().await
+                    }
+                };
+                let Some((job, origin)) = next else { break };
+                let span = tracing::info_span!(parent: &origin, "job", kind = job.kind());
+                let completed = ChangeEvent::JobCompleted {
+                    job: job.kind().to_string(),
+                    memory_id: job.memory_id().map(str::to_string),
+                };
+                let project = provider.get_project(job.project_id());
+                process_job(job, &provider).instrument(span).await;
+                if let Some(ctx) = project {
+                    ctx.main.events().publish(completed);
+                }
             }
         });
         
-        Self { sender: tx }
+        Self {
+            sender: tx,
+            stop,
+            worker: Mutex::new(Some(worker)),
+        }
+    }
+
+    /// Stop accepting jobs and wait up to `timeout` for queued ones to finish.
+    /// Returns false if jobs were still running when the timeout expired; they
+    /// are abandoned.
+    pub async fn shutdown(&self, timeout: Duration) -> bool {
+        let Some(mut worker) = self.worker.lock().unwrap().take() else { return true };
+        self.stop.notify_one();
+        match tokio::time::timeout(timeout, &mut worker).await {
+            Ok(_) => true,
+            Err(_) => {
+                worker.abort();
+                warn!("Job queue did not drain within {:?}; abandoning remaining jobs", timeout);
+                false
+            }
+        }
+    }
+    
+    /// Whether the background worker is still accepting jobs.
+    pub fn is_running(&self) -> bool {
+        !self.sender.is_closed()
     }
     
     pub async fn enqueue(&self, job: Job) {
-        if let Err(e) = self.sender.send(job).await {
+        if let Err(e) = self.sender.send((job, Span::current())).await {
             warn!("Failed to enqueue job: {}", e);
         }
     }
-}
 
-struct CueCandidate {
-    cue: String,
-    len: usize,
-    sample: HashSet<String>, // Hashed set for fast lookups in stage 1
+    /// Enqueue without waiting, for callers outside an async context. The
+    /// job is dropped (and logged) when the queue is full or closed.
+    pub fn try_enqueue(&self, job: Job) -> bool {
+        match self.sender.try_send((job, Span::current())) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("Failed to enqueue job: {}", e);
+                false
+            }
+        }
+    }
 }
 
-// --- Helper Functions ---
+/// Link each trainable cue of `memory` to the lexicon tokens of its content.
+fn train_lexicon(ctx: &ProjectContext, memory: &Memory) {
+    // Tokenize content
+    let tokens = ctx.lexicon_tokens(&memory.content);
+    if tokens.is_empty() {
+        return;
+    }
 
-/// Split cue into significant tokens
-fn cue_tokens(cue: &str) -> SmallVec<[String; 8]> {
-    let mut tokens = SmallVec::new();
-    let parts = cue.split(|c| c == ':' || c == '-' || c == '_');
-    
-    for part in parts {
-        let lower = part.to_lowercase();
-        if lower.len() >= 3 {
-            tokens.push(lower);
+    // Upsert into lexicon
+    // For each canonical cue in memory.cues
+    for canonical_cue in &memory.cues {
+        if !is_lexicon_trainable(canonical_cue) {
+            continue;
         }
+
+        let lex_id = format!("cue:{}", canonical_cue);
+
+        // The memory content in lexicon is the canonical cue string
+        // The cues in lexicon are the tokens
+        ctx.lexicon.upsert_memory_with_id(lex_id, canonical_cue.to_string(), tokens.clone(), None, false);
     }
-    tokens
 }
 
-/// Check if two cues share at least one significant token
-fn lexical_gate(a: &str, b: &str) -> bool {
-    // 1. Check if one contains the other (simple rewrite)
-    if a.contains(b) || b.contains(a) {
-        return true;
+/// Ask the LLM for cues for a memory and attach the accepted ones it does
+/// not hold yet, training the lexicon on them. Locked memories are left
+/// alone. Returns the attached cues.
+pub(crate) async fn propose_and_attach(ctx: &ProjectContext, memory_id: &str, content: &str, config: &LlmConfig) -> Result<Vec<String>, String> {
+    if ctx.main.get_memory(memory_id).is_some_and(|memory| memory.locked) {
+        debug!("Job: Memory {} is locked; not proposing cues", memory_id);
+        return Ok(Vec::new());
     }
-    
-    // 2. Token overlap
-    let tokens_a = cue_tokens(a);
-    if tokens_a.is_empty() { return false; }
-    
-    let tokens_b = cue_tokens(b);
-    if tokens_b.is_empty() { return false; }
-    
-    for ta in &tokens_a {
-        for tb in &tokens_b {
-            if ta == tb {
-                return true;
-            }
+    let known_cues = ctx.resolve_cues_from_text(content);
+    let proposed_cues = propose_cues(content, config, &known_cues).await?;
+
+    // Normalize & Validate
+    let normalized_cues = ctx.normalize_cues(&proposed_cues);
+    let report = ctx.validate_cues(normalized_cues);
+    ctx.rejections.record(memory_id, &report.rejected);
+
+    // Attach accepted cues the memory does not hold yet
+    let held: HashSet<String> = ctx
+        .main
+        .get_memory(memory_id)
+        .map(|memory| memory.cues.iter().map(|cue| cue.to_lowercase()).collect())
+        .unwrap_or_default();
+    let mut attached: Vec<String> = Vec::new();
+    for cue in report.accepted {
+        if !held.contains(&cue.to_lowercase()) && !attached.contains(&cue) {
+            attached.push(cue);
         }
     }
-    
-    false
-}
-
-/// Check if cue is in canonical key:value format
-fn is_canonical_format(cue: &str) -> bool {
-    match cue.split_once(':') {
-        Some((k, v)) => !k.is_empty() && !v.is_empty(),
-        None => false,
+    if attached.is_empty() {
+        return Ok(attached);
+    }
+    // The memory may have been locked while the LLM answered
+    if !ctx.main.attach_cues_unless_locked(memory_id, attached.clone()) {
+        debug!("Job: Memory {} was locked or changed meanwhile; not attaching cues", memory_id);
+        return Ok(Vec::new());
     }
-}
 
-/// Deterministically choose (canonical, alias)
-fn choose_canonical(a: &str, b: &str) -> (String, String) {
-    let a_canon = is_canonical_format(a);
-    let b_canon = is_canonical_format(b);
-    
-    if a_canon && !b_canon {
-        (a.to_string(), b.to_string())
-    } else if !a_canon && b_canon {
-        (b.to_string(), a.to_string())
-    } else {
-        // Tie-breaker: lexicographical
-        if a < b {
-            (a.to_string(), b.to_string())
-        } else {
-            (b.to_string(), a.to_string())
+    // Retrain lexicon with new cues
+    let tokens = ctx.lexicon_tokens(content);
+    if !tokens.is_empty() {
+        for canonical_cue in &attached {
+            if !is_lexicon_trainable(canonical_cue) {
+                continue;
+            }
+            let lex_id = format!("cue:{}", canonical_cue);
+            ctx.lexicon.upsert_memory_with_id(lex_id, canonical_cue.clone(), tokens.clone(), None, false);
         }
     }
+    Ok(attached)
+}
+
+fn now_secs() -> f64 {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs_f64()
 }
 
 /// Check if a cue is suitable for lexicon training (excluding high-cardinality cues)
@@ -158,186 +413,116 @@ async fn process_job(job: Job, provider: &Arc<dyn ProjectProvider>) {
             if let Some(ctx) = provider.get_project(&project_id) {
                 // Fetch memory from main engine
                 if let Some(memory) = ctx.main.get_memory(&memory_id) {
-                    // Tokenize content
-                    let tokens = crate::nl::tokenize_to_cues(&memory.content);
-                    
-                    if tokens.is_empty() {
-                        return;
+                    ctx.observe_document(&memory.content);
+                    train_lexicon(&ctx, &memory);
+                }
+            }
+        }
+        Job::RebuildLexicon { project_id } => {
+            let Some(ctx) = provider.get_project(&project_id) else { return };
+            let memory_ids: Vec<String> = ctx.main.get_memories().iter().map(|entry| entry.key().clone()).collect();
+            let started_at = now_secs();
+            ctx.update_lexicon_rebuild(|rebuild| {
+                rebuild.state = "running";
+                rebuild.total = memory_ids.len();
+                rebuild.processed = 0;
+                rebuild.started_at = Some(started_at);
+            });
+            let removed = ctx.clear_lexicon();
+            info!("Job: Rebuilding lexicon of project {} from {} memories ({} entries cleared)", project_id, memory_ids.len(), removed);
+
+            // Keyword statistics first, so keyword extraction scores against the whole corpus
+            for batch in memory_ids.chunks(LEXICON_REBUILD_BATCH_SIZE) {
+                for memory_id in batch {
+                    if let Some(memory) = ctx.main.get_memory(memory_id) {
+                        ctx.observe_document(&memory.content);
                     }
-                    
-                    // Upsert into lexicon
-                    // For each canonical cue in memory.cues
-                    for canonical_cue in &memory.cues {
-                         if !is_lexicon_trainable(canonical_cue) {
-                             continue;
-                         }
-                         
-                         let lex_id = format!("cue:{}", canonical_cue);
-                         
-                         // The memory content in lexicon is the canonical cue string
-                         // The cues in lexicon are the tokens
-                         ctx.lexicon.upsert_memory_with_id(
-                             lex_id, 
-                             canonical_cue.clone(), 
-                             tokens.clone(), 
-                             None,
-                             false
-                         );
+                }
+                tokio::time::sleep(Duration::from_millis(LEXICON_REBUILD_BATCH_PAUSE_MS)).await;
+            }
+            let mut processed = 0;
+            for batch in memory_ids.chunks(LEXICON_REBUILD_BATCH_SIZE) {
+                for memory_id in batch {
+                    // Memories deleted since the rebuild started are skipped
+                    if let Some(memory) = ctx.main.get_memory(memory_id) {
+                        train_lexicon(&ctx, &memory);
                     }
                 }
+                processed += batch.len();
+                ctx.update_lexicon_rebuild(|rebuild| rebuild.processed = processed);
+                debug!("Job: Lexicon rebuild of project {}: {}/{} memories", project_id, processed, memory_ids.len());
+                tokio::time::sleep(Duration::from_millis(LEXICON_REBUILD_BATCH_PAUSE_MS)).await;
             }
+
+            ctx.query_cache.clear();
+            let finished_at = now_secs();
+            ctx.update_lexicon_rebuild(|rebuild| {
+                rebuild.state = "completed";
+                rebuild.finished_at = Some(finished_at);
+            });
+            info!(
+                "Job: Rebuilt lexicon of project {}: {} entries from {} memories in {:.1}s",
+                project_id, ctx.lexicon.get_memories().len(), memory_ids.len(), finished_at - started_at
+            );
         }
         Job::LlmProposeCues { project_id, memory_id, content } => {
              // 1. Check if LLM is configured
              if let Some(config) = LlmConfig::from_env() {
                  info!("Job: Calling LLM for memory {} in project {}", memory_id, project_id);
-                 
-                 let known_cues = if let Some(ctx) = provider.get_project(&project_id) {
-                     ctx.resolve_cues_from_text(&content)
-                 } else {
-                     Vec::new()
-                 };
-
-                 // 2. Call LLM
-                 match propose_cues(&content, &config, &known_cues).await {
-                     Ok(proposed_cues) => {
-                         if let Some(ctx) = provider.get_project(&project_id) {
-                             // 3. Normalize & Validate
-                             let mut normalized_cues = Vec::new();
-                             for cue in proposed_cues {
-                                 let (normalized, _) = normalize_cue(&cue, &ctx.normalization);
-                                 normalized_cues.push(normalized);
-                             }
-                             
-                             let report = validate_cues(normalized_cues, &ctx.taxonomy);
-                             
-                             // 4. Attach accepted cues
-                             if !report.accepted.is_empty() {
-                                 ctx.main.attach_cues(&memory_id, report.accepted.clone());
-                                 info!("Job: Attached {} cues to memory {}", report.accepted.len(), memory_id);
-                                 
-                                 // 5. Retrain lexicon with new cues
-                                 let tokens = crate::nl::tokenize_to_cues(&content);
-                                 if !tokens.is_empty() {
-                                     for canonical_cue in report.accepted {
-                                         if !is_lexicon_trainable(&canonical_cue) {
-                                             continue;
-                                         }
-                                         
-                                         let lex_id = format!("cue:{}", canonical_cue);
-                                         ctx.lexicon.upsert_memory_with_id(
-                                             lex_id, 
-                                             canonical_cue, 
-                                             tokens.clone(), 
-                                             None,
-                                             false
-                                         );
-                                     }
-                                 }
-                             }
+                 if let Some(ctx) = provider.get_project(&project_id) {
+                     match propose_and_attach(&ctx, &memory_id, &content, &config).await {
+                         Ok(attached) if !attached.is_empty() => {
+                             info!("Job: Attached {} cues to memory {}", attached.len(), memory_id);
+                         }
+                         Ok(_) => {}
+                         Err(e) => {
+                             error!("Job: LLM failed: {}", e);
                          }
-                     },
-                     Err(e) => {
-                         error!("Job: LLM failed: {}", e);
                      }
                  }
              }
         }
+        Job::Repropose { project_id, filter, rate } => {
+            let Some(ctx) = provider.get_project(&project_id) else { return };
+            let memory_ids = filter.select(&ctx);
+            let started_at = now_secs();
+            ctx.update_reproposal(|reproposal| {
+                reproposal.state = "running";
+                reproposal.counts.total = memory_ids.len();
+                reproposal.started_at = Some(started_at);
+            });
+            match LlmConfig::from_env() {
+                Some(config) => {
+                    info!("Job: Re-proposing cues for {} memories of project {} at {}/s", memory_ids.len(), project_id, rate);
+                    let counts = reproposal::run(&ctx, &memory_ids, &config, rate, |counts| {
+                        ctx.update_reproposal(|reproposal| reproposal.counts = *counts);
+                    })
+                    .await;
+                    info!(
+                        "Job: Re-proposed cues for {} memories of project {}: {} cues attached, {} failed",
+                        counts.processed, project_id, counts.attached, counts.failed
+                    );
+                }
+                None => warn!("Job: Reproposal of project {} skipped, no LLM configured", project_id),
+            }
+            ctx.query_cache.clear();
+            let finished_at = now_secs();
+            ctx.update_reproposal(|reproposal| {
+                reproposal.state = "completed";
+                reproposal.finished_at = Some(finished_at);
+            });
+        }
         Job::ProposeAliases { project_id } => {
             if let Some(ctx) = provider.get_project(&project_id) {
-                let cue_index = ctx.main.get_cue_index();
-                
-                // 1. Filter and Select Mid-Frequency Cues
-                let mut stats: Vec<(String, usize)> = cue_index
-                    .iter()
-                    .map(|entry| (entry.key().clone(), entry.value().len()))
-                    .filter(|(k, cnt)| k.len() >= 3 && *cnt >= ALIAS_MIN_CUE_MEMORIES && *cnt <= ALIAS_MAX_CUE_MEMORIES)
-                    .collect();
-                
-                stats.sort_unstable_by(|a, b| b.1.cmp(&a.1));
-                let drop_count = (stats.len() as f64 * 0.01) as usize;
-                let stats = stats.into_iter().skip(drop_count).take(ALIAS_MAX_CANDIDATES).collect::<Vec<_>>();
-                
-                if stats.is_empty() {
-                    return;
-                }
-                
-                // 2. Build Candidates
-                let candidates: Vec<CueCandidate> = stats
-                    .into_iter()
-                    .filter_map(|(key, len)| {
-                        if let Some(entry) = cue_index.get(&key) {
-                            let sample_vec = entry.get_recent_owned(Some(ALIAS_SAMPLE_SIZE));
-                            let sample_set: HashSet<String> = sample_vec.into_iter().collect();
-                            Some(CueCandidate {
-                                cue: key,
-                                len,
-                                sample: sample_set,
-                            })
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
-                
-                info!("Job: Analyzing {} candidates for aliases in project {}", candidates.len(), project_id);
-                
-                // 3. Parallel Comparison
-                let proposals: Vec<(String, String, f64, String)> = candidates
-                    .par_iter()
-                    .enumerate()
-                    .fold(Vec::new, |mut acc, (i, cand_a)| {
-                        for cand_b in candidates.iter().skip(i + 1) {
-                            let diff = (cand_a.len as isize - cand_b.len as isize).abs();
-                            let max_len = std::cmp::max(cand_a.len, cand_b.len);
-                            if (diff as f64 / max_len as f64) > ALIAS_SIZE_SIMILARITY_MAX_RATIO {
-                                continue;
-                            }
-                            
-                            if !lexical_gate(&cand_a.cue, &cand_b.cue) {
-                                continue;
-                            }
-                            
-                            let intersection = cand_a.sample.intersection(&cand_b.sample).count();
-                            let min_sample_len = std::cmp::min(cand_a.sample.len(), cand_b.sample.len());
-                            if min_sample_len == 0 { continue; }
-                            
-                            let sample_score = intersection as f64 / min_sample_len as f64;
-                            if sample_score < (ALIAS_OVERLAP_THRESHOLD - 0.15) {
-                                continue;
-                            }
-                            
-                            if let Some(entry_a) = cue_index.get(&cand_a.cue) {
-                                if let Some(entry_b) = cue_index.get(&cand_b.cue) {
-                                    let (smaller, larger) = if entry_a.len() < entry_b.len() {
-                                        (&entry_a.items, &entry_b.items)
-                                    } else {
-                                        (&entry_b.items, &entry_a.items)
-                                    };
-                                    
-                                    let exact_intersection = smaller.iter().filter(|id| larger.contains(*id)).count();
-                                    let min_len = smaller.len();
-                                    if min_len == 0 { continue; }
-                                    
-                                    let exact_score = exact_intersection as f64 / min_len as f64;
-                                    
-                                    if exact_score >= ALIAS_OVERLAP_THRESHOLD {
-                                        let (canon, alias) = choose_canonical(&cand_a.cue, &cand_b.cue);
-                                        let alias_id_str = format!("{}->{}", alias, canon);
-                                        let alias_uuid = Uuid::new_v5(&Uuid::NAMESPACE_OID, alias_id_str.as_bytes());
-                                        acc.push((alias, canon, exact_score, alias_uuid.to_string()));
-                                    }
-                                }
-                            }
-                        }
-                        acc
-                    })
-                    .reduce(Vec::new, |mut a, b| { a.extend(b); a });
-                
+                let candidates = alias_proposal::select(&ctx.main, &ctx.alias_config());
+                info!("Job: Analyzed {} candidates for aliases in project {}", candidates.cues_compared, project_id);
+
                 // 4. Register Proposals
-                for (from, to, score, alias_id) in proposals {
+                for AliasCandidate { from, to, overlap, .. } in candidates.proposed() {
+                    let score = overlap.unwrap_or_default();
+                    let alias_id = Uuid::new_v5(&Uuid::NAMESPACE_OID, format!("{}->{}", from, to).as_bytes()).to_string();
                     let id_cue = format!("alias_id:{}", alias_id);
-                    if !ctx.aliases.get_cue_index().contains_key(&id_cue) {
+                    if !ctx.aliases.get_cue_index().contains_key(id_cue.as_str()) {
                         let content = serde_json::json!({
                             "from": from,
                             "to": to,
@@ -361,25 +546,51 @@ async fn process_job(job: Job, provider: &Arc<dyn ProjectProvider>) {
                 }
             }
         }
-        Job::ExtractAndIngest { project_id, memory_id, content, file_path } => {
+        Job::ExtractAndIngest { project_id, memory_id, content, file_path, mut metadata, cues: policy_cues, cue_policy, collection, provenance, attempt } => {
              if let Some(config) = LlmConfig::from_env() {
-                 debug!("Agent: Starting extraction for {}", memory_id);
+                 debug!("Agent: Starting extraction for {} (job {})", memory_id, provenance.job_id);
                  match crate::llm::extract_facts(&content, &config).await {
                      Ok((extracted_content, cues)) => {
                          if let Some(ctx) = provider.get_project(&project_id) {
-                              let mut final_cues = cues;
+                              if ctx.main.get_memory(&memory_id).is_some_and(|m| m.locked) {
+                                  info!("Agent: Memory {} is locked; keeping it as is", memory_id);
+                                  return;
+                              }
+                              let mut final_cues: Vec<String> = cues.into_iter().filter(|cue| cue_policy.allows(cue)).collect();
+                              final_cues.extend(policy_cues);
                               final_cues.push(format!("path:{}", file_path));
                               final_cues.push("source:agent".to_string());
+                              if let Ok(provenance) = serde_json::to_value(provenance.extracted_by(&config)) {
+                                  metadata.insert(PROVENANCE_KEY.to_string(), provenance);
+                              }
+                              
+                              let mut memory = PendingMemory { content: extracted_content, cues: final_cues, metadata };
+                              if let Err(reason) = ctx.hooks().before_add(&mut memory) {
+                                  info!("Agent: Ingest hook rejected memory {}: {}", memory_id, reason);
+                                  return;
+                              }
+                              let PendingMemory { content: extracted_content, cues: mut final_cues, metadata } = memory;
+                              if let Some(lang_cue) = ctx.language_cue(&extracted_content, &final_cues) {
+                                  final_cues.push(lang_cue);
+                              }
                               
                               ctx.main.upsert_memory_with_id(
                                   memory_id.clone(),
                                   extracted_content.clone(),
                                   final_cues.clone(),
-                                  None,
+                                  (!metadata.is_empty()).then_some(metadata),
                                   false
                               );
+                              if let Some(collection) = &collection {
+                                  if ctx.collection(collection).is_none() {
+                                      let _ = ctx.create_collection(collection, None);
+                                      info!("Agent: Created collection {} for routed files", collection);
+                                  }
+                                  ctx.main.set_collection(&memory_id, Some(collection));
+                              }
                               
-                              let tokens = crate::nl::tokenize_to_cues(&extracted_content);
+                              ctx.observe_document(&extracted_content);
+                              let tokens = ctx.lexicon_tokens(&extracted_content);
                               for canonical_cue in &final_cues {
                                    if !is_lexicon_trainable(canonical_cue) {
                                        continue;
@@ -398,21 +609,27 @@ async fn process_job(job: Job, provider: &Arc<dyn ProjectProvider>) {
                               info!("Agent: Ingested memory {} ({} cues)", memory_id, final_cues.len());
                          }
                      }
-                     Err(e) => error!("Agent: Extraction failed for {}: {}", memory_id, e),
+                     Err(e) => {
+                         error!("Agent: Extraction failed for {}: {}", memory_id, e);
+                         attempt.fail(IngestStage::Extract, e, Some(memory_id));
+                     }
                  }
              }
         }
-        Job::VerifyFile { project_id, file_path, valid_memory_ids } => {
+        Job::VerifyFile { project_id, file_path, valid_memory_ids, attempt } => {
+             if let Some(attempt) = attempt {
+                 attempt.finish();
+             }
              if let Some(ctx) = provider.get_project(&project_id) {
                   // Strategy:
                   // 1. Look up all memories associated with "path:{file_path}"
                   // 2. Filter for those that are NOT in valid_memory_ids
-                  // 3. Delete them
+                  // 3. Delete them, unless they are locked
                   
                   let path_cue = format!("path:{}", file_path);
-                  if let Some(ordered_set) = ctx.main.get_cue_index().get(&path_cue) {
+                  let current_memories = ctx.main.cue_memory_ids(&path_cue, None);
+                  if !current_memories.is_empty() {
                       // Get all memory IDs associated with this file
-                      let current_memories = ordered_set.get_recent_owned(None);
                       let valid_set: HashSet<String> = valid_memory_ids.into_iter().collect();
                       
                       let mut deleted_count = 0;
@@ -420,6 +637,10 @@ async fn process_job(job: Job, provider: &Arc<dyn ProjectProvider>) {
                           // Only delete if it's an agent-managed memory (check prefix "file:")
                           // and not in the valid set.
                           if mem_id.starts_with("file:") && !valid_set.contains(&mem_id) {
+                               if ctx.main.get_memory(&mem_id).is_some_and(|m| m.locked) {
+                                   debug!("Agent: Keeping locked memory {} of {}", mem_id, file_path);
+                                   continue;
+                               }
                                if ctx.main.delete_memory(&mem_id) {
                                    deleted_count += 1;
                                }
@@ -434,6 +655,94 @@ async fn process_job(job: Job, provider: &Arc<dyn ProjectProvider>) {
                   }
              }
         }
+        Job::MigrateDeprecatedKey { project_id, from_key, to_key } => {
+            if let Some(ctx) = provider.get_project(&project_id) {
+                let migrated = ctx.main.rename_cue_key(&from_key, &to_key);
+                ctx.query_cache.clear();
+                info!("Job: Migrated {} memories from key '{}' to '{}' in project {}", migrated, from_key, to_key, project_id);
+            }
+        }
+        Job::ConsolidateMemories { project_id } => {
+            let Some(config) = LlmConfig::from_env() else {
+                debug!("Job: Skipping consolidation in project {}; LLM is disabled", project_id);
+                return;
+            };
+            let Some(ctx) = provider.get_project(&project_id) else { return };
+
+            let groups = ctx.main.consolidation_groups(CONSOLIDATION_CUE_OVERLAP);
+            let mut merged = 0;
+            for group in groups.into_iter().take(CONSOLIDATION_MAX_GROUPS) {
+                let mut memories: Vec<_> = group.iter().filter_map(|id| ctx.main.get_memory(id)).collect();
+                memories.sort_by(|a, b| a.created_at.total_cmp(&b.created_at));
+                let contents: Vec<String> = memories.into_iter().map(|m| m.content).collect();
+
+                match summarize_memories(&contents, &config).await {
+                    Ok(summary) => {
+                        if let Some(summary_id) = ctx.main.merge_memories(&group, summary) {
+                            debug!("Job: Consolidated {} memories into {}", group.len(), summary_id);
+                            merged += 1;
+                        }
+                    }
+                    Err(e) => error!("Job: LLM consolidation failed: {}", e),
+                }
+            }
+            if merged > 0 {
+                info!("Job: Consolidated {} memory groups in project {}", merged, project_id);
+            }
+        }
+        Job::SummarizeOversized { project_id, memory_id, max_bytes } => {
+            let Some(config) = LlmConfig::from_env() else {
+                warn!("Job: Memory {} in project {} keeps its cut content, no LLM configured", memory_id, project_id);
+                return;
+            };
+            let Some(ctx) = provider.get_project(&project_id) else { return };
+            let Some(memory) = ctx.main.get_memory(&memory_id) else { return };
+            let Some(full_content) = memory.full_content.filter(|_| !memory.locked) else { return };
+
+            match shorten_memory(&full_content, max_bytes, &config).await {
+                Ok(summary) => {
+                    ctx.main.shorten_content(&memory_id, content_limit::truncate(&summary, max_bytes).to_string());
+                    ctx.query_cache.clear();
+                    info!("Job: Summarized memory {} in project {} from {} bytes", memory_id, project_id, full_content.len());
+                }
+                Err(e) => error!("Job: LLM summarization of memory {} failed: {}", memory_id, e),
+            }
+        }
+        Job::Maintenance { project_id, decay_halvings } => {
+            if let Some(ctx) = provider.get_project(&project_id) {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs_f64();
+                let decayed = ctx.main.decay_reinforcement(decay_halvings);
+                let demoted = ctx.main.demote_stale(now - (STALE_MEMORY_DAYS * 86_400) as f64, STALE_SALIENCE_PENALTY);
+                let expired = ctx.expire_aliases(now).len();
+                let pruned = ctx.main.prune_empty_cues() + ctx.aliases.prune_empty_cues() + ctx.lexicon.prune_empty_cues();
+                ctx.prune_display_names();
+                info!(
+                    "Job: Maintenance in project {}: decayed {}, demoted {}, expired {} aliases, pruned {} empty cues",
+                    project_id, decayed, demoted, expired, pruned
+                );
+            }
+        }
+        Job::StaleDigest { project_id } => {
+            if let Some(ctx) = provider.get_project(&project_id) {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs_f64();
+                match crate::digest::publish(&ctx.main, now) {
+                    Some((memory_id, digest)) => info!(
+                        "Job: Stale digest {} in project {}: {} stale, {} going stale",
+                        memory_id,
+                        project_id,
+                        digest.stale_count(),
+                        digest.entries.len() - digest.stale_count()
+                    ),
+                    None => debug!("Job: No memories going stale in project {}", project_id),
+                }
+            }
+        }
     }
 }
 
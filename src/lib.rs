@@ -1,16 +1,52 @@
 pub mod structures;
 pub mod engine;
+pub mod events;
+pub mod hooks;
 pub mod api;
+pub mod error;
+pub mod limits;
 pub mod config;
+pub mod config_file;
 pub mod persistence;
+pub mod replication;
+pub mod webhooks;
+pub mod forget;
+pub mod digest;
+pub mod subgraph;
+pub mod reproposal;
+pub mod provenance;
+pub mod alias_proposal;
 pub mod auth;
+pub mod tls;
+pub mod ip_filter;
+pub mod telemetry;
 pub mod normalization;
 pub mod taxonomy;
+pub mod quarantine;
+pub mod collections;
 pub mod projects;
+pub mod query_cache;
+pub mod recall_cache;
+pub mod shared_vocabulary;
 pub mod multi_tenant;
 pub mod nl;
 pub mod jobs;
 pub mod llm;
 pub mod agent;
 pub mod grounding;
+pub mod rerank;
+pub mod ranking;
+pub mod result_fields;
+pub mod content_limit;
 pub mod evals;
+pub mod embedded;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "s3")]
+pub mod snapshot_store;
+pub mod cli;
+pub mod repl;
+
+pub use embedded::CueMap;
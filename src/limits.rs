@@ -0,0 +1,137 @@
+//! Size, time and concurrency limits on HTTP requests.
+//!
+//! Bodies over [`RequestLimits::max_body_bytes`] are refused with `413`
+//! before a handler runs: [`body_limit_middleware`] checks `Content-Length`,
+//! and the router's `DefaultBodyLimit` cuts off chunked bodies while they are
+//! buffered. Handlers check memory content, cue counts and recall limits with
+//! [`check_memory`] and [`check_recall_limit`], so an oversized memory or
+//! result set is rejected before it reaches the engine.
+//!
+//! Expensive routes run under a [`RouteBudget`] (see [`with_budget`]): a
+//! request still running when the budget's time is up is dropped with `408`,
+//! and one arriving while the route already has its maximum in flight is
+//! shed with `503` instead of queueing behind them.
+
+use crate::config::{self, RequestLimits};
+use crate::error::{ApiError, PROBLEM_JSON};
+use axum::{
+    error_handling::HandleErrorLayer,
+    extract::Request,
+    http::{header, StatusCode},
+    middleware::Next,
+    response::Response,
+    routing::MethodRouter,
+    BoxError,
+};
+use std::time::Duration;
+use tower::ServiceBuilder;
+
+/// Middleware refusing request bodies over the configured size.
+pub async fn body_limit_middleware(request: Request, next: Next) -> Result<Response, ApiError> {
+    let max_body_bytes = config::request_limits().max_body_bytes;
+    let declared = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+    if let Some(length) = declared.filter(|length| *length > max_body_bytes) {
+        return Err(ApiError::PayloadTooLarge(format!(
+            "Request body is {} bytes, over the limit of {}",
+            length, max_body_bytes
+        )));
+    }
+
+    let response = next.run(request).await;
+    // Bodies without a length are only stopped by the extractor, whose
+    // rejection is plain text
+    let is_problem = response.headers().get(header::CONTENT_TYPE).is_some_and(|v| v == PROBLEM_JSON);
+    if response.status() == StatusCode::PAYLOAD_TOO_LARGE && !is_problem {
+        return Err(ApiError::PayloadTooLarge(format!("Request body is over the limit of {} bytes", max_body_bytes)));
+    }
+    Ok(response)
+}
+
+/// Refuse memory content over the size limit (`413`) and cue lists over the
+/// count limit (`400`).
+pub fn check_memory(limits: &RequestLimits, content: &str, cues: &[String]) -> Result<(), ApiError> {
+    if content.len() > limits.max_content_bytes {
+        return Err(ApiError::PayloadTooLarge(format!(
+            "content is {} bytes, over the limit of {}",
+            content.len(),
+            limits.max_content_bytes
+        )));
+    }
+    check_cues(limits, cues)
+}
+
+/// Refuse cue lists over the per-memory count limit.
+pub fn check_cues(limits: &RequestLimits, cues: &[String]) -> Result<(), ApiError> {
+    if cues.len() > limits.max_cues_per_memory {
+        return Err(ApiError::BadRequest(format!(
+            "{} cues given, over the limit of {}",
+            cues.len(),
+            limits.max_cues_per_memory
+        )));
+    }
+    Ok(())
+}
+
+/// Refuse recalls asking for more than the result limit.
+pub fn check_recall_limit(limits: &RequestLimits, limit: usize) -> Result<(), ApiError> {
+    if limit > limits.max_recall_limit {
+        return Err(ApiError::BadRequest(format!(
+            "limit {} is over the maximum of {}",
+            limit, limits.max_recall_limit
+        )));
+    }
+    Ok(())
+}
+
+/// Time limit and requests in flight allowed on a route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RouteBudget {
+    pub timeout: Duration,
+    pub max_concurrent: usize,
+}
+
+impl RouteBudget {
+    /// Recalls and citation checks: answered quickly, many at once.
+    pub const QUERY: Self = Self {
+        timeout: Duration::from_millis(config::QUERY_ROUTE_TIMEOUT_MS),
+        max_concurrent: config::QUERY_ROUTE_MAX_CONCURRENT,
+    };
+    /// Admin work that scans whole projects: slow, few at once.
+    pub const ADMIN: Self = Self {
+        timeout: Duration::from_millis(config::ADMIN_ROUTE_TIMEOUT_MS),
+        max_concurrent: config::ADMIN_ROUTE_MAX_CONCURRENT,
+    };
+}
+
+/// Run `route` under `budget`, answering `408` past its timeout and `503`
+/// when it already has `max_concurrent` requests in flight. The budget is
+/// shared by all requests once the router's state is set (`with_state`).
+///
+/// The timeout can only fire while the handler is suspended, so handlers run
+/// their engine work with `spawn_blocking`. The blocking work itself is not
+/// cancelled and still holds its concurrency slot until it returns.
+pub fn with_budget<S>(route: MethodRouter<S>, budget: RouteBudget) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let timeout_ms = budget.timeout.as_millis();
+    route.layer(
+        ServiceBuilder::new()
+            .layer(HandleErrorLayer::new(move |error: BoxError| async move {
+                if error.is::<tower::timeout::error::Elapsed>() {
+                    ApiError::Timeout(format!("Request did not finish within {} ms", timeout_ms))
+                } else if error.is::<tower::load_shed::error::Overloaded>() {
+                    ApiError::Unavailable("Too many requests in flight on this endpoint; retry later".to_string())
+                } else {
+                    ApiError::Internal(error.to_string())
+                }
+            }))
+            .load_shed()
+            .concurrency_limit(budget.max_concurrent)
+            .timeout(budget.timeout),
+    )
+}
@@ -2,6 +2,7 @@ use regex::Regex;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use crate::config_file::LlmSection;
 use std::env;
 use std::sync::OnceLock;
 use std::process::{Command, Stdio};
@@ -133,25 +134,53 @@ pub struct LlmConfig {
 }
 
 impl LlmConfig {
+    /// Settings safe to expose over the API (the API key is masked).
+    pub fn masked(&self) -> serde_json::Value {
+        let api_key = self.api_key.as_deref().map(|key| {
+            let chars: Vec<char> = key.chars().collect();
+            if chars.len() > 8 {
+                format!("****{}", chars[chars.len() - 4..].iter().collect::<String>())
+            } else {
+                "****".to_string()
+            }
+        });
+        serde_json::json!({
+            "provider": self.provider,
+            "model": self.model,
+            "api_key": api_key,
+            "ollama_url": self.ollama_url,
+        })
+    }
+
     pub fn from_env() -> Option<Self> {
+        Self::from_env_or(&LlmSection::default())
+    }
+
+    /// Like [`from_env`](Self::from_env), but settings missing from the
+    /// environment come from `file` before the built-in defaults.
+    pub fn from_env_or(file: &LlmSection) -> Option<Self> {
         let enabled = env::var("LLM_ENABLED")
             .map(|v| v.to_lowercase() != "false")
+            .ok()
+            .or(file.enabled)
             .unwrap_or(true);
             
         if !enabled {
             return None;
         }
 
+        let setting = |var: &str, fallback: &Option<String>| env::var(var).ok().or_else(|| fallback.clone());
+
         // Default to Ollama (local, no API key required)
-        let provider = env::var("LLM_PROVIDER").unwrap_or_else(|_| "ollama".to_string());
+        let provider = setting("LLM_PROVIDER", &file.provider).unwrap_or_else(|| "ollama".to_string());
         
         let (model, api_key, ollama_url) = if provider == "ollama" {
-            let model = env::var("LLM_MODEL").unwrap_or_else(|_| "mistral".to_string());
-            let url = env::var("OLLAMA_URL").unwrap_or_else(|_| "http://localhost:11434".to_string());
+            let model = setting("LLM_MODEL", &file.model).unwrap_or_else(|| "mistral".to_string());
+            let url = setting("OLLAMA_URL", &file.ollama_url).unwrap_or_else(|| "http://localhost:11434".to_string());
             (model, None, url)
         } else {
-            let model = env::var("LLM_MODEL").unwrap_or_else(|_| "gpt-3.5-turbo".to_string());
-            let api_key = env::var("LLM_API_KEY").ok();
+            let model = setting("LLM_MODEL", &file.model).unwrap_or_else(|| "gpt-3.5-turbo".to_string());
+            let api_key = setting("LLM_API_KEY", &file.api_key);
             (model, api_key, "http://localhost:11434".to_string())
         };
         
@@ -164,6 +193,30 @@ impl LlmConfig {
     }
 }
 
+/// Quick availability check for readiness probes: Ollama must answer within
+/// two seconds; hosted providers only need an API key.
+pub async fn check_available(config: &LlmConfig) -> Result<(), String> {
+    match config.provider.as_str() {
+        "ollama" => {
+            let url = format!("{}/api/tags", config.ollama_url.trim_end_matches('/'));
+            get_client()
+                .get(&url)
+                .timeout(Duration::from_secs(2))
+                .send()
+                .await
+                .and_then(|resp| resp.error_for_status())
+                .map(|_| ())
+                .map_err(|e| format!("Ollama unreachable: {}", e))
+        }
+        "openai" | "google" => match config.api_key {
+            Some(_) => Ok(()),
+            None => Err(format!("{} API key not configured", config.provider)),
+        },
+        _ => Err(format!("Unsupported provider: {}", config.provider)),
+    }
+}
+
+#[tracing::instrument(name = "llm.propose_cues", skip_all, fields(provider = %config.provider, model = %config.model))]
 pub async fn propose_cues(content: &str, config: &LlmConfig, known_cues: &[String]) -> Result<Vec<String>, String> {
     match config.provider.as_str() {
         "ollama" => propose_cues_ollama(content, config, known_cues).await,
@@ -173,6 +226,7 @@ pub async fn propose_cues(content: &str, config: &LlmConfig, known_cues: &[Strin
     }
 }
 
+#[tracing::instrument(name = "llm.extract_facts", skip_all, fields(provider = %config.provider, model = %config.model))]
 pub async fn extract_facts(content: &str, config: &LlmConfig) -> Result<(String, Vec<String>), String> {
     // Only implemented for Ollama for this milestone
     match config.provider.as_str() {
@@ -181,8 +235,228 @@ pub async fn extract_facts(content: &str, config: &LlmConfig) -> Result<(String,
     }
 }
 
-async fn extract_facts_ollama(content: &str, config: &LlmConfig) -> Result<(String, Vec<String>), String> {
-    let system_prompt = r#"You are a Knowledge Extraction Agent. 
+const SUMMARIZE_PROMPT: &str = r#"You are a Memory Consolidation Agent.
+You receive several memories from an agentic database that describe the same subject.
+Merge them into ONE canonical memory.
+
+RULES:
+- Keep every distinct fact, number, name and identifier
+- Drop repetition; when memories disagree, keep the most recent wording and mention the conflict
+- Write plain prose, at most a short paragraph
+- Output ONLY the merged memory, no preamble"#;
+
+/// Merge `contents` (oldest first) into one canonical memory.
+#[tracing::instrument(name = "llm.summarize", skip_all, fields(provider = %config.provider, model = %config.model, memories = contents.len()))]
+pub async fn summarize_memories(contents: &[String], config: &LlmConfig) -> Result<String, String> {
+    let prompt = contents
+        .iter()
+        .enumerate()
+        .map(|(i, content)| format!("Memory {}:\n{}", i + 1, content))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    summarize(SUMMARIZE_PROMPT, &prompt, config).await
+}
+
+const SHORTEN_PROMPT: &str = r#"You are a Memory Compression Agent.
+You receive one memory from an agentic database that is too long to store in full.
+Summarize it within the byte budget given.
+
+RULES:
+- Keep every key fact, number, name and identifier
+- Drop examples, repetition and boilerplate first
+- Write plain prose
+- Output ONLY the summary, no preamble"#;
+
+/// Summarize one oversized memory in about `max_bytes` bytes.
+#[tracing::instrument(name = "llm.shorten", skip_all, fields(provider = %config.provider, model = %config.model, bytes = content.len()))]
+pub async fn shorten_memory(content: &str, max_bytes: usize, config: &LlmConfig) -> Result<String, String> {
+    let prompt = format!("Budget: {} bytes\n\nMemory:\n{}", max_bytes, content);
+    summarize(SHORTEN_PROMPT, &prompt, config).await
+}
+
+async fn summarize(system: &str, prompt: &str, config: &LlmConfig) -> Result<String, String> {
+    let summary = match config.provider.as_str() {
+        "ollama" => summarize_ollama(system, prompt, config).await?,
+        "openai" => summarize_openai(system, prompt, config).await?,
+        "google" => summarize_google(system, prompt, config).await?,
+        _ => return Err(format!("Unsupported provider: {}", config.provider)),
+    };
+
+    let summary = summary.trim();
+    if summary.is_empty() {
+        return Err("LLM returned an empty summary".to_string());
+    }
+    Ok(summary.to_string())
+}
+
+async fn summarize_ollama(system: &str, prompt: &str, config: &LlmConfig) -> Result<String, String> {
+    let url = format!("{}/api/generate", config.ollama_url);
+
+    let response = get_client()
+        .post(&url)
+        .json(&json!({
+            "model": config.model,
+            "system": system,
+            "prompt": prompt,
+            "stream": false
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("Ollama connection error: {}. Is Ollama running?", e))?;
+
+    if !response.status().is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("Ollama API error: {}", text));
+    }
+
+    let body: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+    body["response"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| "Invalid Ollama response format".to_string())
+}
+
+async fn summarize_openai(system: &str, prompt: &str, config: &LlmConfig) -> Result<String, String> {
+    let api_key = config.api_key.as_ref().ok_or("OpenAI requires LLM_API_KEY")?;
+
+    let response = get_client()
+        .post("https://api.openai.com/v1/chat/completions")
+        .header("Authorization", format!("Bearer {}", api_key))
+        .json(&json!({
+            "model": config.model,
+            "messages": [
+                { "role": "system", "content": system },
+                { "role": "user", "content": prompt }
+            ]
+        }))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !response.status().is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("OpenAI API error: {}", text));
+    }
+
+    let body: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+    body["choices"][0]["message"]["content"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| "Invalid response format".to_string())
+}
+
+async fn summarize_google(system: &str, prompt: &str, config: &LlmConfig) -> Result<String, String> {
+    let api_key = config.api_key.as_ref().ok_or("Google requires LLM_API_KEY")?;
+    let url = format!(
+        "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
+        config.model, api_key
+    );
+
+    let response = get_client()
+        .post(&url)
+        .json(&json!({
+            "contents": [{
+                "parts": [{ "text": format!("{}\n\n{}", system, prompt) }]
+            }]
+        }))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !response.status().is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("Google API error: {}", text));
+    }
+
+    let body: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+    body["candidates"][0]["content"]["parts"][0]["text"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| "Invalid Gemini response".to_string())
+}
+
+/// Embed `texts` with the embedding `model` of the configured provider; one
+/// vector per text, in order.
+#[tracing::instrument(name = "llm.embed", skip_all, fields(provider = %config.provider, model = %model, texts = texts.len()))]
+pub async fn embed(texts: &[String], config: &LlmConfig, model: &str) -> Result<Vec<Vec<f32>>, String> {
+    if texts.is_empty() {
+        return Ok(Vec::new());
+    }
+    let vectors = match config.provider.as_str() {
+        "ollama" => embed_ollama(texts, config, model).await?,
+        "openai" => embed_openai(texts, config, model).await?,
+        _ => return Err(format!("Unsupported provider for embeddings: {}", config.provider)),
+    };
+    if vectors.len() != texts.len() {
+        return Err(format!("Expected {} embeddings, got {}", texts.len(), vectors.len()));
+    }
+    Ok(vectors)
+}
+
+fn parse_vectors(vectors: Option<Vec<&serde_json::Value>>) -> Result<Vec<Vec<f32>>, String> {
+    vectors
+        .ok_or("Invalid embeddings response format")?
+        .into_iter()
+        .map(|vector| {
+            vector
+                .as_array()
+                .and_then(|values| values.iter().map(|v| v.as_f64().map(|v| v as f32)).collect::<Option<Vec<f32>>>())
+                .ok_or_else(|| "Invalid embedding vector".to_string())
+        })
+        .collect()
+}
+
+async fn embed_ollama(texts: &[String], config: &LlmConfig, model: &str) -> Result<Vec<Vec<f32>>, String> {
+    let url = format!("{}/api/embed", config.ollama_url.trim_end_matches('/'));
+
+    let response = get_client()
+        .post(&url)
+        .json(&json!({
+            "model": model,
+            "input": texts
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("Ollama connection error: {}. Is Ollama running?", e))?;
+
+    if !response.status().is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("Ollama API error: {}", text));
+    }
+
+    let body: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+    parse_vectors(body["embeddings"].as_array().map(|a| a.iter().collect()))
+}
+
+async fn embed_openai(texts: &[String], config: &LlmConfig, model: &str) -> Result<Vec<Vec<f32>>, String> {
+    let api_key = config.api_key.as_ref().ok_or("OpenAI requires LLM_API_KEY")?;
+
+    let response = get_client()
+        .post("https://api.openai.com/v1/embeddings")
+        .header("Authorization", format!("Bearer {}", api_key))
+        .json(&json!({
+            "model": model,
+            "input": texts
+        }))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !response.status().is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("OpenAI API error: {}", text));
+    }
+
+    let body: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+    parse_vectors(body["data"].as_array().map(|a| a.iter().map(|item| &item["embedding"]).collect()))
+}
+
+/// Bump whenever `EXTRACT_PROMPT` changes, so the provenance of extracted
+/// memories tells prompts apart.
+pub const EXTRACT_PROMPT_VERSION: u32 = 1;
+
+const EXTRACT_PROMPT: &str = r#"You are a Knowledge Extraction Agent. 
 Convert the raw file chunk into a structured memory for an agentic database.
 
 OUTPUT FORMAT (JSON):
@@ -200,13 +474,15 @@ CUE RULES:
 
 Keep summary factual and dense."#;
 
+async fn extract_facts_ollama(content: &str, config: &LlmConfig) -> Result<(String, Vec<String>), String> {
+
     let url = format!("{}/api/generate", config.ollama_url);
     
     let response = get_client()
         .post(&url)
         .json(&json!({
             "model": config.model,
-            "system": system_prompt,
+            "system": EXTRACT_PROMPT,
             "prompt": content,
             "stream": false,
             "format": "json" // Force JSON mode in newer Ollama
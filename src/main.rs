@@ -1,33 +1,57 @@
-use cuemap_rust::projects::ProjectContext;
+use cuemap_rust::projects::{ProjectConfig, ProjectContext, PROJECT_CONFIG_FILE};
 use cuemap_rust::normalization::NormalizationConfig;
 use cuemap_rust::taxonomy::Taxonomy;
-use cuemap_rust::auth::AuthConfig;
+use cuemap_rust::auth::{AuthConfig, KeyStore};
 use cuemap_rust::*;
 use axum::Router;
-use clap::Parser;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+use cuemap_rust::config_file::ConfigFile;
+use cuemap_rust::evals::golden::EvalStore;
+use cuemap_rust::shared_vocabulary::{SharedVocabulary, SHARED_VOCABULARY_FILE};
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::path::Path;
+use std::time::{Duration, Instant};
 use tower_http::cors::CorsLayer;
-use tracing::{info, warn, error, Level};
-use tracing_subscriber;
+use tracing::{info, warn, error};
 
-#[derive(Parser, Debug)]
+/// Salted hashes of keys created via /admin/keys, stored in the data directory
+const API_KEYS_FILE: &str = "api_keys.json";
+
+/// Index problems logged per project by --verify-on-start; the rest are counted
+const LOGGED_INDEX_PROBLEMS: usize = 20;
+
+#[derive(clap::Parser, Debug, serde::Serialize)]
 #[command(name = "cuemap-rust")]
 #[command(about = "CueMap Rust Engine - Production Memory Store")]
 struct Args {
+    /// Run an offline command against the data directory instead of serving
+    #[command(subcommand)]
+    #[serde(skip)]
+    command: Option<cli::Command>,
+
+    /// TOML config file; CLI flags and environment variables override its values
+    #[arg(long, global = true)]
+    config: Option<String>,
+
     /// Server port
     #[arg(short, long, default_value = "8080")]
     port: u16,
     
     /// Data directory for persistence
-    #[arg(short, long, default_value = "./data")]
+    #[arg(short, long, global = true, default_value = "./data")]
     data_dir: String,
     
     /// Snapshot interval in seconds
     #[arg(short, long, default_value = "60")]
     snapshot_interval: u64,
     
+    /// Take over the data directory's lock file even if another live process
+    /// holds it
+    #[arg(long)]
+    force_unlock: bool,
+    
     /// Enable multi-tenancy
     #[arg(short, long, default_value = "false")]
     multi_tenant: bool,
@@ -36,6 +60,44 @@ struct Args {
     #[arg(long)]
     load_static: Option<String>,
 
+    /// Run as a read-only follower replicating this primary (e.g. http://primary:8080);
+    /// the primary API key, if needed, comes from CUEMAP_FOLLOW_API_KEY
+    #[arg(long, conflicts_with_all = ["multi_tenant", "load_static"])]
+    follow: Option<String>,
+
+    /// Seconds between a follower's polls of its primary
+    #[arg(long, default_value = "5")]
+    follow_interval: u64,
+
+    /// Shared snapshot store (s3://bucket/prefix); needs the `s3` feature. The
+    /// instance holding the writer lease uploads snapshots, others wait for it
+    #[arg(long)]
+    snapshot_store: Option<String>,
+
+    /// Boot read-only from the --snapshot-store and reload its new snapshots
+    #[arg(long, requires = "snapshot_store")]
+    snapshot_store_reader: bool,
+
+    /// Cross-check each project's cue index against its memories after
+    /// loading and log orphaned and dangling entries
+    #[arg(long)]
+    verify_on_start: bool,
+
+    /// Rebuild inconsistent cue indexes from the memories after loading
+    /// (implies --verify-on-start)
+    #[arg(long)]
+    rebuild_index: bool,
+
+    /// Touch every project's memories and cue index after loading and log a
+    /// readiness summary before listening
+    #[arg(long)]
+    warm_up: bool,
+
+    /// Let every project fall back to the aliases and lexicon terms managed
+    /// under /admin/shared, stored in the data directory
+    #[arg(long)]
+    shared_vocabulary: bool,
+
     /// Directory to watch for Self-Learning Agent
     #[arg(long)]
     agent_dir: Option<String>,
@@ -43,27 +105,301 @@ struct Args {
     /// Agent throttle in milliseconds
     #[arg(long, default_value = "100")]
     agent_throttle: u64,
+
+    /// Seconds between agent reconciliation passes, which rescan the watched
+    /// directory for changes and deletions the watcher missed (0 = off)
+    #[arg(long, default_value = "300")]
+    agent_reconcile_interval: u64,
+
+    /// Cues the agent attaches to every memory it ingests (comma-separated or repeated)
+    #[arg(long, value_delimiter = ',')]
+    agent_cue: Vec<String>,
+
+    /// Cue templates filled from the ingested file's path, e.g. service:{0}
+    #[arg(long, value_delimiter = ',')]
+    agent_path_cue: Vec<String>,
+
+    /// Cue keys the agent's LLM may not attach, e.g. author
+    #[arg(long, value_delimiter = ',')]
+    agent_block_key: Vec<String>,
+
+    /// Subdirectories whose memories go to a collection, as DIR=COLLECTION, e.g. docs=docs
+    #[arg(long, value_delimiter = ',')]
+    agent_route: Vec<String>,
+
+    /// Maximum number of grounding traces retained for audits
+    #[arg(long, default_value = "10000")]
+    trace_retention: usize,
+
+    /// Maximum age of retained grounding traces in days (0 = no age limit)
+    #[arg(long, default_value = "30")]
+    trace_retention_days: u64,
+
+    /// PEM certificate chain; serves HTTPS when set together with --tls-key
+    #[arg(long, requires = "tls_key")]
+    tls_cert: Option<String>,
+
+    /// PEM private key for --tls-cert
+    #[arg(long, requires = "tls_cert")]
+    tls_key: Option<String>,
+
+    /// PEM CA bundle for verifying client certificates (enables mTLS)
+    #[arg(long, requires = "tls_cert")]
+    tls_client_ca: Option<String>,
+
+    /// Only accept clients in these CIDRs (comma-separated or repeated)
+    #[arg(long, value_delimiter = ',')]
+    allow_cidr: Vec<String>,
+
+    /// Reject clients in these CIDRs (takes precedence over --allow-cidr)
+    #[arg(long, value_delimiter = ',')]
+    deny_cidr: Vec<String>,
+
+    /// Proxies whose X-Forwarded-For header is trusted for the client IP
+    #[arg(long, value_delimiter = ',')]
+    trusted_proxy: Vec<String>,
+
+    /// DashMap shard count for engine maps (power of two); more shards reduce write contention
+    #[arg(long, default_value_t = config::DASHMAP_SHARD_COUNT)]
+    dashmap_shards: usize,
+
+    /// Memories to pre-allocate for when the single-tenant store starts empty
+    #[arg(long, default_value = "0")]
+    expected_memories: usize,
+
+    /// Serve recalls from an index view republished every N ms so writes never
+    /// block them; recalls may miss writes from the last interval (0 = read live)
+    #[arg(long, default_value = "0")]
+    read_view_refresh_ms: u64,
+
+    /// Cache recall responses for N ms, until any write to their project, so
+    /// repeated identical recalls skip scoring (0 = off)
+    #[arg(long, default_value = "0")]
+    recall_cache_ttl_ms: u64,
+
+    /// Alias hops followed when expanding a query cue, so `pay` -> `payments` ->
+    /// `service:payments` chains resolve (1 = direct aliases only, 0 = none)
+    #[arg(long, default_value_t = config::ALIAS_MAX_DEPTH)]
+    alias_max_depth: usize,
+
+    /// Stop scanning cue lists once no unscanned memory can catch up on cue
+    /// weight; faster on broad queries, but salience, recency and frequency
+    /// are ignored, so a memory they would rank high can be missed
+    #[arg(long)]
+    approximate_early_exit: bool,
+
+    /// Largest HTTP request body accepted, in bytes (larger ones get 413)
+    #[arg(long, default_value_t = config::MAX_BODY_BYTES)]
+    max_body_bytes: usize,
+
+    /// Longest memory content accepted, in bytes
+    #[arg(long, default_value_t = config::MAX_CONTENT_BYTES)]
+    max_content_bytes: usize,
+
+    /// Most cues accepted on one memory
+    #[arg(long, default_value_t = config::MAX_CUES_PER_MEMORY)]
+    max_cues_per_memory: usize,
+
+    /// Largest `limit` a recall may ask for
+    #[arg(long, default_value_t = config::MAX_RECALL_LIMIT)]
+    max_recall_limit: usize,
+
+    /// Compress HTTP responses larger than this many bytes with gzip or brotli
+    /// when the client accepts it (0 = off)
+    #[arg(long, default_value_t = config::COMPRESSION_MIN_BYTES)]
+    compression_min_bytes: u16,
+
+    /// Seconds between LLM consolidation runs, which merge memories sharing most
+    /// of their cues into one summary and tombstone the originals (0 = off)
+    #[arg(long, default_value = "0")]
+    consolidate_interval: u64,
+
+    /// Seconds between maintenance runs, which decay reinforcement counts, demote
+    /// memories not accessed for 90 days and prune empty cue entries (0 = off)
+    #[arg(long, default_value = "0")]
+    maintenance_interval: u64,
+
+    /// Seconds between stale memory digests, which list memories demoted or about
+    /// to be demoted by maintenance for review (604800 = weekly, 0 = off)
+    #[arg(long, default_value = "0")]
+    stale_digest_interval: u64,
+
+    /// Also serve the gRPC API (proto/cuemap.proto) on this port; needs the `grpc` feature
+    #[arg(long)]
+    grpc_port: Option<u16>,
+
+    /// Seconds to wait on shutdown for in-flight requests, then for queued
+    /// background jobs, before saving and exiting
+    #[arg(long, default_value = "30")]
+    shutdown_timeout: u64,
+
+    /// Log line format; per-module levels come from RUST_LOG (e.g. "info,cuemap_rust::jobs=debug")
+    #[arg(long, value_enum, default_value = "pretty")]
+    log_format: telemetry::LogFormat,
 }
 
 #[tokio::main]
 async fn main() {
-    // Parse CLI arguments
-    let args = Args::parse();
+    // Parse CLI arguments, then fill in whatever they left unset from the config file
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let config_file = match args.config.as_deref().map(|path| ConfigFile::load(Path::new(path))).transpose() {
+        Ok(file) => file.unwrap_or_default(),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    apply_config_file(&mut args, &matches, &config_file);
+    
+    // Offline commands keep stdout for their own output, so skip log setup
+    if let Some(command) = args.command.clone() {
+        if let Err(e) = cli::run(command, Path::new(&args.data_dir), &mut std::io::stdout().lock()) {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
     
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_max_level(Level::INFO)
-        .init();
+    // Initialize tracing (and OTLP export when built with `otel` and configured)
+    let _telemetry = telemetry::init(args.log_format);
     
     info!("CueMap Rust Engine - Production Mode");
     
-    // Initialize authentication
-    let auth_config = AuthConfig::new();
+    // Engine map sizing must be fixed before any engine is constructed
+    let sizing = config::EngineSizing {
+        shard_count: args.dashmap_shards,
+        expected_memories: args.expected_memories,
+    };
+    if let Err(e) = config::set_engine_sizing(sizing) {
+        error!("Invalid --dashmap-shards: {}", e);
+        std::process::exit(1);
+    }
+    // Likewise read isolation, which is decided when a project is built
+    let read_view_refresh = (args.read_view_refresh_ms > 0).then(|| Duration::from_millis(args.read_view_refresh_ms));
+    if let Some(every) = read_view_refresh {
+        if let Err(e) = config::set_read_view_refresh(every) {
+            error!("Invalid --read-view-refresh-ms: {}", e);
+            std::process::exit(1);
+        }
+        info!("Recalls read an index view refreshed every {}ms", args.read_view_refresh_ms);
+    }
+    if args.recall_cache_ttl_ms > 0 {
+        if let Err(e) = config::set_recall_cache_ttl(Duration::from_millis(args.recall_cache_ttl_ms)) {
+            error!("Invalid --recall-cache-ttl-ms: {}", e);
+            std::process::exit(1);
+        }
+        info!("Recall responses are cached for {}ms", args.recall_cache_ttl_ms);
+    }
+    
+    if let Err(e) = config::set_alias_max_depth(args.alias_max_depth) {
+        error!("Invalid --alias-max-depth: {}", e);
+        std::process::exit(1);
+    }
+    if args.approximate_early_exit {
+        if let Err(e) = config::set_approximate_early_exit(true) {
+            error!("Invalid --approximate-early-exit: {}", e);
+            std::process::exit(1);
+        }
+        info!("Recalls exit early on cue weight; results are approximate");
+    }
+    // Request limits are read when the router is built
+    let request_limits = config::RequestLimits {
+        max_body_bytes: args.max_body_bytes,
+        max_content_bytes: args.max_content_bytes,
+        max_cues_per_memory: args.max_cues_per_memory,
+        max_recall_limit: args.max_recall_limit,
+    };
+    if let Err(e) = config::set_request_limits(request_limits) {
+        error!("Invalid request limits: {}", e);
+        std::process::exit(1);
+    }
+    if let Err(e) = config::set_compression_min_bytes(args.compression_min_bytes) {
+        error!("Invalid --compression-min-bytes: {}", e);
+        std::process::exit(1);
+    }
+    
+    // The config file can set what --follow conflicts with on the command line
+    if args.follow.is_some() && (args.multi_tenant || args.load_static.is_some()) {
+        error!("--follow cannot be combined with multi-tenant or static loading mode");
+        std::process::exit(1);
+    }
+    // The shared store replaces the local snapshot; this may wait for the writer lease
+    #[cfg(feature = "s3")]
+    let snapshot_store = match args.snapshot_store.clone() {
+        Some(url) => Some(open_snapshot_store(&url, &mut args).await),
+        None => None,
+    };
+    #[cfg(not(feature = "s3"))]
+    if args.snapshot_store.is_some() {
+        warn!("--snapshot-store is set, but this build lacks the `s3` feature; using the local data directory");
+    }
+    
+    // Static snapshots and followers are read-only and keep nothing on disk
+    let is_static = args.load_static.is_some() || args.follow.is_some();
+    
+    // Initialize persistence (skip if static mode); the data dir lock is taken
+    // before anything in it is written
+    let persistence = if !is_static {
+        match persistence::PersistenceManager::new(&args.data_dir, args.snapshot_interval, args.force_unlock) {
+            Ok(pm) => Some(pm),
+            Err(e) => {
+                error!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+    
+    // Initialize authentication (runtime-managed keys are in-memory only in static mode)
+    let mut auth_config = AuthConfig::from_env_or(config_file.auth.api_keys.as_deref().unwrap_or_default());
+    if !is_static {
+        let _ = std::fs::create_dir_all(&args.data_dir);
+        auth_config = auth_config.with_key_store(KeyStore::open(Path::new(&args.data_dir).join(API_KEYS_FILE)));
+    }
+    auth_config.log_status();
+    
+    // Webhook subscriptions; read-only instances have no writes to report
+    let webhooks = (!is_static).then(|| Arc::new(webhooks::WebhookHub::open(Path::new(&args.data_dir).join(webhooks::WEBHOOKS_FILE))));
+    // Key for signing /admin/forget deletion reports
+    let forget_signer = (!is_static).then(|| Arc::new(forget::ReportSigner::open(Path::new(&args.data_dir).join(forget::SIGNING_KEY_FILE))));
+    // Golden sets and their run history (in memory only in static mode)
+    let evals = Arc::new(if is_static { EvalStore::new() } else { EvalStore::open(&args.data_dir) });
+    // Aliases and lexicon terms every project falls back to; read-only in static mode
+    let shared_vocabulary = args.shared_vocabulary.then(|| {
+        let dir = args.load_static.as_deref().unwrap_or(&args.data_dir);
+        Arc::new(SharedVocabulary::open(Path::new(dir).join(SHARED_VOCABULARY_FILE)))
+    });
+    
+    // LLM consolidation rewrites memories, so read-only instances never schedule it
+    let consolidate_interval = (!is_static && args.consolidate_interval > 0).then(|| Duration::from_secs(args.consolidate_interval));
+    if let Some(every) = consolidate_interval {
+        info!("Consolidating similar memories with the LLM every {}s", every.as_secs());
+    }
+    let maintenance_interval = (!is_static && args.maintenance_interval > 0).then(|| Duration::from_secs(args.maintenance_interval));
+    let stale_digest_interval = (!is_static && args.stale_digest_interval > 0).then(|| Duration::from_secs(args.stale_digest_interval));
+    
+    // Resolved startup settings, reported by GET /admin/config
+    let mut server_settings = serde_json::to_value(&args).unwrap_or_default();
+    server_settings["auth"] = serde_json::json!({
+        "enabled": auth_config.is_enabled(),
+        "env_keys": auth_config.env_key_count(),
+    });
     
-    // Check for static loading mode
-    let is_static = args.load_static.is_some();
+    // IP allow/deny lists, enforced before auth
+    let ip_filter = match ip_filter::IpFilter::new(&args.allow_cidr, &args.deny_cidr, &args.trusted_proxy) {
+        Ok(filter) => filter,
+        Err(e) => {
+            error!("Invalid IP filter configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+    ip_filter.log_status();
     
-    if is_static {
+    if let Some(primary) = &args.follow {
+        info!("Follower mode enabled (read-only), replicating {} every {}s", primary, args.follow_interval);
+    } else if is_static {
         info!("Static loading mode enabled (read-only)");
         info!("Loading from: {}", args.load_static.as_ref().unwrap());
         info!("Persistence disabled - all changes will be lost on restart");
@@ -72,16 +408,24 @@ async fn main() {
         info!("Snapshot interval: {}s", args.snapshot_interval);
     }
     
-    // Initialize persistence (skip if static mode)
-    let persistence = if !is_static {
-        Some(persistence::PersistenceManager::new(&args.data_dir, args.snapshot_interval))
+    // Initialize grounding trace store (in-memory only in static mode)
+    let trace_max_age = if args.trace_retention_days > 0 {
+        Some((args.trace_retention_days * 86_400) as f64)
     } else {
         None
     };
+    let traces = if is_static {
+        Arc::new(grounding::GroundingTraceStore::new(args.trace_retention, trace_max_age))
+    } else {
+        let _ = std::fs::create_dir_all(&args.data_dir);
+        let traces_path = Path::new(&args.data_dir).join(grounding::GROUNDING_TRACES_FILE);
+        Arc::new(grounding::GroundingTraceStore::open(traces_path, args.trace_retention, trace_max_age))
+    };
     
 
     
     // Initialize engine for single-tenant mode
+    let load_started = Instant::now();
     let project = if !args.multi_tenant {
         info!("Single-tenant mode");
         
@@ -94,66 +438,80 @@ async fn main() {
                     Ok((memories, cue_index)) => {
                         info!("Loaded {} memories, {} cues", memories.len(), cue_index.len());
                         let main_engine = engine::CueMapEngine::from_state(memories, cue_index);
-                        Arc::new(ProjectContext {
-                            main: main_engine,
-                            aliases: engine::CueMapEngine::new(),
-                            lexicon: engine::CueMapEngine::new(),
-                            query_cache: dashmap::DashMap::new(),
-                            normalization: NormalizationConfig::default(),
-                            taxonomy: Taxonomy::default(),
-                        })
+                        let config = ProjectConfig::load_or_default(&Path::new(static_dir).join(PROJECT_CONFIG_FILE));
+                        ProjectContext::from_config(main_engine, config)
                     }
                     Err(e) => {
                         warn!("Failed to load static snapshot: {}, starting fresh", e);
-                        Arc::new(ProjectContext::new(NormalizationConfig::default(), Taxonomy::default()))
+                        ProjectContext::new(NormalizationConfig::default(), Taxonomy::default())
                     }
                 }
             } else {
                 warn!("No snapshot found at {:?}, starting fresh", snapshot_path);
-                Arc::new(ProjectContext::new(NormalizationConfig::default(), Taxonomy::default()))
+                ProjectContext::new(NormalizationConfig::default(), Taxonomy::default())
             }
         } else if let Some(ref pm) = persistence {
             // Load from data directory
-            match pm.load_state() {
-                Ok((memories, cue_index)) => {
+            let config_path = Path::new(&args.data_dir).join(PROJECT_CONFIG_FILE);
+            let config = ProjectConfig::load_or_default(&config_path);
+            let main_engine = match pm.load_state() {
+                Ok((memories, cue_index)) if !memories.is_empty() => {
                     info!("Loaded {} memories, {} cues", memories.len(), cue_index.len());
-                    let main_engine = engine::CueMapEngine::from_state(memories, cue_index);
-                    Arc::new(ProjectContext {
-                        main: main_engine,
-                        aliases: engine::CueMapEngine::new(),
-                        lexicon: engine::CueMapEngine::new(),
-                        query_cache: dashmap::DashMap::new(),
-                        normalization: NormalizationConfig::default(),
-                        taxonomy: Taxonomy::default(),
-                    })
+                    engine::CueMapEngine::from_state(memories, cue_index)
                 }
+                Ok(_) => engine::CueMapEngine::with_sizing(sizing),
                 Err(e) => {
                     info!("Failed to load state: {}, starting fresh", e);
-                    Arc::new(ProjectContext::new(NormalizationConfig::default(), Taxonomy::default()))
+                    engine::CueMapEngine::with_sizing(sizing)
                 }
-            }
+            };
+            ProjectContext::from_config(main_engine, config).with_config_path(config_path)
         } else {
-            Arc::new(ProjectContext::new(NormalizationConfig::default(), Taxonomy::default()))
+            ProjectContext::new(NormalizationConfig::default(), Taxonomy::default())
         }
     } else {
         // Not used in multi-tenant mode, but we need a dummy value matching the type if we were using same variable.
         // But we can just use a dummy context.
-        Arc::new(ProjectContext::new(NormalizationConfig::default(), Taxonomy::default()))
+        ProjectContext::new(NormalizationConfig::default(), Taxonomy::default())
     };
+    let project = Arc::new(match &shared_vocabulary {
+        Some(shared) => project.with_shared_vocabulary(shared.clone()),
+        None => project,
+    });
+    
+    let loaded_in = load_started.elapsed();
     
+    if !args.multi_tenant && (args.verify_on_start || args.rebuild_index) {
+        verify_project("default", &project, args.rebuild_index);
+    }
+    if !args.multi_tenant && args.warm_up {
+        warm_up_project("default", &project, loaded_in);
+    }
+
     // Start background snapshots (skip if static mode)
+    let mut snapshot_task = None;
     if let Some(ref pm) = persistence {
         if !args.multi_tenant {
             // We need to pass Arc<CueMapEngine> to persistence, so we wrap the main engine.
             // Since CueMapEngine holds Arcs internally, cloning it is cheap and shares data.
             let main_engine = Arc::new(project.main.clone());
-            let _snapshot_handle = pm.start_background_snapshots(main_engine.clone()).await;
-            persistence::setup_shutdown_handler(pm.clone(), main_engine).await;
+            snapshot_task = Some(pm.start_background_snapshots(main_engine).await);
         }
     }
     
+    // SIGINT/SIGTERM flips this; the servers stop accepting and drain
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(async move {
+        persistence::shutdown_signal().await;
+        let _ = shutdown_tx.send(true);
+    });
+    let drain_timeout = Duration::from_secs(args.shutdown_timeout);
+    
+    #[cfg(feature = "s3")]
+    let mut store_writer = None;
+    
     // Build the router with appropriate engine state
-    let app = if args.multi_tenant {
+    let (app, engine_state) = if args.multi_tenant {
         info!("Multi-tenant mode enabled");
         
         let snapshots_dir = if let Some(ref static_dir) = args.load_static {
@@ -162,11 +520,17 @@ async fn main() {
             format!("{}/snapshots", args.data_dir)
         };
         
-        let mt_engine = Arc::new(multi_tenant::MultiTenantEngine::with_snapshots_dir(&snapshots_dir));
+        let mut mt_engine = multi_tenant::MultiTenantEngine::with_snapshots_dir(&snapshots_dir);
+        if let Some(shared) = &shared_vocabulary {
+            mt_engine = mt_engine.with_shared_vocabulary(shared.clone());
+        }
+        let mt_engine = Arc::new(mt_engine);
         
         // Auto-load all available snapshots
         info!("Loading snapshots from: {}", snapshots_dir);
+        let load_started = Instant::now();
         let load_results = mt_engine.load_all();
+        let loaded_in = load_started.elapsed();
         let loaded = load_results.iter().filter(|(_, r)| r.is_ok()).count();
         let failed = load_results.iter().filter(|(_, r)| r.is_err()).count();
         
@@ -184,28 +548,94 @@ async fn main() {
         if loaded == 0 && failed == 0 {
             info!("No existing snapshots found, starting fresh");
         }
-        
-        // Setup shutdown handler for auto-save (skip if static mode)
-        if !is_static {
-            setup_multi_tenant_shutdown_handler(mt_engine.clone()).await;
+        if args.verify_on_start || args.rebuild_index {
+            for project_id in mt_engine.project_ids() {
+                if let Some(project) = mt_engine.get_project(&project_id) {
+                    verify_project(&project_id, &project, args.rebuild_index);
+                }
+            }
+        }
+        if args.warm_up {
+            for project_id in mt_engine.project_ids() {
+                if let Some(project) = mt_engine.get_project(&project_id) {
+                    warm_up_project(&project_id, &project, loaded_in);
+                }
+            }
         }
         
         let provider: Arc<dyn jobs::ProjectProvider> = mt_engine.clone();
-        let job_queue = Arc::new(jobs::JobQueue::new(provider));
+        if let Some(every) = read_view_refresh {
+            jobs::spawn_read_view_refresher(provider.clone(), every);
+        }
+        if let Some(hub) = &webhooks {
+            hub.watch_all(provider.as_ref());
+        }
+        let job_queue = Arc::new(jobs::JobQueue::new(provider.clone()));
+        if let Some(every) = consolidate_interval {
+            jobs::spawn_consolidation_scheduler(provider.clone(), job_queue.clone(), every);
+        }
+        if let Some(every) = maintenance_interval {
+            jobs::spawn_maintenance_scheduler(provider.clone(), job_queue.clone(), every);
+        }
+        if let Some(every) = stale_digest_interval {
+            jobs::spawn_stale_digest_scheduler(provider, job_queue.clone(), every);
+        }
         
-        let mt_engine = mt_engine;
+        let engine_state = api::EngineState::MultiTenant {
+            mt_engine: mt_engine.clone(),
+            read_only: is_static,
+            job_queue: job_queue.clone(),
+            traces: traces.clone(),
+        };
         
-        Router::new()
-            .merge(api::routes_with_mt_engine(mt_engine, job_queue, auth_config, is_static))
-            .layer(CorsLayer::permissive())
+        let router = Router::new()
+            .merge(api::routes_with_mt_engine(mt_engine, job_queue, traces, auth_config.clone(), is_static))
+            .layer(CorsLayer::permissive());
+        (router, engine_state)
     } else {
         let provider = Arc::new(jobs::SingleTenantProvider { project: project.clone() });
         let job_queue = Arc::new(jobs::JobQueue::new(provider.clone()));
+        if let Some(hub) = &webhooks {
+            hub.watch_all(provider.as_ref());
+        }
+        if let Some(every) = read_view_refresh {
+            jobs::spawn_read_view_refresher(provider.clone(), every);
+        }
+        if let Some(every) = consolidate_interval {
+            jobs::spawn_consolidation_scheduler(provider.clone(), job_queue.clone(), every);
+        }
+        if let Some(every) = maintenance_interval {
+            jobs::spawn_maintenance_scheduler(provider.clone(), job_queue.clone(), every);
+        }
+        if let Some(every) = stale_digest_interval {
+            jobs::spawn_stale_digest_scheduler(provider.clone(), job_queue.clone(), every);
+        }
         
         // Start Agent if configured
-        let _agent_handle = if let Some(agent_dir) = args.agent_dir {
+        let agent_handle = if let Some(agent_dir) = args.agent_dir {
             info!("Initializing Self-Learning Agent for: {}", agent_dir);
-            if let Some(llm_config) = llm::LlmConfig::from_env() {
+            // Routes on the command line replace the config file's
+            let routes: Result<Vec<_>, String> = if args.agent_route.is_empty() {
+                Ok(config_file.agent.routes.clone().unwrap_or_default())
+            } else {
+                args.agent_route.iter().map(|route| agent::cue_policy::DirectoryRoute::parse(route)).collect()
+            };
+            let routes = routes.unwrap_or_else(|e| {
+                error!("Invalid agent route: {}", e);
+                std::process::exit(1);
+            });
+            let cue_policy = agent::cue_policy::CuePolicy {
+                static_cues: args.agent_cue,
+                path_cues: args.agent_path_cue,
+                blocked_keys: args.agent_block_key,
+                routes,
+            };
+            let problems = cue_policy.validate();
+            if !problems.is_empty() {
+                error!("Invalid agent cue policy: {}", problems.join("; "));
+                std::process::exit(1);
+            }
+            if let Some(llm_config) = llm::LlmConfig::from_env_or(&config_file.llm) {
                 // ... (Ollama check kept)
                 if !llm::setup::ensure_ollama_running(&llm_config).await {
                     error!("Failed to setup Ollama (install/serve/pull). Agent will likely fail.");
@@ -215,13 +645,15 @@ async fn main() {
                     watch_dir: agent_dir,
                     throttle_ms: args.agent_throttle,
                     llm: llm_config,
+                    cue_policy,
                 };
                 
                 let provider_for_agent: Arc<dyn jobs::ProjectProvider> = provider.clone();
                 
                 match agent::Agent::new(config, job_queue.clone(), provider_for_agent) {
                     Ok(agent) => {
-                        agent.start().await;
+                        let reconcile_every = (args.agent_reconcile_interval > 0).then(|| Duration::from_secs(args.agent_reconcile_interval));
+                        agent.start(reconcile_every).await;
                         Some(agent) // Keep alive
                     },
                     Err(e) => {
@@ -237,53 +669,353 @@ async fn main() {
             None
         };
 
-        Router::new()
-            .merge(api::routes(project, job_queue, auth_config, is_static))
-            .layer(CorsLayer::permissive())
+        let engine_state = api::EngineState::SingleTenant {
+            project: project.clone(),
+            read_only: is_static,
+            job_queue: job_queue.clone(),
+            traces: traces.clone(),
+        };
+
+        let mut router = Router::new()
+            .merge(api::routes(project.clone(), job_queue, traces, auth_config.clone(), is_static))
+            .layer(CorsLayer::permissive());
+        if let Some(agent) = &agent_handle {
+            router = router.layer(axum::Extension(agent.handle().await));
+        }
+        
+        #[cfg(feature = "s3")]
+        if let Some(store) = &snapshot_store {
+            let every = Duration::from_secs(args.snapshot_interval.max(1));
+            if args.snapshot_store_reader {
+                store.clone().spawn_reader(project.clone(), every);
+            } else {
+                store_writer = Some(store.clone().spawn_writer(project.clone(), every));
+            }
+        }
+        
+        if let Some(primary) = &args.follow {
+            let api_key = std::env::var(replication::FOLLOW_API_KEY_ENV).ok();
+            let follower = replication::Follower::new(primary, api_key, project);
+            router = router.layer(axum::Extension(follower.status()));
+            follower.spawn(Duration::from_secs(args.follow_interval.max(1)));
+        }
+        (router, engine_state)
+    };
+    
+    if let Some(port) = args.grpc_port {
+        start_grpc(port, engine_state.clone(), auth_config, shutdown_requested(shutdown_rx.clone()));
+    }
+    
+    let app = match webhooks {
+        Some(hub) => app.layer(axum::Extension(hub)),
+        None => app,
+    };
+    let app = match forget_signer {
+        Some(signer) => app.layer(axum::Extension(signer)),
+        None => app,
+    };
+    // /admin/forget saves the single-tenant snapshot right after a purge
+    let app = match (&persistence, args.multi_tenant) {
+        (Some(pm), false) => app.layer(axum::Extension(pm.clone())),
+        _ => app,
+    };
+    let app = app.layer(axum::Extension(evals));
+    let app = match shared_vocabulary {
+        Some(shared) => app.layer(axum::Extension(shared)),
+        None => app,
+    };
+    
+    let app = app
+        .layer(axum::Extension(Arc::new(config::RuntimeSettings(server_settings))))
+        .layer(axum::middleware::from_fn(telemetry::trace_request));
+    
+    let app = if ip_filter.is_enabled() {
+        app.layer(axum::middleware::from_fn_with_state(Arc::new(ip_filter), ip_filter::ip_filter_middleware))
+    } else {
+        app
+    };
+    
+    // Load TLS before binding so a bad certificate fails fast
+    let tls_config = match (&args.tls_cert, &args.tls_key) {
+        (Some(cert), Some(key)) => {
+            match tls::server_config(Path::new(cert), Path::new(key), args.tls_client_ca.as_deref().map(Path::new)) {
+                Ok(config) => Some(config),
+                Err(e) => {
+                    error!("Failed to configure TLS: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        _ => None,
     };
     
     let addr = SocketAddr::from(([0, 0, 0, 0], args.port));
-    info!("Server listening on {}", addr);
+    match (&tls_config, &args.tls_client_ca) {
+        (Some(_), Some(_)) => info!("Server listening on {} (HTTPS, client certificates required)", addr),
+        (Some(_), None) => info!("Server listening on {} (HTTPS)", addr),
+        _ => info!("Server listening on {}", addr),
+    }
     info!("Performance optimizations enabled:");
     info!("   - IndexSet for O(1) operations");
-    info!("   - DashMap with {} shards", config::DASHMAP_SHARD_COUNT);
-    info!("   - Pre-allocated collections");
+    info!("   - DashMap with {} shards", sizing.shard_count);
+    info!("   - Pre-allocated collections (expected memories: {})", sizing.expected_memories);
     info!("   - Unstable sorting for speed");
     
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    let server = async {
+        if let Some(tls_config) = tls_config {
+            let rustls_config = axum_server::tls_rustls::RustlsConfig::from_config(Arc::new(tls_config));
+            let handle = axum_server::Handle::new();
+            let signal_handle = handle.clone();
+            let signal = shutdown_requested(shutdown_rx.clone());
+            tokio::spawn(async move {
+                signal.await;
+                signal_handle.graceful_shutdown(Some(drain_timeout));
+            });
+            axum_server::bind_rustls(addr, rustls_config)
+                .handle(handle)
+                .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+                .await
+                .unwrap();
+        } else {
+            let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+            axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+                .with_graceful_shutdown(shutdown_requested(shutdown_rx.clone()))
+                .await
+                .unwrap();
+        }
+    };
+    
+    // Graceful shutdown waits for open connections forever; cap the drain
+    let drain_deadline = async {
+        shutdown_requested(shutdown_rx.clone()).await;
+        tokio::time::sleep(drain_timeout).await;
+    };
+    tokio::select! {
+        _ = server => info!("In-flight requests drained"),
+        _ = drain_deadline => warn!("In-flight requests still running after {}s; shutting down anyway", args.shutdown_timeout),
+    }
+    
+    // Jobs may still write to engines, so finish them before the final save
+    let (api::EngineState::SingleTenant { job_queue, .. } | api::EngineState::MultiTenant { job_queue, .. }) = &engine_state;
+    if job_queue.shutdown(drain_timeout).await {
+        info!("Background jobs drained");
+    }
+    
+    if let Some(task) = snapshot_task {
+        task.abort();
+        let _ = task.await;
+    }
+    match &engine_state {
+        _ if is_static => {}
+        api::EngineState::SingleTenant { project, .. } => {
+            if let Some(pm) = &persistence {
+                info!("Saving final snapshot before shutdown...");
+                match pm.save_state(&project.main) {
+                    Ok(()) => info!("Final snapshot saved successfully"),
+                    Err(e) => error!("Failed to save final snapshot: {}", e),
+                }
+            }
+        }
+        api::EngineState::MultiTenant { mt_engine, .. } => save_all_projects(mt_engine),
+    }
+    if let Some(pm) = &persistence {
+        pm.release_lock();
+    }
+    
+    // Hand the store to a successor: last upload, then release the lease
+    #[cfg(feature = "s3")]
+    if let (Some(store), Some(writer), api::EngineState::SingleTenant { project, .. }) = (&snapshot_store, store_writer, &engine_state) {
+        writer.abort();
+        let _ = writer.await;
+        match store.upload(project).await {
+            Ok(()) => info!("Final snapshot uploaded to the snapshot store"),
+            Err(e) => error!("Failed to upload final snapshot: {}", e),
+        }
+        if let Err(e) = store.release().await {
+            warn!("Failed to release the snapshot store lease: {}", e);
+        }
+    }
+    info!("Shutdown complete");
+}
+
+/// Resolves once a shutdown signal has been received.
+async fn shutdown_requested(mut shutdown: tokio::sync::watch::Receiver<bool>) {
+    let _ = shutdown.wait_for(|requested| *requested).await;
 }
 
-/// Setup shutdown handler for multi-tenant mode
-async fn setup_multi_tenant_shutdown_handler(mt_engine: Arc<multi_tenant::MultiTenantEngine>) {
+/// Serve the gRPC API alongside HTTP on its own port.
+#[cfg(feature = "grpc")]
+fn start_grpc(port: u16, state: api::EngineState, auth_config: AuthConfig, shutdown: impl std::future::Future<Output = ()> + Send + 'static) {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    info!("gRPC listening on {}", addr);
     tokio::spawn(async move {
-        match tokio::signal::ctrl_c().await {
-            Ok(()) => {
-                info!("Shutdown signal received, saving all projects...");
-                let save_results = mt_engine.save_all();
-                let saved = save_results.iter().filter(|(_, r)| r.is_ok()).count();
-                let failed = save_results.iter().filter(|(_, r)| r.is_err()).count();
-                
-                if saved > 0 {
-                    info!("✓ Saved {} project snapshots", saved);
-                }
-                if failed > 0 {
-                    warn!("✗ Failed to save {} projects", failed);
-                    for (project_id, result) in save_results.iter() {
-                        if let Err(e) = result {
-                            warn!("  - {}: {}", project_id, e);
-                        }
-                    }
-                }
-                
-                info!("Shutdown complete");
-                std::process::exit(0);
-            }
-            Err(err) => {
-                warn!("Error setting up shutdown handler: {}", err);
-            }
+        if let Err(e) = grpc::serve(grpc::GrpcService::new(state, auth_config), addr, shutdown).await {
+            error!("gRPC server failed: {}", e);
         }
     });
 }
 
+#[cfg(not(feature = "grpc"))]
+fn start_grpc(_port: u16, _state: api::EngineState, _auth_config: AuthConfig, _shutdown: impl std::future::Future<Output = ()>) {
+    warn!("--grpc-port is set, but this build lacks the `grpc` feature; gRPC is not served");
+}
+
+/// Connect to the shared snapshot store and prepare the data directory from it.
+/// Writers block here until they hold the lease; readers switch to static mode.
+#[cfg(feature = "s3")]
+async fn open_snapshot_store(url: &str, args: &mut Args) -> Arc<snapshot_store::SharedSnapshotStore> {
+    if args.multi_tenant || args.load_static.is_some() || args.follow.is_some() {
+        error!("--snapshot-store cannot be combined with multi-tenant, static loading or follower mode");
+        std::process::exit(1);
+    }
+    let store = match snapshot_store::SharedSnapshotStore::from_url(url) {
+        Ok(store) => Arc::new(store),
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    
+    if args.snapshot_store_reader {
+        info!("Snapshot store reader: {}", url);
+    } else {
+        info!("Snapshot store writer: {} (lease holder {})", url, store.holder());
+        if let Err(e) = store.acquire().await {
+            error!("Failed to acquire the snapshot store lease: {}", e);
+            std::process::exit(1);
+        }
+        info!("Acquired the snapshot store writer lease");
+    }
+    
+    match store.download(Path::new(&args.data_dir)).await {
+        Ok(true) => info!("Downloaded the latest snapshot from the snapshot store"),
+        Ok(false) => info!("Snapshot store is empty, starting fresh"),
+        Err(e) => {
+            error!("Failed to download from the snapshot store: {}", e);
+            std::process::exit(1);
+        }
+    }
+    if args.snapshot_store_reader {
+        args.load_static = Some(args.data_dir.clone());
+    }
+    store
+}
+
+/// Log how a project's cue indexes agree with its memories, rebuilding them
+/// when asked.
+fn verify_project(project_id: &str, project: &ProjectContext, rebuild: bool) {
+    let report = project.verify_indexes(rebuild);
+    if report.is_consistent() {
+        info!("Project {}: cue indexes agree with memories", project_id);
+        return;
+    }
+    let problems = report.problems();
+    if report.rebuilt {
+        warn!("Project {}: rebuilt cue indexes with {} problems", project_id, problems.len());
+    } else {
+        warn!("Project {}: {} cue index problems; start with --rebuild-index to repair them", project_id, problems.len());
+    }
+    for problem in problems.iter().take(LOGGED_INDEX_PROBLEMS) {
+        warn!("  - {}", problem);
+    }
+    if problems.len() > LOGGED_INDEX_PROBLEMS {
+        warn!("  ... and {} more", problems.len() - LOGGED_INDEX_PROBLEMS);
+    }
+}
+
+/// Warm up a freshly loaded project and log that it is ready to serve.
+fn warm_up_project(project_id: &str, project: &ProjectContext, loaded_in: Duration) {
+    let started = Instant::now();
+    let cues = project.warm_up();
+    info!(
+        "Project {}: ready with {} memories and {} cues ({} postings, p50/p99/max {}/{}/{} per cue); loaded in {}ms, warmed up in {}ms",
+        project_id,
+        project.main.get_memories().len(),
+        cues.cues,
+        cues.postings,
+        cues.p50,
+        cues.p99,
+        cues.max,
+        loaded_in.as_millis(),
+        started.elapsed().as_millis(),
+    );
+}
 
+/// Copy config file values into `args` for every flag not given on the command line.
+fn apply_config_file(args: &mut Args, matches: &ArgMatches, file: &ConfigFile) {
+    macro_rules! fill {
+        ($field:ident, $value:expr) => {
+            if matches.value_source(stringify!($field)) != Some(ValueSource::CommandLine) {
+                if let Some(value) = $value.clone() {
+                    args.$field = value.into();
+                }
+            }
+        };
+    }
+
+    let server = &file.server;
+    fill!(port, server.port);
+    fill!(data_dir, server.data_dir);
+    fill!(snapshot_interval, server.snapshot_interval);
+    fill!(multi_tenant, server.multi_tenant);
+    fill!(load_static, server.load_static);
+    fill!(trace_retention, server.trace_retention);
+    fill!(trace_retention_days, server.trace_retention_days);
+    fill!(dashmap_shards, server.dashmap_shards);
+    fill!(expected_memories, server.expected_memories);
+    fill!(read_view_refresh_ms, server.read_view_refresh_ms);
+    fill!(recall_cache_ttl_ms, server.recall_cache_ttl_ms);
+    fill!(alias_max_depth, server.alias_max_depth);
+    fill!(approximate_early_exit, server.approximate_early_exit);
+    fill!(max_body_bytes, server.max_body_bytes);
+    fill!(max_content_bytes, server.max_content_bytes);
+    fill!(max_cues_per_memory, server.max_cues_per_memory);
+    fill!(max_recall_limit, server.max_recall_limit);
+    fill!(compression_min_bytes, server.compression_min_bytes);
+    fill!(consolidate_interval, server.consolidate_interval);
+    fill!(maintenance_interval, server.maintenance_interval);
+    fill!(stale_digest_interval, server.stale_digest_interval);
+    fill!(grpc_port, server.grpc_port);
+    fill!(shutdown_timeout, server.shutdown_timeout);
+    fill!(follow, server.follow);
+    fill!(follow_interval, server.follow_interval);
+    fill!(snapshot_store, server.snapshot_store);
+    fill!(snapshot_store_reader, server.snapshot_store_reader);
+    fill!(verify_on_start, server.verify_on_start);
+    fill!(rebuild_index, server.rebuild_index);
+    fill!(warm_up, server.warm_up);
+    fill!(shared_vocabulary, server.shared_vocabulary);
+    fill!(log_format, server.log_format);
+    fill!(tls_cert, file.tls.cert);
+    fill!(tls_key, file.tls.key);
+    fill!(tls_client_ca, file.tls.client_ca);
+    fill!(allow_cidr, file.network.allow_cidr);
+    fill!(deny_cidr, file.network.deny_cidr);
+    fill!(trusted_proxy, file.network.trusted_proxy);
+    fill!(agent_dir, file.agent.dir);
+    fill!(agent_throttle, file.agent.throttle_ms);
+    fill!(agent_reconcile_interval, file.agent.reconcile_interval);
+    fill!(agent_cue, file.agent.cues);
+    fill!(agent_path_cue, file.agent.path_cues);
+    fill!(agent_block_key, file.agent.blocked_keys);
+}
+
+/// Save every project snapshot in multi-tenant mode.
+fn save_all_projects(mt_engine: &multi_tenant::MultiTenantEngine) {
+    info!("Saving all projects...");
+    let save_results = mt_engine.save_all();
+    let saved = save_results.iter().filter(|(_, r)| r.is_ok()).count();
+    let failed = save_results.iter().filter(|(_, r)| r.is_err()).count();
+    
+    if saved > 0 {
+        info!("✓ Saved {} project snapshots", saved);
+    }
+    if failed > 0 {
+        warn!("✗ Failed to save {} projects", failed);
+        for (project_id, result) in save_results.iter() {
+            if let Err(e) = result {
+                warn!("  - {}: {}", project_id, e);
+            }
+        }
+    }
+}
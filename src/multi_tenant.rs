@@ -1,10 +1,10 @@
 //! Multi-tenant engine supporting project isolation.
 
 use crate::engine::CueMapEngine;
+use crate::hooks::Hooks;
 use crate::persistence::PersistenceManager;
-use crate::projects::ProjectContext;
-use crate::normalization::NormalizationConfig;
-use crate::taxonomy::Taxonomy;
+use crate::projects::{ProjectConfig, ProjectContext};
+use crate::shared_vocabulary::SharedVocabulary;
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -28,6 +28,10 @@ pub struct ProjectStats {
 pub struct MultiTenantEngine {
     projects: Arc<DashMap<ProjectId, Arc<ProjectContext>>>,
     snapshots_dir: PathBuf,
+    /// Given to every project created or loaded
+    hooks: Hooks,
+    /// Given to every project created or loaded, when enabled
+    shared_vocabulary: Option<Arc<SharedVocabulary>>,
 }
 
 impl MultiTenantEngine {
@@ -47,6 +51,31 @@ impl MultiTenantEngine {
         Self {
             projects: Arc::new(DashMap::new()),
             snapshots_dir,
+            hooks: Hooks::default(),
+            shared_vocabulary: None,
+        }
+    }
+    
+    /// Run `hooks` on the ingests and recalls of every project.
+    pub fn with_hooks(mut self, hooks: Hooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
+    
+    /// Let every project fall back to `shared` aliases and lexicon terms.
+    pub fn with_shared_vocabulary(mut self, shared: Arc<SharedVocabulary>) -> Self {
+        self.shared_vocabulary = Some(shared);
+        self
+    }
+    
+    /// Attach the config path, hooks and shared vocabulary of `project_id`.
+    fn configure(&self, project_id: &str, ctx: ProjectContext) -> ProjectContext {
+        let ctx = ctx
+            .with_config_path(self.config_path(project_id))
+            .with_hooks(self.hooks.clone());
+        match &self.shared_vocabulary {
+            Some(shared) => ctx.with_shared_vocabulary(shared.clone()),
+            None => ctx,
         }
     }
     
@@ -54,12 +83,10 @@ impl MultiTenantEngine {
         if let Some(ctx) = self.projects.get(&project_id) {
             ctx.clone()
         } else {
-            // Create new project with default config
-            // TODO: Load config from disk if available
-            let ctx = Arc::new(ProjectContext::new(
-                NormalizationConfig::default(),
-                Taxonomy::default(),
-            ));
+            // Create new project, restoring its config from disk if available
+            let config_path = self.config_path(&project_id);
+            let config = ProjectConfig::load_or_default(&config_path);
+            let ctx = Arc::new(self.configure(&project_id, ProjectContext::from_config(CueMapEngine::new(), config)));
             self.projects.insert(project_id, ctx.clone());
             ctx
         }
@@ -69,6 +96,21 @@ impl MultiTenantEngine {
         self.projects.get(project_id).map(|e| e.clone())
     }
     
+    /// Contexts of all loaded projects.
+    pub fn project_contexts(&self) -> Vec<Arc<ProjectContext>> {
+        self.projects.iter().map(|entry| entry.value().clone()).collect()
+    }
+
+    /// Ids of all loaded projects.
+    pub fn project_ids(&self) -> Vec<ProjectId> {
+        self.projects.iter().map(|entry| entry.key().clone()).collect()
+    }
+
+    /// Number of projects currently loaded.
+    pub fn project_count(&self) -> usize {
+        self.projects.len()
+    }
+    
     pub fn list_projects(&self) -> Vec<ProjectStats> {
         self.projects
             .iter()
@@ -108,6 +150,11 @@ impl MultiTenantEngine {
         self.projects.insert(project_id, ctx);
     }
     
+    /// Path of a project's persisted config (normalization, taxonomy, tokenizer)
+    fn config_path(&self, project_id: &str) -> PathBuf {
+        self.snapshots_dir.join(format!("{}.config.json", project_id))
+    }
+    
     /// Save a project snapshot to disk
     pub fn save_project(&self, project_id: &ProjectId) -> Result<PathBuf, String> {
         let ctx = self.get_project(project_id)
@@ -135,14 +182,9 @@ impl MultiTenantEngine {
         // Create context and populate main engine
         let main_engine = CueMapEngine::from_state(memories, cue_index);
         
-        let ctx = Arc::new(ProjectContext {
-            main: main_engine,
-            aliases: CueMapEngine::new(),
-            lexicon: CueMapEngine::new(),
-            query_cache: DashMap::new(),
-            normalization: NormalizationConfig::default(),
-            taxonomy: Taxonomy::default(),
-        });
+        let config_path = self.config_path(project_id);
+        let config = ProjectConfig::load_or_default(&config_path);
+        let ctx = Arc::new(self.configure(project_id, ProjectContext::from_config(main_engine, config)));
         
         self.projects.insert(project_id.clone(), ctx.clone());
         
@@ -1,53 +1,342 @@
-use regex::Regex;
-use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::OnceLock;
 
 // Simple stopword list
-static STOPWORDS: OnceLock<HashSet<&'static str>> = OnceLock::new();
-static TOKEN_REGEX: OnceLock<Regex> = OnceLock::new();
+const DEFAULT_STOPWORDS: [&str; 16] = [
+    "the", "is", "at", "which", "on", "in", "a", "an", "and", "or", "for", "to", "of", "it", "this", "that",
+];
 
-fn get_stopwords() -> &'static HashSet<&'static str> {
-    STOPWORDS.get_or_init(|| {
-        ["the", "is", "at", "which", "on", "in", "a", "an", "and", "or", "for", "to", "of", "it", "this", "that"].into_iter().collect()
-    })
+static DEFAULT_TOKENIZER: OnceLock<TokenizerConfig> = OnceLock::new();
+
+/// Per-project tokenizer settings used for lexicon training and query resolution.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TokenizerConfig {
+    #[serde(default = "default_stopwords")]
+    pub stopwords: BTreeSet<String>,
+    /// Tokens shorter than this many characters are dropped.
+    #[serde(default = "default_min_token_len")]
+    pub min_token_len: usize,
+    /// Emit `phr:a_b` cues for adjacent token pairs.
+    #[serde(default = "default_bigrams")]
+    pub bigrams: bool,
+    /// How memory content is turned into lexicon training tokens.
+    #[serde(default)]
+    pub mode: TokenizerMode,
+    /// Number of keywords kept per memory in `keywords` mode.
+    #[serde(default = "default_max_keywords")]
+    pub max_keywords: usize,
+    /// Extra stopwords per detected language (ISO 639-1 code), applied on top of `stopwords`.
+    #[serde(default = "default_language_stopwords")]
+    pub language_stopwords: BTreeMap<String, BTreeSet<String>>,
+    /// Attach a `lang:xx` cue to ingested memories when the language is detected reliably.
+    #[serde(default = "default_language_cue")]
+    pub language_cue: bool,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenizerMode {
+    /// Train the lexicon on every token and bigram.
+    #[default]
+    Exhaustive,
+    /// Train the lexicon on the top `max_keywords` TF-IDF scored terms only.
+    Keywords,
+}
+
+fn default_stopwords() -> BTreeSet<String> {
+    DEFAULT_STOPWORDS.iter().map(|w| w.to_string()).collect()
+}
+
+fn default_min_token_len() -> usize {
+    2
+}
+
+fn default_bigrams() -> bool {
+    true
 }
 
-fn get_token_regex() -> &'static Regex {
-    TOKEN_REGEX.get_or_init(|| {
-        Regex::new(r"[a-z0-9]+").unwrap()
-    })
+fn default_max_keywords() -> usize {
+    20
+}
+
+fn default_language_stopwords() -> BTreeMap<String, BTreeSet<String>> {
+    let lists: [(&str, &[&str]); 3] = [
+        ("de", &["der", "die", "das", "und", "ist", "ein", "eine", "zu", "mit", "von", "den", "dem", "des", "im", "nicht", "auf", "für", "sich"]),
+        ("fr", &["le", "la", "les", "de", "des", "du", "un", "une", "et", "est", "en", "que", "qui", "pour", "dans", "sur", "pas"]),
+        ("es", &["el", "la", "los", "las", "de", "del", "y", "en", "que", "un", "una", "es", "por", "con", "para", "se", "no"]),
+    ];
+    lists
+        .iter()
+        .map(|(lang, words)| (lang.to_string(), words.iter().map(|w| w.to_string()).collect()))
+        .collect()
+}
+
+fn default_language_cue() -> bool {
+    true
+}
+
+impl Default for TokenizerConfig {
+    fn default() -> Self {
+        Self {
+            stopwords: default_stopwords(),
+            min_token_len: default_min_token_len(),
+            bigrams: default_bigrams(),
+            mode: TokenizerMode::default(),
+            max_keywords: default_max_keywords(),
+            language_stopwords: default_language_stopwords(),
+            language_cue: default_language_cue(),
+        }
+    }
 }
 
+impl TokenizerConfig {
+    /// Lowercase and trim stopwords so they match normalized tokens.
+    pub fn normalized(mut self) -> Self {
+        fn clean(words: BTreeSet<String>) -> BTreeSet<String> {
+            words
+                .into_iter()
+                .map(|w| w.trim().to_lowercase())
+                .filter(|w| !w.is_empty())
+                .collect()
+        }
+        self.stopwords = clean(self.stopwords);
+        self.language_stopwords = self.language_stopwords
+            .into_iter()
+            .map(|(lang, words)| (lang.trim().to_lowercase(), clean(words)))
+            .collect();
+        self
+    }
+
+    pub(crate) fn is_stopword(&self, token: &str, lang: Option<&str>) -> bool {
+        self.stopwords.contains(token)
+            || lang
+                .and_then(|l| self.language_stopwords.get(l))
+                .is_some_and(|words| words.contains(token))
+    }
+}
+
+/// Detect the language of `text`, returning its ISO 639-1 code (or the ISO 639-3 code
+/// for languages without one). Returns `None` unless the detection is reliable.
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    use whatlang::Lang;
+
+    let info = whatlang::detect(text)?;
+    if !info.is_reliable() {
+        return None;
+    }
+    let code = match info.lang() {
+        Lang::Epo => "eo", Lang::Eng => "en", Lang::Rus => "ru", Lang::Cmn => "zh",
+        Lang::Spa => "es", Lang::Por => "pt", Lang::Ita => "it", Lang::Ben => "bn",
+        Lang::Fra => "fr", Lang::Deu => "de", Lang::Ukr => "uk", Lang::Kat => "ka",
+        Lang::Ara => "ar", Lang::Hin => "hi", Lang::Jpn => "ja", Lang::Heb => "he",
+        Lang::Yid => "yi", Lang::Pol => "pl", Lang::Amh => "am", Lang::Jav => "jv",
+        Lang::Kor => "ko", Lang::Nob => "nb", Lang::Dan => "da", Lang::Swe => "sv",
+        Lang::Fin => "fi", Lang::Tur => "tr", Lang::Nld => "nl", Lang::Hun => "hu",
+        Lang::Ces => "cs", Lang::Ell => "el", Lang::Bul => "bg", Lang::Bel => "be",
+        Lang::Mar => "mr", Lang::Kan => "kn", Lang::Ron => "ro", Lang::Slv => "sl",
+        Lang::Hrv => "hr", Lang::Srp => "sr", Lang::Mkd => "mk", Lang::Lit => "lt",
+        Lang::Lav => "lv", Lang::Est => "et", Lang::Tam => "ta", Lang::Vie => "vi",
+        Lang::Urd => "ur", Lang::Tha => "th", Lang::Guj => "gu", Lang::Uzb => "uz",
+        Lang::Pan => "pa", Lang::Aze => "az", Lang::Ind => "id", Lang::Tel => "te",
+        Lang::Pes => "fa", Lang::Mal => "ml", Lang::Ori => "or", Lang::Mya => "my",
+        Lang::Nep => "ne", Lang::Sin => "si", Lang::Khm => "km", Lang::Tuk => "tk",
+        Lang::Aka => "ak", Lang::Zul => "zu", Lang::Sna => "sn", Lang::Afr => "af",
+        Lang::Lat => "la", Lang::Slk => "sk", Lang::Cat => "ca", Lang::Tgl => "tl",
+        Lang::Hye => "hy",
+        #[allow(unreachable_patterns)]
+        other => other.code(),
+    };
+    Some(code)
+}
+
+/// Whether a character belongs to a script written without spaces between words
+/// (Han, Hiragana, Katakana) or one better matched by n-grams than whole words (Hangul).
+pub fn is_cjk(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x3040..=0x30FF         // Hiragana, Katakana
+            | 0x31F0..=0x31FF   // Katakana phonetic extensions
+            | 0x3400..=0x4DBF   // CJK Extension A
+            | 0x4E00..=0x9FFF   // CJK Unified Ideographs
+            | 0xF900..=0xFAFF   // CJK Compatibility Ideographs
+            | 0x1100..=0x11FF   // Hangul Jamo
+            | 0x3130..=0x318F   // Hangul Compatibility Jamo
+            | 0xAC00..=0xD7AF   // Hangul Syllables
+            | 0xFF66..=0xFF9F   // Half-width Katakana
+            | 0x20000..=0x2FA1F // CJK Extensions B-F, Compatibility Supplement
+    )
+}
+
+/// Lowercase, replace punctuation with spaces, and split CJK runs from adjacent
+/// non-CJK text (e.g. `API接口` -> `api 接口`).
 pub fn normalize_text(text: &str) -> String {
-    text.to_lowercase()
-        .chars()
-        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
-        .collect::<String>()
-        .split_whitespace()
-        .collect::<Vec<_>>()
-        .join(" ")
+    let mut out = String::with_capacity(text.len());
+    let mut prev_cjk: Option<bool> = None;
+
+    for c in text.to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            let cjk = is_cjk(c);
+            if prev_cjk.is_some_and(|prev| prev != cjk) {
+                out.push(' ');
+            }
+            out.push(c);
+            prev_cjk = Some(cjk);
+        } else {
+            out.push(' ');
+            prev_cjk = None;
+        }
+    }
+
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Segment a run of CJK characters into tokens.
+/// Han text uses the jieba segmenter when built with the `jieba` feature;
+/// otherwise (and for Kana/Hangul) overlapping character bigrams are used.
+fn segment_cjk(run: &str) -> Vec<String> {
+    #[cfg(feature = "jieba")]
+    {
+        if run.chars().all(|c| matches!(c as u32, 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x2FA1F)) {
+            return jieba()
+                .cut(run, false)
+                .into_iter()
+                .map(|w| w.trim().to_string())
+                .filter(|w| !w.is_empty())
+                .collect();
+        }
+    }
+
+    let chars: Vec<char> = run.chars().collect();
+    if chars.len() < 2 {
+        return vec![run.to_string()];
+    }
+    chars.windows(2).map(|pair| pair.iter().collect()).collect()
+}
+
+#[cfg(feature = "jieba")]
+fn jieba() -> &'static jieba_rs::Jieba {
+    static JIEBA: OnceLock<jieba_rs::Jieba> = OnceLock::new();
+    JIEBA.get_or_init(jieba_rs::Jieba::new)
 }
 
 pub fn tokenize_to_cues(text: &str) -> Vec<String> {
+    tokenize_to_cues_with(text, DEFAULT_TOKENIZER.get_or_init(TokenizerConfig::default))
+}
+
+pub fn tokenize_to_cues_with(text: &str, config: &TokenizerConfig) -> Vec<String> {
+    tokenize_to_cues_for_language(text, config, None)
+}
+
+/// Tokenize with the stopword list for `lang` (an ISO 639-1 code) applied on top of the base list.
+pub fn tokenize_to_cues_for_language(text: &str, config: &TokenizerConfig, lang: Option<&str>) -> Vec<String> {
     let normalized = normalize_text(text);
     let mut cues = Vec::new();
-    let mut tokens = Vec::new();
+    let mut phrases = Vec::new();
+    let mut prev_token: Option<String> = None;
     
     // Extract tokens
-    for token in get_token_regex().find_iter(&normalized) {
-        let t = token.as_str();
-        if !get_stopwords().contains(t) && t.len() > 1 {
-            tokens.push(t.to_string());
-            cues.push(format!("tok:{}", t));
+    for word in normalized.split_whitespace() {
+        if word.chars().next().is_some_and(is_cjk) {
+            // CJK runs are segmented; segments don't form phrases with neighbouring words
+            for segment in segment_cjk(word) {
+                if !config.is_stopword(&segment, lang) {
+                    cues.push(format!("tok:{}", segment));
+                }
+            }
+            prev_token = None;
+            continue;
         }
-    }
-    
-    // Extract bigrams (phrases)
-    if tokens.len() >= 2 {
-        for windows in tokens.windows(2) {
-            cues.push(format!("phr:{}_{}", windows[0], windows[1]));
+
+        if !config.is_stopword(word, lang) && word.chars().count() >= config.min_token_len {
+            cues.push(format!("tok:{}", word));
+
+            // Extract bigrams (phrases)
+            if config.bigrams {
+                if let Some(prev) = &prev_token {
+                    phrases.push(format!("phr:{}_{}", prev, word));
+                }
+            }
+            prev_token = Some(word.to_string());
         }
     }
     
+    cues.extend(phrases);
     cues
 }
+
+const PHRASE_BOOST: f64 = 1.5;
+
+/// Keep the `max_keywords` most salient cues of a text, scored by TF-IDF.
+///
+/// `doc_freq` returns how many previously seen documents contained a cue, out of
+/// `total_docs`. Repeated phrases are boosted over their single tokens since they
+/// are more specific. Ties keep first-occurrence order.
+pub fn extract_keywords(
+    text: &str,
+    config: &TokenizerConfig,
+    lang: Option<&str>,
+    doc_freq: impl Fn(&str) -> u64,
+    total_docs: u64,
+) -> Vec<String> {
+    let cues = tokenize_to_cues_for_language(text, config, lang);
+
+    // Term frequency, in first-occurrence order
+    let mut terms: Vec<(String, f64)> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for cue in cues {
+        match positions.get(&cue) {
+            Some(&i) => terms[i].1 += 1.0,
+            None => {
+                positions.insert(cue.clone(), terms.len());
+                terms.push((cue, 1.0));
+            }
+        }
+    }
+
+    let idf = |cue: &str| ((total_docs as f64 + 1.0) / (doc_freq(cue) as f64 + 1.0)).ln() + 1.0;
+    let mut scored: Vec<(usize, f64)> = terms
+        .iter()
+        .enumerate()
+        .map(|(i, (cue, tf))| {
+            let boost = if cue.starts_with("phr:") { PHRASE_BOOST } else { 1.0 };
+            (i, tf * idf(cue) * boost)
+        })
+        .collect();
+
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal).then(a.0.cmp(&b.0)));
+    scored.truncate(config.max_keywords);
+    scored.sort_unstable_by_key(|(i, _)| *i);
+
+    scored.into_iter().map(|(i, _)| terms[i].0.clone()).collect()
+}
+
+/// Edit distance between `a` and `b` counting insertions, deletions,
+/// substitutions and adjacent transpositions (optimal string alignment).
+/// `None` once the distance is known to exceed `max`.
+pub fn edit_distance(a: &str, b: &str, max: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+
+    // Three rolling rows: two back (for transpositions), previous and current
+    let mut before: Vec<usize> = Vec::new();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut current = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1).min(current[j - 1] + 1).min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+        if current.iter().min().is_some_and(|&best| best > max) {
+            return None;
+        }
+        before = std::mem::replace(&mut previous, current);
+    }
+    Some(previous[b.len()]).filter(|&distance| distance <= max)
+}
@@ -1,4 +1,5 @@
 use regex::Regex;
+use unicode_normalization::UnicodeNormalization;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -8,12 +9,64 @@ pub struct RewriteRule {
     pub replace: String,
 }
 
+impl RewriteRule {
+    /// Whether `pattern` is a valid regex.
+    pub fn is_valid(&self) -> bool {
+        Regex::new(&self.pattern).is_ok()
+    }
+}
+
+/// Regexes of a config's rewrite rules, with the patterns they were built
+/// from. Opaque; built by [`NormalizationConfig::compile`].
+#[derive(Clone, Debug, Default)]
+pub struct CompiledRules {
+    patterns: Vec<String>,
+    /// `None` for a pattern that is not a valid regex
+    regexes: Vec<Option<Regex>>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct NormalizationConfig {
     pub lowercase: bool,
     pub trim: bool,
     #[serde(default)]
     pub rewrite_rules: Vec<RewriteRule>,
+    /// Apply Unicode NFKC normalization (e.g. full-width and ligature forms fold to their canonical equivalents).
+    #[serde(default)]
+    pub unicode_nfkc: bool,
+    /// Transliterate non-ASCII letters to ASCII (e.g. `café:menü` -> `cafe:menu`).
+    /// Characters without a single-word ASCII form (such as CJK ideographs) are kept as-is.
+    #[serde(default)]
+    pub transliterate: bool,
+    /// Also index composite cues (`deploy:region=eu,env=prod`) as the atomic
+    /// cues their values carry (`region:eu`, `env:prod`), at ingest.
+    #[serde(default = "default_composite_cues")]
+    pub composite_cues: bool,
+    /// Built by [`compile`](Self::compile); never serialized. Leave it at its
+    /// default when building a config.
+    #[serde(skip)]
+    pub compiled: CompiledRules,
+}
+
+fn default_composite_cues() -> bool {
+    true
+}
+
+impl NormalizationConfig {
+    /// Compile the rewrite rules once, so `normalize_cue` doesn't for every
+    /// cue. Projects call it whenever their config is set; a config whose
+    /// rules changed since is still applied correctly, just without the cache.
+    pub fn compile(&mut self) {
+        let patterns: Vec<String> = self.rewrite_rules.iter().map(|rule| rule.pattern.clone()).collect();
+        let regexes = patterns.iter().map(|pattern| Regex::new(pattern).ok()).collect();
+        self.compiled = CompiledRules { patterns, regexes };
+    }
+
+    /// Cached regexes, unless the rules changed since they were compiled.
+    fn compiled_regexes(&self) -> Option<&[Option<Regex>]> {
+        let current = self.rewrite_rules.iter().map(|rule| &rule.pattern);
+        current.eq(self.compiled.patterns.iter()).then_some(self.compiled.regexes.as_slice())
+    }
 }
 
 impl Default for NormalizationConfig {
@@ -22,6 +75,10 @@ impl Default for NormalizationConfig {
             lowercase: true,
             trim: true,
             rewrite_rules: Vec::new(),
+            unicode_nfkc: false,
+            transliterate: false,
+            composite_cues: default_composite_cues(),
+            compiled: CompiledRules::default(),
         }
     }
 }
@@ -31,10 +88,50 @@ pub struct NormalizeTrace {
     pub raw: String,
     pub normalized: String,
     pub applied_rules: Vec<String>,
+    /// Normalized atomic cues of a composite cue, indexed alongside it
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub derived: Vec<String>,
+}
+
+/// The `key:value` cues a composite cue carries: `deploy:region=eu,env=prod`
+/// carries `region:eu` and `env:prod`. `None` unless every comma-separated
+/// part of the value is a `key=value` pair with a plain key, so values that
+/// merely contain `=` (URLs, expressions) are left alone.
+pub fn composite_parts(cue: &str) -> Option<Vec<String>> {
+    let (key, value) = cue.split_once(':')?;
+    if key.is_empty() || !value.contains('=') {
+        return None;
+    }
+    value
+        .split(',')
+        .map(|part| {
+            let (key, value) = part.split_once('=')?;
+            let (key, value) = (key.trim(), value.trim());
+            let plain_key = !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'));
+            (plain_key && !value.is_empty() && !value.contains('=')).then(|| format!("{}:{}", key, value))
+        })
+        .collect()
+}
+
+/// Transliterate non-ASCII characters to ASCII, keeping characters whose
+/// transliteration is not a single word (e.g. CJK ideographs map to syllables).
+fn transliterate(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii() {
+            out.push(c);
+            continue;
+        }
+        match deunicode::deunicode_char(c) {
+            Some(ascii) if !ascii.is_empty() && !ascii.contains(char::is_whitespace) => out.push_str(ascii),
+            _ => out.push(c),
+        }
+    }
+    out
 }
 
 /// Normalizes a cue string based on the provided configuration.
-/// Applies trimming, lowercasing, and rewrite rules sequentially.
+/// Applies trimming, NFKC, lowercasing, transliteration, and rewrite rules sequentially.
 pub fn normalize_cue(raw: &str, config: &NormalizationConfig) -> (String, NormalizeTrace) {
     let mut current = raw.to_string();
     let mut applied_rules = Vec::new();
@@ -44,25 +141,52 @@ pub fn normalize_cue(raw: &str, config: &NormalizationConfig) -> (String, Normal
         current = current.trim().to_string();
     }
 
-    // 2. Lowercase
+    // 2. Unicode NFKC
+    if config.unicode_nfkc && !current.is_ascii() {
+        let folded: String = current.nfkc().collect();
+        if folded != current {
+            current = folded;
+            applied_rules.push("nfkc".to_string());
+        }
+    }
+
+    // 3. Lowercase
     if config.lowercase {
         current = current.to_lowercase();
     }
 
-    // 3. Rewrite Rules
-    for rule in &config.rewrite_rules {
-        if let Ok(re) = Regex::new(&rule.pattern) {
-            if re.is_match(&current) {
-                let new_val = re.replace_all(&current, &rule.replace).to_string();
-                if new_val != current {
-                    current = new_val;
-                    applied_rules.push(rule.name.clone());
-                }
+    // 4. Transliteration
+    if config.transliterate && !current.is_ascii() {
+        let transliterated = transliterate(&current);
+        if transliterated != current {
+            current = transliterated;
+            applied_rules.push("transliterate".to_string());
+        }
+    }
+
+    // 5. Rewrite Rules
+    // Compiled once per config; a config never compiled builds each regex here
+    let compiled = config.compiled_regexes();
+    for (i, rule) in config.rewrite_rules.iter().enumerate() {
+        let uncached;
+        let re = match compiled {
+            Some(regexes) => regexes[i].as_ref(),
+            None => {
+                uncached = Regex::new(&rule.pattern).ok();
+                uncached.as_ref()
+            }
+        };
+        let Some(re) = re else { continue };
+        if re.is_match(&current) {
+            let new_val = re.replace_all(&current, &rule.replace).to_string();
+            if new_val != current {
+                current = new_val;
+                applied_rules.push(rule.name.clone());
             }
         }
     }
 
-    // 4. Fix Duplicated Prefixes (e.g., "key:value:value" -> "key:value")
+    // 6. Fix Duplicated Prefixes (e.g., "key:value:value" -> "key:value")
     // This handles cases where rewrite rules or LLM output might accidentally double-prefix
     let parts: Vec<&str> = current.split(':').collect();
     if parts.len() >= 3 && parts[1] == parts[2] && !parts[1].is_empty() {
@@ -74,12 +198,19 @@ pub fn normalize_cue(raw: &str, config: &NormalizationConfig) -> (String, Normal
         applied_rules.push("dedupe_prefix".to_string());
     }
 
+    // 7. Composite cues carry atomic ones, normalized like any other cue
+    let derived = match config.composite_cues.then(|| composite_parts(&current)).flatten() {
+        Some(parts) => parts.iter().map(|part| normalize_cue(part, config).0).collect(),
+        None => Vec::new(),
+    };
+
     (
         current.clone(),
         NormalizeTrace {
             raw: raw.to_string(),
             normalized: current,
             applied_rules,
+            derived,
         },
     )
 }
@@ -1,45 +1,601 @@
 //! Persistence layer with bincode serialization and background snapshots.
 
+use crate::config::{engine_sizing, DATA_DIR_LOCK_HEARTBEAT_SECS, DATA_DIR_LOCK_STALE_SECS};
 use crate::engine::CueMapEngine;
-use crate::structures::{Memory, OrderedSet};
+use crate::structures::Memory;
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::time::interval;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
+/// Advisory lock file in the data directory, naming the process that snapshots it
+pub const LOCK_FILE: &str = "cuemap.lock";
+
+/// Snapshot layout. Each distinct cue string is stored once in `cues`;
+/// memories and the cue index refer to it by position.
 #[derive(Debug, Serialize, Deserialize)]
-struct PersistedState {
-    memories: HashMap<String, Memory>,
-    cue_index: HashMap<String, Vec<String>>, // Flattened OrderedSet
+struct PersistedState<M = PersistedMemory> {
     version: u32,
     saved_at: u64,
+    cues: Vec<Arc<str>>,
+    memories: Vec<M>,
+    cue_index: Vec<(u32, Vec<String>)>, // Flattened OrderedSet per cue slot
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedMemory {
+    id: String,
+    content: String,
+    created_at: f64,
+    last_accessed: f64,
+    reinforcement_count: u64,
+    salience: f64,
+    cues: Vec<u32>,
+    metadata: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    collection: Option<String>,
+    #[serde(default)]
+    locked: bool,
+    #[serde(default)]
+    full_content: Option<String>,
+}
+
+/// Version 4 memories, from before content limits. Still loaded.
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedMemoryV4 {
+    id: String,
+    content: String,
+    created_at: f64,
+    last_accessed: f64,
+    reinforcement_count: u64,
+    salience: f64,
+    cues: Vec<u32>,
+    metadata: HashMap<String, serde_json::Value>,
+    collection: Option<String>,
+    locked: bool,
+}
+
+impl From<PersistedMemoryV4> for PersistedMemory {
+    fn from(memory: PersistedMemoryV4) -> Self {
+        Self {
+            id: memory.id,
+            content: memory.content,
+            created_at: memory.created_at,
+            last_accessed: memory.last_accessed,
+            reinforcement_count: memory.reinforcement_count,
+            salience: memory.salience,
+            cues: memory.cues,
+            metadata: memory.metadata,
+            collection: memory.collection,
+            locked: memory.locked,
+            full_content: None,
+        }
+    }
+}
+
+/// Version 3 memories, from before locking. Still loaded.
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedMemoryV3 {
+    id: String,
+    content: String,
+    created_at: f64,
+    last_accessed: f64,
+    reinforcement_count: u64,
+    salience: f64,
+    cues: Vec<u32>,
+    metadata: HashMap<String, serde_json::Value>,
+    collection: Option<String>,
+}
+
+impl From<PersistedMemoryV3> for PersistedMemory {
+    fn from(memory: PersistedMemoryV3) -> Self {
+        Self {
+            id: memory.id,
+            content: memory.content,
+            created_at: memory.created_at,
+            last_accessed: memory.last_accessed,
+            reinforcement_count: memory.reinforcement_count,
+            salience: memory.salience,
+            cues: memory.cues,
+            metadata: memory.metadata,
+            collection: memory.collection,
+            locked: false,
+            full_content: None,
+        }
+    }
+}
+
+/// Version 2 memories, from before collections. Still loaded.
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedMemoryV2 {
+    id: String,
+    content: String,
+    created_at: f64,
+    last_accessed: f64,
+    reinforcement_count: u64,
+    salience: f64,
+    cues: Vec<u32>,
+    metadata: HashMap<String, serde_json::Value>,
+}
+
+impl From<PersistedMemoryV2> for PersistedMemory {
+    fn from(memory: PersistedMemoryV2) -> Self {
+        Self {
+            id: memory.id,
+            content: memory.content,
+            created_at: memory.created_at,
+            last_accessed: memory.last_accessed,
+            reinforcement_count: memory.reinforcement_count,
+            salience: memory.salience,
+            cues: memory.cues,
+            metadata: memory.metadata,
+            collection: None,
+            locked: false,
+            full_content: None,
+        }
+    }
+}
+
+/// Version 1 layout, which repeated every cue string per memory. Still loaded.
+#[derive(Debug, Serialize, Deserialize)]
+struct LegacyState {
+    memories: HashMap<String, LegacyMemory>,
+    cue_index: HashMap<String, Vec<String>>,
+    version: u32,
+    saved_at: u64,
+}
+
+/// `Memory` as version 1 snapshots stored it.
+#[derive(Debug, Serialize, Deserialize)]
+struct LegacyMemory {
+    id: String,
+    content: String,
+    created_at: f64,
+    last_accessed: f64,
+    reinforcement_count: u64,
+    salience: f64,
+    cues: Vec<Arc<str>>,
+    metadata: HashMap<String, serde_json::Value>,
+}
+
+impl From<LegacyMemory> for Memory {
+    fn from(memory: LegacyMemory) -> Self {
+        Self {
+            id: memory.id,
+            content: memory.content,
+            created_at: memory.created_at,
+            last_accessed: memory.last_accessed,
+            reinforcement_count: memory.reinforcement_count,
+            salience: memory.salience,
+            cues: memory.cues,
+            metadata: memory.metadata,
+            collection: None,
+            locked: false,
+            full_content: None,
+        }
+    }
+}
+
+const PERSISTENCE_VERSION: u32 = 5;
+
+/// Leads every snapshot since version 5. Version 1 files start with the
+/// memory count instead, which never looks like this.
+const SNAPSHOT_MAGIC: &[u8; 8] = b"CUEMAP\0\x05";
+
+/// Leads version 4 snapshots, whose memories have no full content.
+const SNAPSHOT_MAGIC_V4: &[u8; 8] = b"CUEMAP\0\x04";
+
+/// Leads version 3 snapshots, whose memories cannot be locked.
+const SNAPSHOT_MAGIC_V3: &[u8; 8] = b"CUEMAP\0\x03";
+
+/// Leads version 2 snapshots, whose memories have no collection.
+const SNAPSHOT_MAGIC_V2: &[u8; 8] = b"CUEMAP\0\x02";
+
+/// Memories and per-cue memory ids (most recent first) read from a snapshot.
+pub type SnapshotContents = (DashMap<String, Memory>, DashMap<String, Vec<String>>);
+
+/// Assigns cue table slots in first-seen order.
+#[derive(Default)]
+struct CueTable {
+    slots: HashMap<Arc<str>, u32>,
+    cues: Vec<Arc<str>>,
+}
+
+impl CueTable {
+    fn slot(&mut self, cue: &Arc<str>) -> u32 {
+        if let Some(slot) = self.slots.get(cue) {
+            return *slot;
+        }
+        let slot = self.cues.len() as u32;
+        self.slots.insert(cue.clone(), slot);
+        self.cues.push(cue.clone());
+        slot
+    }
+}
+
+/// An engine's memories and cue index in the snapshot layout.
+fn persisted_state(engine: &CueMapEngine) -> PersistedState {
+    let ids = engine.get_id_table();
+    let mut table = CueTable::default();
+
+    let memories: Vec<PersistedMemory> = engine
+        .get_memories()
+        .iter()
+        .map(|entry| {
+            let memory = entry.value();
+            PersistedMemory {
+                id: entry.key().clone(),
+                content: memory.content.clone(),
+                created_at: memory.created_at,
+                last_accessed: memory.last_accessed,
+                reinforcement_count: memory.reinforcement_count,
+                salience: memory.salience,
+                cues: memory.cues.iter().map(|cue| table.slot(cue)).collect(),
+                metadata: memory.metadata.clone(),
+                collection: memory.collection.clone(),
+                locked: memory.locked,
+                full_content: memory.full_content.clone(),
+            }
+        })
+        .collect();
+
+    let cue_index: Vec<(u32, Vec<String>)> = engine
+        .get_cue_index()
+        .iter()
+        .map(|entry| (table.slot(entry.key()), ids.resolve_all(&entry.value().get_recent(None))))
+        .collect();
+
+    PersistedState {
+        version: PERSISTENCE_VERSION,
+        saved_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        cues: table.cues,
+        memories,
+        cue_index,
+    }
+}
+
+/// Serialize an engine's memories and cue index.
+/// Returns the bytes with the memory and cue counts.
+fn encode_snapshot(engine: &CueMapEngine) -> Result<(Vec<u8>, usize, usize), Box<dyn std::error::Error>> {
+    let state = persisted_state(engine);
+    let mut data = SNAPSHOT_MAGIC.to_vec();
+    bincode::serialize_into(&mut data, &state)?;
+    Ok((data, state.memories.len(), state.cue_index.len()))
+}
+
+/// Deserialize a snapshot of any layout.
+fn decode_snapshot(data: &[u8]) -> Result<SnapshotContents, Box<dyn std::error::Error>> {
+    if let Some(body) = data.strip_prefix(SNAPSHOT_MAGIC_V4.as_slice()) {
+        let state: PersistedState<PersistedMemoryV4> = bincode::deserialize(body)?;
+        log_loaded(state.memories.len(), state.cue_index.len(), state.version, state.saved_at);
+        return restore_state(upgrade_state(state));
+    }
+    if let Some(body) = data.strip_prefix(SNAPSHOT_MAGIC_V3.as_slice()) {
+        let state: PersistedState<PersistedMemoryV3> = bincode::deserialize(body)?;
+        log_loaded(state.memories.len(), state.cue_index.len(), state.version, state.saved_at);
+        return restore_state(upgrade_state(state));
+    }
+    if let Some(body) = data.strip_prefix(SNAPSHOT_MAGIC_V2.as_slice()) {
+        let state: PersistedState<PersistedMemoryV2> = bincode::deserialize(body)?;
+        log_loaded(state.memories.len(), state.cue_index.len(), state.version, state.saved_at);
+        return restore_state(upgrade_state(state));
+    }
+    let Some(body) = data.strip_prefix(SNAPSHOT_MAGIC.as_slice()) else {
+        let state: LegacyState = bincode::deserialize(data)?;
+        log_loaded(state.memories.len(), state.cue_index.len(), state.version, state.saved_at);
+        let memories = engine_sizing().dashmap(state.memories.len());
+        for (id, memory) in state.memories {
+            memories.insert(id, memory.into());
+        }
+        return Ok((memories, state.cue_index.into_iter().collect()));
+    };
+
+    let state: PersistedState = bincode::deserialize(body)?;
+    log_loaded(state.memories.len(), state.cue_index.len(), state.version, state.saved_at);
+    restore_state(state)
 }
 
-const PERSISTENCE_VERSION: u32 = 1;
+/// An older snapshot-layout state with its memories in the current layout.
+fn upgrade_state<M: Into<PersistedMemory>>(state: PersistedState<M>) -> PersistedState {
+    PersistedState {
+        version: state.version,
+        saved_at: state.saved_at,
+        cues: state.cues,
+        memories: state.memories.into_iter().map(Into::into).collect(),
+        cue_index: state.cue_index,
+    }
+}
+
+fn log_loaded(memories: usize, cues: usize, version: u32, saved_at: u64) {
+    info!(
+        "Loaded {} memories and {} cues from snapshot (version: {}, saved: {})",
+        memories, cues, version, saved_at
+    );
+}
+
+/// Memories and cue index from a snapshot-layout state.
+fn restore_state(state: PersistedState) -> Result<SnapshotContents, Box<dyn std::error::Error>> {
+    let cue = |slot: u32| {
+        state
+            .cues
+            .get(slot as usize)
+            .cloned()
+            .ok_or_else(|| format!("Snapshot refers to missing cue slot {}", slot))
+    };
+
+    let memories = engine_sizing().dashmap(state.memories.len());
+    for persisted in &state.memories {
+        let memory = Memory {
+            id: persisted.id.clone(),
+            content: persisted.content.clone(),
+            created_at: persisted.created_at,
+            last_accessed: persisted.last_accessed,
+            reinforcement_count: persisted.reinforcement_count,
+            salience: persisted.salience,
+            cues: persisted.cues.iter().map(|slot| cue(*slot)).collect::<Result<_, _>>()?,
+            metadata: persisted.metadata.clone(),
+            collection: persisted.collection.clone(),
+            locked: persisted.locked,
+            full_content: persisted.full_content.clone(),
+        };
+        memories.insert(persisted.id.clone(), memory);
+    }
+
+    let cue_index = DashMap::with_capacity(state.cue_index.len());
+    for (slot, memory_ids) in &state.cue_index {
+        cue_index.insert(cue(*slot)?.to_string(), memory_ids.clone());
+    }
+
+    Ok((memories, cue_index))
+}
+
+/// An engine's memories and cue index in the snapshot layout, for shipping
+/// to followers as JSON (which, unlike bincode, round-trips memory metadata).
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ReplicaState(PersistedState);
+
+impl ReplicaState {
+    pub fn capture(engine: &CueMapEngine) -> Self {
+        Self(persisted_state(engine))
+    }
+
+    pub fn memory_count(&self) -> usize {
+        self.0.memories.len()
+    }
+
+    /// Memories and cue index, ready for `CueMapEngine::replace_state`.
+    pub fn into_contents(self) -> Result<SnapshotContents, Box<dyn std::error::Error>> {
+        restore_state(self.0)
+    }
+}
+
+/// Contents of [`LOCK_FILE`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockInfo {
+    pub pid: u32,
+    /// Tells apart two holders in the same process
+    pub token: String,
+    pub acquired_at: f64,
+    /// Refreshed every `DATA_DIR_LOCK_HEARTBEAT_SECS` while held
+    pub heartbeat: f64,
+}
+
+impl LockInfo {
+    fn read(path: &Path) -> Option<Self> {
+        serde_json::from_slice(&fs::read(path).ok()?).ok()
+    }
+
+    /// Replace `path` with this info. The temp file is per holder, so two
+    /// processes writing at once never rename each other's bytes.
+    fn write(&self, path: &Path) -> std::io::Result<()> {
+        let temp_path = path.with_extension(format!("lock.{}.tmp", self.token));
+        fs::write(&temp_path, serde_json::to_vec(self)?)?;
+        fs::rename(&temp_path, path)
+    }
+
+    /// Whether the holder still runs: its heartbeat is recent and, where
+    /// processes can be looked up, its PID exists.
+    fn is_live(&self) -> bool {
+        now_secs() - self.heartbeat < DATA_DIR_LOCK_STALE_SECS as f64 && process_alive(self.pid) != Some(false)
+    }
+
+    /// Create `path` holding this info, failing if it already exists.
+    fn create(&self, path: &Path) -> std::io::Result<()> {
+        let mut file = fs::OpenOptions::new().write(true).create_new(true).open(path)?;
+        file.write_all(&serde_json::to_vec(self)?)?;
+        file.sync_all()
+    }
+}
+
+/// Whether an unparsable lock file may still be a holder's half-written one.
+fn recently_modified(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age < Duration::from_secs(DATA_DIR_LOCK_STALE_SECS))
+}
+
+/// Whether process `pid` exists, where that can be checked cheaply.
+fn process_alive(pid: u32) -> Option<bool> {
+    cfg!(target_os = "linux").then(|| Path::new("/proc").join(pid.to_string()).exists())
+}
+
+fn now_secs() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs_f64()
+}
+
+/// Held [`LOCK_FILE`]. A thread refreshes its heartbeat until it is released
+/// or dropped, which removes the file.
+struct DataDirLock {
+    path: PathBuf,
+    token: String,
+    /// Set on release; wakes and stops the heartbeat thread
+    released: Arc<(Mutex<bool>, Condvar)>,
+    /// Set once another process took the lock over; nothing may be written
+    /// to the data directory after that
+    lost: Arc<AtomicBool>,
+}
+
+impl DataDirLock {
+    /// Take the lock of `data_dir`. Errors when another live process holds
+    /// it, unless `force` is set.
+    fn acquire(data_dir: &Path, force: bool) -> Result<Self, String> {
+        let path = data_dir.join(LOCK_FILE);
+        let now = now_secs();
+        let info = LockInfo { pid: std::process::id(), token: uuid::Uuid::new_v4().to_string(), acquired_at: now, heartbeat: now };
+        let write_error = |e: std::io::Error| format!("failed to write lock file {:?}: {}", path, e);
+
+        // Creating the file is atomic: of two processes starting together,
+        // exactly one gets it
+        match info.create(&path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                match LockInfo::read(&path) {
+                    Some(holder) if holder.is_live() && !force => {
+                        return Err(format!(
+                            "data directory {:?} is locked by process {} (last heartbeat {:.0}s ago). Two instances on one data directory corrupt each other's snapshots; stop the other one, or pass --force-unlock if it is gone",
+                            data_dir,
+                            holder.pid,
+                            now_secs() - holder.heartbeat
+                        ));
+                    }
+                    None if recently_modified(&path) && !force => {
+                        return Err(format!("data directory {:?} is being locked by another process; pass --force-unlock if it is gone", data_dir));
+                    }
+                    Some(holder) => {
+                        warn!("Taking over {:?} from process {}{}", path, holder.pid, if force { " (--force-unlock)" } else { ", which is gone" });
+                    }
+                    None => warn!("Replacing unreadable lock file {:?}", path),
+                }
+                info.write(&path).map_err(write_error)?;
+                // Another process taking over the same stale lock renames
+                // its file over this one; the later rename wins
+                if LockInfo::read(&path).is_none_or(|current| current.token != info.token) {
+                    return Err(format!("data directory {:?} was locked by another process at the same time", data_dir));
+                }
+            }
+            Err(e) => return Err(write_error(e)),
+        }
+
+        let lock = Self {
+            path,
+            token: info.token.clone(),
+            released: Arc::new((Mutex::new(false), Condvar::new())),
+            lost: Arc::new(AtomicBool::new(false)),
+        };
+        let (path, released, lost) = (lock.path.clone(), lock.released.clone(), lock.lost.clone());
+        std::thread::spawn(move || heartbeat(path, info, released, lost));
+        Ok(lock)
+    }
+
+    /// Errors once another process took the lock over. Reads the lock file,
+    /// so a takeover between heartbeats is seen too.
+    fn check(&self) -> Result<(), String> {
+        if !self.lost.load(Ordering::Acquire) && LockInfo::read(&self.path).is_none_or(|current| current.token != self.token) {
+            error!("Lock {:?} was taken over by another process; no longer writing to the data directory", self.path);
+            self.lost.store(true, Ordering::Release);
+        }
+        if self.lost.load(Ordering::Acquire) {
+            return Err(format!("lost the lock {:?} to another process; stop this one", self.path));
+        }
+        Ok(())
+    }
+
+    fn release(&self) {
+        let (released, wake) = &*self.released;
+        let mut released = released.lock().unwrap();
+        if *released {
+            return;
+        }
+        *released = true;
+        wake.notify_all();
+        if LockInfo::read(&self.path).is_some_and(|info| info.token == self.token) {
+            if let Err(e) = fs::remove_file(&self.path) {
+                warn!("Failed to remove lock file {:?}: {}", self.path, e);
+            }
+        }
+    }
+}
+
+impl Drop for DataDirLock {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+/// Refresh the heartbeat of `info` in `path` until `released` is set. Stops
+/// early, setting `lost`, if another process took the lock over.
+fn heartbeat(path: PathBuf, mut info: LockInfo, released: Arc<(Mutex<bool>, Condvar)>, lost: Arc<AtomicBool>) {
+    let (released, wake) = &*released;
+    let mut stop = released.lock().unwrap();
+    loop {
+        stop = wake.wait_timeout(stop, Duration::from_secs(DATA_DIR_LOCK_HEARTBEAT_SECS)).unwrap().0;
+        if *stop {
+            return;
+        }
+        if LockInfo::read(&path).is_none_or(|current| current.token != info.token) {
+            error!("Lock {:?} was taken over by another process; no longer writing to the data directory", path);
+            lost.store(true, Ordering::Release);
+            return;
+        }
+        info.heartbeat = now_secs();
+        if let Err(e) = info.write(&path) {
+            warn!("Failed to refresh lock file {:?}: {}", path, e);
+        }
+    }
+}
 
 pub struct PersistenceManager {
     data_dir: PathBuf,
     snapshot_interval: Duration,
+    lock: Arc<DataDirLock>,
+    // Saves share one temp file; held while one is written
+    saving: Arc<Mutex<()>>,
 }
 
 impl PersistenceManager {
-    pub fn new(data_dir: impl AsRef<Path>, snapshot_interval_secs: u64) -> Self {
+    /// Take the data directory's [`LOCK_FILE`], creating the directory if
+    /// needed. Errors when another live process holds the lock; `force_unlock`
+    /// takes it over anyway.
+    pub fn new(data_dir: impl AsRef<Path>, snapshot_interval_secs: u64, force_unlock: bool) -> Result<Self, String> {
         let data_dir = data_dir.as_ref().to_path_buf();
         
         // Create data directory if it doesn't exist
         if let Err(e) = fs::create_dir_all(&data_dir) {
             error!("Failed to create data directory {:?}: {}", data_dir, e);
         }
+        let lock = DataDirLock::acquire(&data_dir, force_unlock)?;
         
-        Self {
+        Ok(Self {
             data_dir,
             snapshot_interval: Duration::from_secs(snapshot_interval_secs),
-        }
+            lock: Arc::new(lock),
+            saving: Arc::new(Mutex::new(())),
+        })
+    }
+    
+    /// Remove the lock file so another process can use the data directory.
+    /// Call after the final snapshot; dropping every clone does the same.
+    pub fn release_lock(&self) {
+        self.lock.release();
+    }
+
+    /// Errors once another process took the data directory's lock over.
+    /// `save_state` refuses to write from then on.
+    pub fn check_lock(&self) -> Result<(), String> {
+        self.lock.check()
     }
     
     /// Save engine state to a specific path (used by multi-tenant)
@@ -49,37 +605,7 @@ impl PersistenceManager {
     ) -> Result<(), Box<dyn std::error::Error>> {
         let start = std::time::Instant::now();
         
-        let memories = engine.get_memories();
-        let cue_index = engine.get_cue_index();
-        
-        // Convert DashMaps to serializable format
-        let memories_map: HashMap<String, Memory> = memories
-            .iter()
-            .map(|entry| (entry.key().clone(), entry.value().clone()))
-            .collect();
-        
-        let cue_index_map: HashMap<String, Vec<String>> = cue_index
-            .iter()
-            .map(|entry| {
-                let cue = entry.key().clone();
-                let ordered_set = entry.value();
-                let memory_ids = ordered_set.get_recent_owned(None);
-                (cue, memory_ids)
-            })
-            .collect();
-        
-        let state = PersistedState {
-            memories: memories_map,
-            cue_index: cue_index_map,
-            version: PERSISTENCE_VERSION,
-            saved_at: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        };
-        
-        // Serialize to bincode
-        let data = bincode::serialize(&state)?;
+        let (data, memory_count, cue_count) = encode_snapshot(engine)?;
         
         // Write to temp file first (atomic operation)
         let temp_path = path.with_extension("bin.tmp");
@@ -91,8 +617,8 @@ impl PersistenceManager {
         let duration = start.elapsed();
         info!(
             "Saved {} memories and {} cues to {:?} in {:?} ({} bytes)",
-            state.memories.len(),
-            state.cue_index.len(),
+            memory_count,
+            cue_count,
             path,
             duration,
             data.len()
@@ -101,10 +627,20 @@ impl PersistenceManager {
         Ok(())
     }
     
+    /// Snapshot bytes for an engine, in the layout of the snapshot files.
+    pub fn encode(engine: &CueMapEngine) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        Ok(encode_snapshot(engine)?.0)
+    }
+
+    /// Inverse of [`PersistenceManager::encode`].
+    pub fn decode(data: &[u8]) -> Result<SnapshotContents, Box<dyn std::error::Error>> {
+        decode_snapshot(data)
+    }
+
     /// Load engine state from a specific path (used by multi-tenant)
     pub fn load_from_path(
         path: &Path,
-    ) -> Result<(DashMap<String, Memory>, DashMap<String, OrderedSet>), Box<dyn std::error::Error>> {
+    ) -> Result<SnapshotContents, Box<dyn std::error::Error>> {
         if !path.exists() {
             return Err(format!("Snapshot not found: {:?}", path).into());
         }
@@ -112,32 +648,7 @@ impl PersistenceManager {
         info!("Loading state from {:?}", path);
         
         let data = fs::read(path)?;
-        let state: PersistedState = bincode::deserialize(&data)?;
-        
-        info!(
-            "Loaded {} memories and {} cues from snapshot (version: {}, saved: {})",
-            state.memories.len(),
-            state.cue_index.len(),
-            state.version,
-            state.saved_at
-        );
-        
-        // Convert to DashMaps
-        let memories = DashMap::new();
-        for (id, memory) in state.memories {
-            memories.insert(id, memory);
-        }
-        
-        let cue_index = DashMap::new();
-        for (cue, memory_ids) in state.cue_index {
-            let mut ordered_set = OrderedSet::new();
-            for memory_id in memory_ids {
-                ordered_set.add(memory_id);
-            }
-            cue_index.insert(cue, ordered_set);
-        }
-        
-        Ok((memories, cue_index))
+        decode_snapshot(&data)
     }
     
     /// List all snapshot files in a directory
@@ -175,12 +686,12 @@ impl PersistenceManager {
     }
     
     fn temp_snapshot_path(&self) -> PathBuf {
-        self.data_dir.join("cuemap.bin.tmp")
+        self.data_dir.join(format!("cuemap.bin.{}.tmp", self.lock.token))
     }
     
     pub fn load_state(
         &self,
-    ) -> Result<(DashMap<String, Memory>, DashMap<String, OrderedSet>), Box<dyn std::error::Error>> {
+    ) -> Result<SnapshotContents, Box<dyn std::error::Error>> {
         let snapshot_path = self.snapshot_path();
         
         if !snapshot_path.exists() {
@@ -191,85 +702,38 @@ impl PersistenceManager {
         info!("Loading state from {:?}", snapshot_path);
         
         let data = fs::read(&snapshot_path)?;
-        let state: PersistedState = bincode::deserialize(&data)?;
-        
-        info!(
-            "Loaded {} memories and {} cues from snapshot (version: {}, saved: {})",
-            state.memories.len(),
-            state.cue_index.len(),
-            state.version,
-            state.saved_at
-        );
-        
-        // Convert to DashMaps
-        let memories = DashMap::new();
-        for (id, memory) in state.memories {
-            memories.insert(id, memory);
-        }
-        
-        let cue_index = DashMap::new();
-        for (cue, memory_ids) in state.cue_index {
-            let mut ordered_set = OrderedSet::new();
-            for memory_id in memory_ids {
-                ordered_set.add(memory_id);
-            }
-            cue_index.insert(cue, ordered_set);
-        }
-        
-        Ok((memories, cue_index))
+        decode_snapshot(&data)
     }
     
     pub fn save_state(
         &self,
         engine: &CueMapEngine,
     ) -> Result<(), Box<dyn std::error::Error>> {
+        let _saving = self.saving.lock().unwrap_or_else(|e| e.into_inner());
+        self.check_lock()?;
         let start = std::time::Instant::now();
         
-        let memories = engine.get_memories();
-        let cue_index = engine.get_cue_index();
-        
-        // Convert DashMaps to serializable format
-        let memories_map: HashMap<String, Memory> = memories
-            .iter()
-            .map(|entry| (entry.key().clone(), entry.value().clone()))
-            .collect();
-        
-        let cue_index_map: HashMap<String, Vec<String>> = cue_index
-            .iter()
-            .map(|entry| {
-                let cue = entry.key().clone();
-                let ordered_set = entry.value();
-                // Use owned version for serialization
-                let memory_ids = ordered_set.get_recent_owned(None);
-                (cue, memory_ids)
-            })
-            .collect();
-        
-        let state = PersistedState {
-            memories: memories_map,
-            cue_index: cue_index_map,
-            version: PERSISTENCE_VERSION,
-            saved_at: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        };
-        
-        // Serialize to bincode
-        let data = bincode::serialize(&state)?;
+        let (data, memory_count, cue_count) = encode_snapshot(engine)?;
         
         // Write to temp file first (atomic operation)
         let temp_path = self.temp_snapshot_path();
         fs::write(&temp_path, &data)?;
         
+        // Encoding takes a while; a process that took the lock over meanwhile
+        // owns the snapshot now
+        if let Err(e) = self.check_lock() {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e.into());
+        }
+        
         // Rename to final location (atomic on most filesystems)
         fs::rename(&temp_path, &self.snapshot_path())?;
         
         let duration = start.elapsed();
         info!(
             "Saved {} memories and {} cues to snapshot in {:?} ({} bytes)",
-            state.memories.len(),
-            state.cue_index.len(),
+            memory_count,
+            cue_count,
             duration,
             data.len()
         );
@@ -289,6 +753,10 @@ impl PersistenceManager {
             loop {
                 interval.tick().await;
                 
+                if let Err(e) = persistence.check_lock() {
+                    error!("Stopping background snapshots: {}", e);
+                    return;
+                }
                 if let Err(e) = persistence.save_state(&engine) {
                     error!("Background snapshot failed: {}", e);
                 } else {
@@ -304,40 +772,49 @@ impl Clone for PersistenceManager {
         Self {
             data_dir: self.data_dir.clone(),
             snapshot_interval: self.snapshot_interval,
+            lock: self.lock.clone(),
+            saving: self.saving.clone(),
         }
     }
 }
 
-/// Setup graceful shutdown handler
-pub async fn setup_shutdown_handler(
-    persistence: PersistenceManager,
-    engine: Arc<CueMapEngine>,
-) {
-    tokio::spawn(async move {
-        // Wait for SIGINT (Ctrl+C) or SIGTERM
-        let mut sigint = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::interrupt())
-            .expect("Failed to create SIGINT handler");
-        let mut sigterm = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
-            .expect("Failed to create SIGTERM handler");
-        
-        tokio::select! {
-            _ = sigint.recv() => {
-                info!("Received SIGINT, shutting down gracefully...");
-            }
-            _ = sigterm.recv() => {
-                info!("Received SIGTERM, shutting down gracefully...");
-            }
+/// Resolve on SIGINT (Ctrl+C) or SIGTERM, or on Windows on Ctrl+C or
+/// Ctrl+Break. The server then stops accepting requests, drains in-flight
+/// ones and background jobs, and saves a final snapshot before exiting.
+#[cfg(unix)]
+pub async fn shutdown_signal() {
+    let mut sigint = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::interrupt())
+        .expect("Failed to create SIGINT handler");
+    let mut sigterm = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+        .expect("Failed to create SIGTERM handler");
+    
+    tokio::select! {
+        _ = sigint.recv() => {
+            info!("Received SIGINT, shutting down gracefully...");
         }
-        
-        // Save final snapshot
-        info!("Saving final snapshot before shutdown...");
-        if let Err(e) = persistence.save_state(&engine) {
-            error!("Failed to save final snapshot: {}", e);
-        } else {
-            info!("Final snapshot saved successfully");
+        _ = sigterm.recv() => {
+            info!("Received SIGTERM, shutting down gracefully...");
         }
-        
-        // Exit
-        std::process::exit(0);
-    });
+    }
+}
+
+#[cfg(windows)]
+pub async fn shutdown_signal() {
+    let mut ctrl_c = tokio::signal::windows::ctrl_c().expect("Failed to create Ctrl+C handler");
+    let mut ctrl_break = tokio::signal::windows::ctrl_break().expect("Failed to create Ctrl+Break handler");
+
+    tokio::select! {
+        _ = ctrl_c.recv() => {
+            info!("Received Ctrl+C, shutting down gracefully...");
+        }
+        _ = ctrl_break.recv() => {
+            info!("Received Ctrl+Break, shutting down gracefully...");
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub async fn shutdown_signal() {
+    tokio::signal::ctrl_c().await.expect("Failed to create Ctrl+C handler");
+    info!("Received Ctrl+C, shutting down gracefully...");
 }
@@ -1,106 +1,1371 @@
-use crate::engine::CueMapEngine;
-use crate::normalization::NormalizationConfig;
-use crate::taxonomy::Taxonomy;
+use crate::alias_proposal::AliasConfig;
+use crate::collections::{validate_name, Collection, CollectionError};
+use crate::content_limit::ContentLimit;
+use crate::config::{alias_max_depth, approximate_early_exit, read_view_refresh, recall_cache_ttl, ALIAS_IDLE_DAYS, ALIAS_MIN_FEEDBACK, ALIAS_MIN_PRECISION, LEXICON_MIN_RELATIVE_SCORE, QUERY_CACHE_CAPACITY, RECALL_CACHE_CAPACITY, REJECTION_QUARANTINE_CAPACITY, REPROPOSAL_DEFAULT_RATE, SPELL_LONG_WORD_LEN, SPELL_MIN_WORD_LEN, QUERY_CACHE_TTL_SECS};
+use crate::engine::{string_bytes, with_table_overhead, CueMapEngine, CueSizeStats, IndexReport, MemoryUsage, RecallResult};
+use crate::hooks::{Hooks, PendingMemory, RecallQueryInfo};
+use crate::query_cache::QueryCache;
+use crate::recall_cache::RecallCache;
+use crate::shared_vocabulary::SharedVocabulary;
+use crate::ranking::RankingConfig;
+use crate::quarantine::{QuarantinedCue, RejectionQuarantine};
+use crate::rerank::EMBEDDING_KEY;
+use crate::reproposal::{Reproposal, ReproposalFilter};
+use crate::normalization::{composite_parts, normalize_cue, NormalizationConfig};
+use crate::taxonomy::{apply_enforcement, enforce_cardinality, validate_cues, Enforcement, EnforcementCounter, Taxonomy, ValidationReport};
+use crate::nl::{TokenizerConfig, TokenizerMode};
 use dashmap::DashMap;
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use serde_json::Value;
+use tracing::{error, info, warn};
+
+/// Single-tenant project config file, stored in the data directory
+pub const PROJECT_CONFIG_FILE: &str = "project_config.json";
+
+/// Recall settings a project applies when a request leaves them out. Unset
+/// ones fall back to the request defaults.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct RecallDefaults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_intersection: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_reinforce: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explain: Option<bool>,
+    /// Grounded recall: decay scores by age
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefer_fresh: Option<bool>,
+    /// Grounded recall: leave out memories older than this
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<f64>,
+}
+
+impl RecallDefaults {
+    /// Reject defaults no request could use.
+    pub fn validate(&self, max_recall_limit: usize) -> Result<(), String> {
+        if let Some(limit) = self.limit {
+            if limit == 0 || limit > max_recall_limit {
+                return Err(format!("limit must be between 1 and {}", max_recall_limit));
+            }
+        }
+        if self.min_intersection == Some(0) {
+            return Err("min_intersection must be at least 1".to_string());
+        }
+        if self.max_age_days.is_some_and(|days| !(days.is_finite() && days > 0.0)) {
+            return Err("max_age_days must be a positive number".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Per-project configuration persisted next to the project's snapshot.
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+pub struct ProjectConfig {
+    #[serde(default)]
+    pub normalization: NormalizationConfig,
+    #[serde(default)]
+    pub taxonomy: Taxonomy,
+    #[serde(default)]
+    pub tokenizer: TokenizerConfig,
+    #[serde(default)]
+    pub aliases: AliasConfig,
+    #[serde(default)]
+    pub ranking: RankingConfig,
+    #[serde(default)]
+    pub recall: RecallDefaults,
+    #[serde(default)]
+    pub content_limit: ContentLimit,
+    /// Collections memories can be filed under, by name
+    #[serde(default)]
+    pub collections: BTreeMap<String, Collection>,
+    /// How cues were first written before normalization lowercased them,
+    /// by normalized cue
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub cue_display_names: BTreeMap<String, String>,
+}
+
+impl ProjectConfig {
+    /// Load a config file, falling back to defaults if it is missing or unreadable.
+    pub fn load_or_default(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default();
+        }
+        match fs::read_to_string(path).map(|data| serde_json::from_str::<ProjectConfig>(&data)) {
+            Ok(Ok(config)) => config,
+            Ok(Err(e)) => {
+                warn!("Invalid project config {:?}: {}, using defaults", path, e);
+                Self::default()
+            }
+            Err(e) => {
+                warn!("Failed to read project config {:?}: {}, using defaults", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Settings that differ from the defaults, grouped by section
+    /// (e.g. `{"tokenizer": {"min_token_len": 3}}`). Unchanged sections are omitted.
+    pub fn overrides(&self) -> serde_json::Value {
+        let current = serde_json::json!(self);
+        let defaults = serde_json::json!(Self::default());
+        let mut overrides = serde_json::Map::new();
+
+        if let (Some(current), Some(defaults)) = (current.as_object(), defaults.as_object()) {
+            for (section, values) in current {
+                let (Some(values), Some(default_values)) =
+                    (values.as_object(), defaults.get(section).and_then(|d| d.as_object()))
+                else {
+                    continue;
+                };
+                let changed: serde_json::Map<String, serde_json::Value> = values
+                    .iter()
+                    .filter(|(key, value)| default_values.get(*key) != Some(*value))
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect();
+                if !changed.is_empty() {
+                    overrides.insert(section.clone(), serde_json::Value::Object(changed));
+                }
+            }
+        }
+        serde_json::Value::Object(overrides)
+    }
+
+    /// Write the config atomically (temp file + rename).
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let data = serde_json::to_vec_pretty(self)?;
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, data)?;
+        fs::rename(&temp_path, path)
+    }
+}
 
 pub struct ProjectContext {
     pub main: CueMapEngine,
     pub aliases: CueMapEngine,
     pub lexicon: CueMapEngine,
-    pub query_cache: DashMap<String, Vec<String>>,
-    pub normalization: NormalizationConfig,
-    pub taxonomy: Taxonomy,
+    pub query_cache: QueryCache,
+    /// Recall responses, dropped on any write to the project
+    pub recall_cache: RecallCache,
+    /// Cues rejected by taxonomy validation, for review and promotion
+    pub rejections: RejectionQuarantine,
+    /// Cues `warn` enforcement accepted that `strict` would reject
+    pub enforcement_warnings: EnforcementCounter,
+    pub normalization: RwLock<NormalizationConfig>,
+    pub taxonomy: RwLock<Taxonomy>,
+    pub tokenizer: RwLock<TokenizerConfig>,
+    alias_config: RwLock<AliasConfig>,
+    recall_defaults: RwLock<RecallDefaults>,
+    content_limit: RwLock<ContentLimit>,
+    collections: RwLock<BTreeMap<String, Collection>>,
+    cue_display_names: RwLock<BTreeMap<String, String>>,
+    /// Document frequencies of token cues, for keyword extraction
+    keyword_df: DashMap<String, u64>,
+    keyword_docs: AtomicU64,
+    /// Where config changes are persisted; `None` keeps config in memory only.
+    config_path: Option<PathBuf>,
+    hooks: Hooks,
+    /// Aliases and lexicon terms shared with other projects, consulted after this project's own
+    shared: Option<Arc<SharedVocabulary>>,
+    /// Latest lexicon rebuild, queued, running or completed
+    lexicon_rebuild: RwLock<Option<LexiconRebuild>>,
+    /// Latest cue reproposal, queued, running or completed
+    reproposal: RwLock<Option<Reproposal>>,
+}
+
+/// Metadata key of an alias's [`AliasUsage`].
+pub const ALIAS_USAGE_KEY: &str = "usage";
+
+/// How an alias has fared in queries, kept in the alias memory's metadata so
+/// it is snapshotted with the alias.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AliasUsage {
+    /// Query expansions that applied the alias
+    #[serde(default)]
+    pub expansions: u64,
+    /// Expansions whose target cue had memories to match
+    #[serde(default)]
+    pub hits: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_hit_at: Option<f64>,
+    /// Recall feedback on memories the alias's target cue matched
+    #[serde(default)]
+    pub useful: u64,
+    #[serde(default)]
+    pub harmful: u64,
+}
+
+impl AliasUsage {
+    fn of(metadata: &HashMap<String, Value>) -> Self {
+        metadata
+            .get(ALIAS_USAGE_KEY)
+            .and_then(|usage| serde_json::from_value(usage.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    fn update(metadata: &mut HashMap<String, Value>, f: impl FnOnce(&mut AliasUsage)) {
+        let mut usage = Self::of(metadata);
+        f(&mut usage);
+        metadata.insert(ALIAS_USAGE_KEY.to_string(), serde_json::json!(usage));
+    }
+
+    /// Useful share of judged memories, once there is feedback.
+    pub fn precision(&self) -> Option<f64> {
+        let judged = self.useful + self.harmful;
+        (judged > 0).then(|| self.useful as f64 / judged as f64)
+    }
+}
+
+/// An alias with its usage, as listed by `GET /aliases/stats`.
+#[derive(Debug, Clone, Serialize)]
+pub struct AliasStats {
+    pub id: String,
+    pub from: String,
+    pub to: String,
+    pub status: String,
+    pub created_at: f64,
+    pub usage: AliasUsage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precision: Option<f64>,
+}
+
+/// An active alias reverted to proposed by [`ProjectContext::expire_aliases`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExpiredAlias {
+    pub id: String,
+    pub from: String,
+    pub to: String,
+    /// `unused` or `low_precision`
+    pub reason: &'static str,
+}
+
+/// A query cue, or a cue reached from one through aliases.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CueExpansion {
+    pub cue: String,
+    /// The query cue's weight times the downweights along `path`
+    pub weight: f64,
+    /// Cues from the query cue to this one, both included
+    pub path: Vec<String>,
+}
+
+/// A query word replaced by the closest word the lexicon knows.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpellingCorrection {
+    pub from: String,
+    pub to: String,
+    pub distance: usize,
+}
+
+/// Progress of a lexicon rebuild, as reported by `GET /admin/lexicon/rebuild`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LexiconRebuild {
+    /// `queued`, `running` or `completed`
+    pub state: &'static str,
+    /// Memories to retrain from, counted when the rebuild starts
+    pub total: usize,
+    /// Memories retrained so far
+    pub processed: usize,
+    pub queued_at: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<f64>,
+}
+
+impl LexiconRebuild {
+    fn queued() -> Self {
+        Self { state: "queued", total: 0, processed: 0, queued_at: now_secs(), started_at: None, finished_at: None }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.state != "completed"
+    }
+}
+
+/// Result of [`ProjectContext::promote_rejected`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Promotion {
+    /// Quarantined cues now accepted and attached to their memories
+    pub reattached: Vec<QuarantinedCue>,
+    /// Matching cues the taxonomy still rejects (e.g. `cardinality_exceeded`), left in quarantine
+    pub still_rejected: usize,
+}
+
+/// A memory that passed the ingest hooks, with its validated cues.
+#[derive(Debug, Clone)]
+pub struct PreparedMemory {
+    pub content: String,
+    pub metadata: Option<HashMap<String, Value>>,
+    pub report: ValidationReport,
+}
+
+/// Estimated memory footprint of a project, see `CueMapEngine::memory_usage`.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ProjectMemoryUsage {
+    pub main: MemoryUsage,
+    pub lexicon: MemoryUsage,
+    pub aliases: MemoryUsage,
+    /// Cue strings shared by the three engines
+    pub cue_table_bytes: usize,
+    pub query_cache_bytes: usize,
+    pub recall_cache_bytes: usize,
+    pub keyword_df_bytes: usize,
+    pub total_bytes: usize,
+}
+
+/// Cue index checks of a project's engines, see `CueMapEngine::verify_index`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectIndexReport {
+    pub main: IndexReport,
+    pub lexicon: IndexReport,
+    pub aliases: IndexReport,
+    /// Whether inconsistent indexes were rebuilt
+    pub rebuilt: bool,
+}
+
+impl ProjectIndexReport {
+    pub fn is_consistent(&self) -> bool {
+        self.main.is_consistent() && self.lexicon.is_consistent() && self.aliases.is_consistent()
+    }
+
+    /// One line per problem, prefixed with the engine it was found in.
+    pub fn problems(&self) -> Vec<String> {
+        [("main", &self.main), ("lexicon", &self.lexicon), ("aliases", &self.aliases)]
+            .into_iter()
+            .flat_map(|(engine, report)| report.problems().into_iter().map(move |problem| format!("{}: {}", engine, problem)))
+            .collect()
+    }
 }
 
 impl ProjectContext {
     pub fn new(normalization: NormalizationConfig, taxonomy: Taxonomy) -> Self {
-        Self {
-            main: CueMapEngine::new(),
-            aliases: CueMapEngine::new(),
-            lexicon: CueMapEngine::new(),
-            query_cache: DashMap::new(),
+        Self::with_engine(CueMapEngine::new(), normalization, taxonomy)
+    }
+
+    /// Build a context around an existing main engine (e.g. one restored from a snapshot).
+    pub fn with_engine(main: CueMapEngine, normalization: NormalizationConfig, taxonomy: Taxonomy) -> Self {
+        Self::from_config(main, ProjectConfig {
             normalization,
             taxonomy,
+            ..Default::default()
+        })
+    }
+
+    /// The main engine serves recalls from a published read view when
+    /// `config::read_view_refresh` is set; lexicon and alias lookups stay live.
+    /// It exits recalls early when `config::approximate_early_exit` is set.
+    pub fn from_config(main: CueMapEngine, mut config: ProjectConfig) -> Self {
+        config.normalization.compile();
+        if read_view_refresh().is_some() {
+            main.enable_read_view();
+        }
+        if approximate_early_exit() {
+            main.set_approximate_early_exit(true);
+        }
+        main.set_ranking(config.ranking);
+        let cues = main.cue_interner().clone();
+        Self {
+            main,
+            aliases: CueMapEngine::with_cue_interner(cues.clone()),
+            lexicon: CueMapEngine::with_cue_interner(cues),
+            query_cache: QueryCache::new(QUERY_CACHE_CAPACITY, Duration::from_secs(QUERY_CACHE_TTL_SECS)),
+            recall_cache: RecallCache::new(RECALL_CACHE_CAPACITY, recall_cache_ttl()),
+            rejections: RejectionQuarantine::new(REJECTION_QUARANTINE_CAPACITY),
+            enforcement_warnings: EnforcementCounter::default(),
+            normalization: RwLock::new(config.normalization),
+            taxonomy: RwLock::new(config.taxonomy),
+            tokenizer: RwLock::new(config.tokenizer.normalized()),
+            alias_config: RwLock::new(config.aliases),
+            recall_defaults: RwLock::new(config.recall),
+            content_limit: RwLock::new(config.content_limit),
+            collections: RwLock::new(config.collections),
+            cue_display_names: RwLock::new(config.cue_display_names),
+            keyword_df: DashMap::new(),
+            keyword_docs: AtomicU64::new(0),
+            config_path: None,
+            hooks: Hooks::default(),
+            shared: None,
+            lexicon_rebuild: RwLock::new(None),
+            reproposal: RwLock::new(None),
+        }
+    }
+
+    /// Publish the main engine's pending index writes to recall.
+    pub fn refresh_read_view(&self) -> Option<u64> {
+        self.main.refresh_read_view()
+    }
+
+    /// Cross-check each engine's cue index against its memories; with
+    /// `rebuild`, rebuild the inconsistent ones from their memories. The
+    /// reports describe the indexes as found.
+    pub fn verify_indexes(&self, rebuild: bool) -> ProjectIndexReport {
+        let check = |engine: &CueMapEngine| if rebuild { engine.rebuild_cue_index() } else { engine.verify_index() };
+        let mut report = ProjectIndexReport {
+            main: check(&self.main),
+            lexicon: check(&self.lexicon),
+            aliases: check(&self.aliases),
+            rebuilt: false,
+        };
+        if rebuild && !report.is_consistent() {
+            self.query_cache.clear();
+            report.rebuilt = true;
+        }
+        report
+    }
+
+    /// Warm up the main, lexicon and alias engines after a snapshot load.
+    /// Returns the cue sizes of the main engine.
+    pub fn warm_up(&self) -> CueSizeStats {
+        self.lexicon.warm_up();
+        self.aliases.warm_up();
+        self.main.warm_up()
+    }
+
+    /// Estimated bytes held by the project's engines and caches.
+    pub fn memory_usage(&self) -> ProjectMemoryUsage {
+        let main = self.main.memory_usage();
+        let lexicon = self.lexicon.memory_usage();
+        let aliases = self.aliases.memory_usage();
+        let cue_table_bytes = self.main.cue_table_bytes();
+        let query_cache_bytes = self.query_cache.estimated_bytes();
+        let recall_cache_bytes = self.recall_cache.estimated_bytes();
+        let keyword_df_bytes = with_table_overhead(
+            self.keyword_df
+                .iter()
+                .map(|entry| string_bytes(entry.key()) + std::mem::size_of::<u64>())
+                .sum(),
+        );
+        ProjectMemoryUsage {
+            total_bytes: main.total_bytes + lexicon.total_bytes + aliases.total_bytes + cue_table_bytes + query_cache_bytes + recall_cache_bytes + keyword_df_bytes,
+            main,
+            lexicon,
+            aliases,
+            cue_table_bytes,
+            query_cache_bytes,
+            recall_cache_bytes,
+            keyword_df_bytes,
+        }
+    }
+
+    /// Persist config changes to `path` from now on.
+    pub fn with_config_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_path = Some(path.into());
+        self
+    }
+
+    /// Run `hooks` on this project's ingests and recalls.
+    pub fn with_hooks(mut self, hooks: Hooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    pub fn hooks(&self) -> &Hooks {
+        &self.hooks
+    }
+
+    /// Fall back to `shared` aliases and lexicon terms when resolving queries.
+    pub fn with_shared_vocabulary(mut self, shared: Arc<SharedVocabulary>) -> Self {
+        self.shared = Some(shared);
+        self
+    }
+
+    pub fn shared_vocabulary(&self) -> Option<&Arc<SharedVocabulary>> {
+        self.shared.as_ref()
+    }
+
+    /// Run the ingest hooks, then normalize, language-tag and validate the
+    /// cues. Errors with the reason when a hook rejects the memory.
+    pub fn prepare_memory(
+        &self,
+        content: String,
+        cues: Vec<String>,
+        metadata: Option<HashMap<String, Value>>,
+    ) -> Result<PreparedMemory, String> {
+        let had_metadata = metadata.is_some();
+        let mut memory = PendingMemory { content, cues, metadata: metadata.unwrap_or_default() };
+        self.hooks.before_add(&mut memory)?;
+
+        let mut normalized_cues = self.normalize_cues(&memory.cues);
+        let client_cues = normalized_cues.len();
+        normalized_cues.extend(self.derived_cues(&normalized_cues));
+        if let Some(lang_cue) = self.language_cue(&memory.content, &normalized_cues) {
+            normalized_cues.push(lang_cue);
+        }
+        let report = self.validate_cues(normalized_cues.clone());
+        self.record_display_names(&memory.cues, &normalized_cues[..client_cues], &report.accepted);
+        let metadata = (had_metadata || !memory.metadata.is_empty()).then_some(memory.metadata);
+        Ok(PreparedMemory {
+            report,
+            content: memory.content,
+            metadata,
+        })
+    }
+
+    /// Run the recall hooks over results of a recall with `cues`. Cached
+    /// embeddings are dropped from the results first.
+    pub fn after_recall(&self, cues: &[(String, f64)], query_text: Option<&str>, results: &mut Vec<RecallResult>) {
+        for result in results.iter_mut() {
+            result.metadata.remove(EMBEDDING_KEY);
+        }
+        self.hooks.after_recall(&RecallQueryInfo { cues, query_text }, results);
+    }
+
+    /// Snapshot of the full project config.
+    pub fn config(&self) -> ProjectConfig {
+        ProjectConfig {
+            normalization: self.normalization(),
+            taxonomy: self.taxonomy(),
+            tokenizer: self.tokenizer(),
+            aliases: self.alias_config(),
+            ranking: self.ranking(),
+            recall: self.recall_defaults(),
+            content_limit: self.content_limit(),
+            collections: self.collections.read().unwrap().clone(),
+            cue_display_names: self.cue_display_names.read().unwrap().clone(),
+        }
+    }
+
+    /// Save a changed config, and drop recalls cached under the old one.
+    fn persist_config(&self) {
+        self.recall_cache.clear();
+        if let Some(path) = &self.config_path {
+            if let Err(e) = self.config().save(path) {
+                error!("Failed to persist project config to {:?}: {}", path, e);
+            }
         }
     }
+
+    /// Snapshot of the current tokenizer settings.
+    pub fn tokenizer(&self) -> TokenizerConfig {
+        self.tokenizer.read().unwrap().clone()
+    }
+
+    /// Replace the tokenizer settings. Clears the query cache since cached
+    /// resolutions were tokenized under the old settings.
+    pub fn set_tokenizer(&self, tokenizer: TokenizerConfig) {
+        *self.tokenizer.write().unwrap() = tokenizer.normalized();
+        self.query_cache.clear();
+        self.persist_config();
+    }
+
+    /// Snapshot of the current alias proposal thresholds.
+    pub fn alias_config(&self) -> AliasConfig {
+        self.alias_config.read().unwrap().clone()
+    }
+
+    /// Replace the alias proposal thresholds used by the next `ProposeAliases` run.
+    pub fn set_alias_config(&self, alias_config: AliasConfig) {
+        *self.alias_config.write().unwrap() = alias_config;
+        self.persist_config();
+    }
+
+    /// Snapshot of the recall settings applied when a request omits them.
+    pub fn recall_defaults(&self) -> RecallDefaults {
+        self.recall_defaults.read().unwrap().clone()
+    }
+
+    /// Replace the project's recall defaults.
+    pub fn set_recall_defaults(&self, recall_defaults: RecallDefaults) {
+        *self.recall_defaults.write().unwrap() = recall_defaults;
+        self.persist_config();
+    }
+
+    /// Snapshot of the content size limit applied to new memories.
+    pub fn content_limit(&self) -> ContentLimit {
+        self.content_limit.read().unwrap().clone()
+    }
+
+    /// Replace the content size limit. Memories already stored are unchanged.
+    pub fn set_content_limit(&self, content_limit: ContentLimit) {
+        *self.content_limit.write().unwrap() = content_limit;
+        self.persist_config();
+    }
+
+    /// Snapshot of the current ranking rules.
+    pub fn ranking(&self) -> RankingConfig {
+        self.main.ranking()
+    }
+
+    /// Replace the ranking rules, normalizing the cues they match on. Clears
+    /// the query cache since cached results were ranked under the old rules.
+    pub fn set_ranking(&self, mut ranking: RankingConfig) {
+        for rule in &mut ranking.rules {
+            if let Some(cue) = &rule.cue {
+                rule.cue = Some(normalize_cue(cue, &self.normalization.read().unwrap()).0);
+            }
+        }
+        self.main.set_ranking(ranking);
+        self.query_cache.clear();
+        self.persist_config();
+    }
+
+    /// Tokenize text into `tok:`/`phr:` cues using the project tokenizer settings.
+    pub fn tokenize(&self, text: &str) -> Vec<String> {
+        crate::nl::tokenize_to_cues_with(text, &self.tokenizer.read().unwrap())
+    }
+
+    /// Tokens used to train the lexicon for a memory's content: every token in
+    /// `exhaustive` mode, or only the top-scoring keywords in `keywords` mode.
+    /// The detected language selects the extra stopword list.
+    pub fn lexicon_tokens(&self, text: &str) -> Vec<String> {
+        let tokenizer = self.tokenizer.read().unwrap();
+        let lang = self.content_language(&tokenizer, text);
+        match tokenizer.mode {
+            TokenizerMode::Exhaustive => crate::nl::tokenize_to_cues_for_language(text, &tokenizer, lang),
+            TokenizerMode::Keywords => crate::nl::extract_keywords(
+                text,
+                &tokenizer,
+                lang,
+                |cue| self.keyword_df.get(cue).map(|df| *df).unwrap_or(0),
+                self.keyword_docs.load(Ordering::Relaxed),
+            ),
+        }
+    }
+
+    /// Record a memory's content in the document-frequency statistics used by
+    /// keyword extraction. Call once per ingested memory.
+    pub fn observe_document(&self, text: &str) {
+        let tokenizer = self.tokenizer.read().unwrap();
+        if tokenizer.mode != TokenizerMode::Keywords {
+            return;
+        }
+        let lang = self.content_language(&tokenizer, text);
+        let unique: HashSet<String> = crate::nl::tokenize_to_cues_for_language(text, &tokenizer, lang)
+            .into_iter()
+            .collect();
+        for cue in unique {
+            *self.keyword_df.entry(cue).or_insert(0) += 1;
+        }
+        self.keyword_docs.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Take a deleted memory's content back out of the document-frequency
+    /// statistics, undoing [`observe_document`](Self::observe_document).
+    pub fn forget_document(&self, text: &str) {
+        let tokenizer = self.tokenizer.read().unwrap();
+        if tokenizer.mode != TokenizerMode::Keywords {
+            return;
+        }
+        let lang = self.content_language(&tokenizer, text);
+        let unique: HashSet<String> = crate::nl::tokenize_to_cues_for_language(text, &tokenizer, lang)
+            .into_iter()
+            .collect();
+        for cue in unique {
+            self.keyword_df.remove_if_mut(&cue, |_, df| {
+                *df = df.saturating_sub(1);
+                *df == 0
+            });
+        }
+        let _ = self.keyword_docs.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |docs| Some(docs.saturating_sub(1)));
+    }
+
+    /// Drop every lexicon entry and the keyword statistics, ahead of retraining
+    /// the lexicon from scratch. Returns the number of entries removed.
+    pub fn clear_lexicon(&self) -> usize {
+        let ids: Vec<String> = self.lexicon.get_memories().iter().map(|entry| entry.key().clone()).collect();
+        let removed = ids.iter().filter(|id| self.lexicon.delete_memory(id)).count();
+        self.lexicon.prune_empty_cues();
+        self.keyword_df.clear();
+        self.keyword_docs.store(0, Ordering::Relaxed);
+        self.query_cache.clear();
+        removed
+    }
+
+    /// Mark a lexicon rebuild as queued. False when one is already queued or running.
+    pub fn queue_lexicon_rebuild(&self) -> bool {
+        let mut rebuild = self.lexicon_rebuild.write().unwrap();
+        if rebuild.as_ref().is_some_and(LexiconRebuild::is_pending) {
+            return false;
+        }
+        *rebuild = Some(LexiconRebuild::queued());
+        true
+    }
+
+    /// The latest lexicon rebuild, if one was ever queued.
+    pub fn lexicon_rebuild(&self) -> Option<LexiconRebuild> {
+        self.lexicon_rebuild.read().unwrap().clone()
+    }
+
+    /// Update the progress of the current lexicon rebuild, recording one as
+    /// queued now if none was.
+    pub fn update_lexicon_rebuild(&self, f: impl FnOnce(&mut LexiconRebuild)) {
+        let mut rebuild = self.lexicon_rebuild.write().unwrap();
+        let rebuild = rebuild.get_or_insert_with(LexiconRebuild::queued);
+        f(rebuild);
+    }
+
+    /// Mark a reproposal as queued. False when one is already queued or running.
+    pub fn queue_reproposal(&self, filter: ReproposalFilter, rate: f64) -> bool {
+        let mut reproposal = self.reproposal.write().unwrap();
+        if reproposal.as_ref().is_some_and(Reproposal::is_pending) {
+            return false;
+        }
+        *reproposal = Some(Reproposal::queued(filter, rate));
+        true
+    }
+
+    /// The latest reproposal, if one was ever queued.
+    pub fn reproposal(&self) -> Option<Reproposal> {
+        self.reproposal.read().unwrap().clone()
+    }
+
+    /// Update the progress of the current reproposal, recording one as queued
+    /// now if none was.
+    pub fn update_reproposal(&self, f: impl FnOnce(&mut Reproposal)) {
+        let mut reproposal = self.reproposal.write().unwrap();
+        let reproposal = reproposal.get_or_insert_with(|| Reproposal::queued(ReproposalFilter::default(), REPROPOSAL_DEFAULT_RATE));
+        f(reproposal);
+    }
+
+    fn content_language(&self, tokenizer: &TokenizerConfig, text: &str) -> Option<&'static str> {
+        if tokenizer.language_stopwords.is_empty() {
+            return None;
+        }
+        crate::nl::detect_language(text)
+    }
+
+    /// `lang:xx` cue for ingested content, if enabled, reliably detected, and
+    /// the memory doesn't already carry a `lang:` cue.
+    pub fn language_cue(&self, content: &str, cues: &[String]) -> Option<String> {
+        if !self.tokenizer.read().unwrap().language_cue {
+            return None;
+        }
+        if cues.iter().any(|c| c.starts_with("lang:")) {
+            return None;
+        }
+        crate::nl::detect_language(content).map(|code| format!("lang:{}", code))
+    }
+
+    /// Snapshot of the current normalization config.
+    pub fn normalization(&self) -> NormalizationConfig {
+        self.normalization.read().unwrap().clone()
+    }
+
+    /// Replace the normalization config. Rewrite rules are recompiled once here
+    /// and the query cache is cleared since cached cues were normalized under the old rules.
+    pub fn set_normalization(&self, mut normalization: NormalizationConfig) {
+        normalization.compile();
+        *self.normalization.write().unwrap() = normalization;
+        self.query_cache.clear();
+        self.persist_config();
+    }
+
+    /// Collections of the project, by name.
+    pub fn collections(&self) -> Vec<Collection> {
+        self.collections.read().unwrap().values().cloned().collect()
+    }
+
+    pub fn collection(&self, name: &str) -> Option<Collection> {
+        self.collections.read().unwrap().get(name).cloned()
+    }
+
+    pub fn create_collection(&self, name: &str, description: Option<String>) -> Result<Collection, CollectionError> {
+        validate_name(name)?;
+        let collection = {
+            let mut collections = self.collections.write().unwrap();
+            if collections.contains_key(name) {
+                return Err(CollectionError::AlreadyExists);
+            }
+            let collection = Collection::new(name.to_string(), description);
+            collections.insert(name.to_string(), collection.clone());
+            collection
+        };
+        self.persist_config();
+        Ok(collection)
+    }
+
+    pub fn update_collection(&self, name: &str, description: Option<String>) -> Result<Collection, CollectionError> {
+        let collection = {
+            let mut collections = self.collections.write().unwrap();
+            let collection = collections.get_mut(name).ok_or(CollectionError::NotFound)?;
+            collection.description = description;
+            collection.clone()
+        };
+        self.persist_config();
+        Ok(collection)
+    }
+
+    /// Remove a collection. Its memories are kept outside any collection;
+    /// returns how many there were.
+    pub fn delete_collection(&self, name: &str) -> Result<usize, CollectionError> {
+        if self.collections.write().unwrap().remove(name).is_none() {
+            return Err(CollectionError::NotFound);
+        }
+        self.persist_config();
+        Ok(self.main.clear_collection(name))
+    }
+
+    /// Remember the form each of `originals` was written in, when it is the
+    /// first one seen for its cue, the cue was `accepted`, and normalization
+    /// only changed its case. `normalized` holds the normalized `originals`.
+    pub fn record_display_names(&self, originals: &[String], normalized: &[String], accepted: &[String]) {
+        let mut recorded = false;
+        {
+            let mut names = self.cue_display_names.write().unwrap();
+            for (original, cue) in originals.iter().zip(normalized) {
+                let original = original.trim();
+                if original == cue || original.to_lowercase() != *cue || !accepted.contains(cue) {
+                    continue;
+                }
+                if !names.contains_key(cue) {
+                    names.insert(cue.clone(), original.to_string());
+                    recorded = true;
+                }
+            }
+        }
+        if recorded {
+            self.persist_config();
+        }
+    }
+
+    /// The form `cue` was first written in, if normalization changed it.
+    pub fn display_name(&self, cue: &str) -> Option<String> {
+        self.cue_display_names.read().unwrap().get(cue).cloned()
+    }
+
+    /// Display names of those of `cues` that have one.
+    pub fn display_names<'a>(&self, cues: impl IntoIterator<Item = &'a str>) -> BTreeMap<String, String> {
+        let names = self.cue_display_names.read().unwrap();
+        cues.into_iter()
+            .filter_map(|cue| names.get(cue).map(|name| (cue.to_string(), name.clone())))
+            .collect()
+    }
+
+    /// Forget the display names of cues no memory holds any more; returns
+    /// how many were dropped.
+    pub fn prune_display_names(&self) -> usize {
+        let pruned = {
+            let mut names = self.cue_display_names.write().unwrap();
+            let before = names.len();
+            names.retain(|cue, _| self.main.cue_len(cue) > 0);
+            before - names.len()
+        };
+        if pruned > 0 {
+            self.persist_config();
+        }
+        pruned
+    }
+
+    /// Replace every display name, as when following a primary.
+    pub fn set_display_names(&self, names: BTreeMap<String, String>) {
+        *self.cue_display_names.write().unwrap() = names;
+        self.persist_config();
+    }
+
+    /// Normalize a batch of cues under a single read of the normalization config.
+    #[tracing::instrument(name = "normalize", skip_all, fields(cues = cues.len()))]
+    pub fn normalize_cues(&self, cues: &[String]) -> Vec<String> {
+        let normalization = self.normalization.read().unwrap();
+        cues.iter()
+            .map(|cue| normalize_cue(cue, &normalization).0)
+            .collect()
+    }
+
+    /// Atomic cues carried by the composite cues among the normalized
+    /// `cues`, normalized, that `cues` does not already hold. Empty when
+    /// `composite_cues` is off.
+    pub fn derived_cues(&self, cues: &[String]) -> Vec<String> {
+        let normalization = self.normalization.read().unwrap();
+        if !normalization.composite_cues {
+            return Vec::new();
+        }
+        let mut derived: Vec<String> = Vec::new();
+        for part in cues.iter().filter_map(|cue| composite_parts(cue)).flatten() {
+            let cue = normalize_cue(&part, &normalization).0;
+            if !cues.contains(&cue) && !derived.contains(&cue) {
+                derived.push(cue);
+            }
+        }
+        derived
+    }
+
+    /// Snapshot of the current taxonomy.
+    pub fn taxonomy(&self) -> Taxonomy {
+        self.taxonomy.read().unwrap().clone()
+    }
+
+    /// Validate cues against the project taxonomy, including per-key cardinality limits
+    /// measured against the main engine's cue index. Rejections are waived
+    /// unless the taxonomy's enforcement is strict, and counted in `warn` mode.
+    pub fn validate_cues(&self, cues: Vec<String>) -> ValidationReport {
+        let taxonomy = self.taxonomy.read().unwrap();
+        let mut report = validate_cues(cues, &taxonomy);
+        enforce_cardinality(
+            &mut report,
+            &taxonomy,
+            |cue| self.main.has_cue(cue),
+            |key| self.main.key_cardinality(key),
+        );
+        let waived = apply_enforcement(&mut report, taxonomy.enforcement);
+        if waived > 0 && taxonomy.enforcement == Enforcement::Warn {
+            let violations = &report.flagged[report.flagged.len() - waived..];
+            let cues: Vec<&str> = violations.iter().map(|violation| violation.cue.as_str()).collect();
+            info!("Taxonomy (warn): accepted {} cues strict enforcement would reject: {}", waived, cues.join(", "));
+            self.enforcement_warnings.record(violations);
+        }
+        report
+    }
+
+    /// Replace the project taxonomy. Clears the query cache since cached
+    /// resolutions were validated against the old rules.
+    pub fn set_taxonomy(&self, taxonomy: Taxonomy) {
+        *self.taxonomy.write().unwrap() = taxonomy;
+        // Counted against the old rules
+        self.enforcement_warnings.reset();
+        self.query_cache.clear();
+        self.persist_config();
+    }
+
+    /// Allow `key` (and `value`, when given) in the taxonomy, then re-validate
+    /// the matching quarantined cues and attach the accepted ones to their
+    /// memories. Cues of memories that no longer exist are dropped.
+    pub fn promote_rejected(&self, key: &str, value: Option<&str>) -> Promotion {
+        let mut taxonomy = self.taxonomy();
+        if !taxonomy.allowed_keys.is_empty() && !taxonomy.allowed_keys.iter().any(|k| k == key) {
+            taxonomy.allowed_keys.push(key.to_string());
+        }
+        if let Some(value) = value {
+            let constrained = taxonomy.allowed_values.contains_key(key) || taxonomy.allowed_value_prefixes.contains_key(key);
+            if constrained && validate_cues(vec![format!("{}:{}", key, value)], &taxonomy).accepted.is_empty() {
+                taxonomy.allowed_values.entry(key.to_string()).or_default().push(value.to_string());
+            }
+        }
+        self.set_taxonomy(taxonomy);
+
+        let mut promotion = Promotion { reattached: Vec::new(), still_rejected: 0 };
+        for entry in self.rejections.take(key, value) {
+            let report = self.validate_cues(vec![entry.cue.clone()]);
+            if !report.rejected.is_empty() {
+                self.rejections.record(&entry.memory_id, &report.rejected);
+                promotion.still_rejected += 1;
+            } else if self.main.attach_cues(&entry.memory_id, report.accepted) {
+                promotion.reattached.push(entry);
+            }
+        }
+        promotion
+    }
+
+    /// Mark `key` as deprecated in favour of `replacement`.
+    /// Clears the query cache since cached resolutions may carry the old key.
+    pub fn deprecate_key(&self, key: &str, replacement: &str) {
+        self.taxonomy
+            .write()
+            .unwrap()
+            .deprecated_keys
+            .insert(key.to_string(), replacement.to_string());
+        self.query_cache.clear();
+        self.persist_config();
+    }
     
+    /// Changes whenever the lexicon, alias engine or shared vocabulary is
+    /// modified; tags query cache entries.
+    fn resolution_generation(&self) -> u64 {
+        let shared = self.shared.as_ref().map_or(0, |shared| shared.revision());
+        self.lexicon.revision().wrapping_add(self.aliases.revision()).wrapping_add(shared)
+    }
+
     pub fn resolve_cues_from_text(&self, text: &str) -> Vec<String> {
+        self.resolve_weighted_cues_from_text(text).into_iter().map(|(cue, _)| cue).collect()
+    }
+
+    /// Cues resolved from free text through the lexicon, each weighted by its
+    /// lexicon score relative to the best hit. Hits scoring under
+    /// `LEXICON_MIN_RELATIVE_SCORE` of the best are dropped, so a short query
+    /// doesn't pull in every cue that shares one of its tokens. Shared
+    /// vocabulary terms are weighed the same way against each other and only
+    /// add cues the project's lexicon did not resolve.
+    #[tracing::instrument(name = "lexicon_resolve", skip_all)]
+    pub fn resolve_weighted_cues_from_text(&self, text: &str) -> Vec<(String, f64)> {
         let normalized_text = crate::nl::normalize_text(text);
         
-        // Check cache
-        if let Some(cues) = self.query_cache.get(&normalized_text) {
-            return cues.clone();
+        // Check cache (entries resolved before the last lexicon/alias change miss)
+        let generation = self.resolution_generation();
+        if let Some(cues) = self.query_cache.get(&normalized_text, generation) {
+            return cues;
         }
         
         // Tokenize
-        let tokens = crate::nl::tokenize_to_cues(text);
+        let tokens = self.tokenize(text);
         
         if tokens.is_empty() {
             return Vec::new();
         }
         
-        // Query lexicon (limit 8, auto_reinforce true)
-        let lexicon_results = self.lexicon.recall(tokens, 8, true);
+        // Query lexicon (limit 8, auto_reinforce true); result.content is the canonical cue
+        let lexicon_results: Vec<(String, f64)> = self
+            .lexicon
+            .recall(tokens.clone(), 8, true)
+            .into_iter()
+            .map(|result| (result.content, result.score))
+            .collect();
+        let mut weights: HashMap<String, f64> = HashMap::new();
+        let mut contents = Vec::new();
+        self.collect_lexicon_hits(lexicon_results, &mut weights, &mut contents);
         
-        let mut canonical_cues = Vec::new();
-        for result in lexicon_results {
-            // result.content is the canonical cue
-            let (normalized, _) = crate::normalization::normalize_cue(&result.content, &self.normalization);
-            canonical_cues.push(normalized);
+        // Shared terms only add cues the project's lexicon did not resolve
+        if let Some(shared) = &self.shared {
+            let local: HashSet<String> = weights.keys().cloned().collect();
+            let mut shared_weights: HashMap<String, f64> = HashMap::new();
+            let mut shared_contents = Vec::new();
+            self.collect_lexicon_hits(shared.resolve(tokens), &mut shared_weights, &mut shared_contents);
+            for cue in shared_contents {
+                if !local.contains(&cue) {
+                    weights.insert(cue.clone(), shared_weights[&cue]);
+                    contents.push(cue);
+                }
+            }
         }
         
         // Validate list
-        let report = crate::taxonomy::validate_cues(canonical_cues, &self.taxonomy);
-        let accepted = report.accepted;
+        let taxonomy = self.taxonomy.read().unwrap();
+        let mut report = validate_cues(contents, &taxonomy);
+        apply_enforcement(&mut report, taxonomy.enforcement);
+        drop(taxonomy);
+        let renamed: HashMap<&str, &str> = report.rewritten.iter().map(|r| (r.to.as_str(), r.from.as_str())).collect();
+        let mut accepted: Vec<(String, f64)> = Vec::new();
+        for cue in report.accepted {
+            let source = renamed.get(cue.as_str()).copied().unwrap_or(&cue);
+            let weight = weights.get(source).copied().unwrap_or(1.0);
+            merge_weighted_cue(&mut accepted, cue, weight);
+        }
         
         // Cache
-        self.query_cache.insert(normalized_text, accepted.clone());
+        self.query_cache.insert(normalized_text, accepted.clone(), generation);
         
         accepted
     }
+
+    /// Normalize the cues of lexicon `(cue, score)` hits into `contents`, each
+    /// weighted in `weights` by its score relative to the best hit.
+    fn collect_lexicon_hits(&self, hits: Vec<(String, f64)>, weights: &mut HashMap<String, f64>, contents: &mut Vec<String>) {
+        let best = hits.iter().map(|(_, score)| *score).fold(0.0, f64::max);
+        for (cue, score) in hits {
+            let weight = if best > 0.0 { score / best } else { 1.0 };
+            if weight < LEXICON_MIN_RELATIVE_SCORE {
+                continue;
+            }
+            let cue = self.normalize_cues(std::slice::from_ref(&cue)).remove(0);
+            let entry = weights.entry(cue.clone()).or_insert(0.0);
+            *entry = entry.max(weight);
+            contents.push(cue);
+        }
+    }
+
+    /// Replace words of `text` that the lexicon has never seen with the
+    /// closest lexicon token, one edit away (two for words of
+    /// `SPELL_LONG_WORD_LEN` or more). Ties go to the token with more entries.
+    /// Returns the normalized, corrected text and the corrections made.
+    #[tracing::instrument(name = "spell_correct", skip_all)]
+    pub fn correct_spelling(&self, text: &str) -> (String, Vec<SpellingCorrection>) {
+        let normalized_text = crate::nl::normalize_text(text);
+        let tokenizer = self.tokenizer.read().unwrap();
+        let mut corrections: Vec<SpellingCorrection> = Vec::new();
+        let mut words: Vec<String> = Vec::new();
+
+        for word in normalized_text.split_whitespace() {
+            let skip = word.chars().count() < SPELL_MIN_WORD_LEN
+                || word.chars().any(|c| c.is_numeric() || crate::nl::is_cjk(c))
+                || tokenizer.is_stopword(word, None)
+                || self.lexicon.cue_len(&format!("tok:{}", word)) > 0;
+            if skip {
+                words.push(word.to_string());
+                continue;
+            }
+            let max_distance = if word.chars().count() >= SPELL_LONG_WORD_LEN { 2 } else { 1 };
+            match self.closest_lexicon_word(word, max_distance) {
+                Some((to, distance)) => {
+                    corrections.push(SpellingCorrection { from: word.to_string(), to: to.clone(), distance });
+                    words.push(to);
+                }
+                None => words.push(word.to_string()),
+            }
+        }
+
+        (words.join(" "), corrections)
+    }
+
+    /// The lexicon token within `max_distance` edits of `word`: closest first,
+    /// then the one with most entries, then alphabetical.
+    fn closest_lexicon_word(&self, word: &str, max_distance: usize) -> Option<(String, usize)> {
+        let mut best: Option<(usize, Reverse<usize>, String)> = None;
+        for entry in self.lexicon.get_cue_index().iter() {
+            let Some(candidate) = entry.key().strip_prefix("tok:") else { continue };
+            let entries = entry.value().len();
+            if entries == 0 {
+                continue;
+            }
+            let Some(distance) = crate::nl::edit_distance(word, candidate, max_distance) else { continue };
+            let rank = (distance, Reverse(entries), candidate.to_string());
+            if best.as_ref().is_none_or(|current| rank < *current) {
+                best = Some(rank);
+            }
+        }
+        best.map(|(distance, _, word)| (word, distance))
+    }
+
+    /// Cues of a query: the normalized `cues` at full weight, then the cues
+    /// resolved from `text` at their resolution weight. A cue given both ways
+    /// keeps the higher weight.
+    pub fn query_cues(&self, cues: &[String], text: Option<&str>) -> Vec<(String, f64)> {
+        let mut query: Vec<(String, f64)> = Vec::new();
+        for cue in self.normalize_cues(cues) {
+            merge_weighted_cue(&mut query, cue, 1.0);
+        }
+        if let Some(text) = text {
+            for (cue, weight) in self.resolve_weighted_cues_from_text(text) {
+                merge_weighted_cue(&mut query, cue, weight);
+            }
+        }
+        query
+    }
     
     pub fn expand_query_cues(&self, cues: Vec<String>) -> Vec<(String, f64)> {
-        let mut expanded: Vec<(String, f64)> = Vec::new();
-        
-        for cue in cues {
-            // 1. Add original cue with weight 1.0
-            expanded.push((cue.clone(), 1.0));
-            
-            // 2. Query aliases
-            let alias_query = vec![
-                "type:alias".to_string(),
-                format!("from:{}", cue),
-                "status:active".to_string(),
-            ];
-            
-            // Recall aliases (limit 8, auto_reinforce false to avoid noise)
-            let aliases = self.aliases.recall(alias_query, 8, false);
-            
-            for alias in aliases {
-                // Parse alias content to get target cue and weight
-                if let Ok(data) = serde_json::from_str::<Value>(&alias.content) {
-                     // STRICT FILTER: Check if 'from' matches the current cue exactly
-                     if let Some(from_val) = data.get("from").and_then(|v| v.as_str()) {
-                         if from_val != cue {
-                             continue;
-                         }
-                     }
-
-                     if let Some(to_cue) = data.get("to").and_then(|v| v.as_str()) {
-                         // Default downweight 0.85 if not specified
-                         let downweight = data.get("downweight").and_then(|v| v.as_f64()).unwrap_or(0.85);
-                         
-                         // The "to" field in content is the actual cue, e.g., "service:payments"
-                         expanded.push((to_cue.to_string(), downweight));
-                     }
+        self.expand_weighted_query_cues(cues.into_iter().map(|cue| (cue, 1.0)).collect())
+    }
+
+    /// Like [`Self::expand_query_cues`], for query cues that carry a weight
+    /// (e.g. from [`Self::query_cues`]); aliases scale the weight of their source.
+    pub fn expand_weighted_query_cues(&self, cues: Vec<(String, f64)>) -> Vec<(String, f64)> {
+        self.expand_query_paths(cues, alias_max_depth())
+            .into_iter()
+            .map(|expansion| (expansion.cue, expansion.weight))
+            .collect()
+    }
+
+    /// Expand query cues through active aliases, following chains such as
+    /// `pay` → `payments` → `service:payments` up to `max_depth` hops.
+    /// Weights multiply along a chain. Each cue appears once, reached by its
+    /// shortest chain, so alias cycles end where they loop back. Shared
+    /// vocabulary aliases are followed after the project's own.
+    #[tracing::instrument(name = "alias_expand", skip_all, fields(cues = cues.len()))]
+    pub fn expand_query_paths(&self, cues: Vec<(String, f64)>, max_depth: usize) -> Vec<CueExpansion> {
+        let taxonomy = self.taxonomy.read().unwrap().clone();
+        // Queries using a deprecated key resolve to the replacement key
+        let cues: Vec<(String, f64)> = cues
+            .into_iter()
+            .map(|(cue, weight)| (taxonomy.rewrite_deprecated(&cue).unwrap_or(cue), weight))
+            .collect();
+
+        // Query cues keep their own weight even when an alias also leads to them
+        let mut seen: HashSet<String> = cues.iter().map(|(cue, _)| cue.clone()).collect();
+        let mut expanded: Vec<CueExpansion> = Vec::new();
+
+        for (cue, weight) in cues {
+            let start = expanded.len();
+            expanded.push(CueExpansion { cue: cue.clone(), weight, path: vec![cue] });
+
+            // Breadth-first, so a cue is reached by its shortest chain
+            let mut frontier = start..expanded.len();
+            for _ in 0..max_depth {
+                let next_start = expanded.len();
+                for i in frontier {
+                    let from = expanded[i].clone();
+                    let mut targets: Vec<(String, f64)> = Vec::new();
+                    for (alias_id, to_cue, downweight) in self.active_aliases_from(&from.cue) {
+                        self.record_alias_use(&alias_id, &to_cue);
+                        targets.push((to_cue, downweight));
+                    }
+                    if let Some(shared) = &self.shared {
+                        targets.extend(shared.aliases_from(&from.cue));
+                    }
+                    for (to_cue, downweight) in targets {
+                        if !seen.insert(to_cue.clone()) {
+                            continue;
+                        }
+                        let mut path = from.path.clone();
+                        path.push(to_cue.clone());
+                        expanded.push(CueExpansion { cue: to_cue, weight: from.weight * downweight, path });
+                    }
+                }
+                frontier = next_start..expanded.len();
+                if frontier.is_empty() {
+                    break;
                 }
             }
         }
-        
+
         expanded
     }
+
+    /// `(id, to, downweight)` of active aliases that expand `cue`.
+    fn active_aliases_from(&self, cue: &str) -> Vec<(String, String, f64)> {
+        let alias_query = vec![
+            "type:alias".to_string(),
+            format!("from:{}", cue),
+            "status:active".to_string(),
+        ];
+
+        // Recall aliases (limit 8, auto_reinforce false to avoid noise)
+        self.aliases
+            .recall(alias_query, 8, false)
+            .into_iter()
+            .filter_map(|alias| {
+                let data: Value = serde_json::from_str(&alias.content).ok()?;
+                // STRICT FILTER: Check if 'from' matches the current cue exactly
+                if data.get("from").and_then(|v| v.as_str()).is_some_and(|from| from != cue) {
+                    return None;
+                }
+                // Recall also ranks proposed and expired aliases that share the from cue
+                if data.get("status").and_then(|v| v.as_str()).is_some_and(|status| status != "active") {
+                    return None;
+                }
+                // The "to" field in content is the actual cue, e.g., "service:payments"
+                let to_cue = data.get("to")?.as_str()?.to_string();
+                // Default downweight 0.85 if not specified
+                let downweight = data.get("downweight").and_then(|v| v.as_f64()).unwrap_or(0.85);
+                Some((alias.memory_id, to_cue, downweight))
+            })
+            .collect()
+    }
+
+    /// Count an expansion through an alias, and a hit when its target cue
+    /// has memories.
+    fn record_alias_use(&self, alias_id: &str, to_cue: &str) {
+        let hit = self.main.cue_len(to_cue) > 0;
+        let now = now_secs();
+        self.aliases.update_metadata(alias_id, |metadata| {
+            AliasUsage::update(metadata, |usage| {
+                usage.expansions += 1;
+                if hit {
+                    usage.hits += 1;
+                    usage.last_hit_at = Some(now);
+                }
+            })
+        });
+    }
+
+    /// `(id, from, to)` of active aliases that expand to `to_cue`.
+    fn active_aliases_to(&self, to_cue: &str) -> Vec<(String, String, String)> {
+        let query = vec!["type:alias".to_string(), format!("to:{}", to_cue), "status:active".to_string()];
+        self.aliases
+            .recall(query, 50, false)
+            .into_iter()
+            .filter_map(|alias| {
+                let data: Value = serde_json::from_str(&alias.content).ok()?;
+                let from = data.get("from")?.as_str()?.to_string();
+                let to = data.get("to")?.as_str()?.to_string();
+                (to == to_cue).then_some((alias.memory_id, from, to))
+            })
+            .collect()
+    }
+
+    /// Attribute feedback on a memory to the aliases that reached it: active
+    /// aliases from one of the query's `expanded_cues` to one of the memory's
+    /// `matched_cues`. Returns how many aliases were credited.
+    pub fn record_alias_feedback(&self, expanded_cues: &[(String, f64)], matched_cues: &[String], useful: bool) -> usize {
+        let query_cues: HashSet<String> = expanded_cues.iter().map(|(cue, _)| cue.to_lowercase()).collect();
+        let mut credited = 0;
+        for cue in matched_cues {
+            for (alias_id, from, _) in self.active_aliases_to(&cue.to_lowercase()) {
+                if !query_cues.contains(&from.to_lowercase()) {
+                    continue;
+                }
+                let updated = self.aliases.update_metadata(&alias_id, |metadata| {
+                    AliasUsage::update(metadata, |usage| {
+                        if useful {
+                            usage.useful += 1;
+                        } else {
+                            usage.harmful += 1;
+                        }
+                    })
+                });
+                credited += usize::from(updated.is_some());
+            }
+        }
+        credited
+    }
+
+    /// Every alias of the project with its usage, most hits first.
+    pub fn alias_stats(&self) -> Vec<AliasStats> {
+        let mut stats: Vec<AliasStats> = self
+            .aliases
+            .get_memories()
+            .iter()
+            .filter(|alias| alias.cues.iter().any(|cue| &**cue == "type:alias"))
+            .filter_map(|alias| {
+                let data: Value = serde_json::from_str(&alias.content).ok()?;
+                let status = if alias.cues.iter().any(|cue| &**cue == "status:active") { "active" } else { "proposed" };
+                let usage = AliasUsage::of(&alias.metadata);
+                Some(AliasStats {
+                    id: alias.id.clone(),
+                    from: data.get("from")?.as_str()?.to_string(),
+                    to: data.get("to")?.as_str()?.to_string(),
+                    status: status.to_string(),
+                    created_at: alias.created_at,
+                    precision: usage.precision(),
+                    usage,
+                })
+            })
+            .collect();
+        stats.sort_by(|a, b| b.usage.hits.cmp(&a.usage.hits).then_with(|| a.id.cmp(&b.id)));
+        stats
+    }
+
+    /// Revert active aliases to proposed when their target matched nothing
+    /// for `ALIAS_IDLE_DAYS` (counted from creation until the first hit), or
+    /// when at least `ALIAS_MIN_FEEDBACK` judged memories put their precision
+    /// under `ALIAS_MIN_PRECISION`. Usage is kept with the reverted alias.
+    pub fn expire_aliases(&self, now: f64) -> Vec<ExpiredAlias> {
+        let idle_before = now - (ALIAS_IDLE_DAYS * 86_400) as f64;
+        let expired: Vec<ExpiredAlias> = self
+            .alias_stats()
+            .into_iter()
+            .filter(|alias| alias.status == "active")
+            .filter_map(|alias| {
+                let reason = if alias.usage.last_hit_at.unwrap_or(alias.created_at) < idle_before {
+                    "unused"
+                } else if alias.usage.useful + alias.usage.harmful >= ALIAS_MIN_FEEDBACK
+                    && alias.precision.is_some_and(|precision| precision < ALIAS_MIN_PRECISION)
+                {
+                    "low_precision"
+                } else {
+                    return None;
+                };
+                Some(ExpiredAlias { id: alias.id, from: alias.from, to: alias.to, reason })
+            })
+            .collect();
+
+        for alias in &expired {
+            let Some(memory) = self.aliases.get_memory(&alias.id) else { continue };
+            let mut content: Value = serde_json::from_str(&memory.content).unwrap_or_default();
+            content["status"] = serde_json::json!("proposed");
+            let cues: Vec<String> = memory
+                .cues
+                .iter()
+                .map(|cue| if &**cue == "status:active" { "status:proposed".to_string() } else { cue.to_string() })
+                .collect();
+            let mut metadata = memory.metadata.clone();
+            metadata.insert("expired".to_string(), serde_json::json!({"reason": alias.reason, "at": now}));
+            self.aliases.delete_memory(&alias.id);
+            self.aliases.upsert_memory_with_id(alias.id.clone(), content.to_string(), cues, Some(metadata), false);
+            info!("Expired alias {} -> {} ({})", alias.from, alias.to, alias.reason);
+        }
+        expired
+    }
+}
+
+/// Add `cue` to `cues`, or raise its weight if it is already there.
+fn merge_weighted_cue(cues: &mut Vec<(String, f64)>, cue: String, weight: f64) {
+    match cues.iter_mut().find(|(existing, _)| *existing == cue) {
+        Some((_, existing)) => *existing = existing.max(weight),
+        None => cues.push((cue, weight)),
+    }
+}
+
+fn now_secs() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
 }
 
 pub struct ProjectStore {
@@ -0,0 +1,177 @@
+//! Where agent-ingested memories came from (`GET /memories/:id/provenance`).
+//!
+//! `ExtractAndIngest` stores a [`Provenance`] under the `provenance` metadata
+//! key of every memory it creates: the file and chunk it read, the model and
+//! prompt version that extracted it, and the job that ran. Summaries carry
+//! no provenance of their own but list the memories they were consolidated
+//! from, so the chain of a summary is the chains of its sources.
+//!
+//! Grouped by file, the same records list what the agent learned from each
+//! document (`GET /agent/files` and `GET /agent/files/memories`).
+
+use crate::engine::CueMapEngine;
+use crate::llm::{LlmConfig, EXTRACT_PROMPT_VERSION};
+use crate::structures::{Memory, CONSOLIDATED_INTO_KEY};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Metadata key the provenance of agent-ingested memories is stored under.
+pub const PROVENANCE_KEY: &str = "provenance";
+
+/// Metadata key summaries list their source memories under.
+const CONSOLIDATED_FROM_KEY: &str = "consolidated_from";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Path of the file, as in the memory's `path:` cue
+    pub file_path: String,
+    /// SHA-256 of the chunk content, also part of the memory id
+    pub chunk_hash: String,
+    /// Bytes of the file the chunk was read from, when the chunk is a
+    /// verbatim slice of it (not for PDFs or rewritten sections)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub byte_range: Option<Range<usize>>,
+    /// `provider/model` of the LLM that extracted the memory
+    #[serde(default)]
+    pub extraction_model: String,
+    /// Version of the extraction prompt, see [`EXTRACT_PROMPT_VERSION`]
+    #[serde(default)]
+    pub prompt_version: u32,
+    pub job_id: String,
+    #[serde(default)]
+    pub extracted_at: f64,
+}
+
+impl Provenance {
+    /// Provenance of a chunk about to be queued for extraction.
+    pub fn new(file_path: String, chunk_hash: String, byte_range: Option<Range<usize>>, job_id: String) -> Self {
+        Self {
+            file_path,
+            chunk_hash,
+            byte_range,
+            extraction_model: String::new(),
+            prompt_version: 0,
+            job_id,
+            extracted_at: 0.0,
+        }
+    }
+
+    /// Record that `config` extracted the memory just now.
+    pub fn extracted_by(mut self, config: &LlmConfig) -> Self {
+        self.extraction_model = format!("{}/{}", config.provider, config.model);
+        self.prompt_version = EXTRACT_PROMPT_VERSION;
+        self.extracted_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs_f64()).unwrap_or(0.0);
+        self
+    }
+
+    /// The provenance stored on `memory`, if the agent created it.
+    pub fn of(memory: &Memory) -> Option<Self> {
+        memory.metadata.get(PROVENANCE_KEY).and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+}
+
+/// One memory in a provenance chain.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProvenanceNode {
+    pub memory_id: String,
+    /// `None` for memories the agent did not extract, such as API writes
+    /// and summaries
+    pub provenance: Option<Provenance>,
+    /// The summary this memory was consolidated into, if it was
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consolidated_into: Option<String>,
+    /// Chains of the memories this one summarizes
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<ProvenanceNode>,
+    /// Set for sources that have since been deleted
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub missing: bool,
+}
+
+/// The provenance chain of `memory_id`, or `None` when it does not exist.
+pub fn chain(engine: &CueMapEngine, memory_id: &str) -> Option<ProvenanceNode> {
+    let memory = engine.get_memory(memory_id)?;
+    Some(node(engine, memory, &mut HashSet::new()))
+}
+
+fn node(engine: &CueMapEngine, memory: Memory, visited: &mut HashSet<String>) -> ProvenanceNode {
+    visited.insert(memory.id.clone());
+    let source_ids: Vec<String> = memory
+        .metadata
+        .get(CONSOLIDATED_FROM_KEY)
+        .and_then(|value| serde_json::from_value(value.clone()).ok())
+        .unwrap_or_default();
+    let mut sources = Vec::new();
+    for id in source_ids {
+        if visited.contains(&id) {
+            continue;
+        }
+        sources.push(match engine.get_memory(&id) {
+            Some(source) => node(engine, source, visited),
+            None => ProvenanceNode {
+                memory_id: id,
+                provenance: None,
+                consolidated_into: None,
+                sources: Vec::new(),
+                missing: true,
+            },
+        });
+    }
+    ProvenanceNode {
+        provenance: Provenance::of(&memory),
+        consolidated_into: memory.metadata.get(CONSOLIDATED_INTO_KEY).and_then(|value| value.as_str()).map(str::to_string),
+        memory_id: memory.id,
+        sources,
+        missing: false,
+    }
+}
+
+/// A file the agent has ingested, as recorded by its memories' provenance.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AgentFile {
+    pub path: String,
+    /// Chunks of the file the agent extracted a memory from
+    pub chunks: usize,
+    /// Those memories not since consolidated into a summary
+    pub memories: usize,
+    /// When the most recent of them was extracted
+    pub last_ingested_at: f64,
+}
+
+/// Files the agent has ingested into `engine`, by path.
+pub fn agent_files(engine: &CueMapEngine) -> Vec<AgentFile> {
+    let mut files: BTreeMap<String, AgentFile> = BTreeMap::new();
+    for entry in engine.get_memories().iter() {
+        let Some(provenance) = Provenance::of(entry.value()) else { continue };
+        let file = files.entry(provenance.file_path.clone()).or_insert_with(|| AgentFile {
+            path: provenance.file_path,
+            chunks: 0,
+            memories: 0,
+            last_ingested_at: 0.0,
+        });
+        file.chunks += 1;
+        if !entry.value().is_tombstoned() {
+            file.memories += 1;
+        }
+        file.last_ingested_at = file.last_ingested_at.max(provenance.extracted_at);
+    }
+    files.into_values().collect()
+}
+
+/// Memories the agent extracted from the file at `path`, consolidated ones
+/// included, in file order.
+pub fn file_memories(engine: &CueMapEngine, path: &str) -> Vec<Memory> {
+    let mut memories: Vec<(Provenance, Memory)> = engine
+        .get_memories()
+        .iter()
+        .filter_map(|entry| Provenance::of(entry.value()).map(|provenance| (provenance, entry.value().clone())))
+        .filter(|(provenance, _)| provenance.file_path == path)
+        .collect();
+    memories.sort_by(|(a, a_memory), (b, b_memory)| {
+        let start = |provenance: &Provenance| provenance.byte_range.as_ref().map_or(usize::MAX, |range| range.start);
+        start(a).cmp(&start(b)).then_with(|| a_memory.id.cmp(&b_memory.id))
+    });
+    memories.into_iter().map(|(_, memory)| memory).collect()
+}
@@ -0,0 +1,175 @@
+//! Python bindings for the embedded engine (`python` feature).
+//!
+//! Exposes [`CueMap`] as the `cuemap` extension module, so Python agents can
+//! recall in-process instead of over HTTP. Build and install it with maturin
+//! from the repository root:
+//!
+//! ```text
+//! pip install maturin
+//! maturin develop --release
+//! ```
+//!
+//! ```python
+//! import cuemap
+//!
+//! cm = cuemap.CueMap("./data")
+//! added = cm.add("Payments timed out after the deploy", ["service:payments", "error:timeout"])
+//! results = cm.recall(["service:payments"], limit=5)
+//! grounded = cm.recall_grounded("why did payments time out", token_budget=300)
+//! cm.snapshot()
+//! ```
+//!
+//! Results are plain dicts and lists with the same fields as the REST
+//! responses. Engine calls release the GIL while they run.
+
+use crate::embedded::{CueMap, GroundedQuery, RecallQuery, DEFAULT_TRACE_RETENTION};
+use crate::grounding::FreshnessPolicy;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// A single CueMap project; see `cuemap_rust::CueMap`.
+#[pyclass(name = "CueMap", module = "cuemap", frozen)]
+pub struct PyCueMap {
+    inner: CueMap,
+}
+
+#[pymethods]
+impl PyCueMap {
+    /// Open (or create) `data_dir`, or keep everything in memory without one.
+    #[new]
+    #[pyo3(signature = (data_dir=None, trace_retention=DEFAULT_TRACE_RETENTION))]
+    fn new(data_dir: Option<PathBuf>, trace_retention: usize) -> PyResult<Self> {
+        let mut builder = CueMap::builder().trace_retention(trace_retention);
+        if let Some(dir) = data_dir {
+            builder = builder.data_dir(dir);
+        }
+        let inner = builder.open().map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        Ok(Self { inner })
+    }
+
+    /// Store a memory. Returns `{"id", "rejected_cues", "rewritten_cues", "flagged_cues"}`.
+    #[pyo3(signature = (content, cues, metadata=None, disable_temporal_chunking=false))]
+    fn add(
+        &self,
+        py: Python<'_>,
+        content: String,
+        cues: Vec<String>,
+        metadata: Option<Bound<'_, PyAny>>,
+        disable_temporal_chunking: bool,
+    ) -> PyResult<Py<PyAny>> {
+        let metadata: Option<HashMap<String, serde_json::Value>> = match metadata {
+            Some(obj) => Some(from_python(py, &obj)?),
+            None => None,
+        };
+        let outcome = py
+            .detach(|| self.inner.add_with(content, cues, metadata, disable_temporal_chunking))
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        to_python(
+            py,
+            &serde_json::json!({
+                "id": outcome.id,
+                "rejected_cues": outcome.rejected,
+                "rewritten_cues": outcome.rewritten,
+                "flagged_cues": outcome.flagged,
+            }),
+        )
+    }
+
+    /// Memories best matching `cues` (plus cues resolved from `query_text`),
+    /// strongest first.
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (cues=Vec::new(), limit=10, query_text=None, auto_reinforce=false, min_intersection=None, explain=false))]
+    fn recall(
+        &self,
+        py: Python<'_>,
+        cues: Vec<String>,
+        limit: usize,
+        query_text: Option<String>,
+        auto_reinforce: bool,
+        min_intersection: Option<usize>,
+        explain: bool,
+    ) -> PyResult<Py<PyAny>> {
+        let query = RecallQuery {
+            cues,
+            query_text,
+            limit,
+            auto_reinforce,
+            min_intersection,
+            explain,
+            ..Default::default()
+        };
+        let results = py.detach(|| self.inner.recall_with(&query));
+        to_python(py, &results)
+    }
+
+    /// Context selected within `token_budget`. Returns `{"verified_context", "proof"}`.
+    #[pyo3(signature = (query_text, token_budget=500, limit=10, max_age_days=None, prefer_fresh=false))]
+    fn recall_grounded(
+        &self,
+        py: Python<'_>,
+        query_text: String,
+        token_budget: u32,
+        limit: usize,
+        max_age_days: Option<f64>,
+        prefer_fresh: bool,
+    ) -> PyResult<Py<PyAny>> {
+        let query = GroundedQuery {
+            token_budget,
+            limit,
+            freshness: FreshnessPolicy { max_age_days, prefer_fresh },
+            ..GroundedQuery::new(query_text)
+        };
+        let grounded = py.detach(|| self.inner.recall_grounded(&query));
+        to_python(
+            py,
+            &serde_json::json!({
+                "verified_context": grounded.verified_context,
+                "proof": grounded.proof,
+            }),
+        )
+    }
+
+    /// Strengthen a memory. False if it does not exist.
+    #[pyo3(signature = (memory_id, cues=Vec::new()))]
+    fn reinforce(&self, py: Python<'_>, memory_id: &str, cues: Vec<String>) -> bool {
+        py.detach(|| self.inner.reinforce(memory_id, cues))
+    }
+
+    /// Remove a memory. False if it does not exist.
+    fn delete(&self, py: Python<'_>, memory_id: &str) -> bool {
+        py.detach(|| self.inner.delete(memory_id))
+    }
+
+    /// Write all memories to the data directory.
+    fn snapshot(&self, py: Python<'_>) -> PyResult<()> {
+        py.detach(|| self.inner.snapshot())
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+/// The `cuemap` Python module.
+#[pymodule]
+pub fn cuemap(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyCueMap>()?;
+    Ok(())
+}
+
+// Values cross the boundary through the stdlib `json` module, which keeps
+// the Python shapes identical to the REST responses.
+
+fn to_python(py: Python<'_>, value: &impl Serialize) -> PyResult<Py<PyAny>> {
+    let json = serde_json::to_string(value).map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+    Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+fn from_python<T: serde::de::DeserializeOwned>(py: Python<'_>, obj: &Bound<'_, PyAny>) -> PyResult<T> {
+    let json: String = py.import("json")?.call_method1("dumps", (obj,))?.extract()?;
+    serde_json::from_str(&json).map_err(|e| PyValueError::new_err(e.to_string()))
+}
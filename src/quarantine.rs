@@ -0,0 +1,167 @@
+//! Quarantine of cues rejected by taxonomy validation.
+//!
+//! Ingest reports rejected cues once and stores the memory without them. The
+//! quarantine keeps the most recent rejections of a project together with the
+//! memory they came from, so `GET /taxonomy/rejections` can show which keys
+//! the taxonomy keeps turning away and a promoted key or value can be
+//! re-attached to the memories that carried it.
+
+use crate::taxonomy::RejectedCue;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::sync::Mutex;
+
+/// A rejected cue and the memory it was submitted with.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuarantinedCue {
+    pub cue: String,
+    pub memory_id: String,
+    /// Rejection code, as in [`RejectedCue::code`]
+    pub code: String,
+    pub detail: String,
+    /// Seconds since the epoch
+    pub rejected_at: f64,
+}
+
+impl QuarantinedCue {
+    /// The cue's key; the whole cue when it has no `key:value` form.
+    pub fn key(&self) -> &str {
+        self.cue.split_once(':').map_or(self.cue.as_str(), |(key, _)| key)
+    }
+
+    pub fn value(&self) -> Option<&str> {
+        self.cue.split_once(':').map(|(_, value)| value)
+    }
+}
+
+/// Rejections of one key, as listed by `GET /taxonomy/rejections`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyRejections {
+    pub key: String,
+    pub count: usize,
+    /// Distinct memories the key was rejected on
+    pub memories: usize,
+    /// Rejections per code
+    pub codes: BTreeMap<String, usize>,
+    /// Rejections per value
+    pub values: BTreeMap<String, usize>,
+}
+
+/// Bounded per-project store of rejected cues; the oldest are dropped first.
+pub struct RejectionQuarantine {
+    entries: Mutex<VecDeque<QuarantinedCue>>,
+    capacity: usize,
+}
+
+impl RejectionQuarantine {
+    pub fn new(capacity: usize) -> Self {
+        Self { entries: Mutex::new(VecDeque::new()), capacity: capacity.max(1) }
+    }
+
+    /// Quarantine the cues rejected on `memory_id`. A cue already
+    /// quarantined for the memory is refreshed instead of repeated.
+    pub fn record(&self, memory_id: &str, rejected: &[RejectedCue]) {
+        if rejected.is_empty() {
+            return;
+        }
+        let rejected_at = now_secs();
+        let mut entries = self.entries.lock().unwrap();
+        for cue in rejected {
+            entries.retain(|entry| entry.memory_id != memory_id || entry.cue != cue.cue);
+            if entries.len() == self.capacity {
+                entries.pop_front();
+            }
+            entries.push_back(QuarantinedCue {
+                cue: cue.cue.clone(),
+                memory_id: memory_id.to_string(),
+                code: cue.code.clone(),
+                detail: cue.detail.clone(),
+                rejected_at,
+            });
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Up to `limit` quarantined cues, newest first, optionally of one key only.
+    pub fn entries(&self, key: Option<&str>, limit: usize) -> Vec<QuarantinedCue> {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .filter(|entry| key.is_none_or(|key| entry.key() == key))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Rejection counts per key, most rejected first.
+    pub fn by_key(&self) -> Vec<KeyRejections> {
+        let entries = self.entries.lock().unwrap();
+        let mut keys: BTreeMap<&str, (KeyRejections, HashSet<&str>)> = BTreeMap::new();
+        for entry in entries.iter() {
+            let (counts, memories) = keys.entry(entry.key()).or_insert_with(|| {
+                let counts = KeyRejections {
+                    key: entry.key().to_string(),
+                    count: 0,
+                    memories: 0,
+                    codes: BTreeMap::new(),
+                    values: BTreeMap::new(),
+                };
+                (counts, HashSet::new())
+            });
+            counts.count += 1;
+            *counts.codes.entry(entry.code.clone()).or_default() += 1;
+            if let Some(value) = entry.value() {
+                *counts.values.entry(value.to_string()).or_default() += 1;
+            }
+            memories.insert(&entry.memory_id);
+        }
+        let mut by_key: Vec<KeyRejections> = keys
+            .into_values()
+            .map(|(counts, memories)| KeyRejections { memories: memories.len(), ..counts })
+            .collect();
+        by_key.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+        by_key
+    }
+
+    /// Whether any quarantined cue has `key` (and `value`, when given).
+    pub fn contains(&self, key: &str, value: Option<&str>) -> bool {
+        self.entries.lock().unwrap().iter().any(|entry| Self::matches(entry, key, value))
+    }
+
+    /// Remove and return the quarantined cues with `key` (and `value`, when given).
+    pub fn take(&self, key: &str, value: Option<&str>) -> Vec<QuarantinedCue> {
+        let mut entries = self.entries.lock().unwrap();
+        let (taken, kept): (VecDeque<_>, VecDeque<_>) = entries.drain(..).partition(|entry| Self::matches(entry, key, value));
+        *entries = kept;
+        taken.into()
+    }
+
+    /// Drop the cues quarantined on `memory_ids`, which were deleted. Returns
+    /// how many were dropped.
+    pub fn forget_memories(&self, memory_ids: &HashSet<&str>) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|entry| !memory_ids.contains(entry.memory_id.as_str()));
+        before - entries.len()
+    }
+
+    fn matches(entry: &QuarantinedCue, key: &str, value: Option<&str>) -> bool {
+        entry.key() == key && value.is_none_or(|value| entry.value() == Some(value))
+    }
+}
+
+fn now_secs() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
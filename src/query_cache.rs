@@ -0,0 +1,89 @@
+//! Bounded LRU cache of text → cue resolutions.
+//!
+//! Entries expire after a TTL and are tagged with the generation of the
+//! lexicon and alias engines they were resolved against, so retraining
+//! either one makes older entries miss without an explicit flush.
+
+use crate::engine::{string_bytes, with_table_overhead};
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+struct CachedResolution {
+    /// Resolved cues with their resolution weights
+    cues: Vec<(String, f64)>,
+    generation: u64,
+    inserted_at: Instant,
+}
+
+pub struct QueryCache {
+    entries: Mutex<LruCache<String, CachedResolution>>,
+    ttl: Duration,
+}
+
+impl QueryCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            ttl,
+        }
+    }
+
+    /// Cached cues for `text`, if present, unexpired and resolved at `generation`.
+    /// Expired or stale entries are dropped.
+    pub fn get(&self, text: &str, generation: u64) -> Option<Vec<(String, f64)>> {
+        let mut entries = self.entries.lock().unwrap();
+        let fresh = match entries.get(text) {
+            Some(entry) => entry.generation == generation && entry.inserted_at.elapsed() < self.ttl,
+            None => return None,
+        };
+        if fresh {
+            entries.peek(text).map(|entry| entry.cues.clone())
+        } else {
+            entries.pop(text);
+            None
+        }
+    }
+
+    /// Cache a resolution, evicting the least recently used entry when full.
+    pub fn insert(&self, text: String, cues: Vec<(String, f64)>, generation: u64) {
+        self.entries.lock().unwrap().put(text, CachedResolution {
+            cues,
+            generation,
+            inserted_at: Instant::now(),
+        });
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.entries.lock().unwrap().cap().get()
+    }
+
+    /// Approximate heap bytes held by cached entries.
+    pub fn estimated_bytes(&self) -> usize {
+        let entries = self.entries.lock().unwrap();
+        with_table_overhead(
+            entries
+                .iter()
+                .map(|(text, entry)| {
+                    string_bytes(text)
+                        + std::mem::size_of::<CachedResolution>()
+                        + entry.cues.iter().map(|(c, _)| string_bytes(c) + std::mem::size_of::<f64>()).sum::<usize>()
+                })
+                .sum(),
+        )
+    }
+}
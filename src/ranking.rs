@@ -0,0 +1,75 @@
+//! Business-rule re-ranking of recall results (`GET`/`PUT /ranking`).
+//!
+//! A [`RankingRule`] multiplies the score of every memory it matches, after
+//! intersection, recency, frequency and salience scoring and before the top
+//! results are picked. `{"name": "gold", "metadata": {"tier": "gold"},
+//! "multiplier": 1.2}` boosts gold-tier memories and `{"name": "agent",
+//! "cue": "source:agent", "multiplier": 0.8}` demotes agent-ingested ones.
+//! The multipliers of every rule a memory matches compound. Explain output
+//! lists the rules applied to each result.
+
+use crate::structures::Memory;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+
+/// Per-project ranking rules, applied in order.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct RankingConfig {
+    #[serde(default)]
+    pub rules: Vec<RankingRule>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RankingRule {
+    pub name: String,
+    /// A cue the memory must hold, normalized like the project's cues
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cue: Option<String>,
+    /// Metadata values the memory must have, compared as JSON
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, Value>,
+    /// Factor applied to the score; above 1 boosts, below 1 demotes
+    pub multiplier: f64,
+}
+
+impl RankingRule {
+    /// Whether `memory` meets every condition of the rule.
+    pub fn matches(&self, memory: &Memory) -> bool {
+        self.cue.as_deref().is_none_or(|cue| memory.cues.iter().any(|c| &**c == cue))
+            && self.metadata.iter().all(|(key, value)| memory.metadata.get(key) == Some(value))
+    }
+}
+
+impl RankingConfig {
+    /// Every problem with the rules; empty when they can be applied.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut names = HashSet::new();
+        for (i, rule) in self.rules.iter().enumerate() {
+            if rule.name.trim().is_empty() {
+                problems.push(format!("rule {}: name is empty", i + 1));
+            } else if !names.insert(rule.name.as_str()) {
+                problems.push(format!("rule {}: duplicate name {}", i + 1, rule.name));
+            }
+            if rule.cue.is_none() && rule.metadata.is_empty() {
+                problems.push(format!("rule {}: needs a cue or metadata to match", i + 1));
+            }
+            if !(rule.multiplier.is_finite() && rule.multiplier > 0.0) {
+                problems.push(format!("rule {}: multiplier must be a positive number", i + 1));
+            }
+        }
+        problems
+    }
+
+    /// The combined multiplier of the rules `memory` matches, and their names.
+    pub fn apply(&self, memory: &Memory) -> (f64, Vec<String>) {
+        let mut multiplier = 1.0;
+        let mut applied = Vec::new();
+        for rule in self.rules.iter().filter(|rule| rule.matches(memory)) {
+            multiplier *= rule.multiplier;
+            applied.push(rule.name.clone());
+        }
+        (multiplier, applied)
+    }
+}
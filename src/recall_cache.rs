@@ -0,0 +1,125 @@
+//! Short-lived cache of recall responses, per project.
+//!
+//! Agents often repeat the same recall within a session. Responses are keyed
+//! by the expanded query cues and the recall parameters, expire after a
+//! short TTL and are tagged with the main engine's recall generation, so any
+//! write to the project makes older entries miss without an explicit flush.
+//! Off unless a TTL is configured at startup.
+
+use crate::engine::{string_bytes, with_table_overhead};
+use lru::LruCache;
+use serde::Serialize;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+struct CachedResponse {
+    response: serde_json::Value,
+    generation: u64,
+    inserted_at: Instant,
+}
+
+/// Hit and miss counts of a project's recall cache, reported in `/stats`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecallCacheStats {
+    pub enabled: bool,
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+}
+
+pub struct RecallCache {
+    entries: Mutex<LruCache<String, CachedResponse>>,
+    /// `None` turns the cache off
+    ttl: Option<Duration>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl RecallCache {
+    pub fn new(capacity: usize, ttl: Option<Duration>) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            ttl,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.ttl.is_some()
+    }
+
+    /// Cached response for `key`, if present, unexpired and computed at
+    /// `generation`. Expired or stale entries are dropped. Counts a hit or a
+    /// miss while the cache is on.
+    pub fn get(&self, key: &str, generation: u64) -> Option<serde_json::Value> {
+        let ttl = self.ttl?;
+        let mut entries = self.entries.lock().unwrap();
+        let cached = match entries.get(key) {
+            Some(entry) if entry.generation == generation && entry.inserted_at.elapsed() < ttl => Some(entry.response.clone()),
+            Some(_) => {
+                entries.pop(key);
+                None
+            }
+            None => None,
+        };
+        let counter = if cached.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        cached
+    }
+
+    /// Cache a response computed at `generation`, evicting the least
+    /// recently used entry when full. Does nothing while the cache is off.
+    pub fn insert(&self, key: String, response: serde_json::Value, generation: u64) {
+        if self.ttl.is_none() {
+            return;
+        }
+        self.entries.lock().unwrap().put(key, CachedResponse {
+            response,
+            generation,
+            inserted_at: Instant::now(),
+        });
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn stats(&self) -> RecallCacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
+        RecallCacheStats {
+            enabled: self.is_enabled(),
+            entries: self.len(),
+            hits,
+            misses,
+            hit_rate: if lookups > 0 { hits as f64 / lookups as f64 } else { 0.0 },
+        }
+    }
+
+    /// Approximate heap bytes held by cached entries.
+    pub fn estimated_bytes(&self) -> usize {
+        let entries = self.entries.lock().unwrap();
+        with_table_overhead(
+            entries
+                .iter()
+                .map(|(key, entry)| {
+                    string_bytes(key) + std::mem::size_of::<CachedResponse>() + entry.response.to_string().len()
+                })
+                .sum(),
+        )
+    }
+}
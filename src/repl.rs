@@ -0,0 +1,286 @@
+//! Interactive shell over an embedded [`CueMap`] (`cuemap-rust repl`).
+//!
+//! Meant for poking at scoring: add a few memories, recall them, and look at
+//! the per-result breakdown, alias expansion and grounded selection without a
+//! server or HTTP client in between.
+
+use crate::embedded::{CueMap, GroundedQuery, RecallQuery};
+use crate::engine::RecallResult;
+use rustyline::error::ReadlineError;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+const PROMPT: &str = "cuemap> ";
+
+/// Content longer than this is cut in result listings.
+const PREVIEW_CHARS: usize = 72;
+
+/// Cues listed by `cues` when no limit is given.
+const DEFAULT_CUE_LISTING: usize = 25;
+
+const HELP: &str = "\
+Commands:
+  add <cue,cue,...> <content>    store a memory (cues are comma-separated)
+  recall <cue> [cue ...]         recall by cues
+  search <text>                  recall by cues resolved from text
+  explain <cue> [cue ...]        recall with the score breakdown per result
+  grounded <text>                select context under a token budget
+  budget <tokens>                set the grounded token budget (default 500)
+  limit <n>                      set the result limit (default 10)
+  get <id>                       show a memory
+  reinforce <id> <cue> [cue ...] strengthen a memory under cues
+  cues [prefix]                  cues by number of memories
+  aliases <cue>                  how a query cue expands through aliases
+  stats                          store statistics
+  save                           snapshot to the data directory
+  help                           this text
+  quit                           leave (saves first unless started with --no-save)";
+
+/// What the shell should do after a command.
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Print(String),
+    Quit,
+}
+
+pub struct Repl {
+    cuemap: CueMap,
+    limit: usize,
+    token_budget: u32,
+    /// Writes since the last snapshot
+    unsaved: usize,
+}
+
+impl Repl {
+    pub fn new(cuemap: CueMap) -> Self {
+        Self {
+            cuemap,
+            limit: 10,
+            token_budget: 500,
+            unsaved: 0,
+        }
+    }
+
+    pub fn cuemap(&self) -> &CueMap {
+        &self.cuemap
+    }
+
+    /// Run one command line.
+    pub fn execute(&mut self, line: &str) -> Result<Outcome, String> {
+        let line = line.trim();
+        let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        let words: Vec<String> = rest.split_whitespace().map(str::to_string).collect();
+
+        let output = match command {
+            "" => String::new(),
+            "help" | "?" => HELP.to_string(),
+            "quit" | "exit" => return Ok(Outcome::Quit),
+            "add" => {
+                let (cues, content) = rest.split_once(char::is_whitespace).ok_or("usage: add <cue,cue,...> <content>")?;
+                let cues: Vec<&str> = cues.split(',').filter(|c| !c.is_empty()).collect();
+                let outcome = self.cuemap.add(content.trim(), cues).map_err(|e| e.to_string())?;
+                self.unsaved += 1;
+                let mut out = format!("added {}", outcome.id);
+                for rejected in &outcome.rejected {
+                    let _ = write!(out, "\n  rejected {}: {} ({})", rejected.cue, rejected.code, rejected.detail);
+                }
+                for rewritten in &outcome.rewritten {
+                    let _ = write!(out, "\n  rewrote {} -> {}", rewritten.from, rewritten.to);
+                }
+                for flagged in &outcome.flagged {
+                    let _ = write!(out, "\n  flagged {}: {} ({})", flagged.cue, flagged.code, flagged.detail);
+                }
+                out
+            }
+            "recall" | "explain" => {
+                if words.is_empty() {
+                    return Err(format!("usage: {} <cue> [cue ...]", command));
+                }
+                let explain = command == "explain";
+                let results = self.cuemap.recall_with(&RecallQuery {
+                    cues: words.clone(),
+                    limit: self.limit,
+                    explain,
+                    ..Default::default()
+                });
+                let mut out = String::new();
+                if explain {
+                    let expanded = self.cuemap.project().expand_query_cues(self.cuemap.project().normalize_cues(&words));
+                    let _ = writeln!(out, "query: {}", format_weighted(&expanded));
+                }
+                out + &format_results(&results, explain)
+            }
+            "search" => {
+                if rest.is_empty() {
+                    return Err("usage: search <text>".to_string());
+                }
+                let resolved = self.cuemap.project().resolve_cues_from_text(rest);
+                let results = self.cuemap.recall_text(rest, self.limit);
+                format!("resolved: {}\n{}", resolved.join(" "), format_results(&results, false))
+            }
+            "grounded" => {
+                if rest.is_empty() {
+                    return Err("usage: grounded <text>".to_string());
+                }
+                let mut query = GroundedQuery::new(rest);
+                query.limit = self.limit;
+                query.token_budget = self.token_budget;
+                let grounded = self.cuemap.recall_grounded(&query);
+                let proof = &grounded.proof;
+                let mut out = format!("trace {} ({} of {} tokens)", proof.trace_id, proof.selected.iter().map(|s| s.estimated_tokens).sum::<u32>(), proof.token_budget);
+                for item in &proof.selected {
+                    let _ = write!(out, "\n  + {} {:.2} {}", item.memory_id, item.score, preview(&item.content));
+                }
+                for item in &proof.excluded_top {
+                    let _ = write!(out, "\n  - {} {:.2} {}", item.memory_id, item.score, item.reason);
+                }
+                let _ = write!(out, "\n\n{}", grounded.verified_context);
+                out
+            }
+            "budget" => {
+                self.token_budget = parse_number(rest, "budget <tokens>")?;
+                format!("token budget {}", self.token_budget)
+            }
+            "limit" => {
+                self.limit = parse_number(rest, "limit <n>")?;
+                format!("limit {}", self.limit)
+            }
+            "get" => {
+                let id = words.first().ok_or("usage: get <id>")?;
+                let memory = self.cuemap.get(id).ok_or_else(|| format!("no memory {}", id))?;
+                serde_json::to_string_pretty(&memory).map_err(|e| e.to_string())?
+            }
+            "reinforce" => {
+                let (id, cues) = words.split_first().ok_or("usage: reinforce <id> <cue> [cue ...]")?;
+                if !self.cuemap.reinforce(id, cues.iter().cloned()) {
+                    return Err(format!("no memory {}", id));
+                }
+                self.unsaved += 1;
+                format!("reinforced {}", id)
+            }
+            "cues" => {
+                let prefix = words.first().map(String::as_str).unwrap_or("");
+                let mut cues: Vec<(String, usize)> = self
+                    .cuemap
+                    .project()
+                    .main
+                    .get_cue_index()
+                    .iter()
+                    .filter(|entry| entry.key().starts_with(prefix))
+                    .map(|entry| (entry.key().to_string(), entry.value().len()))
+                    .collect();
+                cues.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                let total = cues.len();
+                let mut out = String::new();
+                for (cue, count) in cues.iter().take(DEFAULT_CUE_LISTING) {
+                    let _ = writeln!(out, "{:>7}  {}", count, cue);
+                }
+                if total > DEFAULT_CUE_LISTING {
+                    let _ = writeln!(out, "({} more)", total - DEFAULT_CUE_LISTING);
+                }
+                out.trim_end().to_string()
+            }
+            "aliases" => {
+                let cue = words.first().ok_or("usage: aliases <cue>")?;
+                let project = self.cuemap.project();
+                let expanded = project.expand_query_cues(project.normalize_cues(std::slice::from_ref(cue)));
+                if expanded.len() <= 1 {
+                    format!("{} has no aliases", expanded.first().map(|(c, _)| c.as_str()).unwrap_or(cue))
+                } else {
+                    format_weighted(&expanded)
+                }
+            }
+            "stats" => {
+                let stats: serde_json::Map<String, serde_json::Value> = self.cuemap.project().main.get_stats().into_iter().collect();
+                serde_json::to_string_pretty(&stats).map_err(|e| e.to_string())?
+            }
+            "save" => {
+                self.cuemap.snapshot().map_err(|e| e.to_string())?;
+                self.unsaved = 0;
+                format!("saved {} memories", self.cuemap.len())
+            }
+            other => return Err(format!("unknown command '{}', try 'help'", other)),
+        };
+        Ok(Outcome::Print(output))
+    }
+
+    /// Read commands until `quit` or end of input. History is kept in
+    /// `history` when given.
+    pub fn run(mut self, history: Option<PathBuf>, save_on_exit: bool) -> Result<(), String> {
+        let mut editor = rustyline::DefaultEditor::new().map_err(|e| e.to_string())?;
+        if let Some(path) = &history {
+            let _ = editor.load_history(path);
+        }
+        println!("{} memories loaded. Type 'help' for commands.", self.cuemap.len());
+
+        loop {
+            match editor.readline(PROMPT) {
+                Ok(line) => {
+                    if !line.trim().is_empty() {
+                        let _ = editor.add_history_entry(line.as_str());
+                    }
+                    match self.execute(&line) {
+                        Ok(Outcome::Print(text)) if text.is_empty() => {}
+                        Ok(Outcome::Print(text)) => println!("{}", text),
+                        Ok(Outcome::Quit) => break,
+                        Err(e) => eprintln!("error: {}", e),
+                    }
+                }
+                // Ctrl-C clears the line, Ctrl-D leaves
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => break,
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+
+        if let Some(path) = &history {
+            let _ = editor.save_history(path);
+        }
+        if save_on_exit && self.unsaved > 0 {
+            self.cuemap.snapshot().map_err(|e| e.to_string())?;
+            println!("saved {} memories", self.cuemap.len());
+        }
+        Ok(())
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, usage: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("usage: {}", usage))
+}
+
+fn preview(content: &str) -> String {
+    let line = content.lines().next().unwrap_or("");
+    if line.chars().count() > PREVIEW_CHARS || content.contains('\n') {
+        format!("{}…", line.chars().take(PREVIEW_CHARS).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+fn format_weighted(cues: &[(String, f64)]) -> String {
+    cues.iter().map(|(cue, weight)| format!("{} ({:.2})", cue, weight)).collect::<Vec<_>>().join(" ")
+}
+
+fn format_results(results: &[RecallResult], explain: bool) -> String {
+    if results.is_empty() {
+        return "no results".to_string();
+    }
+    let mut out = String::new();
+    for (rank, result) in results.iter().enumerate() {
+        let _ = writeln!(out, "{:>2}. {:>8.2}  {}  {}", rank + 1, result.score, result.memory_id, preview(&result.content));
+        if let Some(explain) = explain.then_some(result.explain.as_ref()).flatten() {
+            let _ = writeln!(
+                out,
+                "      intersection {:.2}  recency {:.2}  frequency {:.2}  salience {:.2}  integrity {:.2}  matched {}",
+                explain["intersection_score"].as_f64().unwrap_or_default(),
+                explain["recency_component"].as_f64().unwrap_or_default(),
+                explain["frequency_component"].as_f64().unwrap_or_default(),
+                explain["salience_score"].as_f64().unwrap_or_default(),
+                explain["match_integrity"].as_f64().unwrap_or_default(),
+                explain["match_count"],
+            );
+        }
+    }
+    out.trim_end().to_string()
+}
@@ -0,0 +1,234 @@
+//! Read replicas that follow a primary (`--follow <primary-url>`).
+//!
+//! The primary serves its project state on `GET /admin/replica`: memories and
+//! cue lists of the main, alias and lexicon engines in the snapshot layout plus
+//! the project config, tagged with an `ETag`. A follower polls that endpoint, sends the last tag back as
+//! `If-None-Match` so an unchanged primary answers `304`, and applies a new
+//! state in place. Followers are read-only and keep nothing on disk; recalls
+//! see the primary's writes within one poll interval.
+
+use crate::persistence::ReplicaState;
+use crate::projects::{ProjectConfig, ProjectContext};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Environment variable holding the API key a follower presents to its primary.
+pub const FOLLOW_API_KEY_ENV: &str = "CUEMAP_FOLLOW_API_KEY";
+
+/// Body of `GET /admin/replica`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReplicaSnapshot {
+    pub config: ProjectConfig,
+    pub state: ReplicaState,
+    /// Absent from primaries that predate alias and lexicon replication
+    #[serde(default)]
+    pub aliases: Option<ReplicaState>,
+    #[serde(default)]
+    pub lexicon: Option<ReplicaState>,
+}
+
+impl ReplicaSnapshot {
+    pub fn capture(project: &ProjectContext) -> Self {
+        Self {
+            config: project.config(),
+            state: ReplicaState::capture(&project.main),
+            aliases: Some(ReplicaState::capture(&project.aliases)),
+            lexicon: Some(ReplicaState::capture(&project.lexicon)),
+        }
+    }
+}
+
+/// Tag that changes whenever the replica body would: whenever a recall on any
+/// of the three engines could answer differently, on config updates and on
+/// primary restarts.
+pub fn replica_etag(project: &ProjectContext) -> String {
+    static BOOT: OnceLock<u64> = OnceLock::new();
+    let boot = BOOT.get_or_init(|| SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos() as u64);
+
+    let mut hasher = DefaultHasher::new();
+    project.aliases.recall_generation().hash(&mut hasher);
+    project.lexicon.recall_generation().hash(&mut hasher);
+    serde_json::to_string(&project.config()).unwrap_or_default().hash(&mut hasher);
+    format!("\"{:x}-{}-{:x}\"", boot, project.main.recall_generation(), hasher.finish())
+}
+
+/// Replication progress of a follower, reported by `/readyz`.
+#[derive(Debug, Default)]
+pub struct FollowerStatus {
+    synced: AtomicBool,
+    last_sync: Mutex<Option<f64>>,
+    last_error: Mutex<Option<String>>,
+}
+
+impl FollowerStatus {
+    /// Whether at least one state from the primary has been applied.
+    pub fn is_synced(&self) -> bool {
+        self.synced.load(Ordering::Acquire)
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "ok": self.is_synced(),
+            "last_sync": *self.last_sync.lock().unwrap(),
+            "last_error": self.last_error.lock().unwrap().clone(),
+        })
+    }
+
+    fn record(&self, result: &Result<bool, String>) {
+        match result {
+            Ok(_) => {
+                self.synced.store(true, Ordering::Release);
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs_f64();
+                *self.last_sync.lock().unwrap() = Some(now);
+                *self.last_error.lock().unwrap() = None;
+            }
+            Err(e) => *self.last_error.lock().unwrap() = Some(e.clone()),
+        }
+    }
+}
+
+/// Pulls a primary's state into a local project.
+pub struct Follower {
+    replica_url: String,
+    api_key: Option<String>,
+    client: reqwest::Client,
+    project: Arc<ProjectContext>,
+    etag: Option<String>,
+    status: Arc<FollowerStatus>,
+}
+
+impl Follower {
+    pub fn new(primary_url: &str, api_key: Option<String>, project: Arc<ProjectContext>) -> Self {
+        Self {
+            replica_url: format!("{}/admin/replica", primary_url.trim_end_matches('/')),
+            api_key,
+            client: reqwest::Client::new(),
+            project,
+            etag: None,
+            status: Arc::new(FollowerStatus::default()),
+        }
+    }
+
+    pub fn status(&self) -> Arc<FollowerStatus> {
+        self.status.clone()
+    }
+
+    /// Fetch the primary's state once. Returns whether anything changed.
+    pub async fn sync(&mut self) -> Result<bool, String> {
+        let result = self.pull().await;
+        self.status.record(&result);
+        result
+    }
+
+    async fn pull(&mut self) -> Result<bool, String> {
+        let mut request = self.client.get(&self.replica_url);
+        if let Some(key) = &self.api_key {
+            request = request.header("X-API-Key", key);
+        }
+        if let Some(etag) = &self.etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+
+        let response = request.send().await.map_err(|e| format!("primary unreachable: {}", e))?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(false);
+        }
+        if !response.status().is_success() {
+            return Err(format!("primary returned {}", response.status()));
+        }
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let body = response.bytes().await.map_err(|e| format!("reading replica state: {}", e))?;
+
+        // Decoding and re-indexing is CPU work; keep it off the async workers
+        let project = self.project.clone();
+        let memories = tokio::task::spawn_blocking(move || -> Result<usize, String> {
+            let snapshot: ReplicaSnapshot = serde_json::from_slice(&body).map_err(|e| format!("invalid replica state: {}", e))?;
+            let count = snapshot.state.memory_count();
+            let (memories, cue_index) = snapshot.state.into_contents().map_err(|e| format!("invalid replica state: {}", e))?;
+            project.main.replace_state(memories, cue_index);
+            for (engine, state) in [(&project.aliases, snapshot.aliases), (&project.lexicon, snapshot.lexicon)] {
+                if let Some(state) = state {
+                    let (memories, cue_index) = state.into_contents().map_err(|e| format!("invalid replica state: {}", e))?;
+                    engine.replace_state(memories, cue_index);
+                }
+            }
+            apply_config(&project, snapshot.config);
+            Ok(count)
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+
+        debug!("Applied {} memories from primary", memories);
+        self.etag = etag;
+        Ok(true)
+    }
+
+    /// Poll the primary every `every` until the task is aborted.
+    pub fn spawn(mut self, every: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(every);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut failing = false;
+            loop {
+                ticker.tick().await;
+                let first = !self.status.is_synced();
+                match self.sync().await {
+                    Ok(_) => {
+                        if first || failing {
+                            info!("Follower in sync with primary ({} memories)", self.project.main.get_memories().len());
+                        }
+                        failing = false;
+                    }
+                    // Log the first failure of a streak, not every poll
+                    Err(e) if !failing => {
+                        warn!("Follower sync failed: {}", e);
+                        failing = true;
+                    }
+                    Err(e) => debug!("Follower sync failed: {}", e),
+                }
+            }
+        })
+    }
+}
+
+/// Replace config sections that differ, so unchanged ones keep their caches.
+fn apply_config(project: &ProjectContext, config: ProjectConfig) {
+    if !same_json(&project.normalization(), &config.normalization) {
+        project.set_normalization(config.normalization);
+    }
+    if !same_json(&project.taxonomy(), &config.taxonomy) {
+        project.set_taxonomy(config.taxonomy);
+    }
+    if !same_json(&project.tokenizer(), &config.tokenizer) {
+        project.set_tokenizer(config.tokenizer);
+    }
+    if project.alias_config() != config.aliases {
+        project.set_alias_config(config.aliases);
+    }
+    if project.ranking() != config.ranking {
+        project.set_ranking(config.ranking);
+    }
+    if project.recall_defaults() != config.recall {
+        project.set_recall_defaults(config.recall);
+    }
+    if project.content_limit() != config.content_limit {
+        project.set_content_limit(config.content_limit);
+    }
+    if project.config().cue_display_names != config.cue_display_names {
+        project.set_display_names(config.cue_display_names);
+    }
+}
+
+fn same_json<T: Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
@@ -0,0 +1,133 @@
+//! Re-running LLM cue proposals over existing memories
+//! (`POST /admin/jobs/reproposal` and the `repropose` subcommand).
+//!
+//! Memories only get LLM-proposed cues when they are added, so a better
+//! model leaves the existing ones with whatever the old model proposed.
+//! A reproposal asks the configured LLM again for every memory a
+//! [`ReproposalFilter`] selects, oldest first, at a bounded rate. Proposed
+//! cues go through normalization and taxonomy validation as usual; only
+//! cues the memory does not already hold are attached, so nothing is
+//! removed or duplicated. Tombstoned and locked memories are skipped.
+
+use crate::jobs::propose_and_attach;
+use crate::llm::LlmConfig;
+use crate::projects::ProjectContext;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+/// Which memories to re-propose cues for. Every given criterion must match;
+/// an empty filter selects every memory.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReproposalFilter {
+    /// A cue the memory must hold, normalized like the project's cues
+    #[serde(default, rename = "filter", skip_serializing_if = "Option::is_none")]
+    pub cue: Option<String>,
+    /// Only memories with at most this many cues, to target sparse ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cues: Option<usize>,
+}
+
+impl ReproposalFilter {
+    /// Ids of the live memories of `project` the filter selects, oldest first.
+    pub fn select(&self, project: &ProjectContext) -> Vec<String> {
+        let cue = self
+            .cue
+            .as_ref()
+            .map(|cue| project.normalize_cues(std::slice::from_ref(cue)).remove(0).to_lowercase());
+        let mut selected: Vec<(f64, String)> = project
+            .main
+            .get_memories()
+            .iter()
+            .filter(|m| !m.is_tombstoned() && !m.locked)
+            .filter(|m| self.max_cues.is_none_or(|max| m.cues.len() <= max))
+            .filter(|m| cue.as_deref().is_none_or(|cue| m.cues.iter().any(|c| c.to_lowercase() == cue)))
+            .map(|m| (m.created_at, m.key().clone()))
+            .collect();
+        selected.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+        selected.into_iter().map(|(_, id)| id).collect()
+    }
+}
+
+/// How far a reproposal got.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct ReproposalCounts {
+    /// Memories selected, counted when the reproposal starts
+    pub total: usize,
+    /// Memories the LLM has been asked about so far
+    pub processed: usize,
+    /// New cues attached across all memories
+    pub attached: usize,
+    /// Memories whose proposal failed, e.g. on an LLM error
+    pub failed: usize,
+}
+
+/// Progress of a reproposal, as reported by `GET /admin/jobs/reproposal`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Reproposal {
+    /// `queued`, `running` or `completed`
+    pub state: &'static str,
+    pub filter: ReproposalFilter,
+    /// LLM proposals per second
+    pub rate: f64,
+    #[serde(flatten)]
+    pub counts: ReproposalCounts,
+    pub queued_at: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<f64>,
+}
+
+impl Reproposal {
+    pub fn queued(filter: ReproposalFilter, rate: f64) -> Self {
+        Self {
+            state: "queued",
+            filter,
+            rate,
+            counts: ReproposalCounts::default(),
+            queued_at: now_secs(),
+            started_at: None,
+            finished_at: None,
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.state != "completed"
+    }
+}
+
+/// Ask the LLM for cues for each of `memory_ids` in turn, at most `rate`
+/// times a second, reporting the counts after every memory.
+pub async fn run(
+    project: &ProjectContext,
+    memory_ids: &[String],
+    config: &LlmConfig,
+    rate: f64,
+    mut on_progress: impl FnMut(&ReproposalCounts),
+) -> ReproposalCounts {
+    let pause = Duration::from_secs_f64(1.0 / rate);
+    let mut counts = ReproposalCounts { total: memory_ids.len(), ..Default::default() };
+    for (i, memory_id) in memory_ids.iter().enumerate() {
+        if i > 0 {
+            tokio::time::sleep(pause).await;
+        }
+        // Memories deleted since the reproposal started are skipped
+        if let Some(memory) = project.main.get_memory(memory_id).filter(|m| !m.is_tombstoned()) {
+            match propose_and_attach(project, memory_id, &memory.content, config).await {
+                Ok(attached) => counts.attached += attached.len(),
+                Err(e) => {
+                    warn!("Reproposal for memory {} failed: {}", memory_id, e);
+                    counts.failed += 1;
+                }
+            }
+        }
+        counts.processed += 1;
+        on_progress(&counts);
+    }
+    counts
+}
+
+fn now_secs() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs_f64()).unwrap_or(0.0)
+}
@@ -0,0 +1,135 @@
+//! Optional embedding rerank of recall results.
+//!
+//! Cue intersection stays the retrieval step. When a recall sets `rerank`,
+//! the top [`RERANK_TOP_N`] cue-ranked results and the query text are
+//! embedded by the configured LLM provider, and each of those results is
+//! re-scored as a blend of its cue score and its cosine similarity to the
+//! query:
+//!
+//! ```text
+//! score = (1 - w) * cue_score + w * similarity * best_cue_score
+//! ```
+//!
+//! with `w` = [`RERANK_SIMILARITY_WEIGHT`], which keeps scores on the cue
+//! scale. Results past the top N keep their cue order behind them.
+//!
+//! Memory vectors are computed on first use and cached in the memory's
+//! metadata under [`EMBEDDING_KEY`], tagged with the model and a hash of the
+//! content so a new model or edited content recomputes them. Recall
+//! responses never include the cached vectors.
+
+use crate::config::{RERANK_SIMILARITY_WEIGHT, RERANK_TOP_N};
+use crate::engine::{CueMapEngine, RecallResult};
+use crate::llm::{self, LlmConfig};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+
+/// Metadata key holding a memory's cached embedding.
+pub const EMBEDDING_KEY: &str = "embedding";
+
+#[derive(Debug, Clone)]
+pub struct RerankConfig {
+    pub llm: LlmConfig,
+    /// Embedding model of the provider
+    pub model: String,
+    /// Share of the final score taken from cosine similarity, 0 to 1
+    pub weight: f64,
+}
+
+impl RerankConfig {
+    /// LLM settings from the environment; the model comes from
+    /// `EMBEDDING_MODEL` (default `nomic-embed-text` on Ollama,
+    /// `text-embedding-3-small` on OpenAI). `None` when the LLM is disabled.
+    pub fn from_env() -> Option<Self> {
+        let llm = LlmConfig::from_env()?;
+        let model = std::env::var("EMBEDDING_MODEL").ok().filter(|m| !m.is_empty()).unwrap_or_else(|| {
+            match llm.provider.as_str() {
+                "openai" => "text-embedding-3-small",
+                _ => "nomic-embed-text",
+            }
+            .to_string()
+        });
+        Some(Self { llm, model, weight: RERANK_SIMILARITY_WEIGHT })
+    }
+}
+
+fn content_hash(content: &str) -> String {
+    let digest = format!("{:x}", Sha256::digest(content.as_bytes()));
+    digest[..16].to_string()
+}
+
+/// The cached vector of `result`, if it was made by `model` from the
+/// current content.
+fn cached_vector(result: &RecallResult, model: &str) -> Option<Vec<f32>> {
+    let cached = result.metadata.get(EMBEDDING_KEY)?;
+    if cached["model"].as_str() != Some(model) || cached["content_hash"].as_str() != Some(&content_hash(&result.content)) {
+        return None;
+    }
+    cached["vector"].as_array()?.iter().map(|v| v.as_f64().map(|v| v as f32)).collect()
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f64, 0.0f64, 0.0f64);
+    for (x, y) in a.iter().zip(b) {
+        let (x, y) = (*x as f64, *y as f64);
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+/// Rerank the leading results of a recall on `engine` against `query`.
+/// Returns how many results were re-scored; on error the order is unchanged.
+pub async fn rerank(
+    engine: &CueMapEngine,
+    query: &str,
+    results: &mut [RecallResult],
+    config: &RerankConfig,
+) -> Result<usize, String> {
+    let query = query.trim();
+    let n = results.len().min(RERANK_TOP_N);
+    if n == 0 || query.is_empty() {
+        return Ok(0);
+    }
+    let head = &mut results[..n];
+
+    let mut vectors: Vec<Option<Vec<f32>>> = head.iter().map(|r| cached_vector(r, &config.model)).collect();
+    let missing: Vec<usize> = (0..n).filter(|&i| vectors[i].is_none()).collect();
+    let mut texts = vec![query.to_string()];
+    texts.extend(missing.iter().map(|&i| head[i].content.clone()));
+
+    let mut embedded = llm::embed(&texts, &config.llm, &config.model).await?.into_iter();
+    let query_vector = embedded.next().unwrap_or_default();
+    for (&i, vector) in missing.iter().zip(embedded) {
+        engine.set_metadata(
+            &head[i].memory_id,
+            EMBEDDING_KEY,
+            serde_json::json!({
+                "model": config.model,
+                "content_hash": content_hash(&head[i].content),
+                "vector": vector,
+            }),
+        );
+        vectors[i] = Some(vector);
+    }
+
+    let best = head.iter().map(|r| r.score).fold(0.0, f64::max);
+    let weight = config.weight.clamp(0.0, 1.0);
+    for (result, vector) in head.iter_mut().zip(&vectors) {
+        let similarity = vector.as_deref().map_or(0.0, |v| cosine_similarity(&query_vector, v));
+        result.score = (1.0 - weight) * result.score + weight * similarity * best;
+        if let Some(explain) = result.explain.as_mut().and_then(|e| e.as_object_mut()) {
+            explain.insert("semantic_similarity".to_string(), serde_json::json!(similarity));
+        }
+    }
+    head.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+
+    Ok(n)
+}
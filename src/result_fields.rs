@@ -0,0 +1,107 @@
+//! Selecting the fields of recall results (`fields` on `POST /recall`).
+//!
+//! `"fields": "id,score,cues"` returns only those fields of each result, so a
+//! caller that reranks on ids and scores doesn't pay for serializing content
+//! and metadata it discards. Fields are picked while the response is
+//! serialized; `cues`, which full results leave out, is read from the memory
+//! only when selected.
+
+use crate::engine::{CueMapEngine, RecallResult};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use std::sync::Arc;
+
+/// Fields a recall result can be narrowed to, in response order.
+pub const RESULT_FIELDS: [&str; 14] = [
+    "memory_id",
+    "content",
+    "score",
+    "match_integrity",
+    "intersection_count",
+    "matched_cues",
+    "recency_score",
+    "reinforcement_score",
+    "salience_score",
+    "created_at",
+    "metadata",
+    "locked",
+    "cues",
+    "explain",
+];
+
+/// The result fields a recall asked for, in `RESULT_FIELDS` order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultFields {
+    fields: Vec<&'static str>,
+}
+
+impl ResultFields {
+    /// Parse a comma-separated list such as `id,score,cues`; `id` stands for
+    /// `memory_id`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut requested = Vec::new();
+        for name in spec.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            let name = if name == "id" { "memory_id" } else { name };
+            if !RESULT_FIELDS.contains(&name) {
+                return Err(format!("unknown result field {}; expected one of id, {}", name, RESULT_FIELDS.join(", ")));
+            }
+            requested.push(name);
+        }
+        if requested.is_empty() {
+            return Err("fields names no result field".to_string());
+        }
+        let fields = RESULT_FIELDS.into_iter().filter(|field| requested.contains(field)).collect();
+        Ok(Self { fields })
+    }
+
+    pub fn contains(&self, field: &str) -> bool {
+        self.fields.contains(&field)
+    }
+
+    /// `results` narrowed to the selected fields, ready to serialize.
+    pub fn project<'a>(&'a self, engine: &CueMapEngine, results: &'a [RecallResult]) -> Vec<ProjectedResult<'a>> {
+        results
+            .iter()
+            .map(|result| ProjectedResult {
+                result,
+                fields: self,
+                cues: self
+                    .contains("cues")
+                    .then(|| engine.get_memories().get(&result.memory_id).map(|memory| memory.cues.clone()).unwrap_or_default()),
+            })
+            .collect()
+    }
+}
+
+/// A recall result that serializes only the selected fields.
+pub struct ProjectedResult<'a> {
+    result: &'a RecallResult,
+    fields: &'a ResultFields,
+    cues: Option<Vec<Arc<str>>>,
+}
+
+impl Serialize for ProjectedResult<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let result = self.result;
+        let mut map = serializer.serialize_map(None)?;
+        for field in &self.fields.fields {
+            match *field {
+                "memory_id" => map.serialize_entry(field, &result.memory_id)?,
+                "content" => map.serialize_entry(field, &result.content)?,
+                "score" => map.serialize_entry(field, &result.score)?,
+                "match_integrity" => map.serialize_entry(field, &result.match_integrity)?,
+                "intersection_count" => map.serialize_entry(field, &result.intersection_count)?,
+                "matched_cues" => map.serialize_entry(field, &result.matched_cues)?,
+                "recency_score" => map.serialize_entry(field, &result.recency_score)?,
+                "reinforcement_score" => map.serialize_entry(field, &result.reinforcement_score)?,
+                "salience_score" => map.serialize_entry(field, &result.salience_score)?,
+                "created_at" => map.serialize_entry(field, &result.created_at)?,
+                "metadata" => map.serialize_entry(field, &result.metadata)?,
+                "locked" => map.serialize_entry(field, &result.locked)?,
+                "cues" => map.serialize_entry(field, &self.cues)?,
+                "explain" if result.explain.is_some() => map.serialize_entry(field, &result.explain)?,
+                _ => {}
+            }
+        }
+        map.end()
+    }
+}
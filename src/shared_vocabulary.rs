@@ -0,0 +1,223 @@
+//! Aliases and lexicon terms shared by every project, e.g. company-wide
+//! service names that each tenant would otherwise learn on its own.
+//!
+//! Projects consult the shared vocabulary after their own aliases and
+//! lexicon: a project's entries win, and shared ones only add cues the
+//! project did not reach itself. Entries are managed through
+//! `/admin/shared/aliases` and `/admin/shared/lexicon` and persisted as JSON
+//! in the data directory.
+
+use crate::engine::CueMapEngine;
+use crate::nl::TokenizerConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+use tracing::warn;
+
+/// Shared aliases and terms, stored in the data directory
+pub const SHARED_VOCABULARY_FILE: &str = "shared_vocabulary.json";
+
+/// Weight a shared alias passes on when none is given, as for project aliases
+pub const SHARED_ALIAS_DEFAULT_WEIGHT: f64 = 0.85;
+
+/// Lexicon hits considered per resolution, as for a project's lexicon
+const SHARED_LEXICON_LIMIT: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SharedAlias {
+    pub id: String,
+    pub from: String,
+    pub to: String,
+    /// Multiplies the weight of `from` to give the weight of `to`
+    pub weight: f64,
+}
+
+/// Query text sharing tokens with `text` resolves to `cue`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SharedTerm {
+    pub cue: String,
+    pub text: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Stored {
+    #[serde(default)]
+    aliases: BTreeMap<String, SharedAlias>,
+    /// Keyed by cue; a cue has one term
+    #[serde(default)]
+    terms: BTreeMap<String, SharedTerm>,
+}
+
+pub struct SharedVocabulary {
+    stored: RwLock<Stored>,
+    /// Built from the terms: each entry's content is a cue, its cues the term's tokens
+    lexicon: CueMapEngine,
+    /// Bumped on every change; tags project query cache entries
+    revision: AtomicU64,
+    /// Where changes are persisted; `None` keeps the vocabulary in memory only.
+    path: Option<PathBuf>,
+}
+
+impl Default for SharedVocabulary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SharedVocabulary {
+    /// An empty vocabulary kept in memory only.
+    pub fn new() -> Self {
+        Self {
+            stored: RwLock::new(Stored::default()),
+            lexicon: CueMapEngine::new(),
+            revision: AtomicU64::new(0),
+            path: None,
+        }
+    }
+
+    /// Load the vocabulary persisted at `path`, if any, and persist changes there.
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let stored: Stored = match fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data).unwrap_or_else(|e| {
+                warn!("Failed to load shared vocabulary from {:?}: {}", path, e);
+                Stored::default()
+            }),
+            Err(_) => Stored::default(),
+        };
+        let vocabulary = Self { path: Some(path), ..Self::new() };
+        for term in stored.terms.values() {
+            vocabulary.index_term(term);
+        }
+        *vocabulary.stored.write().unwrap() = stored;
+        vocabulary
+    }
+
+    /// Changes whenever an alias or term is added or removed.
+    pub fn revision(&self) -> u64 {
+        self.revision.load(Ordering::Acquire)
+    }
+
+    pub fn aliases(&self) -> Vec<SharedAlias> {
+        self.stored.read().unwrap().aliases.values().cloned().collect()
+    }
+
+    /// Add an alias from `from` to `to`. Errors when a cue is empty or the
+    /// alias would map a cue to itself.
+    pub fn add_alias(&self, from: &str, to: &str, weight: Option<f64>) -> Result<SharedAlias, String> {
+        let (from, to) = (clean_cue(from), clean_cue(to));
+        if from.is_empty() || to.is_empty() {
+            return Err("'from' and 'to' must not be empty".to_string());
+        }
+        if from == to {
+            return Err(format!("Alias maps '{}' to itself", from));
+        }
+        let weight = weight.unwrap_or(SHARED_ALIAS_DEFAULT_WEIGHT);
+        if !(weight > 0.0 && weight <= 1.0) {
+            return Err(format!("'weight' must be in (0, 1], got {}", weight));
+        }
+        let alias = SharedAlias { id: uuid::Uuid::new_v4().to_string(), from, to, weight };
+        self.update(|stored| {
+            stored.aliases.insert(alias.id.clone(), alias.clone());
+        });
+        Ok(alias)
+    }
+
+    /// Remove an alias. Returns whether it existed.
+    pub fn remove_alias(&self, id: &str) -> bool {
+        self.update(|stored| stored.aliases.remove(id).is_some())
+    }
+
+    /// `(to, weight)` of the aliases that expand `cue`.
+    pub fn aliases_from(&self, cue: &str) -> Vec<(String, f64)> {
+        self.stored
+            .read()
+            .unwrap()
+            .aliases
+            .values()
+            .filter(|alias| alias.from == cue)
+            .map(|alias| (alias.to.clone(), alias.weight))
+            .collect()
+    }
+
+    pub fn terms(&self) -> Vec<SharedTerm> {
+        self.stored.read().unwrap().terms.values().cloned().collect()
+    }
+
+    /// Add the term for `cue`, replacing its previous text. Errors when the
+    /// cue is empty or `text` has no tokens.
+    pub fn put_term(&self, cue: &str, text: &str) -> Result<SharedTerm, String> {
+        let cue = clean_cue(cue);
+        if cue.is_empty() {
+            return Err("'cue' must not be empty".to_string());
+        }
+        let term = SharedTerm { cue, text: text.to_string() };
+        if tokenize(&term.text).is_empty() {
+            return Err(format!("'text' has no tokens for cue '{}'", term.cue));
+        }
+        self.index_term(&term);
+        self.update(|stored| {
+            stored.terms.insert(term.cue.clone(), term.clone());
+        });
+        Ok(term)
+    }
+
+    /// Remove the term for `cue`. Returns whether it existed.
+    pub fn remove_term(&self, cue: &str) -> bool {
+        let cue = clean_cue(cue);
+        self.lexicon.delete_memory(&term_id(&cue));
+        self.update(|stored| stored.terms.remove(&cue).is_some())
+    }
+
+    /// Cues whose terms share `tokens`, with their lexicon scores, best first.
+    pub fn resolve(&self, tokens: Vec<String>) -> Vec<(String, f64)> {
+        self.lexicon
+            .recall(tokens, SHARED_LEXICON_LIMIT, false)
+            .into_iter()
+            .map(|result| (result.content, result.score))
+            .collect()
+    }
+
+    fn index_term(&self, term: &SharedTerm) {
+        self.lexicon.upsert_memory_with_id(term_id(&term.cue), term.cue.clone(), tokenize(&term.text), None, false);
+    }
+
+    /// Apply a change, persist the result and bump the revision.
+    fn update<T>(&self, f: impl FnOnce(&mut Stored) -> T) -> T {
+        let mut stored = self.stored.write().unwrap();
+        let result = f(&mut stored);
+        self.revision.fetch_add(1, Ordering::AcqRel);
+        self.persist(&stored);
+        result
+    }
+
+    fn persist(&self, stored: &Stored) {
+        let Some(path) = &self.path else { return };
+        if let Err(e) = Self::save(path, stored) {
+            warn!("Failed to persist shared vocabulary to {:?}: {}", path, e);
+        }
+    }
+
+    fn save(path: &Path, stored: &Stored) -> std::io::Result<()> {
+        let data = serde_json::to_vec_pretty(stored)?;
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, data)?;
+        fs::rename(&temp_path, path)
+    }
+}
+
+fn clean_cue(cue: &str) -> String {
+    cue.trim().to_lowercase()
+}
+
+fn term_id(cue: &str) -> String {
+    format!("cue:{}", cue)
+}
+
+/// Terms are tokenized with the default tokenizer, whatever the projects use.
+fn tokenize(text: &str) -> Vec<String> {
+    crate::nl::tokenize_to_cues_with(text, &TokenizerConfig::default())
+}
@@ -0,0 +1,339 @@
+//! Shared snapshot store for stateless deployments (`s3` feature).
+//!
+//! With `--snapshot-store s3://bucket/prefix`, instances keep the single-tenant
+//! snapshot and project config in an object store instead of relying on the
+//! local data volume:
+//!
+//! - A **writer** waits for the writer lease (an object under the prefix,
+//!   taken and renewed with conditional puts), boots from the latest
+//!   snapshot, and uploads a new one every snapshot interval while it holds
+//!   the lease. On shutdown it uploads a final snapshot and hands the lease
+//!   back, so a blue/green successor starts right away.
+//! - A **reader** (`--snapshot-store-reader`) boots read-only from the latest
+//!   snapshot and reloads it whenever the writer uploads a new one. The
+//!   writer uploads its alias and lexicon engines next to the snapshot, and
+//!   readers load those too.
+//!
+//! A writer that crashes keeps the lease until it expires ([`LEASE_TTL`]).
+//! Each new holder bumps the lease's generation; uploads check that the
+//! writer still holds its generation, and a writer that could not renew for
+//! a whole TTL stops uploading, so a paused writer can't overwrite its
+//! successor's snapshots.
+//! Credentials and region come from the usual `AWS_*` environment variables;
+//! `AWS_ENDPOINT` points the store at S3-compatible services such as MinIO.
+
+use crate::persistence::PersistenceManager;
+use crate::projects::{ProjectConfig, ProjectContext, PROJECT_CONFIG_FILE};
+use crate::replication::replica_etag;
+use object_store::aws::AmazonS3Builder;
+use object_store::path::Path as ObjectPath;
+use object_store::{GetOptions, ObjectStore, PutMode, UpdateVersion};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+/// Object holding the single-tenant snapshot, as `cuemap.bin` on disk.
+pub const SNAPSHOT_OBJECT: &str = "cuemap.bin";
+
+/// Objects holding the writer's alias and lexicon engines, in the snapshot layout.
+pub const ALIASES_OBJECT: &str = "aliases.bin";
+pub const LEXICON_OBJECT: &str = "lexicon.bin";
+
+/// Object naming the instance allowed to upload snapshots.
+pub const LEASE_OBJECT: &str = "writer.lease";
+
+/// How long a lease lasts without renewal; renewed every third of this.
+pub const LEASE_TTL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Lease {
+    holder: String,
+    expires_at: f64,
+    /// Bumped whenever the lease changes hands
+    #[serde(default)]
+    generation: u64,
+}
+
+/// One snapshot location shared by a writer and any number of readers.
+pub struct SharedSnapshotStore {
+    store: Arc<dyn ObjectStore>,
+    prefix: ObjectPath,
+    holder: String,
+    /// Generation of the lease this instance last took or renewed; 0 before
+    generation: AtomicU64,
+}
+
+impl SharedSnapshotStore {
+    pub fn new(store: Arc<dyn ObjectStore>, prefix: &str) -> Self {
+        Self {
+            store,
+            prefix: ObjectPath::from(prefix),
+            holder: format!("{}-{}", hostname(), uuid::Uuid::new_v4()),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    /// Open an `s3://bucket/prefix` URL with credentials from the environment.
+    pub fn from_url(url: &str) -> Result<Self, String> {
+        let Some(location) = url.strip_prefix("s3://") else {
+            return Err(format!("unsupported snapshot store URL '{}' (expected s3://bucket/prefix)", url));
+        };
+        let (bucket, prefix) = location.split_once('/').unwrap_or((location, ""));
+        let store = AmazonS3Builder::from_env()
+            .with_bucket_name(bucket)
+            .build()
+            .map_err(|e| format!("snapshot store: {}", e))?;
+        Ok(Self::new(Arc::new(store), prefix))
+    }
+
+    /// Identity written into the lease while this instance holds it.
+    pub fn holder(&self) -> &str {
+        &self.holder
+    }
+
+    fn object(&self, name: &str) -> ObjectPath {
+        self.prefix.child(name)
+    }
+
+    /// Copy the latest snapshot and project config into `data_dir`, replacing
+    /// local copies. Returns false when the store holds no snapshot yet.
+    pub async fn download(&self, data_dir: &Path) -> Result<bool, String> {
+        let config = self.fetch(PROJECT_CONFIG_FILE).await?;
+        let Some(snapshot) = self.fetch(SNAPSHOT_OBJECT).await? else {
+            return Ok(false);
+        };
+        std::fs::create_dir_all(data_dir).map_err(|e| e.to_string())?;
+        if let Some(config) = config {
+            std::fs::write(data_dir.join(PROJECT_CONFIG_FILE), config).map_err(|e| e.to_string())?;
+        }
+        let temp_path = data_dir.join("cuemap.bin.tmp");
+        std::fs::write(&temp_path, snapshot).map_err(|e| e.to_string())?;
+        std::fs::rename(&temp_path, data_dir.join(SNAPSHOT_OBJECT)).map_err(|e| e.to_string())?;
+        Ok(true)
+    }
+
+    async fn fetch(&self, name: &str) -> Result<Option<Vec<u8>>, String> {
+        match self.store.get(&self.object(name)).await {
+            Ok(result) => Ok(Some(result.bytes().await.map_err(|e| e.to_string())?.to_vec())),
+            Err(object_store::Error::NotFound { .. }) => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// Upload a project's snapshot, config, aliases and lexicon. The snapshot
+    /// goes last so a reader never pairs a new snapshot with a stale config.
+    /// Fails unless this instance still holds the lease generation it last took.
+    pub async fn upload(&self, project: &ProjectContext) -> Result<(), String> {
+        self.check_lease().await?;
+        let snapshot = PersistenceManager::encode(&project.main).map_err(|e| e.to_string())?;
+        let config = serde_json::to_vec_pretty(&project.config()).map_err(|e| e.to_string())?;
+        let aliases = PersistenceManager::encode(&project.aliases).map_err(|e| e.to_string())?;
+        let lexicon = PersistenceManager::encode(&project.lexicon).map_err(|e| e.to_string())?;
+        for (name, bytes) in [(PROJECT_CONFIG_FILE, config), (ALIASES_OBJECT, aliases), (LEXICON_OBJECT, lexicon)] {
+            self.store
+                .put(&self.object(name), bytes.into())
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        self.store
+            .put(&self.object(SNAPSHOT_OBJECT), snapshot.into())
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Err unless the stored lease is ours, unexpired and of our generation.
+    async fn check_lease(&self) -> Result<(), String> {
+        let generation = self.generation.load(Ordering::Acquire);
+        let lease = self
+            .fetch(LEASE_OBJECT)
+            .await?
+            .and_then(|bytes| serde_json::from_slice::<Lease>(&bytes).ok());
+        match lease {
+            Some(lease) if generation > 0 && lease.holder == self.holder && lease.generation == generation && lease.expires_at > now() => Ok(()),
+            Some(lease) if lease.holder != self.holder => Err(format!("writer lease is held by {}", lease.holder)),
+            _ => Err(format!("writer lease generation {} is no longer held", generation)),
+        }
+    }
+
+    /// Take or renew the writer lease. False while another live instance holds it.
+    pub async fn try_acquire(&self) -> Result<bool, String> {
+        self.write_lease(now() + LEASE_TTL.as_secs_f64()).await
+    }
+
+    /// Give the lease up early so a successor need not wait for it to expire.
+    pub async fn release(&self) -> Result<(), String> {
+        self.write_lease(0.0).await.map(|_| ())
+    }
+
+    /// Conditionally write our lease; a concurrent writer makes the put fail.
+    async fn write_lease(&self, expires_at: f64) -> Result<bool, String> {
+        let path = self.object(LEASE_OBJECT);
+        let mut generation = 1;
+        let mode = match self.store.get(&path).await {
+            Ok(result) => {
+                let version = UpdateVersion {
+                    e_tag: result.meta.e_tag.clone(),
+                    version: result.meta.version.clone(),
+                };
+                let bytes = result.bytes().await.map_err(|e| e.to_string())?;
+                // An unreadable lease is treated as expired
+                if let Ok(current) = serde_json::from_slice::<Lease>(&bytes) {
+                    if current.holder != self.holder && current.expires_at > now() {
+                        debug!("Snapshot store lease held by {}", current.holder);
+                        return Ok(false);
+                    }
+                    generation = if current.holder == self.holder { current.generation } else { current.generation + 1 };
+                }
+                PutMode::Update(version)
+            }
+            Err(object_store::Error::NotFound { .. }) => PutMode::Create,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let lease = serde_json::to_vec(&Lease { holder: self.holder.clone(), expires_at, generation }).map_err(|e| e.to_string())?;
+        match self.store.put_opts(&path, lease.into(), mode.into()).await {
+            Ok(_) => {
+                self.generation.store(generation, Ordering::Release);
+                Ok(true)
+            }
+            Err(object_store::Error::Precondition { .. } | object_store::Error::AlreadyExists { .. }) => Ok(false),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// Block until this instance holds the writer lease.
+    pub async fn acquire(&self) -> Result<(), String> {
+        let mut waiting = false;
+        loop {
+            if self.try_acquire().await? {
+                return Ok(());
+            }
+            if !waiting {
+                info!("Waiting for the snapshot store writer lease...");
+                waiting = true;
+            }
+            tokio::time::sleep(LEASE_TTL / 3).await;
+        }
+    }
+
+    /// Renew the lease and upload `project` every `every` while it changes.
+    /// Stops uploading if the lease is lost, or goes a whole [`LEASE_TTL`]
+    /// without renewal and may have been taken over.
+    pub fn spawn_writer(self: Arc<Self>, project: Arc<ProjectContext>, every: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut renew = tokio::time::interval(LEASE_TTL / 3);
+            let mut snapshot = tokio::time::interval(every);
+            snapshot.tick().await; // The store already holds the state we booted from
+            let mut uploaded = Some(replica_etag(&project));
+            // The lease was just taken; `renew` fires right away to confirm it
+            let mut last_renewed = Instant::now();
+            loop {
+                tokio::select! {
+                    _ = renew.tick() => match self.try_acquire().await {
+                        Ok(true) => last_renewed = Instant::now(),
+                        Ok(false) => {
+                            error!("Lost the snapshot store writer lease; no longer uploading snapshots");
+                            return;
+                        }
+                        Err(e) => warn!("Failed to renew snapshot store lease: {}", e),
+                    },
+                    _ = snapshot.tick() => {
+                        if last_renewed.elapsed() > LEASE_TTL {
+                            error!("Snapshot store writer lease not renewed for {:?}; no longer uploading snapshots", LEASE_TTL);
+                            return;
+                        }
+                        let etag = replica_etag(&project);
+                        if uploaded.as_ref() == Some(&etag) {
+                            continue;
+                        }
+                        match self.upload(&project).await {
+                            Ok(()) => {
+                                debug!("Uploaded snapshot to the snapshot store");
+                                uploaded = Some(etag);
+                            }
+                            Err(e) => error!("Failed to upload snapshot: {}", e),
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Reload `project` from the store every `every` when the writer has
+    /// uploaded a new snapshot.
+    pub fn spawn_reader(self: Arc<Self>, project: Arc<ProjectContext>, every: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(every);
+            let mut seen: Option<String> = None;
+            loop {
+                ticker.tick().await;
+                match self.refresh(&project, seen.clone()).await {
+                    Ok(Some(e_tag)) => {
+                        if seen.is_some() {
+                            info!("Reloaded snapshot from the snapshot store ({} memories)", project.main.get_memories().len());
+                        }
+                        seen = Some(e_tag);
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!("Failed to reload from the snapshot store: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Apply the stored snapshot unless its tag is `seen`. Returns the new tag.
+    async fn refresh(&self, project: &Arc<ProjectContext>, seen: Option<String>) -> Result<Option<String>, String> {
+        let options = GetOptions { if_none_match: seen, ..Default::default() };
+        let result = match self.store.get_opts(&self.object(SNAPSHOT_OBJECT), options).await {
+            Ok(result) => result,
+            Err(object_store::Error::NotModified { .. } | object_store::Error::NotFound { .. }) => return Ok(None),
+            Err(e) => return Err(e.to_string()),
+        };
+        let e_tag = result.meta.e_tag.clone().unwrap_or_default();
+        let snapshot = result.bytes().await.map_err(|e| e.to_string())?;
+        let config = self.fetch(PROJECT_CONFIG_FILE).await?;
+        let aliases = self.fetch(ALIASES_OBJECT).await?;
+        let lexicon = self.fetch(LEXICON_OBJECT).await?;
+
+        let project = project.clone();
+        tokio::task::spawn_blocking(move || -> Result<(), String> {
+            let (memories, cue_index) = PersistenceManager::decode(&snapshot).map_err(|e| e.to_string())?;
+            project.main.replace_state(memories, cue_index);
+            // Stores written before aliases and lexicon were uploaded lack them
+            for (engine, bytes) in [(&project.aliases, aliases), (&project.lexicon, lexicon)] {
+                if let Some(bytes) = bytes {
+                    let (memories, cue_index) = PersistenceManager::decode(&bytes).map_err(|e| e.to_string())?;
+                    engine.replace_state(memories, cue_index);
+                }
+            }
+            if let Some(config) = config {
+                let config: ProjectConfig = serde_json::from_slice(&config).map_err(|e| e.to_string())?;
+                project.set_normalization(config.normalization);
+                project.set_taxonomy(config.taxonomy);
+                project.set_tokenizer(config.tokenizer);
+                project.set_alias_config(config.aliases);
+                project.set_ranking(config.ranking);
+                project.set_recall_defaults(config.recall);
+                project.set_content_limit(config.content_limit);
+                project.set_display_names(config.cue_display_names);
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+        Ok(Some(e_tag))
+    }
+}
+
+fn now() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs_f64()
+}
+
+fn hostname() -> String {
+    std::env::var("HOSTNAME").unwrap_or_else(|_| "cuemap".to_string())
+}
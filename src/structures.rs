@@ -1,9 +1,20 @@
+use crate::config::{INDEX_SET_ENTRY_OVERHEAD_BYTES, POSTINGS_BITMAP_THRESHOLD, POSTINGS_RECENT_RING};
+use crate::rerank::EMBEDDING_KEY;
+use dashmap::{DashMap, DashSet};
 use indexmap::IndexSet;
+use roaring::RoaringTreemap;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
+/// Metadata key naming the summary a tombstoned memory was consolidated into.
+pub const CONSOLIDATED_INTO_KEY: &str = "consolidated_into";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Memory {
     pub id: String,
@@ -14,10 +25,22 @@ pub struct Memory {
     pub reinforcement_count: u64,
     #[serde(default = "default_salience")]
     pub salience: f64,
+    /// Interned through the engine's `CueInterner`
     #[serde(default)]
-    pub cues: Vec<String>,
+    pub cues: Vec<Arc<str>>,
     #[serde(default)]
     pub metadata: HashMap<String, serde_json::Value>,
+    /// Collection the memory belongs to within its project, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
+    /// Curated memories are locked: background jobs leave them alone and
+    /// only explicit API calls change them
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool,
+    /// The original content of a memory stored shortened for being over its
+    /// project's content limit; served by `GET /memories/:id/full-content`
+    #[serde(default, skip_serializing)]
+    pub full_content: Option<String>,
 }
 
 fn default_salience() -> f64 {
@@ -40,6 +63,9 @@ impl Memory {
             salience: 1.0,
             cues: Vec::new(),
             metadata: metadata.unwrap_or_default(),
+            collection: None,
+            locked: false,
+            full_content: None,
         }
     }
     
@@ -50,6 +76,174 @@ impl Memory {
             .as_secs_f64();
         self.reinforcement_count += 1;
     }
+
+    /// Whether the memory was folded into a consolidated summary; see
+    /// `CueMapEngine::tombstone_memory`.
+    pub fn is_tombstoned(&self) -> bool {
+        self.metadata.contains_key(CONSOLIDATED_INTO_KEY)
+    }
+
+    /// Strong HTTP entity tag over the editable state (content, cues,
+    /// metadata, collection and lock). Access times, reinforcement and the cached
+    /// rerank embedding do not change it.
+    pub fn etag(&self) -> String {
+        let mut hasher = DefaultHasher::new();
+        self.id.hash(&mut hasher);
+        self.content.hash(&mut hasher);
+        self.cues.hash(&mut hasher);
+        let metadata: BTreeMap<&String, String> = self
+            .metadata
+            .iter()
+            .filter(|(key, _)| key.as_str() != EMBEDDING_KEY)
+            .map(|(key, value)| (key, value.to_string()))
+            .collect();
+        metadata.hash(&mut hasher);
+        if let Some(collection) = &self.collection {
+            collection.hash(&mut hasher);
+        }
+        if self.locked {
+            true.hash(&mut hasher);
+        }
+        format!("\"{:016x}\"", hasher.finish())
+    }
+}
+
+/// Bidirectional table between memory id strings and compact `u64` handles.
+///
+/// The cue index stores handles instead of UUID strings, so each posting costs
+/// 8 bytes rather than a full string. Handles are never reused, which keeps a
+/// stale handle from resolving to a different memory.
+#[derive(Debug, Default)]
+pub struct IdInterner {
+    handles: DashMap<Arc<str>, u64>,
+    ids: DashMap<u64, Arc<str>>,
+    next: AtomicU64,
+}
+
+impl IdInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle for `id`, assigning a new one on first use.
+    pub fn intern(&self, id: &str) -> u64 {
+        if let Some(handle) = self.handles.get(id) {
+            return *handle;
+        }
+        let id: Arc<str> = Arc::from(id);
+        *self.handles.entry(id.clone()).or_insert_with(|| {
+            let handle = self.next.fetch_add(1, Ordering::Relaxed);
+            self.ids.insert(handle, id);
+            handle
+        })
+    }
+
+    /// Handle for `id` if it has been interned.
+    pub fn get(&self, id: &str) -> Option<u64> {
+        self.handles.get(id).map(|h| *h)
+    }
+
+    /// Memory id for a handle.
+    pub fn resolve(&self, handle: u64) -> Option<Arc<str>> {
+        self.ids.get(&handle).map(|id| id.clone())
+    }
+
+    /// Resolve handles to owned ids, skipping any that were released.
+    pub fn resolve_all(&self, handles: &[u64]) -> Vec<String> {
+        handles
+            .iter()
+            .filter_map(|h| self.resolve(*h))
+            .map(|id| id.to_string())
+            .collect()
+    }
+
+    /// Forget `id`; its handle is not handed out again.
+    pub fn release(&self, id: &str) -> Option<u64> {
+        let (_, handle) = self.handles.remove(id)?;
+        self.ids.remove(&handle);
+        Some(handle)
+    }
+
+    /// Every interned id.
+    pub fn ids(&self) -> Vec<Arc<str>> {
+        self.handles.iter().map(|entry| entry.key().clone()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+}
+
+/// One shared `Arc<str>` per distinct cue string.
+///
+/// Memories, the cue index and the co-occurrence matrix hold the interned
+/// allocation, so repeating a cue costs a pointer instead of a string copy.
+/// A project's lexicon and alias engines share its main engine's interner.
+#[derive(Debug, Default)]
+pub struct CueInterner {
+    cues: DashSet<Arc<str>>,
+}
+
+impl CueInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shared allocation for `cue`, allocating only on first use.
+    pub fn intern(&self, cue: &str) -> Arc<str> {
+        if let Some(existing) = self.cues.get(cue) {
+            return existing.key().clone();
+        }
+        self.insert(Arc::from(cue))
+    }
+
+    /// Like `intern`, but adopts `cue`'s allocation when the string is new.
+    pub fn intern_shared(&self, cue: &Arc<str>) -> Arc<str> {
+        if let Some(existing) = self.cues.get(&**cue) {
+            return existing.key().clone();
+        }
+        self.insert(cue.clone())
+    }
+
+    fn insert(&self, cue: Arc<str>) -> Arc<str> {
+        if self.cues.insert(cue.clone()) {
+            return cue;
+        }
+        // Lost a race with another writer; hand out the winner's allocation
+        self.cues.get(&*cue).map(|existing| existing.key().clone()).unwrap_or(cue)
+    }
+
+    /// The interned allocation for `cue`, if any.
+    pub fn get(&self, cue: &str) -> Option<Arc<str>> {
+        self.cues.get(cue).map(|existing| existing.key().clone())
+    }
+
+    /// Drop cues nothing else references any more. Returns how many were dropped.
+    pub fn purge_unused(&self) -> usize {
+        let before = self.cues.len();
+        self.cues.retain(|cue| Arc::strong_count(cue) > 1);
+        before - self.cues.len()
+    }
+
+    /// Estimated heap bytes of the table and the interned strings.
+    pub fn estimated_bytes(&self) -> usize {
+        self.cues
+            .iter()
+            .map(|cue| std::mem::size_of::<Arc<str>>() + 2 * std::mem::size_of::<usize>() + cue.len())
+            .sum()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cues.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cues.is_empty()
+    }
 }
 
 /// Ordered set implementation using IndexSet for O(1) operations
@@ -61,79 +255,166 @@ impl Memory {
 /// - O(1) lookup
 /// - Maintains insertion order
 /// 
-/// TODO: Optimize storage by interning UUID strings to u64 integers for V2.
-/// This would reduce memory overhead from ~5M string copies to ~5M u64s (8 bytes each)
-/// for a 1M memory dataset with 5 cues per memory.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// Items are interned memory handles (see `IdInterner`), not id strings.
+///
+/// Sets larger than `POSTINGS_BITMAP_THRESHOLD` become hybrid: only the newest
+/// `POSTINGS_RECENT_RING` items stay in the IndexSet, and older ones move to a
+/// roaring bitmap. Bitmap items rank older than every ring item, and among
+/// themselves they are ordered by handle (i.e. by memory creation).
+#[derive(Debug, Clone, Default)]
 pub struct OrderedSet {
-    pub items: IndexSet<String>,
+    recent: IndexSet<u64>,
+    overflow: Option<RoaringTreemap>,
 }
 
 impl OrderedSet {
     pub fn new() -> Self {
         Self {
-            items: IndexSet::new(),
+            recent: IndexSet::new(),
+            overflow: None,
         }
     }
     
     /// Add item to the end (most recent position) - O(1) amortized
     /// If item exists, removes it first then re-adds at end
-    pub fn add(&mut self, item: String) {
+    pub fn add(&mut self, item: u64) {
+        if let Some(overflow) = self.overflow.as_mut() {
+            overflow.remove(item);
+        }
         // shift_remove is O(1) average case (hash lookup + swap with last)
         // insert is O(1) amortized
-        self.items.shift_remove(&item);
-        self.items.insert(item);
+        self.recent.shift_remove(&item);
+        self.recent.insert(item);
+        self.spill();
+    }
+
+    /// Move the oldest ring items into the bitmap once the set is large.
+    fn spill(&mut self) {
+        if let Some(overflow) = self.overflow.as_mut() {
+            while self.recent.len() > POSTINGS_RECENT_RING {
+                if let Some(oldest) = self.recent.shift_remove_index(0) {
+                    overflow.insert(oldest);
+                }
+            }
+        } else if self.recent.len() > POSTINGS_BITMAP_THRESHOLD {
+            let spill = self.recent.len() - POSTINGS_RECENT_RING;
+            self.overflow = Some(self.recent.drain(..spill).collect());
+        }
     }
 
     /// Remove item from set - O(1) amortized
-    pub fn remove(&mut self, item: &str) -> bool {
-        self.items.shift_remove(item)
+    pub fn remove(&mut self, item: u64) -> bool {
+        if self.recent.shift_remove(&item) {
+            return true;
+        }
+        let Some(overflow) = self.overflow.as_mut() else { return false };
+        let removed = overflow.remove(item);
+        if overflow.is_empty() {
+            self.overflow = None;
+        }
+        removed
     }
     
     /// Move item to end (most recent position) - O(1) amortized
     /// This is the critical operation for reinforcement
-    pub fn move_to_front(&mut self, item: &str) {
-        // O(1) removal + O(1) insertion = O(1) total
-        if self.items.shift_remove(item) {
-            self.items.insert(item.to_string());
+    pub fn move_to_front(&mut self, item: u64) {
+        if self.contains(item) {
+            self.add(item);
         }
     }
     
-    /// Get items in reverse order (most recent first) - O(min(n, limit))
-    /// Returns references to avoid cloning strings (zero-copy)
-    pub fn get_recent(&self, limit: Option<usize>) -> Vec<&String> {
-        let iter = self.items.iter().rev();
-        
-        match limit {
-            Some(lim) => iter.take(lim).collect(),
-            None => iter.collect(),
+    /// Move item to the start (least recent position) - O(n)
+    /// Used for negative feedback, which is rare compared to reinforcement.
+    /// In a hybrid set the item drops into the bitmap, ranked by its handle.
+    pub fn move_to_back(&mut self, item: u64) {
+        if let Some(index) = self.recent.get_index_of(&item) {
+            match self.overflow.as_mut() {
+                Some(overflow) => {
+                    self.recent.shift_remove_index(index);
+                    overflow.insert(item);
+                }
+                None => self.recent.move_index(index, 0),
+            }
         }
     }
     
-    /// Get items as owned strings (for serialization)
-    /// Only use when you need to own the strings
-    pub fn get_recent_owned(&self, limit: Option<usize>) -> Vec<String> {
-        let iter = self.items.iter().rev();
-        
-        match limit {
-            Some(lim) => iter.take(lim).cloned().collect(),
-            None => iter.cloned().collect(),
+    /// Get items in reverse order (most recent first) - O(min(n, limit))
+    pub fn get_recent(&self, limit: Option<usize>) -> Vec<u64> {
+        let limit = limit.unwrap_or(usize::MAX);
+        let mut items: Vec<u64> = self.recent.iter().rev().copied().take(limit).collect();
+        if let Some(overflow) = &self.overflow {
+            let remaining = limit - items.len();
+            items.extend(overflow.iter().rev().take(remaining));
         }
+        items
     }
 
-    /// Get the index of an item in the set - O(1)
+    /// Get the index of an item in the set - O(1) for ring items, O(log n) for bitmap items
     /// Note: Returns index in insertion order (oldest -> newest)
-    pub fn get_index_of(&self, item: &str) -> Option<usize> {
-        self.items.get_index_of(item)
+    pub fn get_index_of(&self, item: u64) -> Option<usize> {
+        let overflow_len = self.overflow_len();
+        if let Some(index) = self.recent.get_index_of(&item) {
+            return Some(overflow_len + index);
+        }
+        let overflow = self.overflow.as_ref()?;
+        if overflow.contains(item) {
+            Some(overflow.rank(item) as usize - 1)
+        } else {
+            None
+        }
+    }
+
+    pub fn contains(&self, item: u64) -> bool {
+        self.recent.contains(&item) || self.overflow.as_ref().is_some_and(|o| o.contains(item))
+    }
+
+    /// Number of items present in both sets. Bitmap portions are intersected
+    /// directly; only ring items are probed one by one.
+    pub fn intersection_len(&self, other: &OrderedSet) -> usize {
+        match (&self.overflow, &other.overflow) {
+            (Some(a), Some(b)) => {
+                let both_old = a.intersection_len(b) as usize;
+                let recent_a = self.recent.iter().filter(|h| other.contains(**h)).count();
+                let recent_b = other.recent.iter().filter(|h| a.contains(**h)).count();
+                both_old + recent_a + recent_b
+            }
+            _ => {
+                let (smaller, larger) = if self.len() <= other.len() { (self, other) } else { (other, self) };
+                smaller.iter().filter(|h| larger.contains(*h)).count()
+            }
+        }
+    }
+
+    /// All items, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        self.overflow
+            .iter()
+            .flat_map(|o| o.iter())
+            .chain(self.recent.iter().copied())
+    }
+
+    /// Whether older items are held in a roaring bitmap.
+    pub fn is_hybrid(&self) -> bool {
+        self.overflow.is_some()
+    }
+
+    /// Approximate heap bytes held by the set.
+    pub fn estimated_bytes(&self) -> usize {
+        self.recent.len() * (std::mem::size_of::<u64>() + INDEX_SET_ENTRY_OVERHEAD_BYTES)
+            + self.overflow.as_ref().map_or(0, |o| o.serialized_size())
+    }
+
+    fn overflow_len(&self) -> usize {
+        self.overflow.as_ref().map_or(0, |o| o.len() as usize)
     }
     
     #[allow(dead_code)]
     pub fn len(&self) -> usize {
-        self.items.len()
+        self.recent.len() + self.overflow_len()
     }
     
     #[allow(dead_code)]
     pub fn is_empty(&self) -> bool {
-        self.items.is_empty()
+        self.recent.is_empty() && self.overflow.is_none()
     }
 }
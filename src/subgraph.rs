@@ -0,0 +1,139 @@
+//! Subgraph of a cue, for visualization tools.
+//!
+//! The graph is bipartite: memory nodes link to the cue nodes they hold.
+//! Starting from one cue, each hop takes the memories holding the cues
+//! reached so far, then the other cues of those memories, so two memories
+//! sharing a co-occurring cue end up two edges apart. Memories found on the
+//! last hop are leaves: their further cues are left out. Tombstoned
+//! memories are skipped.
+
+use crate::config::SUBGRAPH_MAX_NODES;
+use crate::engine::{index_key, CueMapEngine};
+use serde::Serialize;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SubgraphNode {
+    Cue {
+        /// `cue:` followed by the cue
+        id: String,
+        cue: String,
+        /// Hops from the requested cue
+        depth: usize,
+    },
+    Memory {
+        /// `memory:` followed by the memory id
+        id: String,
+        memory_id: String,
+        content: String,
+        depth: usize,
+    },
+}
+
+/// A memory holding a cue; both ends are nodes of the subgraph.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SubgraphEdge {
+    pub source: String,
+    pub target: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Subgraph {
+    pub cue: String,
+    pub depth: usize,
+    pub nodes: Vec<SubgraphNode>,
+    pub edges: Vec<SubgraphEdge>,
+    /// Stopped at [`SUBGRAPH_MAX_NODES`] before every hop was taken
+    pub truncated: bool,
+}
+
+fn cue_node_id(cue: &str) -> String {
+    format!("cue:{}", cue)
+}
+
+fn memory_node_id(memory_id: &str) -> String {
+    format!("memory:{}", memory_id)
+}
+
+/// Subgraph around `cue` out to `depth` hops. `None` when no memory holds
+/// the cue.
+pub fn extract(engine: &CueMapEngine, cue: &str, depth: usize) -> Option<Subgraph> {
+    let seed = index_key(cue).into_owned();
+    if engine.cue_len(&seed) == 0 {
+        return None;
+    }
+
+    let mut nodes = vec![SubgraphNode::Cue { id: cue_node_id(&seed), cue: seed.clone(), depth: 0 }];
+    let mut cues: HashSet<String> = HashSet::from([seed.clone()]);
+    // Index-form cues of each memory node, for the edges
+    let mut memory_cues: Vec<(String, Vec<String>)> = Vec::new();
+    let mut seen_memories: HashSet<String> = HashSet::new();
+    let mut frontier = vec![seed.clone()];
+    let mut truncated = false;
+
+    'hops: for hop in 0..=depth {
+        let mut next = Vec::new();
+        for frontier_cue in &frontier {
+            for memory_id in engine.cue_memory_ids(frontier_cue, None) {
+                if seen_memories.contains(&memory_id) {
+                    continue;
+                }
+                let Some(memory) = engine.get_memory(&memory_id) else { continue };
+                if memory.is_tombstoned() {
+                    continue;
+                }
+                if nodes.len() >= SUBGRAPH_MAX_NODES {
+                    truncated = true;
+                    break 'hops;
+                }
+                let mut held: Vec<String> = memory.cues.iter().map(|c| index_key(c).into_owned()).collect();
+                held.sort();
+                held.dedup();
+                if hop < depth {
+                    for held_cue in &held {
+                        if !cues.contains(held_cue) {
+                            cues.insert(held_cue.clone());
+                            next.push(held_cue.clone());
+                        }
+                    }
+                }
+                nodes.push(SubgraphNode::Memory {
+                    id: memory_node_id(&memory_id),
+                    memory_id: memory_id.clone(),
+                    content: memory.content,
+                    depth: hop,
+                });
+                seen_memories.insert(memory_id.clone());
+                memory_cues.push((memory_id, held));
+            }
+        }
+        for next_cue in &next {
+            if nodes.len() >= SUBGRAPH_MAX_NODES {
+                truncated = true;
+                break 'hops;
+            }
+            nodes.push(SubgraphNode::Cue { id: cue_node_id(next_cue), cue: next_cue.clone(), depth: hop + 1 });
+        }
+        frontier = next;
+    }
+
+    // Cues queued for a hop the node cap cut off have no node
+    let cue_nodes: HashSet<&str> = nodes
+        .iter()
+        .filter_map(|node| match node {
+            SubgraphNode::Cue { cue, .. } => Some(cue.as_str()),
+            SubgraphNode::Memory { .. } => None,
+        })
+        .collect();
+    let edges = memory_cues
+        .iter()
+        .flat_map(|(memory_id, held)| {
+            held.iter()
+                .filter(|cue| cue_nodes.contains(cue.as_str()))
+                .map(move |cue| SubgraphEdge { source: memory_node_id(memory_id), target: cue_node_id(cue) })
+        })
+        .collect();
+
+    Some(Subgraph { cue: seed, depth, nodes, edges, truncated })
+}
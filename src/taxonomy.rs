@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct Taxonomy {
@@ -9,26 +10,111 @@ pub struct Taxonomy {
     pub allowed_values: HashMap<String, Vec<String>>,
     #[serde(default)]
     pub allowed_value_prefixes: HashMap<String, Vec<String>>,
+    /// Deprecated keys mapped to their replacement key (e.g. `svc` -> `service`).
+    #[serde(default)]
+    pub deprecated_keys: HashMap<String, String>,
+    /// Maximum number of distinct values allowed per key (e.g. `id` -> 1000).
+    #[serde(default)]
+    pub max_key_cardinality: HashMap<String, usize>,
+    /// What happens to a new value once its key is over the cardinality limit.
+    #[serde(default)]
+    pub cardinality_action: CardinalityAction,
+    /// What happens to cues the rules above reject.
+    #[serde(default)]
+    pub enforcement: Enforcement,
+}
+
+/// How validation treats the cues a taxonomy rejects.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Enforcement {
+    /// Reject them; memories are stored without them.
+    #[default]
+    Strict,
+    /// Accept them but report them in `ValidationReport.flagged` and count
+    /// them, to see what `strict` would turn away before switching to it.
+    Warn,
+    /// Accept them.
+    Off,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CardinalityAction {
+    /// Reject new values with `cardinality_exceeded`.
+    #[default]
+    Reject,
+    /// Accept new values but report them in `ValidationReport.flagged`.
+    Flag,
+}
+
+/// Upper bound on deprecation hops followed when resolving a key, guards against cycles.
+const MAX_DEPRECATION_HOPS: usize = 8;
+
+impl Taxonomy {
+    /// Resolve a key through the deprecation map to its current replacement.
+    /// Returns `None` if the key is not deprecated.
+    pub fn resolve_deprecated_key(&self, key: &str) -> Option<String> {
+        let mut current = self.deprecated_keys.get(key)?;
+        for _ in 0..MAX_DEPRECATION_HOPS {
+            match self.deprecated_keys.get(current) {
+                Some(next) if next != key => current = next,
+                _ => break,
+            }
+        }
+        Some(current.clone())
+    }
+
+    /// Rewrite a `key:value` cue whose key is deprecated. Returns `None` if no rewrite applies.
+    pub fn rewrite_deprecated(&self, cue: &str) -> Option<String> {
+        let (key, value) = cue.split_once(':')?;
+        let replacement = self.resolve_deprecated_key(key)?;
+        if replacement == key {
+            return None;
+        }
+        Some(format!("{}:{}", replacement, value))
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ValidationReport {
     pub accepted: Vec<String>,
     pub rejected: Vec<RejectedCue>,
+    #[serde(default)]
+    pub rewritten: Vec<RewrittenCue>,
+    /// Cues accepted despite a policy violation (e.g. cardinality in `flag` mode).
+    #[serde(default)]
+    pub flagged: Vec<RejectedCue>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RewrittenCue {
+    pub from: String,
+    pub to: String,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct RejectedCue {
     pub cue: String,
-    pub code: String,   // "bad_format" | "unknown_key" | "unknown_value"
+    pub code: String,   // "bad_format" | "unknown_key" | "unknown_value" | "cardinality_exceeded"
     pub detail: String,
 }
 
 pub fn validate_cues(cues: Vec<String>, taxonomy: &Taxonomy) -> ValidationReport {
     let mut accepted = Vec::new();
     let mut rejected = Vec::new();
+    let mut rewritten = Vec::new();
 
     for cue in cues {
+        // 0. Rewrite deprecated keys to their replacement before validating
+        let cue = match taxonomy.rewrite_deprecated(&cue) {
+            Some(new_cue) => {
+                rewritten.push(RewrittenCue { from: cue, to: new_cue.clone() });
+                new_cue
+            }
+            None => cue,
+        };
+
         // 1. Check format k:v
         let parts: Vec<&str> = cue.splitn(2, ':').collect();
         if parts.len() != 2 || parts[0].is_empty() || parts[1].is_empty() {
@@ -103,6 +189,124 @@ pub fn validate_cues(cues: Vec<String>, taxonomy: &Taxonomy) -> ValidationReport
         }
     }
 
-    ValidationReport { accepted, rejected }
+    ValidationReport { accepted, rejected, rewritten, flagged: Vec::new() }
 }
 
+
+/// Accept the cues `report` rejected unless `enforcement` is strict; in
+/// `warn` mode they move to `flagged`. Returns how many were accepted so.
+pub fn apply_enforcement(report: &mut ValidationReport, enforcement: Enforcement) -> usize {
+    if enforcement == Enforcement::Strict {
+        return 0;
+    }
+    let waived = report.rejected.len();
+    for violation in report.rejected.drain(..) {
+        report.accepted.push(violation.cue.clone());
+        if enforcement == Enforcement::Warn {
+            report.flagged.push(violation);
+        }
+    }
+    waived
+}
+
+/// What `strict` enforcement would have rejected while a project was in
+/// `warn` mode, since its taxonomy last changed.
+#[derive(Clone, Debug, Default, Serialize, PartialEq)]
+pub struct EnforcementWarnings {
+    pub total: u64,
+    /// Per rejection code, as in [`RejectedCue::code`]
+    pub by_code: BTreeMap<String, u64>,
+    /// Per cue key; the whole cue when it has no `key:value` form
+    pub by_key: BTreeMap<String, u64>,
+}
+
+/// Running [`EnforcementWarnings`] of a project.
+#[derive(Debug, Default)]
+pub struct EnforcementCounter {
+    warnings: Mutex<EnforcementWarnings>,
+}
+
+impl EnforcementCounter {
+    pub fn record(&self, violations: &[RejectedCue]) {
+        let mut warnings = self.warnings.lock().unwrap();
+        for violation in violations {
+            let key = violation.cue.split_once(':').map_or(violation.cue.as_str(), |(key, _)| key);
+            warnings.total += 1;
+            *warnings.by_code.entry(violation.code.clone()).or_default() += 1;
+            *warnings.by_key.entry(key.to_string()).or_default() += 1;
+        }
+    }
+
+    pub fn snapshot(&self) -> EnforcementWarnings {
+        self.warnings.lock().unwrap().clone()
+    }
+
+    pub fn reset(&self) {
+        *self.warnings.lock().unwrap() = EnforcementWarnings::default();
+    }
+}
+
+/// Apply per-key cardinality limits to an already validated report.
+///
+/// `is_known` tells whether a cue is already indexed (existing values are always allowed),
+/// `cardinality` returns the current number of distinct values for a key.
+pub fn enforce_cardinality(
+    report: &mut ValidationReport,
+    taxonomy: &Taxonomy,
+    is_known: impl Fn(&str) -> bool,
+    cardinality: impl Fn(&str) -> usize,
+) {
+    if taxonomy.max_key_cardinality.is_empty() {
+        return;
+    }
+
+    // New values admitted by this batch count toward the limit too
+    let mut pending: HashMap<String, Vec<String>> = HashMap::new();
+    let mut accepted = Vec::with_capacity(report.accepted.len());
+
+    for cue in report.accepted.drain(..) {
+        let key = match cue.split_once(':') {
+            Some((key, _)) => key.to_lowercase(),
+            None => {
+                accepted.push(cue);
+                continue;
+            }
+        };
+        let limit = match taxonomy.max_key_cardinality.get(&key) {
+            Some(limit) => *limit,
+            None => {
+                accepted.push(cue);
+                continue;
+            }
+        };
+
+        let normalized = cue.to_lowercase();
+        let batch = pending.entry(key.clone()).or_default();
+        if is_known(&normalized) || batch.contains(&normalized) {
+            accepted.push(cue);
+            continue;
+        }
+
+        let current = cardinality(&key) + batch.len();
+        if current < limit {
+            batch.push(normalized);
+            accepted.push(cue);
+            continue;
+        }
+
+        let violation = RejectedCue {
+            cue: cue.clone(),
+            code: "cardinality_exceeded".to_string(),
+            detail: format!("Key '{}' already has {} distinct values (limit {})", key, current, limit),
+        };
+        match taxonomy.cardinality_action {
+            CardinalityAction::Reject => report.rejected.push(violation),
+            CardinalityAction::Flag => {
+                report.flagged.push(violation);
+                accepted.push(cue);
+            }
+        }
+    }
+
+    report.accepted = accepted;
+}
@@ -0,0 +1,232 @@
+//! Tracing subscriber setup and per-request spans.
+//!
+//! Log output is human-readable or JSON (`--log-format`), filtered per module
+//! through `RUST_LOG` (default `info`). Every request span carries a
+//! `request_id` (the caller's `X-Request-Id`, or a generated UUID), so all log
+//! lines emitted while handling a request can be correlated.
+//!
+//! With the `otel` feature, spans are exported over OTLP/HTTP when
+//! `OTEL_EXPORTER_OTLP_ENDPOINT` (or `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT`) is
+//! set, and incoming W3C `traceparent` headers become the parent of request spans.
+
+use axum::{
+    extract::{MatchedPath, Request},
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Instrument;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+/// Header carrying the request id, read from callers and echoed on responses.
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+tokio::task_local! {
+    /// Id of the request being handled, set by [`trace_request`].
+    static REQUEST_ID: String;
+}
+
+/// Id of the request the current task is handling, if it runs under [`trace_request`].
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(String::clone).ok()
+}
+
+/// Filter used when `RUST_LOG` is unset or invalid.
+const DEFAULT_LOG_FILTER: &str = "info";
+
+/// Longest caller-supplied request id that is propagated as-is.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Console log line format.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines with span context
+    #[default]
+    Pretty,
+    /// One JSON object per line, with the enclosing spans' fields
+    Json,
+}
+
+/// Service name reported to the collector unless `OTEL_SERVICE_NAME` is set.
+#[cfg(feature = "otel")]
+const DEFAULT_SERVICE_NAME: &str = "cuemap-engine";
+
+/// Flushes pending spans when dropped.
+pub struct TelemetryGuard {
+    #[cfg(feature = "otel")]
+    provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otel")]
+        if let Some(provider) = self.provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Failed to flush OpenTelemetry spans: {}", e);
+            }
+        }
+    }
+}
+
+fn otlp_endpoint_configured() -> bool {
+    ["OTEL_EXPORTER_OTLP_ENDPOINT", "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT"]
+        .iter()
+        .any(|var| std::env::var(var).map(|v| !v.trim().is_empty()).unwrap_or(false))
+}
+
+fn env_filter() -> EnvFilter {
+    match EnvFilter::try_from_default_env() {
+        Ok(filter) => filter,
+        Err(e) => {
+            if std::env::var_os(EnvFilter::DEFAULT_ENV).is_some() {
+                eprintln!("Ignoring invalid RUST_LOG ({}); using \"{}\"", e, DEFAULT_LOG_FILTER);
+            }
+            EnvFilter::new(DEFAULT_LOG_FILTER)
+        }
+    }
+}
+
+/// Install the global tracing subscriber (console output in `format`, plus OTLP
+/// export when configured).
+pub fn init(format: LogFormat) -> TelemetryGuard {
+    let (pretty, json) = match format {
+        LogFormat::Pretty => (Some(tracing_subscriber::fmt::layer()), None),
+        LogFormat::Json => (
+            None,
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_current_span(false)
+                    .with_span_list(true),
+            ),
+        ),
+    };
+    let registry = tracing_subscriber::registry()
+        .with(env_filter())
+        .with(pretty)
+        .with(json);
+
+    #[cfg(feature = "otel")]
+    if otlp_endpoint_configured() {
+        match otel::tracer_provider() {
+            Ok(provider) => {
+                use opentelemetry::trace::TracerProvider as _;
+                let tracer = provider.tracer("cuemap");
+                registry.with(tracing_opentelemetry::layer().with_tracer(tracer)).init();
+                tracing::info!("OpenTelemetry trace export enabled");
+                return TelemetryGuard { provider: Some(provider) };
+            }
+            Err(e) => {
+                registry.init();
+                tracing::error!("Failed to initialize OpenTelemetry exporter: {}", e);
+                return TelemetryGuard { provider: None };
+            }
+        }
+    }
+
+    registry.init();
+    #[cfg(not(feature = "otel"))]
+    if otlp_endpoint_configured() {
+        tracing::warn!("OTEL_EXPORTER_OTLP_ENDPOINT is set, but this build lacks the `otel` feature; traces will not be exported");
+    }
+    TelemetryGuard {
+        #[cfg(feature = "otel")]
+        provider: None,
+    }
+}
+
+/// The caller's request id when it is usable as a log field, otherwise a new UUID.
+fn request_id(request: &Request) -> String {
+    request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN)
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+}
+
+/// Middleware wrapping each request in a server span, parented to the caller's
+/// `traceparent` when trace propagation is enabled. The request id is recorded
+/// on the span and returned in the `X-Request-Id` response header.
+pub async fn trace_request(request: Request, next: Next) -> Response {
+    let request_id = request_id(&request);
+    let method = request.method().clone();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+
+    let span = tracing::info_span!(
+        "http.request",
+        otel.name = %format!("{} {}", method, route),
+        otel.kind = "server",
+        http.request.method = %method,
+        http.route = %route,
+        http.response.status_code = tracing::field::Empty,
+        request_id = %request_id,
+    );
+
+    #[cfg(feature = "otel")]
+    otel::set_remote_parent(&span, request.headers());
+
+    let mut response = REQUEST_ID.scope(request_id.clone(), next.run(request).instrument(span.clone())).await;
+    span.record("http.response.status_code", response.status().as_u16());
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use axum::http::HeaderMap;
+    use opentelemetry::propagation::Extractor;
+    use opentelemetry_otlp::SpanExporter;
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use opentelemetry_sdk::Resource;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    struct HeaderExtractor<'a>(&'a HeaderMap);
+
+    impl Extractor for HeaderExtractor<'_> {
+        fn get(&self, key: &str) -> Option<&str> {
+            self.0.get(key).and_then(|v| v.to_str().ok())
+        }
+
+        fn keys(&self) -> Vec<&str> {
+            self.0.keys().map(|k| k.as_str()).collect()
+        }
+    }
+
+    /// Build the OTLP/HTTP exporter; endpoint and headers come from the standard `OTEL_*` env vars.
+    pub(super) fn tracer_provider() -> Result<SdkTracerProvider, String> {
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .build()
+            .map_err(|e| e.to_string())?;
+        let service_name = std::env::var("OTEL_SERVICE_NAME")
+            .unwrap_or_else(|_| super::DEFAULT_SERVICE_NAME.to_string());
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(Resource::builder().with_service_name(service_name).build())
+            .build();
+
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        opentelemetry::global::set_tracer_provider(provider.clone());
+        Ok(provider)
+    }
+
+    pub(super) fn set_remote_parent(span: &tracing::Span, headers: &HeaderMap) {
+        let parent = opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(headers))
+        });
+        let _ = span.set_parent(parent);
+    }
+}
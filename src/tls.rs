@@ -0,0 +1,60 @@
+//! TLS and mutual-TLS server configuration.
+
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+
+fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(file))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to parse certificates in {:?}: {}", path, e))?;
+    if certs.is_empty() {
+        return Err(format!("No certificates found in {:?}", path));
+    }
+    Ok(certs)
+}
+
+fn load_private_key(path: &Path) -> Result<PrivateKeyDer<'static>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    rustls_pemfile::private_key(&mut BufReader::new(file))
+        .map_err(|e| format!("Failed to parse private key in {:?}: {}", path, e))?
+        .ok_or_else(|| format!("No private key found in {:?}", path))
+}
+
+/// Build a rustls server config from PEM files.
+///
+/// With `client_ca`, clients must present a certificate signed by one of the
+/// CAs in that bundle (mTLS); otherwise client certificates are not requested.
+pub fn server_config(cert: &Path, key: &Path, client_ca: Option<&Path>) -> Result<ServerConfig, String> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("Invalid TLS protocol configuration: {}", e))?;
+
+    let builder = match client_ca {
+        Some(ca_path) => {
+            let mut roots = RootCertStore::empty();
+            for ca in load_certs(ca_path)? {
+                roots
+                    .add(ca)
+                    .map_err(|e| format!("Invalid client CA certificate in {:?}: {}", ca_path, e))?;
+            }
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .map_err(|e| format!("Failed to build client certificate verifier: {}", e))?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut config = builder
+        .with_single_cert(load_certs(cert)?, load_private_key(key)?)
+        .map_err(|e| format!("Invalid TLS certificate or key: {}", e))?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(config)
+}
@@ -0,0 +1,520 @@
+//! Webhook notifications on memory lifecycle events.
+//!
+//! A webhook subscribes a URL to events of one project: `memory_added`,
+//! `memory_deleted`, `memory_merged` (consolidation summarized memories),
+//! `alias_approved` (an alias became active) and `stale_digest` (memories
+//! listed for review before they go stale, see [`crate::digest`]). Events come from the project's
+//! [`ChangeFeed`](crate::events::ChangeFeed)s and are POSTed as JSON:
+//!
+//! ```json
+//! {"id": "<delivery id>", "event": "memory_added", "project": "default",
+//!  "webhook_id": "...", "created_at": 1717000000.0, "data": {...}}
+//! ```
+//!
+//! The body is signed with the webhook's secret: `X-CueMap-Signature` is
+//! `sha256=<hex HMAC-SHA256 of the body>`. Failed deliveries (network errors,
+//! `408`, `429` and `5xx`) are retried with exponential backoff up to
+//! [`MAX_ATTEMPTS`] times; the outcome of recent deliveries is kept in memory
+//! for `GET /admin/webhooks/:id/deliveries`.
+
+use crate::digest::DIGEST_METADATA_KEY;
+use crate::events::{ChangeEvent, ChangeRecord};
+use crate::jobs::ProjectProvider;
+use crate::projects::ProjectContext;
+use dashmap::DashMap;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
+
+/// Subscriptions file in the data directory.
+pub const WEBHOOKS_FILE: &str = "webhooks.json";
+
+/// Events a webhook can subscribe to.
+pub const EVENT_TYPES: [&str; 6] = ["memory_added", "memory_updated", "memory_deleted", "memory_merged", "alias_approved", "stale_digest"];
+
+pub const SIGNATURE_HEADER: &str = "X-CueMap-Signature";
+pub const EVENT_HEADER: &str = "X-CueMap-Event";
+pub const DELIVERY_HEADER: &str = "X-CueMap-Delivery";
+
+/// Attempts per delivery, including the first.
+pub const MAX_ATTEMPTS: u32 = 5;
+
+/// Deliveries remembered per webhook for the status endpoint.
+pub const DELIVERY_HISTORY: usize = 100;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A stored subscription, including its secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Webhook {
+    id: String,
+    project_id: String,
+    url: String,
+    /// Subscribed event types; empty means all of them
+    events: Vec<String>,
+    secret: String,
+    created_at: f64,
+}
+
+/// A subscription as listed by the API; the secret is never returned.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookInfo {
+    pub id: String,
+    pub project_id: String,
+    pub url: String,
+    pub events: Vec<String>,
+    pub created_at: f64,
+}
+
+impl From<&Webhook> for WebhookInfo {
+    fn from(hook: &Webhook) -> Self {
+        Self {
+            id: hook.id.clone(),
+            project_id: hook.project_id.clone(),
+            url: hook.url.clone(),
+            events: hook.events.clone(),
+            created_at: hook.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+/// Outcome of one event sent to one webhook.
+#[derive(Debug, Clone, Serialize)]
+pub struct Delivery {
+    pub id: String,
+    pub event: String,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    /// HTTP status of the last attempt, if the endpoint answered
+    pub response_status: Option<u16>,
+    pub last_error: Option<String>,
+    pub created_at: f64,
+    pub updated_at: f64,
+}
+
+/// An event ready to send: its type and the `data` of the payload.
+#[derive(Debug, Clone)]
+struct WebhookEvent {
+    kind: &'static str,
+    at: f64,
+    data: serde_json::Value,
+}
+
+/// Subscriptions of every project plus their delivery state.
+pub struct WebhookHub {
+    hooks: DashMap<String, Webhook>,
+    deliveries: DashMap<String, VecDeque<Delivery>>,
+    /// Projects whose change feeds are being forwarded
+    watched: DashMap<String, ()>,
+    client: reqwest::Client,
+    retry_base: Duration,
+    /// Subscriptions file; `None` keeps subscriptions in memory only.
+    path: Option<PathBuf>,
+}
+
+impl Default for WebhookHub {
+    fn default() -> Self {
+        Self {
+            hooks: DashMap::new(),
+            deliveries: DashMap::new(),
+            watched: DashMap::new(),
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            retry_base: Duration::from_secs(1),
+            path: None,
+        }
+    }
+}
+
+impl WebhookHub {
+    /// Load subscriptions from `path`, starting empty if the file is missing or unreadable.
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let hub = Self::default();
+        if path.exists() {
+            match fs::read(&path).map_err(|e| e.to_string()).and_then(|data| {
+                serde_json::from_slice::<Vec<Webhook>>(&data).map_err(|e| e.to_string())
+            }) {
+                Ok(loaded) => {
+                    for hook in loaded {
+                        hub.hooks.insert(hook.id.clone(), hook);
+                    }
+                    info!("Loaded {} webhooks from {:?}", hub.hooks.len(), path);
+                }
+                Err(e) => warn!("Failed to load webhooks from {:?}: {}", path, e),
+            }
+        }
+        Self { path: Some(path), ..hub }
+    }
+
+    /// Base delay between attempts, doubled after each failure (1s by default).
+    pub fn with_retry_base(mut self, retry_base: Duration) -> Self {
+        self.retry_base = retry_base;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Subscribe `url` to `events` of a project (all events when empty).
+    /// Without a `secret` one is generated. Returns the subscription and the
+    /// secret, which cannot be retrieved again.
+    pub fn create(
+        self: &Arc<Self>,
+        project_id: &str,
+        project: &Arc<ProjectContext>,
+        url: String,
+        events: Vec<String>,
+        secret: Option<String>,
+    ) -> Result<(WebhookInfo, String), String> {
+        let parsed = reqwest::Url::parse(&url).map_err(|e| format!("invalid url: {}", e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err("url must be http or https".to_string());
+        }
+        if let Some(unknown) = events.iter().find(|e| !EVENT_TYPES.contains(&e.as_str())) {
+            return Err(format!("unknown event type '{}' (expected one of {})", unknown, EVENT_TYPES.join(", ")));
+        }
+        let secret = match secret {
+            Some(secret) if secret.is_empty() => return Err("secret must not be empty".to_string()),
+            Some(secret) => secret,
+            None => uuid::Uuid::new_v4().simple().to_string(),
+        };
+
+        let hook = Webhook {
+            id: uuid::Uuid::new_v4().simple().to_string()[..12].to_string(),
+            project_id: project_id.to_string(),
+            url,
+            events: dedup(events),
+            secret: secret.clone(),
+            created_at: now(),
+        };
+        let info = WebhookInfo::from(&hook);
+        self.hooks.insert(hook.id.clone(), hook);
+        self.persist();
+        self.watch(project_id, project);
+        Ok((info, secret))
+    }
+
+    /// Subscriptions of a project, oldest first.
+    pub fn list(&self, project_id: &str) -> Vec<WebhookInfo> {
+        let mut hooks: Vec<WebhookInfo> = self
+            .hooks
+            .iter()
+            .filter(|hook| hook.project_id == project_id)
+            .map(|hook| WebhookInfo::from(hook.value()))
+            .collect();
+        hooks.sort_by(|a, b| a.created_at.total_cmp(&b.created_at));
+        hooks
+    }
+
+    /// Remove a project's subscription. False if it does not exist.
+    pub fn delete(&self, project_id: &str, id: &str) -> bool {
+        if self.hooks.remove_if(id, |_, hook| hook.project_id == project_id).is_none() {
+            return false;
+        }
+        self.deliveries.remove(id);
+        self.persist();
+        true
+    }
+
+    /// Remove every subscription of a deleted project.
+    pub fn remove_project(&self, project_id: &str) {
+        let ids: Vec<String> = self
+            .hooks
+            .iter()
+            .filter(|hook| hook.project_id == project_id)
+            .map(|hook| hook.id.clone())
+            .collect();
+        if ids.is_empty() {
+            return;
+        }
+        for id in &ids {
+            self.hooks.remove(id);
+            self.deliveries.remove(id);
+        }
+        self.persist();
+    }
+
+    /// Recent deliveries of a project's webhook, newest first; `None` if the
+    /// webhook does not exist.
+    pub fn deliveries(&self, project_id: &str, id: &str) -> Option<Vec<Delivery>> {
+        self.hooks.get(id).filter(|hook| hook.project_id == project_id)?;
+        Some(
+            self.deliveries
+                .get(id)
+                .map(|history| history.iter().rev().cloned().collect())
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Forward events of every project with subscriptions, e.g. after loading them at startup.
+    pub fn watch_all(self: &Arc<Self>, provider: &dyn ProjectProvider) {
+        let projects: HashSet<String> = self.hooks.iter().map(|hook| hook.project_id.clone()).collect();
+        for project_id in projects {
+            match provider.get_project(&project_id) {
+                Some(project) => self.watch(&project_id, &project),
+                None => debug!("Webhooks of unloaded project {} stay idle", project_id),
+            }
+        }
+    }
+
+    /// Forward a project's change feeds to its webhooks, once per project.
+    /// The forwarder stops when the project is dropped.
+    pub fn watch(self: &Arc<Self>, project_id: &str, project: &Arc<ProjectContext>) {
+        if self.watched.insert(project_id.to_string(), ()).is_some() {
+            return;
+        }
+        let mut memories = project.main.events().subscribe();
+        let mut aliases = project.aliases.events().subscribe();
+        let project = Arc::downgrade(project);
+        let project_id = project_id.to_string();
+        let hub = self.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let (received, is_alias) = tokio::select! {
+                    received = memories.recv() => (received, false),
+                    received = aliases.recv() => (received, true),
+                };
+                let record = match received {
+                    Ok(record) => record,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Webhooks of project {} skipped {} events", project_id, skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                let Some(project) = project.upgrade() else { break };
+                let event = if is_alias {
+                    alias_event(record)
+                } else {
+                    memory_event(&project, record)
+                };
+                if let Some(event) = event {
+                    hub.dispatch(&project_id, event);
+                }
+            }
+            hub.watched.remove(&project_id);
+        });
+    }
+
+    /// Start a delivery to every webhook of the project subscribed to `event`.
+    fn dispatch(self: &Arc<Self>, project_id: &str, event: WebhookEvent) {
+        let targets: Vec<Webhook> = self
+            .hooks
+            .iter()
+            .filter(|hook| hook.project_id == project_id)
+            .filter(|hook| hook.events.is_empty() || hook.events.iter().any(|e| e == event.kind))
+            .map(|hook| hook.value().clone())
+            .collect();
+
+        for hook in targets {
+            let delivery = Delivery {
+                id: uuid::Uuid::new_v4().to_string(),
+                event: event.kind.to_string(),
+                status: DeliveryStatus::Pending,
+                attempts: 0,
+                response_status: None,
+                last_error: None,
+                created_at: event.at,
+                updated_at: now(),
+            };
+            let body = serde_json::json!({
+                "id": delivery.id,
+                "event": event.kind,
+                "project": project_id,
+                "webhook_id": hook.id,
+                "created_at": event.at,
+                "data": event.data,
+            })
+            .to_string();
+
+            let mut history = self.deliveries.entry(hook.id.clone()).or_default();
+            if history.len() >= DELIVERY_HISTORY {
+                history.pop_front();
+            }
+            history.push_back(delivery.clone());
+            drop(history);
+
+            let hub = self.clone();
+            tokio::spawn(async move { hub.deliver(hook, delivery, body).await });
+        }
+    }
+
+    /// POST `body` until it is accepted, a non-retryable status comes back
+    /// or the attempts run out.
+    async fn deliver(&self, hook: Webhook, mut delivery: Delivery, body: String) {
+        let signature = format!("sha256={}", sign(&hook.secret, body.as_bytes()));
+        loop {
+            delivery.attempts += 1;
+            let result = self
+                .client
+                .post(&hook.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(SIGNATURE_HEADER, &signature)
+                .header(EVENT_HEADER, &delivery.event)
+                .header(DELIVERY_HEADER, &delivery.id)
+                .body(body.clone())
+                .send()
+                .await;
+
+            let retryable = match result {
+                Ok(response) => {
+                    let status = response.status();
+                    delivery.response_status = Some(status.as_u16());
+                    if status.is_success() {
+                        delivery.status = DeliveryStatus::Delivered;
+                        delivery.last_error = None;
+                        self.record(&hook.id, &delivery);
+                        return;
+                    }
+                    delivery.last_error = Some(format!("endpoint returned {}", status));
+                    status.is_server_error()
+                        || status == reqwest::StatusCode::REQUEST_TIMEOUT
+                        || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                }
+                Err(e) => {
+                    delivery.response_status = None;
+                    delivery.last_error = Some(e.to_string());
+                    true
+                }
+            };
+
+            if !retryable || delivery.attempts >= MAX_ATTEMPTS {
+                delivery.status = DeliveryStatus::Failed;
+                self.record(&hook.id, &delivery);
+                warn!(
+                    "Webhook {} gave up on {} delivery {} after {} attempts: {}",
+                    hook.id,
+                    delivery.event,
+                    delivery.id,
+                    delivery.attempts,
+                    delivery.last_error.as_deref().unwrap_or_default()
+                );
+                return;
+            }
+            self.record(&hook.id, &delivery);
+            tokio::time::sleep(self.retry_base * 2u32.pow(delivery.attempts - 1)).await;
+        }
+    }
+
+    fn record(&self, hook_id: &str, delivery: &Delivery) {
+        if let Some(mut history) = self.deliveries.get_mut(hook_id) {
+            if let Some(entry) = history.iter_mut().find(|d| d.id == delivery.id) {
+                *entry = Delivery { updated_at: now(), ..delivery.clone() };
+            }
+        }
+    }
+
+    fn persist(&self) {
+        let Some(path) = &self.path else { return };
+        let mut hooks: Vec<Webhook> = self.hooks.iter().map(|hook| hook.value().clone()).collect();
+        hooks.sort_by(|a, b| a.created_at.total_cmp(&b.created_at));
+        if let Err(e) = Self::save(path, &hooks) {
+            warn!("Failed to persist webhooks to {:?}: {}", path, e);
+        }
+    }
+
+    fn save(path: &Path, hooks: &[Webhook]) -> std::io::Result<()> {
+        let data = serde_json::to_vec_pretty(hooks)?;
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, data)?;
+        fs::rename(&temp_path, path)
+    }
+}
+
+/// Hex HMAC-SHA256 of `body` under `secret`, as sent in [`SIGNATURE_HEADER`].
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+/// Map a main-engine change to a webhook event; adds carry the stored memory.
+fn memory_event(project: &ProjectContext, record: ChangeRecord) -> Option<WebhookEvent> {
+    let (kind, data) = match record.event {
+        ChangeEvent::MemoryAdded { memory_id, cues } => {
+            let memory = project.main.get_memory(&memory_id);
+            ("memory_added", serde_json::json!({
+                "memory_id": memory_id,
+                "cues": cues,
+                "content": memory.as_ref().map(|m| m.content.clone()),
+                "metadata": memory.map(|m| m.metadata),
+            }))
+        }
+        ChangeEvent::MemoryUpdated { memory_id, cues } => {
+            let content = project.main.get_memory(&memory_id).map(|m| m.content);
+            ("memory_updated", serde_json::json!({
+                "memory_id": memory_id,
+                "cues": cues,
+                "content": content,
+            }))
+        }
+        ChangeEvent::MemoryDeleted { memory_id } => ("memory_deleted", serde_json::json!({"memory_id": memory_id})),
+        ChangeEvent::MemoryMerged { memory_id, sources } => {
+            let content = project.main.get_memory(&memory_id).map(|m| m.content);
+            ("memory_merged", serde_json::json!({
+                "memory_id": memory_id,
+                "sources": sources,
+                "content": content,
+            }))
+        }
+        ChangeEvent::StaleDigest { memory_id, .. } => {
+            let digest = project.main.get_memory(&memory_id).and_then(|m| m.metadata.get(DIGEST_METADATA_KEY).cloned());
+            ("stale_digest", serde_json::json!({
+                "memory_id": memory_id,
+                "digest": digest,
+            }))
+        }
+        ChangeEvent::MemoryReinforced { .. } | ChangeEvent::JobCompleted { .. } => return None,
+    };
+    Some(WebhookEvent { kind, at: record.at, data })
+}
+
+/// Aliases are memories of the alias engine; one stored as active is approved.
+fn alias_event(record: ChangeRecord) -> Option<WebhookEvent> {
+    let ChangeEvent::MemoryAdded { memory_id, cues } = record.event else {
+        return None;
+    };
+    if !cues.iter().any(|cue| cue == "status:active") {
+        return None;
+    }
+    let value = |prefix: &str| cues.iter().find_map(|cue| cue.strip_prefix(prefix).map(str::to_string));
+    Some(WebhookEvent {
+        kind: "alias_approved",
+        at: record.at,
+        data: serde_json::json!({
+            "alias_id": memory_id,
+            "from": value("from:"),
+            "to": value("to:"),
+            "reason": value("reason:"),
+        }),
+    })
+}
+
+fn dedup(events: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    events.into_iter().filter(|e| seen.insert(e.clone())).collect()
+}
+
+fn now() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs_f64()
+}
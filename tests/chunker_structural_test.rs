@@ -1,5 +1,7 @@
-use cuemap_rust::agent::chunker::Chunker;
-use std::path::PathBuf;
+use cuemap_rust::agent::chunker::{registry, Chunk, Chunker, FileChunker};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 #[test]
 fn test_all_formats_structural_cues() {
@@ -47,3 +49,153 @@ fn test_pdf_office_fallbacks() {
     // pdf_extract might return empty if it can't find the file, which is fine.
     // The main thing is they use ChunkerType::Pdf/Office.
 }
+
+struct WholeFile(&'static str);
+
+impl FileChunker for WholeFile {
+    fn chunk(&self, _path: &Path, content: &str) -> Vec<Chunk> {
+        vec![Chunk {
+            content: content.to_string(),
+            start_line: 1,
+            end_line: content.lines().count(),
+            context: "file".to_string(),
+            structural_cues: vec![self.0.to_string()],
+            metadata: HashMap::new(),
+        }]
+    }
+}
+
+#[test]
+fn test_registered_chunkers() {
+    registry().register_extension(".Proto", Arc::new(WholeFile("lang:protobuf")));
+    let chunks = Chunker::chunk_file(&PathBuf::from("api/payments.proto"), "syntax = \"proto3\";\nmessage Refund {}");
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0].structural_cues, vec!["lang:protobuf"]);
+
+    // Extensionless files are routed by their sniffed MIME type
+    let script = "#!/usr/bin/env python3\ndef main(): pass";
+    let builtin = Chunker::chunk_file(&PathBuf::from("bin/deploy"), script);
+    assert!(builtin.iter().any(|c| c.structural_cues.contains(&"lang:python".to_string())));
+    registry().register_mime("text/x-shellscript", Arc::new(WholeFile("lang:shell")));
+    let chunks = Chunker::chunk_file(&PathBuf::from("bin/release"), "#!/bin/bash\nset -e");
+    assert_eq!(chunks[0].structural_cues, vec!["lang:shell"]);
+}
+
+#[test]
+fn test_html_readability_sections() {
+    let page = r#"<!DOCTYPE html>
+<html>
+<head>
+  <title>Refund Runbook &amp; FAQ</title>
+  <meta property="og:url" content="https://wiki.example.com/payments/refunds">
+  <script>window.analytics = {};</script>
+  <style>.hero { color: red; }</style>
+</head>
+<body>
+  <nav><a href="/">Home</a> <a href="/docs">Docs</a></nav>
+  <div class="cookie-banner">We use cookies to improve your experience.</div>
+  <article>
+    <header><h1>Refunds</h1></header>
+    <p>Refunds are issued by the <b>payments</b> service, which calls the ledger to reverse the original charge.</p>
+    <h2>Failure modes</h2>
+    <p>When the ledger is unavailable, refunds are queued and retried every five minutes.</p>
+    <ul><li>Check the ledger health dashboard.</li><li>Restart the refund worker if the queue keeps growing.</li></ul>
+    <div class="share-buttons">Share on social media</div>
+  </article>
+  <footer>Copyright 2026 Example Corp</footer>
+</body>
+</html>"#;
+    let chunks = Chunker::chunk_file(&PathBuf::from("saved/refunds.html"), page);
+
+    assert_eq!(chunks.iter().map(|c| c.context.as_str()).collect::<Vec<_>>(), vec!["section:Refunds", "section:Failure modes"]);
+    for chunk in &chunks {
+        assert!(chunk.structural_cues.contains(&"type:html_section".to_string()));
+        assert!(chunk.structural_cues.contains(&"title:Refund Runbook & FAQ".to_string()));
+        assert!(chunk.structural_cues.contains(&"url:https://wiki.example.com/payments/refunds".to_string()));
+        for boilerplate in ["<", "cookies", "analytics", "Home", "Copyright", "social media"] {
+            assert!(!chunk.content.contains(boilerplate), "{:?} kept {:?}", chunk.content, boilerplate);
+        }
+    }
+    assert_eq!(
+        chunks[0].content,
+        "# Refunds\n\nRefunds are issued by the payments service, which calls the ledger to reverse the original charge."
+    );
+    assert!(chunks[1].content.starts_with("## Failure modes\n\nWhen the ledger is unavailable"));
+    assert!(chunks[1].content.ends_with("- Check the ledger health dashboard.\n\n- Restart the refund worker if the queue keeps growing."));
+    assert!(chunks[1].structural_cues.contains(&"header:Failure modes".to_string()));
+}
+
+/// PDF with one line of text per page and a document information dictionary.
+fn write_pdf(path: &Path, pages: &[&str]) {
+    use pdf_extract::content::{Content, Operation};
+    use pdf_extract::{dictionary, Document, Object, Stream, StringFormat};
+
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+    });
+    let resources_id = doc.add_object(dictionary! { "Font" => dictionary! { "F1" => font_id } });
+    let kids: Vec<Object> = pages
+        .iter()
+        .map(|text| {
+            let content = Content {
+                operations: vec![
+                    Operation::new("BT", vec![]),
+                    Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                    Operation::new("Td", vec![72.into(), 720.into()]),
+                    Operation::new("Tj", vec![Object::string_literal(*text)]),
+                    Operation::new("ET", vec![]),
+                ],
+            };
+            let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+            doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => content_id,
+            })
+            .into()
+        })
+        .collect();
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => kids.len() as i64,
+            "Kids" => kids,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+    let info_id = doc.add_object(dictionary! {
+        "Title" => Object::String(b"Incident Handbook".to_vec(), StringFormat::Literal),
+        // UTF-16BE with a byte order mark
+        "Author" => Object::String(vec![0xFE, 0xFF, 0x00, b'R', 0x00, 0xE9, 0x00, b'a'], StringFormat::Hexadecimal),
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.trailer.set("Info", info_id);
+    doc.save(path).unwrap();
+}
+
+#[test]
+fn test_pdf_chunked_by_page() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("handbook.pdf");
+    write_pdf(&path, &["Page one covers paging the on-call engineer.", "Page two covers the postmortem."]);
+
+    let chunks = Chunker::chunk_file(&path, "");
+    assert_eq!(chunks.iter().map(|c| c.context.as_str()).collect::<Vec<_>>(), vec!["page:1", "page:2"]);
+    assert!(chunks[0].content.contains("on-call engineer"));
+    assert!(!chunks[0].content.contains("postmortem"));
+    assert!(chunks[1].content.contains("postmortem"));
+    for (chunk, page) in chunks.iter().zip(1..) {
+        assert!(chunk.structural_cues.contains(&"title:Incident Handbook".to_string()));
+        assert!(chunk.structural_cues.contains(&"author:Réa".to_string()));
+        assert!(chunk.structural_cues.contains(&format!("page:{}", page)));
+        assert_eq!(chunk.metadata["page"], serde_json::json!(page));
+        assert_eq!(chunk.metadata["title"], serde_json::json!("Incident Handbook"));
+    }
+}
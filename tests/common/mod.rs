@@ -0,0 +1,113 @@
+//! Helpers shared by the test suites; each suite pulls this in with
+//! `#[path = "../common/mod.rs"] mod common;`.
+#![allow(dead_code)]
+
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::{Json, Router};
+use cuemap_rust::api;
+use cuemap_rust::auth::AuthConfig;
+use cuemap_rust::grounding::GroundingTraceStore;
+use cuemap_rust::jobs::{JobQueue, SingleTenantProvider};
+use cuemap_rust::projects::ProjectContext;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::sync::{Mutex, MutexGuard};
+
+pub fn cues(cues: &[&str]) -> Vec<String> {
+    cues.iter().map(|cue| cue.to_string()).collect()
+}
+
+/// The single-tenant API over `project`, with no API keys and an in-memory
+/// trace store.
+pub fn router(project: &Arc<ProjectContext>) -> Router {
+    router_with_traces(project, Arc::new(GroundingTraceStore::new(100, None)))
+}
+
+pub fn router_with_traces(project: &Arc<ProjectContext>, traces: Arc<GroundingTraceStore>) -> Router {
+    let job_queue = Arc::new(JobQueue::new(Arc::new(SingleTenantProvider { project: project.clone() })));
+    api::routes(project.clone(), job_queue, traces, AuthConfig::with_keys(HashMap::new()), false)
+}
+
+/// Serves `app` on a free local port and returns its base URL.
+pub async fn serve(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app.into_make_service_with_connect_info::<std::net::SocketAddr>()).await });
+    format!("http://{}", addr)
+}
+
+static LLM_ENV: Mutex<()> = Mutex::const_new(());
+
+/// Points the LLM settings at a stand-in Ollama at `url`. The settings are
+/// process-wide, so tests sharing a binary hold the returned guard until
+/// they finish.
+pub async fn use_mock_llm(url: &str) -> MutexGuard<'static, ()> {
+    let guard = LLM_ENV.lock().await;
+    std::env::set_var("LLM_PROVIDER", "ollama");
+    std::env::set_var("LLM_MODEL", "mistral");
+    std::env::set_var("OLLAMA_URL", url);
+    guard
+}
+
+type GenerateFn = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+type EmbedFn = Arc<dyn Fn(&str) -> Vec<f32> + Send + Sync>;
+
+/// A stand-in Ollama answering either `/api/generate` or `/api/embed`.
+pub enum MockOllama {
+    Generate(GenerateFn),
+    Embed(EmbedFn),
+}
+
+impl MockOllama {
+    /// Answers each prompt with `respond`'s text; `None` fails the call.
+    pub fn generate(respond: impl Fn(&str) -> Option<String> + Send + Sync + 'static) -> Self {
+        Self::Generate(Arc::new(respond))
+    }
+
+    /// Embeds each input with `embed`.
+    pub fn embed(embed: impl Fn(&str) -> Vec<f32> + Send + Sync + 'static) -> Self {
+        Self::Embed(Arc::new(embed))
+    }
+
+    /// Serves the stand-in and returns its URL, along with every prompt or
+    /// input it receives.
+    pub async fn start(self) -> (String, mpsc::UnboundedReceiver<String>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let app = match self {
+            Self::Generate(respond) => Router::new().route(
+                "/api/generate",
+                post(move |Json(body): Json<Value>| async move {
+                    let prompt = body["prompt"].as_str().unwrap_or_default();
+                    let _ = tx.send(prompt.to_string());
+                    let text = respond(prompt).ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+                    Ok::<_, StatusCode>(Json(serde_json::json!({ "response": text })))
+                }),
+            ),
+            Self::Embed(embed) => Router::new().route(
+                "/api/embed",
+                post(move |Json(body): Json<Value>| async move {
+                    let embeddings: Vec<Vec<f32>> = body["input"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .map(|input| {
+                            let input = input.as_str().unwrap();
+                            let _ = tx.send(input.to_string());
+                            embed(input)
+                        })
+                        .collect();
+                    Json(serde_json::json!({ "embeddings": embeddings }))
+                }),
+            ),
+        };
+        (serve(app).await, rx)
+    }
+}
+
+/// The model's answer to an extraction prompt.
+pub fn extraction(summary: &str, cues: &[&str]) -> String {
+    serde_json::json!({ "summary": summary, "cues": cues }).to_string()
+}
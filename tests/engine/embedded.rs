@@ -0,0 +1,107 @@
+use cuemap_rust::embedded::*;
+use cuemap_rust::CueMap;
+use std::time::Duration;
+use tempfile::tempdir;
+
+#[test]
+fn test_in_memory_roundtrip() {
+    let cuemap = CueMap::in_memory();
+    let added = cuemap.add("Payments timed out after the deploy", ["Service:Payments", "error:timeout"]).unwrap();
+    cuemap.add("Search latency is back to normal", ["service:search"]).unwrap();
+    assert_eq!(cuemap.len(), 2);
+
+    // Cues are normalized like the server does
+    let results = cuemap.recall(["service:payments"], 10);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].memory_id, added.id);
+
+    assert!(cuemap.reinforce(&added.id, ["team:core"]));
+    assert!(!cuemap.reinforce("missing", ["team:core"]));
+    assert!(cuemap.get(&added.id).unwrap().salience > 1.0);
+
+    assert!(cuemap.delete(&added.id));
+    assert!(cuemap.get(&added.id).is_none());
+    assert!(matches!(cuemap.snapshot(), Err(CueMapError::NoDataDir)));
+}
+
+#[test]
+fn test_open_snapshot_and_reopen() {
+    let dir = tempdir().unwrap();
+
+    let trace_id = {
+        let cuemap = CueMap::open(dir.path()).unwrap();
+        let added = cuemap.add("The payments service was rolled back", ["service:payments"]).unwrap();
+        cuemap.project().lexicon.upsert_memory_with_id("lex_1".to_string(), "service:payments".to_string(), vec!["tok:payments".to_string()], None, false);
+
+        let grounded = cuemap.recall_grounded(&GroundedQuery::new("payments"));
+        assert!(grounded.verified_context.contains("rolled back"));
+        assert_eq!(grounded.proof.selected[0].memory_id, added.id);
+        cuemap.snapshot().unwrap();
+        grounded.proof.trace_id
+    };
+
+    let reopened = CueMap::open(dir.path()).unwrap();
+    assert_eq!(reopened.len(), 1);
+    assert_eq!(reopened.recall(["service:payments"], 10).len(), 1);
+    // Grounding traces are kept in the data directory too
+    assert!(reopened.trace(&trace_id).is_some());
+}
+
+#[test]
+fn test_data_dir_lock() {
+    use cuemap_rust::persistence::{LockInfo, LOCK_FILE};
+
+    let dir = tempdir().unwrap();
+    let lock_path = dir.path().join(LOCK_FILE);
+    let read_lock = || serde_json::from_slice::<LockInfo>(&std::fs::read(&lock_path).unwrap()).unwrap();
+
+    let first = CueMap::open(dir.path()).unwrap();
+    assert_eq!(read_lock().pid, std::process::id());
+    let err = CueMap::open(dir.path()).err().unwrap();
+    assert!(matches!(err, CueMapError::Locked(_)));
+    assert!(err.to_string().contains("--force-unlock"), "{}", err);
+
+    // Forcing takes the lock over; the old holder no longer removes it
+    let second = CueMap::builder().data_dir(dir.path()).force_unlock(true).open().unwrap();
+    let token = read_lock().token;
+    // The old holder stops writing snapshots
+    assert!(matches!(first.snapshot(), Err(CueMapError::Locked(_))));
+    second.snapshot().unwrap();
+    drop(first);
+    assert_eq!(read_lock().token, token);
+    drop(second);
+    assert!(!lock_path.exists());
+    let leftovers: Vec<_> = std::fs::read_dir(dir.path()).unwrap().flatten().filter(|entry| entry.path().extension().is_some_and(|ext| ext == "tmp")).collect();
+    assert!(leftovers.is_empty());
+
+    // A lock whose holder stopped heartbeating is stale
+    let mut stale = LockInfo { pid: std::process::id(), token: "crashed".to_string(), acquired_at: 0.0, heartbeat: 0.0 };
+    std::fs::write(&lock_path, serde_json::to_vec(&stale).unwrap()).unwrap();
+    drop(CueMap::open(dir.path()).unwrap());
+    stale.heartbeat = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs_f64();
+    std::fs::write(&lock_path, serde_json::to_vec(&stale).unwrap()).unwrap();
+    assert!(CueMap::open(dir.path()).is_err());
+}
+
+#[test]
+fn test_background_jobs_need_a_runtime() {
+    let result = CueMap::builder().background_jobs(true).open();
+    assert!(matches!(result, Err(CueMapError::NoRuntime)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_background_jobs_train_lexicon() {
+    let cuemap = CueMap::builder().background_jobs(true).open().unwrap();
+    let added = cuemap.add("Checkout failures in the payments gateway", ["service:payments"]).unwrap();
+
+    // Lexicon training runs in the background; text recall picks it up once done
+    let mut results = Vec::new();
+    for _ in 0..50 {
+        results = cuemap.recall_text("payments", 10);
+        if !results.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(results[0].memory_id, added.id);
+}
@@ -0,0 +1,194 @@
+use cuemap_rust::grounding::*;
+use tempfile::tempdir;
+
+fn proof(trace_id: &str) -> GroundingProof {
+    create_grounding_proof(
+        trace_id.to_string(),
+        "payments latency".to_string(),
+        vec!["service:payments".to_string()],
+        vec![("service:payments".to_string(), 1.0)],
+        500,
+        Vec::new(),
+        Vec::new(),
+    )
+}
+
+#[test]
+fn test_trace_store_get_and_list() {
+    let store = GroundingTraceStore::new(100, None);
+    store.record("proj_a", proof("t1"));
+    store.record("proj_b", proof("t2"));
+    store.record("proj_a", proof("t3"));
+
+    let trace = store.get("t2").expect("trace should be stored");
+    assert_eq!(trace.project_id, "proj_b");
+    assert_eq!(trace.proof.query_text, "payments latency");
+    assert!(store.get("missing").is_none());
+
+    // Newest first, filtered by project
+    let filter = TraceFilter {
+        project: Some("proj_a".to_string()),
+        ..Default::default()
+    };
+    let listed: Vec<String> = store.list(&filter).into_iter().map(|t| t.proof.trace_id).collect();
+    assert_eq!(listed, vec!["t3", "t1"]);
+
+    // Time range excluding everything
+    let filter = TraceFilter {
+        until: Some(0.0),
+        ..Default::default()
+    };
+    assert!(store.list(&filter).is_empty());
+}
+
+#[test]
+fn test_trace_store_bounded_retention() {
+    let store = GroundingTraceStore::new(2, None);
+    store.record("p", proof("t1"));
+    store.record("p", proof("t2"));
+    store.record("p", proof("t3"));
+
+    assert_eq!(store.len(), 2);
+    assert!(store.get("t1").is_none());
+    assert!(store.get("t3").is_some());
+}
+
+#[test]
+fn test_trace_store_survives_reopen() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("grounding_traces.jsonl");
+
+    {
+        let store = GroundingTraceStore::open(&path, 10, None);
+        store.record("p", proof("t1"));
+        store.record("p", proof("t2"));
+    }
+
+    let store = GroundingTraceStore::open(&path, 1, None);
+    assert_eq!(store.len(), 1);
+    assert!(store.get("t2").is_some());
+}
+
+fn selected(memory_id: &str) -> SelectedItem {
+    SelectedItem {
+        memory_id: memory_id.to_string(),
+        content: "payments service timed out".to_string(),
+        score: 100.0,
+        intersection_count: 1,
+        recency_component: 1.0,
+        reinforcement_component: 0.0,
+        match_integrity: 1.0,
+        source: "unknown".to_string(),
+        timestamp: "2025-01-01T00:00:00Z".to_string(),
+        estimated_tokens: 7,
+        why: "test".to_string(),
+    }
+}
+
+#[test]
+fn test_citation_check() {
+    let mut p = proof("t1");
+    p.selected = vec![selected("mem-1"), selected("file:src/pay.rs:abc")];
+
+    let answer = "The payments service timed out yesterday [mem-1]. \
+                  It is implemented in the pay module [file:src/pay.rs:abc]. \
+                  The outage was caused by a bad deploy [mem-9]. \
+                  Engineers restarted the database cluster twice. \
+                  Why?";
+    let report = check_citations(&p, answer);
+
+    assert_eq!(report.valid_citations, vec!["mem-1", "file:src/pay.rs:abc"]);
+    assert_eq!(report.invalid_citations, vec!["mem-9"]);
+    assert_eq!(report.uncited_sentences, vec!["Engineers restarted the database cluster twice."]);
+    assert_eq!(report.total_sentences, 5);
+    assert!(!report.grounded);
+
+    let report = check_citations(&p, "The payments service timed out. [mem-1]\nUnknown.");
+    assert!(report.grounded);
+}
+
+fn result(memory_id: &str, score: f64, age_days: f64) -> cuemap_rust::engine::RecallResult {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs_f64();
+    cuemap_rust::engine::RecallResult {
+        memory_id: memory_id.to_string(),
+        content: "short fact".to_string(),
+        score,
+        match_integrity: 1.0,
+        intersection_count: 1,
+        recency_score: 1.0,
+        reinforcement_score: 0.0,
+        salience_score: 1.0,
+        created_at: now - age_days * 86_400.0,
+        metadata: Default::default(),
+        locked: false,
+        matched_cues: Vec::new(),
+        explain: None,
+    }
+}
+
+#[test]
+fn test_freshness_policy() {
+    let results = || vec![result("old", 120.0, 90.0), result("fresh", 100.0, 1.0)];
+
+    // max_age_days excludes stale memories with a too_old reason
+    let policy = FreshnessPolicy { max_age_days: Some(30.0), prefer_fresh: false };
+    let (selected, excluded, _) =
+        GroundingEngine::select_memories(String::new(), vec![], vec![], results(), 500, &policy);
+    assert_eq!(selected.len(), 1);
+    assert_eq!(selected[0].memory_id, "fresh");
+    assert_eq!(excluded[0].memory_id, "old");
+    assert_eq!(excluded[0].reason, "too_old");
+
+    // prefer_fresh downweights old memories so fresher ones rank first
+    let policy = FreshnessPolicy { max_age_days: None, prefer_fresh: true };
+    let (selected, _, _) =
+        GroundingEngine::select_memories(String::new(), vec![], vec![], results(), 500, &policy);
+    assert_eq!(selected[0].memory_id, "fresh");
+    assert!(selected[1].score < 120.0);
+
+    // Default policy keeps engine ordering
+    let (selected, _, _) = GroundingEngine::select_memories(
+        String::new(), vec![], vec![], results(), 500, &FreshnessPolicy::default(),
+    );
+    assert_eq!(selected[0].memory_id, "old");
+}
+
+#[test]
+fn test_trace_feedback_metrics() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("grounding_traces.jsonl");
+
+    {
+        let store = GroundingTraceStore::open(&path, 10, None);
+        store.record("p", proof("t1"));
+        store.record("p", proof("t2"));
+        store.record("other", proof("t3"));
+
+        let feedback = TraceFeedback::new(vec!["m1".to_string(), "m2".to_string()], vec!["m3".to_string()]);
+        assert!(store.record_feedback("t1", feedback.clone()).is_ok());
+        assert_eq!(store.record_feedback("t1", feedback).unwrap_err(), FeedbackError::AlreadySubmitted);
+        assert_eq!(
+            store.record_feedback("missing", TraceFeedback::new(Vec::new(), Vec::new())).unwrap_err(),
+            FeedbackError::TraceNotFound
+        );
+        store.record_feedback("t2", TraceFeedback::new(Vec::new(), vec!["m4".to_string()])).unwrap();
+    }
+
+    // Feedback survives a reopen and replaces the original trace line
+    let store = GroundingTraceStore::open(&path, 10, None);
+    assert_eq!(store.len(), 3);
+    assert_eq!(store.get("t1").unwrap().feedback.unwrap().useful.len(), 2);
+
+    let metrics = store.feedback_metrics("p");
+    assert_eq!(metrics.traces_with_feedback, 2);
+    assert_eq!((metrics.useful, metrics.harmful), (2, 2));
+    assert_eq!(metrics.precision, Some(0.5));
+    // (2/3 + 0/1) / 2
+    assert!((metrics.mean_trace_precision.unwrap() - 1.0 / 3.0).abs() < 1e-9);
+
+    assert_eq!(store.feedback_metrics("other").traces_with_feedback, 0);
+    assert_eq!(store.feedback_metrics("other").precision, None);
+}
@@ -1,20 +1,33 @@
-use cuemap_rust::engine::CueMapEngine;
+#[path = "../common/mod.rs"]
+mod common;
+
+mod embedded;
+mod grounding;
+mod warm_up;
+
+use cuemap_rust::config::PATTERN_COMPLETION_WEIGHT;
+use cuemap_rust::engine::{AttachOutcome, CueMapEngine, UpdateOutcome};
+use cuemap_rust::structures::Memory;
+
+fn cue_strs(memory: &Memory) -> Vec<&str> {
+    memory.cues.iter().map(|cue| &**cue).collect()
+}
 
 #[test]
 fn test_memory_cues_storage() {
     let engine = CueMapEngine::new();
     let cues = vec!["a".to_string(), "b".to_string()];
-    let memory_id = engine.add_memory("test content".to_string(), cues.clone(), None);
+    let memory_id = engine.add_memory("test content".to_string(), cues.clone(), None, false);
 
     let memory = engine.get_memory(&memory_id).unwrap();
-    assert_eq!(memory.cues, cues);
+    assert_eq!(cue_strs(&memory), cues);
 }
 
 #[test]
 fn test_attach_cues() {
     let engine = CueMapEngine::new();
     let initial_cues = vec!["a".to_string()];
-    let memory_id = engine.add_memory("test content".to_string(), initial_cues.clone(), None);
+    let memory_id = engine.add_memory("test content".to_string(), initial_cues.clone(), None, false);
 
     // Attach new cues
     let new_cues = vec!["b".to_string(), "c".to_string()];
@@ -24,7 +37,7 @@ fn test_attach_cues() {
     // Verify memory has all cues
     let memory = engine.get_memory(&memory_id).unwrap();
     let expected_cues = vec!["a".to_string(), "b".to_string(), "c".to_string()];
-    assert_eq!(memory.cues, expected_cues);
+    assert_eq!(cue_strs(&memory), expected_cues);
 
     // Verify recall works with new cues
     let results = engine.recall(vec!["b".to_string()], 10, false);
@@ -36,13 +49,97 @@ fn test_attach_cues() {
     assert!(!attached_again);
 }
 
+#[test]
+fn test_attach_cues_if_match() {
+    let engine = CueMapEngine::new();
+    let memory_id = engine.add_memory("test content".to_string(), vec!["a".to_string()], None, false);
+    let etag = engine.get_memory(&memory_id).unwrap().etag();
+
+    // Reinforcement does not change the ETag; new cues do
+    engine.reinforce_memory(&memory_id, vec!["a".to_string()]);
+    assert_eq!(engine.get_memory(&memory_id).unwrap().etag(), etag);
+    assert_eq!(engine.attach_cues_if_match(&memory_id, vec!["b".to_string()], Some(&etag)), AttachOutcome::Attached);
+    let current = engine.get_memory(&memory_id).unwrap().etag();
+    assert_ne!(current, etag);
+
+    // A writer holding the old ETag is refused and changes nothing
+    assert_eq!(
+        engine.attach_cues_if_match(&memory_id, vec!["c".to_string()], Some(&etag)),
+        AttachOutcome::Stale(current.clone())
+    );
+    assert_eq!(cue_strs(&engine.get_memory(&memory_id).unwrap()), vec!["a", "b"]);
+    assert_eq!(engine.attach_cues_if_match(&memory_id, vec!["b".to_string()], Some(&current)), AttachOutcome::Unchanged);
+    assert_eq!(engine.attach_cues_if_match("missing", vec!["b".to_string()], None), AttachOutcome::NotFound);
+}
+
+#[test]
+fn test_update_memory_if_match() {
+    let engine = CueMapEngine::new();
+    let memory_id = engine.add_memory("old content".to_string(), vec!["a".to_string(), "b".to_string()], None, false);
+    let etag = engine.get_memory(&memory_id).unwrap().etag();
+
+    // Content alone keeps the cues
+    assert_eq!(engine.update_memory_if_match(&memory_id, "new content".to_string(), None, Some(&etag)), UpdateOutcome::Updated);
+    let memory = engine.get_memory(&memory_id).unwrap();
+    assert_eq!(memory.content, "new content");
+    assert_eq!(cue_strs(&memory), vec!["a", "b"]);
+
+    // A writer holding the old ETag is refused and changes nothing
+    let current = memory.etag();
+    assert_eq!(
+        engine.update_memory_if_match(&memory_id, "lost".to_string(), None, Some(&etag)),
+        UpdateOutcome::Stale(current.clone())
+    );
+
+    // Replaced cues leave the index and new ones join it
+    let cues = vec!["b".to_string(), "c".to_string()];
+    assert_eq!(engine.update_memory_if_match(&memory_id, "newer".to_string(), Some(cues), Some(&current)), UpdateOutcome::Updated);
+    assert_eq!(cue_strs(&engine.get_memory(&memory_id).unwrap()), vec!["b", "c"]);
+    assert_eq!(engine.cue_len("a"), 0);
+    assert_eq!(engine.cue_memory_ids("c", None), vec![memory_id.clone()]);
+    assert_eq!(engine.cue_len("b"), 1);
+    assert!(engine.check_index_consistency().is_empty());
+    assert_eq!(engine.update_memory_if_match("missing", "x".to_string(), None, None), UpdateOutcome::NotFound);
+}
+
+#[test]
+fn test_equal_scores_order_deterministically() {
+    let engine = CueMapEngine::new();
+    // One memory per cue, alike in everything but id and age, so scores tie
+    let ids: Vec<String> = ["x", "y", "z"]
+        .iter()
+        .map(|cue| engine.add_memory("same".to_string(), vec![cue.to_string()], None, true))
+        .collect();
+    let query = || engine.recall_weighted(vec![("x".to_string(), 1.0), ("y".to_string(), 1.0), ("z".to_string(), 1.0)], 10, false, None, false, true, false, false, None);
+
+    // Newest first
+    for (i, id) in ids.iter().enumerate() {
+        engine.get_memories().get_mut(id).unwrap().created_at = 1_000.0 - i as f64;
+    }
+    let results = query();
+    assert_eq!(results[0].score, results[2].score);
+    let order: Vec<&str> = results.iter().map(|r| r.memory_id.as_str()).collect();
+    assert_eq!(order, ids.iter().map(String::as_str).collect::<Vec<_>>());
+
+    // Then by id
+    for id in &ids {
+        engine.get_memories().get_mut(id).unwrap().created_at = 1_000.0;
+    }
+    let mut sorted = ids.clone();
+    sorted.sort();
+    for _ in 0..5 {
+        let order: Vec<String> = query().into_iter().map(|r| r.memory_id).collect();
+        assert_eq!(order, sorted);
+    }
+}
+
 #[test]
 fn test_freshness_boost() {
     let engine = CueMapEngine::new();
     
     // Add two memories with the same cue
-    let _id1 = engine.add_memory("oldest".to_string(), vec!["topic".to_string()], None);
-    let id2 = engine.add_memory("newest".to_string(), vec!["topic".to_string()], None);
+    let _id1 = engine.add_memory("oldest".to_string(), vec!["topic".to_string()], None, false);
+    let id2 = engine.add_memory("newest".to_string(), vec!["topic".to_string()], None, false);
     
     let results = engine.recall(vec!["topic".to_string()], 10, true);
     
@@ -64,7 +161,7 @@ fn test_scoring_gradient() {
     // Add many memories to create a deep list
     let mut ids = Vec::new();
     for i in 0..10 {
-        ids.push(engine.add_memory(format!("content {}", i), vec![cue.clone()], None));
+        ids.push(engine.add_memory(format!("content {}", i), vec![cue.clone()], None, false));
     }
     
     let results = engine.recall(vec![cue], 10, false);
@@ -75,11 +172,26 @@ fn test_scoring_gradient() {
     }
 }
 
+#[test]
+fn test_matched_cues() {
+    let engine = CueMapEngine::new();
+    let timeout = engine.add_memory("timed out".to_string(), vec!["service:payments".to_string(), "error:timeout".to_string()], None, false);
+    let refund = engine.add_memory("refunded".to_string(), vec!["service:payments".to_string()], None, false);
+
+    // error:timeout is inferred from co-occurrence and matched at its lower weight
+    let results = engine.recall(vec!["service:payments".to_string(), "region:eu".to_string()], 10, false);
+    assert_eq!(results.len(), 2);
+    let matched = |id: &str| results.iter().find(|r| r.memory_id == id).unwrap().matched_cues.clone();
+    assert_eq!(matched(&refund), vec![("service:payments".to_string(), 1.0)]);
+    assert_eq!(matched(&timeout), vec![("service:payments".to_string(), 1.0), ("error:timeout".to_string(), PATTERN_COMPLETION_WEIGHT)]);
+    assert!(results.iter().all(|r| r.explain.is_none()));
+}
+
 #[test]
 fn test_log_frequency_scaling() {
     let engine = CueMapEngine::new();
-    let id1 = engine.add_memory("frequent".to_string(), vec!["cue".to_string()], None);
-    let id2 = engine.add_memory("rare".to_string(), vec!["cue".to_string()], None);
+    let id1 = engine.add_memory("frequent".to_string(), vec!["cue".to_string()], None, false);
+    let id2 = engine.add_memory("rare".to_string(), vec!["cue".to_string()], None, false);
     
     // id1 gets 100 reinforcements
     for _ in 0..100 {
@@ -101,3 +213,426 @@ fn test_log_frequency_scaling() {
     assert_eq!(res1.reinforcement_score, 2.0);
     assert_eq!(res2.reinforcement_score, 1.0);
 }
+
+#[test]
+fn test_rename_cue_key() {
+    let engine = CueMapEngine::new();
+    let a = engine.add_memory("payments outage".to_string(), vec!["svc:payments".to_string(), "topic:outage".to_string()], None, true);
+    let b = engine.add_memory("already migrated".to_string(), vec!["svc:auth".to_string(), "service:auth".to_string()], None, true);
+    engine.add_memory("unrelated".to_string(), vec!["topic:misc".to_string()], None, true);
+
+    let migrated = engine.rename_cue_key("svc", "service");
+    assert_eq!(migrated, 2);
+
+    let memory = engine.get_memory(&a).unwrap();
+    assert_eq!(cue_strs(&memory), vec!["service:payments".to_string(), "topic:outage".to_string()]);
+
+    // Duplicate cues collapse after rewrite
+    let memory = engine.get_memory(&b).unwrap();
+    assert_eq!(cue_strs(&memory), vec!["service:auth".to_string()]);
+
+    assert!(engine.recall(vec!["svc:payments".to_string()], 10, false).is_empty());
+    let results = engine.recall(vec!["service:payments".to_string()], 10, false);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].memory_id, a);
+    assert!(!engine.get_cue_index().contains_key("svc:auth"));
+}
+
+#[test]
+fn test_key_cardinality_tracking() {
+    let engine = CueMapEngine::new();
+    engine.add_memory("a".to_string(), vec!["id:1".to_string(), "topic:x".to_string()], None, true);
+    engine.add_memory("b".to_string(), vec!["id:2".to_string(), "topic:x".to_string()], None, true);
+
+    assert_eq!(engine.key_cardinality("id"), 2);
+    assert_eq!(engine.key_cardinality("topic"), 1);
+    assert!(engine.has_cue("ID:1"));
+
+    let stats = engine.get_stats();
+    assert_eq!(stats["key_cardinality"]["id"], 2);
+}
+
+#[test]
+fn test_penalize_memory() {
+    let engine = CueMapEngine::new();
+    let older = engine.add_memory("older".to_string(), vec!["topic:db".to_string()], None, true);
+    let newer = engine.add_memory("newer".to_string(), vec!["topic:db".to_string()], None, true);
+
+    // Newest first before feedback
+    let results = engine.recall(vec!["topic:db".to_string()], 10, false);
+    assert_eq!(results[0].memory_id, newer);
+
+    assert!(engine.penalize_memory(&newer, vec!["topic:db".to_string(), "topic:unknown".to_string()]));
+    assert!(engine.get_memory(&newer).unwrap().salience < engine.get_memory(&older).unwrap().salience);
+
+    let results = engine.recall(vec!["topic:db".to_string()], 10, false);
+    assert_eq!(results[0].memory_id, older);
+
+    assert!(!engine.penalize_memory("missing", Vec::new()));
+}
+
+#[test]
+fn test_interned_cue_index() {
+    let engine = CueMapEngine::new();
+    let old = engine.add_memory("old".to_string(), vec!["svc:auth".to_string()], None, false);
+    let new = engine.add_memory("new".to_string(), vec!["svc:auth".to_string()], None, false);
+    let ids = engine.get_id_table();
+    assert_eq!(ids.len(), 2);
+    assert_ne!(ids.get(&old), ids.get(&new));
+    assert_eq!(engine.cue_memory_ids("svc:auth", None), vec![new.clone(), old.clone()]);
+
+    // Deleting releases the handle and its postings
+    assert!(engine.delete_memory(&old));
+    assert_eq!(ids.get(&old), None);
+    assert_eq!(engine.cue_memory_ids("svc:auth", None), vec![new.clone()]);
+
+    // Rebuilding from persisted (string) state re-interns ids
+    let memories = engine.get_memories().as_ref().clone();
+    let cue_index = dashmap::DashMap::new();
+    cue_index.insert("svc:auth".to_string(), engine.cue_memory_ids("svc:auth", None));
+    let restored = CueMapEngine::from_state(memories, cue_index);
+    let results = restored.recall(vec!["svc:auth".to_string()], 10, false);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].memory_id, new);
+}
+
+#[test]
+fn test_hybrid_postings() {
+    use cuemap_rust::config::{POSTINGS_BITMAP_THRESHOLD, POSTINGS_RECENT_RING};
+    use cuemap_rust::structures::OrderedSet;
+
+    let n = (POSTINGS_BITMAP_THRESHOLD + 10_000) as u64;
+    let mut set = OrderedSet::new();
+    for handle in 0..n {
+        set.add(handle);
+    }
+    assert!(set.is_hybrid());
+    assert_eq!(set.len(), n as usize);
+    assert_eq!(set.get_recent(Some(2)), vec![n - 1, n - 2]);
+    // Bitmap members keep handle order below the recent ring
+    assert_eq!(set.get_index_of(10), Some(10));
+    assert_eq!(set.get_index_of(n - 1), Some(n as usize - 1));
+    assert_eq!(set.get_recent(None).len(), n as usize);
+
+    // Reinforcing an old member brings it back into the ring
+    set.move_to_front(10);
+    assert_eq!(set.get_recent(Some(1)), vec![10]);
+    assert_eq!(set.get_index_of(10), Some(n as usize - 1));
+    set.move_to_back(10);
+    assert_eq!(set.get_index_of(10), Some(10));
+
+    assert!(set.remove(11));
+    assert!(!set.contains(11));
+    assert_eq!(set.len(), n as usize - 1);
+
+    let mut other = OrderedSet::new();
+    for handle in (n / 2)..(n + POSTINGS_RECENT_RING as u64) {
+        other.add(handle);
+    }
+    let probed = set.iter().filter(|h| other.contains(*h)).count();
+    assert_eq!(set.intersection_len(&other), probed);
+    assert_eq!(other.intersection_len(&set), probed);
+}
+
+#[test]
+fn test_recall_candidate_cap_and_top_k() {
+    let engine = CueMapEngine::new();
+    let mut both = Vec::new();
+    for i in 0..5 {
+        both.push(engine.add_memory(format!("both {}", i), vec!["a".to_string(), "b".to_string()], None, true));
+    }
+    for i in 0..50 {
+        engine.add_memory(format!("c only {}", i), vec!["c".to_string()], None, true);
+    }
+
+    // Strong a+b matches win even though the c list is scanned last and may be skipped
+    let results = engine.recall_weighted(
+        vec![("a".to_string(), 1.0), ("b".to_string(), 1.0), ("c".to_string(), 1.0)],
+        5, false, None, false, true, false, false, None,
+    );
+    assert_eq!(results.len(), 5);
+    assert!(results.iter().all(|r| both.contains(&r.memory_id)));
+    assert!(results.windows(2).all(|w| w[0].score >= w[1].score));
+
+    // The cap never drops below `limit`, and results stay sorted best first
+    let capped = engine.recall_weighted(vec![("c".to_string(), 1.0)], 3, false, None, false, true, false, false, Some(1));
+    assert_eq!(capped.len(), 3);
+    assert!(capped.windows(2).all(|w| w[0].score >= w[1].score));
+    assert!(capped[0].content.starts_with("c only 49"));
+}
+
+#[test]
+fn test_engine_sizing() {
+    use cuemap_rust::config::EngineSizing;
+
+    assert!(EngineSizing { shard_count: 64, expected_memories: 0 }.validate().is_ok());
+    assert!(EngineSizing { shard_count: 1, expected_memories: 0 }.validate().is_err());
+    assert!(EngineSizing { shard_count: 96, expected_memories: 0 }.validate().is_err());
+
+    let sizing = EngineSizing { shard_count: 8, expected_memories: 10_000 };
+    assert_eq!(sizing.expected_cues(), 400);
+    let engine = CueMapEngine::with_sizing(sizing);
+    assert!(engine.get_memories().capacity() >= 10_000);
+    let id = engine.add_memory("sized".to_string(), vec!["a".to_string()], None, false);
+    assert_eq!(engine.recall(vec!["a".to_string()], 1, false)[0].memory_id, id);
+}
+
+#[test]
+fn test_read_view_isolation() {
+    let engine = CueMapEngine::new();
+    let old = engine.add_memory("old".to_string(), vec!["a".to_string()], None, false);
+    assert_eq!(engine.read_view_status(), None);
+    assert_eq!(engine.refresh_read_view(), None);
+
+    engine.enable_read_view();
+    assert_eq!(engine.read_view_status(), Some((1, 0)));
+
+    // Writes stay invisible to recall until the next refresh
+    let new = engine.add_memory("new".to_string(), vec!["a".to_string(), "b".to_string()], None, false);
+    assert_eq!(engine.read_view_status(), Some((1, 2)));
+    let before = engine.recall(vec!["a".to_string()], 10, false);
+    assert_eq!(before.len(), 1);
+    assert_eq!(before[0].memory_id, old);
+    assert!(engine.recall(vec!["b".to_string()], 10, false).is_empty());
+
+    assert_eq!(engine.refresh_read_view(), Some(2));
+    assert_eq!(engine.read_view_status(), Some((2, 0)));
+    assert_eq!(engine.recall(vec!["a".to_string()], 10, false)[0].memory_id, new);
+    // Nothing pending: the epoch stays put
+    assert_eq!(engine.refresh_read_view(), Some(2));
+
+    // Deleted memories drop out right away even though the view still lists them
+    engine.delete_memory(&new);
+    let after_delete = engine.recall(vec!["a".to_string()], 10, false);
+    assert_eq!(after_delete.len(), 1);
+    assert_eq!(after_delete[0].memory_id, old);
+}
+
+#[test]
+fn test_cue_interning() {
+    use std::sync::Arc;
+
+    let engine = CueMapEngine::new();
+    let a = engine.add_memory("first".to_string(), vec!["service:payments".to_string()], None, true);
+    let b = engine.add_memory("second".to_string(), vec!["service:payments".to_string(), "Topic:Outage".to_string()], None, true);
+
+    // Memories and the index share one allocation per cue
+    let first = engine.get_memory(&a).unwrap();
+    let second = engine.get_memory(&b).unwrap();
+    assert!(Arc::ptr_eq(&first.cues[0], &second.cues[0]));
+    let key = engine.get_cue_index().get("service:payments").unwrap().key().clone();
+    assert!(Arc::ptr_eq(&key, &first.cues[0]));
+
+    // Stored cues keep their case; the index uses the normalized form
+    assert_eq!(&*second.cues[1], "Topic:Outage");
+    assert!(engine.has_cue(" TOPIC:outage "));
+    assert_eq!(engine.recall(vec!["topic:outage".to_string()], 5, false)[0].memory_id, b);
+
+    // Purging drops only cues nothing else holds
+    let table = cuemap_rust::structures::CueInterner::new();
+    let kept = table.intern("kept");
+    table.intern("dropped");
+    assert_eq!(table.purge_unused(), 1);
+    assert!(Arc::ptr_eq(&kept, &table.intern("kept")));
+    assert!(table.get("dropped").is_none());
+}
+
+#[test]
+fn test_concurrent_soak_keeps_index_consistent() {
+    use std::sync::Arc;
+
+    let engine = Arc::new(CueMapEngine::new());
+    engine.enable_read_view();
+    let workers: Vec<_> = (0..4)
+        .map(|t| {
+            let engine = engine.clone();
+            std::thread::spawn(move || {
+                let mut kept = Vec::new();
+                for i in 0..300 {
+                    let cues = vec![format!("service:s{}", i % 7), format!("topic:t{}", (i + t) % 5)];
+                    let id = engine.add_memory(format!("worker {} memory {}", t, i), cues.clone(), None, true);
+                    engine.recall(cues.clone(), 5, i % 3 == 0);
+                    engine.reinforce_memory(&id, vec![cues[0].clone()]);
+                    if i % 4 == 0 {
+                        engine.delete_memory(&id);
+                    } else {
+                        kept.push(id);
+                    }
+                    if i % 50 == 0 {
+                        engine.refresh_read_view();
+                    }
+                }
+                kept
+            })
+        })
+        .collect();
+    let kept: Vec<String> = workers.into_iter().flat_map(|w| w.join().unwrap()).collect();
+
+    assert_eq!(engine.get_memories().len(), kept.len());
+    assert!(kept.iter().all(|id| engine.get_memory(id).is_some()));
+    assert_eq!(engine.check_index_consistency(), Vec::<String>::new());
+
+    // A dangling posting is reported
+    engine.get_cue_index().get_mut("service:s1").unwrap().add(u64::MAX);
+    let problems = engine.check_index_consistency();
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].contains("unknown handle"));
+}
+
+#[test]
+fn test_delete_racing_an_upsert_still_reports_the_delete() {
+    use cuemap_rust::events::ChangeEvent;
+    use std::sync::Arc;
+
+    let engine = Arc::new(CueMapEngine::new());
+    let mut events = engine.events().subscribe();
+    let start = Arc::new(std::sync::Barrier::new(2));
+    let upserter = {
+        let (engine, start) = (engine.clone(), start.clone());
+        std::thread::spawn(move || {
+            start.wait();
+            for i in 0..20_000 {
+                engine.upsert_memory_with_id("racer".to_string(), format!("take {}", i), vec!["topic:race".to_string()], None, false);
+            }
+        })
+    };
+    start.wait();
+    let mut deleted = 0;
+    while !upserter.is_finished() {
+        if engine.delete_memory("racer") {
+            deleted += 1;
+        }
+    }
+    upserter.join().unwrap();
+
+    let received = std::iter::from_fn(|| events.try_recv().ok())
+        .filter(|record| matches!(record.event, ChangeEvent::MemoryDeleted { .. }))
+        .count();
+    assert_eq!(received, deleted);
+    assert_eq!(engine.check_index_consistency(), Vec::<String>::new());
+}
+
+#[test]
+fn test_change_feed_events() {
+    use cuemap_rust::events::ChangeEvent;
+
+    let engine = CueMapEngine::new();
+    // Nothing is buffered before the first subscriber
+    engine.add_memory("unseen".to_string(), vec!["a".to_string()], None, true);
+    let mut events = engine.events().subscribe();
+
+    let id = engine.add_memory("seen".to_string(), vec!["a".to_string(), "b".to_string()], None, true);
+    assert!(engine.reinforce_memory(&id, vec!["b".to_string()]));
+    assert!(engine.delete_memory(&id));
+    assert!(!engine.delete_memory(&id));
+
+    let received: Vec<ChangeEvent> = std::iter::from_fn(|| events.try_recv().ok()).map(|record| record.event).collect();
+    assert_eq!(
+        received,
+        vec![
+            ChangeEvent::MemoryAdded { memory_id: id.clone(), cues: vec!["a".to_string(), "b".to_string()] },
+            ChangeEvent::MemoryReinforced { memory_id: id.clone(), cues: vec!["b".to_string()] },
+            ChangeEvent::MemoryDeleted { memory_id: id },
+        ]
+    );
+}
+
+#[test]
+fn test_replace_state_in_place() {
+    let engine = CueMapEngine::new();
+    let handle = engine.clone();
+    let old = engine.add_memory("old".to_string(), vec!["topic:old".to_string()], None, true);
+
+    let source = CueMapEngine::new();
+    let kept = source.add_memory("kept".to_string(), vec!["topic:new".to_string(), "service:a".to_string()], None, true);
+    let memories = source.get_memories().iter().map(|e| (e.key().clone(), e.value().clone())).collect();
+    let cue_index = source
+        .get_cue_index()
+        .iter()
+        .map(|e| (e.key().to_string(), source.get_id_table().resolve_all(&e.value().get_recent(None))))
+        .collect();
+
+    let revision = engine.revision();
+    engine.replace_state(memories, cue_index);
+
+    // Clones share the replaced contents
+    assert!(handle.get_memory(&old).is_none());
+    assert!(handle.recall(vec!["topic:old".to_string()], 10, false).is_empty());
+    assert_eq!(handle.recall(vec!["topic:new".to_string()], 10, false)[0].memory_id, kept);
+    assert_eq!(handle.key_cardinality("topic"), 1);
+    assert!(engine.revision() > revision);
+}
+
+#[test]
+fn test_maintenance_decay_demote_and_prune() {
+    let engine = CueMapEngine::new();
+    let busy = engine.add_memory("busy".to_string(), vec!["topic:busy".to_string(), "service:a".to_string()], None, true);
+    let idle = engine.add_memory("idle".to_string(), vec!["service:a".to_string()], None, true);
+    for _ in 0..9 {
+        engine.reinforce_memory(&busy, vec!["service:a".to_string()]);
+    }
+
+    assert_eq!(engine.decay_reinforcement(0), 0);
+    assert_eq!(engine.decay_reinforcement(2), 1);
+    assert_eq!(engine.get_memory(&busy).unwrap().reinforcement_count, 2);
+    assert_eq!(engine.get_memory(&idle).unwrap().reinforcement_count, 0);
+
+    // Everything is idle relative to a cutoff in the future
+    let salience = engine.get_memory(&busy).unwrap().salience;
+    let future = engine.get_memory(&busy).unwrap().last_accessed + 1.0;
+    assert_eq!(engine.demote_stale(future, 0.1), 2);
+    assert!((engine.get_memory(&busy).unwrap().salience - (salience - 0.1)).abs() < 1e-9);
+    assert_eq!(engine.demote_stale(future, 100.0), 2);
+    assert_eq!(engine.get_memory(&busy).unwrap().salience, 0.0);
+    assert_eq!(engine.demote_stale(future, 0.1), 0);
+
+    // Deletes leave empty cue lists behind until they are pruned
+    engine.delete_memory(&busy);
+    assert!(engine.has_cue("topic:busy"));
+    assert_eq!(engine.prune_empty_cues(), 1);
+    assert!(!engine.has_cue("topic:busy"));
+    assert_eq!(engine.key_cardinality("topic"), 0);
+    assert!(engine.has_cue("service:a"));
+    assert_eq!(engine.prune_empty_cues(), 0);
+    assert!(engine.check_index_consistency().is_empty());
+}
+
+#[test]
+fn test_early_exit_ignores_filtered_candidates() {
+    let engine = CueMapEngine::new();
+    engine.set_approximate_early_exit(true);
+    let plain = engine.add_memory("payments runbook".to_string(), vec!["service:payments".to_string()], None, false);
+    for i in 0..3 {
+        let cues = vec!["topic:outage".to_string(), "service:payments".to_string(), "type:summary".to_string()];
+        engine.add_memory(format!("outage summary {}", i), cues, None, false);
+    }
+
+    // The summaries fill the top of the first list scanned; once excluded they
+    // must not let the scan stop before the plain memory is reached
+    let query = vec![("topic:outage".to_string(), 1.0), ("service:payments".to_string(), 1.0)];
+    let recall = |limit: usize| {
+        engine
+            .recall_weighted_in(query.clone(), limit, false, None, false, true, false, true, None, None, None)
+            .into_iter()
+            .map(|result| result.memory_id)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(recall(10), vec![plain.clone()]);
+    assert_eq!(recall(1), vec![plain]);
+}
+
+#[test]
+fn test_recall_is_exact_without_approximate_early_exit() {
+    let engine = CueMapEngine::new();
+    let both = engine.add_memory("payments outage".to_string(), vec!["topic:outage".to_string(), "service:payments".to_string()], None, false);
+    let salient = engine.add_memory("payments runbook".to_string(), vec!["service:payments".to_string()], None, false);
+    engine.get_memories().get_mut(&salient).unwrap().salience = 50.0;
+
+    // Matching one cue fewer costs less than the salient memory's salience
+    // adds, but cue weight alone would end the scan before it is reached
+    let query = vec![("topic:outage".to_string(), 1.0), ("service:payments".to_string(), 1.0)];
+    let top = |engine: &CueMapEngine| engine.recall_weighted(query.clone(), 1, false, None, false, true, false, false, None)[0].memory_id.clone();
+    assert_eq!(top(&engine), salient);
+    engine.set_approximate_early_exit(true);
+    assert_eq!(top(&engine), both);
+}
@@ -0,0 +1,33 @@
+use crate::common::cues;
+use cuemap_rust::engine::{CueMapEngine, CueSizeStats};
+use cuemap_rust::projects::ProjectContext;
+
+#[test]
+fn test_warm_up_reports_cue_sizes() {
+    let engine = CueMapEngine::new();
+    assert_eq!(engine.warm_up(), CueSizeStats::default());
+
+    for i in 0..4 {
+        let mut held = vec!["service:payments"];
+        if i < 2 {
+            held.push("type:error");
+        }
+        engine.add_memory(format!("incident {}", i), cues(&held), None, false);
+    }
+    engine.add_memory("release notes".to_string(), cues(&["topic:release"]), None, false);
+
+    let stats = engine.warm_up();
+    assert_eq!(stats, CueSizeStats { cues: 3, postings: 7, p50: 2, p99: 2, max: 4 });
+}
+
+#[test]
+fn test_warm_up_refreshes_read_view() {
+    let project = ProjectContext::new(Default::default(), Default::default());
+    project.main.enable_read_view();
+    project.main.add_memory("checkout failed".to_string(), cues(&["service:payments"]), None, false);
+    let (epoch, pending) = project.main.read_view_status().unwrap();
+    assert_eq!(pending, 1);
+
+    assert_eq!(project.warm_up().postings, 1);
+    assert_eq!(project.main.read_view_status(), Some((epoch + 1, 0)));
+}
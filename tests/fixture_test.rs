@@ -30,3 +30,56 @@ fn test_fixture_loading_and_recall() {
     // Cleanup
     let _ = fs::remove_file(fixture_path);
 }
+
+#[test]
+fn test_snapshot_cue_table_and_legacy_format() {
+    use cuemap_rust::structures::Memory;
+    use std::collections::HashMap;
+
+    let engine = CueMapEngine::new();
+    for i in 0..50 {
+        engine.add_memory(
+            format!("memory {}", i),
+            vec!["service:payments".to_string(), "topic:outage".to_string(), format!("ticket:{}", i % 5)],
+            None,
+            true,
+        );
+    }
+
+    let path = PathBuf::from("tests/fixtures_cue_table.bin");
+    PersistenceManager::save_to_path(&engine, &path).expect("Failed to save snapshot");
+    let table_size = fs::metadata(&path).unwrap().len();
+    let (memories, cue_index) = PersistenceManager::load_from_path(&path).expect("Failed to load snapshot");
+    let _ = fs::remove_file(&path);
+    let loaded = CueMapEngine::from_state(memories, cue_index);
+    assert_eq!(loaded.get_memories().len(), 50);
+    assert_eq!(loaded.recall(vec!["ticket:3".to_string()], 20, false).len(), 10);
+
+    // Version 1 snapshots repeat each cue string per memory and still load
+    #[derive(serde::Serialize)]
+    struct LegacyState {
+        memories: HashMap<String, Memory>,
+        cue_index: HashMap<String, Vec<String>>,
+        version: u32,
+        saved_at: u64,
+    }
+    let legacy = LegacyState {
+        memories: engine.get_memories().iter().map(|e| (e.key().clone(), e.value().clone())).collect(),
+        cue_index: engine
+            .get_cue_index()
+            .iter()
+            .map(|e| (e.key().to_string(), engine.cue_memory_ids(e.key(), None)))
+            .collect(),
+        version: 1,
+        saved_at: 0,
+    };
+    let legacy_path = PathBuf::from("tests/fixtures_legacy.bin");
+    fs::write(&legacy_path, bincode::serialize(&legacy).unwrap()).unwrap();
+    let legacy_size = fs::metadata(&legacy_path).unwrap().len();
+    let (memories, cue_index) = PersistenceManager::load_from_path(&legacy_path).expect("Failed to load legacy snapshot");
+    let _ = fs::remove_file(&legacy_path);
+    let loaded = CueMapEngine::from_state(memories, cue_index);
+    assert_eq!(loaded.recall(vec!["service:payments".to_string()], 100, false).len(), 50);
+
+    assert!(table_size < legacy_size, "cue table snapshot {} >= legacy {}", table_size, legacy_size);
+}
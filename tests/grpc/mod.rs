@@ -0,0 +1,112 @@
+use cuemap_rust::api::EngineState;
+use cuemap_rust::auth::{AuthConfig, Scope};
+use cuemap_rust::grounding::GroundingTraceStore;
+use cuemap_rust::grpc::{pb, serve, CueMapClient, GrpcService};
+use cuemap_rust::jobs::{JobQueue, SingleTenantProvider};
+use cuemap_rust::normalization::NormalizationConfig;
+use cuemap_rust::projects::ProjectContext;
+use cuemap_rust::taxonomy::Taxonomy;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tonic::transport::Channel;
+use tonic::Code;
+
+/// Serve a fresh single-tenant project and return a connected client.
+async fn start(auth: AuthConfig) -> (Arc<ProjectContext>, CueMapClient<Channel>) {
+    let project = Arc::new(ProjectContext::new(NormalizationConfig::default(), Taxonomy::default()));
+    let state = EngineState::SingleTenant {
+        project: project.clone(),
+        read_only: false,
+        job_queue: Arc::new(JobQueue::new(Arc::new(SingleTenantProvider { project: project.clone() }))),
+        traces: Arc::new(GroundingTraceStore::new(100, None)),
+    };
+
+    let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    tokio::spawn(serve(GrpcService::new(state, auth), addr, std::future::pending()));
+
+    for _ in 0..50 {
+        if let Ok(client) = CueMapClient::connect(format!("http://{}", addr)).await {
+            return (project, client);
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("gRPC server did not start on {}", addr);
+}
+
+#[tokio::test]
+async fn test_add_recall_reinforce_export() {
+    let (project, mut client) = start(AuthConfig::with_keys(HashMap::new())).await;
+
+    let added = client
+        .add_memory(pb::AddMemoryRequest {
+            content: "Payments timed out after the deploy".to_string(),
+            cues: vec!["Service:Payments".to_string(), "error:timeout".to_string()],
+            metadata_json: r#"{"source": "incident"}"#.to_string(),
+            disable_temporal_chunking: true,
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(project.main.get_memory(&added.id).is_some());
+
+    let recalled = client
+        .recall(pb::RecallRequest {
+            cues: vec!["service:payments".to_string()],
+            explain: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(recalled.results.len(), 1);
+    assert_eq!(recalled.results[0].memory_id, added.id);
+    assert!(recalled.results[0].metadata_json.contains("incident"));
+    assert!(!recalled.results[0].explain_json.is_empty());
+
+    client
+        .reinforce(pb::ReinforceRequest { memory_id: added.id.clone(), cues: vec!["error:timeout".to_string()] })
+        .await
+        .unwrap();
+    let missing = client
+        .reinforce(pb::ReinforceRequest { memory_id: "nope".to_string(), cues: vec![] })
+        .await
+        .unwrap_err();
+    assert_eq!(missing.code(), Code::NotFound);
+
+    let grounded = client
+        .recall_grounded(pb::RecallGroundedRequest { query_text: "payments".to_string(), ..Default::default() })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(grounded.proof.unwrap().token_budget, 500);
+
+    let mut stream = client.export(pb::ExportRequest {}).await.unwrap().into_inner();
+    let mut exported = Vec::new();
+    while let Some(memory) = stream.message().await.unwrap() {
+        exported.push(memory);
+    }
+    assert_eq!(exported.len(), 1);
+    assert_eq!(exported[0].reinforcement_count, 1);
+    assert!(exported[0].cues.contains(&"service:payments".to_string()));
+}
+
+#[tokio::test]
+async fn test_api_key_scopes() {
+    let keys = HashMap::from([("reader".to_string(), Scope::Read)]);
+    let (_, mut client) = start(AuthConfig::with_keys(keys)).await;
+
+    let add = || {
+        let mut request = tonic::Request::new(pb::AddMemoryRequest { content: "x".to_string(), ..Default::default() });
+        request.metadata_mut().insert("x-api-key", "reader".parse().unwrap());
+        request
+    };
+    assert_eq!(client.add_memory(add()).await.unwrap_err().code(), Code::PermissionDenied);
+
+    let unauthenticated = client.recall(pb::RecallRequest::default()).await.unwrap_err();
+    assert_eq!(unauthenticated.code(), Code::Unauthenticated);
+
+    let mut recall = tonic::Request::new(pb::RecallRequest::default());
+    recall.metadata_mut().insert("x-api-key", "reader".parse().unwrap());
+    assert!(client.recall(recall).await.is_ok());
+}
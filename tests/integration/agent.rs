@@ -0,0 +1,101 @@
+use cuemap_rust::agent::cue_policy::{CuePolicy, DirectoryRoute};
+use cuemap_rust::agent::ignore_rules::{IgnoreRules, IGNORE_FILE};
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn test_cuemapignore_rules() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    let watch_dir = root.to_str().unwrap();
+
+    assert!(IgnoreRules::load(watch_dir).is_empty());
+
+    fs::write(root.join(IGNORE_FILE), "# tests are not knowledge\ntests/\n*.log\n!keep.log\n/build\n").unwrap();
+    fs::create_dir_all(root.join("tests")).unwrap();
+    fs::create_dir_all(root.join("src/build")).unwrap();
+    let rules = IgnoreRules::load(watch_dir);
+    assert_eq!(rules.len(), 4);
+
+    assert!(rules.is_ignored(&root.join("tests"), true));
+    assert!(rules.is_ignored(&root.join("tests/engine.rs"), false));
+    assert!(rules.is_ignored(&root.join("src/debug.log"), false));
+    assert!(!rules.is_ignored(&root.join("src/keep.log"), false));
+    assert!(rules.is_ignored(&root.join("build/out.rs"), false));
+    // Anchored patterns only match at the watch root
+    assert!(!rules.is_ignored(&root.join("src/build/gen.rs"), false));
+    assert!(!rules.is_ignored(&root.join("src/engine.rs"), false));
+
+    // The ignore file itself is never ingested; paths elsewhere are not judged
+    assert!(rules.is_ignore_file(&root.join(IGNORE_FILE)));
+    assert!(rules.is_ignored(&root.join(IGNORE_FILE), false));
+    assert!(!rules.is_ignored(Path::new("/elsewhere/tests/a.rs"), false));
+
+    // Reloading picks up edits
+    fs::write(root.join(IGNORE_FILE), "*.md\n").unwrap();
+    let rules = IgnoreRules::load(watch_dir);
+    assert!(!rules.is_ignored(&root.join("tests/engine.rs"), false));
+    assert!(rules.is_ignored(&root.join("README.md"), false));
+}
+
+#[test]
+fn test_cue_policy() {
+    let policy = CuePolicy {
+        static_cues: vec!["repo:backend".to_string()],
+        path_cues: vec!["service:{0}".to_string(), "module:{1}/{file}".to_string(), "ext:{ext}".to_string()],
+        blocked_keys: vec!["Author".to_string()],
+        routes: Vec::new(),
+    };
+    assert!(policy.validate().is_empty());
+
+    assert_eq!(
+        policy.cues_for(Path::new("billing/api/refund.rs")),
+        vec!["repo:backend", "service:billing", "module:api/refund", "ext:rs"]
+    );
+    // Templates naming a directory the file lacks are left out
+    assert_eq!(policy.cues_for(Path::new("README.md")), vec!["repo:backend", "ext:md"]);
+
+    assert!(!policy.allows("author:jane"));
+    assert!(policy.allows("topic:auth"));
+    assert!(policy.allows("author"));
+
+    let invalid = CuePolicy {
+        static_cues: vec![" ".to_string()],
+        path_cues: vec!["team".to_string(), "team:{owner}".to_string(), "team:{0".to_string()],
+        blocked_keys: vec!["author:jane".to_string()],
+        routes: Vec::new(),
+    };
+    assert_eq!(invalid.validate().len(), 5, "{:?}", invalid.validate());
+}
+
+#[test]
+fn test_directory_routes() {
+    let route = |dir: &str, collection: Option<&str>, cues: &[&str]| DirectoryRoute {
+        dir: dir.to_string(),
+        collection: collection.map(str::to_string),
+        cues: cues.iter().map(|cue| cue.to_string()).collect(),
+    };
+    let policy = CuePolicy {
+        static_cues: vec!["repo:backend".to_string()],
+        routes: vec![route("docs/", Some("docs"), &["area:docs"]), route("docs/api", Some("api-docs"), &[]), DirectoryRoute::parse("src=code").unwrap()],
+        ..Default::default()
+    };
+    assert!(policy.validate().is_empty(), "{:?}", policy.validate());
+
+    let collection = |path: &str| policy.route_for(Path::new(path)).and_then(|route| route.collection.as_deref());
+    assert_eq!(collection("docs/refunds.md"), Some("docs"));
+    // The most specific route wins, matched like the agent's lowercased paths
+    assert_eq!(collection("Docs/API/refunds.md"), Some("api-docs"));
+    assert_eq!(collection("src/main.rs"), Some("code"));
+    assert_eq!(collection("docs"), None);
+    assert_eq!(collection("README.md"), None);
+    assert_eq!(policy.cues_for(Path::new("docs/refunds.md")), vec!["repo:backend", "area:docs"]);
+    assert_eq!(policy.cues_for(Path::new("docs/api/refunds.md")), vec!["repo:backend"]);
+
+    assert!(DirectoryRoute::parse("docs").is_err());
+    let invalid = CuePolicy {
+        routes: vec![route("/etc", Some("etc"), &[]), route("src", None, &[]), route("src/", Some("bad name"), &[" "])],
+        ..Default::default()
+    };
+    assert_eq!(invalid.validate().len(), 5, "{:?}", invalid.validate());
+}
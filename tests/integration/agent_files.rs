@@ -0,0 +1,67 @@
+use crate::common;
+use cuemap_rust::engine::CueMapEngine;
+use cuemap_rust::projects::ProjectContext;
+use cuemap_rust::provenance::{self, AgentFile, Provenance, PROVENANCE_KEY};
+use cuemap_rust::structures::CONSOLIDATED_INTO_KEY;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Store a memory as the agent would after extracting `chunk` of `path`.
+fn ingest(engine: &CueMapEngine, path: &str, chunk: &str, byte_range: Range<usize>, extracted_at: f64) -> String {
+    let mut provenance = Provenance::new(path.to_string(), chunk.to_string(), Some(byte_range), "job-1".to_string());
+    provenance.extracted_at = extracted_at;
+    let metadata = HashMap::from([(PROVENANCE_KEY.to_string(), serde_json::to_value(provenance).unwrap())]);
+    let id = format!("file:{}:{}", path, chunk);
+    engine.upsert_memory_with_id(id.clone(), format!("facts from {}", chunk), vec![format!("path:{}", path)], Some(metadata), false)
+}
+
+#[test]
+fn test_agent_files() {
+    let engine = CueMapEngine::new();
+    let second = ingest(&engine, "docs/refunds.md", "b", 40..80, 200.0);
+    let first = ingest(&engine, "docs/refunds.md", "a", 0..40, 100.0);
+    ingest(&engine, "docs/billing.md", "c", 0..10, 150.0);
+    engine.add_memory("written through the API".to_string(), vec!["topic:refunds".to_string()], None, false);
+    engine.set_metadata(&second, CONSOLIDATED_INTO_KEY, serde_json::json!("summary-1"));
+
+    assert_eq!(
+        provenance::agent_files(&engine),
+        vec![
+            AgentFile { path: "docs/billing.md".to_string(), chunks: 1, memories: 1, last_ingested_at: 150.0 },
+            AgentFile { path: "docs/refunds.md".to_string(), chunks: 2, memories: 1, last_ingested_at: 200.0 },
+        ]
+    );
+    let ids: Vec<String> = provenance::file_memories(&engine, "docs/refunds.md").into_iter().map(|m| m.id).collect();
+    assert_eq!(ids, vec![first, second]);
+    assert!(provenance::file_memories(&engine, "docs/missing.md").is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_agent_file_routes() {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let chunk = ingest(&project.main, "docs/refunds.md", "a", 0..40, 100.0);
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+
+    let body: Value = client.get(format!("{}/agent/files", base)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(body["files"], serde_json::json!([{"path": "docs/refunds.md", "chunks": 1, "memories": 1, "last_ingested_at": 100.0}]));
+
+    let body: Value = client
+        .get(format!("{}/agent/files/memories", base))
+        .query(&[("path", "Docs/Refunds.md")])
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["path"], "docs/refunds.md");
+    assert_eq!(body["memories"][0]["id"], chunk.as_str());
+    assert_eq!(body["memories"][0]["metadata"]["provenance"]["byte_range"], serde_json::json!({"start": 0, "end": 40}));
+
+    let missing = client.get(format!("{}/agent/files/memories?path=docs/missing.md", base)).send().await.unwrap();
+    assert_eq!(missing.status(), 404);
+}
@@ -0,0 +1,122 @@
+use crate::common;
+use cuemap_rust::alias_proposal::{self, AliasConfig};
+use cuemap_rust::api;
+use cuemap_rust::auth::AuthConfig;
+use cuemap_rust::engine::CueMapEngine;
+use cuemap_rust::grounding::GroundingTraceStore;
+use cuemap_rust::jobs::{Job, JobQueue, SingleTenantProvider};
+use cuemap_rust::projects::{ProjectConfig, ProjectContext};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// `payments` always goes with `service:payments`, while `topic:payments`
+/// shares half of its memories with both.
+fn populate(engine: &CueMapEngine) {
+    for i in 0..30 {
+        let mut cues = Vec::new();
+        if i < 20 {
+            cues.extend(["service:payments".to_string(), "payments".to_string()]);
+        }
+        if i >= 10 {
+            cues.push("topic:payments".to_string());
+        }
+        engine.add_memory(format!("payment event {}", i), cues, None, false);
+    }
+}
+
+#[test]
+fn test_select_alias_candidates() {
+    let engine = CueMapEngine::new();
+    populate(&engine);
+
+    let candidates = alias_proposal::select(&engine, &AliasConfig::default());
+    assert_eq!(candidates.cues_compared, 3);
+    assert_eq!(candidates.pairs.len(), 3);
+    let best = &candidates.pairs[0];
+    assert_eq!((best.from.as_str(), best.to.as_str()), ("payments", "service:payments"));
+    assert_eq!((best.from_memories, best.to_memories), (20, 20));
+    assert_eq!(best.overlap, Some(1.0));
+    assert!(best.proposed);
+    for pair in &candidates.pairs[1..] {
+        assert_eq!(pair.sample_overlap, 0.5);
+        assert_eq!(pair.overlap, None);
+        assert!(!pair.proposed);
+    }
+
+    let loose = AliasConfig { overlap_threshold: 0.5, ..Default::default() };
+    assert_eq!(alias_proposal::select(&engine, &loose).proposed().count(), 3);
+    let strict = AliasConfig { min_cue_memories: 21, ..Default::default() };
+    assert_eq!(alias_proposal::select(&engine, &strict).cues_compared, 0);
+    assert!(AliasConfig { overlap_threshold: 1.5, ..Default::default() }.validate().is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_alias_candidates_route_writes_nothing() {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    populate(&project.main);
+    let job_queue = Arc::new(JobQueue::new(Arc::new(SingleTenantProvider { project: project.clone() })));
+    let traces = Arc::new(GroundingTraceStore::new(100, None));
+    let app = api::routes(project.clone(), job_queue.clone(), traces, AuthConfig::with_keys(HashMap::new()), false);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+    let candidates = |query: &'static str| {
+        let request = client.get(format!("{}/admin/alias-candidates{}", base, query));
+        async move { request.send().await.unwrap() }
+    };
+
+    let body: Value = candidates("").await.json().await.unwrap();
+    assert_eq!(body["cues_compared"], 3);
+    assert_eq!(body["total_pairs"], 3);
+    assert_eq!(body["proposed"], 1);
+    assert_eq!(body["pairs"][0]["from"], "payments");
+    assert_eq!(body["pairs"][0]["overlap"], 1.0);
+    assert!(body["pairs"][1]["overlap"].is_null());
+
+    let body: Value = candidates("?overlap_threshold=0.5&limit=2").await.json().await.unwrap();
+    assert_eq!(body["config"]["overlap_threshold"], 0.5);
+    assert_eq!(body["proposed"], 3);
+    assert_eq!(body["pairs"].as_array().unwrap().len(), 2);
+    assert_eq!(candidates("?overlap_threshold=2").await.status(), 400);
+    assert!(project.aliases.get_memories().is_empty());
+
+    // Thresholds saved for the project apply to the endpoint and the job
+    let response = client
+        .put(format!("{}/aliases/config", base))
+        .json(&serde_json::json!({"overlap_threshold": 0.5}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let config: Value = client.get(format!("{}/admin/config", base)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(config["project_overrides"]["default"]["aliases"], serde_json::json!({"overlap_threshold": 0.5}));
+    let body: Value = candidates("").await.json().await.unwrap();
+    assert_eq!(body["proposed"], 3);
+
+    job_queue.enqueue(Job::ProposeAliases { project_id: "default".to_string() }).await;
+    assert!(job_queue.shutdown(Duration::from_secs(5)).await);
+    assert_eq!(project.aliases.get_memories().len(), 3);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_alias_config_persists_with_project_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("project_config.json");
+    std::fs::write(&path, r#"{"aliases": {"min_cue_memories": 5}}"#).unwrap();
+    let config = ProjectConfig::load_or_default(&path);
+    assert_eq!(config.aliases, AliasConfig { min_cue_memories: 5, ..Default::default() });
+
+    // Cues on 8 memories each are too rare for the default thresholds
+    let project = Arc::new(ProjectContext::from_config(CueMapEngine::new(), config));
+    for i in 0..8 {
+        project.main.add_memory(format!("refund {}", i), vec!["topic:refunds".to_string(), "refunds".to_string()], None, false);
+    }
+    assert_eq!(alias_proposal::select(&project.main, &AliasConfig::default()).cues_compared, 0);
+
+    let job_queue = JobQueue::new(Arc::new(SingleTenantProvider { project: project.clone() }));
+    job_queue.enqueue(Job::ProposeAliases { project_id: "default".to_string() }).await;
+    assert!(job_queue.shutdown(Duration::from_secs(5)).await);
+    assert_eq!(project.aliases.get_memories().len(), 1);
+    assert_eq!(project.config().overrides(), serde_json::json!({"aliases": {"min_cue_memories": 5}}));
+}
@@ -0,0 +1,73 @@
+use axum::http::Method;
+use cuemap_rust::auth::*;
+
+#[test]
+fn test_parse_scoped_keys() {
+    let keys = parse_api_keys("key1:rw, key2:ro,key3:admin,legacy,with:colon:ro,,");
+
+    assert_eq!(keys.get("key1"), Some(&Scope::Write));
+    assert_eq!(keys.get("key2"), Some(&Scope::Read));
+    assert_eq!(keys.get("key3"), Some(&Scope::Admin));
+    // Unscoped keys keep full access
+    assert_eq!(keys.get("legacy"), Some(&Scope::Admin));
+    // Only the last segment is treated as a scope
+    assert_eq!(keys.get("with:colon"), Some(&Scope::Read));
+    assert_eq!(keys.len(), 5);
+
+    let auth = AuthConfig::with_keys(keys);
+    assert!(auth.is_enabled());
+    assert_eq!(auth.key_scope("key2"), Some(Scope::Read));
+    assert_eq!(auth.key_scope("unknown"), None);
+}
+
+#[test]
+fn test_required_scope() {
+    assert_eq!(required_scope(&Method::GET, "/stats"), Scope::Read);
+    assert_eq!(required_scope(&Method::POST, "/recall"), Scope::Read);
+    assert_eq!(required_scope(&Method::POST, "/normalization/test"), Scope::Read);
+
+    assert_eq!(required_scope(&Method::POST, "/memories"), Scope::Write);
+    assert_eq!(required_scope(&Method::PATCH, "/memories/abc/reinforce"), Scope::Write);
+    assert_eq!(required_scope(&Method::DELETE, "/memories/abc"), Scope::Write);
+
+    assert_eq!(required_scope(&Method::DELETE, "/projects/p1"), Scope::Admin);
+    assert_eq!(required_scope(&Method::PUT, "/taxonomy"), Scope::Admin);
+    assert_eq!(required_scope(&Method::POST, "/taxonomy/deprecations"), Scope::Admin);
+    assert_eq!(required_scope(&Method::POST, "/taxonomy/rejections/promote"), Scope::Admin);
+    assert_eq!(required_scope(&Method::GET, "/taxonomy"), Scope::Read);
+    assert_eq!(required_scope(&Method::POST, "/snapshots/save"), Scope::Admin);
+
+    assert!(Scope::Admin > Scope::Write && Scope::Write > Scope::Read);
+}
+
+#[test]
+fn test_managed_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("api_keys.json");
+
+    let auth = AuthConfig::with_keys(Default::default()).with_key_store(KeyStore::open(&path));
+    assert!(!auth.is_enabled());
+
+    let store = auth.key_store();
+    let (info, key) = store.create(Some("ci".to_string()), Scope::Read);
+    assert!(auth.is_enabled());
+    assert_eq!(auth.key_scope(&key), Some(Scope::Read));
+    assert_eq!(auth.key_scope(&format!("{}x", key)), None);
+    assert!(store.list()[0].last_used.is_some());
+
+    // Only salted hashes reach disk
+    let stored = std::fs::read_to_string(&path).unwrap();
+    assert!(!stored.contains(&key));
+    assert!(stored.contains(&info.id));
+
+    // Keys survive a restart
+    let reopened = KeyStore::open(&path);
+    assert_eq!(reopened.list()[0].label.as_deref(), Some("ci"));
+    assert_eq!(reopened.authenticate(&key), Some(Scope::Read));
+
+    let (other, _) = store.create(None, Scope::Admin);
+    assert!(store.revoke(&info.id));
+    assert!(!store.revoke(&info.id));
+    assert_eq!(auth.key_scope(&key), None);
+    assert_eq!(KeyStore::open(&path).list()[0].id, other.id);
+}
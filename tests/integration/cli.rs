@@ -0,0 +1,115 @@
+use cuemap_rust::cli::{run, Command};
+use std::fs;
+use tempfile::tempdir;
+
+fn run_json(command: Command, data_dir: &std::path::Path) -> serde_json::Value {
+    let mut out = Vec::new();
+    run(command, data_dir, &mut out).unwrap();
+    serde_json::from_slice(&out).unwrap()
+}
+
+#[test]
+fn test_import_export_roundtrip() {
+    let dir = tempdir().unwrap();
+    let data = dir.path().join("data");
+    let input = dir.path().join("in.jsonl");
+    fs::write(
+        &input,
+        "{\"content\": \"Payments timed out\", \"cues\": [\"Service:Payments\", \"error:timeout\"]}\n\n{\"id\": \"m2\", \"content\": \"Search is fast\", \"cues\": [\"service:search\"]}\n",
+    )
+    .unwrap();
+
+    let report = run_json(Command::Import { file: input }, &data);
+    assert_eq!(report["imported"], 2);
+    assert_eq!(report["total_memories"], 2);
+
+    let query = Command::Query { cues: vec!["service:payments".to_string()], text: None, limit: 10, explain: false, grounded: false, token_budget: 500 };
+    let results = run_json(query, &data);
+    assert_eq!(results["results"].as_array().unwrap().len(), 1);
+    assert_eq!(results["results"][0]["content"], "Payments timed out");
+
+    // Exported lines import into a fresh directory with their ids
+    let mut exported = Vec::new();
+    run(Command::Export { output: None }, &data, &mut exported).unwrap();
+    assert_eq!(String::from_utf8_lossy(&exported).lines().count(), 2);
+    let dump = dir.path().join("dump.jsonl");
+    fs::write(&dump, &exported).unwrap();
+
+    let copy = dir.path().join("copy");
+    run_json(Command::Import { file: dump }, &copy);
+    let stats = run_json(Command::Stats, &copy);
+    assert_eq!(stats["total_memories"], 2);
+    assert!(stats["memory_usage"]["total_bytes"].as_u64().unwrap() > 0);
+    let mut again = Vec::new();
+    run(Command::Export { output: None }, &copy, &mut again).unwrap();
+    assert!(String::from_utf8_lossy(&again).contains("\"id\":\"m2\""));
+}
+
+#[test]
+fn test_malformed_import_adds_nothing() {
+    let dir = tempdir().unwrap();
+    let input = dir.path().join("bad.jsonl");
+    fs::write(&input, "{\"content\": \"ok\", \"cues\": [\"a:b\"]}\n{\"cues\": []}\n").unwrap();
+
+    let err = run(Command::Import { file: input }, dir.path(), &mut Vec::new()).unwrap_err();
+    assert!(err.starts_with("line 2:"), "{}", err);
+    let stats = run_json(Command::Stats, dir.path());
+    assert_eq!(stats["total_memories"], 0);
+}
+
+#[test]
+fn test_read_commands_need_existing_dir() {
+    let dir = tempdir().unwrap();
+    let missing = dir.path().join("missing");
+    assert!(run(Command::Stats, &missing, &mut Vec::new()).is_err());
+    assert!(!missing.exists());
+}
+
+#[test]
+fn test_repl_commands() {
+    use cuemap_rust::repl::{Outcome, Repl};
+    use cuemap_rust::CueMap;
+
+    let mut repl = Repl::new(CueMap::in_memory());
+    let print = |outcome: Result<Outcome, String>| match outcome.unwrap() {
+        Outcome::Print(text) => text,
+        Outcome::Quit => panic!("unexpected quit"),
+    };
+
+    let added = print(repl.execute("add service:payments,error:timeout Payments timed out"));
+    assert!(added.starts_with("added "), "{}", added);
+    print(repl.execute("add service:search Search is fast"));
+
+    let explained = print(repl.execute("explain service:payments"));
+    assert!(explained.starts_with("query: service:payments"), "{}", explained);
+    assert!(explained.contains("Payments timed out"));
+    assert!(explained.contains("intersection"));
+    assert_eq!(print(repl.execute("recall service:unknown")), "no results");
+
+    let cues = print(repl.execute("cues service:"));
+    assert_eq!(cues.lines().count(), 2);
+    assert_eq!(print(repl.execute("limit 1")), "limit 1");
+
+    assert!(repl.execute("limit many").is_err());
+    assert!(repl.execute("frobnicate").is_err());
+    assert_eq!(repl.execute("quit").unwrap(), Outcome::Quit);
+    assert_eq!(repl.cuemap().len(), 2);
+}
+
+#[test]
+fn test_eval_command() {
+    let dir = tempdir().unwrap();
+    let input = dir.path().join("in.jsonl");
+    fs::write(&input, "{\"id\": \"m1\", \"content\": \"Payments timed out\", \"cues\": [\"service:payments\"]}\n").unwrap();
+    run_json(Command::Import { file: input }, dir.path());
+
+    let err = run(Command::Eval { set: None, file: None }, dir.path(), &mut Vec::new()).unwrap_err();
+    assert!(err.contains("no eval sets"), "{}", err);
+
+    let set = dir.path().join("golden.json");
+    fs::write(&set, r#"{"name": "smoke", "queries": [{"cues": ["service:payments"], "expected": ["m1"]}]}"#).unwrap();
+    let report = run_json(Command::Eval { set: None, file: Some(set) }, dir.path());
+    assert_eq!(report["runs"][0]["set"], "smoke");
+    assert_eq!(report["runs"][0]["recall"], 1.0);
+    assert_eq!(fs::read_to_string(dir.path().join("eval_runs.jsonl")).unwrap().lines().count(), 1);
+}
@@ -0,0 +1,115 @@
+use crate::common;
+use cuemap_rust::collections::{validate_name, CollectionError, CollectionFilter};
+use cuemap_rust::engine::CueMapEngine;
+use cuemap_rust::persistence::PersistenceManager;
+use cuemap_rust::projects::ProjectContext;
+use serde_json::Value;
+use std::sync::Arc;
+
+fn filter(include: &[&str], exclude: &[&str]) -> CollectionFilter {
+    CollectionFilter {
+        include: include.iter().map(|c| c.to_string()).collect(),
+        exclude: exclude.iter().map(|c| c.to_string()).collect(),
+    }
+}
+
+fn recall_ids(engine: &CueMapEngine, limit: usize, collections: Option<&CollectionFilter>) -> Vec<String> {
+    engine
+        .recall_weighted_in(vec![("service:payments".to_string(), 1.0)], limit, false, None, false, true, false, false, None, collections, None)
+        .into_iter()
+        .map(|r| r.memory_id)
+        .collect()
+}
+
+#[test]
+fn test_collection_filter() {
+    assert!(validate_name("chat-history_v2.1").is_ok());
+    assert!(matches!(validate_name(""), Err(CollectionError::InvalidName(_))));
+    assert!(matches!(validate_name("run books"), Err(CollectionError::InvalidName(_))));
+
+    let engine = CueMapEngine::new();
+    let cues = || vec!["service:payments".to_string()];
+    let runbook = engine.add_memory_to("restart the payments pods".to_string(), cues(), None, true, Some("runbooks".to_string()));
+    let chat = engine.add_memory_to("payments is slow again".to_string(), cues(), None, true, Some("chat-history".to_string()));
+    let loose = engine.add_memory("payments latency alert".to_string(), cues(), None, true);
+
+    assert_eq!(recall_ids(&engine, 10, None).len(), 3);
+    // Filtered out before scoring: the older runbook still fills a limit of one
+    assert_eq!(recall_ids(&engine, 1, Some(&filter(&["runbooks"], &[]))), vec![runbook.clone()]);
+    let mut kept = recall_ids(&engine, 10, Some(&filter(&[], &["chat-history"])));
+    kept.sort();
+    let mut expected = vec![runbook.clone(), loose.clone()];
+    expected.sort();
+    assert_eq!(kept, expected);
+
+    // Collections survive a snapshot round trip
+    let (memories, cue_index) = PersistenceManager::decode(&PersistenceManager::encode(&engine).unwrap()).unwrap();
+    let restored = CueMapEngine::from_state(memories, cue_index);
+    assert_eq!(restored.get_memory(&chat).unwrap().collection.as_deref(), Some("chat-history"));
+    assert_eq!(restored.clear_collection("chat-history"), 1);
+    assert_eq!(recall_ids(&restored, 10, Some(&filter(&["chat-history"], &[]))), Vec::<String>::new());
+}
+
+#[tokio::test]
+async fn test_collection_routes() {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+
+    let create = |name: &str| client.post(format!("{}/collections", base)).json(&serde_json::json!({"name": name})).send();
+    assert_eq!(create("runbooks").await.unwrap().status(), 201);
+    assert_eq!(create("runbooks").await.unwrap().status(), 409);
+    assert_eq!(create("run books").await.unwrap().status(), 400);
+
+    let add = |content: &str, collection: Option<&str>| {
+        client
+            .post(format!("{}/memories", base))
+            .json(&serde_json::json!({"content": content, "cues": ["service:payments"], "collection": collection}))
+            .send()
+    };
+    let response = add("restart the payments pods", Some("runbooks")).await.unwrap();
+    let runbook = response.json::<Value>().await.unwrap()["id"].as_str().unwrap().to_string();
+    let response = add("payments is slow again", None).await.unwrap();
+    let chat = response.json::<Value>().await.unwrap()["id"].as_str().unwrap().to_string();
+    assert_eq!(add("payments latency alert", Some("alerts")).await.unwrap().status(), 400);
+
+    let recall = |collections: Value| {
+        let request = client.post(format!("{}/recall", base)).json(&serde_json::json!({"cues": ["service:payments"], "collections": collections}));
+        async move {
+            let body: Value = request.send().await.unwrap().json().await.unwrap();
+            body["results"].as_array().unwrap().iter().map(|r| r["memory_id"].as_str().unwrap().to_string()).collect::<Vec<_>>()
+        }
+    };
+    assert_eq!(recall(serde_json::json!({"include": ["runbooks"]})).await, vec![runbook.clone()]);
+    assert_eq!(recall(serde_json::json!({"exclude": ["runbooks"]})).await, vec![chat.clone()]);
+
+    let response = client
+        .put(format!("{}/collections/runbooks", base))
+        .json(&serde_json::json!({"description": "Operational procedures"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: Value = client.get(format!("{}/collections", base)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(body["collections"][0]["description"], "Operational procedures");
+    assert_eq!(body["collections"][0]["memories"], 1);
+
+    let response = client
+        .post(format!("{}/collections/runbooks/memories", base))
+        .json(&serde_json::json!({"memory_ids": [chat.clone(), "missing"]}))
+        .send()
+        .await
+        .unwrap();
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["moved"], 1);
+    assert_eq!(body["not_found"], serde_json::json!(["missing"]));
+    assert_eq!(recall(serde_json::json!({"include": ["runbooks"]})).await.len(), 2);
+
+    // Deleting a collection keeps its memories, outside any collection
+    let body: Value = client.delete(format!("{}/collections/runbooks", base)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(body["released"], 2);
+    assert_eq!(project.main.get_memory(&runbook).unwrap().collection, None);
+    assert_eq!(client.get(format!("{}/collections/runbooks", base)).send().await.unwrap().status(), 404);
+    assert_eq!(project.config().collections.len(), 0);
+}
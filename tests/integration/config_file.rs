@@ -0,0 +1,108 @@
+use cuemap_rust::cli::{run, Command, ConfigCommand};
+use cuemap_rust::config_file::ConfigFile;
+use cuemap_rust::telemetry::LogFormat;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_parse_sections() {
+    let config = ConfigFile::parse(
+        r#"
+        [server]
+        port = 9090
+        data_dir = "/srv/cuemap"
+        log_format = "json"
+
+        [network]
+        allow_cidr = ["10.0.0.0/8"]
+
+        [auth]
+        api_keys = ["reader:ro", "admin-key"]
+
+        [llm]
+        provider = "openai"
+        model = "gpt-4o-mini"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(config.server.port, Some(9090));
+    assert_eq!(config.server.data_dir.as_deref(), Some("/srv/cuemap"));
+    assert_eq!(config.server.log_format, Some(LogFormat::Json));
+    assert_eq!(config.server.snapshot_interval, None);
+    assert_eq!(config.network.allow_cidr, Some(vec!["10.0.0.0/8".to_string()]));
+    assert_eq!(config.auth.api_keys.as_ref().map(Vec::len), Some(2));
+    assert_eq!(config.llm.model.as_deref(), Some("gpt-4o-mini"));
+    assert!(config.validate().is_empty(), "{:?}", config.validate());
+
+    assert_eq!(ConfigFile::parse("").unwrap(), ConfigFile::default());
+}
+
+#[test]
+fn test_unknown_keys_are_rejected() {
+    let err = ConfigFile::parse("[server]\nprot = 8080\n").unwrap_err();
+    assert!(err.contains("prot"), "{}", err);
+    assert!(ConfigFile::parse("[storage]\npath = \"x\"\n").is_err());
+}
+
+#[test]
+fn test_validate_reports_every_problem() {
+    let config = ConfigFile::parse(
+        r#"
+        [server]
+        dashmap_shards = 100
+        max_recall_limit = 0
+
+        [tls]
+        cert = "/nonexistent/cert.pem"
+
+        [network]
+        deny_cidr = ["not-a-cidr"]
+
+        [llm]
+        provider = "acme"
+
+        [agent]
+        path_cues = ["service:{dir}"]
+        routes = [{ dir = "docs", collection = "docs" }, { dir = "../shared", collection = "shared" }]
+        "#,
+    )
+    .unwrap();
+
+    let problems = config.validate();
+    assert_eq!(problems.len(), 8, "{:?}", problems);
+    assert!(problems.iter().any(|p| p.starts_with("server.dashmap_shards")));
+    assert!(problems.iter().any(|p| p == "server: max recall limit must be positive"));
+    assert!(problems.iter().any(|p| p == "tls.cert requires tls.key"));
+    assert!(problems.iter().any(|p| p.starts_with("tls.cert:")));
+    assert!(problems.iter().any(|p| p.starts_with("network:")));
+    assert!(problems.iter().any(|p| p.starts_with("llm.provider")));
+    assert!(problems.iter().any(|p| p.starts_with("agent: path cue \"service:{dir}\": unknown placeholder")));
+    assert!(problems.iter().any(|p| p == "agent: route \"../shared\": dir must be a directory below the watch root"));
+}
+
+#[test]
+fn test_config_validate_command() {
+    let dir = tempdir().unwrap();
+    let good = dir.path().join("good.toml");
+    fs::write(&good, "[server]\nport = 8081\n").unwrap();
+    let bad = dir.path().join("bad.toml");
+    fs::write(&bad, "[server]\ndashmap_shards = 3\n").unwrap();
+
+    let mut out = Vec::new();
+    let validate = |file| Command::Config { action: ConfigCommand::Validate { file } };
+    run(validate(good), dir.path(), &mut out).unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(report["valid"], true);
+
+    let mut out = Vec::new();
+    assert!(run(validate(bad), dir.path(), &mut out).is_err());
+    let report: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(report["valid"], false);
+    assert_eq!(report["problems"].as_array().unwrap().len(), 1);
+
+    // Validating never creates the data directory
+    let missing = dir.path().join("missing.toml");
+    assert!(run(validate(missing), &dir.path().join("data"), &mut Vec::new()).is_err());
+    assert!(!dir.path().join("data").exists());
+}
@@ -0,0 +1,76 @@
+use crate::common::{self, cues};
+use cuemap_rust::content_limit::{self, ContentLimit, Oversized};
+use cuemap_rust::engine::CueMapEngine;
+use cuemap_rust::persistence::PersistenceManager;
+use cuemap_rust::projects::ProjectContext;
+use serde_json::Value;
+use std::sync::Arc;
+
+#[test]
+fn test_content_limit_and_shortening() {
+    assert!(ContentLimit::default().validate(1000).is_ok());
+    assert!(ContentLimit { max_bytes: Some(0), oversized: Oversized::Reject }.validate(1000).is_err());
+    assert!(ContentLimit { max_bytes: Some(1001), oversized: Oversized::Summarize }.validate(1000).is_err());
+    let limit = ContentLimit { max_bytes: Some(8), oversized: Oversized::Summarize };
+    assert_eq!(limit.exceeded_by("refunds"), None);
+    assert_eq!(limit.exceeded_by("refunds stall"), Some(8));
+    // Never cuts a character in half
+    assert_eq!(content_limit::truncate("café crème", 4), "caf");
+    assert_eq!(content_limit::truncate("café", 10), "café");
+
+    let engine = CueMapEngine::new();
+    let id = engine.add_memory("refunds stall for five days".to_string(), cues(&["topic:refunds"]), None, false);
+    assert!(engine.shorten_content(&id, "refunds stall".to_string()));
+    assert!(engine.shorten_content(&id, "refunds slow".to_string()));
+    let memory = engine.get_memory(&id).unwrap();
+    assert_eq!(memory.content, "refunds slow");
+    assert_eq!(memory.full_content.as_deref(), Some("refunds stall for five days"));
+    assert!(!engine.shorten_content("missing", String::new()));
+
+    let (memories, cue_index) = PersistenceManager::decode(&PersistenceManager::encode(&engine).unwrap()).unwrap();
+    let restored = CueMapEngine::from_state(memories, cue_index);
+    assert_eq!(restored.get_memory(&id).unwrap().full_content.as_deref(), Some("refunds stall for five days"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_content_limit_routes() {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+    let put = |body: Value| {
+        let request = client.put(format!("{}/content-limit", base)).json(&body);
+        async move { request.send().await.unwrap() }
+    };
+    let add = |content: &str| {
+        let request = client
+            .post(format!("{}/memories", base))
+            .json(&serde_json::json!({"content": content, "cues": ["topic:refunds"]}));
+        async move { request.send().await.unwrap() }
+    };
+    let long = "Refunds stall for five days when the payment provider retries. ".repeat(4);
+
+    assert_eq!(put(serde_json::json!({"max_bytes": 0})).await.status(), 400);
+    assert_eq!(put(serde_json::json!({"max_bytes": 64})).await.status(), 200);
+    assert_eq!(add(&long).await.status(), 413);
+    assert_eq!(add("Refunds stall").await.status(), 200);
+
+    assert_eq!(put(serde_json::json!({"max_bytes": 64, "oversized": "summarize"})).await.status(), 200);
+    let limit: Value = client.get(format!("{}/content-limit", base)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(limit, serde_json::json!({"max_bytes": 64, "oversized": "summarize"}));
+    let config: Value = client.get(format!("{}/admin/config", base)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(config["project_overrides"]["default"]["content_limit"], limit);
+
+    let body: Value = add(&long).await.json().await.unwrap();
+    assert_eq!(body["shortened"], true);
+    let id = body["id"].as_str().unwrap();
+    // Stored cut to the limit until a summary replaces it
+    let memory: Value = client.get(format!("{}/memories/{}", base, id)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(memory["content"], long[..64]);
+    assert!(memory.get("full_content").is_none());
+    let full: Value = client.get(format!("{}/memories/{}/full-content", base, id)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(full["full_content"], long.as_str());
+    assert_eq!(full["shortened"], true);
+    let missing = client.get(format!("{}/memories/missing/full-content", base)).send().await.unwrap();
+    assert_eq!(missing.status(), 404);
+}
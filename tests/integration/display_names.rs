@@ -0,0 +1,70 @@
+use crate::common::{self, cues};
+use cuemap_rust::engine::CueMapEngine;
+use cuemap_rust::projects::ProjectContext;
+use serde_json::Value;
+use std::sync::Arc;
+
+#[test]
+fn test_first_seen_display_names() {
+    let project = ProjectContext::new(Default::default(), Default::default());
+    let prepared = project.prepare_memory("payments api down".to_string(), cues(&[" service:PaymentsAPI ", "team:core"]), None).unwrap();
+    let id = project.main.add_memory(prepared.content, prepared.report.accepted, None, false);
+    assert_eq!(project.display_name("service:paymentsapi").as_deref(), Some("service:PaymentsAPI"));
+    assert_eq!(project.display_name("team:core"), None);
+
+    // Later spellings do not replace the first one, and matching stays on the normalized cue
+    let prepared = project.prepare_memory("payments api slow".to_string(), cues(&["service:PAYMENTSAPI"]), None).unwrap();
+    assert_eq!(prepared.report.accepted, cues(&["service:paymentsapi"]));
+    let other = project.main.add_memory(prepared.content, prepared.report.accepted, None, false);
+    assert_eq!(project.display_name("service:paymentsapi").as_deref(), Some("service:PaymentsAPI"));
+    assert_eq!(project.main.cue_len("service:paymentsapi"), 2);
+
+    let config = project.config();
+    assert_eq!(config.cue_display_names.len(), 1);
+    assert_eq!(config.overrides(), serde_json::json!({}));
+    let restored = ProjectContext::from_config(CueMapEngine::new(), config);
+    assert_eq!(restored.display_name("service:paymentsapi").as_deref(), Some("service:PaymentsAPI"));
+
+    project.main.delete_memory(&id);
+    assert_eq!(project.prune_display_names(), 0);
+    project.main.delete_memory(&other);
+    assert_eq!(project.prune_display_names(), 1);
+    assert_eq!(project.display_name("service:paymentsapi"), None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_display_names_in_cues_and_explain() {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+
+    for (content, cues) in [("payments api down", vec!["service:PaymentsAPI", "team:core"]), ("payments api slow", vec!["service:paymentsapi"])] {
+        let response = client
+            .post(format!("{}/memories", base))
+            .json(&serde_json::json!({"content": content, "cues": cues}))
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+    }
+
+    let body: Value = client.get(format!("{}/cues", base)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(body["total"], 2);
+    assert_eq!(body["cues"][0], serde_json::json!({"cue": "service:paymentsapi", "display_name": "service:PaymentsAPI", "memories": 2}));
+    assert_eq!(body["cues"][1], serde_json::json!({"cue": "team:core", "memories": 1}));
+    let body: Value = client.get(format!("{}/cues?prefix=team:&limit=5", base)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(body["total"], 1);
+
+    let body: Value = client
+        .post(format!("{}/recall", base))
+        .json(&serde_json::json!({"cues": ["service:PAYMENTSAPI"], "explain": true}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["results"].as_array().unwrap().len(), 2);
+    assert_eq!(body["explain"]["display_names"], serde_json::json!({"service:paymentsapi": "service:PaymentsAPI"}));
+}
@@ -0,0 +1,52 @@
+use crate::common::{self, cues};
+use cuemap_rust::engine::CueMapEngine;
+use cuemap_rust::projects::ProjectContext;
+use serde_json::Value;
+use std::sync::Arc;
+
+fn recall_within(engine: &CueMapEngine, cue: &str, min_intersection: Option<usize>, within: &[String]) -> Vec<String> {
+    engine
+        .recall_weighted_in(vec![(cue.to_string(), 1.0)], 10, false, min_intersection, false, true, false, false, None, None, Some(within))
+        .into_iter()
+        .map(|r| r.memory_id)
+        .collect()
+}
+
+#[test]
+fn test_recall_within_ids() {
+    let engine = CueMapEngine::new();
+    let timeout = engine.add_memory("payments timed out".to_string(), cues(&["service:payments", "error:timeout"]), None, false);
+    let refund = engine.add_memory("refund issued".to_string(), cues(&["service:payments"]), None, false);
+    let search = engine.add_memory("search timed out".to_string(), cues(&["service:search", "error:timeout"]), None, false);
+
+    let payments = recall_within(&engine, "service:payments", None, &[timeout.clone(), refund.clone(), "missing".to_string(), refund.clone()]);
+    assert_eq!(payments, vec![refund.clone(), timeout.clone()]);
+    // Of those, which timed out? The search timeout is outside the set
+    assert_eq!(recall_within(&engine, "error:timeout", None, &payments), vec![timeout.clone()]);
+    assert!(recall_within(&engine, "error:timeout", None, &[]).is_empty());
+    assert!(recall_within(&engine, "error:timeout", Some(2), &[timeout, search]).is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_recall_within_route() {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let timeout = project.main.add_memory("payments timed out".to_string(), cues(&["service:payments", "error:timeout"]), None, false);
+    project.main.add_memory("refund issued".to_string(), cues(&["service:payments"]), None, false);
+    project.main.add_memory("search timed out".to_string(), cues(&["service:search", "error:timeout"]), None, false);
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+    let recall = |body: Value| {
+        let request = client.post(format!("{}/recall", base)).json(&body);
+        async move { request.send().await.unwrap().json::<Value>().await.unwrap() }
+    };
+
+    let body = recall(serde_json::json!({"cues": ["service:payments"], "fields": "id", "disable_pattern_completion": true})).await;
+    let ids: Vec<Value> = body["results"].as_array().unwrap().iter().map(|r| r["memory_id"].clone()).collect();
+    assert_eq!(ids.len(), 2);
+
+    let body = recall(serde_json::json!({"cues": ["error:timeout"], "within_ids": ids, "disable_pattern_completion": true})).await;
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["memory_id"], timeout.as_str());
+}
@@ -0,0 +1,67 @@
+use crate::common;
+use axum::http::StatusCode;
+use cuemap_rust::api;
+use cuemap_rust::auth::AuthConfig;
+use cuemap_rust::error::{ApiError, PROBLEM_JSON};
+use cuemap_rust::grounding::GroundingTraceStore;
+use cuemap_rust::jobs::{JobQueue, SingleTenantProvider};
+use cuemap_rust::projects::ProjectContext;
+use cuemap_rust::telemetry;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[test]
+fn test_problem_body() {
+    let error = ApiError::Invalid { detail: "Invalid eval set".to_string(), problems: vec!["set has no queries".to_string()] };
+    assert_eq!(error.status(), StatusCode::BAD_REQUEST);
+    let problem = error.to_problem();
+    assert_eq!(problem["type"], "about:blank");
+    assert_eq!(problem["title"], "Bad Request");
+    assert_eq!(problem["status"], 400);
+    assert_eq!(problem["code"], "validation_failed");
+    assert_eq!(problem["detail"], "Invalid eval set");
+    assert_eq!(problem["error"], "Invalid eval set");
+    assert_eq!(problem["problems"][0], "set has no queries");
+    // Outside a request there is no id to report
+    assert!(problem.get("request_id").is_none());
+
+    assert_eq!(ApiError::ReadOnly.status(), StatusCode::FORBIDDEN);
+    assert_eq!(ApiError::WrongMode("multi-tenant").status(), StatusCode::NOT_FOUND);
+    assert_eq!(ApiError::WrongMode("multi-tenant").detail(), "Endpoint is only available in multi-tenant mode");
+}
+
+#[tokio::test]
+async fn test_router_returns_problem_json() {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let job_queue = Arc::new(JobQueue::new(Arc::new(SingleTenantProvider { project: project.clone() })));
+    let traces = Arc::new(GroundingTraceStore::new(100, None));
+    let app = api::routes(project, job_queue, traces, AuthConfig::with_keys(HashMap::new()), true)
+        .layer(axum::middleware::from_fn(telemetry::trace_request));
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(format!("{}/memories/missing", base))
+        .header("X-Request-Id", "req-42")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+    assert_eq!(response.headers()["content-type"], PROBLEM_JSON);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["status"], 404);
+    assert_eq!(body["code"], "not_found");
+    assert_eq!(body["request_id"], "req-42");
+
+    let response = client
+        .post(format!("{}/memories", base))
+        .json(&serde_json::json!({"content": "INC-1", "cues": ["service:auth"]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 403);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "read_only");
+    // Requests without an id get a generated one
+    assert!(body["request_id"].as_str().is_some_and(|id| !id.is_empty()));
+}
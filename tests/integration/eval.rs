@@ -0,0 +1,124 @@
+use crate::common::{self, cues};
+use cuemap_rust::evals::golden::{self, EvalStore, GoldenQuery, GoldenSet, EVAL_RUNS_FILE};
+use cuemap_rust::projects::ProjectContext;
+use std::sync::Arc;
+
+#[test]
+fn test_run_set_scores_queries() {
+    let project = ProjectContext::new(Default::default(), Default::default());
+    let old = project.main.add_memory("payments runbook".to_string(), cues(&["service:payments", "type:runbook"]), None, true);
+    let new = project.main.add_memory("payments incident".to_string(), cues(&["service:payments"]), None, true);
+    let search = project.main.add_memory("search guide".to_string(), cues(&["service:search", "type:guide"]), None, true);
+
+    let set = GoldenSet {
+        name: "smoke".to_string(),
+        queries: vec![
+            // The incident matches one cue fewer and ranks second
+            GoldenQuery { cues: cues(&["service:payments", "type:runbook"]), query_text: None, expected: vec![new.clone()], limit: 2 },
+            GoldenQuery { cues: cues(&["service:search"]), query_text: None, expected: vec![search.clone(), "gone".to_string()], limit: 5 },
+        ],
+    };
+    let run = golden::run_set("default", &project, &set);
+    assert_eq!(run.queries, 2);
+    assert_eq!(run.per_query[0].returned[..2], [old, new]);
+    assert_eq!(run.per_query[0].reciprocal_rank, 0.5);
+    assert_eq!(run.per_query[1].missed, vec!["gone".to_string()]);
+    assert_eq!(run.recall, 0.75);
+    assert_eq!(run.mrr, 0.75);
+    assert_eq!(run.per_query[1].precision, 1.0);
+
+    let invalid = GoldenSet { name: "bad".to_string(), queries: vec![GoldenQuery { cues: Vec::new(), query_text: None, expected: Vec::new(), limit: 10 }] };
+    assert_eq!(invalid.validate().len(), 2);
+}
+
+#[tokio::test]
+async fn test_eval_endpoints_record_history() {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(EvalStore::open(dir.path()));
+    let app = common::router(&project).layer(axum::Extension(store));
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+
+    let memory = project.main.add_memory("payments timed out".to_string(), cues(&["service:payments"]), None, true);
+
+    let response = client.put(format!("{}/admin/eval/sets/smoke", base)).json(&serde_json::json!({"queries": [{"cues": ["a:b"], "expected": []}]})).send().await.unwrap();
+    assert_eq!(response.status(), 400);
+
+    let response = client
+        .put(format!("{}/admin/eval/sets/smoke", base))
+        .json(&serde_json::json!({"queries": [{"cues": ["Service:Payments"], "expected": [memory]}]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let sets: serde_json::Value = client.get(format!("{}/admin/eval/sets", base)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(sets["sets"][0]["queries"][0]["limit"], 10);
+
+    for _ in 0..2 {
+        let body: serde_json::Value = client.post(format!("{}/admin/eval/run", base)).send().await.unwrap().json().await.unwrap();
+        assert_eq!(body["runs"][0]["mrr"], 1.0);
+        assert_eq!(body["runs"][0]["per_query"][0]["returned"], serde_json::json!([memory]));
+    }
+    let response = client.post(format!("{}/admin/eval/run", base)).json(&serde_json::json!({"set": "missing"})).send().await.unwrap();
+    assert_eq!(response.status(), 404);
+
+    let history: serde_json::Value = client.get(format!("{}/admin/eval/runs?set=smoke", base)).send().await.unwrap().json().await.unwrap();
+    let runs = history["runs"].as_array().unwrap();
+    assert_eq!(runs.len(), 2);
+    assert!(runs[0].get("per_query").is_none());
+
+    // Sets and history survive a restart
+    let reopened = EvalStore::open(dir.path());
+    assert_eq!(reopened.sets("default").len(), 1);
+    assert_eq!(reopened.runs("default", None).len(), 2);
+    assert_eq!(std::fs::read_to_string(dir.path().join(EVAL_RUNS_FILE)).unwrap().lines().count(), 2);
+
+    let response = client.delete(format!("{}/admin/eval/sets/smoke", base)).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let response = client.get(format!("{}/admin/eval/sets/smoke", base)).send().await.unwrap();
+    assert_eq!(response.status(), 404);
+}
+
+#[test]
+fn test_eval_run_history_is_capped() {
+    use cuemap_rust::config::EVAL_RUNS_PER_SET;
+
+    let project = ProjectContext::new(Default::default(), Default::default());
+    let dir = tempfile::tempdir().unwrap();
+    let store = EvalStore::open(dir.path());
+    let set = |name: &str| GoldenSet { name: name.to_string(), queries: vec![GoldenQuery { cues: vec!["a:b".to_string()], query_text: None, expected: Vec::new(), limit: 10 }] };
+    let (smoke, other) = (set("smoke"), set("other"));
+    let first = golden::run_set("default", &project, &other);
+    store.record(&first);
+    for _ in 0..EVAL_RUNS_PER_SET + 2 {
+        store.record(&golden::run_set("default", &project, &smoke));
+    }
+
+    // Each set keeps its newest runs; other sets are untouched
+    assert_eq!(store.runs("default", Some("smoke")).len(), EVAL_RUNS_PER_SET);
+    assert_eq!(store.runs("default", Some("other"))[0].id, first.id);
+    let lines = std::fs::read_to_string(dir.path().join(EVAL_RUNS_FILE)).unwrap().lines().count();
+    assert_eq!(lines, EVAL_RUNS_PER_SET + 1);
+    assert_eq!(EvalStore::open(dir.path()).runs("default", None).len(), EVAL_RUNS_PER_SET + 1);
+}
+
+#[tokio::test]
+async fn test_eval_sets_are_read_only_on_static_instances() {
+    use cuemap_rust::api;
+    use cuemap_rust::auth::AuthConfig;
+    use cuemap_rust::grounding::GroundingTraceStore;
+    use cuemap_rust::jobs::{JobQueue, SingleTenantProvider};
+
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let job_queue = Arc::new(JobQueue::new(Arc::new(SingleTenantProvider { project: project.clone() })));
+    let traces = Arc::new(GroundingTraceStore::new(100, None));
+    let app = api::routes(project, job_queue, traces, AuthConfig::with_keys(Default::default()), true).layer(axum::Extension(Arc::new(EvalStore::new())));
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+
+    let body = serde_json::json!({"queries": [{"cues": ["a:b"], "expected": []}]});
+    assert_eq!(client.put(format!("{}/admin/eval/sets/smoke", base)).json(&body).send().await.unwrap().status(), 403);
+    assert_eq!(client.delete(format!("{}/admin/eval/sets/smoke", base)).send().await.unwrap().status(), 403);
+    assert_eq!(client.post(format!("{}/admin/eval/run", base)).send().await.unwrap().status(), 403);
+}
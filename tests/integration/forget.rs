@@ -0,0 +1,151 @@
+use crate::common::{self, cues};
+use cuemap_rust::forget::{self, DeletionReport, ReportSigner, Selector};
+use cuemap_rust::grounding::GroundingTraceStore;
+use cuemap_rust::persistence::PersistenceManager;
+use cuemap_rust::projects::ProjectContext;
+use cuemap_rust::taxonomy::RejectedCue;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_forget_endpoint_deletes_and_signs() {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let dir = tempfile::tempdir().unwrap();
+    let traces_path = dir.path().join("traces.jsonl");
+    let traces = Arc::new(GroundingTraceStore::open(&traces_path, 100, None));
+    let signer = Arc::new(ReportSigner::open(dir.path().join(forget::SIGNING_KEY_FILE)));
+    let persistence = PersistenceManager::new(dir.path(), 3600, false).unwrap();
+    let app = common::router_with_traces(&project, traces.clone())
+        .layer(axum::Extension(signer))
+        .layer(axum::Extension(persistence));
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+
+    let main = &project.main;
+    let alice = main.add_memory("alice prefers email".to_string(), cues(&["user:id_123", "topic:contact"]), None, true);
+    let old = main.add_memory("alice moved".to_string(), cues(&["user:id_123", "topic:address"]), None, true);
+    let bob = main.add_memory("bob prefers phone".to_string(), cues(&["user:id_456", "topic:contact"]), None, true);
+    // A consolidated summary keeps the cue; its tombstoned source only in metadata
+    let summary = main.merge_memories(&[old.clone(), bob.clone()], "moves and phones".to_string()).unwrap();
+    let bob_note = main.add_memory("bob is on call".to_string(), cues(&["user:id_456"]), None, true);
+    project.aliases.upsert_memory_with_id(
+        "alias_1".to_string(),
+        "{}".to_string(),
+        cues(&["type:alias", "from:uid:123", "to:user:id_123", "status:active"]),
+        None,
+        false,
+    );
+    project.lexicon.upsert_memory_with_id("cue:user:id_123".to_string(), "user:id_123".to_string(), cues(&["tok:alice"]), None, false);
+
+    let grounded: serde_json::Value = client
+        .post(format!("{}/recall/grounded", base))
+        .json(&serde_json::json!({"query_text": "alice"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(grounded["verified_context"].as_str().unwrap().contains("alice prefers email"));
+    assert_eq!(traces.len(), 1);
+
+    let response = client.post(format!("{}/admin/forget", base)).json(&serde_json::json!({})).send().await.unwrap();
+    assert_eq!(response.status(), 400);
+
+    let response = client
+        .post(format!("{}/admin/forget", base))
+        .json(&serde_json::json!({"cue": "User:ID_123"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let report: DeletionReport = serde_json::from_value(body["report"].clone()).unwrap();
+    let mut deleted = report.memories.clone();
+    deleted.sort();
+    let mut expected = vec![alice.clone(), old.clone(), summary.clone()];
+    expected.sort();
+    assert_eq!(deleted, expected);
+    assert_eq!(report.aliases, vec!["alias_1".to_string()]);
+    assert_eq!(report.lexicon, vec!["cue:user:id_123".to_string()]);
+    assert_eq!(report.grounding_traces, 1);
+    // The snapshot is rewritten at once; replicas catch up on their next sync
+    assert_eq!(report.snapshot_purge, "saved");
+    assert_eq!(report.replica_purge, "next_sync");
+    let snapshot = std::fs::read(dir.path().join("cuemap.bin")).unwrap();
+    let holds = |content: &str| snapshot.windows(content.len()).any(|window| window == content.as_bytes());
+    assert!(!holds("alice prefers email"));
+    assert!(holds("bob is on call"));
+
+    // The signature covers the report and uses the persisted key
+    let signer = ReportSigner::open(dir.path().join(forget::SIGNING_KEY_FILE));
+    let signature = body["signature"].as_str().unwrap();
+    assert!(signer.verify(&report, signature));
+    let mut tampered = report.clone();
+    tampered.memories.pop();
+    assert!(!signer.verify(&tampered, signature));
+
+    assert!(main.get_memory(&alice).is_none());
+    assert!(main.get_memory(&bob).is_some());
+    assert!(project.aliases.get_memory("alias_1").is_none());
+    assert!(traces.is_empty());
+    assert!(!std::fs::read_to_string(&traces_path).unwrap().contains("alice"));
+
+    // Metadata selectors match exact values
+    let tenant = HashMap::from([("tenant".to_string(), serde_json::json!("t1"))]);
+    let carol_content = "carol lives in lisbon";
+    let carol = main.add_memory(carol_content.to_string(), cues(&["topic:contact", "city:lisbon"]), Some(tenant), true);
+    main.add_memory("dave prefers phone".to_string(), cues(&["topic:contact"]), None, true);
+    // The lexicon learned carol's words for both of her cues, and dave's for the shared one
+    let train = |cue: &str, content: &str| {
+        project.lexicon.upsert_memory_with_id(format!("cue:{}", cue), cue.to_string(), project.lexicon_tokens(content), None, false);
+    };
+    train("topic:contact", "dave prefers phone");
+    train("topic:contact", carol_content);
+    train("city:lisbon", carol_content);
+    let rejected = RejectedCue { cue: "owner:carol".to_string(), code: "unknown_key".to_string(), detail: String::new() };
+    project.rejections.record(&carol, std::slice::from_ref(&rejected));
+    project.rejections.record(&bob_note, &[rejected]);
+
+    let selector = Selector { cue: None, metadata: BTreeMap::from([("tenant".to_string(), serde_json::json!("t1"))]) };
+    let report = forget::forget("default", &project, &traces, &selector);
+    assert_eq!(report.memories, vec![carol.clone()]);
+    assert!(main.get_memory(&bob_note).is_some());
+    assert_eq!(report.lexicon, vec!["cue:city:lisbon".to_string()]);
+    assert_eq!(report.lexicon_retrained, vec!["cue:topic:contact".to_string()]);
+    let contact = project.lexicon.get_memory("cue:topic:contact").unwrap();
+    let contact_tokens: Vec<&str> = contact.cues.iter().map(|cue| &**cue).collect();
+    assert!(contact_tokens.contains(&"tok:phone"));
+    assert!(!contact_tokens.iter().any(|token| token.contains("lisbon") || token.contains("carol")));
+    assert!(project.lexicon.get_memory("cue:city:lisbon").is_none());
+    assert_eq!(report.quarantined_cues, 1);
+    let quarantined = project.rejections.entries(None, 10);
+    assert_eq!(quarantined.len(), 1);
+    assert_eq!(quarantined[0].memory_id, bob_note);
+}
+
+#[test]
+fn test_report_signature_checks() {
+    let signer = ReportSigner::new("secret");
+    let report = DeletionReport {
+        id: "r1".to_string(),
+        project: "default".to_string(),
+        selector: Selector { cue: Some("user:id_123".to_string()), metadata: BTreeMap::new() },
+        memories: vec!["m1".to_string()],
+        aliases: Vec::new(),
+        lexicon: Vec::new(),
+        lexicon_retrained: Vec::new(),
+        quarantined_cues: 0,
+        grounding_traces: 0,
+        snapshot_purge: "next_snapshot".to_string(),
+        replica_purge: "next_sync".to_string(),
+        deleted_at: 0,
+    };
+    let signature = signer.sign(&report);
+    assert!(signer.verify(&report, &signature));
+    assert!(signer.verify(&report, &signature.to_uppercase().replace("SHA256=", "sha256=")));
+    assert!(!signer.verify(&report, signature.trim_start_matches("sha256=")));
+    assert!(!signer.verify(&report, &signature[..signature.len() - 2]));
+    assert!(!signer.verify(&report, "sha256=zz"));
+    assert!(!ReportSigner::new("other").verify(&report, &signature));
+}
@@ -0,0 +1,108 @@
+use crate::common;
+use cuemap_rust::embedded::{CueMapError, GroundedQuery};
+use cuemap_rust::engine::RecallResult;
+use cuemap_rust::hooks::{Hooks, PendingMemory, RecallQueryInfo};
+use cuemap_rust::multi_tenant::MultiTenantEngine;
+use cuemap_rust::projects::ProjectContext;
+use cuemap_rust::CueMap;
+use std::sync::Arc;
+
+/// Tags incidents, redacts emails and refuses anything mentioning a password.
+fn ingest_hook(memory: &mut PendingMemory) -> Result<(), String> {
+    if memory.content.contains("password") {
+        return Err("content contains a credential".to_string());
+    }
+    if memory.content.contains("INC-") {
+        memory.cues.push("Type:Incident".to_string());
+        memory.metadata.insert("tagged_by".to_string(), serde_json::json!("hook"));
+    }
+    memory.content = memory.content.replace("ops@example.com", "[email]");
+    Ok(())
+}
+
+/// Drops drafts and moves runbooks to the front.
+fn recall_hook(_: &RecallQueryInfo, results: &mut Vec<RecallResult>) {
+    results.retain(|r| !r.content.starts_with("[draft]"));
+    results.sort_by_key(|r| !r.content.starts_with("Runbook"));
+}
+
+fn hooks() -> Hooks {
+    Hooks::new().ingest(ingest_hook).recall(recall_hook)
+}
+
+#[test]
+fn test_embedded_hooks() {
+    let cuemap = CueMap::builder().hooks(hooks()).open().unwrap();
+
+    let added = cuemap.add("INC-7: paged ops@example.com", ["service:payments"]).unwrap();
+    let memory = cuemap.get(&added.id).unwrap();
+    assert_eq!(memory.content, "INC-7: paged [email]");
+    assert_eq!(memory.metadata["tagged_by"], "hook");
+    // Cues added by hooks are normalized like the caller's
+    assert_eq!(cuemap.recall(["type:incident"], 10)[0].memory_id, added.id);
+
+    let rejected = cuemap.add("the password is hunter2", ["service:payments"]);
+    assert!(matches!(rejected, Err(CueMapError::Rejected(reason)) if reason.contains("credential")));
+    assert_eq!(cuemap.len(), 1);
+
+    cuemap.add("[draft] payments notes", ["service:payments"]).unwrap();
+    let runbook = cuemap.add("Runbook: restart the payments workers", ["service:payments"]).unwrap();
+    let results = cuemap.recall(["service:payments"], 10);
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].memory_id, runbook.id);
+
+    cuemap.project().lexicon.upsert_memory_with_id("lex_1".to_string(), "service:payments".to_string(), vec!["tok:payments".to_string()], None, false);
+    let grounded = cuemap.recall_grounded(&GroundedQuery::new("payments"));
+    assert!(!grounded.verified_context.contains("[draft]"));
+}
+
+#[test]
+fn test_multi_tenant_projects_get_hooks() {
+    let dir = tempfile::tempdir().unwrap();
+    let mt_engine = MultiTenantEngine::with_snapshots_dir(dir.path()).with_hooks(hooks());
+    let ctx = mt_engine.get_or_create_project("acme".to_string());
+    assert!(ctx.prepare_memory("password: x".to_string(), Vec::new(), None).is_err());
+
+    let memory = ctx.prepare_memory("INC-1".to_string(), vec!["service:a".to_string()], None).unwrap();
+    assert_eq!(memory.report.accepted, vec!["service:a".to_string(), "type:incident".to_string()]);
+}
+
+#[tokio::test]
+async fn test_router_runs_hooks() {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()).with_hooks(hooks()));
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("{}/memories", base))
+        .json(&serde_json::json!({"content": "password reset for INC-2", "cues": ["service:auth"]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 422);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "hook_rejected");
+
+    for content in ["[draft] auth outage", "Runbook: rotate auth keys", "auth latency INC-3"] {
+        let response = client
+            .post(format!("{}/memories", base))
+            .json(&serde_json::json!({"content": content, "cues": ["service:auth"]}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+    }
+
+    let body: serde_json::Value = client
+        .post(format!("{}/recall", base))
+        .json(&serde_json::json!({"cues": ["service:auth"]}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let contents: Vec<&str> = body["results"].as_array().unwrap().iter().map(|r| r["content"].as_str().unwrap()).collect();
+    assert_eq!(contents, vec!["Runbook: rotate auth keys", "auth latency INC-3"]);
+}
@@ -0,0 +1,45 @@
+use cuemap_rust::ip_filter::*;
+use std::net::IpAddr;
+
+fn ip(s: &str) -> IpAddr {
+    s.parse().unwrap()
+}
+
+fn strings(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_allow_and_deny() {
+    let filter = IpFilter::new(&strings(&["10.0.0.0/8", "192.168.1.5"]), &strings(&["10.9.0.0/16"]), &[]).unwrap();
+    assert!(filter.is_enabled());
+
+    assert!(filter.is_allowed(ip("10.1.2.3")));
+    assert!(filter.is_allowed(ip("192.168.1.5")));
+    assert!(!filter.is_allowed(ip("192.168.1.6")));
+    // Deny wins over allow
+    assert!(!filter.is_allowed(ip("10.9.1.1")));
+    // IPv4-mapped IPv6 peers match IPv4 rules
+    assert!(filter.is_allowed(ip("::ffff:10.1.2.3")));
+
+    let deny_only = IpFilter::new(&[], &strings(&["203.0.113.0/24"]), &[]).unwrap();
+    assert!(deny_only.is_allowed(ip("198.51.100.1")));
+    assert!(!deny_only.is_allowed(ip("203.0.113.7")));
+
+    assert!(!IpFilter::default().is_enabled());
+    assert!(IpFilter::new(&strings(&["not-a-cidr"]), &[], &[]).is_err());
+}
+
+#[test]
+fn test_forwarded_for_from_trusted_proxy() {
+    let filter = IpFilter::new(&[], &[], &strings(&["10.0.0.0/24"])).unwrap();
+    let xff = Some("203.0.113.7, 198.51.100.2, 10.0.0.3");
+
+    // Untrusted peers cannot spoof their address
+    assert_eq!(filter.client_ip(ip("198.51.100.9"), xff), ip("198.51.100.9"));
+    // Trusted hops are skipped from the right
+    assert_eq!(filter.client_ip(ip("10.0.0.1"), xff), ip("198.51.100.2"));
+    assert_eq!(filter.client_ip(ip("10.0.0.1"), Some("10.0.0.4, 10.0.0.5")), ip("10.0.0.4"));
+    assert_eq!(filter.client_ip(ip("10.0.0.1"), None), ip("10.0.0.1"));
+    assert_eq!(filter.client_ip(ip("10.0.0.1"), Some("garbage")), ip("10.0.0.1"));
+}
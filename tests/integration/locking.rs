@@ -0,0 +1,118 @@
+use crate::common::{self, cues};
+use cuemap_rust::api;
+use cuemap_rust::auth::AuthConfig;
+use cuemap_rust::engine::CueMapEngine;
+use cuemap_rust::grounding::GroundingTraceStore;
+use cuemap_rust::jobs::{Job, JobQueue, SingleTenantProvider};
+use cuemap_rust::persistence::PersistenceManager;
+use cuemap_rust::projects::ProjectContext;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn test_background_maintenance_skips_locked_memories() {
+    let engine = CueMapEngine::new();
+    let shared = cues(&["service:payments", "topic:refunds", "team:core"]);
+    let curated = engine.add_memory("refunds take five days".to_string(), shared.clone(), None, true);
+    let first = engine.add_memory("refund queue stalled".to_string(), shared.clone(), None, true);
+    let second = engine.add_memory("refund retried".to_string(), shared.clone(), None, true);
+    for id in [&curated, &first] {
+        engine.reinforce_memory(id, shared.clone());
+        engine.reinforce_memory(id, shared.clone());
+    }
+    assert!(engine.set_locked(&curated, true));
+    assert!(!engine.set_locked("missing", true));
+
+    let groups = engine.consolidation_groups(0.5);
+    assert_eq!(groups.len(), 1);
+    assert!(!groups[0].contains(&curated));
+
+    let salience = |id: &str| engine.get_memory(id).unwrap().salience;
+    let (curated_salience, first_salience) = (salience(&curated), salience(&first));
+    engine.decay_reinforcement(1);
+    engine.demote_stale(f64::MAX, 0.5);
+    assert_eq!(engine.get_memory(&curated).unwrap().reinforcement_count, 2);
+    assert_eq!(salience(&curated), curated_salience);
+    assert_eq!(engine.get_memory(&first).unwrap().reinforcement_count, 1);
+    assert!(salience(&first) < first_salience);
+
+    // Jobs attach proposed cues only to unlocked memories
+    assert!(!engine.attach_cues_unless_locked(&curated, cues(&["topic:llm"])));
+    assert!(engine.attach_cues_unless_locked(&second, cues(&["topic:llm"])));
+    assert!(!engine.get_memory(&curated).unwrap().cues.iter().any(|cue| &**cue == "topic:llm"));
+
+    assert_eq!(engine.rename_cue_key("team", "owner"), 2);
+    assert!(engine.get_memory(&curated).unwrap().cues.iter().any(|cue| &**cue == "team:core"));
+
+    let (memories, cue_index) = PersistenceManager::decode(&PersistenceManager::encode(&engine).unwrap()).unwrap();
+    let restored = CueMapEngine::from_state(memories, cue_index);
+    assert!(restored.get_memory(&curated).unwrap().locked);
+    assert!(!restored.get_memory(&second).unwrap().locked);
+
+    let group = vec![curated.clone(), first.clone(), second.clone()];
+    assert!(engine.merge_memories(&group, "refunds stall and get retried".to_string()).is_some());
+    assert!(!engine.get_memory(&curated).unwrap().is_tombstoned());
+    assert!(engine.get_memory(&first).unwrap().is_tombstoned());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_lock_routes_and_agent_verification() {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let job_queue = Arc::new(JobQueue::new(Arc::new(SingleTenantProvider { project: project.clone() })));
+    let traces = Arc::new(GroundingTraceStore::new(100, None));
+    let app = api::routes(project.clone(), job_queue.clone(), traces, AuthConfig::with_keys(HashMap::new()), false);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+
+    let file_cues = cues(&["path:refunds.md", "source:agent"]);
+    let curated = "file:refunds.md:0";
+    let stale = "file:refunds.md:1";
+    project.main.upsert_memory_with_id(curated.to_string(), "refunds take five days".to_string(), file_cues.clone(), None, false);
+    project.main.upsert_memory_with_id(stale.to_string(), "refunds need approval".to_string(), file_cues, None, false);
+
+    let response = client.post(format!("{}/memories/missing/lock", base)).send().await.unwrap();
+    assert_eq!(response.status(), 404);
+    let response = client.post(format!("{}/memories/{}/lock", base, curated)).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let etag = response.headers()["etag"].to_str().unwrap().to_string();
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["locked"], true);
+
+    let memory: Value = client.get(format!("{}/memories/{}", base, curated)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(memory["locked"], true);
+    let recall: Value = client
+        .post(format!("{}/recall", base))
+        .json(&serde_json::json!({"cues": ["path:refunds.md"], "limit": 10}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let results = recall["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    for result in results {
+        assert_eq!(result["locked"].as_bool().unwrap_or(false), result["memory_id"] == curated);
+    }
+
+    // The file no longer yields either memory, but the agent keeps the locked one
+    job_queue
+        .enqueue(Job::VerifyFile {
+            project_id: "default".to_string(),
+            file_path: "refunds.md".to_string(),
+            valid_memory_ids: Vec::new(),
+            attempt: None,
+        })
+        .await;
+    assert!(job_queue.shutdown(Duration::from_secs(5)).await);
+    assert!(project.main.get_memory(curated).is_some());
+    assert!(project.main.get_memory(stale).is_none());
+
+    let response = client.delete(format!("{}/memories/{}/lock", base, curated)).header("If-Match", etag).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["locked"], false);
+    assert!(!project.main.get_memory(curated).unwrap().locked);
+}
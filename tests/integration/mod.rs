@@ -1,3 +1,32 @@
+#[path = "../common/mod.rs"]
+mod common;
+
+mod agent;
+mod agent_files;
+mod alias_candidates;
+mod auth;
+mod cli;
+mod collections;
+mod config_file;
+mod content_limit;
+mod display_names;
+mod drill_down;
+mod errors;
+mod eval;
+mod forget;
+mod hooks;
+mod ip_filter;
+mod locking;
+mod ranking;
+mod recall_batch;
+mod recall_defaults;
+mod replication;
+mod result_fields;
+mod subgraph;
+mod tls;
+mod verify;
+mod webhooks;
+
 use cuemap_rust::engine::CueMapEngine;
 use cuemap_rust::projects::ProjectContext;
 use cuemap_rust::normalization::NormalizationConfig;
@@ -21,7 +50,7 @@ async fn test_lexicon_resolution() {
     // 1. Add memory with cues
     let content = "The payments service is experiencing high latency.".to_string();
     let cues = vec!["service:payments".to_string(), "status:slow".to_string()];
-    let memory_id = ctx.main.add_memory(content.clone(), cues.clone(), None);
+    let memory_id = ctx.main.add_memory(content.clone(), cues.clone(), None, false);
 
     // 2. Manually trigger Lexicon training job (usually triggered by API)
     job_queue.enqueue(Job::TrainLexiconFromMemory {
@@ -61,7 +90,7 @@ async fn test_alias_expansion_and_weighting() {
         "status:active".to_string(),
     ];
     
-    ctx.aliases.add_memory(alias_content, alias_cues, None);
+    ctx.aliases.add_memory(alias_content, alias_cues, None, false);
 
     // 2. Expand cues
     let query_cues = vec!["pay".to_string()];
@@ -74,10 +103,10 @@ async fn test_alias_expansion_and_weighting() {
 
     // 3. Verify weighting in recall
     // Add two memories: one for "pay", one for "service:payments"
-    let id_exact = ctx.main.add_memory("Direct pay".to_string(), vec!["pay".to_string()], None);
-    let id_aliased = ctx.main.add_memory("Payments service".to_string(), vec!["service:payments".to_string()], None);
+    let id_exact = ctx.main.add_memory("Direct pay".to_string(), vec!["pay".to_string()], None, false);
+    let id_aliased = ctx.main.add_memory("Payments service".to_string(), vec!["service:payments".to_string()], None, false);
 
-    let results = ctx.main.recall_weighted(expanded, 10, false, None, true);
+    let results = ctx.main.recall_weighted(expanded, 10, false, None, true, false, false, false, None);
 
     assert_eq!(results.len(), 2);
     // Exact match (weight 1.0) should be first
@@ -94,9 +123,9 @@ async fn test_alias_expansion_and_weighting() {
 async fn test_explain_output_structure() {
     let engine = CueMapEngine::new();
     
-    engine.add_memory("test".to_string(), vec!["a".to_string()], None);
+    engine.add_memory("test".to_string(), vec!["a".to_string()], None, false);
     
-    let results = engine.recall_weighted(vec![("a".to_string(), 1.0)], 10, false, None, true);
+    let results = engine.recall_weighted(vec![("a".to_string(), 1.0)], 10, false, None, true, false, false, false, None);
     
     assert!(!results.is_empty());
     let explain = results[0].explain.as_ref().expect("Explain should be present");
@@ -119,7 +148,7 @@ async fn test_alias_proposal_job() {
     // Create a scenario for alias discovery:
     // "prod" and "production" share 100% of memories
     for i in 0..25 {
-        ctx.main.add_memory(format!("Mem {}", i), vec!["prod".to_string(), "production".to_string()], None);
+        ctx.main.add_memory(format!("Mem {}", i), vec!["prod".to_string(), "production".to_string()], None, false);
     }
 
     // Trigger alias proposal job
@@ -141,3 +170,289 @@ async fn test_alias_proposal_job() {
         (content["from"] == "production" && content["to"] == "prod")
     );
 }
+
+#[tokio::test]
+async fn test_recall_expansion_flags() {
+    let project = Arc::new(ProjectContext::new(NormalizationConfig::default(), Taxonomy::default()));
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+
+    let payments = project.main.add_memory("payments timed out".to_string(), vec!["service:payments".to_string()], None, false);
+    let billing = project.main.add_memory("invoice run failed".to_string(), vec!["topic:billing".to_string()], None, false);
+    let alias = serde_json::json!({"from": "pay", "to": "service:payments", "downweight": 0.85, "status": "active"}).to_string();
+    project.aliases.upsert_memory_with_id("alias_pay".to_string(), alias, vec!["type:alias".to_string(), "from:pay".to_string(), "to:service:payments".to_string(), "status:active".to_string()], None, false);
+    project.lexicon.upsert_memory_with_id("cue:topic:billing".to_string(), "topic:billing".to_string(), vec!["tok:invoice".to_string()], None, false);
+
+    let recall = |body: Value| {
+        let request = client.post(format!("{}/recall", base)).json(&body);
+        async move {
+            let body: Value = request.send().await.unwrap().json().await.unwrap();
+            body["results"].as_array().unwrap().iter().map(|r| r["memory_id"].as_str().unwrap().to_string()).collect::<Vec<_>>()
+        }
+    };
+
+    // Both steps are on by default
+    assert_eq!(recall(serde_json::json!({"cues": ["pay"], "query_text": "invoice"})).await.len(), 2);
+    assert_eq!(recall(serde_json::json!({"cues": ["pay"], "query_text": "invoice", "resolve_text": false})).await, vec![payments.clone()]);
+    assert_eq!(recall(serde_json::json!({"cues": ["pay"], "query_text": "invoice", "expand_aliases": false})).await, vec![billing.clone()]);
+
+    let grounded: Value = client
+        .post(format!("{}/recall/grounded", base))
+        .json(&serde_json::json!({"query_text": "invoice", "cues": ["pay"], "resolve_text": false, "expand_aliases": false}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(grounded["proof"]["selected"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_memory_etags() {
+    let project = Arc::new(ProjectContext::new(NormalizationConfig::default(), Taxonomy::default()));
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+
+    let memory_id = project.main.add_memory("payments timed out".to_string(), vec!["service:payments".to_string()], None, false);
+    let url = format!("{}/memories/{}", base, memory_id);
+
+    let response = client.get(&url).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let etag = response.headers()["etag"].to_str().unwrap().to_string();
+    let response = client.get(&url).header("If-None-Match", &etag).send().await.unwrap();
+    assert_eq!(response.status(), 304);
+
+    // The first editor attaches under its ETag and gets the new one back
+    let attach = |if_match: &str, cue: &str| {
+        client
+            .post(format!("{}/cues", url))
+            .header("If-Match", if_match)
+            .json(&serde_json::json!({"cues": [cue]}))
+            .send()
+    };
+    let response = attach(&etag, "topic:billing").await.unwrap();
+    assert_eq!(response.status(), 200);
+    let new_etag = response.headers()["etag"].to_str().unwrap().to_string();
+    assert_ne!(new_etag, etag);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["status"], "attached");
+
+    // The second, still holding the old ETag, is refused instead of clobbering
+    let response = attach(&etag, "team:core").await.unwrap();
+    assert_eq!(response.status(), 412);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "precondition_failed");
+    let response = client
+        .patch(format!("{}/reinforce", url))
+        .header("If-Match", &etag)
+        .json(&serde_json::json!({"cues": ["service:payments"]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 412);
+
+    let cues: Vec<String> = project.main.get_memory(&memory_id).unwrap().cues.iter().map(|cue| cue.to_string()).collect();
+    assert_eq!(cues, vec!["service:payments", "topic:billing"]);
+    let response = client.get(&url).header("If-None-Match", &etag).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["etag"], new_etag.as_str());
+}
+
+#[tokio::test]
+async fn test_update_memory() {
+    let project = Arc::new(ProjectContext::new(NormalizationConfig::default(), Taxonomy::default()));
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+
+    let memory_id = project.main.add_memory("payments timed out".to_string(), vec!["service:payments".to_string()], None, false);
+    let url = format!("{}/memories/{}", base, memory_id);
+    let etag = project.main.get_memory(&memory_id).unwrap().etag();
+
+    let response = client
+        .put(&url)
+        .header("If-Match", &etag)
+        .json(&serde_json::json!({"content": "billing timed out", "cues": ["Service:Billing"]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert_ne!(response.headers()["etag"], etag.as_str());
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["status"], "updated");
+    assert_eq!(body["cues"], serde_json::json!(["service:billing"]));
+
+    let memory = project.main.get_memory(&memory_id).unwrap();
+    assert_eq!(memory.content, "billing timed out");
+    assert_eq!(project.main.cue_len("service:payments"), 0);
+    assert_eq!(project.main.cue_memory_ids("service:billing", None), vec![memory_id]);
+
+    // A stale ETag is refused; an unknown memory is not found
+    let response = client.put(&url).header("If-Match", &etag).json(&serde_json::json!({"content": "lost"})).send().await.unwrap();
+    assert_eq!(response.status(), 412);
+    let response = client.put(format!("{}/memories/missing", base)).json(&serde_json::json!({"content": "x"})).send().await.unwrap();
+    assert_eq!(response.status(), 404);
+}
+
+#[tokio::test]
+async fn test_response_compression() {
+    let project = Arc::new(ProjectContext::new(NormalizationConfig::default(), Taxonomy::default()));
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+
+    for i in 0..50 {
+        project.main.add_memory(format!("payments incident {} timed out after the deploy", i), vec!["service:payments".to_string()], None, false);
+    }
+    let recall = |encoding: &str, limit: usize| {
+        client
+            .post(format!("{}/recall", base))
+            .header("Accept-Encoding", encoding)
+            .json(&serde_json::json!({"cues": ["service:payments"], "limit": limit}))
+            .send()
+    };
+
+    for encoding in ["gzip", "br"] {
+        let response = recall(encoding, 50).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["content-encoding"], encoding);
+    }
+    // Small responses and clients that accept no encoding get plain JSON
+    let response = recall("gzip", 1).await.unwrap();
+    assert!(response.headers().get("content-encoding").is_none());
+    let response = recall("identity", 50).await.unwrap();
+    assert!(response.headers().get("content-encoding").is_none());
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["results"].as_array().unwrap().len(), 50);
+}
+
+#[tokio::test]
+async fn test_reinforce_trace() {
+    let project = Arc::new(ProjectContext::new(NormalizationConfig::default(), Taxonomy::default()));
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+
+    let payments = project.main.add_memory("payments timed out".to_string(), vec!["service:payments".to_string(), "team:core".to_string()], None, false);
+    project.main.add_memory("invoice run failed".to_string(), vec!["topic:billing".to_string()], None, false);
+
+    let grounded: Value = client
+        .post(format!("{}/recall/grounded", base))
+        .json(&serde_json::json!({"query_text": "payments", "cues": ["service:payments"], "resolve_text": false, "expand_aliases": false}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let trace_id = grounded["proof"]["trace_id"].as_str().unwrap().to_string();
+    let before = project.main.get_memory(&payments).unwrap().reinforcement_count;
+
+    let reinforce = || client.post(format!("{}/grounding/traces/{}/reinforce", base, trace_id)).send();
+    let response = reinforce().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["reinforced"], 1);
+    assert_eq!(body["memory_ids"], serde_json::json!([payments.clone()]));
+    assert_eq!(body["metrics"]["useful"], 1);
+    assert_eq!(project.main.get_memory(&payments).unwrap().reinforcement_count, before + 1);
+
+    // The trace's feedback is spent, so a second call can't reinforce twice
+    assert_eq!(reinforce().await.unwrap().status(), 409);
+    let response = client.post(format!("{}/grounding/traces/missing/reinforce", base)).send().await.unwrap();
+    assert_eq!(response.status(), 404);
+}
+
+#[tokio::test]
+async fn test_shared_vocabulary_endpoints() {
+    use cuemap_rust::shared_vocabulary::SharedVocabulary;
+
+    let shared = Arc::new(SharedVocabulary::new());
+    let project = Arc::new(ProjectContext::new(NormalizationConfig::default(), Taxonomy::default()).with_shared_vocabulary(shared.clone()));
+    let app = common::router(&project).layer(axum::Extension(shared));
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+    project.main.add_memory("ledger replication lag".to_string(), vec!["service:ledger".to_string()], None, false);
+
+    let response = client
+        .post(format!("{}/admin/shared/aliases", base))
+        .json(&serde_json::json!({"from": "gl", "to": "service:ledger"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 201);
+    let alias: Value = response.json().await.unwrap();
+    assert_eq!(alias["weight"], 0.85);
+    let response = client
+        .post(format!("{}/admin/shared/lexicon", base))
+        .json(&serde_json::json!({"cue": "service:ledger", "text": "general ledger"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    // Both layers reach the memory without any project-local vocabulary
+    for query in [serde_json::json!({"cues": ["gl"]}), serde_json::json!({"query_text": "ledger"})] {
+        let body: Value = client.post(format!("{}/recall", base)).json(&query).send().await.unwrap().json().await.unwrap();
+        assert_eq!(body["results"].as_array().unwrap().len(), 1, "{}", query);
+    }
+
+    let listed: Value = client.get(format!("{}/admin/shared/aliases", base)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(listed["aliases"].as_array().unwrap().len(), 1);
+    let id = alias["id"].as_str().unwrap();
+    let response = client.delete(format!("{}/admin/shared/aliases/{}", base, id)).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let response = client.delete(format!("{}/admin/shared/aliases/{}", base, id)).send().await.unwrap();
+    assert_eq!(response.status(), 404);
+    let response = client.delete(format!("{}/admin/shared/lexicon/service:ledger", base)).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let body: Value = client.get(format!("{}/admin/shared/lexicon", base)).send().await.unwrap().json().await.unwrap();
+    assert!(body["terms"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_delete_memory() {
+    use cuemap_rust::auth::AuthConfig;
+    use cuemap_rust::grounding::GroundingTraceStore;
+
+    let serve = |read_only: bool| async move {
+        let project = Arc::new(ProjectContext::new(NormalizationConfig::default(), Taxonomy::default()));
+        let job_queue = Arc::new(JobQueue::new(Arc::new(SingleTenantProvider { project: project.clone() })));
+        let traces = Arc::new(GroundingTraceStore::new(100, None));
+        let app = cuemap_rust::api::routes(project.clone(), job_queue, traces, AuthConfig::with_keys(Default::default()), read_only);
+        let base = common::serve(app).await;
+        (project, base)
+    };
+    let client = reqwest::Client::new();
+    let (project, base) = serve(false).await;
+    let stale = project.main.add_memory("old payments runbook".to_string(), vec!["service:payments".to_string()], None, false);
+    let fresh = project.main.add_memory("new payments runbook".to_string(), vec!["service:payments".to_string()], None, false);
+
+    let response = client.delete(format!("{}/memories/{}", base, stale)).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let body: Value = client
+        .post(format!("{}/recall", base))
+        .json(&serde_json::json!({"cues": ["service:payments"]}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let ids: Vec<&str> = body["results"].as_array().unwrap().iter().map(|r| r["memory_id"].as_str().unwrap()).collect();
+    assert_eq!(ids, vec![fresh.as_str()]);
+    let response = client.delete(format!("{}/memories/{}", base, stale)).send().await.unwrap();
+    assert_eq!(response.status(), 404);
+    let response = client.get(format!("{}/memories/{}", base, stale)).send().await.unwrap();
+    assert_eq!(response.status(), 404);
+
+    // Static snapshots and followers refuse it
+    let (project, base) = serve(true).await;
+    let id = project.main.add_memory("kept".to_string(), vec!["topic:kept".to_string()], None, false);
+    let response = client.delete(format!("{}/memories/{}", base, id)).send().await.unwrap();
+    assert_eq!(response.status(), 403);
+    assert!(project.main.get_memory(&id).is_some());
+}
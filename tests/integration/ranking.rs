@@ -0,0 +1,131 @@
+use crate::common::{self, cues};
+use cuemap_rust::engine::CueMapEngine;
+use cuemap_rust::projects::ProjectContext;
+use cuemap_rust::ranking::{RankingConfig, RankingRule};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn rule(name: &str, cue: Option<&str>, metadata: &[(&str, Value)], multiplier: f64) -> RankingRule {
+    RankingRule {
+        name: name.to_string(),
+        cue: cue.map(str::to_string),
+        metadata: metadata.iter().map(|(key, value)| (key.to_string(), value.clone())).collect::<BTreeMap<_, _>>(),
+        multiplier,
+    }
+}
+
+/// Adds an agent-ingested, a gold-tier and a plain memory, newest last, and
+/// returns their ids.
+fn populate(engine: &CueMapEngine) -> (String, String, String) {
+    let gold = HashMap::from([("tier".to_string(), serde_json::json!("gold"))]);
+    let agent = engine.add_memory("refund steps from the wiki".to_string(), cues(&["topic:refunds", "source:agent"]), None, false);
+    std::thread::sleep(Duration::from_millis(5));
+    let curated = engine.add_memory("refund policy".to_string(), cues(&["topic:refunds"]), Some(gold), false);
+    std::thread::sleep(Duration::from_millis(5));
+    let plain = engine.add_memory("refund asked in chat".to_string(), cues(&["topic:refunds"]), None, false);
+    (agent, curated, plain)
+}
+
+fn recall(engine: &CueMapEngine) -> Vec<(String, f64, Value)> {
+    engine
+        .recall_weighted(vec![("topic:refunds".to_string(), 1.0)], 10, false, None, true, true, false, false, None)
+        .into_iter()
+        .map(|result| (result.memory_id, result.score, result.explain.unwrap()))
+        .collect()
+}
+
+#[test]
+fn test_ranking_rules_rescore_recall() {
+    let engine = CueMapEngine::new();
+    let (agent, curated, plain) = populate(&engine);
+    let before = recall(&engine);
+    assert_eq!(before[0].0, plain);
+    assert_eq!(before[0].2["ranking_rules"], serde_json::json!([]));
+    let score = |results: &[(String, f64, Value)], id: &str| results.iter().find(|r| r.0 == id).unwrap().1;
+
+    engine.set_ranking(RankingConfig {
+        rules: vec![
+            rule("gold_tier", None, &[("tier", serde_json::json!("gold"))], 1.5),
+            rule("agent", Some("source:agent"), &[], 0.8),
+        ],
+    });
+    let after = recall(&engine);
+    assert_eq!(after[0].0, curated);
+    assert_eq!(after[0].2["ranking_rules"], serde_json::json!(["gold_tier"]));
+    assert_eq!(after[0].2["rule_multiplier"], 1.5);
+    assert!((score(&after, &curated) - score(&before, &curated) * 1.5).abs() < 1e-9);
+    assert!((score(&after, &agent) - score(&before, &agent) * 0.8).abs() < 1e-9);
+    assert_eq!(after[2].0, agent);
+    assert_eq!(score(&after, &plain), score(&before, &plain));
+
+    let invalid = RankingConfig { rules: vec![rule("", None, &[], 0.0), rule("a", Some("x:y"), &[], 1.0), rule("a", Some("x:y"), &[], 1.0)] };
+    assert_eq!(invalid.validate().len(), 4);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_ranking_routes() {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let (agent, curated, _) = populate(&project.main);
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+    let put = |body: Value| {
+        let request = client.put(format!("{}/ranking", base)).json(&body);
+        async move { request.send().await.unwrap() }
+    };
+
+    let response = put(serde_json::json!({"rules": [{"name": "boost", "multiplier": 2.0}]})).await;
+    assert_eq!(response.status(), 400);
+    assert!(project.ranking().rules.is_empty());
+
+    let response = put(serde_json::json!({"rules": [
+        {"name": "gold_tier", "metadata": {"tier": "gold"}, "multiplier": 1.5},
+        {"name": "agent", "cue": " Source:Agent ", "multiplier": 0.8}
+    ]}))
+    .await;
+    assert_eq!(response.status(), 200);
+    let ranking: Value = client.get(format!("{}/ranking", base)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(ranking["rules"][1]["cue"], "source:agent");
+    let config: Value = client.get(format!("{}/admin/config", base)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(config["project_overrides"]["default"]["ranking"]["rules"].as_array().unwrap().len(), 2);
+
+    let body: Value = client
+        .post(format!("{}/recall", base))
+        .json(&serde_json::json!({"cues": ["topic:refunds"], "explain": true, "disable_pattern_completion": true}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results[0]["memory_id"], curated.as_str());
+    assert_eq!(results[0]["explain"]["ranking_rules"], serde_json::json!(["gold_tier"]));
+    assert_eq!(results[2]["memory_id"], agent.as_str());
+    assert_eq!(results[2]["explain"]["ranking_rules"], serde_json::json!(["agent"]));
+}
+
+#[test]
+fn test_ranking_rules_disable_early_exit() {
+    let engine = CueMapEngine::new();
+    engine.set_approximate_early_exit(true);
+    let gold = HashMap::from([("tier".to_string(), serde_json::json!("gold"))]);
+    let curated = engine.add_memory("payments policy".to_string(), cues(&["service:payments"]), Some(gold), false);
+    let outage = engine.add_memory("payments outage".to_string(), cues(&["topic:outage", "service:payments"]), None, false);
+    engine.set_ranking(RankingConfig { rules: vec![rule("gold_tier", None, &[("tier", serde_json::json!("gold"))], 10.0)] });
+
+    // The outage memory matches both cues, which alone would end the scan
+    // before the boosted memory is ever seen
+    let query = vec![("topic:outage".to_string(), 1.0), ("service:payments".to_string(), 1.0)];
+    let recall = |limit: usize| {
+        engine
+            .recall_weighted(query.clone(), limit, false, None, false, true, false, false, None)
+            .into_iter()
+            .map(|result| result.memory_id)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(recall(10), vec![curated.clone(), outage]);
+    assert_eq!(recall(1), vec![curated]);
+}
@@ -0,0 +1,56 @@
+use crate::common::{self, cues};
+use cuemap_rust::config::RECALL_BATCH_MAX_QUERIES;
+use cuemap_rust::projects::ProjectContext;
+use serde_json::Value;
+use std::sync::Arc;
+
+fn ids(response: &Value) -> Vec<&str> {
+    response["results"].as_array().unwrap().iter().map(|r| r["memory_id"].as_str().unwrap()).collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_recall_batch() {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let timeout = project.main.add_memory("payments timed out".to_string(), cues(&["service:payments", "error:timeout"]), None, false);
+    let refund = project.main.add_memory("refund issued".to_string(), cues(&["service:payments"]), None, false);
+    let deploy = project.main.add_memory("search deployed".to_string(), cues(&["service:search"]), None, false);
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+    let batch = |body: Value| {
+        let request = client.post(format!("{}/recall/batch", base)).json(&body);
+        async move { request.send().await.unwrap() }
+    };
+
+    let payments = serde_json::json!({"cues": ["Service:Payments"], "disable_pattern_completion": true});
+    let single: Value = client.post(format!("{}/recall", base)).json(&payments).send().await.unwrap().json().await.unwrap();
+
+    let response = batch(serde_json::json!([
+        payments,
+        {"cues": ["service:search"], "explain": true},
+        {"cues": ["Service:Payments"], "limit": 1, "disable_pattern_completion": true},
+        {"cues": ["service:missing"]}
+    ]))
+    .await;
+    assert_eq!(response.status(), 200);
+    let body: Value = response.json().await.unwrap();
+    let responses = body["responses"].as_array().unwrap();
+    assert_eq!(responses.len(), 4);
+    assert_eq!(ids(&responses[0]), ids(&single));
+    assert_eq!(ids(&responses[0]), vec![refund.as_str(), timeout.as_str()]);
+    assert_eq!(ids(&responses[1]), vec![deploy.as_str()]);
+    assert_eq!(responses[1]["explain"]["normalized_query"], serde_json::json!(["service:search"]));
+    assert_eq!(ids(&responses[2]), vec![refund.as_str()]);
+    assert!(ids(&responses[3]).is_empty());
+    assert!(body["engine_latency"].as_f64().is_some());
+    for (index, response) in responses.iter().enumerate() {
+        assert_eq!(response["index"], index);
+        assert!(response["engine_latency"].as_f64().unwrap() <= body["engine_latency"].as_f64().unwrap());
+    }
+
+    assert_eq!(batch(serde_json::json!([])).await.json::<Value>().await.unwrap()["responses"], serde_json::json!([]));
+    let response = batch(serde_json::json!([{"cues": ["service:search"], "projects": ["other"]}])).await;
+    assert_eq!(response.status(), 400);
+    let response = batch(Value::Array(vec![serde_json::json!({"cues": ["service:search"]}); RECALL_BATCH_MAX_QUERIES + 1])).await;
+    assert_eq!(response.status(), 400);
+}
@@ -0,0 +1,88 @@
+use crate::common::{self, cues};
+use cuemap_rust::projects::{ProjectContext, RecallDefaults};
+use serde_json::Value;
+use std::sync::Arc;
+
+#[test]
+fn test_validate_recall_defaults() {
+    assert!(RecallDefaults::default().validate(1000).is_ok());
+    assert!(RecallDefaults { limit: Some(50), min_intersection: Some(2), ..Default::default() }.validate(1000).is_ok());
+    assert!(RecallDefaults { limit: Some(0), ..Default::default() }.validate(1000).is_err());
+    assert!(RecallDefaults { limit: Some(1001), ..Default::default() }.validate(1000).is_err());
+    assert!(RecallDefaults { min_intersection: Some(0), ..Default::default() }.validate(1000).is_err());
+    assert!(RecallDefaults { max_age_days: Some(-1.0), ..Default::default() }.validate(1000).is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_recall_defaults_route() {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    project.main.add_memory("payments timed out".to_string(), cues(&["service:payments", "error:timeout"]), None, false);
+    project.main.add_memory("refund issued".to_string(), cues(&["service:payments"]), None, false);
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+    let put = |body: Value| {
+        let request = client.put(format!("{}/recall/defaults", base)).json(&body);
+        async move { request.send().await.unwrap() }
+    };
+    let recall = |body: Value| {
+        let request = client.post(format!("{}/recall", base)).json(&body);
+        async move { request.send().await.unwrap().json::<Value>().await.unwrap() }
+    };
+
+    assert_eq!(put(serde_json::json!({"limit": 0})).await.status(), 400);
+    assert_eq!(project.recall_defaults(), RecallDefaults::default());
+
+    let response = put(serde_json::json!({"limit": 1, "explain": true})).await;
+    assert_eq!(response.status(), 200);
+    let defaults: Value = client.get(format!("{}/recall/defaults", base)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(defaults, serde_json::json!({"limit": 1, "explain": true}));
+    let config: Value = client.get(format!("{}/admin/config", base)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(config["project_overrides"]["default"]["recall"], serde_json::json!({"limit": 1, "explain": true}));
+
+    let body = recall(serde_json::json!({"cues": ["service:payments"], "disable_pattern_completion": true})).await;
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(results[0]["explain"].is_object());
+
+    // Values the request sets win over the project's
+    let body = recall(serde_json::json!({"cues": ["service:payments"], "limit": 10, "explain": false, "disable_pattern_completion": true})).await;
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].get("explain").is_none_or(Value::is_null));
+}
+
+#[tokio::test]
+async fn test_auto_reinforce_needs_write_scope() {
+    use cuemap_rust::api;
+    use cuemap_rust::auth::{AuthConfig, Scope};
+    use cuemap_rust::grounding::GroundingTraceStore;
+    use cuemap_rust::jobs::{JobQueue, SingleTenantProvider};
+    use std::collections::HashMap;
+
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let memory = project.main.add_memory("payments timed out".to_string(), cues(&["service:payments"]), None, false);
+    let job_queue = Arc::new(JobQueue::new(Arc::new(SingleTenantProvider { project: project.clone() })));
+    let traces = Arc::new(GroundingTraceStore::new(100, None));
+    let keys = HashMap::from([("read-key".to_string(), Scope::Read), ("write-key".to_string(), Scope::Write)]);
+    let base = common::serve(api::routes(project.clone(), job_queue, traces, AuthConfig::with_keys(keys), false)).await;
+    let client = reqwest::Client::new();
+    let recall = |key: &'static str, body: Value| {
+        let request = client.post(format!("{}/recall", base)).header("X-API-Key", key).json(&body);
+        async move { request.send().await.unwrap().status() }
+    };
+
+    let query = serde_json::json!({"cues": ["service:payments"]});
+    assert_eq!(recall("read-key", query.clone()).await, 200);
+    assert_eq!(recall("read-key", serde_json::json!({"cues": ["service:payments"], "auto_reinforce": true})).await, 403);
+
+    // A project default turning it on counts the same
+    project.set_recall_defaults(RecallDefaults { auto_reinforce: Some(true), ..Default::default() });
+    assert_eq!(recall("read-key", query.clone()).await, 403);
+    let batch = client.post(format!("{}/recall/batch", base)).header("X-API-Key", "read-key").json(&serde_json::json!([query])).send().await.unwrap();
+    assert_eq!(batch.status(), 403);
+    assert_eq!(project.main.get_memory(&memory).unwrap().reinforcement_count, 0);
+
+    assert_eq!(recall("write-key", query).await, 200);
+    assert_eq!(project.main.get_memory(&memory).unwrap().reinforcement_count, 1);
+}
@@ -0,0 +1,101 @@
+use crate::common;
+use cuemap_rust::api;
+use cuemap_rust::auth::{AuthConfig, Scope};
+use cuemap_rust::grounding::GroundingTraceStore;
+use cuemap_rust::jobs::{JobQueue, SingleTenantProvider};
+use cuemap_rust::projects::ProjectContext;
+use cuemap_rust::replication::Follower;
+use cuemap_rust::taxonomy::Taxonomy;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Serve a fresh single-tenant primary and return its project and base URL.
+async fn start_primary(auth: AuthConfig) -> (Arc<ProjectContext>, String) {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let job_queue = Arc::new(JobQueue::new(Arc::new(SingleTenantProvider { project: project.clone() })));
+    let traces = Arc::new(GroundingTraceStore::new(100, None));
+    let app = api::routes(project.clone(), job_queue, traces, auth, false);
+
+    (project, common::serve(app).await)
+}
+
+fn replica() -> Arc<ProjectContext> {
+    Arc::new(ProjectContext::new(Default::default(), Default::default()))
+}
+
+#[tokio::test]
+async fn test_follower_applies_primary_state() {
+    let (primary, url) = start_primary(AuthConfig::with_keys(HashMap::new())).await;
+    let mut metadata = HashMap::new();
+    metadata.insert("source".to_string(), serde_json::json!(["pager", 1]));
+    let kept = primary.main.add_memory("payments timed out".to_string(), vec!["service:payments".to_string()], Some(metadata), true);
+    let dropped = primary.main.add_memory("billing retry".to_string(), vec!["service:billing".to_string()], None, true);
+
+    let follower_project = replica();
+    let mut follower = Follower::new(&url, None, follower_project.clone());
+    let status = follower.status();
+    assert!(!status.is_synced());
+
+    assert_eq!(follower.sync().await, Ok(true));
+    assert!(status.is_synced());
+    let results = follower_project.main.recall(vec!["service:payments".to_string()], 10, false);
+    assert_eq!(results[0].memory_id, kept);
+    assert_eq!(results[0].metadata["source"], serde_json::json!(["pager", 1]));
+
+    // Unchanged primary: nothing is transferred
+    assert_eq!(follower.sync().await, Ok(false));
+
+    // Deletes, reinforcement and config changes all reach the follower
+    primary.main.delete_memory(&dropped);
+    primary.main.reinforce_memory(&kept, vec!["service:payments".to_string()]);
+    let mut taxonomy = Taxonomy::default();
+    taxonomy.allowed_keys.push("service".to_string());
+    primary.set_taxonomy(taxonomy);
+
+    assert_eq!(follower.sync().await, Ok(true));
+    assert!(follower_project.main.get_memory(&dropped).is_none());
+    assert!(follower_project.main.recall(vec!["service:billing".to_string()], 10, false).is_empty());
+    assert_eq!(follower_project.main.get_memory(&kept).unwrap().reinforcement_count, 1);
+    assert!(follower_project.taxonomy().allowed_keys.contains(&"service".to_string()));
+}
+
+#[tokio::test]
+async fn test_follower_uses_api_key() {
+    let mut keys = HashMap::new();
+    keys.insert("admin-key".to_string(), Scope::Admin);
+    keys.insert("read-key".to_string(), Scope::Read);
+    let (_, url) = start_primary(AuthConfig::with_keys(keys)).await;
+
+    let mut follower = Follower::new(&url, Some("read-key".to_string()), replica());
+    let err = follower.sync().await.unwrap_err();
+    assert!(err.contains("403"), "{}", err);
+    assert!(!follower.status().is_synced());
+    assert_eq!(follower.status().to_json()["last_error"], serde_json::json!(err));
+
+    let mut follower = Follower::new(&format!("{}/", url), Some("admin-key".to_string()), replica());
+    assert_eq!(follower.sync().await, Ok(true));
+}
+
+#[tokio::test]
+async fn test_follower_copies_aliases_and_lexicon() {
+    let (primary, url) = start_primary(AuthConfig::with_keys(HashMap::new())).await;
+    let memory = primary.main.add_memory("payments timed out".to_string(), vec!["service:payments".to_string()], None, true);
+    primary.lexicon.add_memory("service:payments".to_string(), vec!["tok:payments".to_string()], None, true);
+    primary.aliases.add_memory("service:payments".to_string(), vec!["service:pay".to_string()], None, true);
+
+    let follower_project = replica();
+    let mut follower = Follower::new(&url, None, follower_project.clone());
+    assert_eq!(follower.sync().await, Ok(true));
+    assert_eq!(follower_project.lexicon.recall(vec!["tok:payments".to_string()], 10, false).len(), 1);
+    assert_eq!(follower_project.aliases.recall(vec!["service:pay".to_string()], 10, false).len(), 1);
+
+    // Lexicon training alone changes the tag
+    primary.lexicon.add_memory("service:billing".to_string(), vec!["tok:billing".to_string()], None, true);
+    assert_eq!(follower.sync().await, Ok(true));
+    assert_eq!(follower_project.lexicon.recall(vec!["tok:billing".to_string()], 10, false).len(), 1);
+
+    // So does reinforcement of the main engine
+    primary.main.reinforce_memory(&memory, vec!["service:payments".to_string()]);
+    assert_eq!(follower.sync().await, Ok(true));
+    assert_eq!(follower.sync().await, Ok(false));
+}
@@ -0,0 +1,57 @@
+use crate::common::{self, cues};
+use cuemap_rust::projects::ProjectContext;
+use cuemap_rust::result_fields::ResultFields;
+use serde_json::Value;
+use std::sync::Arc;
+
+#[test]
+fn test_parse_fields() {
+    let fields = ResultFields::parse(" id, score,cues,id ").unwrap();
+    assert!(fields.contains("memory_id") && fields.contains("score") && fields.contains("cues"));
+    assert!(!fields.contains("content"));
+    assert_eq!(fields, ResultFields::parse("memory_id,cues,score").unwrap());
+    assert!(ResultFields::parse("id,body").unwrap_err().contains("body"));
+    assert!(ResultFields::parse(" , ").is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_recall_fields() {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let id = project.main.add_memory("x".repeat(10_000), cues(&["service:payments", "error:timeout"]), None, false);
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+    let post = |path: &str, body: Value| {
+        let request = client.post(format!("{}{}", base, path)).json(&body);
+        async move { request.send().await.unwrap() }
+    };
+
+    let body: Value = post("/recall", serde_json::json!({"cues": ["service:payments"], "fields": "id,score,cues"}))
+        .await
+        .json()
+        .await
+        .unwrap();
+    let result = body["results"][0].as_object().unwrap();
+    assert_eq!(result.keys().collect::<Vec<_>>(), vec!["cues", "memory_id", "score"]);
+    assert_eq!(result["memory_id"], id.as_str());
+    assert_eq!(result["cues"], serde_json::json!(["service:payments", "error:timeout"]));
+
+    // Unselected explain output is dropped along with the rest
+    let body: Value = post("/recall", serde_json::json!({"cues": ["service:payments"], "fields": "content", "explain": true}))
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["results"][0].as_object().unwrap().len(), 1);
+    assert!(body["explain"].is_object());
+
+    let body: Value = post("/recall/batch", serde_json::json!([{"cues": ["service:payments"], "fields": "score"}, {"cues": ["service:payments"]}]))
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["responses"][0]["results"][0].as_object().unwrap().len(), 1);
+    assert_eq!(body["responses"][1]["results"][0]["content"].as_str().unwrap().len(), 10_000);
+
+    assert_eq!(post("/recall", serde_json::json!({"cues": ["service:payments"], "fields": "id,body"})).await.status(), 400);
+}
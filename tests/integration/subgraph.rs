@@ -0,0 +1,65 @@
+use crate::common;
+use cuemap_rust::engine::CueMapEngine;
+use cuemap_rust::projects::ProjectContext;
+use cuemap_rust::subgraph::{extract, SubgraphNode};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn memory_depths(nodes: &[SubgraphNode]) -> HashMap<String, usize> {
+    nodes
+        .iter()
+        .filter_map(|node| match node {
+            SubgraphNode::Memory { memory_id, depth, .. } => Some((memory_id.clone(), *depth)),
+            SubgraphNode::Cue { .. } => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_subgraph_hops() {
+    let engine = CueMapEngine::new();
+    let cues = |cues: &[&str]| cues.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+    let refund = engine.add_memory("refunds call the ledger".to_string(), cues(&["service:payments", "service:ledger"]), None, true);
+    let ledger = engine.add_memory("ledger runs on postgres".to_string(), cues(&["service:ledger", "db:postgres"]), None, true);
+    let backup = engine.add_memory("postgres backups run nightly".to_string(), cues(&["db:postgres"]), None, true);
+    engine.add_memory("unrelated".to_string(), cues(&["service:search"]), None, true);
+
+    assert!(extract(&engine, "service:billing", 1).is_none());
+
+    let seed_only = extract(&engine, "Service:Payments", 0).unwrap();
+    assert_eq!(seed_only.cue, "service:payments");
+    assert_eq!(memory_depths(&seed_only.nodes), HashMap::from([(refund.clone(), 0)]));
+    assert_eq!(seed_only.nodes.len(), 2);
+    assert_eq!(seed_only.edges.len(), 1);
+
+    let two_hops = extract(&engine, "service:payments", 2).unwrap();
+    assert_eq!(memory_depths(&two_hops.nodes), HashMap::from([(refund, 0), (ledger.clone(), 1), (backup.clone(), 2)]));
+    // payments, ledger and postgres cues; one edge per memory-cue pair
+    assert_eq!(two_hops.nodes.len(), 6);
+    assert_eq!(two_hops.edges.len(), 5);
+    assert!(!two_hops.truncated);
+    assert!(two_hops.edges.iter().any(|e| e.source == format!("memory:{}", backup) && e.target == "cue:db:postgres"));
+}
+
+#[tokio::test]
+async fn test_subgraph_route() {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    project.main.add_memory("refunds call the ledger".to_string(), vec!["service:payments".to_string(), "service:ledger".to_string()], None, true);
+    project.main.add_memory("ledger runs on postgres".to_string(), vec!["service:ledger".to_string()], None, true);
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+
+    let export = |query: &str| client.get(format!("{}/export/subgraph?{}", base, query)).send();
+    let response = export("cue=service:payments&depth=1").await.unwrap();
+    assert_eq!(response.status(), 200);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["depth"], 1);
+    assert_eq!(body["nodes"].as_array().unwrap().len(), 4);
+    assert_eq!(body["edges"].as_array().unwrap().len(), 3);
+    assert_eq!(body["nodes"][0], serde_json::json!({"kind": "cue", "id": "cue:service:payments", "cue": "service:payments", "depth": 0}));
+
+    assert_eq!(export("cue=service:billing").await.unwrap().status(), 404);
+    assert_eq!(export("cue=service:payments&depth=99").await.unwrap().status(), 400);
+}
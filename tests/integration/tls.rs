@@ -0,0 +1,15 @@
+use cuemap_rust::tls::server_config;
+use std::path::Path;
+
+#[test]
+fn test_tls_config_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let empty = dir.path().join("empty.pem");
+    std::fs::write(&empty, "").unwrap();
+
+    let missing = server_config(Path::new("/nonexistent/cert.pem"), &empty, None).unwrap_err();
+    assert!(missing.contains("Failed to open"));
+
+    let no_certs = server_config(&empty, &empty, None).unwrap_err();
+    assert!(no_certs.contains("No certificates found"));
+}
@@ -0,0 +1,79 @@
+use crate::common::{self, cues};
+use cuemap_rust::engine::{CueMapEngine, CuePosting};
+use cuemap_rust::projects::ProjectContext;
+use cuemap_rust::structures::OrderedSet;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn posting(cue: &str, memory_id: &str) -> CuePosting {
+    CuePosting { cue: cue.to_string(), memory_id: memory_id.to_string() }
+}
+
+/// Break the index of `engine` in every way `verify_index` reports: `orphan`
+/// drops out of `service:payments`, `stray` is listed under a cue it does
+/// not hold, an unknown handle is listed and a stale id is interned.
+fn corrupt(engine: &CueMapEngine, orphan: &str, stray: &str) {
+    let ids = engine.get_id_table();
+    let index = engine.get_cue_index();
+    index.get_mut("service:payments").unwrap().remove(ids.get(orphan).unwrap());
+    let mut set = OrderedSet::new();
+    set.add(ids.get(stray).unwrap());
+    set.add(u64::MAX);
+    index.insert(Arc::from("topic:search"), set);
+    ids.intern("ghost");
+}
+
+#[test]
+fn test_verify_and_rebuild_index() {
+    let engine = CueMapEngine::new();
+    let orphan = engine.add_memory("checkout failed".to_string(), cues(&["service:payments", "type:error"]), None, false);
+    std::thread::sleep(Duration::from_millis(5));
+    let recent = engine.add_memory("refund issued".to_string(), cues(&["service:payments"]), None, false);
+    assert!(engine.verify_index().is_consistent());
+
+    corrupt(&engine, &orphan, &recent);
+    let report = engine.verify_index();
+    assert!(!report.is_consistent());
+    assert_eq!(report.orphans, vec![posting("service:payments", &orphan)]);
+    assert_eq!(report.stray, vec![posting("topic:search", &recent)]);
+    assert_eq!(report.dangling, vec![posting("topic:search", &format!("#{}", u64::MAX))]);
+    assert_eq!(report.stale_ids, vec!["ghost".to_string()]);
+    assert_eq!(engine.check_index_consistency().len(), 4);
+
+    assert_eq!(engine.rebuild_cue_index(), report);
+    assert!(engine.verify_index().is_consistent());
+    assert!(!engine.has_cue("topic:search"));
+    // The restored entry ranks least recent
+    assert_eq!(engine.cue_memory_ids("service:payments", None), vec![recent, orphan]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_verify_route() {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let orphan = project.main.add_memory("checkout failed".to_string(), cues(&["service:payments"]), None, false);
+    let stray = project.main.add_memory("refund issued".to_string(), cues(&["service:payments"]), None, false);
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+    let verify = |query: &'static str| {
+        let request = client.post(format!("{}/admin/verify{}", base, query));
+        async move { request.send().await.unwrap().json::<Value>().await.unwrap() }
+    };
+
+    assert_eq!(verify("").await["consistent"], true);
+    corrupt(&project.main, &orphan, &stray);
+
+    let body = verify("").await;
+    assert_eq!(body["consistent"], false);
+    assert_eq!(body["report"]["rebuilt"], false);
+    assert_eq!(body["report"]["main"]["orphans"][0]["memory_id"], orphan.as_str());
+    assert_eq!(body["report"]["main"]["dangling"].as_array().unwrap().len(), 1);
+    assert_eq!(body["report"]["lexicon"]["orphans"].as_array().unwrap().len(), 0);
+
+    let body = verify("?rebuild=true").await;
+    assert_eq!(body["consistent"], false);
+    assert_eq!(body["report"]["rebuilt"], true);
+    assert_eq!(verify("").await["consistent"], true);
+    assert_eq!(project.main.cue_memory_ids("service:payments", None).len(), 2);
+}
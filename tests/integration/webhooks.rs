@@ -0,0 +1,179 @@
+use crate::common;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use cuemap_rust::projects::ProjectContext;
+use cuemap_rust::webhooks::{self, DeliveryStatus, WebhookHub};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// A webhook receiver that answers `503` to the first `failures` requests and
+/// forwards every accepted request as (headers, body).
+async fn start_receiver(failures: usize) -> (String, mpsc::UnboundedReceiver<(HeaderMap, String)>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let seen = Arc::new(AtomicUsize::new(0));
+    let app = Router::new().route(
+        "/hook",
+        post(move |headers: HeaderMap, body: String| {
+            let tx = tx.clone();
+            let seen = seen.clone();
+            async move {
+                if seen.fetch_add(1, Ordering::SeqCst) < failures {
+                    return StatusCode::SERVICE_UNAVAILABLE;
+                }
+                let _ = tx.send((headers, body));
+                StatusCode::OK
+            }
+        }),
+    );
+    (format!("{}/hook", common::serve(app).await), rx)
+}
+
+async fn next_payload(rx: &mut mpsc::UnboundedReceiver<(HeaderMap, String)>) -> (HeaderMap, serde_json::Value, String) {
+    let (headers, body) = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap();
+    let payload = serde_json::from_str(&body).unwrap();
+    (headers, payload, body)
+}
+
+fn project() -> Arc<ProjectContext> {
+    Arc::new(ProjectContext::new(Default::default(), Default::default()))
+}
+
+#[tokio::test]
+async fn test_webhook_delivers_signed_events() {
+    let (url, mut rx) = start_receiver(0).await;
+    let hub = Arc::new(WebhookHub::default());
+    let project = project();
+    let events = vec!["memory_added".to_string(), "memory_merged".to_string(), "alias_approved".to_string()];
+    let (hook, secret) = hub.create("default", &project, url, events, Some("s3cret".to_string())).unwrap();
+    assert_eq!(secret, "s3cret");
+
+    let id = project.main.add_memory("payments timed out".to_string(), vec!["service:payments".to_string()], None, true);
+    let (headers, payload, body) = next_payload(&mut rx).await;
+    assert_eq!(headers[webhooks::EVENT_HEADER], "memory_added");
+    assert_eq!(headers[webhooks::SIGNATURE_HEADER], format!("sha256={}", webhooks::sign("s3cret", body.as_bytes())));
+    assert_eq!(headers[webhooks::DELIVERY_HEADER], payload["id"].as_str().unwrap());
+    assert_eq!(payload["project"], "default");
+    assert_eq!(payload["webhook_id"], serde_json::json!(hook.id));
+    assert_eq!(payload["data"]["memory_id"], serde_json::json!(id));
+    assert_eq!(payload["data"]["content"], "payments timed out");
+
+    // Unsubscribed events are not sent; proposed aliases are not approved ones
+    project.main.delete_memory(&id);
+    project.aliases.upsert_memory_with_id(
+        "proposed".to_string(),
+        "{}".to_string(),
+        vec!["type:alias".to_string(), "status:proposed".to_string()],
+        None,
+        false,
+    );
+    project.aliases.upsert_memory_with_id(
+        "approved".to_string(),
+        "{}".to_string(),
+        vec![
+            "type:alias".to_string(),
+            "from:svc:payments".to_string(),
+            "to:service:payments".to_string(),
+            "status:active".to_string(),
+        ],
+        None,
+        false,
+    );
+    let (_, payload, _) = next_payload(&mut rx).await;
+    assert_eq!(payload["event"], "alias_approved");
+    assert_eq!(payload["data"]["alias_id"], "approved");
+    assert_eq!(payload["data"]["from"], "svc:payments");
+    assert_eq!(payload["data"]["to"], "service:payments");
+
+    let first = project.main.add_memory("a".to_string(), vec!["x".to_string(), "y".to_string()], None, true);
+    let second = project.main.add_memory("b".to_string(), vec!["x".to_string(), "y".to_string()], None, true);
+    next_payload(&mut rx).await;
+    next_payload(&mut rx).await;
+    let merged = project.main.consolidate_memories(0.9);
+    assert_eq!(merged.len(), 1);
+    // The summary is added and then reported as a merge; deliveries run concurrently
+    let (_, a, _) = next_payload(&mut rx).await;
+    let (_, b, _) = next_payload(&mut rx).await;
+    let payload = if a["event"] == "memory_merged" { a } else { b };
+    assert_eq!(payload["event"], "memory_merged");
+    assert_eq!(payload["data"]["memory_id"], serde_json::json!(merged[0].0));
+    let mut sources: Vec<String> = serde_json::from_value(payload["data"]["sources"].clone()).unwrap();
+    sources.sort();
+    let mut expected = vec![first, second];
+    expected.sort();
+    assert_eq!(sources, expected);
+}
+
+#[tokio::test]
+async fn test_webhook_retries_failed_deliveries() {
+    let (url, mut rx) = start_receiver(2).await;
+    let hub = Arc::new(WebhookHub::default().with_retry_base(Duration::from_millis(10)));
+    let project = project();
+    let (hook, secret) = hub.create("default", &project, url, Vec::new(), None).unwrap();
+    assert!(!secret.is_empty());
+
+    project.main.add_memory("flaky".to_string(), vec!["a".to_string()], None, true);
+    next_payload(&mut rx).await;
+
+    // The delivery is recorded once the receiver's answer is processed
+    let mut deliveries = Vec::new();
+    for _ in 0..50 {
+        deliveries = hub.deliveries("default", &hook.id).unwrap();
+        if deliveries[0].status != DeliveryStatus::Pending {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(deliveries.len(), 1);
+    assert_eq!(deliveries[0].status, DeliveryStatus::Delivered);
+    assert_eq!(deliveries[0].attempts, 3);
+    assert_eq!(deliveries[0].response_status, Some(200));
+
+    assert!(hub.deliveries("other", &hook.id).is_none());
+}
+
+#[tokio::test]
+async fn test_webhook_endpoints() {
+    let project = project();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(webhooks::WEBHOOKS_FILE);
+    let hub = Arc::new(WebhookHub::open(&path));
+    let app = common::router(&project).layer(axum::Extension(hub));
+    let base = format!("{}/admin/webhooks", common::serve(app).await);
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(&base)
+        .json(&serde_json::json!({"url": "http://localhost:9/hook", "events": ["memory_reinforced"]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+
+    let response = client
+        .post(&base)
+        .json(&serde_json::json!({"url": "http://localhost:9/hook", "events": ["memory_deleted"]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 201);
+    let created: serde_json::Value = response.json().await.unwrap();
+    let id = created["id"].as_str().unwrap().to_string();
+    assert!(created["secret"].is_string());
+
+    // Subscriptions persist without exposing the secret through the API
+    assert_eq!(WebhookHub::open(&path).list("default").len(), 1);
+    let listed: serde_json::Value = client.get(&base).send().await.unwrap().json().await.unwrap();
+    assert_eq!(listed["webhooks"][0]["id"], serde_json::json!(id));
+    assert!(listed["webhooks"][0].get("secret").is_none());
+
+    let deliveries: serde_json::Value = client.get(format!("{}/{}/deliveries", base, id)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(deliveries["deliveries"], serde_json::json!([]));
+
+    assert_eq!(client.delete(format!("{}/{}", base, id)).send().await.unwrap().status(), 200);
+    assert_eq!(client.delete(format!("{}/{}", base, id)).send().await.unwrap().status(), 404);
+    assert_eq!(client.get(format!("{}/{}/deliveries", base, id)).send().await.unwrap().status(), 404);
+    assert!(WebhookHub::open(&path).is_empty());
+}
@@ -13,3 +13,148 @@ fn test_lexicon_trainability() {
     assert!(!is_lexicon_trainable("source:agent"));
     assert!(!is_lexicon_trainable("file:/tmp/foo"));
 }
+
+#[tokio::test]
+async fn test_job_queue_running() {
+    let project = std::sync::Arc::new(cuemap_rust::projects::ProjectContext::new(Default::default(), Default::default()));
+    let queue = JobQueue::new(std::sync::Arc::new(SingleTenantProvider { project }));
+    assert!(queue.is_running());
+
+    queue.enqueue(Job::ProposeAliases { project_id: "default".to_string() }).await;
+    assert!(queue.is_running());
+    assert_eq!(Job::ProposeAliases { project_id: "default".to_string() }.kind(), "propose_aliases");
+}
+
+#[tokio::test]
+async fn test_job_completion_is_published() {
+    use cuemap_rust::events::ChangeEvent;
+
+    let project = std::sync::Arc::new(cuemap_rust::projects::ProjectContext::new(Default::default(), Default::default()));
+    let queue = JobQueue::new(std::sync::Arc::new(SingleTenantProvider { project: project.clone() }));
+    let memory_id = project.main.add_memory("payments".to_string(), vec!["service:payments".to_string()], None, true);
+    let mut events = project.main.events().subscribe();
+
+    queue.enqueue(Job::TrainLexiconFromMemory { project_id: "default".to_string(), memory_id: memory_id.clone() }).await;
+    let record = tokio::time::timeout(std::time::Duration::from_secs(5), events.recv()).await.unwrap().unwrap();
+    assert_eq!(
+        record.event,
+        ChangeEvent::JobCompleted { job: "train_lexicon_from_memory".to_string(), memory_id: Some(memory_id) }
+    );
+}
+
+#[tokio::test]
+async fn test_shutdown_drains_queued_jobs() {
+    use cuemap_rust::events::ChangeEvent;
+
+    let project = std::sync::Arc::new(cuemap_rust::projects::ProjectContext::new(Default::default(), Default::default()));
+    let queue = JobQueue::new(std::sync::Arc::new(SingleTenantProvider { project: project.clone() }));
+    let mut events = project.main.events().subscribe();
+    for _ in 0..3 {
+        queue.enqueue(Job::ProposeAliases { project_id: "default".to_string() }).await;
+    }
+
+    assert!(queue.shutdown(std::time::Duration::from_secs(5)).await);
+    assert!(!queue.is_running());
+    for _ in 0..3 {
+        let record = events.try_recv().unwrap();
+        assert!(matches!(record.event, ChangeEvent::JobCompleted { .. }));
+    }
+
+    // A second call has nothing left to wait for
+    assert!(queue.shutdown(std::time::Duration::from_secs(1)).await);
+}
+
+#[tokio::test]
+async fn test_maintenance_job() {
+    use cuemap_rust::events::ChangeEvent;
+
+    let project = std::sync::Arc::new(cuemap_rust::projects::ProjectContext::new(Default::default(), Default::default()));
+    let queue = JobQueue::new(std::sync::Arc::new(SingleTenantProvider { project: project.clone() }));
+    let memory_id = project.main.add_memory("payments".to_string(), vec!["service:payments".to_string()], None, true);
+    for _ in 0..4 {
+        project.main.reinforce_memory(&memory_id, Vec::new());
+    }
+    let gone = project.main.add_memory("gone".to_string(), vec!["service:gone".to_string()], None, true);
+    project.main.delete_memory(&gone);
+    let mut events = project.main.events().subscribe();
+
+    queue.enqueue(Job::Maintenance { project_id: "default".to_string(), decay_halvings: 1 }).await;
+    let record = tokio::time::timeout(std::time::Duration::from_secs(5), events.recv()).await.unwrap().unwrap();
+    assert_eq!(record.event, ChangeEvent::JobCompleted { job: "maintenance".to_string(), memory_id: None });
+
+    let memory = project.main.get_memory(&memory_id).unwrap();
+    assert_eq!(memory.reinforcement_count, 2);
+    // Freshly accessed memories are not stale
+    assert!(memory.salience > 0.0);
+    assert!(!project.main.has_cue("service:gone"));
+    assert!(project.main.has_cue("service:payments"));
+}
+
+#[tokio::test]
+async fn test_rebuild_lexicon_job() {
+    let project = std::sync::Arc::new(cuemap_rust::projects::ProjectContext::new(Default::default(), Default::default()));
+    let queue = JobQueue::new(std::sync::Arc::new(SingleTenantProvider { project: project.clone() }));
+    project.lexicon.upsert_memory_with_id("cue:team:gone".to_string(), "team:gone".to_string(), vec!["tok:payments".to_string()], None, false);
+    for i in 0..3 {
+        project.main.add_memory(format!("payments gateway timeout {}", i), vec!["service:payments".to_string(), "path:/tmp/log".to_string()], None, false);
+    }
+
+    assert!(project.queue_lexicon_rebuild());
+    assert!(!project.queue_lexicon_rebuild());
+    queue.enqueue(Job::RebuildLexicon { project_id: "default".to_string() }).await;
+    assert!(queue.shutdown(std::time::Duration::from_secs(5)).await);
+
+    let rebuild = project.lexicon_rebuild().unwrap();
+    assert_eq!((rebuild.state, rebuild.total, rebuild.processed), ("completed", 3, 3));
+    assert!(rebuild.finished_at.is_some());
+    assert!(project.lexicon.get_memory("cue:team:gone").is_none());
+    assert!(project.lexicon.get_memory("cue:path:/tmp/log").is_none());
+    assert!(project.resolve_cues_from_text("gateway timeout").contains(&"service:payments".to_string()));
+    assert!(project.queue_lexicon_rebuild());
+}
+
+/// Run a `StaleDigest` job; returns the digest memory and the memories it lists.
+async fn run_stale_digest(
+    queue: &JobQueue,
+    events: &mut tokio::sync::broadcast::Receiver<cuemap_rust::events::ChangeRecord>,
+) -> (String, Vec<String>) {
+    queue.enqueue(Job::StaleDigest { project_id: "default".to_string() }).await;
+    loop {
+        let record = tokio::time::timeout(std::time::Duration::from_secs(5), events.recv()).await.unwrap().unwrap();
+        if let cuemap_rust::events::ChangeEvent::StaleDigest { memory_id, memory_ids } = record.event {
+            return (memory_id, memory_ids);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_stale_digest_job() {
+    use cuemap_rust::digest::{StaleDigest, DIGEST_CUE, DIGEST_METADATA_KEY};
+
+    let project = std::sync::Arc::new(cuemap_rust::projects::ProjectContext::new(Default::default(), Default::default()));
+    let queue = JobQueue::new(std::sync::Arc::new(SingleTenantProvider { project: project.clone() }));
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs_f64();
+    let add_idle = |content: &str, idle_days: f64| {
+        let id = project.main.add_memory(content.to_string(), vec!["service:payments".to_string()], None, true);
+        project.main.get_memories().get_mut(&id).unwrap().last_accessed = now - idle_days * 86_400.0;
+        id
+    };
+    let stale = add_idle("payments runbook", 120.0);
+    let nearing = add_idle("payments escalation contacts", 85.0);
+    add_idle("payments dashboard link", 10.0);
+    let mut events = project.main.events().subscribe();
+
+    let (first, listed) = run_stale_digest(&queue, &mut events).await;
+    assert_eq!(listed, vec![stale.clone(), nearing.clone()]);
+
+    let memory = project.main.get_memory(&first).unwrap();
+    assert!(memory.content.contains(&stale));
+    let digest: StaleDigest = serde_json::from_value(memory.metadata[DIGEST_METADATA_KEY].clone()).unwrap();
+    assert_eq!(digest.stale_count(), 1);
+    assert!(digest.entries[0].stale && !digest.entries[1].stale);
+
+    // A later run replaces the digest instead of piling them up
+    let (second, _) = run_stale_digest(&queue, &mut events).await;
+    assert_ne!(second, first);
+    assert_eq!(project.main.cue_memory_ids(DIGEST_CUE, None), vec![second]);
+}
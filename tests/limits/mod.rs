@@ -0,0 +1,123 @@
+#[path = "../common/mod.rs"]
+mod common;
+
+use cuemap_rust::config::{self, RequestLimits};
+use cuemap_rust::limits::{with_budget, RouteBudget};
+use cuemap_rust::projects::ProjectContext;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn test_limits_validate() {
+    assert!(RequestLimits::default().validate().is_ok());
+    let no_cues = RequestLimits { max_cues_per_memory: 0, ..Default::default() };
+    assert!(no_cues.validate().is_err());
+    let content_over_body = RequestLimits { max_body_bytes: 1_000, max_content_bytes: 2_000, ..Default::default() };
+    assert!(content_over_body.validate().is_err());
+}
+
+#[tokio::test]
+async fn test_router_enforces_limits() {
+    // Limits are process-wide; this test binary is the only one setting them
+    config::set_request_limits(RequestLimits {
+        max_body_bytes: 4_096,
+        max_content_bytes: 1_024,
+        max_cues_per_memory: 3,
+        max_recall_limit: 50,
+    })
+    .unwrap();
+
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+    let add = |body: serde_json::Value| client.post(format!("{}/memories", base)).json(&body).send();
+
+    let response = add(serde_json::json!({"content": "INC-1", "cues": ["service:auth"]})).await.unwrap();
+    assert_eq!(response.status(), 200);
+
+    let response = add(serde_json::json!({"content": "x".repeat(8_000), "cues": ["service:auth"]})).await.unwrap();
+    assert_eq!(response.status(), 413);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "payload_too_large");
+
+    let response = add(serde_json::json!({"content": "x".repeat(2_000), "cues": ["service:auth"]})).await.unwrap();
+    assert_eq!(response.status(), 413);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert!(body["detail"].as_str().unwrap().starts_with("content is 2000 bytes"));
+
+    let response = add(serde_json::json!({"content": "INC-2", "cues": ["a:1", "b:2", "c:3", "d:4"]})).await.unwrap();
+    assert_eq!(response.status(), 400);
+    assert_eq!(project.main.get_stats()["total_memories"], 1);
+
+    let recall = |limit: usize| {
+        client
+            .post(format!("{}/recall", base))
+            .json(&serde_json::json!({"cues": ["service:auth"], "limit": limit}))
+            .send()
+    };
+    assert_eq!(recall(50).await.unwrap().status(), 200);
+    let response = recall(51).await.unwrap();
+    assert_eq!(response.status(), 400);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["detail"], "limit 51 is over the maximum of 50");
+}
+
+#[tokio::test]
+async fn test_route_budget() {
+    let budget = RouteBudget { timeout: Duration::from_millis(200), max_concurrent: 1 };
+    let slow = |ms: u64| async move {
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+        "done"
+    };
+    let app = axum::Router::new()
+        .route("/fast", with_budget(axum::routing::get(move || slow(100)), budget))
+        .route("/slow", with_budget(axum::routing::get(move || slow(1_000)), budget))
+        // Build the routes once, as the API routers do, so requests share the budget
+        .with_state(());
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+
+    let response = client.get(format!("{}/slow", base)).send().await.unwrap();
+    assert_eq!(response.status(), 408);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "timeout");
+
+    // A second request while the first is in flight is shed, not queued
+    let (first, second) = tokio::join!(client.get(format!("{}/fast", base)).send(), async {
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        client.get(format!("{}/fast", base)).send().await
+    });
+    assert_eq!(first.unwrap().status(), 200);
+    let second = second.unwrap();
+    assert_eq!(second.status(), 503);
+    let body: serde_json::Value = second.json().await.unwrap();
+    assert_eq!(body["code"], "unavailable");
+    assert_eq!(client.get(format!("{}/fast", base)).send().await.unwrap().status(), 200);
+}
+
+#[tokio::test]
+async fn test_route_budget_times_out_cpu_bound_work() {
+    let budget = RouteBudget { timeout: Duration::from_millis(200), max_concurrent: 1 };
+    // Busy work that never yields, run off the async workers as the engine's is
+    let busy = || async {
+        tokio::task::spawn_blocking(|| {
+            let start = std::time::Instant::now();
+            let mut spins = 0u64;
+            while start.elapsed() < Duration::from_millis(1_500) {
+                spins = std::hint::black_box(spins.wrapping_add(1));
+            }
+            spins
+        })
+        .await
+        .unwrap()
+        .to_string()
+    };
+    let app = axum::Router::new().route("/busy", with_budget(axum::routing::get(busy), budget)).with_state(());
+    let base = common::serve(app).await;
+
+    let start = std::time::Instant::now();
+    let response = reqwest::get(format!("{}/busy", base)).await.unwrap();
+    assert_eq!(response.status(), 408);
+    assert!(start.elapsed() < Duration::from_millis(1_000));
+}
@@ -0,0 +1,124 @@
+use crate::common::{self, MockOllama};
+use cuemap_rust::agent::cue_policy::CuePolicy;
+use cuemap_rust::agent::errors::{FileErrors, IngestErrors, IngestStage};
+use cuemap_rust::agent::ingester::Ingester;
+use cuemap_rust::agent::{AgentConfig, AgentHandle};
+use cuemap_rust::api;
+use cuemap_rust::auth::AuthConfig;
+use cuemap_rust::grounding::GroundingTraceStore;
+use cuemap_rust::jobs::{JobQueue, SingleTenantProvider};
+use cuemap_rust::llm::LlmConfig;
+use cuemap_rust::projects::ProjectContext;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+fn file_errors(errors: &IngestErrors, path: &Path) -> Option<FileErrors> {
+    errors.files().into_iter().find(|file| file.path == path)
+}
+
+/// Wait for the extraction jobs of a scan to record `failures` for `path`.
+async fn wait_for(errors: &IngestErrors, path: &Path, failures: Option<u32>) {
+    for _ in 0..100 {
+        if file_errors(errors, path).map(|file| file.failures) == failures {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("{:?} has {:?}, expected {:?} failures", path, file_errors(errors, path), failures);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_ingest_errors_and_quarantine() {
+    let (ollama, _) = MockOllama::generate(|prompt| {
+        // Chunks saying FAIL fail extraction
+        (!prompt.contains("FAIL")).then(|| common::extraction("Refunds take five days", &["topic:refunds"]))
+    })
+    .start()
+    .await;
+    let _llm = common::use_mock_llm(&ollama).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let good = dir.path().join("good.txt");
+    let bad = dir.path().join("bad.txt");
+    let blob = dir.path().join("blob.bin");
+    std::fs::write(&good, "Refunds take five days.").unwrap();
+    std::fs::write(&bad, "FAIL to extract this.").unwrap();
+    std::fs::write(&blob, [0xff, 0xfe, 0x00, 0x01]).unwrap();
+
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let job_queue = Arc::new(JobQueue::new(Arc::new(SingleTenantProvider { project: project.clone() })));
+    let config = AgentConfig {
+        watch_dir: dir.path().to_string_lossy().to_string(),
+        throttle_ms: 0,
+        llm: LlmConfig::from_env().unwrap(),
+        cue_policy: CuePolicy::default(),
+    };
+    let ingester = Arc::new(Mutex::new(Ingester::new(config, job_queue.clone())));
+    let errors = ingester.lock().await.errors();
+
+    // Failing files are retried on every scan, unchanged or not
+    for failures in 1..=3 {
+        ingester.lock().await.scan_all().await.unwrap();
+        wait_for(&errors, &bad, Some(failures)).await;
+        assert_eq!(file_errors(&errors, &blob).unwrap().failures, failures);
+    }
+    let failing = file_errors(&errors, &bad).unwrap();
+    assert!(failing.quarantined);
+    assert_eq!(failing.errors.len(), 3);
+    assert_eq!(failing.errors[0].stage, IngestStage::Extract);
+    assert!(failing.errors[0].memory_id.as_deref().unwrap().starts_with("file:"));
+    let unparsable = file_errors(&errors, &blob).unwrap();
+    assert!(unparsable.quarantined);
+    assert_eq!(unparsable.errors[0].stage, IngestStage::Parse);
+    assert!(file_errors(&errors, &good).is_none());
+
+    // Quarantined files are skipped until they change
+    ingester.lock().await.scan_all().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(file_errors(&errors, &bad).unwrap().errors.len(), 3);
+    std::fs::write(&bad, "Refunds are fixed now.").unwrap();
+    ingester.lock().await.scan_all().await.unwrap();
+    wait_for(&errors, &bad, None).await;
+    assert_eq!(project.main.cue_memory_ids(&format!("path:{}", bad.to_string_lossy().to_lowercase()), None).len(), 1);
+
+    // Unreadable files are recorded too
+    let folder = dir.path().join("folder");
+    std::fs::create_dir(&folder).unwrap();
+    assert!(ingester.lock().await.process_file_path(folder.clone()).await.is_err());
+    assert_eq!(file_errors(&errors, &folder).unwrap().errors[0].stage, IngestStage::Read);
+
+    let traces = Arc::new(GroundingTraceStore::new(100, None));
+    let app = api::routes(project.clone(), job_queue, traces, AuthConfig::with_keys(HashMap::new()), false)
+        .layer(axum::Extension(AgentHandle::new(ingester.clone()).await));
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+
+    let body: Value = client.get(format!("{}/agent/errors", base)).send().await.unwrap().json().await.unwrap();
+    let files = body["files"].as_array().unwrap();
+    assert_eq!(files.len(), 2);
+    let listed = files.iter().find(|file| file["path"] == blob.to_string_lossy().as_ref()).unwrap();
+    assert_eq!(listed["quarantined"], true);
+    assert_eq!(listed["failures"], 3);
+    assert_eq!(listed["errors"][0]["stage"], "parse");
+
+    // Re-ingesting takes a file out of quarantine for another try
+    let response = client.post(format!("{}/agent/reingest", base)).json(&serde_json::json!({"path": "blob.bin"})).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let retried = file_errors(&errors, &blob).unwrap();
+    assert_eq!(retried.failures, 1);
+    assert!(!retried.quarantined);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_agent_errors_needs_agent() {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+
+    let response = reqwest::get(format!("{}/agent/errors", base)).await.unwrap();
+    assert_eq!(response.status(), 404);
+}
@@ -0,0 +1,69 @@
+use crate::common::{self, MockOllama};
+use cuemap_rust::agent::cue_policy::{CuePolicy, DirectoryRoute};
+use cuemap_rust::agent::ingester::Ingester;
+use cuemap_rust::agent::AgentConfig;
+use cuemap_rust::jobs::{JobQueue, SingleTenantProvider};
+use cuemap_rust::llm::LlmConfig;
+use cuemap_rust::projects::ProjectContext;
+use cuemap_rust::structures::Memory;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The memory extracted from `path`, once its job has run.
+async fn memory_of(project: &ProjectContext, path: &Path) -> Memory {
+    let path_cue = format!("path:{}", path.to_string_lossy().to_lowercase());
+    for _ in 0..100 {
+        let ids = project.main.cue_memory_ids(&path_cue, None);
+        if let Some(memory) = ids.first().and_then(|id| project.main.get_memory(id)) {
+            return memory;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("no memory extracted from {:?}", path);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_routed_ingestion() {
+    let (ollama, _) = MockOllama::generate(|_| Some(common::extraction("Refunds take five days", &["topic:refunds"]))).start().await;
+    let _llm = common::use_mock_llm(&ollama).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("docs")).unwrap();
+    std::fs::create_dir(dir.path().join("src")).unwrap();
+    let doc = dir.path().join("docs/refunds.txt");
+    let code = dir.path().join("src/refunds.txt");
+    let readme = dir.path().join("README.txt");
+    for path in [&doc, &code, &readme] {
+        std::fs::write(path, "Refunds take five days.").unwrap();
+    }
+
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    project.create_collection("code", Some("Source files".to_string())).unwrap();
+    let job_queue = Arc::new(JobQueue::new(Arc::new(SingleTenantProvider { project: project.clone() })));
+    let cue_policy = CuePolicy {
+        routes: vec![
+            DirectoryRoute { dir: "docs".to_string(), collection: Some("docs".to_string()), cues: vec!["area:docs".to_string()] },
+            DirectoryRoute::parse("src=code").unwrap(),
+        ],
+        ..Default::default()
+    };
+    let config = AgentConfig {
+        watch_dir: dir.path().to_string_lossy().to_string(),
+        throttle_ms: 0,
+        llm: LlmConfig::from_env().unwrap(),
+        cue_policy,
+    };
+    Ingester::new(config, job_queue.clone()).scan_all().await.unwrap();
+
+    let routed = memory_of(&project, &doc).await;
+    assert_eq!(routed.collection.as_deref(), Some("docs"));
+    assert!(routed.cues.iter().any(|cue| cue.as_ref() == "area:docs"));
+    assert_eq!(memory_of(&project, &code).await.collection.as_deref(), Some("code"));
+    assert_eq!(memory_of(&project, &readme).await.collection, None);
+
+    // Missing collections are created, existing ones kept as they are
+    let collections: Vec<(String, Option<String>)> =
+        project.collections().into_iter().map(|collection| (collection.name, collection.description)).collect();
+    assert_eq!(collections, vec![("code".to_string(), Some("Source files".to_string())), ("docs".to_string(), None)]);
+}
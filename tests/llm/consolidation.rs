@@ -0,0 +1,84 @@
+use crate::common::{self, cues, MockOllama};
+use cuemap_rust::engine::CueMapEngine;
+use cuemap_rust::events::ChangeEvent;
+use cuemap_rust::jobs::{Job, JobQueue, SingleTenantProvider};
+use cuemap_rust::projects::ProjectContext;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn test_merge_memories_tombstones_originals() {
+    let engine = CueMapEngine::new();
+    let first = engine.add_memory("payments timed out".to_string(), cues(&["service:payments", "type:incident"]), None, true);
+    let second = engine.add_memory("raised the pool size".to_string(), cues(&["service:payments", "type:fix"]), None, true);
+    let mut events = engine.events().subscribe();
+
+    let summary_id = engine.merge_memories(&[first.clone(), second.clone()], "merged".to_string()).unwrap();
+    let summary = engine.get_memory(&summary_id).unwrap();
+    let summary_cues: Vec<String> = summary.cues.iter().map(|cue| cue.to_string()).collect();
+    assert_eq!(summary_cues, cues(&["service:payments", "type:incident", "type:fix", "type:summary"]));
+    assert_eq!(summary.metadata["consolidated_from"], serde_json::json!([first, second]));
+
+    // Originals stay readable by id but drop out of recall
+    let original = engine.get_memory(&first).unwrap();
+    assert!(original.is_tombstoned());
+    assert_eq!(original.metadata["consolidated_into"], serde_json::json!(summary_id));
+    assert_eq!(original.metadata["original_cues"], serde_json::json!(["service:payments", "type:incident"]));
+    let results = engine.recall(cues(&["service:payments"]), 10, false);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].memory_id, summary_id);
+    assert_eq!(engine.recall(cues(&["status:tombstoned"]), 10, false).len(), 2);
+    assert!(engine.check_index_consistency().is_empty());
+
+    let merged = std::iter::from_fn(|| events.try_recv().ok())
+        .find(|record| matches!(record.event, ChangeEvent::MemoryMerged { .. }))
+        .unwrap();
+    assert_eq!(merged.event, ChangeEvent::MemoryMerged { memory_id: summary_id.clone(), sources: vec![first.clone(), second.clone()] });
+
+    // Tombstones are neither merged again nor grouped
+    assert!(engine.merge_memories(&[first, second], "again".to_string()).is_none());
+    assert!(engine.consolidation_groups(0.1).is_empty());
+}
+
+#[tokio::test]
+async fn test_consolidation_job_summarizes_with_llm() {
+    let summary = "  The payments service times out under load; fixed by raising the pool size.  ";
+    let (url, mut prompts) = MockOllama::generate(move |_| Some(summary.to_string())).start().await;
+    let _llm = common::use_mock_llm(&url).await;
+
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let main = &project.main;
+    let older = main.add_memory("payments timed out".to_string(), cues(&["service:payments", "type:incident", "env:prod"]), None, true);
+    let newer = main.add_memory("pool size raised".to_string(), cues(&["service:payments", "type:incident", "env:prod", "team:core"]), None, true);
+    let unrelated = main.add_memory("search reindexed".to_string(), cues(&["service:search"]), None, true);
+
+    let queue = JobQueue::new(Arc::new(SingleTenantProvider { project: project.clone() }));
+    let mut events = project.main.events().subscribe();
+    queue.enqueue(Job::ConsolidateMemories { project_id: "default".to_string() }).await;
+
+    let mut merged = None;
+    loop {
+        let record = tokio::time::timeout(Duration::from_secs(5), events.recv()).await.unwrap().unwrap();
+        match record.event {
+            ChangeEvent::MemoryMerged { memory_id, sources } => merged = Some((memory_id, sources)),
+            ChangeEvent::JobCompleted { job, .. } if job == "consolidate_memories" => break,
+            _ => {}
+        }
+    }
+    let (summary_id, mut sources) = merged.unwrap();
+    sources.sort();
+    let mut expected = vec![older.clone(), newer.clone()];
+    expected.sort();
+    assert_eq!(sources, expected);
+
+    // The LLM sees the originals oldest first
+    let prompt = prompts.try_recv().unwrap();
+    assert!(prompt.find("payments timed out").unwrap() < prompt.find("pool size raised").unwrap());
+
+    let summary = main.get_memory(&summary_id).unwrap();
+    assert_eq!(summary.content, "The payments service times out under load; fixed by raising the pool size.");
+    assert!(summary.cues.iter().any(|cue| &**cue == "team:core"));
+    assert!(main.get_memory(&older).unwrap().is_tombstoned());
+    assert!(main.get_memory(&newer).unwrap().is_tombstoned());
+    assert!(!main.get_memory(&unrelated).unwrap().is_tombstoned());
+}
@@ -1,3 +1,16 @@
+#[path = "../common/mod.rs"]
+mod common;
+
+mod agent_errors;
+mod agent_routes;
+mod consolidation;
+mod provenance;
+mod readiness;
+mod reingest;
+mod reproposal;
+mod rerank;
+mod watcher_recovery;
+
 use cuemap_rust::llm::*;
 
 #[test]
@@ -0,0 +1,69 @@
+use crate::common::{self, MockOllama};
+use cuemap_rust::agent::cue_policy::CuePolicy;
+use cuemap_rust::agent::ingester::Ingester;
+use cuemap_rust::agent::AgentConfig;
+use cuemap_rust::jobs::{JobQueue, SingleTenantProvider};
+use cuemap_rust::llm::{LlmConfig, EXTRACT_PROMPT_VERSION};
+use cuemap_rust::projects::ProjectContext;
+use cuemap_rust::provenance::Provenance;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_agent_memories_record_provenance() {
+    let (ollama, _) = MockOllama::generate(|_| Some(common::extraction("Refund policy", &["topic:refunds"]))).start().await;
+    let _llm = common::use_mock_llm(&ollama).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let text = "Refunds take five days.\n\nChargebacks need approval.";
+    let file = dir.path().join("refunds.txt");
+    std::fs::write(&file, text).unwrap();
+
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let job_queue = Arc::new(JobQueue::new(Arc::new(SingleTenantProvider { project: project.clone() })));
+    let config = AgentConfig {
+        watch_dir: dir.path().to_string_lossy().to_string(),
+        throttle_ms: 0,
+        llm: LlmConfig::from_env().unwrap(),
+        cue_policy: CuePolicy::default(),
+    };
+    let mut ingester = Ingester::new(config, job_queue.clone());
+    ingester.process_file_path(file.clone()).await.unwrap();
+    assert!(job_queue.shutdown(Duration::from_secs(10)).await);
+
+    let path = file.to_string_lossy().to_lowercase();
+    let mut memory_ids = project.main.cue_memory_ids(&format!("path:{}", path), None);
+    assert_eq!(memory_ids.len(), 2);
+    memory_ids.sort_by_key(|id| Provenance::of(&project.main.get_memory(id).unwrap()).unwrap().byte_range.unwrap().start);
+
+    let mut job_ids = Vec::new();
+    for (id, paragraph) in memory_ids.iter().zip(text.split("\n\n")) {
+        let provenance = Provenance::of(&project.main.get_memory(id).unwrap()).unwrap();
+        assert_eq!(provenance.file_path, path);
+        assert!(id.ends_with(&provenance.chunk_hash));
+        assert_eq!(&text[provenance.byte_range.unwrap()], paragraph);
+        assert_eq!(provenance.extraction_model, "ollama/mistral");
+        assert_eq!(provenance.prompt_version, EXTRACT_PROMPT_VERSION);
+        assert!(provenance.extracted_at > 0.0);
+        job_ids.push(provenance.job_id);
+    }
+    assert_ne!(job_ids[0], job_ids[1]);
+
+    let summary = project.main.merge_memories(&memory_ids, "Refunds and chargebacks".to_string()).unwrap();
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+
+    let response = client.get(format!("{}/memories/missing/provenance", base)).send().await.unwrap();
+    assert_eq!(response.status(), 404);
+    let chain: Value = client.get(format!("{}/memories/{}/provenance", base, summary)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(chain["memory_id"], summary.as_str());
+    assert!(chain["provenance"].is_null());
+    let sources = chain["sources"].as_array().unwrap();
+    assert_eq!(sources.len(), 2);
+    assert_eq!(sources[0]["memory_id"], memory_ids[0].as_str());
+    assert_eq!(sources[0]["consolidated_into"], summary.as_str());
+    assert_eq!(sources[0]["provenance"]["job_id"], job_ids[0].as_str());
+    assert_eq!(sources[0]["provenance"]["byte_range"], serde_json::json!({"start": 0, "end": 23}));
+}
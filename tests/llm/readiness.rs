@@ -0,0 +1,50 @@
+use crate::common;
+use axum::routing::get;
+use axum::Router;
+use cuemap_rust::projects::ProjectContext;
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// A stand-in Ollama answering `/api/tags`, with the number of calls it got.
+async fn tags_server() -> (String, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counted = calls.clone();
+    let app = Router::new().route(
+        "/api/tags",
+        get(move || {
+            counted.fetch_add(1, Ordering::SeqCst);
+            async { axum::Json(serde_json::json!({"models": []})) }
+        }),
+    );
+    (common::serve(app).await, calls)
+}
+
+#[tokio::test]
+async fn test_llm_readiness_is_cached() {
+    let (ollama, calls) = tags_server().await;
+    let llm = common::use_mock_llm(&ollama).await;
+
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let base = common::serve(common::router(&project)).await;
+    let client = reqwest::Client::new();
+    let readyz = || {
+        let request = client.get(format!("{}/readyz?llm=true", base));
+        async move { request.send().await.unwrap().json::<Value>().await.unwrap() }
+    };
+
+    // Unauthenticated probes reuse one provider check
+    for _ in 0..3 {
+        let body = readyz().await;
+        assert_eq!(body["status"], "ready");
+        assert_eq!(body["checks"]["llm"]["ok"], true);
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Other provider settings are checked afresh
+    drop(llm);
+    let (other, other_calls) = tags_server().await;
+    let _llm = common::use_mock_llm(&other).await;
+    assert_eq!(readyz().await["checks"]["llm"]["ok"], true);
+    assert_eq!(other_calls.load(Ordering::SeqCst), 1);
+}
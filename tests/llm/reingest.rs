@@ -0,0 +1,99 @@
+use crate::common::{self, MockOllama};
+use cuemap_rust::agent::cue_policy::CuePolicy;
+use cuemap_rust::agent::ingester::Ingester;
+use cuemap_rust::agent::{AgentConfig, AgentHandle};
+use cuemap_rust::api;
+use cuemap_rust::auth::AuthConfig;
+use cuemap_rust::grounding::GroundingTraceStore;
+use cuemap_rust::jobs::{JobQueue, SingleTenantProvider};
+use cuemap_rust::llm::LlmConfig;
+use cuemap_rust::projects::ProjectContext;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_reingest_route() {
+    // Summaries are numbered, so a re-extraction shows in the memory's content
+    let calls = AtomicUsize::new(0);
+    let (ollama, _) = MockOllama::generate(move |_| {
+        let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+        Some(common::extraction(&format!("Refund policy v{}", call), &["topic:refunds"]))
+    })
+    .start()
+    .await;
+    let _llm = common::use_mock_llm(&ollama).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("docs")).unwrap();
+    let file = dir.path().join("docs/refunds.txt");
+    std::fs::write(&file, "Refunds take five days.").unwrap();
+
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let job_queue = Arc::new(JobQueue::new(Arc::new(SingleTenantProvider { project: project.clone() })));
+    let config = AgentConfig {
+        watch_dir: dir.path().to_string_lossy().to_string(),
+        throttle_ms: 0,
+        llm: LlmConfig::from_env().unwrap(),
+        cue_policy: CuePolicy::default(),
+    };
+    let ingester = Arc::new(Mutex::new(Ingester::new(config, job_queue.clone())));
+    ingester.lock().await.scan_all().await.unwrap();
+
+    let path_cue = format!("path:{}", file.to_string_lossy().to_lowercase());
+    let content = || {
+        let ids = project.main.cue_memory_ids(&path_cue, None);
+        ids.first().and_then(|id| project.main.get_memory(id)).map(|memory| memory.content)
+    };
+    let wait_for = |expected: &'static str| async move {
+        for _ in 0..100 {
+            if content().as_deref() == Some(expected) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("memory content is {:?}, expected {}", content(), expected);
+    };
+    wait_for("Refund policy v1").await;
+
+    let traces = Arc::new(GroundingTraceStore::new(100, None));
+    let app = api::routes(project.clone(), job_queue, traces, AuthConfig::with_keys(HashMap::new()), false)
+        .layer(axum::Extension(AgentHandle::new(ingester.clone()).await));
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+    let reingest = |path: &str| {
+        let request = client.post(format!("{}/agent/reingest", base)).json(&serde_json::json!({ "path": path }));
+        async move { request.send().await.unwrap() }
+    };
+
+    // Unchanged on disk, yet extracted again
+    let response = reingest("docs/*.txt").await;
+    assert_eq!(response.status(), 200);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["paths"], serde_json::json!([file.to_string_lossy()]));
+    wait_for("Refund policy v2").await;
+    assert_eq!(project.main.cue_memory_ids(&path_cue, None).len(), 1);
+
+    assert_eq!(reingest("docs/*.md").await.status(), 404);
+    assert_eq!(reingest("docs/[").await.status(), 400);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_reingest_needs_agent() {
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/agent/reingest", base))
+        .json(&serde_json::json!({"path": "docs/refunds.txt"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "not_enabled");
+}
@@ -0,0 +1,90 @@
+use crate::common::{self, cues, MockOllama};
+use cuemap_rust::cli::{run, Command};
+use cuemap_rust::projects::ProjectContext;
+use cuemap_rust::reproposal::ReproposalFilter;
+use serde_json::Value;
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn memory_cues(project: &ProjectContext, id: &str) -> Vec<String> {
+    let mut cues: Vec<String> = project.main.get_memory(id).unwrap().cues.iter().map(|cue| cue.to_string()).collect();
+    cues.sort();
+    cues
+}
+
+#[test]
+fn test_filter_selects_oldest_first() {
+    let project = ProjectContext::new(Default::default(), Default::default());
+    let sparse = project.main.add_memory("checkout failed".to_string(), cues(&["service:payments"]), None, true);
+    std::thread::sleep(Duration::from_millis(5));
+    let rich = project.main.add_memory("refund issued".to_string(), cues(&["service:payments", "topic:refunds", "team:core"]), None, true);
+    std::thread::sleep(Duration::from_millis(5));
+    let other = project.main.add_memory("search reindexed".to_string(), cues(&["service:search"]), None, true);
+
+    assert_eq!(ReproposalFilter::default().select(&project), vec![sparse.clone(), rich.clone(), other.clone()]);
+    let filter = ReproposalFilter { cue: Some("Service:Payments".to_string()), max_cues: None };
+    assert_eq!(filter.select(&project), vec![sparse.clone(), rich]);
+    let filter = ReproposalFilter { cue: Some("service:payments".to_string()), max_cues: Some(2) };
+    assert_eq!(filter.select(&project), vec![sparse]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_reproposal_route_and_command() {
+    let (ollama, _) = MockOllama::generate(|_| Some(r#"{"cues": ["topic:payments", "intent:debugging"]}"#.to_string())).start().await;
+    let _llm = common::use_mock_llm(&ollama).await;
+
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let sparse = project.main.add_memory("checkout failed".to_string(), cues(&["service:payments"]), None, true);
+    let tagged = project.main.add_memory("payment retried".to_string(), cues(&["service:payments", "topic:payments"]), None, true);
+    let other = project.main.add_memory("search reindexed".to_string(), cues(&["service:search"]), None, true);
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+    let url = format!("{}/admin/jobs/reproposal", base);
+
+    assert_eq!(client.get(&url).send().await.unwrap().status(), 404);
+    assert_eq!(client.post(format!("{}?rate=0", url)).send().await.unwrap().status(), 400);
+
+    let response = client.post(format!("{}?filter=service:payments&rate=50", url)).send().await.unwrap();
+    assert_eq!(response.status(), 202);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["reproposal"]["filter"], serde_json::json!({"filter": "service:payments"}));
+
+    let mut reproposal = Value::Null;
+    for _ in 0..100 {
+        reproposal = client.get(&url).send().await.unwrap().json::<Value>().await.unwrap()["reproposal"].clone();
+        if reproposal["state"] == "completed" {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(reproposal["state"], "completed", "{}", reproposal);
+    assert_eq!(reproposal["total"], 2);
+    assert_eq!(reproposal["processed"], 2);
+    assert_eq!(reproposal["failed"], 0);
+    // Only cues the memories lacked are attached
+    assert_eq!(reproposal["attached"], 3);
+    assert_eq!(memory_cues(&project, &sparse), cues(&["intent:debugging", "service:payments", "topic:payments"]));
+    assert_eq!(memory_cues(&project, &tagged), cues(&["intent:debugging", "service:payments", "topic:payments"]));
+    assert_eq!(memory_cues(&project, &other), cues(&["service:search"]));
+
+    // The subcommand does the same against a data directory
+    let dir = tempfile::tempdir().unwrap();
+    let data = dir.path().join("data");
+    let input = dir.path().join("in.jsonl");
+    fs::write(&input, "{\"id\": \"m1\", \"content\": \"checkout failed\", \"cues\": [\"service:payments\"]}\n{\"id\": \"m2\", \"content\": \"search reindexed\", \"cues\": [\"service:search\", \"topic:search\"]}\n").unwrap();
+    run(Command::Import { file: input }, &data, &mut Vec::new()).unwrap();
+    let mut out = Vec::new();
+    run(Command::Repropose { filter: None, max_cues: Some(1), rate: 50.0 }, &data, &mut out).unwrap();
+    let report: Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(report["total"], 1);
+    assert_eq!(report["attached"], 2);
+
+    let mut exported = Vec::new();
+    run(Command::Export { output: None }, &data, &mut exported).unwrap();
+    let exported = String::from_utf8(exported).unwrap();
+    let m1 = exported.lines().find(|line| line.contains("\"m1\"")).unwrap();
+    assert!(m1.contains("intent:debugging"));
+    assert!(!exported.lines().find(|line| line.contains("\"m2\"")).unwrap().contains("intent:debugging"));
+}
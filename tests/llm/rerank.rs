@@ -0,0 +1,61 @@
+use crate::common::{self, MockOllama};
+use cuemap_rust::projects::ProjectContext;
+use cuemap_rust::rerank::{cosine_similarity, EMBEDDING_KEY};
+use std::sync::Arc;
+
+#[test]
+fn test_cosine_similarity() {
+    assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-9);
+    assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 3.0]).abs() < 1e-9);
+    assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), 0.0);
+}
+
+#[tokio::test]
+async fn test_recall_rerank_blends_similarity() {
+    // Texts mentioning refunds embed on one axis, everything else on another
+    let (url, mut inputs) =
+        MockOllama::embed(|text| if text.contains("refund") { vec![1.0, 0.1] } else { vec![0.1, 1.0] }).start().await;
+    let _llm = common::use_mock_llm(&url).await;
+
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+
+    let main = &project.main;
+    let refund = main.add_memory("refunds are accepted within 30 days".to_string(), vec!["topic:billing".to_string()], None, true);
+    let invoice = main.add_memory("invoices now show the tax id".to_string(), vec!["topic:billing".to_string()], None, true);
+
+    let recall = |rerank: bool| {
+        let request = client
+            .post(format!("{}/recall", base))
+            .json(&serde_json::json!({"cues": ["topic:billing"], "query_text": "refund policy", "rerank": rerank, "limit": 1, "explain": true}));
+        async move { request.send().await.unwrap().json::<serde_json::Value>().await.unwrap() }
+    };
+
+    // Cue order alone puts the newer memory first
+    let body = recall(false).await;
+    assert_eq!(body["results"][0]["memory_id"], invoice.as_str());
+    assert!(body.get("reranked").is_none());
+
+    let body = recall(true).await;
+    assert_eq!(body["reranked"], 2);
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["memory_id"], refund.as_str());
+    assert!(results[0]["explain"]["semantic_similarity"].as_f64().unwrap() > 0.9);
+    assert!(results[0]["metadata"].get(EMBEDDING_KEY).is_none());
+
+    let embedded: Vec<String> = std::iter::from_fn(|| inputs.try_recv().ok()).collect();
+    assert_eq!(embedded.len(), 3);
+    assert_eq!(embedded[0], "refund policy");
+
+    // Vectors are cached on the memories and reused
+    let cached = main.get_memory(&refund).unwrap().metadata[EMBEDDING_KEY].clone();
+    assert_eq!(cached["model"], "nomic-embed-text");
+    assert_eq!(cached["vector"].as_array().unwrap().len(), 2);
+    recall(true).await;
+    let embedded: Vec<String> = std::iter::from_fn(|| inputs.try_recv().ok()).collect();
+    assert_eq!(embedded, vec!["refund policy".to_string()]);
+}
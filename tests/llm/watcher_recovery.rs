@@ -0,0 +1,76 @@
+use crate::common::{self, MockOllama};
+use cuemap_rust::agent::cue_policy::CuePolicy;
+use cuemap_rust::agent::ingester::Ingester;
+use cuemap_rust::agent::{self, AgentConfig};
+use cuemap_rust::jobs::{JobQueue, SingleTenantProvider};
+use cuemap_rust::llm::LlmConfig;
+use cuemap_rust::projects::ProjectContext;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// The content of the memory extracted from `path`, if any.
+fn content(project: &ProjectContext, path: &Path) -> Option<String> {
+    let ids = project.main.cue_memory_ids(&format!("path:{}", path.to_string_lossy().to_lowercase()), None);
+    ids.first().and_then(|id| project.main.get_memory(id)).map(|memory| memory.content)
+}
+
+async fn wait_for(project: &ProjectContext, path: &Path, expected: Option<&str>) {
+    for _ in 0..100 {
+        if content(project, path).as_deref() == expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("{:?} has {:?}, expected {:?}", path, content(project, path), expected);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rescan_and_reconcile() {
+    // A chunk's summary is its last line, so a memory shows which version of
+    // its file it came from
+    let (ollama, _) =
+        MockOllama::generate(|prompt| Some(common::extraction(prompt.lines().last().unwrap_or(""), &["topic:refunds"]))).start().await;
+    let _llm = common::use_mock_llm(&ollama).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("docs")).unwrap();
+    let edited = dir.path().join("docs/refunds.txt");
+    let deleted = dir.path().join("docs/billing.txt");
+    let added = dir.path().join("docs/returns.txt");
+    let outside = dir.path().join("notes.txt");
+    std::fs::write(&edited, "Refunds take five days.").unwrap();
+    std::fs::write(&deleted, "Invoices go out monthly.").unwrap();
+
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let job_queue = Arc::new(JobQueue::new(Arc::new(SingleTenantProvider { project: project.clone() })));
+    let config = AgentConfig {
+        watch_dir: dir.path().to_string_lossy().to_string(),
+        throttle_ms: 0,
+        llm: LlmConfig::from_env().unwrap(),
+        cue_policy: CuePolicy::default(),
+    };
+    let ingester = Arc::new(Mutex::new(Ingester::new(config, job_queue.clone())));
+    ingester.lock().await.scan_all().await.unwrap();
+    wait_for(&project, &edited, Some("Refunds take five days.")).await;
+    wait_for(&project, &deleted, Some("Invoices go out monthly.")).await;
+
+    // Changes no event reported, caught up on below docs/ only
+    std::fs::write(&edited, "Refunds take two days.").unwrap();
+    std::fs::remove_file(&deleted).unwrap();
+    std::fs::write(&added, "Returns are free.").unwrap();
+    std::fs::write(&outside, "Support answers within a day.").unwrap();
+    assert_eq!(ingester.lock().await.rescan(&dir.path().join("docs")).await, 1);
+    wait_for(&project, &edited, Some("Refunds take two days.")).await;
+    wait_for(&project, &deleted, None).await;
+    wait_for(&project, &added, Some("Returns are free.")).await;
+    assert_eq!(content(&project, &outside), None);
+
+    // The reconciler covers the whole watch directory
+    std::fs::remove_file(&added).unwrap();
+    let reconciler = agent::spawn_reconciler(ingester.clone(), dir.path().to_path_buf(), Duration::from_millis(100));
+    wait_for(&project, &outside, Some("Support answers within a day.")).await;
+    wait_for(&project, &added, None).await;
+    reconciler.abort();
+}
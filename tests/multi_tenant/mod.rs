@@ -19,8 +19,8 @@ fn test_multi_tenant_isolation() {
     let ctx1 = engine.get_or_create_project("proj1".to_string());
     let ctx2 = engine.get_or_create_project("proj2".to_string());
     
-    ctx1.main.add_memory("Project 1 content".to_string(), vec!["cue1".to_string()], None);
-    ctx2.main.add_memory("Project 2 content".to_string(), vec!["cue2".to_string()], None);
+    ctx1.main.add_memory("Project 1 content".to_string(), vec!["cue1".to_string()], None, false);
+    ctx2.main.add_memory("Project 2 content".to_string(), vec!["cue2".to_string()], None, false);
     
     // Proj1 should not see cue2
     assert_eq!(ctx1.main.recall(vec!["cue2".to_string()], 10, false).len(), 0);
@@ -42,7 +42,7 @@ fn test_snapshot_roundtrip() {
     {
         let engine = MultiTenantEngine::with_snapshots_dir(&snapshots_dir);
         let ctx = engine.get_or_create_project(project_id.clone());
-        ctx.main.add_memory("persist me".to_string(), vec!["save:true".to_string()], None);
+        ctx.main.add_memory("persist me".to_string(), vec!["save:true".to_string()], None, false);
         
         // Save
         engine.save_project(&project_id).expect("Should save successfully");
@@ -73,3 +73,27 @@ fn test_delete_project() {
     assert!(engine.delete_project(&project_id.to_string()));
     assert!(engine.get_project(&project_id.to_string()).is_none());
 }
+
+#[test]
+fn test_project_config_persisted() {
+    let dir = tempdir().unwrap();
+
+    {
+        let engine = MultiTenantEngine::with_snapshots_dir(dir.path());
+        let ctx = engine.get_or_create_project("config_test".to_string());
+        let mut tokenizer = ctx.tokenizer();
+        tokenizer.stopwords.insert("DER".to_string());
+        tokenizer.bigrams = false;
+        ctx.set_tokenizer(tokenizer);
+    }
+
+    assert!(dir.path().join("config_test.config.json").exists());
+
+    // A fresh engine restores the config for the same project
+    let engine = MultiTenantEngine::with_snapshots_dir(dir.path());
+    let ctx = engine.get_or_create_project("config_test".to_string());
+    let tokenizer = ctx.tokenizer();
+    assert!(tokenizer.stopwords.contains("der"));
+    assert!(!tokenizer.bigrams);
+    assert_eq!(ctx.tokenize("der alte Server"), vec!["tok:alte", "tok:server"]);
+}
@@ -24,3 +24,105 @@ fn test_normalize_text() {
     assert_eq!(normalize_text("  HELLO   WORLD  "), "hello world");
     assert_eq!(normalize_text("Mixed-Case_With_Dots.com"), "mixed case with dots com");
 }
+
+#[test]
+fn test_tokenizer_config() {
+    let config = TokenizerConfig {
+        stopwords: ["der", "die", "das"].iter().map(|w| w.to_string()).collect(),
+        min_token_len: 4,
+        bigrams: false,
+        ..Default::default()
+    };
+
+    let tokens = tokenize_to_cues_with("Der Server und das Netzwerk", &config);
+    assert_eq!(tokens, vec!["tok:server", "tok:netzwerk"]);
+
+    // Defaults keep the built-in behaviour
+    assert_eq!(
+        tokenize_to_cues_with("The quick brown fox", &TokenizerConfig::default()),
+        tokenize_to_cues("The quick brown fox")
+    );
+}
+
+#[test]
+fn test_cjk_tokenization() {
+    // Mixed-script text is split at script boundaries
+    assert_eq!(normalize_text("API接口文档"), "api 接口文档");
+
+    let tokens = tokenize_to_cues("支付服务超时");
+    assert!(!tokens.is_empty());
+    assert!(tokens.iter().all(|t| t.starts_with("tok:")));
+    #[cfg(not(feature = "jieba"))]
+    assert_eq!(tokens, vec!["tok:支付", "tok:付服", "tok:服务", "tok:务超", "tok:超时"]);
+
+    // Japanese and Korean runs are segmented too
+    assert!(tokenize_to_cues("東京タワー").contains(&"tok:東京".to_string()));
+    assert!(tokenize_to_cues("결제 서비스").contains(&"tok:결제".to_string()));
+
+    // Latin phrases don't bridge across CJK runs, non-ASCII words stay whole
+    let mixed = tokenize_to_cues("payment 服务 gateway für");
+    assert!(mixed.contains(&"tok:payment".to_string()));
+    assert!(mixed.contains(&"tok:für".to_string()));
+    assert!(!mixed.contains(&"phr:payment_gateway".to_string()));
+    assert!(mixed.contains(&"phr:gateway_für".to_string()));
+}
+
+#[test]
+fn test_keyword_extraction() {
+    let config = TokenizerConfig {
+        mode: TokenizerMode::Keywords,
+        max_keywords: 3,
+        ..Default::default()
+    };
+    let text = "Payments gateway timeout. The payments gateway retried the request after a timeout.";
+
+    // "request" and "retried" appear in every seen document, so they score low
+    let common = |cue: &str| match cue {
+        "tok:request" | "tok:retried" | "tok:after" => 100,
+        _ => 1,
+    };
+    let keywords = extract_keywords(text, &config, None, common, 100);
+
+    assert_eq!(keywords.len(), 3);
+    assert!(keywords.contains(&"phr:payments_gateway".to_string()));
+    assert!(!keywords.iter().any(|k| k == "tok:request" || k == "tok:retried"));
+
+    // Without corpus statistics, term frequency decides
+    let keywords = extract_keywords(text, &config, None, |_| 0, 0);
+    assert_eq!(keywords.len(), 3);
+    assert!(keywords.contains(&"tok:payments".to_string()));
+}
+
+#[test]
+fn test_language_detection() {
+    assert_eq!(
+        detect_language("Der Zahlungsdienst ist wegen einer Zeitüberschreitung ausgefallen und wird gerade neu gestartet."),
+        Some("de")
+    );
+    assert_eq!(
+        detect_language("The payments service is down because of a timeout and is being restarted."),
+        Some("en")
+    );
+    // Too short to be reliable
+    assert_eq!(detect_language("ok"), None);
+
+    // The detected language selects the extra stopword list
+    let config = TokenizerConfig::default();
+    let german = "Der Server und das Netzwerk";
+    assert!(tokenize_to_cues(german).contains(&"tok:der".to_string()));
+    let tokens = tokenize_to_cues_for_language(german, &config, Some("de"));
+    assert!(!tokens.contains(&"tok:der".to_string()));
+    assert!(!tokens.contains(&"tok:das".to_string()));
+    assert!(tokens.contains(&"tok:netzwerk".to_string()));
+}
+
+#[test]
+fn test_edit_distance() {
+    assert_eq!(edit_distance("payments", "payments", 2), Some(0));
+    assert_eq!(edit_distance("paymnts", "payments", 2), Some(1));
+    // An adjacent transposition is one edit
+    assert_eq!(edit_distance("latnecy", "latency", 1), Some(1));
+    assert_eq!(edit_distance("kitten", "sitting", 3), Some(3));
+    assert_eq!(edit_distance("kitten", "sitting", 2), None);
+    assert_eq!(edit_distance("pay", "payments", 2), None);
+}
@@ -19,6 +19,7 @@ fn test_rewrite_rules() {
                 replace: "service:$1".to_string(),
             },
         ],
+        ..Default::default()
     };
 
     let (normalized, trace) = normalize_cue("Payments-Service", &config);
@@ -43,6 +44,7 @@ fn test_rewrite_chaining() {
                 replace: "tag:$1".to_string(),
             },
         ],
+        ..Default::default()
     };
 
     // Input: "My-Value"
@@ -63,3 +65,61 @@ fn test_prefix_deduplication() {
     let (normalized2, _) = normalize_cue("topic:payments:payments", &config);
     assert_eq!(normalized2, "topic:payments");
 }
+
+#[test]
+fn test_rewrite_rules_precompiled() {
+    let mut config: NormalizationConfig = serde_json::from_value(serde_json::json!({
+        "lowercase": true,
+        "trim": true,
+        "rewrite_rules": [
+            {"name": "svc", "pattern": "^svc-(.+)$", "replace": "service:$1"},
+            {"name": "broken", "pattern": "([", "replace": ""}
+        ]
+    })).unwrap();
+    assert!(config.rewrite_rules[0].is_valid());
+    assert!(!config.rewrite_rules[1].is_valid());
+
+    // Invalid patterns are skipped, compiled or not
+    let (uncompiled, _) = normalize_cue("svc-payments", &config);
+    config.compile();
+    let (normalized, trace) = normalize_cue("svc-payments", &config);
+    assert_eq!((uncompiled.as_str(), normalized.as_str()), ("service:payments", "service:payments"));
+    assert_eq!(trace.applied_rules, vec!["svc"]);
+
+    // Rules changed after compiling are applied as they are now
+    config.rewrite_rules[0].pattern = "^srv-(.+)$".to_string();
+    assert_eq!(normalize_cue("srv-payments", &config).0, "service:payments");
+    assert_eq!(normalize_cue("svc-payments", &config).0, "svc-payments");
+}
+
+#[test]
+fn test_unicode_folding() {
+    let config = NormalizationConfig {
+        unicode_nfkc: true,
+        transliterate: true,
+        ..Default::default()
+    };
+
+    let (folded, trace) = normalize_cue("Café:Menü", &config);
+    assert_eq!(folded, "cafe:menu");
+    assert_eq!(trace.applied_rules, vec!["transliterate"]);
+
+    // Inputs that differ only in composition or ASCII folding converge
+    let (decomposed, _) = normalize_cue("cafe\u{301}:menu\u{308}", &config);
+    assert_eq!(decomposed, folded);
+    let (ascii, _) = normalize_cue("cafe:menu", &config);
+    assert_eq!(ascii, folded);
+
+    // NFKC folds compatibility forms such as full-width letters
+    let (full_width, trace) = normalize_cue("ＡＰＩ:ｖ２", &config);
+    assert_eq!(full_width, "api:v2");
+    assert!(trace.applied_rules.contains(&"nfkc".to_string()));
+
+    // CJK is left intact rather than romanized
+    let (cjk, _) = normalize_cue("topic:中文", &config);
+    assert_eq!(cjk, "topic:中文");
+
+    // Disabled by default
+    let (plain, _) = normalize_cue("Café", &NormalizationConfig::default());
+    assert_eq!(plain, "café");
+}
@@ -0,0 +1,50 @@
+use crate::common::cues;
+use cuemap_rust::normalization::{composite_parts, normalize_cue, NormalizationConfig};
+use cuemap_rust::projects::ProjectContext;
+
+#[test]
+fn test_composite_parts() {
+    assert_eq!(composite_parts("deploy:region=eu,env=prod"), Some(cues(&["region:eu", "env:prod"])));
+    assert_eq!(composite_parts("deploy:region = eu"), Some(cues(&["region:eu"])));
+    assert_eq!(composite_parts("service:payments"), None);
+    assert_eq!(composite_parts("link:https://example.com/?a=b"), None);
+    assert_eq!(composite_parts("deploy:region=eu,prod"), None);
+    assert_eq!(composite_parts("deploy:region=eu=west"), None);
+
+    let (normalized, trace) = normalize_cue("Deploy:Region=EU,Env=Prod", &NormalizationConfig::default());
+    assert_eq!(normalized, "deploy:region=eu,env=prod");
+    assert_eq!(trace.derived, cues(&["region:eu", "env:prod"]));
+    let config = NormalizationConfig { composite_cues: false, ..Default::default() };
+    assert!(normalize_cue("deploy:region=eu,env=prod", &config).1.derived.is_empty());
+}
+
+#[test]
+fn test_composite_cues_are_queryable_by_part() {
+    let project = ProjectContext::new(Default::default(), Default::default());
+    let add = |content: &str, raw: &[&str]| {
+        let prepared = project.prepare_memory(content.to_string(), cues(raw), None).unwrap();
+        project.main.add_memory(prepared.content, prepared.report.accepted, None, false)
+    };
+    let eu_prod = add("deployed to eu prod", &["deploy:region=eu,env=prod", "env:prod"]);
+    let us_prod = add("deployed to us prod", &["deploy:region=us,env=prod"]);
+
+    let memory = project.main.get_memory(&eu_prod).unwrap();
+    let held: Vec<&str> = memory.cues.iter().map(|cue| &**cue).collect();
+    assert_eq!(held, vec!["deploy:region=eu,env=prod", "env:prod", "region:eu"]);
+
+    let listed = |cue: &str| {
+        let mut ids = project.main.cue_memory_ids(cue, None);
+        ids.sort();
+        ids
+    };
+    let mut both = vec![eu_prod.clone(), us_prod.clone()];
+    both.sort();
+    assert_eq!(listed("env:prod"), both);
+    assert_eq!(listed("region:eu"), vec![eu_prod.clone()]);
+    assert_eq!(listed("deploy:region=us,env=prod"), vec![us_prod]);
+    // Queries match the composite as written, without expanding it
+    assert_eq!(project.normalize_cues(&cues(&["deploy:region=eu,env=prod"])), cues(&["deploy:region=eu,env=prod"]));
+
+    project.set_normalization(NormalizationConfig { composite_cues: false, ..Default::default() });
+    assert!(project.derived_cues(&cues(&["deploy:region=eu,env=prod"])).is_empty());
+}
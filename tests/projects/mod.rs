@@ -1,3 +1,8 @@
+#[path = "../common/mod.rs"]
+mod common;
+
+mod composite_cues;
+
 use cuemap_rust::projects::*;
 use std::sync::Arc;
 
@@ -18,7 +23,7 @@ fn test_project_persistence() {
     let ctx1 = store.get_or_create("proj_1");
     
     // Add a memory to ctx1
-    ctx1.main.add_memory("test".to_string(), vec!["cue".to_string()], None);
+    ctx1.main.add_memory("test".to_string(), vec!["cue".to_string()], None, false);
     
     // Get the same project again
     let ctx2 = store.get_or_create("proj_1");
@@ -33,8 +38,8 @@ fn test_context_isolation() {
     let ctx1 = store.get_or_create("proj_A");
     let ctx2 = store.get_or_create("proj_B");
     
-    ctx1.main.add_memory("A".to_string(), vec![], None);
-    ctx2.main.add_memory("B".to_string(), vec![], None);
+    ctx1.main.add_memory("A".to_string(), vec![], None, false);
+    ctx2.main.add_memory("B".to_string(), vec![], None, false);
     
     assert_eq!(ctx1.main.get_memories().len(), 1);
     assert_eq!(ctx2.main.get_memories().len(), 1);
@@ -46,3 +51,250 @@ fn test_context_isolation() {
     // Verify they are different objects in memory (Arc pointers)
     assert!(!Arc::ptr_eq(&ctx1, &ctx2));
 }
+
+#[test]
+fn test_language_cue() {
+    let store = ProjectStore::new();
+    let ctx = store.get_or_create("proj_lang");
+    let content = "Le service de paiement est en panne à cause d'un délai d'attente dépassé.";
+
+    assert_eq!(ctx.language_cue(content, &[]), Some("lang:fr".to_string()));
+    // Existing lang: cues (e.g. from the code chunker) are kept
+    assert_eq!(ctx.language_cue(content, &["lang:python".to_string()]), None);
+
+    let mut tokenizer = ctx.tokenizer();
+    tokenizer.language_cue = false;
+    ctx.set_tokenizer(tokenizer);
+    assert_eq!(ctx.language_cue(content, &[]), None);
+}
+
+#[test]
+fn test_config_overrides() {
+    let mut config = ProjectConfig::default();
+    assert_eq!(config.overrides(), serde_json::json!({}));
+
+    config.tokenizer.min_token_len = 3;
+    config.normalization.transliterate = true;
+    let overrides = config.overrides();
+    assert_eq!(overrides["tokenizer"], serde_json::json!({"min_token_len": 3}));
+    assert_eq!(overrides["normalization"], serde_json::json!({"transliterate": true}));
+    assert!(overrides.get("taxonomy").is_none());
+}
+
+#[test]
+fn test_memory_usage() {
+    let store = ProjectStore::new();
+    let ctx = store.get_or_create("proj_mem");
+    let empty = ctx.memory_usage();
+    assert_eq!(empty.total_bytes, 0);
+
+    ctx.main.add_memory("a fairly long piece of content".to_string(), vec!["service:payments".to_string()], None, false);
+    let usage = ctx.memory_usage();
+    assert_eq!(usage.main.memory_count, 1);
+    assert!(usage.main.memories_bytes > "a fairly long piece of content".len());
+    assert!(usage.main.cue_index_bytes > 0);
+    assert!(usage.cue_table_bytes > "service:payments".len());
+    // One cue table per project
+    assert!(Arc::ptr_eq(ctx.main.cue_interner(), ctx.lexicon.cue_interner()));
+    assert_eq!(
+        usage.total_bytes,
+        usage.main.total_bytes + usage.lexicon.total_bytes + usage.aliases.total_bytes + usage.cue_table_bytes + usage.query_cache_bytes + usage.keyword_df_bytes
+    );
+}
+
+#[test]
+fn test_query_cache_invalidated_by_lexicon_changes() {
+    let store = ProjectStore::new();
+    let ctx = store.get_or_create("proj_cache");
+    ctx.lexicon.upsert_memory_with_id("lex_1".to_string(), "service:payments".to_string(), vec!["tok:payments".to_string()], None, false);
+
+    assert_eq!(ctx.resolve_cues_from_text("payments"), vec!["service:payments".to_string()]);
+    assert_eq!(ctx.query_cache.len(), 1);
+
+    // Retraining the lexicon makes the cached resolution stale
+    ctx.lexicon.upsert_memory_with_id("lex_2".to_string(), "topic:billing".to_string(), vec!["tok:payments".to_string()], None, false);
+    let resolved = ctx.resolve_cues_from_text("payments");
+    assert_eq!(resolved.len(), 2);
+    assert!(resolved.contains(&"topic:billing".to_string()));
+}
+
+#[test]
+fn test_query_cache_lru_and_ttl() {
+    use cuemap_rust::query_cache::QueryCache;
+    use std::time::Duration;
+
+    let cache = QueryCache::new(2, Duration::from_secs(60));
+    cache.insert("a".to_string(), vec![("x".to_string(), 1.0)], 0);
+    cache.insert("b".to_string(), vec![("y".to_string(), 1.0)], 0);
+    assert!(cache.get("a", 0).is_some());
+    cache.insert("c".to_string(), vec![("z".to_string(), 1.0)], 0);
+    // "b" was least recently used
+    assert!(cache.get("b", 0).is_none());
+    assert_eq!(cache.len(), 2);
+    // A newer generation misses and drops the entry
+    assert!(cache.get("a", 1).is_none());
+    assert_eq!(cache.len(), 1);
+
+    let expiring = QueryCache::new(8, Duration::ZERO);
+    expiring.insert("a".to_string(), vec![("x".to_string(), 1.0)], 0);
+    assert!(expiring.get("a", 0).is_none());
+    assert!(expiring.is_empty());
+}
+
+fn add_alias(ctx: &ProjectContext, id: &str, from: &str, to: &str) {
+    let content = serde_json::json!({"from": from, "to": to, "downweight": 0.85, "status": "active"}).to_string();
+    let cues = vec!["type:alias".to_string(), format!("from:{}", from), format!("to:{}", to), "status:active".to_string()];
+    ctx.aliases.upsert_memory_with_id(id.to_string(), content, cues, None, false);
+}
+
+#[test]
+fn test_alias_usage_and_expiry() {
+    let ctx = ProjectContext::new(Default::default(), Default::default());
+    add_alias(&ctx, "pay", "pay", "service:payments");
+    add_alias(&ctx, "legacy", "old", "service:legacy");
+    add_alias(&ctx, "noisy", "db", "service:search");
+    ctx.main.add_memory("payments timed out".to_string(), vec!["service:payments".to_string()], None, false);
+    let search = ctx.main.add_memory("search is slow".to_string(), vec!["service:search".to_string()], None, false);
+
+    ctx.expand_query_cues(vec!["pay".to_string()]);
+    ctx.expand_query_cues(vec!["pay".to_string(), "old".to_string()]);
+    let stats = ctx.alias_stats();
+    assert_eq!(stats[0].id, "pay");
+    assert_eq!((stats[0].usage.expansions, stats[0].usage.hits), (2, 2));
+    let legacy = stats.iter().find(|a| a.id == "legacy").unwrap();
+    assert_eq!((legacy.usage.expansions, legacy.usage.hits), (1, 0));
+
+    // Feedback is credited to aliases that led from the query to a matched cue
+    let expanded = ctx.expand_query_cues(vec!["db".to_string()]);
+    let matched = ctx.main.get_memory(&search).unwrap().cues.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+    assert_eq!(ctx.record_alias_feedback(&[("pay".to_string(), 1.0)], &matched, false), 0);
+    for i in 0..10 {
+        assert_eq!(ctx.record_alias_feedback(&expanded, &matched, i < 3), 1);
+    }
+
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs_f64();
+    let expired = ctx.expire_aliases(now);
+    assert_eq!(expired.len(), 1);
+    assert_eq!((expired[0].id.as_str(), expired[0].reason), ("noisy", "low_precision"));
+    let noisy = ctx.alias_stats().into_iter().find(|a| a.id == "noisy").unwrap();
+    assert_eq!(noisy.status, "proposed");
+    assert_eq!(noisy.usage.harmful, 7);
+    assert!(!ctx.expand_query_cues(vec!["db".to_string()]).iter().any(|(cue, _)| cue == "service:search"));
+
+    // A month without hits expires the rest
+    let mut later: Vec<(String, &str)> = ctx.expire_aliases(now + 31.0 * 86_400.0).into_iter().map(|a| (a.id, a.reason)).collect();
+    later.sort();
+    assert_eq!(later, vec![("legacy".to_string(), "unused"), ("pay".to_string(), "unused")]);
+}
+
+#[test]
+fn test_transitive_alias_expansion() {
+    let ctx = ProjectContext::new(Default::default(), Default::default());
+    add_alias(&ctx, "a1", "pay", "payments");
+    add_alias(&ctx, "a2", "payments", "service:payments");
+    add_alias(&ctx, "a3", "service:payments", "pay");
+    add_alias(&ctx, "a4", "service:payments", "team:billing");
+
+    let expanded = ctx.expand_query_paths(vec![("pay".to_string(), 1.0)], 3);
+    let cues: Vec<(&str, usize)> = expanded.iter().map(|e| (e.cue.as_str(), e.path.len())).collect();
+    // The alias back to "pay" closes a cycle and adds nothing
+    assert_eq!(cues, vec![("pay", 1), ("payments", 2), ("service:payments", 3), ("team:billing", 4)]);
+    assert!((expanded[2].weight - 0.85 * 0.85).abs() < 1e-9);
+    assert_eq!(expanded[3].path, vec!["pay", "payments", "service:payments", "team:billing"]);
+
+    let shallow = ctx.expand_query_paths(vec![("pay".to_string(), 1.0)], 1);
+    assert_eq!(shallow.len(), 2);
+    assert_eq!(ctx.expand_query_paths(vec![("pay".to_string(), 1.0)], 0).len(), 1);
+
+    // Query cues keep full weight even when an alias also reaches them
+    let both = ctx.expand_query_paths(vec![("pay".to_string(), 1.0), ("service:payments".to_string(), 1.0)], 3);
+    let direct = both.iter().find(|e| e.cue == "service:payments").unwrap();
+    assert_eq!((direct.weight, direct.path.len()), (1.0, 1));
+    assert_eq!(both.len(), 4);
+}
+
+#[test]
+fn test_text_resolution_confidence() {
+    let ctx = ProjectContext::new(Default::default(), Default::default());
+    let lexicon = |id: &str, cue: &str, tokens: &[&str]| {
+        let tokens = tokens.iter().map(|t| t.to_string()).collect();
+        ctx.lexicon.upsert_memory_with_id(id.to_string(), cue.to_string(), tokens, None, false);
+    };
+    lexicon("lex_payments", "service:payments", &["tok:payments", "tok:timeout", "tok:gateway"]);
+    lexicon("lex_search", "service:search", &["tok:timeout"]);
+    lexicon("lex_billing", "topic:billing", &["tok:payments", "tok:gateway"]);
+
+    let resolved = ctx.resolve_weighted_cues_from_text("payments gateway timeout");
+    // One token of three is too weak a match to inject service:search
+    assert!(!resolved.iter().any(|(cue, _)| cue == "service:search"));
+    assert_eq!(resolved[0], ("service:payments".to_string(), 1.0));
+    let billing = resolved.iter().find(|(cue, _)| cue == "topic:billing").unwrap().1;
+    assert!(billing > 0.5 && billing < 1.0);
+
+    // Explicit cues keep full weight; resolved ones carry their confidence into recall
+    let query = ctx.query_cues(&["Topic:Billing".to_string()], Some("payments gateway timeout"));
+    assert_eq!(query[0], ("topic:billing".to_string(), 1.0));
+    assert_eq!(query.len(), 2);
+    let expanded = ctx.expand_weighted_query_cues(vec![("topic:billing".to_string(), billing)]);
+    assert_eq!(expanded, vec![("topic:billing".to_string(), billing)]);
+}
+
+#[test]
+fn test_spell_correction() {
+    let ctx = ProjectContext::new(Default::default(), Default::default());
+    ctx.lexicon.upsert_memory_with_id("lex_payments".to_string(), "service:payments".to_string(), vec!["tok:payments".to_string()], None, false);
+    ctx.lexicon.upsert_memory_with_id("lex_latency".to_string(), "topic:latency".to_string(), vec!["tok:latency".to_string()], None, false);
+    ctx.lexicon.upsert_memory_with_id("lex_pigments".to_string(), "topic:art".to_string(), vec!["tok:pigments".to_string()], None, false);
+
+    assert!(ctx.resolve_cues_from_text("paymnts latnecy").is_empty());
+    let (text, corrections) = ctx.correct_spelling("Paymnts latnecy in prod");
+    assert_eq!(text, "payments latency in prod");
+    assert_eq!(corrections.len(), 2);
+    assert_eq!((corrections[0].from.as_str(), corrections[0].to.as_str(), corrections[0].distance), ("paymnts", "payments", 1));
+    let resolved = ctx.resolve_cues_from_text(&text);
+    assert!(resolved.contains(&"service:payments".to_string()) && resolved.contains(&"topic:latency".to_string()));
+
+    // Known and short words are left alone, and nothing close enough leaves a word as is
+    let (text, corrections) = ctx.correct_spelling("pigments pay xyzzyplugh");
+    assert_eq!(text, "pigments pay xyzzyplugh");
+    assert!(corrections.is_empty());
+}
+
+#[test]
+fn test_shared_vocabulary_fallback() {
+    use cuemap_rust::shared_vocabulary::SharedVocabulary;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("shared_vocabulary.json");
+    let shared = Arc::new(SharedVocabulary::open(&path));
+    let billing = ProjectContext::new(Default::default(), Default::default()).with_shared_vocabulary(shared.clone());
+    let search = ProjectContext::new(Default::default(), Default::default()).with_shared_vocabulary(shared.clone());
+    assert!(billing.resolve_cues_from_text("ledger outage").is_empty());
+
+    // Added once, visible to every project, and cached resolutions are refreshed
+    shared.put_term("Service:Ledger", "ledger double entry").unwrap();
+    shared.add_alias("ledger", "service:ledger", Some(0.9)).unwrap();
+    assert_eq!(billing.resolve_cues_from_text("ledger outage"), vec!["service:ledger"]);
+    assert_eq!(search.resolve_cues_from_text("ledger outage"), vec!["service:ledger"]);
+    assert_eq!(
+        search.expand_query_cues(vec!["ledger".to_string()]),
+        vec![("ledger".to_string(), 1.0), ("service:ledger".to_string(), 0.9)]
+    );
+
+    // A project's own lexicon and aliases come first
+    billing.lexicon.upsert_memory_with_id("lex_ledger".to_string(), "service:gl".to_string(), vec!["tok:ledger".to_string()], None, false);
+    add_alias(&billing, "gl", "ledger", "service:gl");
+    assert_eq!(billing.resolve_cues_from_text("ledger outage"), vec!["service:gl", "service:ledger"]);
+    let expanded = billing.expand_query_cues(vec!["ledger".to_string()]);
+    assert_eq!(expanded[1], ("service:gl".to_string(), 0.85));
+    assert_eq!(expanded[2], ("service:ledger".to_string(), 0.9));
+
+    // Reopening restores the terms and aliases
+    let reopened = SharedVocabulary::open(&path);
+    assert_eq!(reopened.terms(), shared.terms());
+    assert_eq!(reopened.aliases(), shared.aliases());
+    assert_eq!(reopened.resolve(vec!["tok:ledger".to_string()])[0].0, "service:ledger");
+    assert!(reopened.remove_term("service:ledger"));
+    assert!(reopened.resolve(vec!["tok:ledger".to_string()]).is_empty());
+    assert!(shared.add_alias("ledger", "LEDGER", None).is_err());
+}
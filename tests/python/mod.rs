@@ -0,0 +1,43 @@
+use cuemap_rust::python::cuemap;
+use pyo3::prelude::*;
+use std::ffi::CString;
+
+fn run(script: &str) {
+    pyo3::append_to_inittab!(cuemap);
+    Python::initialize();
+    Python::attach(|py| {
+        let code = CString::new(script).unwrap();
+        if let Err(e) = py.run(&code, None, None) {
+            e.print(py);
+            panic!("python script failed");
+        }
+    });
+}
+
+#[test]
+fn test_python_module() {
+    run(r#"
+import cuemap
+
+cm = cuemap.CueMap()
+added = cm.add("Payments timed out after the deploy", ["service:payments", "error:timeout"], metadata={"source": "pager"})
+assert added["rejected_cues"] == []
+assert len(cm) == 1
+
+results = cm.recall(["service:payments"], limit=5)
+assert results[0]["memory_id"] == added["id"]
+assert results[0]["metadata"] == {"source": "pager"}
+assert cm.recall(query_text="nothing matches this", limit=5) == []
+
+grounded = cm.recall_grounded("payments", token_budget=100)
+assert "verified_context" in grounded and grounded["proof"]["token_budget"] == 100
+
+assert cm.reinforce(added["id"])
+assert not cm.delete("missing")
+try:
+    cm.snapshot()
+    raise AssertionError("in-memory snapshot should fail")
+except RuntimeError:
+    pass
+"#);
+}
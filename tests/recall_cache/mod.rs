@@ -0,0 +1,128 @@
+#[path = "../common/mod.rs"]
+mod common;
+
+use common::cues;
+use cuemap_rust::config;
+use cuemap_rust::engine::RecallResult;
+use cuemap_rust::hooks::{Hooks, RecallQueryInfo};
+use cuemap_rust::projects::ProjectContext;
+use cuemap_rust::recall_cache::RecallCache;
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn ids(response: &Value) -> Vec<&str> {
+    response["results"].as_array().unwrap().iter().map(|r| r["memory_id"].as_str().unwrap()).collect()
+}
+
+#[test]
+fn test_recall_cache_expiry_and_generations() {
+    let cache = RecallCache::new(2, Some(Duration::from_millis(50)));
+    cache.insert("a".to_string(), serde_json::json!({"results": []}), 1);
+    assert!(cache.get("a", 1).is_some());
+    // Any write moves the generation on
+    assert!(cache.get("a", 2).is_none());
+    assert!(cache.is_empty());
+
+    cache.insert("b".to_string(), serde_json::json!({"results": []}), 1);
+    std::thread::sleep(Duration::from_millis(60));
+    assert!(cache.get("b", 1).is_none());
+    let stats = cache.stats();
+    assert_eq!((stats.hits, stats.misses), (1, 2));
+
+    let off = RecallCache::new(2, None);
+    off.insert("a".to_string(), serde_json::json!({}), 1);
+    assert!(off.get("a", 1).is_none());
+    assert_eq!(off.stats().misses, 0);
+}
+
+#[tokio::test]
+async fn test_recall_responses_cached_until_write() {
+    // The TTL is process-wide; this test binary is the only one setting it
+    config::set_recall_cache_ttl(Duration::from_secs(60)).unwrap();
+
+    let project = Arc::new(ProjectContext::new(Default::default(), Default::default()));
+    let timeout = project.main.add_memory("payments timed out".to_string(), cues(&["service:payments"]), None, false);
+    let app = common::router(&project);
+    let base = common::serve(app).await;
+    let client = reqwest::Client::new();
+    let recall = |body: Value| {
+        let request = client.post(format!("{}/recall", base)).json(&body);
+        async move { request.send().await.unwrap().json::<Value>().await.unwrap() }
+    };
+
+    let first = recall(serde_json::json!({"cues": ["service:payments"]})).await;
+    assert!(first.get("cached").is_none());
+    // Cue spelling is normalized before the lookup
+    let second = recall(serde_json::json!({"cues": ["Service:Payments"]})).await;
+    assert_eq!(second["cached"], true);
+    assert_eq!(ids(&second), ids(&first));
+
+    // Different settings miss, and auto-reinforcing recalls always run
+    assert!(recall(serde_json::json!({"cues": ["service:payments"], "limit": 1})).await.get("cached").is_none());
+    assert!(recall(serde_json::json!({"cues": ["service:payments"], "auto_reinforce": true})).await.get("cached").is_none());
+
+    // A write to the project invalidates the cached response
+    let refund = project.main.add_memory("refund issued".to_string(), cues(&["service:payments"]), None, false);
+    let after_write = recall(serde_json::json!({"cues": ["service:payments"]})).await;
+    assert!(after_write.get("cached").is_none());
+    assert_eq!(ids(&after_write), vec![refund.as_str(), timeout.as_str()]);
+
+    // So does a metadata edit, which results carry
+    assert_eq!(recall(serde_json::json!({"cues": ["service:payments"]})).await["cached"], true);
+    project.main.set_metadata(&refund, "status", serde_json::json!("approved"));
+    let after_edit = recall(serde_json::json!({"cues": ["service:payments"]})).await;
+    assert!(after_edit.get("cached").is_none());
+    assert_eq!(after_edit["results"][0]["metadata"]["status"], "approved");
+
+    let stats: Value = client.get(format!("{}/stats", base)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(stats["recall_cache"]["enabled"], true);
+    assert_eq!(stats["recall_cache"]["hits"], 2);
+
+    // Grounded recalls reuse the memory selection but keep a trace per response
+    let grounded = |body: Value| {
+        let request = client.post(format!("{}/recall/grounded", base)).json(&body);
+        async move { request.send().await.unwrap().json::<Value>().await.unwrap() }
+    };
+    let body = serde_json::json!({"query_text": "payments", "cues": ["service:payments"]});
+    let first = grounded(body.clone()).await;
+    assert!(first.get("cached").is_none());
+    let second = grounded(body.clone()).await;
+    assert_eq!(second["cached"], true);
+    assert_eq!(second["verified_context"], first["verified_context"]);
+    assert_ne!(second["proof"]["trace_id"], first["proof"]["trace_id"]);
+    for response in [&first, &second] {
+        let trace_id = response["proof"]["trace_id"].as_str().unwrap();
+        let trace = client.get(format!("{}/grounding/traces/{}", base, trace_id)).send().await.unwrap();
+        assert!(trace.status().is_success());
+    }
+    // Different settings miss, and so does anything after a write
+    assert!(grounded(serde_json::json!({"query_text": "payments", "cues": ["service:payments"], "limit": 1})).await.get("cached").is_none());
+    project.main.add_memory("payments retried".to_string(), cues(&["service:payments"]), None, false);
+    assert!(grounded(body).await.get("cached").is_none());
+
+    // Recall hooks see every recall, so a project with hooks is never served from the cache
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counted = calls.clone();
+    let hooks = Hooks::new().recall(move |_: &RecallQueryInfo<'_>, _: &mut Vec<RecallResult>| {
+        counted.fetch_add(1, Ordering::SeqCst);
+    });
+    let hooked = Arc::new(ProjectContext::new(Default::default(), Default::default()).with_hooks(hooks));
+    hooked.main.add_memory("payments timed out".to_string(), cues(&["service:payments"]), None, false);
+    let app = common::router(&hooked);
+    let hooked_base = common::serve(app).await;
+    for _ in 0..2 {
+        let response: Value = client
+            .post(format!("{}/recall", hooked_base))
+            .json(&serde_json::json!({"cues": ["service:payments"]}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(response.get("cached").is_none());
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
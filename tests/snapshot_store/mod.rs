@@ -0,0 +1,100 @@
+use cuemap_rust::projects::{ProjectConfig, ProjectContext, PROJECT_CONFIG_FILE};
+use cuemap_rust::snapshot_store::SharedSnapshotStore;
+use cuemap_rust::taxonomy::Taxonomy;
+use object_store::memory::InMemory;
+use object_store::ObjectStore;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn project() -> Arc<ProjectContext> {
+    Arc::new(ProjectContext::new(Default::default(), Default::default()))
+}
+
+#[tokio::test]
+async fn test_writer_lease_is_exclusive() {
+    let objects: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    let blue = SharedSnapshotStore::new(objects.clone(), "prod");
+    let green = SharedSnapshotStore::new(objects.clone(), "prod");
+    let other = SharedSnapshotStore::new(objects, "staging");
+
+    assert!(blue.try_acquire().await.unwrap());
+    assert!(blue.try_acquire().await.unwrap(), "renewal by the holder");
+    assert!(!green.try_acquire().await.unwrap());
+    assert!(other.try_acquire().await.unwrap(), "leases are per prefix");
+
+    blue.release().await.unwrap();
+    assert!(green.try_acquire().await.unwrap());
+    assert!(!blue.try_acquire().await.unwrap());
+}
+
+#[tokio::test]
+async fn test_upload_fenced_by_lease_generation() {
+    let objects: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    let blue = SharedSnapshotStore::new(objects.clone(), "prod");
+    let green = SharedSnapshotStore::new(objects, "prod");
+    let writer = project();
+    writer.main.add_memory("payments timed out".to_string(), vec!["service:payments".to_string()], None, true);
+
+    assert!(blue.upload(&writer).await.is_err(), "no lease taken yet");
+    assert!(blue.try_acquire().await.unwrap());
+    blue.upload(&writer).await.unwrap();
+
+    // Once the lease changes hands the old holder's uploads are refused
+    blue.release().await.unwrap();
+    assert!(green.try_acquire().await.unwrap());
+    assert!(blue.upload(&writer).await.unwrap_err().contains(green.holder()));
+    green.upload(&writer).await.unwrap();
+
+    // Taking it back is a new generation, which uploads again
+    green.release().await.unwrap();
+    assert!(blue.try_acquire().await.unwrap());
+    blue.upload(&writer).await.unwrap();
+    assert!(green.upload(&writer).await.is_err());
+}
+
+#[tokio::test]
+async fn test_upload_download_round_trip() {
+    let objects: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    let store = SharedSnapshotStore::new(objects, "prod");
+    let dir = tempfile::tempdir().unwrap();
+    assert!(!store.download(dir.path()).await.unwrap());
+
+    let writer = project();
+    let id = writer.main.add_memory("payments timed out".to_string(), vec!["service:payments".to_string()], None, true);
+    let mut taxonomy = Taxonomy::default();
+    taxonomy.allowed_keys.push("service".to_string());
+    writer.set_taxonomy(taxonomy);
+    assert!(store.try_acquire().await.unwrap());
+    store.upload(&writer).await.unwrap();
+
+    assert!(store.download(dir.path()).await.unwrap());
+    let (memories, _) = cuemap_rust::persistence::PersistenceManager::load_from_path(&dir.path().join("cuemap.bin")).unwrap();
+    assert!(memories.contains_key(&id));
+    let config = ProjectConfig::load_or_default(&dir.path().join(PROJECT_CONFIG_FILE));
+    assert_eq!(config.taxonomy.allowed_keys, vec!["service".to_string()]);
+}
+
+#[tokio::test]
+async fn test_reader_reloads_new_snapshots() {
+    let objects: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    let store = SharedSnapshotStore::new(objects.clone(), "prod");
+    let writer = project();
+    writer.main.add_memory("first".to_string(), vec!["topic:first".to_string()], None, true);
+    assert!(store.try_acquire().await.unwrap());
+    store.upload(&writer).await.unwrap();
+
+    let reader = project();
+    let task = Arc::new(SharedSnapshotStore::new(objects, "prod")).spawn_reader(reader.clone(), Duration::from_millis(20));
+    let second = writer.main.add_memory("second".to_string(), vec!["topic:second".to_string()], None, true);
+    store.upload(&writer).await.unwrap();
+
+    for _ in 0..100 {
+        if reader.main.get_memory(&second).is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    task.abort();
+    assert_eq!(reader.main.get_memories().len(), 2);
+    assert_eq!(reader.main.recall(vec!["topic:second".to_string()], 10, false)[0].memory_id, second);
+}
@@ -1,3 +1,4 @@
+use cuemap_rust::projects::ProjectContext;
 use cuemap_rust::taxonomy::*;
 use std::collections::HashMap;
 
@@ -39,6 +40,7 @@ fn test_value_validation() {
         allowed_keys: vec!["status".to_string(), "user".to_string()],
         allowed_values,
         allowed_value_prefixes,
+        ..Default::default()
     };
 
     let cues = vec![
@@ -54,3 +56,162 @@ fn test_value_validation() {
     assert_eq!(report.rejected[0].code, "unknown_value"); // status:unknown
     assert_eq!(report.rejected[1].code, "unknown_value"); // user:admin
 }
+
+#[test]
+fn test_deprecated_key_rewrite() {
+    let mut deprecated_keys = HashMap::new();
+    deprecated_keys.insert("svc".to_string(), "service".to_string());
+    deprecated_keys.insert("srv".to_string(), "svc".to_string());
+
+    let taxonomy = Taxonomy {
+        allowed_keys: vec!["service".to_string()],
+        deprecated_keys,
+        ..Default::default()
+    };
+
+    let cues = vec![
+        "svc:payments".to_string(),     // Rewritten to service:payments
+        "srv:billing".to_string(),      // Chained: srv -> svc -> service
+        "service:auth".to_string(),     // Untouched
+    ];
+    let report = validate_cues(cues, &taxonomy);
+
+    assert_eq!(report.accepted, vec!["service:payments", "service:billing", "service:auth"]);
+    assert!(report.rejected.is_empty());
+    assert_eq!(report.rewritten.len(), 2);
+    assert_eq!(report.rewritten[0], RewrittenCue {
+        from: "svc:payments".to_string(),
+        to: "service:payments".to_string(),
+    });
+}
+
+#[test]
+fn test_deprecated_key_cycle_terminates() {
+    let mut deprecated_keys = HashMap::new();
+    deprecated_keys.insert("a".to_string(), "b".to_string());
+    deprecated_keys.insert("b".to_string(), "a".to_string());
+
+    let taxonomy = Taxonomy { deprecated_keys, ..Default::default() };
+
+    assert_eq!(taxonomy.resolve_deprecated_key("a").as_deref(), Some("b"));
+    assert_eq!(taxonomy.rewrite_deprecated("x:1"), None);
+}
+
+#[test]
+fn test_cardinality_guard() {
+    let mut max_key_cardinality = HashMap::new();
+    max_key_cardinality.insert("id".to_string(), 2);
+
+    let mut taxonomy = Taxonomy { max_key_cardinality, ..Default::default() };
+
+    // "id:1" is already indexed; one more distinct value fits under the limit
+    let known = |cue: &str| cue == "id:1";
+    let cardinality = |key: &str| if key == "id" { 1 } else { 0 };
+
+    let cues = vec![
+        "id:1".to_string(),
+        "id:2".to_string(),
+        "id:3".to_string(),
+        "topic:auth".to_string(),
+    ];
+    let mut report = validate_cues(cues.clone(), &taxonomy);
+    enforce_cardinality(&mut report, &taxonomy, known, cardinality);
+
+    assert_eq!(report.accepted, vec!["id:1", "id:2", "topic:auth"]);
+    assert_eq!(report.rejected.len(), 1);
+    assert_eq!(report.rejected[0].cue, "id:3");
+    assert_eq!(report.rejected[0].code, "cardinality_exceeded");
+
+    // Flag mode accepts the value but reports it
+    taxonomy.cardinality_action = CardinalityAction::Flag;
+    let mut report = validate_cues(cues, &taxonomy);
+    enforce_cardinality(&mut report, &taxonomy, known, cardinality);
+
+    assert_eq!(report.accepted.len(), 4);
+    assert!(report.rejected.is_empty());
+    assert_eq!(report.flagged.len(), 1);
+    assert_eq!(report.flagged[0].code, "cardinality_exceeded");
+}
+
+#[test]
+fn test_rejection_quarantine_and_promotion() {
+    let taxonomy = Taxonomy {
+        allowed_keys: vec!["service".to_string(), "env".to_string()],
+        allowed_values: HashMap::from([("env".to_string(), vec!["prod".to_string()])]),
+        ..Default::default()
+    };
+    let ctx = ProjectContext::new(Default::default(), taxonomy);
+    let add = |content: &str, cues: &[&str]| {
+        let memory = ctx.prepare_memory(content.to_string(), cues.iter().map(|c| c.to_string()).collect(), None).unwrap();
+        let id = ctx.main.add_memory(memory.content, memory.report.accepted, None, true);
+        ctx.rejections.record(&id, &memory.report.rejected);
+        id
+    };
+    let first = add("search is slow", &["service:search", "team:search", "env:staging"]);
+    let second = add("search reindexed", &["team:search", "team:infra"]);
+
+    let by_key = ctx.rejections.by_key();
+    assert_eq!(by_key[0].key, "team");
+    assert_eq!(by_key[0].count, 3);
+    assert_eq!(by_key[0].memories, 2);
+    assert_eq!(by_key[0].codes["unknown_key"], 3);
+    assert_eq!(by_key[0].values["search"], 2);
+    assert_eq!(by_key[1].codes["unknown_value"], 1);
+    assert_eq!(ctx.rejections.entries(Some("team"), 1)[0].cue, "team:infra");
+
+    // Promoting a value re-attaches it where it was rejected
+    let promotion = ctx.promote_rejected("env", Some("staging"));
+    assert_eq!(promotion.reattached.len(), 1);
+    assert!(ctx.taxonomy().allowed_values["env"].contains(&"staging".to_string()));
+    assert!(ctx.main.get_memory(&first).unwrap().cues.iter().any(|c| &**c == "env:staging"));
+
+    // Promoting a key brings back all of its values
+    let promotion = ctx.promote_rejected("team", None);
+    assert_eq!(promotion.reattached.len(), 3);
+    assert!(ctx.taxonomy().allowed_keys.contains(&"team".to_string()));
+    assert!(ctx.main.get_memory(&second).unwrap().cues.iter().any(|c| &**c == "team:infra"));
+    assert!(ctx.rejections.is_empty());
+}
+
+#[test]
+fn test_enforcement_modes() {
+    let closed = |enforcement: Enforcement| Taxonomy {
+        allowed_keys: vec!["service".to_string()],
+        max_key_cardinality: HashMap::from([("service".to_string(), 1)]),
+        enforcement,
+        ..Default::default()
+    };
+    let cues = || vec!["service:search".to_string(), "team:search".to_string(), "service:billing".to_string()];
+
+    let strict = ProjectContext::new(Default::default(), closed(Enforcement::Strict));
+    let report = strict.validate_cues(cues());
+    assert_eq!(report.accepted, vec!["service:search"]);
+    assert_eq!(report.rejected.len(), 2);
+    assert_eq!(strict.enforcement_warnings.snapshot().total, 0);
+
+    // Warn accepts everything, reporting and counting what strict rejects
+    let warn = ProjectContext::new(Default::default(), closed(Enforcement::Warn));
+    let report = warn.validate_cues(cues());
+    assert_eq!(report.accepted.len(), 3);
+    assert!(report.rejected.is_empty());
+    let codes: Vec<&str> = report.flagged.iter().map(|violation| violation.code.as_str()).collect();
+    assert_eq!(codes, vec!["unknown_key", "cardinality_exceeded"]);
+    warn.validate_cues(vec!["team:billing".to_string()]);
+    let warnings = warn.enforcement_warnings.snapshot();
+    assert_eq!(warnings.total, 3);
+    assert_eq!(warnings.by_code, [("cardinality_exceeded".to_string(), 1), ("unknown_key".to_string(), 2)].into());
+    assert_eq!(warnings.by_key, [("service".to_string(), 1), ("team".to_string(), 2)].into());
+    // Counts start over with new rules
+    warn.set_taxonomy(closed(Enforcement::Strict));
+    assert_eq!(warn.enforcement_warnings.snapshot(), EnforcementWarnings::default());
+
+    let off = ProjectContext::new(Default::default(), closed(Enforcement::Off));
+    let report = off.validate_cues(cues());
+    assert_eq!(report.accepted.len(), 3);
+    assert!(report.rejected.is_empty() && report.flagged.is_empty());
+    assert_eq!(off.enforcement_warnings.snapshot().total, 0);
+
+    let parsed: Taxonomy = serde_json::from_value(serde_json::json!({"enforcement": "warn"})).unwrap();
+    assert_eq!(parsed.enforcement, Enforcement::Warn);
+    assert_eq!(Taxonomy::default().enforcement, Enforcement::Strict);
+}